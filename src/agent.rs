@@ -0,0 +1,40 @@
+/// Agent living in continuous space on top of the board, in board cell
+/// coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Agent {
+    pub position: (f64, f64),
+    pub velocity: (f64, f64)
+}
+
+impl Agent {
+    /// Direction of movement in radians, 0 pointing along the X axis.
+    pub fn heading(&self) -> f64 {
+        self.velocity.1.atan2(self.velocity.0)
+    }
+
+    /// Vertices of a triangle pointing in the direction of movement.
+    pub fn triangle(&self,
+                    size: f64) -> [[f64; 2]; 3] {
+        let heading = self.heading();
+        let vertex = |angle: f64, length: f64| {
+            [self.position.0 + (heading + angle).cos() * length,
+             self.position.1 + (heading + angle).sin() * length]
+        };
+
+        [vertex(0.0, size),
+         vertex(2.5, size * 0.6),
+         vertex(-2.5, size * 0.6)]
+    }
+}
+
+#[test]
+fn test_agent_triangle() {
+    let agent = Agent {
+        position: (1.0, 2.0),
+        velocity: (0.0, 3.0)
+    };
+
+    let tip = agent.triangle(2.0)[0];
+    assert!((tip[0] - 1.0).abs() < 1e-9);
+    assert!((tip[1] - 4.0).abs() < 1e-9);
+}
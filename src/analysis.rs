@@ -0,0 +1,304 @@
+//! Comparison of the runs of a parameter sweep: how often runs survived and
+//! how unequally energy ended up distributed, for every value of every
+//! swept parameter, written as a self-contained HTML report.
+
+use std::cmp::Ordering;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use manifest::Manifest;
+use sweep::{self, Sweep};
+
+/// Bar chart size in pixels.
+const CHART_WIDTH: usize = 480;
+const CHART_HEIGHT: usize = 200;
+/// Room for axis labels below and left of the bars.
+const CHART_MARGIN: usize = 40;
+
+/// What the report needs to know about a single run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunSummary {
+    pub name: String,
+    /// Values of swept parameters, in order of `Analysis::params`.
+    pub params: Vec<String>,
+    pub outcome: String,
+    pub iterations: usize,
+    /// Number of specimens left, if the simulation has any.
+    pub survivors: usize,
+    /// Gini coefficient of energy of the survivors.
+    pub energy_gini: f64
+}
+
+impl RunSummary {
+    pub fn of_manifest(name: &str,
+                       params: Vec<String>,
+                       manifest: &Manifest) -> RunSummary {
+        let energies: Vec<f32> = manifest.specimens.iter().map(|s| s.energy).collect();
+        RunSummary {
+            name: name.to_string(),
+            params: params,
+            outcome: manifest.outcome.clone(),
+            iterations: manifest.iterations,
+            survivors: energies.len(),
+            energy_gini: gini(&energies)
+        }
+    }
+
+    pub fn survived(&self) -> bool {
+        self.outcome != "extinction"
+    }
+}
+
+/// 0 if everyone has the same energy, approaching 1 if one has it all.
+pub fn gini(values: &[f32]) -> f64 {
+    let mut sorted: Vec<f64> = values.iter().map(|&v| v as f64).collect();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    let n = sorted.len() as f64;
+    let sum = sorted.iter().fold(0.0, |sum, v| sum + v);
+    if sorted.is_empty() || sum <= 0.0 {
+        return 0.0;
+    }
+
+    let weighted = sorted.iter().enumerate().fold(0.0, |total, (i, v)| total + (i as f64 + 1.0) * v);
+    2.0 * weighted / (n * sum) - (n + 1.0) / n
+}
+
+/// Options given to a job as `(name, value)`, with an empty value for
+/// flags.
+fn job_options(args: &[String]) -> Vec<(String, String)> {
+    let mut options = Vec::new();
+    let mut idx = 0;
+    while idx < args.len() {
+        if args[idx].starts_with("--") {
+            let name = args[idx][2..].to_string();
+            match args.get(idx + 1) {
+                Some(value) if !value.starts_with("--") => {
+                    options.push((name, value.clone()));
+                    idx += 1;
+                },
+                _ => options.push((name, String::new()))
+            }
+        }
+        idx += 1;
+    }
+    options
+}
+
+/// Orders parameter values numerically if they're numbers.
+fn compare_values(a: &str,
+                  b: &str) -> Ordering {
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        _ => a.cmp(b)
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+pub struct Analysis {
+    /// Names of options whose value differs between runs.
+    pub params: Vec<String>,
+    pub runs: Vec<RunSummary>
+}
+
+impl Analysis {
+    /// Reads the finished jobs of a sweep in `dir`, or just every manifest
+    /// in it if it doesn't hold a sweep.
+    pub fn load(dir: &str) -> Result<Analysis, String> {
+        let mut runs = Vec::new();
+
+        if Path::new(dir).join(sweep::JOBS_FILE).exists() {
+            let sweep = try!(Sweep::open(dir));
+            for job in sweep.jobs().iter().filter(|job| sweep.is_done(job)) {
+                let manifest = try!(Manifest::load(&sweep.manifest_path(job).to_string_lossy()));
+                runs.push((job.name.clone(), job_options(&job.args), manifest));
+            }
+        } else {
+            let entries = try!(fs::read_dir(dir).map_err(|e| format!("{}: {}", dir, e)));
+            let mut paths: Vec<_> = entries.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                                           .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+                                           .collect();
+            paths.sort();
+            for path in paths {
+                let manifest = try!(Manifest::load(&path.to_string_lossy()));
+                let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+                runs.push((name, Vec::new(), manifest));
+            }
+        }
+
+        if runs.is_empty() {
+            return Err(format!("{}: no finished runs", dir));
+        }
+        Ok(Analysis::of_runs(runs))
+    }
+
+    /// Summarizes runs given as `(name, options, manifest)`.
+    pub fn of_runs(runs: Vec<(String, Vec<(String, String)>, Manifest)>) -> Analysis {
+        let value_of = |options: &[(String, String)], name: &str| {
+            options.iter().find(|option| option.0 == name).map(|option| option.1.clone())
+        };
+
+        let mut params: Vec<String> = Vec::new();
+        for &(_, ref options, _) in runs.iter() {
+            for &(ref name, _) in options.iter() {
+                let first = value_of(&runs[0].1, name);
+                if !params.contains(name) && runs.iter().any(|run| value_of(&run.1, name) != first) {
+                    params.push(name.clone());
+                }
+            }
+        }
+
+        let summaries = runs.iter().map(|&(ref name, ref options, ref manifest)| {
+            let values = params.iter().map(|param| value_of(options, param).unwrap_or(String::new())).collect();
+            RunSummary::of_manifest(name, values, manifest)
+        }).collect();
+
+        Analysis {
+            params: params,
+            runs: summaries
+        }
+    }
+
+    /// Runs grouped by the value of the `param_idx`-th parameter, in order
+    /// of values.
+    pub fn groups(&self,
+                  param_idx: usize) -> Vec<(String, Vec<&RunSummary>)> {
+        let mut values: Vec<String> = Vec::new();
+        for run in self.runs.iter() {
+            if !values.contains(&run.params[param_idx]) {
+                values.push(run.params[param_idx].clone());
+            }
+        }
+        values.sort_by(|a, b| compare_values(a, b));
+
+        values.into_iter().map(|value| {
+            let runs = self.runs.iter().filter(|run| run.params[param_idx] == value).collect();
+            (value, runs)
+        }).collect()
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut html = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+                                     <title>Sweep report</title>\n<style>\n\
+                                     body { font-family: sans-serif; margin: 2em; }\n\
+                                     table { border-collapse: collapse; }\n\
+                                     td, th { border: 1px solid #ccc; padding: 2px 8px; text-align: right; }\n\
+                                     svg { margin: 0 1em 1em 0; }\n\
+                                     </style>\n</head>\n<body>\n");
+        let survived = self.runs.iter().filter(|run| run.survived()).count();
+        html.push_str(&format!("<h1>Sweep report</h1>\n<p>{} runs, {} survived.</p>\n", self.runs.len(), survived));
+
+        for (idx, param) in self.params.iter().enumerate() {
+            let groups = self.groups(idx);
+            let labels: Vec<String> = groups.iter().map(|group| group.0.clone()).collect();
+            let mean = |runs: &[&RunSummary], value: &Fn(&RunSummary) -> f64| {
+                runs.iter().fold(0.0, |sum, run| sum + value(run)) / runs.len() as f64
+            };
+            let survival: Vec<f64> = groups.iter()
+                                           .map(|group| mean(&group.1, &|run| if run.survived() { 1.0 } else { 0.0 }))
+                                           .collect();
+            let survivors: Vec<f64> = groups.iter().map(|group| mean(&group.1, &|run| run.survivors as f64)).collect();
+            let inequality: Vec<f64> = groups.iter().map(|group| mean(&group.1, &|run| run.energy_gini)).collect();
+
+            html.push_str(&format!("<h2>{}</h2>\n", escape(param)));
+            html.push_str(&bar_chart("survival rate", &labels, &survival));
+            html.push_str(&bar_chart("specimens left", &labels, &survivors));
+            html.push_str(&bar_chart("energy inequality (Gini)", &labels, &inequality));
+        }
+
+        html.push_str("<h2>Runs</h2>\n<table>\n<tr><th>run</th>");
+        for param in self.params.iter() {
+            html.push_str(&format!("<th>{}</th>", escape(param)));
+        }
+        html.push_str("<th>outcome</th><th>iterations</th><th>specimens</th><th>energy Gini</th></tr>\n");
+        for run in self.runs.iter() {
+            html.push_str(&format!("<tr><td>{}</td>", escape(&run.name)));
+            for value in run.params.iter() {
+                html.push_str(&format!("<td>{}</td>", escape(value)));
+            }
+            html.push_str(&format!("<td>{}</td><td>{}</td><td>{}</td><td>{:.3}</td></tr>\n",
+                                   escape(&run.outcome), run.iterations, run.survivors, run.energy_gini));
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+
+    pub fn save(&self,
+                path: &str) -> Result<(), String> {
+        File::create(path).and_then(|mut f| f.write_all(self.to_html().as_bytes()))
+                          .map_err(|e| format!("cannot write {}: {}", path, e))
+    }
+}
+
+/// SVG bar chart with a bar for each label, scaled to the largest value.
+fn bar_chart(title: &str,
+             labels: &[String],
+             values: &[f64]) -> String {
+    let max = values.iter().fold(0.0f64, |max, &v| if v > max { v } else { max });
+    let scale = if max > 0.0 { CHART_HEIGHT as f64 / max } else { 0.0 };
+    let slot = CHART_WIDTH as f64 / labels.len() as f64;
+    let bottom = CHART_HEIGHT + 20;
+
+    let mut svg = format!("<svg width=\"{}\" height=\"{}\" xmlns=\"http://www.w3.org/2000/svg\">\n\
+                           <text x=\"{}\" y=\"14\">{}</text>\n",
+                          CHART_WIDTH + CHART_MARGIN, bottom + CHART_MARGIN, CHART_MARGIN, escape(title));
+    svg.push_str(&format!("<text x=\"0\" y=\"30\" font-size=\"10\">{:.3}</text>\n", max));
+    for (idx, (label, &value)) in labels.iter().zip(values.iter()).enumerate() {
+        let height = value * scale;
+        let x = CHART_MARGIN as f64 + idx as f64 * slot;
+        svg.push_str(&format!("<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#4878d0\">\
+                               <title>{:.3}</title></rect>\n",
+                              x + slot * 0.1, bottom as f64 - height, slot * 0.8, height, value));
+        svg.push_str(&format!("<text x=\"{:.1}\" y=\"{}\" font-size=\"10\" text-anchor=\"middle\">{}</text>\n",
+                              x + slot / 2.0, bottom + 14, escape(label)));
+    }
+    svg.push_str(&format!("<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"black\"/>\n</svg>\n",
+                          CHART_MARGIN, bottom, CHART_MARGIN + CHART_WIDTH, bottom));
+    svg
+}
+
+#[test]
+fn test_gini() {
+    assert_eq!(0.0, gini(&[]));
+    assert!(gini(&[2.0, 2.0, 2.0]).abs() < 1e-9);
+    assert!((gini(&[0.0, 0.0, 0.0, 4.0]) - 0.75).abs() < 1e-9);
+}
+
+#[test]
+fn test_analysis_groups_swept_params() {
+    use simulation::Specimen;
+    use warnings::WarningLog;
+
+    let manifest = |outcome: &str, energies: &[f32]| Manifest {
+        simulation: "goodevil".to_string(),
+        seed: Some(1),
+        board_size: (10, 10),
+        iterations: 100,
+        outcome: outcome.to_string(),
+        stats: Vec::new(),
+        specimens: energies.iter().map(|&e| Specimen::new(e)).collect(),
+        warnings: WarningLog::new()
+    };
+    let options = |loss: &str| vec![("seed".to_string(), "1".to_string()),
+                                    ("energy-loss".to_string(), loss.to_string()),
+                                    ("headless".to_string(), String::new())];
+    let analysis = Analysis::of_runs(vec![
+        ("job-0000".to_string(), options("0.01"), manifest("completed", &[1.0, 1.0])),
+        ("job-0001".to_string(), options("0.1"), manifest("extinction", &[])),
+        ("job-0002".to_string(), options("0.002"), manifest("completed", &[0.0, 2.0]))
+    ]);
+
+    assert_eq!(vec!["energy-loss".to_string()], analysis.params);
+    let groups = analysis.groups(0);
+    let values: Vec<&str> = groups.iter().map(|group| &group.0[..]).collect();
+    assert_eq!(vec!["0.002", "0.01", "0.1"], values);
+    assert!(!groups[2].1[0].survived());
+    assert!((groups[0].1[0].energy_gini - 0.5).abs() < 1e-9);
+
+    let html = analysis.to_html();
+    assert_eq!(3, html.matches("<svg").count());
+    assert!(html.contains("<td>job-0001</td><td>0.1</td><td>extinction</td>"));
+}
@@ -0,0 +1,359 @@
+use agent::Agent;
+use board::Board;
+use i18n::{Language, Message};
+use layers::{Layer, Layers};
+use rand::{Rng, StdRng};
+use simulation::Simulation;
+use stats::Stats;
+
+#[derive(Clone, Copy, Debug)]
+pub struct AntsConfig {
+    pub num_ants: usize,
+    pub num_food_sources: usize,
+    /// Amount of food in every cell of a food source.
+    pub food_per_cell: u32,
+    /// Pheromone left by an ant carrying food on each step.
+    pub deposit: f32,
+    /// Fraction of pheromone that evaporates in each step.
+    pub evaporation: f32,
+    /// Probability of an ant ignoring the pheromone and turning randomly.
+    pub wander_probability: f32
+}
+
+impl Default for AntsConfig {
+    fn default() -> AntsConfig {
+        AntsConfig {
+            num_ants: 100,
+            num_food_sources: 3,
+            food_per_cell: 20,
+            deposit: 1.0,
+            evaporation: 0.01,
+            wander_probability: 0.1
+        }
+    }
+}
+
+/// What the ant colony looks like in a single cell, combining all layers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AntCell {
+    pub pheromone: f32,
+    pub food: u32,
+    pub nest: bool
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Ant {
+    x: usize,
+    y: usize,
+    /// Index into DIRECTIONS.
+    heading: usize,
+    carrying: bool
+}
+
+/// Directions an ant can face, ordered clockwise, so that neighboring
+/// entries differ by 45 degrees.
+const DIRECTIONS: [(i64, i64); 8] = [(1, 0), (1, 1), (0, 1), (-1, 1),
+                                     (-1, 0), (-1, -1), (0, -1), (1, -1)];
+
+const FOOD_SOURCE_RADIUS: i64 = 2;
+
+/// Ants search for food, carry it back to the nest and leave pheromone on
+/// the way, which guides other searching ants towards the food. The
+/// pheromone and food are separate layers, the visible ones combined into a
+/// single board for rendering.
+#[derive(Clone)]
+pub struct AntColony {
+    pub cfg: AntsConfig,
+    rng: Box<StdRng>,
+    layers: Layers,
+    pheromone: Layer<f32>,
+    food: Layer<u32>,
+    nest: (usize, usize),
+    ants: Vec<Ant>,
+    view: Board<AntCell>,
+    agents: Vec<Agent>,
+    delivered: usize,
+    iteration: usize
+}
+
+impl AntColony {
+    /// Places the nest in the middle of the board and food sources at
+    /// random positions.
+    pub fn new(width: usize,
+               height: usize,
+               cfg: AntsConfig,
+               mut rng: Box<StdRng>) -> AntColony {
+        let mut food = Board::new(width, height, 0);
+        for _ in 0..cfg.num_food_sources {
+            let center_x = rng.gen_range(0, width) as i64;
+            let center_y = rng.gen_range(0, height) as i64;
+
+            for (x, y) in food.indices() {
+                let (dx, dy) = (x as i64 - center_x, y as i64 - center_y);
+                if dx.abs() <= FOOD_SOURCE_RADIUS && dy.abs() <= FOOD_SOURCE_RADIUS {
+                    *food.at_mut(x, y) = cfg.food_per_cell;
+                }
+            }
+        }
+
+        AntColony::from_food(food, (width / 2, height / 2), cfg, rng)
+    }
+
+    pub fn from_food(food: Board<u32>,
+                     nest: (usize, usize),
+                     cfg: AntsConfig,
+                     mut rng: Box<StdRng>) -> AntColony {
+        let ants = (0..cfg.num_ants).map(|_| Ant {
+                                        x: nest.0,
+                                        y: nest.1,
+                                        heading: rng.gen_range(0, DIRECTIONS.len()),
+                                        carrying: false
+                                    })
+                                    .collect();
+        let (width, height) = (food.width, food.height);
+        let mut layers = Layers::new(width, height);
+        let pheromone = layers.add("pheromone", Board::new(width, height, 0.0));
+        let food = layers.add("food", food);
+
+        let mut colony = AntColony {
+            cfg: cfg,
+            rng: rng,
+            layers: layers,
+            pheromone: pheromone,
+            food: food,
+            nest: nest,
+            ants: ants,
+            view: Board::new(width, height, AntCell { pheromone: 0.0, food: 0, nest: false }),
+            agents: Vec::new(),
+            delivered: 0,
+            iteration: 0
+        };
+        colony.update_view();
+        colony
+    }
+
+    fn step(&self,
+            x: usize,
+            y: usize,
+            heading: usize) -> Option<(usize, usize)> {
+        let (dx, dy) = DIRECTIONS[heading];
+        let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+
+        if nx < 0 || ny < 0 || nx >= self.layers.width as i64 || ny >= self.layers.height as i64 {
+            None
+        } else {
+            Some((nx as usize, ny as usize))
+        }
+    }
+
+    /// Heading of a searching ant: mostly forward, preferring cells with
+    /// more pheromone.
+    fn search_heading(&mut self,
+                      ant: &Ant) -> usize {
+        let len = DIRECTIONS.len();
+        if self.rng.gen::<f32>() < self.cfg.wander_probability {
+            return (ant.heading + self.rng.gen_range(len - 1, len + 2)) % len;
+        }
+
+        let candidates = [(ant.heading + len - 1) % len, ant.heading, (ant.heading + 1) % len];
+        let weights: Vec<f32> = candidates.iter().map(|&heading| {
+            match self.step(ant.x, ant.y, heading) {
+                Some((x, y)) => 0.1 + *self.layers.get(self.pheromone).at(x, y),
+                None => 0.0
+            }
+        }).collect();
+
+        let total = weights.iter().fold(0.0, |sum, w| sum + w);
+        if total == 0.0 {
+            // facing a wall, turn around
+            return (ant.heading + len / 2) % len;
+        }
+
+        let mut choice = self.rng.gen::<f32>() * total;
+        for (&heading, &weight) in candidates.iter().zip(weights.iter()) {
+            if choice < weight {
+                return heading;
+            }
+            choice -= weight;
+        }
+        ant.heading
+    }
+
+    fn home_heading(&self,
+                    ant: &Ant) -> usize {
+        let dx = (self.nest.0 as i64 - ant.x as i64).signum();
+        let dy = (self.nest.1 as i64 - ant.y as i64).signum();
+
+        DIRECTIONS.iter().position(|&d| d == (dx, dy)).unwrap_or(ant.heading)
+    }
+
+    fn move_ant(&mut self,
+                mut ant: Ant) -> Ant {
+        let len = DIRECTIONS.len();
+
+        if ant.carrying {
+            *self.layers.get_mut(self.pheromone).at_mut(ant.x, ant.y) += self.cfg.deposit;
+
+            if (ant.x, ant.y) == self.nest {
+                ant.carrying = false;
+                ant.heading = (ant.heading + len / 2) % len;
+                self.delivered += 1;
+                return ant;
+            }
+            ant.heading = self.home_heading(&ant);
+        } else {
+            let food = self.layers.get_mut(self.food).at_mut(ant.x, ant.y);
+            if *food > 0 {
+                *food -= 1;
+                ant.carrying = true;
+                ant.heading = (ant.heading + len / 2) % len;
+                return ant;
+            }
+            ant.heading = self.search_heading(&ant);
+        }
+
+        if let Some((x, y)) = self.step(ant.x, ant.y, ant.heading) {
+            ant.x = x;
+            ant.y = y;
+        }
+        ant
+    }
+
+    fn update_view(&mut self) {
+        let show_pheromone = self.layers.is_visible(self.pheromone);
+        let show_food = self.layers.is_visible(self.food);
+        for ((x, y), &pheromone, &food) in self.layers.zip(self.pheromone, self.food) {
+            *self.view.at_mut(x, y) = AntCell {
+                pheromone: if show_pheromone { pheromone } else { 0.0 },
+                food: if show_food { food } else { 0 },
+                nest: (x, y) == self.nest
+            };
+        }
+
+        self.agents = self.ants.iter().map(|ant| {
+            let (dx, dy) = DIRECTIONS[ant.heading];
+            Agent {
+                position: (ant.x as f64 + 0.5, ant.y as f64 + 0.5),
+                velocity: (dx as f64, dy as f64)
+            }
+        }).collect();
+    }
+
+    fn food_left(&self) -> usize {
+        self.layers.get(self.food).iter().fold(0, |sum, &food| sum + food as usize)
+    }
+}
+
+impl Simulation<AntCell> for AntColony {
+    fn advance(&mut self) {
+        for pheromone in self.layers.get_mut(self.pheromone).as_mut_slice() {
+            *pheromone *= 1.0 - self.cfg.evaporation;
+        }
+
+        for idx in 0..self.ants.len() {
+            let ant = self.ants[idx];
+            self.ants[idx] = self.move_ant(ant);
+        }
+
+        self.update_view();
+        self.iteration += 1;
+    }
+
+    fn board(&self) -> &Board<AntCell> {
+        &self.view
+    }
+
+    fn agents(&self) -> &[Agent] {
+        &self.agents
+    }
+
+    fn stats(&self) -> Stats {
+        let carrying = self.ants.iter().filter(|ant| ant.carrying).count();
+        let pheromone = self.layers.get(self.pheromone).iter().fold(0.0, |sum, p| sum + p);
+
+        Stats::new(self.iteration).count("ants", self.ants.len())
+                                  .count("carrying", carrying)
+                                  .count("food_left", self.food_left())
+                                  .count("delivered", self.delivered)
+                                  .real("pheromone", pheromone)
+    }
+
+    /// There is nothing left to do once all food is in the nest.
+    fn is_extinct(&self) -> bool {
+        self.food_left() == 0 && !self.ants.iter().any(|ant| ant.carrying)
+    }
+
+    fn describe_cell(&self,
+                     x: usize,
+                     y: usize,
+                     lang: Language) -> Vec<String> {
+        let pheromone = format!("{:.3}", self.layers.get(self.pheromone).at(x, y));
+        let mut details = vec![lang.format(Message::CellPheromone, &[&pheromone]),
+                               lang.format(Message::CellFood, &[&self.layers.get(self.food).at(x, y).to_string()])];
+        if (x, y) == self.nest {
+            details.push(lang.tr(Message::CellNest).to_string());
+        }
+        details
+    }
+
+    fn toggle_layer(&mut self,
+                    index: usize) -> Option<(&'static str, bool)> {
+        let toggled = self.layers.toggle(index);
+        self.update_view();
+        toggled
+    }
+}
+
+#[cfg(test)]
+fn test_colony(food_at: (usize, usize)) -> AntColony {
+    use rand::SeedableRng;
+
+    let mut food = Board::new(10, 10, 0);
+    *food.at_mut(food_at.0, food_at.1) = 1;
+
+    let cfg = AntsConfig {
+        num_ants: 1,
+        wander_probability: 0.0,
+        .. AntsConfig::default()
+    };
+    AntColony::from_food(food, (5, 5), cfg, Box::new(StdRng::from_seed(&[0usize][..])))
+}
+
+#[test]
+fn test_ant_returns_food_to_nest() {
+    let mut sim = test_colony((5, 5));
+
+    sim.advance();
+    assert_eq!(Some(::stats::Value::Count(1)), sim.stats().get("carrying"));
+    assert_eq!(0, sim.food_left());
+
+    sim.advance();
+    assert_eq!(1, sim.delivered);
+    assert!(sim.is_extinct());
+    assert!(sim.board().at(5, 5).pheromone > 0.0);
+    assert!(sim.board().at(5, 5).nest);
+}
+
+#[test]
+fn test_carrying_ant_heads_home_leaving_pheromone() {
+    let mut sim = test_colony((0, 0));
+    sim.ants[0] = Ant { x: 1, y: 1, heading: 0, carrying: true };
+
+    for _ in 0..4 {
+        sim.advance();
+    }
+
+    assert_eq!((5, 5), (sim.ants[0].x, sim.ants[0].y));
+    assert!(*sim.layers.get(sim.pheromone).at(2, 2) > 0.0);
+    assert_eq!(0.0, *sim.layers.get(sim.pheromone).at(2, 1));
+}
+
+#[test]
+fn test_pheromone_evaporates() {
+    let mut sim = test_colony((0, 0));
+    let pheromone = sim.pheromone;
+    *sim.layers.get_mut(pheromone).at_mut(3, 3) = 1.0;
+
+    sim.advance();
+    assert!(*sim.layers.get(sim.pheromone).at(3, 3) < 1.0);
+}
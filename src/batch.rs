@@ -0,0 +1,165 @@
+//! Monte Carlo estimates of a metric over many runs of a simulation, each
+//! with a different seed.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+use diagnostics::panic_message;
+use simulation::Simulation;
+
+/// z value of the 95% two-sided confidence interval.
+const Z_95: f64 = 1.959964;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchConfig {
+    pub runs: usize,
+    /// Runs use seeds `first_seed`, `first_seed + 1`, ...
+    pub first_seed: usize,
+    /// Steps of every run. Runs going extinct stop early.
+    pub iterations: usize,
+    /// Number of runs done at the same time.
+    pub threads: usize
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Estimate {
+    pub mean: f64,
+    /// Sample variance, 0 for less than two samples.
+    pub variance: f64,
+    /// 95% confidence interval of the mean, based on the normal
+    /// approximation, so it's too narrow for just a few runs.
+    pub confidence_interval: (f64, f64),
+    /// Metric of every run, in order of seeds.
+    pub samples: Vec<f64>
+}
+
+impl Estimate {
+    pub fn from_samples(samples: Vec<f64>) -> Estimate {
+        let n = samples.len() as f64;
+        let mean = if samples.is_empty() { 0.0 } else { samples.iter().fold(0.0, |sum, x| sum + x) / n };
+        let variance = if samples.len() < 2 {
+            0.0
+        } else {
+            samples.iter().fold(0.0, |sum, x| sum + (x - mean) * (x - mean)) / (n - 1.0)
+        };
+        let margin = if samples.is_empty() { 0.0 } else { Z_95 * (variance / n).sqrt() };
+
+        Estimate {
+            mean: mean,
+            variance: variance,
+            confidence_interval: (mean - margin, mean + margin),
+            samples: samples
+        }
+    }
+}
+
+/// Runs simulations made by `build` from consecutive seeds, and estimates
+/// `metric` of their final states. Results don't depend on the number of
+/// threads. Fails if any of the runs panicked.
+pub fn estimate<T, S, B, M>(cfg: &BatchConfig,
+                            build: B,
+                            metric: M) -> Result<Estimate, String>
+        where S: Simulation<T> + 'static,
+              T: 'static,
+              B: Fn(usize) -> S + Send + Sync + 'static,
+              M: Fn(&S) -> f64 + Send + Sync + 'static {
+    let build = Arc::new(build);
+    let metric = Arc::new(metric);
+    let next_run = Arc::new(AtomicUsize::new(0));
+    let (tx, rx) = mpsc::channel();
+
+    let workers: Vec<thread::JoinHandle<()>> = (0..cfg.threads.max(1).min(cfg.runs)).map(|_| {
+        let (build, metric, next_run, tx) = (build.clone(), metric.clone(), next_run.clone(), tx.clone());
+        let (runs, first_seed, iterations) = (cfg.runs, cfg.first_seed, cfg.iterations);
+
+        thread::spawn(move || {
+            loop {
+                let run = next_run.fetch_add(1, Ordering::SeqCst);
+                if run >= runs {
+                    break;
+                }
+
+                let seed = first_seed + run;
+                let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                    let mut simulation = build(seed);
+                    for _ in 0..iterations {
+                        if simulation.is_extinct() {
+                            break;
+                        }
+                        simulation.advance();
+                    }
+                    metric(&simulation)
+                }));
+                let _ = tx.send((run, result.map_err(|payload| {
+                    format!("run with seed {} panicked: {}", seed, panic_message(payload))
+                })));
+            }
+        })
+    }).collect();
+    drop(tx);
+
+    let mut samples = vec![0.0; cfg.runs];
+    let mut errors = Vec::new();
+    for (run, result) in rx.iter() {
+        match result {
+            Ok(value) => samples[run] = value,
+            Err(e) => errors.push((run, e))
+        }
+    }
+    for worker in workers {
+        let _ = worker.join();
+    }
+
+    errors.sort();
+    match errors.into_iter().next() {
+        Some((_, e)) => Err(e),
+        None => Ok(Estimate::from_samples(samples))
+    }
+}
+
+#[test]
+fn test_estimate_from_samples() {
+    let estimate = Estimate::from_samples(vec![1.0, 2.0, 3.0, 4.0]);
+
+    assert_eq!(2.5, estimate.mean);
+    assert!((estimate.variance - 5.0 / 3.0).abs() < 1e-9);
+    let margin = Z_95 * (5.0f64 / 3.0 / 4.0).sqrt();
+    assert!((estimate.confidence_interval.0 - (2.5 - margin)).abs() < 1e-9);
+    assert!((estimate.confidence_interval.1 - (2.5 + margin)).abs() < 1e-9);
+}
+
+#[test]
+fn test_estimate_does_not_depend_on_threads() {
+    use rand::{SeedableRng, StdRng};
+    use sandpile::{Sandpile, SandpileConfig};
+
+    let run = |threads| {
+        let cfg = BatchConfig { runs: 6, first_seed: 10, iterations: 30, threads: threads };
+        estimate(&cfg,
+                 |seed| Sandpile::new(5, 5, SandpileConfig { drop_rate: 1.0 },
+                                      Box::new(StdRng::from_seed(&[seed][..]))),
+                 |sim| sim.stats().get("grains").unwrap().as_f32() as f64).unwrap()
+    };
+
+    let serial = run(1);
+    assert_eq!(6, serial.samples.len());
+    assert_eq!(serial, run(4));
+}
+
+#[test]
+fn test_estimate_reports_panics() {
+    use simulation::GameOfLife;
+    use board::Board;
+
+    let cfg = BatchConfig { runs: 3, first_seed: 0, iterations: 1, threads: 2 };
+    let result = estimate(&cfg,
+                          |seed| {
+                              assert!(seed != 1, "bad seed");
+                              GameOfLife::new(Board::new(2, 2, false))
+                          },
+                          |_| 0.0);
+    assert_eq!(Err("run with seed 1 panicked: bad seed".to_string()), result);
+}
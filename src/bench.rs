@@ -0,0 +1,96 @@
+//! Standardized workloads timing the steps of every simulation without
+//! rendering, so that performance regressions in `advance` show up as
+//! numbers comparable between builds.
+
+use std::fmt;
+use std::time::Instant;
+
+use diagnostics::{PerfReport, PhaseTimings};
+use simulation::Simulation;
+
+/// Board size and number of steps of a single benchmark run.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Workload {
+    pub board_size: (usize, usize),
+    pub iterations: usize
+}
+
+/// Larger boards run fewer steps, so that every workload takes roughly the
+/// same time.
+pub const WORKLOADS: &'static [Workload] = &[
+    Workload { board_size: (64, 64), iterations: 1000 },
+    Workload { board_size: (256, 256), iterations: 100 },
+    Workload { board_size: (1024, 1024), iterations: 10 }
+];
+
+/// Timings of a single workload: `update` for `advance`, `stats` for
+/// gathering stats after every step, and the phases of `advance` for
+/// simulations that time them, e.g. collision resolution of GoodEvil.
+pub struct BenchResult {
+    pub iterations: usize,
+    pub timings: PhaseTimings,
+    pub phases: Option<PerfReport>
+}
+
+impl BenchResult {
+    pub fn steps_per_second(&self) -> f64 {
+        let update_ns = self.timings.report(self.iterations)
+                                    .phase("update")
+                                    .map_or(0, |phase| phase.total_ns);
+        if update_ns == 0 {
+            return ::std::f64::INFINITY;
+        }
+        self.iterations as f64 * 1e9 / update_ns as f64
+    }
+}
+
+/// Steps `simulation` `iterations` times, timing each step and the stats
+/// after it.
+pub fn run<T, S>(mut simulation: S,
+                 iterations: usize) -> BenchResult where S: Simulation<T> {
+    let mut timings = PhaseTimings::new();
+    for _ in 0..iterations {
+        let started = Instant::now();
+        simulation.advance();
+        timings.record("update", started.elapsed());
+
+        let started = Instant::now();
+        simulation.stats();
+        timings.record("stats", started.elapsed());
+    }
+
+    BenchResult {
+        iterations: iterations,
+        timings: timings,
+        phases: simulation.perf_report()
+    }
+}
+
+impl fmt::Display for BenchResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(writeln!(f, "{:.1} steps/s", self.steps_per_second()));
+        let own = self.timings.report(self.iterations);
+        let phases = own.phases.iter().chain(self.phases.iter().flat_map(|report| report.phases.iter()));
+        for phase in phases {
+            try!(writeln!(f, "  {}: {} calls, mean {:.3} ms, max {:.3} ms",
+                          phase.name, phase.calls, phase.mean_ns() as f64 / 1e6, phase.max_ns as f64 / 1e6));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_bench_run() {
+    use rand::{SeedableRng, StdRng};
+    use sandpile::{Sandpile, SandpileConfig};
+
+    let rng = Box::new(StdRng::from_seed(&[0usize][..]));
+    let result = run(Sandpile::new(10, 10, SandpileConfig { drop_rate: 1.0 }, rng), 5);
+
+    let report = result.timings.report(result.iterations);
+    assert_eq!(5, report.phase("update").unwrap().calls);
+    assert_eq!(5, report.phase("stats").unwrap().calls);
+    assert!(result.phases.is_none());
+    assert!(result.steps_per_second() > 0.0);
+    assert!(result.to_string().starts_with(&format!("{:.1} steps/s\n  update: 5 calls", result.steps_per_second())));
+}
@@ -1,19 +1,113 @@
-use std::iter::Iterator;
+use std::cmp::max;
+use std::iter::{Iterator, Zip};
+use std::slice;
 use rand::{random, Rand};
 
+
 pub struct Board<T> {
     fields: Box<[T]>,
     pub width: usize,
     pub height: usize
 }
 
+impl<T: Clone> Clone for Board<T> {
+    fn clone(&self) -> Board<T> {
+        Board {
+            fields: self.fields.clone(),
+            width: self.width,
+            height: self.height
+        }
+    }
+
+    /// Copies `source` into the cells of this board without reallocating
+    /// them, if both have the same number of cells.
+    fn clone_from(&mut self,
+                  source: &Board<T>) {
+        if self.fields.len() == source.fields.len() {
+            self.fields.clone_from_slice(&source.fields);
+        } else {
+            self.fields = source.fields.clone();
+        }
+        self.width = source.width;
+        self.height = source.height;
+    }
+}
+
 impl<T> Board<T> {
+    /// Board made of `fields`, row by row.
+    pub fn from_vec(width: usize,
+                    height: usize,
+                    fields: Vec<T>) -> Board<T> {
+        assert_eq!(width * height, fields.len());
+        Board {
+            fields: fields.into_boxed_slice(),
+            width: width,
+            height: height
+        }
+    }
+
+    pub fn at(&self,
+              x: usize,
+              y: usize) -> &T {
+        debug_assert!(x < self.width && y < self.height,
+                      "({}, {}) is outside of the {}x{} board", x, y, self.width, self.height);
+        &self.fields[y * self.width + x]
+    }
+
     pub fn at_mut(&mut self,
                   x: usize,
                   y: usize) -> &mut T {
+        debug_assert!(x < self.width && y < self.height,
+                      "({}, {}) is outside of the {}x{} board", x, y, self.width, self.height);
         &mut self.fields[y * self.width + x]
     }
 
+    /// Cell at (x, y), or None if that's off the board. Coordinates are
+    /// signed, so that neighbors of edge cells can be looked up directly.
+    pub fn get(&self,
+               x: i64,
+               y: i64) -> Option<&T> {
+        if self.contains(x, y) {
+            Some(&self.fields[y as usize * self.width + x as usize])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self,
+                   x: i64,
+                   y: i64) -> Option<&mut T> {
+        if self.contains(x, y) {
+            Some(&mut self.fields[y as usize * self.width + x as usize])
+        } else {
+            None
+        }
+    }
+
+    /// Cell at (x, y) with the board wrapped around like a torus, so that
+    /// e.g. x = -1 is the last column.
+    pub fn get_wrapped(&self,
+                       x: i64,
+                       y: i64) -> &T {
+        self.at(wrap(x, self.width), wrap(y, self.height))
+    }
+
+    /// Cell (dx, dy) away from (x, y), see `offset`.
+    pub fn offset(&self,
+                  x: usize,
+                  y: usize,
+                  dx: isize,
+                  dy: isize,
+                  wrap: bool) -> Option<(usize, usize)> {
+        offset(x, y, dx, dy, self.width, self.height, wrap)
+    }
+
+    fn contains(&self,
+                x: i64,
+                y: i64) -> bool {
+        x >= 0 && y >= 0 && x < self.width as i64 && y < self.height as i64
+    }
+
     pub fn iter(&self) -> Iter<T> {
         Iter {
             board: &self,
@@ -24,6 +118,53 @@ impl<T> Board<T> {
     pub fn indices(&self) -> Indices2D {
         indices_2d(self.width, self.height)
     }
+
+    /// Coordinates of every cell along with the cell, row by row.
+    pub fn enumerate(&self) -> Zip<Indices2D, slice::Iter<T>> {
+        indices_2d(self.width, self.height).zip(self.fields.iter())
+    }
+
+    pub fn enumerate_mut(&mut self) -> Zip<Indices2D, slice::IterMut<T>> {
+        indices_2d(self.width, self.height).zip(self.fields.iter_mut())
+    }
+
+    /// All cells, row by row.
+    pub fn as_slice(&self) -> &[T] {
+        &self.fields
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.fields
+    }
+
+    pub fn row(&self,
+               y: usize) -> &[T] {
+        assert!(y < self.height);
+        &self.fields[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self,
+                   y: usize) -> &mut [T] {
+        assert!(y < self.height);
+        &mut self.fields[y * self.width..(y + 1) * self.width]
+    }
+
+    /// Rows from the top one down.
+    pub fn rows(&self) -> slice::Chunks<T> {
+        // chunks() refuses a size of 0, and a board 0 cells wide has no
+        // fields to split anyway
+        self.fields.chunks(max(self.width, 1))
+    }
+
+    /// Cells of column `x` from the top one down.
+    pub fn column(&self,
+                  x: usize) -> Column<T> {
+        assert!(x < self.width);
+        Column {
+            board: &self,
+            idx: x
+        }
+    }
 }
 
 impl<T: Clone> Board<T> {
@@ -36,12 +177,6 @@ impl<T: Clone> Board<T> {
             height: height
         }
     }
-
-    pub fn at(&self,
-              x: usize,
-              y: usize) -> &T {
-        &self.fields[y * self.width + x]
-    }
 }
 
 impl<T: Clone + Rand> Board<T> {
@@ -60,6 +195,32 @@ impl<T: Clone + Rand> Board<T> {
     }
 }
 
+fn wrap(value: i64,
+        len: usize) -> usize {
+    let len = len as i64;
+    (((value % len) + len) % len) as usize
+}
+
+/// Cell (dx, dy) away from (x, y) on a `width` x `height` board. With
+/// `wrap` the edges wrap around like a torus, otherwise cells off the board
+/// are None.
+pub fn offset(x: usize,
+              y: usize,
+              dx: isize,
+              dy: isize,
+              width: usize,
+              height: usize,
+              wrap: bool) -> Option<(usize, usize)> {
+    let (target_x, target_y) = (x as i64 + dx as i64, y as i64 + dy as i64);
+    if wrap {
+        Some((self::wrap(target_x, width), self::wrap(target_y, height)))
+    } else if target_x >= 0 && target_y >= 0 && target_x < width as i64 && target_y < height as i64 {
+        Some((target_x as usize, target_y as usize))
+    } else {
+        None
+    }
+}
+
 pub struct Iter<'a, T> where T: 'a {
     board: &'a Board<T>,
     idx: usize
@@ -80,6 +241,26 @@ impl<'a, T> Iterator for Iter<'a, T> {
     }
 }
 
+pub struct Column<'a, T> where T: 'a {
+    board: &'a Board<T>,
+    idx: usize
+}
+
+impl<'a, T> Iterator for Column<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let i = self.idx;
+        self.idx += self.board.width;
+
+        if i < self.board.fields.len() {
+            Some(&self.board.fields[i])
+        } else {
+            None
+        }
+    }
+}
+
 #[test]
 fn test_board_at() {
     let mut board = Board::new(4, 3, 0);
@@ -91,11 +272,70 @@ fn test_board_at() {
         for x in 0..board.width {
             let value = y * board.width + x;
             board.fields[value] = value;
-            assert_eq!(board.at(x, y), value);
+            assert_eq!(*board.at(x, y), value);
         }
     }
 }
 
+#[test]
+fn test_board_enumerate() {
+    let mut board = Board::new(2, 2, 0);
+    for ((x, y), cell) in board.enumerate_mut() {
+        *cell = 10 * y + x;
+    }
+
+    let cells: Vec<((usize, usize), usize)> = board.enumerate().map(|(xy, &cell)| (xy, cell)).collect();
+    assert_eq!(vec![((0, 0), 0), ((1, 0), 1), ((0, 1), 10), ((1, 1), 11)], cells);
+}
+
+#[test]
+fn test_board_get() {
+    let mut board = Board::new(3, 2, 0);
+    *board.at_mut(2, 1) = 5;
+
+    assert_eq!(Some(&5), board.get(2, 1));
+    assert_eq!(None, board.get(-1, 0));
+    assert_eq!(None, board.get(3, 0));
+    assert_eq!(None, board.get(0, 2));
+    assert_eq!(&5, board.get_wrapped(-1, -1));
+    assert_eq!(&5, board.get_wrapped(5, 3));
+
+    *board.get_mut(0, 0).unwrap() = 1;
+    assert_eq!(1, *board.at(0, 0));
+    assert!(board.get_mut(0, -1).is_none());
+}
+
+#[test]
+#[should_panic(expected = "(3, 0) is outside of the 3x2 board")]
+fn test_board_at_outside() {
+    Board::new(3, 2, 0).at(3, 0);
+}
+
+#[test]
+fn test_board_offset() {
+    let board = Board::new(4, 3, 0);
+
+    assert_eq!(Some((1, 2)), board.offset(2, 1, -1, 1, false));
+    assert_eq!(None, board.offset(0, 1, -1, 0, false));
+    assert_eq!(None, board.offset(3, 2, 0, 1, false));
+    assert_eq!(Some((3, 2)), board.offset(0, 0, -1, -1, true));
+    assert_eq!(Some((0, 0)), board.offset(3, 2, 1, 1, true));
+    assert_eq!(Some((1, 0)), board.offset(1, 1, 0, 5, true));
+}
+
+#[test]
+fn test_board_rows_and_columns() {
+    let mut board = Board::new(3, 2, 0);
+    board.row_mut(1).copy_from_slice(&[3, 4, 5]);
+    *board.at_mut(0, 0) = 1;
+
+    assert_eq!(&[1, 0, 0, 3, 4, 5], board.as_slice());
+    assert_eq!(&[3, 4, 5], board.row(1));
+    assert_eq!(vec![&[1, 0, 0][..], &[3, 4, 5][..]], board.rows().collect::<Vec<_>>());
+    assert_eq!(vec![0, 5], board.column(2).cloned().collect::<Vec<_>>());
+    assert_eq!(0, Board::new(0, 2, 0).rows().count());
+}
+
 pub struct Indices2D {
     x: usize,
     y: usize,
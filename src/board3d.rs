@@ -0,0 +1,82 @@
+use board::Board;
+
+/// Board of `depth` layers, each `width` x `height` cells.
+#[derive(Clone)]
+pub struct Board3D<T> {
+    fields: Box<[T]>,
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize
+}
+
+impl<T> Board3D<T> {
+    pub fn at(&self,
+              x: usize,
+              y: usize,
+              z: usize) -> &T {
+        debug_assert!(x < self.width && y < self.height && z < self.depth,
+                      "({}, {}, {}) is outside of the {}x{}x{} board",
+                      x, y, z, self.width, self.height, self.depth);
+        &self.fields[(z * self.height + y) * self.width + x]
+    }
+
+    pub fn at_mut(&mut self,
+                  x: usize,
+                  y: usize,
+                  z: usize) -> &mut T {
+        debug_assert!(x < self.width && y < self.height && z < self.depth,
+                      "({}, {}, {}) is outside of the {}x{}x{} board",
+                      x, y, z, self.width, self.height, self.depth);
+        &mut self.fields[(z * self.height + y) * self.width + x]
+    }
+
+    /// Coordinates of every cell, layer by layer and row by row.
+    pub fn indices(&self) -> Vec<(usize, usize, usize)> {
+        let mut indices = Vec::with_capacity(self.fields.len());
+        for z in 0..self.depth {
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    indices.push((x, y, z));
+                }
+            }
+        }
+        indices
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.fields
+    }
+}
+
+impl<T: Clone> Board3D<T> {
+    pub fn new(width: usize,
+               height: usize,
+               depth: usize,
+               default: T) -> Board3D<T> {
+        Board3D {
+            fields: vec![default; width * height * depth].into_boxed_slice(),
+            width: width,
+            height: height,
+            depth: depth
+        }
+    }
+
+    /// Copy of layer `z`.
+    pub fn layer(&self,
+                 z: usize) -> Board<T> {
+        assert!(z < self.depth);
+        let size = self.width * self.height;
+        Board::from_vec(self.width, self.height, self.fields[z * size..(z + 1) * size].to_vec())
+    }
+}
+
+#[test]
+fn test_board3d_layer() {
+    let mut board = Board3D::new(3, 2, 4, 0);
+    *board.at_mut(2, 1, 3) = 7;
+
+    assert_eq!(7, *board.at(2, 1, 3));
+    assert_eq!(0, *board.at(2, 1, 2));
+    assert_eq!(&[0, 0, 0, 0, 0, 7], board.layer(3).as_slice());
+    assert_eq!((2, 1, 3), *board.indices().last().unwrap());
+}
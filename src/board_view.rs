@@ -0,0 +1,166 @@
+use std::ops::Deref;
+use std::sync::{Arc, RwLock};
+
+use board::Board;
+
+/// Immutable snapshot of a board taken after a tick. Cloning is cheap, and
+/// the snapshot stays valid while the simulation keeps mutating its own
+/// board, so it can be read from other threads.
+pub struct BoardView<T> {
+    board: Arc<Board<T>>,
+    iteration: usize
+}
+
+impl<T> BoardView<T> {
+    pub fn new(board: Board<T>,
+               iteration: usize) -> BoardView<T> {
+        BoardView {
+            board: Arc::new(board),
+            iteration: iteration
+        }
+    }
+
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+}
+
+impl<T> Clone for BoardView<T> {
+    fn clone(&self) -> BoardView<T> {
+        BoardView {
+            board: self.board.clone(),
+            iteration: self.iteration
+        }
+    }
+}
+
+impl<T> Deref for BoardView<T> {
+    type Target = Board<T>;
+
+    fn deref(&self) -> &Board<T> {
+        &self.board
+    }
+}
+
+/// Publishes a snapshot of the board after every tick for any number of
+/// observers. The board is copied once per tick; the previous snapshot is
+/// overwritten in place if no observer holds it anymore.
+pub struct ViewPublisher<T> {
+    latest: Arc<RwLock<BoardView<T>>>
+}
+
+impl<T> Clone for ViewPublisher<T> {
+    fn clone(&self) -> ViewPublisher<T> {
+        ViewPublisher {
+            latest: self.latest.clone()
+        }
+    }
+}
+
+impl<T: Clone> ViewPublisher<T> {
+    pub fn new(board: &Board<T>,
+               iteration: usize) -> ViewPublisher<T> {
+        ViewPublisher {
+            latest: Arc::new(RwLock::new(BoardView::new(board.clone(), iteration)))
+        }
+    }
+
+    pub fn publish(&self,
+                   board: &Board<T>,
+                   iteration: usize) -> BoardView<T> {
+        let mut latest = self.latest.write().unwrap();
+
+        let reused = match Arc::get_mut(&mut latest.board) {
+            Some(buffer) => {
+                buffer.clone_from(board);
+                true
+            },
+            None => false
+        };
+        if !reused {
+            latest.board = Arc::new(board.clone());
+        }
+        latest.iteration = iteration;
+
+        latest.clone()
+    }
+
+    pub fn observer(&self) -> ViewObserver<T> {
+        ViewObserver {
+            latest: self.latest.clone()
+        }
+    }
+}
+
+/// Read-only handle to the most recently published snapshot.
+pub struct ViewObserver<T> {
+    latest: Arc<RwLock<BoardView<T>>>
+}
+
+impl<T> Clone for ViewObserver<T> {
+    fn clone(&self) -> ViewObserver<T> {
+        ViewObserver {
+            latest: self.latest.clone()
+        }
+    }
+}
+
+impl<T> ViewObserver<T> {
+    pub fn latest(&self) -> BoardView<T> {
+        self.latest.read().unwrap().clone()
+    }
+}
+
+#[test]
+fn test_board_view_is_a_snapshot() {
+    let mut board = Board::new(2, 2, 0);
+    let publisher = ViewPublisher::new(&board, 0);
+    let observer = publisher.observer();
+
+    *board.at_mut(0, 0) = 1;
+    let first = publisher.publish(&board, 1);
+    *board.at_mut(0, 0) = 2;
+    publisher.publish(&board, 2);
+
+    assert_eq!((1, 1), (first.iteration(), *first.at(0, 0)));
+    assert_eq!((2, 2), (observer.latest().iteration(), *observer.latest().at(0, 0)));
+}
+
+#[test]
+fn test_board_view_reuses_unobserved_snapshot() {
+    let board = Board::new(2, 2, 0);
+    let publisher = ViewPublisher::new(&board, 0);
+    let address = |view: BoardView<i32>| view.at(0, 0) as *const i32 as usize;
+
+    let first = address(publisher.publish(&board, 1));
+    assert_eq!(first, address(publisher.publish(&board, 2)));
+
+    let held = publisher.publish(&board, 3);
+    assert!(address(held.clone()) != address(publisher.publish(&board, 4)));
+}
+
+#[test]
+fn test_board_view_concurrent_reads() {
+    use std::thread;
+
+    let mut board = Board::new(16, 16, 0usize);
+    let publisher = ViewPublisher::new(&board, 0);
+    let observer = publisher.observer();
+
+    let reader = thread::spawn(move || {
+        for _ in 0..100 {
+            let view = observer.latest();
+            let first = *view.at(0, 0);
+            assert!(view.iter().all(|&value| value == first));
+        }
+    });
+
+    for iteration in 1..100 {
+        for (x, y) in board.indices() {
+            *board.at_mut(x, y) = iteration;
+        }
+        publisher.publish(&board, iteration);
+    }
+
+    reader.join().unwrap();
+}
@@ -0,0 +1,237 @@
+use agent::Agent;
+use board::Board;
+use rand::{Rng, StdRng};
+use simulation::Simulation;
+use stats::Stats;
+
+#[derive(Clone, Copy, Debug)]
+pub struct BoidsConfig {
+    pub num_boids: usize,
+    /// Boids closer than this steer away from each other.
+    pub separation_radius: f64,
+    /// Boids closer than this are considered flockmates.
+    pub neighbor_radius: f64,
+    /// Minimum and maximum distance traveled in one step, in cells.
+    pub min_speed: f64,
+    pub max_speed: f64,
+    pub separation_weight: f64,
+    pub alignment_weight: f64,
+    pub cohesion_weight: f64
+}
+
+impl Default for BoidsConfig {
+    fn default() -> BoidsConfig {
+        BoidsConfig {
+            num_boids: 150,
+            separation_radius: 1.5,
+            neighbor_radius: 6.0,
+            min_speed: 0.2,
+            max_speed: 0.5,
+            separation_weight: 0.05,
+            alignment_weight: 0.05,
+            cohesion_weight: 0.005
+        }
+    }
+}
+
+/// Reynolds' boids flocking in continuous space. The world wraps around
+/// like a torus; the board only defines its size and stays empty.
+#[derive(Clone)]
+pub struct Boids {
+    pub cfg: BoidsConfig,
+    board: Board<bool>,
+    boids: Vec<Agent>,
+    iteration: usize
+}
+
+/// Shortest difference between two coordinates on a circle of given size.
+fn wrapped_delta(from: f64,
+                 to: f64,
+                 size: f64) -> f64 {
+    let delta = to - from;
+    if delta > size / 2.0 {
+        delta - size
+    } else if delta < -size / 2.0 {
+        delta + size
+    } else {
+        delta
+    }
+}
+
+impl Boids {
+    pub fn new(width: usize,
+               height: usize,
+               cfg: BoidsConfig,
+               mut rng: Box<StdRng>) -> Boids {
+        use std::f64::consts::PI;
+
+        let boids = (0..cfg.num_boids).map(|_| {
+                                          let angle = rng.gen::<f64>() * 2.0 * PI;
+                                          Agent {
+                                              position: (rng.gen::<f64>() * width as f64,
+                                                         rng.gen::<f64>() * height as f64),
+                                              velocity: (angle.cos() * cfg.max_speed,
+                                                         angle.sin() * cfg.max_speed)
+                                          }
+                                      })
+                                      .collect();
+
+        Boids::from_agents(width, height, cfg, boids)
+    }
+
+    pub fn from_agents(width: usize,
+                       height: usize,
+                       cfg: BoidsConfig,
+                       boids: Vec<Agent>) -> Boids {
+        Boids {
+            cfg: cfg,
+            board: Board::new(width, height, false),
+            boids: boids,
+            iteration: 0
+        }
+    }
+
+    fn steer(&self,
+             idx: usize) -> (f64, f64) {
+        let boid = &self.boids[idx];
+        let (width, height) = (self.board.width as f64, self.board.height as f64);
+
+        let mut separation = (0.0, 0.0);
+        let mut velocity_sum = (0.0, 0.0);
+        let mut offset_sum = (0.0, 0.0);
+        let mut neighbors = 0;
+
+        for (other_idx, other) in self.boids.iter().enumerate() {
+            if other_idx == idx {
+                continue;
+            }
+
+            let dx = wrapped_delta(boid.position.0, other.position.0, width);
+            let dy = wrapped_delta(boid.position.1, other.position.1, height);
+            let distance = (dx * dx + dy * dy).sqrt();
+
+            if distance < self.cfg.neighbor_radius {
+                neighbors += 1;
+                velocity_sum = (velocity_sum.0 + other.velocity.0, velocity_sum.1 + other.velocity.1);
+                offset_sum = (offset_sum.0 + dx, offset_sum.1 + dy);
+            }
+            if distance < self.cfg.separation_radius && distance > 0.0 {
+                separation = (separation.0 - dx / distance, separation.1 - dy / distance);
+            }
+        }
+
+        let mut velocity = boid.velocity;
+        velocity.0 += separation.0 * self.cfg.separation_weight;
+        velocity.1 += separation.1 * self.cfg.separation_weight;
+
+        if neighbors > 0 {
+            let n = neighbors as f64;
+            velocity.0 += (velocity_sum.0 / n - boid.velocity.0) * self.cfg.alignment_weight
+                          + offset_sum.0 / n * self.cfg.cohesion_weight;
+            velocity.1 += (velocity_sum.1 / n - boid.velocity.1) * self.cfg.alignment_weight
+                          + offset_sum.1 / n * self.cfg.cohesion_weight;
+        }
+
+        let speed = (velocity.0 * velocity.0 + velocity.1 * velocity.1).sqrt();
+        let clamped = speed.max(self.cfg.min_speed).min(self.cfg.max_speed);
+        if speed > 0.0 && clamped != speed {
+            velocity = (velocity.0 / speed * clamped,
+                        velocity.1 / speed * clamped);
+        }
+
+        velocity
+    }
+
+    /// Length of the average heading: 1 when all boids fly the same
+    /// direction, close to 0 when headings are random.
+    pub fn polarization(&self) -> f64 {
+        if self.boids.is_empty() {
+            return 0.0;
+        }
+
+        let (sum_x, sum_y) = self.boids.iter().fold((0.0, 0.0), |(x, y), boid| {
+            let heading = boid.heading();
+            (x + heading.cos(), y + heading.sin())
+        });
+
+        (sum_x * sum_x + sum_y * sum_y).sqrt() / self.boids.len() as f64
+    }
+}
+
+impl Simulation<bool> for Boids {
+    fn advance(&mut self) {
+        let velocities: Vec<(f64, f64)> = (0..self.boids.len()).map(|idx| self.steer(idx)).collect();
+        let (width, height) = (self.board.width as f64, self.board.height as f64);
+
+        for (boid, velocity) in self.boids.iter_mut().zip(velocities) {
+            boid.velocity = velocity;
+            boid.position = ((boid.position.0 + velocity.0 + width) % width,
+                             (boid.position.1 + velocity.1 + height) % height);
+        }
+
+        self.iteration += 1;
+    }
+
+    fn board(&self) -> &Board<bool> {
+        &self.board
+    }
+
+    fn agents(&self) -> &[Agent] {
+        &self.boids
+    }
+
+    fn stats(&self) -> Stats {
+        let mean_speed = if self.boids.is_empty() {
+            0.0
+        } else {
+            self.boids.iter()
+                      .fold(0.0, |sum, boid| sum + (boid.velocity.0 * boid.velocity.0
+                                                    + boid.velocity.1 * boid.velocity.1).sqrt())
+                / self.boids.len() as f64
+        };
+
+        Stats::new(self.iteration).count("boids", self.boids.len())
+                                  .real("polarization", self.polarization() as f32)
+                                  .real("mean_speed", mean_speed as f32)
+    }
+}
+
+#[test]
+fn test_wrapped_delta() {
+    assert_eq!(1.0, wrapped_delta(9.5, 0.5, 10.0));
+    assert_eq!(-1.0, wrapped_delta(0.5, 9.5, 10.0));
+    assert_eq!(2.0, wrapped_delta(3.0, 5.0, 10.0));
+}
+
+#[test]
+fn test_boids_align() {
+    let cfg = BoidsConfig {
+        separation_weight: 0.0,
+        cohesion_weight: 0.0,
+        alignment_weight: 0.5,
+        .. BoidsConfig::default()
+    };
+    let mut sim = Boids::from_agents(20, 20, cfg, vec![
+        Agent { position: (5.0, 5.0), velocity: (0.5, 0.0) },
+        Agent { position: (6.0, 5.0), velocity: (0.0, 0.5) }
+    ]);
+
+    let before = sim.polarization();
+    for _ in 0..10 {
+        sim.advance();
+    }
+
+    assert!(sim.polarization() > before);
+    assert!(sim.polarization() > 0.99);
+}
+
+#[test]
+fn test_boids_wrap_around() {
+    let mut sim = Boids::from_agents(10, 10, BoidsConfig::default(), vec![
+        Agent { position: (9.8, 0.2), velocity: (0.5, -0.5) }
+    ]);
+
+    sim.advance();
+    let position = sim.agents()[0].position;
+    assert!(position.0 < 1.0 && position.1 > 9.0);
+}
@@ -0,0 +1,109 @@
+/// What happens at the edges of a GoodEvil board.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Boundary {
+    /// Specimens can't leave the board.
+    Closed,
+    /// Specimens walking off the board are removed, and new ones
+    /// immigrate through the edges.
+    Open
+}
+
+impl Boundary {
+    pub fn from_name(name: &str) -> Option<Boundary> {
+        match name {
+            "closed" => Some(Boundary::Closed),
+            "open" => Some(Boundary::Open),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Boundary::Closed => "closed",
+            Boundary::Open => "open"
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom
+}
+
+impl Edge {
+    pub fn all() -> &'static [Edge] {
+        const ALL: &'static [Edge] = &[Edge::Left, Edge::Right, Edge::Top, Edge::Bottom];
+        ALL
+    }
+
+    pub fn index(&self) -> usize {
+        match *self {
+            Edge::Left => 0,
+            Edge::Right => 1,
+            Edge::Top => 2,
+            Edge::Bottom => 3
+        }
+    }
+
+    /// Returns the edge crossed to get to (x, y), or None if the point lies
+    /// on the board. Leaving through a corner counts as crossing the left or
+    /// right edge.
+    pub fn crossed(x: i64,
+                   y: i64,
+                   width: usize,
+                   height: usize) -> Option<Edge> {
+        if x < 0 {
+            Some(Edge::Left)
+        } else if x >= width as i64 {
+            Some(Edge::Right)
+        } else if y < 0 {
+            Some(Edge::Top)
+        } else if y >= height as i64 {
+            Some(Edge::Bottom)
+        } else {
+            None
+        }
+    }
+
+    pub fn len(&self,
+               width: usize,
+               height: usize) -> usize {
+        match *self {
+            Edge::Left | Edge::Right => height,
+            Edge::Top | Edge::Bottom => width
+        }
+    }
+
+    /// Coordinates of the `idx`-th board cell adjacent to this edge.
+    pub fn cell(&self,
+                idx: usize,
+                width: usize,
+                height: usize) -> (usize, usize) {
+        match *self {
+            Edge::Left => (0, idx),
+            Edge::Right => (width - 1, idx),
+            Edge::Top => (idx, 0),
+            Edge::Bottom => (idx, height - 1)
+        }
+    }
+}
+
+#[test]
+fn test_edge_crossed() {
+    assert_eq!(None, Edge::crossed(0, 0, 4, 3));
+    assert_eq!(None, Edge::crossed(3, 2, 4, 3));
+    assert_eq!(Some(Edge::Left), Edge::crossed(-1, -1, 4, 3));
+    assert_eq!(Some(Edge::Right), Edge::crossed(4, 1, 4, 3));
+    assert_eq!(Some(Edge::Top), Edge::crossed(2, -1, 4, 3));
+    assert_eq!(Some(Edge::Bottom), Edge::crossed(2, 3, 4, 3));
+}
+
+#[test]
+fn test_edge_cell() {
+    assert_eq!((3, 1), Edge::Right.cell(1, 4, 3));
+    assert_eq!((1, 2), Edge::Bottom.cell(1, 4, 3));
+    assert_eq!(3, Edge::Left.len(4, 3));
+}
@@ -0,0 +1,103 @@
+//! Shape of the area painted around the cursor in a single stroke.
+
+/// Largest radius the brush tip can be grown to.
+pub const MAX_RADIUS: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BrushShape {
+    Square,
+    Circle
+}
+
+impl BrushShape {
+    pub fn from_name(name: &str) -> Option<BrushShape> {
+        match name {
+            "square" => Some(BrushShape::Square),
+            "circle" => Some(BrushShape::Circle),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            BrushShape::Square => "square",
+            BrushShape::Circle => "circle"
+        }
+    }
+}
+
+/// Cells painted around the cursor: a square or circle of `radius` cells
+/// around it, the cursor cell only if `radius` is 0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BrushTip {
+    pub shape: BrushShape,
+    pub radius: usize
+}
+
+impl Default for BrushTip {
+    fn default() -> BrushTip {
+        BrushTip {
+            shape: BrushShape::Square,
+            radius: 0
+        }
+    }
+}
+
+impl BrushTip {
+    /// The tip with its radius changed by `steps`, within [0, MAX_RADIUS].
+    pub fn resized(&self,
+                   steps: i32) -> BrushTip {
+        let radius = (self.radius as i64 + steps as i64).max(0).min(MAX_RADIUS as i64);
+        BrushTip {
+            radius: radius as usize,
+            ..*self
+        }
+    }
+
+    /// Cells of a `width` x `height` board covered by the tip centered at
+    /// (x, y), row by row.
+    pub fn cells(&self,
+                 x: usize,
+                 y: usize,
+                 width: usize,
+                 height: usize) -> Vec<(usize, usize)> {
+        let radius = self.radius as i64;
+        let mut cells = Vec::new();
+        for dy in -radius..radius + 1 {
+            for dx in -radius..radius + 1 {
+                if self.shape == BrushShape::Circle && dx * dx + dy * dy > radius * radius + radius {
+                    continue;
+                }
+                let (px, py) = (x as i64 + dx, y as i64 + dy);
+                if px >= 0 && py >= 0 && px < width as i64 && py < height as i64 {
+                    cells.push((px as usize, py as usize));
+                }
+            }
+        }
+        cells
+    }
+}
+
+#[test]
+fn test_brush_tip_cells() {
+    assert_eq!(vec![(2, 2)], BrushTip::default().cells(2, 2, 5, 5));
+
+    let square = BrushTip { shape: BrushShape::Square, radius: 1 };
+    assert_eq!(9, square.cells(2, 2, 5, 5).len());
+    assert_eq!(vec![(0, 0), (1, 0), (0, 1), (1, 1)], square.cells(0, 0, 5, 5));
+
+    let circle = BrushTip { shape: BrushShape::Circle, radius: 2 };
+    let cells = circle.cells(2, 2, 5, 5);
+    assert_eq!(21, cells.len());
+    assert!(!cells.contains(&(0, 0)));
+    assert!(cells.contains(&(2, 0)));
+    assert!(cells.contains(&(1, 0)));
+}
+
+#[test]
+fn test_brush_tip_resized() {
+    let tip = BrushTip::default();
+    assert_eq!(0, tip.resized(-1).radius);
+    assert_eq!(3, tip.resized(3).radius);
+    assert_eq!(MAX_RADIUS, tip.resized(100).radius);
+}
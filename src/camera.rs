@@ -0,0 +1,206 @@
+use std::cmp::min;
+
+use symmetry::Region;
+
+/// Part of the view left around a region the camera is fitted to.
+const FIT_MARGIN: f64 = 0.1;
+
+/// How the board is fitted to a window of other proportions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Aspect {
+    /// Fills the window, with cells stretched to its proportions.
+    Stretch,
+    /// Keeps cells square, leaving bars of background along two sides.
+    Letterbox,
+    /// Keeps cells square and fills the width, cutting off the top and
+    /// bottom of the board if needed.
+    FitWidth,
+    /// Like `FitWidth`, but fills the height.
+    FitHeight
+}
+
+impl Aspect {
+    pub fn from_name(name: &str) -> Option<Aspect> {
+        match name {
+            "stretch" => Some(Aspect::Stretch),
+            "letterbox" => Some(Aspect::Letterbox),
+            "fit-width" => Some(Aspect::FitWidth),
+            "fit-height" => Some(Aspect::FitHeight),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Aspect::Stretch => "stretch",
+            Aspect::Letterbox => "letterbox",
+            Aspect::FitWidth => "fit-width",
+            Aspect::FitHeight => "fit-height"
+        }
+    }
+}
+
+/// Describes which part of the board is displayed in the window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    /// Board coordinates displayed in the middle of the window. `None`
+    /// keeps the board centered.
+    pub center: Option<(f64, f64)>,
+    /// Magnification relative to fitting the whole board in the window.
+    pub zoom: f64,
+    pub aspect: Aspect
+}
+
+impl Camera {
+    pub fn new() -> Camera {
+        Camera {
+            center: None,
+            zoom: 1.0,
+            aspect: Aspect::Stretch
+        }
+    }
+
+    /// Camera centered on `region`, zoomed in to show it whole with a
+    /// margin around, but never zoomed out below showing the whole board.
+    pub fn fit(region: Region,
+               board_size: (usize, usize)) -> Camera {
+        let zoom_x = board_size.0 as f64 / region.width as f64;
+        let zoom_y = board_size.1 as f64 / region.height as f64;
+        Camera {
+            center: Some((region.x as f64 + region.width as f64 / 2.0,
+                          region.y as f64 + region.height as f64 / 2.0)),
+            zoom: (zoom_x.min(zoom_y) * (1.0 - FIT_MARGIN)).max(1.0),
+            aspect: Aspect::Stretch
+        }
+    }
+
+    /// Size of a cell, computed from the viewport size every frame so that
+    /// the board follows the window as it's resized.
+    pub fn cell_size(&self,
+                     board_size: (usize, usize),
+                     viewport_size: [f64; 2]) -> [f64; 2] {
+        let stretched = [viewport_size[0] / board_size.0 as f64,
+                         viewport_size[1] / board_size.1 as f64];
+        let side = match self.aspect {
+            Aspect::Stretch => return [stretched[0] * self.zoom, stretched[1] * self.zoom],
+            Aspect::Letterbox => stretched[0].min(stretched[1]),
+            Aspect::FitWidth => stretched[0],
+            Aspect::FitHeight => stretched[1]
+        };
+        [side * self.zoom, side * self.zoom]
+    }
+
+    /// Screen position of the top-left corner of the (0, 0) board cell.
+    pub fn origin(&self,
+                  board_size: (usize, usize),
+                  viewport_size: [f64; 2]) -> [f64; 2] {
+        let cell_size = self.cell_size(board_size, viewport_size);
+        let (center_x, center_y) = match self.center {
+            Some(center) => center,
+            None => (board_size.0 as f64 / 2.0, board_size.1 as f64 / 2.0)
+        };
+
+        [viewport_size[0] / 2.0 - center_x * cell_size[0],
+         viewport_size[1] / 2.0 - center_y * cell_size[1]]
+    }
+
+    /// Returns the board cell under given screen position, if any.
+    pub fn board_position(&self,
+                          board_size: (usize, usize),
+                          viewport_size: [f64; 2],
+                          position: [f64; 2]) -> Option<(usize, usize)> {
+        let cell_size = self.cell_size(board_size, viewport_size);
+        let origin = self.origin(board_size, viewport_size);
+
+        let x = (position[0] - origin[0]) / cell_size[0];
+        let y = (position[1] - origin[1]) / cell_size[1];
+
+        if x >= 0.0 && y >= 0.0 && x < board_size.0 as f64 && y < board_size.1 as f64 {
+            Some((x as usize, y as usize))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the half-open ranges of board columns and rows that are at
+    /// least partially visible.
+    pub fn visible_cells(&self,
+                         board_size: (usize, usize),
+                         viewport_size: [f64; 2]) -> ((usize, usize), (usize, usize)) {
+        let cell_size = self.cell_size(board_size, viewport_size);
+        let origin = self.origin(board_size, viewport_size);
+
+        let range = |origin: f64, cell_size: f64, viewport_size: f64, cells: usize| {
+            let first = (-origin / cell_size).floor().max(0.0) as usize;
+            let last = ((viewport_size - origin) / cell_size).ceil().max(0.0) as usize;
+            (min(first, cells), min(last, cells))
+        };
+
+        (range(origin[0], cell_size[0], viewport_size[0], board_size.0),
+         range(origin[1], cell_size[1], viewport_size[1], board_size.1))
+    }
+}
+
+#[test]
+fn test_camera_default_shows_whole_board() {
+    let camera = Camera::new();
+
+    assert_eq!([10.0, 5.0], camera.cell_size((8, 6), [80.0, 30.0]));
+    assert_eq!([0.0, 0.0], camera.origin((8, 6), [80.0, 30.0]));
+    assert_eq!(((0, 8), (0, 6)), camera.visible_cells((8, 6), [80.0, 30.0]));
+}
+
+#[test]
+fn test_camera_zoomed_in() {
+    let camera = Camera {
+        center: Some((2.0, 2.0)),
+        zoom: 2.0,
+        aspect: Aspect::Stretch
+    };
+
+    assert_eq!([20.0, 20.0], camera.cell_size((8, 8), [80.0, 80.0]));
+    assert_eq!([0.0, 0.0], camera.origin((8, 8), [80.0, 80.0]));
+    assert_eq!(((0, 4), (0, 4)), camera.visible_cells((8, 8), [80.0, 80.0]));
+}
+
+#[test]
+fn test_camera_board_position() {
+    let camera = Camera {
+        center: Some((2.0, 2.0)),
+        zoom: 2.0,
+        aspect: Aspect::Stretch
+    };
+
+    assert_eq!(Some((1, 3)), camera.board_position((8, 8), [80.0, 80.0], [25.0, 79.0]));
+    assert_eq!(None, camera.board_position((8, 8), [80.0, 80.0], [-1.0, 10.0]));
+    assert_eq!(None, Camera::new().board_position((8, 8), [0.0, 0.0], [10.0, 10.0]));
+}
+
+#[test]
+fn test_camera_fit() {
+    let camera = Camera::fit(Region { x: 2, y: 4, width: 4, height: 2 }, (40, 40));
+
+    assert_eq!(Some((4.0, 5.0)), camera.center);
+    assert_eq!(9.0, camera.zoom);
+    assert_eq!(1.0, Camera::fit(Region { x: 0, y: 0, width: 40, height: 40 }, (40, 40)).zoom);
+}
+
+#[test]
+fn test_camera_aspect() {
+    let letterbox = Camera { aspect: Aspect::Letterbox, ..Camera::new() };
+    assert_eq!([5.0, 5.0], letterbox.cell_size((8, 6), [80.0, 30.0]));
+    assert_eq!([20.0, 0.0], letterbox.origin((8, 6), [80.0, 30.0]));
+    assert_eq!(None, letterbox.board_position((8, 6), [80.0, 30.0], [10.0, 10.0]));
+    assert_eq!(Some((0, 2)), letterbox.board_position((8, 6), [80.0, 30.0], [20.0, 10.0]));
+
+    let fit_width = Camera { aspect: Aspect::FitWidth, ..Camera::new() };
+    assert_eq!([10.0, 10.0], fit_width.cell_size((8, 6), [80.0, 30.0]));
+    assert_eq!(((0, 8), (1, 5)), fit_width.visible_cells((8, 6), [80.0, 30.0]));
+
+    let fit_height = Camera { aspect: Aspect::FitHeight, ..Camera::new() };
+    assert_eq!([5.0, 5.0], fit_height.cell_size((8, 6), [80.0, 30.0]));
+
+    for aspect in &[Aspect::Stretch, Aspect::Letterbox, Aspect::FitWidth, Aspect::FitHeight] {
+        assert_eq!(Some(*aspect), Aspect::from_name(aspect.name()));
+    }
+}
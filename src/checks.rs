@@ -0,0 +1,75 @@
+//! Internal consistency checks done by simulations as they run, grouped by
+//! cost, so that benchmarks can skip them while debugging runs do them all
+//! on every step.
+
+use std::fmt::Debug;
+
+use board::Board;
+
+/// How thoroughly a simulation checks itself. Every level includes the
+/// checks of the levels before it.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum CheckLevel {
+    Off,
+    /// Checks costing no more than the step itself, such as post-conditions
+    /// of resolving conflicts.
+    Cheap,
+    /// Checks worth a noticeable part of a step, such as auditing that no
+    /// energy appears out of nowhere.
+    Expensive,
+    /// Checks several times as costly as the step, such as repeating it on
+    /// a copy of the simulation and comparing board digests.
+    Paranoid
+}
+
+impl CheckLevel {
+    pub fn from_name(name: &str) -> Option<CheckLevel> {
+        match name {
+            "off" => Some(CheckLevel::Off),
+            "cheap" => Some(CheckLevel::Cheap),
+            "expensive" => Some(CheckLevel::Expensive),
+            "paranoid" => Some(CheckLevel::Paranoid),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            CheckLevel::Off => "off",
+            CheckLevel::Cheap => "cheap",
+            CheckLevel::Expensive => "expensive",
+            CheckLevel::Paranoid => "paranoid"
+        }
+    }
+}
+
+/// FNV-1a hash of the debug representation of all cells, for telling
+/// whether two boards are equal at a glance.
+pub fn digest<T: Debug>(board: &Board<T>) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    for cell in board.iter() {
+        for byte in format!("{:?};", cell).bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
+}
+
+#[test]
+fn test_check_levels() {
+    assert!(CheckLevel::Paranoid > CheckLevel::Expensive);
+    assert!(CheckLevel::Cheap > CheckLevel::Off);
+    assert_eq!(Some(CheckLevel::Expensive), CheckLevel::from_name("expensive"));
+    assert_eq!(None, CheckLevel::from_name("thorough"));
+}
+
+#[test]
+fn test_digest() {
+    let mut board = Board::new(3, 2, 0u8);
+    let empty = digest(&board);
+    *board.at_mut(1, 1) = 1;
+    assert!(digest(&board) != empty);
+    *board.at_mut(1, 1) = 0;
+    assert_eq!(empty, digest(&board));
+}
@@ -0,0 +1,112 @@
+//! Color maps drawing scalar values, like pheromone, fluid density or
+//! specimen energy, as heatmaps.
+
+/// Perceptually uniform or classic palettes, each a list of colors evenly
+/// spaced over range [0, 1] and interpolated between.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorMap {
+    Viridis,
+    Inferno,
+    Jet
+}
+
+const VIRIDIS: &'static [[u8; 3]] = &[
+    [68, 1, 84], [59, 82, 139], [33, 145, 140], [94, 201, 98], [253, 231, 37]
+];
+
+const INFERNO: &'static [[u8; 3]] = &[
+    [0, 0, 4], [66, 10, 104], [147, 38, 103], [221, 81, 58], [252, 165, 10], [252, 255, 164]
+];
+
+const JET: &'static [[u8; 3]] = &[
+    [0, 0, 128], [0, 0, 255], [0, 128, 255], [0, 255, 255], [128, 255, 128],
+    [255, 255, 0], [255, 128, 0], [255, 0, 0], [128, 0, 0]
+];
+
+/// Maps `value` in range [`min`, `max`] to [0, 1], clamping values outside
+/// of it.
+pub fn normalize(value: f32,
+                 min: f32,
+                 max: f32) -> f32 {
+    if max <= min {
+        return 0.0;
+    }
+    ((value - min) / (max - min)).max(0.0).min(1.0)
+}
+
+impl ColorMap {
+    pub fn from_name(name: &str) -> Option<ColorMap> {
+        match name {
+            "viridis" => Some(ColorMap::Viridis),
+            "inferno" => Some(ColorMap::Inferno),
+            "jet" => Some(ColorMap::Jet),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ColorMap::Viridis => "viridis",
+            ColorMap::Inferno => "inferno",
+            ColorMap::Jet => "jet"
+        }
+    }
+
+    /// The color map after `map`, with None standing for the default colors
+    /// of each simulation.
+    pub fn cycle(map: Option<ColorMap>) -> Option<ColorMap> {
+        match map {
+            None => Some(ColorMap::Viridis),
+            Some(ColorMap::Viridis) => Some(ColorMap::Inferno),
+            Some(ColorMap::Inferno) => Some(ColorMap::Jet),
+            Some(ColorMap::Jet) => None
+        }
+    }
+
+    fn stops(&self) -> &'static [[u8; 3]] {
+        match *self {
+            ColorMap::Viridis => VIRIDIS,
+            ColorMap::Inferno => INFERNO,
+            ColorMap::Jet => JET
+        }
+    }
+
+    /// Color of `value` in range [0, 1], clamped if outside of it.
+    pub fn color(&self,
+                 value: f32) -> [f32; 4] {
+        let stops = self.stops();
+        let position = value.max(0.0).min(1.0) * (stops.len() - 1) as f32;
+        let low = (position as usize).min(stops.len() - 2);
+        let t = position - low as f32;
+
+        let mut color = [1.0; 4];
+        for channel in 0..3 {
+            let (from, to) = (stops[low][channel] as f32, stops[low + 1][channel] as f32);
+            color[channel] = (from + (to - from) * t) / 255.0;
+        }
+        color
+    }
+}
+
+#[test]
+fn test_normalize() {
+    assert_eq!(0.5, normalize(1.0, 0.9, 1.1));
+    assert_eq!(0.0, normalize(-3.0, 0.0, 4.0));
+    assert_eq!(1.0, normalize(5.0, 0.0, 4.0));
+    assert_eq!(0.0, normalize(1.0, 1.0, 1.0));
+}
+
+#[test]
+fn test_color_map() {
+    assert_eq!([0.0, 0.0, 128.0 / 255.0, 1.0], ColorMap::Jet.color(-1.0));
+    assert_eq!([128.0 / 255.0, 1.0, 128.0 / 255.0, 1.0], ColorMap::Jet.color(0.5));
+    assert_eq!([128.0 / 255.0, 0.0, 0.0, 1.0], ColorMap::Jet.color(2.0));
+    assert_eq!([253.0 / 255.0, 231.0 / 255.0, 37.0 / 255.0, 1.0], ColorMap::Viridis.color(1.0));
+
+    let mut map = None;
+    for _ in 0..4 {
+        map = ColorMap::cycle(map);
+        assert_eq!(map, map.and_then(|map| ColorMap::from_name(map.name())));
+    }
+    assert_eq!(None, map);
+}
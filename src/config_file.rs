@@ -0,0 +1,403 @@
+//! Option files loaded with `--config`. A file maps long command line option
+//! names to their values, e.g. `board-size = "80,60"` or `headless = true`,
+//! so that everything that can be set on the command line can be kept in a
+//! file. Files ending with `.json` are read as JSON objects, all others as a
+//! subset of TOML: `key = value` lines with strings, numbers, booleans and
+//! one-line arrays. Tables only group options, their names are ignored.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{Read, Write};
+
+use rustc_serialize::json::{Json, ToJson};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Toml,
+    Json
+}
+
+impl Format {
+    pub fn from_path(path: &str) -> Format {
+        if path.ends_with(".json") {
+            Format::Json
+        } else {
+            Format::Toml
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// Option without a value, set if true.
+    Flag(bool),
+    Text(String),
+    /// Option given multiple times.
+    List(Vec<String>)
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Flag(set) => write!(f, "{}", set),
+            Value::Text(ref text) => write!(f, "{}", text),
+            Value::List(ref items) => write!(f, "{}", items.join(", "))
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigFile {
+    /// Option values, in the order they were set.
+    pub entries: Vec<(String, Value)>
+}
+
+impl ConfigFile {
+    pub fn new() -> ConfigFile {
+        ConfigFile {
+            entries: Vec::new()
+        }
+    }
+
+    pub fn get(&self,
+               name: &str) -> Option<&Value> {
+        self.entries.iter()
+                    .find(|&&(ref key, _)| key == name)
+                    .map(|&(_, ref value)| value)
+    }
+
+    /// Sets `name` to `value`, replacing the previous value if any.
+    pub fn set(&mut self,
+               name: &str,
+               value: Value) {
+        match self.entries.iter().position(|&(ref key, _)| key == name) {
+            Some(idx) => self.entries[idx].1 = value,
+            None => self.entries.push((name.to_string(), value))
+        }
+    }
+
+    pub fn parse(text: &str,
+                 format: Format) -> Result<ConfigFile, String> {
+        match format {
+            Format::Toml => ConfigFile::parse_toml(text),
+            Format::Json => ConfigFile::parse_json(text)
+        }
+    }
+
+    pub fn load(path: &str) -> Result<ConfigFile, String> {
+        let mut contents = String::new();
+        try!(File::open(path).and_then(|mut f| f.read_to_string(&mut contents))
+                             .map_err(|e| format!("cannot read {}: {}", path, e)));
+
+        ConfigFile::parse(&contents, Format::from_path(path)).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn save(&self,
+                path: &str) -> Result<(), String> {
+        let text = match Format::from_path(path) {
+            Format::Toml => self.to_toml(),
+            Format::Json => format!("{}\n", self.to_json().pretty())
+        };
+
+        File::create(path).and_then(|mut f| f.write_all(text.as_bytes()))
+                          .map_err(|e| format!("cannot write {}: {}", path, e))
+    }
+
+    fn parse_toml(text: &str) -> Result<ConfigFile, String> {
+        let mut config = ConfigFile::new();
+
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('[') {
+                if !line.ends_with(']') {
+                    return Err(format!("line {}: unterminated table header", idx + 1));
+                }
+                continue;
+            }
+
+            let (key, rest) = match line.find('=') {
+                Some(pos) => (line[..pos].trim(), &line[pos + 1..]),
+                None => return Err(format!("line {}: expected KEY = VALUE", idx + 1))
+            };
+            let key = match toml::parse_key(key) {
+                Some(key) => key,
+                None => return Err(format!("line {}: invalid key: {}", idx + 1, key))
+            };
+            let value = try!(toml::parse_value(rest).map_err(|e| format!("line {}: {}", idx + 1, e)));
+            config.set(&key, value);
+        }
+
+        Ok(config)
+    }
+
+    fn parse_json(text: &str) -> Result<ConfigFile, String> {
+        let json = try!(Json::from_str(text).map_err(|e| e.to_string()));
+        let mut config = ConfigFile::new();
+        try!(config.add_json_object(&json));
+        Ok(config)
+    }
+
+    /// Adds all options from a JSON object, descending into nested objects
+    /// used for grouping.
+    fn add_json_object(&mut self,
+                       json: &Json) -> Result<(), String> {
+        let obj = try!(json.as_object().ok_or("configuration is not a JSON object".to_string()));
+
+        for (key, json) in obj {
+            let value = match *json {
+                Json::Object(_) => {
+                    try!(self.add_json_object(json));
+                    continue;
+                },
+                Json::Boolean(set) => Value::Flag(set),
+                Json::Array(ref items) => {
+                    let items: Vec<String> = try!(items.iter().map(|item| json_text(item, key)).collect());
+                    Value::List(items)
+                },
+                _ => Value::Text(try!(json_text(json, key)))
+            };
+            self.set(key, value);
+        }
+
+        Ok(())
+    }
+
+    pub fn to_toml(&self) -> String {
+        self.entries.iter()
+                    .map(|&(ref key, ref value)| {
+                        let value = match *value {
+                            Value::Flag(set) => set.to_string(),
+                            Value::Text(ref text) => toml::quote(text),
+                            Value::List(ref items) => {
+                                let items: Vec<String> = items.iter().map(|item| toml::quote(item)).collect();
+                                format!("[{}]", items.join(", "))
+                            }
+                        };
+                        format!("{} = {}\n", key, value)
+                    })
+                    .collect()
+    }
+
+    pub fn to_json(&self) -> Json {
+        let obj: BTreeMap<String, Json> = self.entries.iter()
+                                                      .map(|&(ref key, ref value)| {
+                                                          let json = match *value {
+                                                              Value::Flag(set) => set.to_json(),
+                                                              Value::Text(ref text) => text.to_json(),
+                                                              Value::List(ref items) => items.to_json()
+                                                          };
+                                                          (key.clone(), json)
+                                                      })
+                                                      .collect();
+        Json::Object(obj)
+    }
+
+    /// Command line arguments setting the options for which `given` returns
+    /// false, so that options given on the command line take precedence.
+    pub fn to_args<F>(&self,
+                      given: F) -> Vec<String> where F: Fn(&str) -> bool {
+        let mut args = Vec::new();
+
+        for &(ref key, ref value) in self.entries.iter().filter(|&&(ref key, _)| !given(key)) {
+            let option = format!("--{}", key);
+            match *value {
+                Value::Flag(true) => args.push(option),
+                Value::Flag(false) => {},
+                Value::Text(ref text) => {
+                    args.push(option);
+                    args.push(text.clone());
+                },
+                Value::List(ref items) => for item in items {
+                    args.push(option.clone());
+                    args.push(item.clone());
+                }
+            }
+        }
+
+        args
+    }
+}
+
+fn json_text(json: &Json,
+             key: &str) -> Result<String, String> {
+    match *json {
+        Json::String(ref s) => Ok(s.clone()),
+        Json::I64(n) => Ok(n.to_string()),
+        Json::U64(n) => Ok(n.to_string()),
+        Json::F64(n) => Ok(n.to_string()),
+        _ => Err(format!("invalid value of {}: expected a string or a number", key))
+    }
+}
+
+mod toml {
+    use super::Value;
+
+    pub fn parse_key(key: &str) -> Option<String> {
+        if key.len() >= 2 && key.starts_with('"') && key.ends_with('"') {
+            return Some(key[1..key.len() - 1].to_string());
+        }
+        let bare = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+        if !key.is_empty() && key.chars().all(bare) {
+            Some(key.to_string())
+        } else {
+            None
+        }
+    }
+
+    pub fn quote(text: &str) -> String {
+        let mut quoted = String::from("\"");
+        for c in text.chars() {
+            match c {
+                '"' => quoted.push_str("\\\""),
+                '\\' => quoted.push_str("\\\\"),
+                '\n' => quoted.push_str("\\n"),
+                '\t' => quoted.push_str("\\t"),
+                c => quoted.push(c)
+            }
+        }
+        quoted.push('"');
+        quoted
+    }
+
+    /// Parses a value followed by an optional comment.
+    pub fn parse_value(text: &str) -> Result<Value, String> {
+        let text = text.trim();
+
+        let (value, rest) = if text.starts_with('[') {
+            let mut items = Vec::new();
+            let mut rest = text[1..].trim();
+            loop {
+                if rest.starts_with(']') {
+                    break;
+                }
+                let (item, after) = try!(parse_scalar(rest));
+                items.push(item);
+                rest = after.trim();
+                if rest.starts_with(',') {
+                    rest = rest[1..].trim();
+                } else if !rest.starts_with(']') {
+                    return Err("expected , or ] in array".to_string());
+                }
+            }
+            (Value::List(items), &rest[1..])
+        } else {
+            match text {
+                _ if text.starts_with("true") => (Value::Flag(true), &text["true".len()..]),
+                _ if text.starts_with("false") => (Value::Flag(false), &text["false".len()..]),
+                _ => {
+                    let (scalar, rest) = try!(parse_scalar(text));
+                    (Value::Text(scalar), rest)
+                }
+            }
+        };
+
+        let rest = rest.trim();
+        if rest.is_empty() || rest.starts_with('#') {
+            Ok(value)
+        } else {
+            Err(format!("unexpected text after value: {}", rest))
+        }
+    }
+
+    /// Parses a string or a number, returning it and the text following it.
+    fn parse_scalar(text: &str) -> Result<(String, &str), String> {
+        if text.starts_with('\'') {
+            return match text[1..].find('\'') {
+                Some(end) => Ok((text[1..end + 1].to_string(), &text[end + 2..])),
+                None => Err("unterminated string".to_string())
+            };
+        }
+        if text.starts_with('"') {
+            let mut value = String::new();
+            let mut chars = text.char_indices().skip(1);
+            while let Some((idx, c)) = chars.next() {
+                match c {
+                    '"' => return Ok((value, &text[idx + 1..])),
+                    '\\' => match chars.next() {
+                        Some((_, '"')) => value.push('"'),
+                        Some((_, '\\')) => value.push('\\'),
+                        Some((_, 'n')) => value.push('\n'),
+                        Some((_, 't')) => value.push('\t'),
+                        _ => return Err("invalid escape sequence".to_string())
+                    },
+                    c => value.push(c)
+                }
+            }
+            return Err("unterminated string".to_string());
+        }
+
+        let number = |c: char| c.is_alphanumeric() || c == '-' || c == '+' || c == '.' || c == '_';
+        let end = text.find(|c: char| !number(c)).unwrap_or(text.len());
+        if end == 0 {
+            return Err(format!("invalid value: {}", text));
+        }
+        Ok((text[..end].replace('_', ""), &text[end..]))
+    }
+}
+
+#[test]
+fn test_parse_toml_config() {
+    let text = "# comment\n\
+                board-size = \"80,60\"\n\
+                headless = true\n\
+                show-wind = false # not shown\n\
+                \n\
+                [goodevil]\n\
+                crowding = 0.05\n\
+                event = ['drought@100', \"flood@200:0.5\"]\n";
+    let config = ConfigFile::parse(text, Format::Toml).unwrap();
+
+    assert_eq!(Some(&Value::Text("80,60".to_string())), config.get("board-size"));
+    assert_eq!(Some(&Value::Flag(true)), config.get("headless"));
+    assert_eq!(Some(&Value::Flag(false)), config.get("show-wind"));
+    assert_eq!(Some(&Value::Text("0.05".to_string())), config.get("crowding"));
+    assert_eq!(Some(&Value::List(vec!["drought@100".to_string(), "flood@200:0.5".to_string()])),
+               config.get("event"));
+
+    assert!(ConfigFile::parse("board-size", Format::Toml).is_err());
+    assert!(ConfigFile::parse("sim = \"life", Format::Toml).is_err());
+    assert!(ConfigFile::parse("sim = life life", Format::Toml).is_err());
+}
+
+#[test]
+fn test_parse_json_config() {
+    let text = r#"{"sim": "goodevil", "seed": 42, "goodevil": {"reproduction": true}}"#;
+    let config = ConfigFile::parse(text, Format::Json).unwrap();
+
+    assert_eq!(Some(&Value::Text("goodevil".to_string())), config.get("sim"));
+    assert_eq!(Some(&Value::Text("42".to_string())), config.get("seed"));
+    assert_eq!(Some(&Value::Flag(true)), config.get("reproduction"));
+    assert!(ConfigFile::parse("[1, 2]", Format::Json).is_err());
+}
+
+#[test]
+fn test_config_to_args() {
+    let mut config = ConfigFile::new();
+    config.set("sim", Value::Text("life".to_string()));
+    config.set("seed", Value::Text("1".to_string()));
+    config.set("headless", Value::Flag(true));
+    config.set("symmetry", Value::Flag(false));
+    config.set("event", Value::List(vec!["a".to_string(), "b".to_string()]));
+
+    let args = config.to_args(|name| name == "seed");
+    assert_eq!(vec!["--sim", "life", "--headless", "--event", "a", "--event", "b"], args);
+}
+
+#[test]
+fn test_config_round_trip() {
+    let mut config = ConfigFile::new();
+    config.set("sim", Value::Text("say \"hi\"\\".to_string()));
+    config.set("headless", Value::Flag(true));
+    config.set("event", Value::List(vec!["a".to_string(), "b".to_string()]));
+
+    assert_eq!(config, ConfigFile::parse(&config.to_toml(), Format::Toml).unwrap());
+
+    let from_json = ConfigFile::parse(&config.to_json().to_string(), Format::Json).unwrap();
+    for &(ref key, ref value) in &config.entries {
+        assert_eq!(Some(value), from_json.get(key));
+    }
+}
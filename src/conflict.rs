@@ -0,0 +1,93 @@
+use rand::{Rng, StdRng};
+
+use simulation::Specimen;
+
+/// Decides which of the GoodEvil specimens trying to move into the same
+/// cell gets it. The others stay where they were.
+pub trait ConflictResolver: Sync {
+    /// Index of the winner among `contenders`, given in order of arrival.
+    fn winner(&self,
+              contenders: &[Specimen],
+              rng: &mut StdRng) -> usize;
+
+    fn name(&self) -> &'static str;
+}
+
+/// The strongest contender wins, ties going to the one that arrived last.
+pub struct StrongestWins;
+
+/// Every contender is equally likely to win.
+pub struct RandomWins;
+
+/// The contender that arrived first wins.
+pub struct FirstComeWins;
+
+pub static STRONGEST_WINS: StrongestWins = StrongestWins;
+pub static RANDOM_WINS: RandomWins = RandomWins;
+pub static FIRST_COME_WINS: FirstComeWins = FirstComeWins;
+
+impl ConflictResolver for StrongestWins {
+    fn winner(&self,
+              contenders: &[Specimen],
+              _rng: &mut StdRng) -> usize {
+        let mut winner = 0;
+        for (idx, specimen) in contenders.iter().enumerate() {
+            if specimen.strength() >= contenders[winner].strength() {
+                winner = idx;
+            }
+        }
+        winner
+    }
+
+    fn name(&self) -> &'static str {
+        "strongest"
+    }
+}
+
+impl ConflictResolver for RandomWins {
+    fn winner(&self,
+              contenders: &[Specimen],
+              rng: &mut StdRng) -> usize {
+        rng.gen_range(0, contenders.len())
+    }
+
+    fn name(&self) -> &'static str {
+        "random"
+    }
+}
+
+impl ConflictResolver for FirstComeWins {
+    fn winner(&self,
+              _contenders: &[Specimen],
+              _rng: &mut StdRng) -> usize {
+        0
+    }
+
+    fn name(&self) -> &'static str {
+        "first-come"
+    }
+}
+
+/// Built-in resolver with given name.
+pub fn from_name(name: &str) -> Option<&'static ConflictResolver> {
+    let all: [&'static ConflictResolver; 3] = [&STRONGEST_WINS, &RANDOM_WINS, &FIRST_COME_WINS];
+    all.iter().cloned().find(|resolver| resolver.name() == name)
+}
+
+#[test]
+fn test_conflict_winners() {
+    use rand::SeedableRng;
+
+    let mut rng = StdRng::from_seed(&[0usize][..]);
+    let contenders = [Specimen::new(1.0), Specimen::new(2.0), Specimen::new(2.0), Specimen::new(0.5)];
+
+    assert_eq!(2, STRONGEST_WINS.winner(&contenders, &mut rng));
+    assert_eq!(0, FIRST_COME_WINS.winner(&contenders, &mut rng));
+    assert!(RANDOM_WINS.winner(&contenders, &mut rng) < contenders.len());
+}
+
+#[test]
+fn test_conflict_resolver_from_name() {
+    assert_eq!("random", from_name("random").unwrap().name());
+    assert!(from_name("loudest").is_none());
+}
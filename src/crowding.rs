@@ -0,0 +1,34 @@
+/// Extra energy loss of GoodEvil specimens in crowded neighborhoods, keeping
+/// clusters from growing without bound.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CrowdingConfig {
+    /// Energy lost per step for every neighbor above the capacity.
+    pub coefficient: f32,
+    /// Number of occupied neighboring cells tolerated without penalty.
+    pub capacity: usize
+}
+
+impl Default for CrowdingConfig {
+    fn default() -> CrowdingConfig {
+        CrowdingConfig {
+            coefficient: 0.001,
+            capacity: 2
+        }
+    }
+}
+
+impl CrowdingConfig {
+    pub fn penalty(&self,
+                   neighbors: usize) -> f32 {
+        self.coefficient * neighbors.saturating_sub(self.capacity) as f32
+    }
+}
+
+#[test]
+fn test_crowding_penalty() {
+    let cfg = CrowdingConfig { coefficient: 0.5, capacity: 2 };
+
+    assert_eq!(0.0, cfg.penalty(0));
+    assert_eq!(0.0, cfg.penalty(2));
+    assert_eq!(1.5, cfg.penalty(5));
+}
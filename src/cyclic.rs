@@ -0,0 +1,152 @@
+use board::Board;
+use rand::{Rng, StdRng};
+use simulation::{Simulation, torus_neighbors};
+use stats::Stats;
+
+#[derive(Clone, Copy, Debug)]
+pub struct CyclicConfig {
+    pub num_states: u8,
+    /// Minimum number of neighbors in the successor state required for a
+    /// cell to advance to that state.
+    pub threshold: usize
+}
+
+impl Default for CyclicConfig {
+    fn default() -> CyclicConfig {
+        CyclicConfig {
+            num_states: 14,
+            threshold: 1
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CyclicState {
+    pub value: u8,
+    pub num_states: u8
+}
+
+impl CyclicState {
+    pub fn successor(&self) -> CyclicState {
+        CyclicState {
+            value: (self.value + 1) % self.num_states,
+            .. *self
+        }
+    }
+}
+
+/// Cyclic cellular automaton: a cell in state k is "eaten" by state k+1
+/// (mod N) once enough of its Moore neighbors are in state k+1.
+#[derive(Clone)]
+pub struct Cyclic {
+    pub cfg: CyclicConfig,
+    board: Board<CyclicState>,
+    iteration: usize,
+    last_changes: usize
+}
+
+impl Cyclic {
+    pub fn new(width: usize,
+               height: usize,
+               cfg: CyclicConfig,
+               mut rng: Box<StdRng>) -> Cyclic {
+        assert!(cfg.num_states >= 2);
+
+        let mut board = Board::new(width, height, CyclicState {
+            value: 0,
+            num_states: cfg.num_states
+        });
+        for (x, y) in board.indices() {
+            board.at_mut(x, y).value = rng.gen_range(0, cfg.num_states);
+        }
+
+        Cyclic::from_board(board, cfg)
+    }
+
+    pub fn from_board(board: Board<CyclicState>,
+                      cfg: CyclicConfig) -> Cyclic {
+        Cyclic {
+            cfg: cfg,
+            board: board,
+            iteration: 0,
+            // nothing is known to be frozen before the first step
+            last_changes: 1
+        }
+    }
+}
+
+impl Simulation<CyclicState> for Cyclic {
+    fn advance(&mut self) {
+        let mut new = Board::new(self.board.width, self.board.height, *self.board.at(0, 0));
+        let mut changes = 0;
+
+        for (x, y) in self.board.indices() {
+            let state = *self.board.at(x, y);
+            let successor = state.successor();
+            let eaters = torus_neighbors(x, y, self.board.width, self.board.height)
+                             .filter(|&(nbr_x, nbr_y)| *self.board.at(nbr_x, nbr_y) == successor)
+                             .count();
+
+            *new.at_mut(x, y) = if eaters >= self.cfg.threshold {
+                changes += 1;
+                successor
+            } else {
+                state
+            };
+        }
+
+        self.board = new;
+        self.last_changes = changes;
+        self.iteration += 1;
+    }
+
+    fn board(&self) -> &Board<CyclicState> {
+        &self.board
+    }
+
+    fn stats(&self) -> Stats {
+        Stats::new(self.iteration).count("changes", self.last_changes)
+    }
+
+    /// The automaton is frozen once no cell changes its state.
+    fn is_extinct(&self) -> bool {
+        self.last_changes == 0
+    }
+}
+
+#[cfg(test)]
+fn cyclic_board(values: &[u8],
+                width: usize,
+                num_states: u8) -> Board<CyclicState> {
+    let mut board = Board::new(width, values.len() / width, CyclicState {
+        value: 0,
+        num_states: num_states
+    });
+    for (idx, &value) in values.iter().enumerate() {
+        board.at_mut(idx % width, idx / width).value = value;
+    }
+    board
+}
+
+#[test]
+fn test_cyclic_successor_wraps() {
+    let state = CyclicState { value: 2, num_states: 3 };
+    assert_eq!(0, state.successor().value);
+}
+
+#[test]
+fn test_cyclic_threshold() {
+    let board = cyclic_board(&[0, 0, 0,
+                               0, 0, 1,
+                               0, 0, 1], 3, 3);
+
+    let mut sim = Cyclic::from_board(board.clone(), CyclicConfig { num_states: 3, threshold: 2 });
+    sim.advance();
+    // every cell in state 0 has both cells in state 1 as neighbors on a 3x3 torus
+    assert!(sim.board().iter().all(|s| s.value == 1 || s.value == 2));
+    assert_eq!(7, sim.last_changes);
+
+    let mut sim = Cyclic::from_board(board, CyclicConfig { num_states: 3, threshold: 3 });
+    sim.advance();
+    assert!(sim.is_extinct());
+}
@@ -0,0 +1,136 @@
+use board::Board;
+use camera::{Aspect, Camera};
+use i18n::{Language, Message};
+use simulation::SimulationKind;
+
+/// Bundle of settings that sets up a ready-to-watch simulation.
+pub struct Demo {
+    pub name: &'static str,
+    pub description: Message,
+    pub simulation: SimulationKind,
+    pub board_size: (usize, usize),
+    pub seed: usize,
+    pub updates_per_second: f64,
+    pub camera: Camera,
+    /// Initial pattern in plaintext format ('O' = alive) and its position.
+    /// Random initial state is used if not set.
+    pub pattern: Option<(&'static [&'static str], (usize, usize))>
+}
+
+const GOSPER_GLIDER_GUN: &'static [&'static str] = &[
+    "........................O...........",
+    "......................O.O...........",
+    "............OO......OO............OO",
+    "...........O...O....OO............OO",
+    "OO........O.....O...OO..............",
+    "OO........O...O.OO....O.O...........",
+    "..........O.....O.......O...........",
+    "...........O...O....................",
+    "............OO......................"
+];
+
+pub const DEMOS: &'static [Demo] = &[
+    Demo {
+        name: "life-glider-gun",
+        description: Message::DemoLifeGliderGun,
+        simulation: SimulationKind::Life,
+        board_size: (160, 120),
+        seed: 0,
+        updates_per_second: 20.0,
+        camera: Camera {
+            center: Some((40.0, 30.0)),
+            zoom: 2.0,
+            aspect: Aspect::Stretch
+        },
+        pattern: Some((GOSPER_GLIDER_GUN, (10, 10)))
+    },
+    Demo {
+        name: "goodevil-boom-bust",
+        description: Message::DemoGoodEvilBoomBust,
+        simulation: SimulationKind::GoodEvil,
+        board_size: (120, 90),
+        seed: 1275,
+        updates_per_second: 50.0,
+        camera: Camera {
+            center: None,
+            zoom: 1.0,
+            aspect: Aspect::Stretch
+        },
+        pattern: None
+    },
+    Demo {
+        name: "fire-front",
+        description: Message::DemoFireFront,
+        simulation: SimulationKind::ForestFire,
+        board_size: (160, 120),
+        seed: 3,
+        updates_per_second: 20.0,
+        camera: Camera {
+            center: None,
+            zoom: 1.0,
+            aspect: Aspect::Stretch
+        },
+        pattern: None
+    },
+    Demo {
+        name: "epidemic-wave",
+        description: Message::DemoEpidemicWave,
+        simulation: SimulationKind::Epidemic,
+        board_size: (160, 120),
+        seed: 2,
+        updates_per_second: 20.0,
+        camera: Camera {
+            center: None,
+            zoom: 1.0,
+            aspect: Aspect::Stretch
+        },
+        pattern: None
+    }
+];
+
+pub fn find(name: &str) -> Option<&'static Demo> {
+    DEMOS.iter().find(|demo| demo.name == name)
+}
+
+pub fn list(lang: Language) -> String {
+    DEMOS.iter()
+         .map(|demo| format!("    {:20} {}\n", demo.name, lang.tr(demo.description)))
+         .collect()
+}
+
+/// Builds a board with `pattern` placed at `offset`. Cells marked with 'O'
+/// are set to `alive`, all others to `dead`.
+pub fn board_from_pattern<T: Clone>(board_size: (usize, usize),
+                                    pattern: &[&str],
+                                    offset: (usize, usize),
+                                    alive: T,
+                                    dead: T) -> Board<T> {
+    let mut board = Board::new(board_size.0, board_size.1, dead);
+
+    for (dy, row) in pattern.iter().enumerate() {
+        for (dx, c) in row.chars().enumerate() {
+            let (x, y) = (offset.0 + dx, offset.1 + dy);
+            if c == 'O' && x < board.width && y < board.height {
+                *board.at_mut(x, y) = alive.clone();
+            }
+        }
+    }
+
+    board
+}
+
+#[test]
+fn test_find_demo() {
+    assert_eq!(SimulationKind::Life, find("life-glider-gun").unwrap().simulation);
+    assert!(find("no-such-demo").is_none());
+}
+
+#[test]
+fn test_board_from_pattern() {
+    let board = board_from_pattern((4, 3), &[".O", "OO"], (1, 1), true, false);
+
+    let alive: Vec<(usize, usize)> = board.indices()
+                                          .filter(|&(x, y)| *board.at(x, y))
+                                          .collect();
+    assert_eq!(vec![(2, 1), (1, 2), (2, 2)], alive);
+}
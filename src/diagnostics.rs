@@ -0,0 +1,282 @@
+//! Diagnostics bundle written when a simulation panics, e.g. because one of
+//! its invariant assertions failed. The bundle holds everything needed to
+//! reproduce the crash, so that a bug report can simply attach it.
+
+use std::any::Any;
+use std::cmp::max;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use board_view::BoardView;
+use offscreen::Image;
+use palette::CellColor;
+
+/// Pixels per cell side of the snapshot image.
+const SNAPSHOT_CELL_SIZE: usize = 2;
+
+pub fn panic_message(payload: Box<Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(msg) => *msg,
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(msg) => msg.to_string(),
+            Err(_) => "simulation panicked".to_string()
+        }
+    }
+}
+
+/// The last `capacity` lines of output.
+#[derive(Clone, Debug)]
+pub struct LogTail {
+    capacity: usize,
+    lines: VecDeque<String>
+}
+
+impl LogTail {
+    pub fn new(capacity: usize) -> LogTail {
+        LogTail {
+            capacity: capacity,
+            lines: VecDeque::with_capacity(capacity)
+        }
+    }
+
+    pub fn push(&mut self,
+                line: String) {
+        if self.lines.len() == self.capacity {
+            self.lines.pop_front();
+        }
+        if self.capacity > 0 {
+            self.lines.push_back(line);
+        }
+    }
+
+    pub fn lines(&self) -> Vec<&str> {
+        self.lines.iter().map(|line| &line[..]).collect()
+    }
+}
+
+#[derive(Clone)]
+struct Phase {
+    name: &'static str,
+    count: usize,
+    total_ns: u64,
+    max_ns: u64
+}
+
+fn nanos(duration: Duration) -> u64 {
+    duration.as_secs() * 1000000000 + duration.subsec_nanos() as u64
+}
+
+fn millis(ns: u64) -> f64 {
+    ns as f64 / 1000000.0
+}
+
+/// Time spent in a single phase, as reported by `PhaseTimings::report`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PhaseReport {
+    pub name: &'static str,
+    pub calls: usize,
+    pub total_ns: u64,
+    pub max_ns: u64
+}
+
+impl PhaseReport {
+    pub fn mean_ns(&self) -> u64 {
+        if self.calls == 0 { 0 } else { self.total_ns / self.calls as u64 }
+    }
+}
+
+/// Profiler counters, for embedders and benchmarks that want them as data
+/// rather than printed lines.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PerfReport {
+    /// Number of steps profiled.
+    pub ticks: usize,
+    /// Phases in order of first use.
+    pub phases: Vec<PhaseReport>,
+    /// Number of heap allocations, if the allocator counts them. The
+    /// default one doesn't, so this is always None for now.
+    pub allocations: Option<usize>
+}
+
+impl PerfReport {
+    pub fn phase(&self,
+                 name: &str) -> Option<&PhaseReport> {
+        self.phases.iter().find(|phase| phase.name == name)
+    }
+}
+
+/// How long each phase of the main loop took, in order of first use.
+#[derive(Clone)]
+pub struct PhaseTimings {
+    phases: Vec<Phase>
+}
+
+impl PhaseTimings {
+    pub fn new() -> PhaseTimings {
+        PhaseTimings {
+            phases: Vec::new()
+        }
+    }
+
+    pub fn record(&mut self,
+                  name: &'static str,
+                  duration: Duration) {
+        let ns = nanos(duration);
+
+        if !self.phases.iter().any(|phase| phase.name == name) {
+            self.phases.push(Phase {
+                name: name,
+                count: 0,
+                total_ns: 0,
+                max_ns: 0
+            });
+        }
+
+        let phase = self.phases.iter_mut().find(|phase| phase.name == name).unwrap();
+        phase.count += 1;
+        phase.total_ns += ns;
+        phase.max_ns = max(phase.max_ns, ns);
+    }
+
+    /// Counters gathered so far over `ticks` steps.
+    pub fn report(&self,
+                  ticks: usize) -> PerfReport {
+        PerfReport {
+            ticks: ticks,
+            phases: self.phases.iter()
+                               .map(|phase| PhaseReport {
+                                       name: phase.name,
+                                       calls: phase.count,
+                                       total_ns: phase.total_ns,
+                                       max_ns: phase.max_ns
+                                   })
+                               .collect(),
+            allocations: None
+        }
+    }
+}
+
+impl fmt::Display for PhaseTimings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for phase in self.phases.iter() {
+            try!(writeln!(f, "{}: {} calls, mean {:.3} ms, max {:.3} ms",
+                          phase.name, phase.count, millis(phase.total_ns) / phase.count as f64, millis(phase.max_ns)));
+        }
+        Ok(())
+    }
+}
+
+/// State of a simulation that panicked.
+pub struct Crash<T> {
+    pub message: String,
+    pub log_tail: LogTail,
+    pub timings: PhaseTimings,
+    /// Board after a recent step, if known.
+    pub snapshot: Option<BoardView<T>>
+}
+
+impl<T> Crash<T> {
+    pub fn new(message: String) -> Crash<T> {
+        Crash {
+            message: message,
+            log_tail: LogTail::new(0),
+            timings: PhaseTimings::new(),
+            snapshot: None
+        }
+    }
+}
+
+fn write_file(dir: &Path,
+              name: &str,
+              contents: &str) -> Result<(), String> {
+    let path = dir.join(name);
+    let mut file = try!(File::create(&path).map_err(|e| format!("cannot create {}: {}", path.display(), e)));
+    file.write_all(contents.as_bytes())
+        .map_err(|e| format!("cannot write {}: {}", path.display(), e))
+}
+
+impl<T: CellColor> Crash<T> {
+    /// Writes the bundle to `dir`, creating it if needed. `config` should
+    /// include the seed, so that the run can be repeated.
+    pub fn write_bundle(&self,
+                        dir: &str,
+                        config: &str) -> Result<(), String> {
+        try!(fs::create_dir_all(dir).map_err(|e| format!("cannot create {}: {}", dir, e)));
+        let dir = Path::new(dir);
+
+        try!(write_file(dir, "error.txt", &format!("{}\n", self.message)));
+        try!(write_file(dir, "config.txt", &format!("{}\n", config)));
+        try!(write_file(dir, "log.txt", &self.log_tail.lines().iter().map(|line| format!("{}\n", line)).collect::<String>()));
+        try!(write_file(dir, "timings.txt", &self.timings.to_string()));
+
+        if let Some(ref snapshot) = self.snapshot {
+            let image = Image::render_board(snapshot, None, SNAPSHOT_CELL_SIZE);
+            try!(write_file(dir, "snapshot.ppm", &image.to_ppm()));
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_log_tail_keeps_last_lines() {
+    let mut tail = LogTail::new(2);
+    for i in 0..5 {
+        tail.push(i.to_string());
+    }
+
+    assert_eq!(vec!["3", "4"], tail.lines());
+}
+
+#[test]
+fn test_phase_timings() {
+    let mut timings = PhaseTimings::new();
+    timings.record("advance", Duration::from_millis(1));
+    timings.record("output", Duration::from_millis(4));
+    timings.record("advance", Duration::from_millis(3));
+
+    assert_eq!("advance: 2 calls, mean 2.000 ms, max 3.000 ms\n\
+                output: 1 calls, mean 4.000 ms, max 4.000 ms\n",
+               timings.to_string());
+}
+
+#[test]
+fn test_perf_report() {
+    let mut timings = PhaseTimings::new();
+    timings.record("advance", Duration::from_millis(1));
+    timings.record("advance", Duration::new(0, 3000));
+
+    let report = timings.report(2);
+    assert_eq!(2, report.ticks);
+    assert_eq!(None, report.allocations);
+    assert_eq!(Some(&PhaseReport { name: "advance", calls: 2, total_ns: 1003000, max_ns: 1000000 }),
+               report.phase("advance"));
+    assert_eq!(501500, report.phase("advance").unwrap().mean_ns());
+    assert!(report.phase("output").is_none());
+}
+
+#[test]
+fn test_write_bundle() {
+    use std::env;
+    use std::io::Read;
+    use board::Board;
+
+    let dir = env::temp_dir().join("cell-test-crash-bundle");
+    let dir = dir.to_str().unwrap();
+
+    let mut crash = Crash::new("invariant violated".to_string());
+    crash.snapshot = Some(BoardView::new(Board::new(2, 1, true), 7));
+    crash.write_bundle(dir, "seed: 3").unwrap();
+
+    let snapshot = Image::load(&format!("{}/snapshot.ppm", dir)).unwrap();
+    assert_eq!((4, 2), (snapshot.width, snapshot.height));
+    let mut config = String::new();
+    File::open(&format!("{}/config.txt", dir)).unwrap().read_to_string(&mut config).unwrap();
+    assert_eq!("seed: 3\n", config);
+
+    let _ = fs::remove_dir_all(dir);
+}
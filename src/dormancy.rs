@@ -0,0 +1,23 @@
+/// Dormancy of GoodEvil specimens low on energy: a dormant specimen doesn't
+/// move and loses energy much slower, until food or an active specimen
+/// shows up next to it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DormancyConfig {
+    /// Energy below which a specimen with nothing to feed on around it goes
+    /// dormant.
+    pub threshold: f32,
+    /// Fraction of the usual energy loss per step paid while dormant.
+    pub loss_factor: f32,
+    /// Amount of food on a neighboring cell that wakes a specimen up.
+    pub wake_food: f32
+}
+
+impl Default for DormancyConfig {
+    fn default() -> DormancyConfig {
+        DormancyConfig {
+            threshold: 0.3,
+            loss_factor: 0.1,
+            wake_food: 0.5
+        }
+    }
+}
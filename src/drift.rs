@@ -0,0 +1,251 @@
+use std::fs::File;
+use std::io::Read;
+
+use rand::{Rng, StdRng};
+
+use board::Board;
+use movement::{exp_weights, pick_weighted};
+
+/// Per-cell drift vectors (wind or current) biasing the direction in which
+/// specimens move.
+#[derive(Clone)]
+pub struct DriftField {
+    vectors: Board<(f32, f32)>
+}
+
+/// Where the drift field comes from, as given on the command line.
+#[derive(Clone)]
+pub enum DriftSource {
+    Uniform((f32, f32)),
+    /// Smooth random field with vectors of given length.
+    Noise(f32),
+    Field(DriftField)
+}
+
+impl DriftSource {
+    pub fn build(&self,
+                 width: usize,
+                 height: usize,
+                 rng: &mut StdRng) -> DriftField {
+        match *self {
+            DriftSource::Uniform(drift) => DriftField::uniform(width, height, drift),
+            DriftSource::Noise(strength) => DriftField::from_noise(width, height, strength, rng),
+            DriftSource::Field(ref field) => field.clone()
+        }
+    }
+}
+
+/// Distance between random vectors interpolated by DriftField::from_noise.
+const NOISE_CELL_SIZE: usize = 8;
+
+impl DriftField {
+    pub fn uniform(width: usize,
+                   height: usize,
+                   drift: (f32, f32)) -> DriftField {
+        DriftField {
+            vectors: Board::new(width, height, drift)
+        }
+    }
+
+    /// Interpolates random vectors placed on a coarse grid, so that nearby
+    /// cells drift in similar directions.
+    pub fn from_noise(width: usize,
+                      height: usize,
+                      strength: f32,
+                      rng: &mut StdRng) -> DriftField {
+        use std::f32::consts::PI;
+
+        let grid_width = width / NOISE_CELL_SIZE + 2;
+        let grid_height = height / NOISE_CELL_SIZE + 2;
+        let mut grid = Board::new(grid_width, grid_height, (0.0, 0.0));
+        for (x, y) in grid.indices() {
+            let angle = rng.gen::<f32>() * 2.0 * PI;
+            *grid.at_mut(x, y) = (angle.cos(), angle.sin());
+        }
+
+        let mut vectors = Board::new(width, height, (0.0, 0.0));
+        for (x, y) in vectors.indices() {
+            let fx = x as f32 / NOISE_CELL_SIZE as f32;
+            let fy = y as f32 / NOISE_CELL_SIZE as f32;
+            let (gx, gy) = (fx as usize, fy as usize);
+            let (tx, ty) = (fx - gx as f32, fy - gy as f32);
+
+            let lerp = |a: (f32, f32), b: (f32, f32), t: f32| {
+                (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+            };
+            let top = lerp(*grid.at(gx, gy), *grid.at(gx + 1, gy), tx);
+            let bottom = lerp(*grid.at(gx, gy + 1), *grid.at(gx + 1, gy + 1), tx);
+            let (dx, dy) = lerp(top, bottom, ty);
+
+            let len = (dx * dx + dy * dy).sqrt();
+            *vectors.at_mut(x, y) = if len > 0.0 {
+                (dx / len * strength, dy / len * strength)
+            } else {
+                (0.0, 0.0)
+            };
+        }
+
+        DriftField {
+            vectors: vectors
+        }
+    }
+
+    /// Parses a field with one line per board row, each containing
+    /// whitespace-separated `DX,DY` vectors. Empty lines and lines starting
+    /// with '#' are ignored.
+    pub fn parse(contents: &str,
+                 width: usize,
+                 height: usize) -> Result<DriftField, String> {
+        let mut vectors = Board::new(width, height, (0.0, 0.0));
+        let rows: Vec<&str> = contents.lines()
+                                      .map(|line| line.trim())
+                                      .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                                      .collect();
+        if rows.len() != height {
+            return Err(format!("expected {} rows, got {}", height, rows.len()));
+        }
+
+        for (y, row) in rows.iter().enumerate() {
+            let cells: Vec<&str> = row.split_whitespace().collect();
+            if cells.len() != width {
+                return Err(format!("row {}: expected {} vectors, got {}", y + 1, width, cells.len()));
+            }
+
+            for (x, cell) in cells.iter().enumerate() {
+                *vectors.at_mut(x, y) = try!(parse_vector(cell).ok_or_else(|| {
+                    format!("row {}: invalid vector: {}", y + 1, cell)
+                }));
+            }
+        }
+
+        Ok(DriftField {
+            vectors: vectors
+        })
+    }
+
+    pub fn load(path: &str,
+                width: usize,
+                height: usize) -> Result<DriftField, String> {
+        let mut contents = String::new();
+        try!(File::open(path).and_then(|mut f| f.read_to_string(&mut contents))
+                             .map_err(|e| format!("cannot read {}: {}", path, e)));
+
+        DriftField::parse(&contents, width, height).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn at(&self,
+              x: usize,
+              y: usize) -> (f32, f32) {
+        *self.vectors.at(x, y)
+    }
+
+    /// Exponent of the weight of moving by `offset` from (x, y),
+    /// drift . offset, see `movement::exp_weights`.
+    pub fn offset_exponent(&self,
+                           x: usize,
+                           y: usize,
+                           offset: (i64, i64)) -> f32 {
+        let (dx, dy) = self.at(x, y);
+        dx * offset.0 as f32 + dy * offset.1 as f32
+    }
+
+    /// Picks one of `offsets` for a specimen at (x, y). Each offset is
+    /// weighted with exp(`offset_exponent`), so with no drift all of them
+    /// are equally likely.
+    pub fn pick_offset(&self,
+                       x: usize,
+                       y: usize,
+                       offsets: &[(i64, i64)],
+                       rng: &mut StdRng) -> (i64, i64) {
+        let exponents: Vec<f32> = offsets.iter()
+                                         .map(|&offset| self.offset_exponent(x, y, offset))
+                                         .collect();
+        pick_weighted(offsets, &exp_weights(&exponents), rng)
+    }
+
+    /// Traces streamlines starting in every `spacing`-th cell, following
+    /// the field for at most `steps` half-cell steps. Points are in board
+    /// coordinates.
+    pub fn streamlines(&self,
+                       spacing: usize,
+                       steps: usize) -> Vec<Vec<(f64, f64)>> {
+        let (width, height) = (self.vectors.width as f64, self.vectors.height as f64);
+        let mut lines = Vec::new();
+
+        for start_y in (0..self.vectors.height).filter(|y| y % spacing == spacing / 2) {
+            for start_x in (0..self.vectors.width).filter(|x| x % spacing == spacing / 2) {
+                let mut point = (start_x as f64 + 0.5, start_y as f64 + 0.5);
+                let mut line = vec![point];
+
+                for _ in 0..steps {
+                    let (dx, dy) = self.at(point.0 as usize, point.1 as usize);
+                    let len = ((dx * dx + dy * dy) as f64).sqrt();
+                    if len < 1e-6 {
+                        break;
+                    }
+
+                    point = (point.0 + dx as f64 / len * 0.5, point.1 + dy as f64 / len * 0.5);
+                    if point.0 < 0.0 || point.1 < 0.0 || point.0 >= width || point.1 >= height {
+                        break;
+                    }
+                    line.push(point);
+                }
+
+                if line.len() > 1 {
+                    lines.push(line);
+                }
+            }
+        }
+
+        lines
+    }
+}
+
+/// Parses a `DX,DY` pair of finite numbers.
+pub fn parse_vector(string: &str) -> Option<(f32, f32)> {
+    let parts: Vec<&str> = string.split(',').collect();
+    if parts.len() != 2 {
+        return None;
+    }
+
+    match (parts[0].trim().parse::<f32>(), parts[1].trim().parse::<f32>()) {
+        (Ok(dx), Ok(dy)) if dx.is_finite() && dy.is_finite() => Some((dx, dy)),
+        _ => None
+    }
+}
+
+#[test]
+fn test_drift_parse() {
+    let field = DriftField::parse("# wind\n1,0 0,1\n\n-1,0 0.5,-0.5\n", 2, 2).unwrap();
+    assert_eq!((0.5, -0.5), field.at(1, 1));
+
+    assert!(DriftField::parse("1,0 0,1\n", 2, 2).is_err());
+    assert!(DriftField::parse("1,0\n0,1\n", 2, 2).is_err());
+    assert!(DriftField::parse("1,0 x\n1,0 0,0\n", 2, 2).is_err());
+}
+
+#[test]
+fn test_drift_biases_movement() {
+    use rand::SeedableRng;
+
+    let mut rng = StdRng::from_seed(&[0usize][..]);
+    let field = DriftField::uniform(3, 3, (3.0, 0.0));
+    let offsets = [(-1, 0), (0, 0), (1, 0)];
+
+    let right = (0..1000).filter(|_| field.pick_offset(1, 1, &offsets, &mut rng) == (1, 0)).count();
+    assert!(right > 900);
+
+    let gale = DriftField::uniform(3, 3, (1000.0, 0.0));
+    assert!((0..100).all(|_| gale.pick_offset(1, 1, &offsets, &mut rng) == (1, 0)));
+    assert!(parse_vector("inf,0").is_none());
+}
+
+#[test]
+fn test_drift_streamlines() {
+    let field = DriftField::uniform(8, 8, (1.0, 0.0));
+    let lines = field.streamlines(4, 100);
+
+    assert_eq!(4, lines.len());
+    assert_eq!((2.5, 2.5), lines[0][0]);
+    assert!(lines[0].iter().all(|&(x, y)| y == 2.5 && x < 8.0));
+}
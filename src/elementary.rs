@@ -0,0 +1,127 @@
+use board::Board;
+use simulation::Simulation;
+use stats::Stats;
+
+#[derive(Clone, Copy, Debug)]
+pub struct ElementaryConfig {
+    /// Wolfram code of the rule: bit N tells the new state of a cell whose
+    /// (left, self, right) neighborhood read as a binary number equals N.
+    pub rule: u8
+}
+
+impl Default for ElementaryConfig {
+    fn default() -> ElementaryConfig {
+        ElementaryConfig {
+            rule: 30
+        }
+    }
+}
+
+/// Elementary (1D, two-state, radius 1) cellular automaton. Each board row
+/// holds one generation: the newest one is at the bottom and older ones
+/// scroll upwards.
+#[derive(Clone)]
+pub struct Elementary {
+    pub cfg: ElementaryConfig,
+    board: Board<bool>,
+    iteration: usize
+}
+
+impl Elementary {
+    /// Starts from a single live cell in the middle of the bottom row.
+    pub fn new(width: usize,
+               height: usize,
+               cfg: ElementaryConfig) -> Elementary {
+        let mut row = vec![false; width];
+        row[width / 2] = true;
+
+        Elementary::from_row(height, cfg, &row)
+    }
+
+    /// Starts from given bottom row, as wide as the board.
+    pub fn from_row(height: usize,
+                    cfg: ElementaryConfig,
+                    row: &[bool]) -> Elementary {
+        let mut board = Board::new(row.len(), height, false);
+        board.row_mut(height - 1).copy_from_slice(row);
+
+        Elementary {
+            cfg: cfg,
+            board: board,
+            iteration: 0
+        }
+    }
+
+    /// Computes the next generation of `row`, wrapping around its ends.
+    pub fn next_row(rule: u8,
+                    row: &[bool]) -> Vec<bool> {
+        let len = row.len();
+
+        (0..len).map(|x| {
+                    let left = row[(x + len - 1) % len] as u8;
+                    let center = row[x] as u8;
+                    let right = row[(x + 1) % len] as u8;
+
+                    rule & (1 << (left << 2 | center << 1 | right)) != 0
+                })
+                .collect()
+    }
+
+    fn newest_row(&self) -> &[bool] {
+        self.board.row(self.board.height - 1)
+    }
+}
+
+impl Simulation<bool> for Elementary {
+    fn advance(&mut self) {
+        let next = Elementary::next_row(self.cfg.rule, self.newest_row());
+        let (width, height) = (self.board.width, self.board.height);
+
+        // scroll everything up a row, the oldest one ending up at the bottom
+        // to be overwritten
+        self.board.as_mut_slice().rotate_left(width);
+        self.board.row_mut(height - 1).copy_from_slice(&next);
+
+        self.iteration += 1;
+    }
+
+    fn board(&self) -> &Board<bool> {
+        &self.board
+    }
+
+    fn stats(&self) -> Stats {
+        let alive = self.newest_row().iter().filter(|&&alive| alive).count();
+        Stats::new(self.iteration).count("alive", alive)
+    }
+
+    /// An empty generation stays empty unless the rule turns 000 into 1.
+    fn is_extinct(&self) -> bool {
+        self.cfg.rule & 1 == 0 && !self.newest_row().iter().any(|&alive| alive)
+    }
+}
+
+#[cfg(test)]
+fn parse_row(row: &str) -> Vec<bool> {
+    row.chars().map(|c| c == '#').collect()
+}
+
+#[test]
+fn test_elementary_rule_30() {
+    assert_eq!(parse_row("..###.."), Elementary::next_row(30, &parse_row("...#...")));
+    assert_eq!(parse_row(".##..#."), Elementary::next_row(30, &parse_row("..###..")));
+}
+
+#[test]
+fn test_elementary_rule_110_wraps() {
+    assert_eq!(parse_row("#...#"), Elementary::next_row(110, &parse_row("#....")));
+}
+
+#[test]
+fn test_elementary_scrolls_up() {
+    let mut sim = Elementary::new(5, 3, ElementaryConfig { rule: 90 });
+    sim.advance();
+
+    assert!(*sim.board().at(2, 1));
+    assert!(*sim.board().at(1, 2) && !*sim.board().at(2, 2) && *sim.board().at(3, 2));
+    assert_eq!(Some(::stats::Value::Count(2)), sim.stats().get("alive"));
+}
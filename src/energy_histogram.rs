@@ -0,0 +1,83 @@
+//! Distribution of specimen energies among buckets of equal width, drawn as
+//! bars in the window and written to the stats log, to compare e.g. split
+//! strategies by the energies they leave specimens with.
+
+use plot::{PLOT_HEIGHT, PLOT_MARGIN};
+use simulation::Specimen;
+
+/// Buckets of the histogram unless set otherwise.
+pub const DEFAULT_BUCKETS: usize = 16;
+/// Size of the bars' area, in pixels.
+const HISTOGRAM_SIZE: [f64; 2] = [240.0, 120.0];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnergyHistogram {
+    /// Highest energy, the upper bound of the last bucket. Buckets span
+    /// from 0 to `max`; specimens with no energy left count into the first.
+    pub max: f32,
+    pub counts: Vec<usize>
+}
+
+impl EnergyHistogram {
+    /// Histogram of `energies` with `buckets` buckets, at least one.
+    pub fn of(energies: &[f32],
+              buckets: usize) -> EnergyHistogram {
+        let buckets = ::std::cmp::max(buckets, 1);
+        let max = energies.iter().fold(0.0f32, |max, &energy| max.max(energy));
+        let mut counts = vec![0; buckets];
+        for &energy in energies.iter() {
+            let bucket = if max > 0.0 { (energy.max(0.0) / max * buckets as f32) as usize } else { 0 };
+            counts[::std::cmp::min(bucket, buckets - 1)] += 1;
+        }
+
+        EnergyHistogram {
+            max: max,
+            counts: counts
+        }
+    }
+
+    pub fn of_specimens(specimens: &[Specimen],
+                        buckets: usize) -> EnergyHistogram {
+        let energies: Vec<f32> = specimens.iter().map(|specimen| specimen.energy).collect();
+        EnergyHistogram::of(&energies, buckets)
+    }
+
+    /// Where the bars are drawn, as [x, y, width, height]: in the bottom
+    /// right corner of the window, above the plot.
+    pub fn rect(viewport_size: [f64; 2]) -> [f64; 4] {
+        [viewport_size[0] - PLOT_MARGIN - HISTOGRAM_SIZE[0],
+         viewport_size[1] - 2.0 * PLOT_MARGIN - PLOT_HEIGHT - HISTOGRAM_SIZE[1],
+         HISTOGRAM_SIZE[0],
+         HISTOGRAM_SIZE[1]]
+    }
+
+    /// Bars within `rect`, as [x, y, width, height], the tallest one
+    /// filling it.
+    pub fn bars(&self,
+                rect: [f64; 4]) -> Vec<[f64; 4]> {
+        let tallest = self.counts.iter().cloned().max().unwrap_or(0);
+        let width = rect[2] / self.counts.len() as f64;
+        self.counts.iter()
+                   .enumerate()
+                   .map(|(idx, &count)| {
+                       let height = if tallest > 0 { count as f64 / tallest as f64 * rect[3] } else { 0.0 };
+                       [rect[0] + idx as f64 * width, rect[1] + rect[3] - height, width, height]
+                   })
+                   .collect()
+    }
+}
+
+#[test]
+fn test_energy_histogram() {
+    let histogram = EnergyHistogram::of(&[-0.5, 0.1, 0.9, 0.95, 1.0, 4.0], 4);
+    assert_eq!(4.0, histogram.max);
+    assert_eq!(vec![4, 1, 0, 1], histogram.counts);
+
+    assert_eq!(vec![[0.0, 0.0, 10.0, 40.0], [10.0, 30.0, 10.0, 10.0], [20.0, 40.0, 10.0, 0.0], [30.0, 30.0, 10.0, 10.0]],
+               histogram.bars([0.0, 0.0, 40.0, 40.0]));
+    assert_eq!([552.0, 144.0, 240.0, 120.0], EnergyHistogram::rect([800.0, 400.0]));
+
+    let empty = EnergyHistogram::of(&[], 0);
+    assert_eq!(vec![0], empty.counts);
+    assert_eq!(vec![[0.0, 40.0, 40.0, 0.0]], empty.bars([0.0, 0.0, 40.0, 40.0]));
+}
@@ -0,0 +1,236 @@
+use board::Board;
+use rand::{Rng, StdRng};
+use simulation::{Simulation, torus_neighbors};
+use stats::Stats;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Health {
+    Susceptible,
+    /// Infected, but not contagious yet. Holds the number of steps left
+    /// until the specimen becomes contagious.
+    Exposed(u32),
+    /// Holds the number of steps left until recovery.
+    Infected(u32),
+    Recovered
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct EpidemicConfig {
+    pub initial_infected: usize,
+    /// Probability of getting infected by a single infected neighbor
+    /// during one step.
+    pub transmission_probability: f32,
+    /// Number of steps spent in the Exposed state. If not set, newly
+    /// infected specimens become contagious immediately (SIR model).
+    pub incubation_period: Option<u32>,
+    pub infection_duration: u32,
+    /// Probability of a recovered specimen becoming susceptible again
+    /// during one step.
+    pub immunity_loss_probability: f32
+}
+
+impl Default for EpidemicConfig {
+    fn default() -> EpidemicConfig {
+        EpidemicConfig {
+            initial_infected: 5,
+            transmission_probability: 0.2,
+            incubation_period: None,
+            infection_duration: 10,
+            immunity_loss_probability: 0.0
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Epidemic {
+    pub cfg: EpidemicConfig,
+    rng: Box<StdRng>,
+    board: Board<Health>,
+    iteration: usize
+}
+
+impl Epidemic {
+    pub fn new(width: usize,
+               height: usize,
+               cfg: EpidemicConfig,
+               mut rng: Box<StdRng>) -> Epidemic {
+        assert!(cfg.initial_infected <= width * height);
+
+        let mut board = Board::new(width, height, Health::Susceptible);
+        let mut infected = 0;
+
+        while infected < cfg.initial_infected {
+            let x = rng.gen_range(0, width);
+            let y = rng.gen_range(0, height);
+
+            if *board.at(x, y) == Health::Susceptible {
+                *board.at_mut(x, y) = Health::Infected(cfg.infection_duration);
+                infected += 1;
+            }
+        }
+
+        Epidemic::from_board(board, cfg, rng)
+    }
+
+    pub fn from_board(board: Board<Health>,
+                      cfg: EpidemicConfig,
+                      rng: Box<StdRng>) -> Epidemic {
+        Epidemic {
+            cfg: cfg,
+            rng: rng,
+            board: board,
+            iteration: 0
+        }
+    }
+
+    fn count_infected_neighbors(board: &Board<Health>,
+                                x: usize,
+                                y: usize) -> i32 {
+        torus_neighbors(x, y, board.width, board.height)
+            .filter(|&(nbr_x, nbr_y)| match *board.at(nbr_x, nbr_y) {
+                        Health::Infected(_) => true,
+                        _ => false
+                    })
+            .count() as i32
+    }
+
+    fn infect(cfg: &EpidemicConfig) -> Health {
+        match cfg.incubation_period {
+            Some(period) if period > 0 => Health::Exposed(period),
+            _ => Health::Infected(cfg.infection_duration)
+        }
+    }
+
+    fn next_state(&mut self,
+                  x: usize,
+                  y: usize) -> Health {
+        match *self.board.at(x, y) {
+            Health::Susceptible => {
+                let infected_nbrs = Epidemic::count_infected_neighbors(&self.board, x, y);
+                let escape_probability = (1.0 - self.cfg.transmission_probability)
+                                             .powi(infected_nbrs);
+
+                if infected_nbrs > 0 && self.rng.gen::<f32>() >= escape_probability {
+                    Epidemic::infect(&self.cfg)
+                } else {
+                    Health::Susceptible
+                }
+            },
+            Health::Exposed(steps) if steps > 1 => Health::Exposed(steps - 1),
+            Health::Exposed(_) => Health::Infected(self.cfg.infection_duration),
+            Health::Infected(steps) if steps > 1 => Health::Infected(steps - 1),
+            Health::Infected(_) => Health::Recovered,
+            Health::Recovered => {
+                if self.rng.gen::<f32>() < self.cfg.immunity_loss_probability {
+                    Health::Susceptible
+                } else {
+                    Health::Recovered
+                }
+            }
+        }
+    }
+
+    /// Returns (susceptible, exposed, infected, recovered) counts.
+    pub fn count_states(board: &Board<Health>) -> (usize, usize, usize, usize) {
+        board.iter().fold((0, 0, 0, 0), |(s, e, i, r), health| match *health {
+            Health::Susceptible => (s + 1, e, i, r),
+            Health::Exposed(_) => (s, e + 1, i, r),
+            Health::Infected(_) => (s, e, i + 1, r),
+            Health::Recovered => (s, e, i, r + 1)
+        })
+    }
+
+}
+
+impl Simulation<Health> for Epidemic {
+    fn advance(&mut self) {
+        let mut new = Board::new(self.board.width, self.board.height, Health::Susceptible);
+
+        for (x, y) in self.board.indices() {
+            *new.at_mut(x, y) = self.next_state(x, y);
+        }
+
+        self.board = new;
+        self.iteration += 1;
+    }
+
+    fn board(&self) -> &Board<Health> {
+        &self.board
+    }
+
+    fn stats(&self) -> Stats {
+        let (susceptible, exposed, infected, recovered) = Epidemic::count_states(&self.board);
+
+        Stats::new(self.iteration).count("susceptible", susceptible)
+                                  .count("exposed", exposed)
+                                  .count("infected", infected)
+                                  .count("recovered", recovered)
+    }
+
+    /// The epidemic is over once nobody is exposed or infected.
+    fn is_extinct(&self) -> bool {
+        !self.board.iter().any(|health| match *health {
+            Health::Exposed(_) | Health::Infected(_) => true,
+            _ => false
+        })
+    }
+}
+
+#[cfg(test)]
+fn test_epidemic(cfg: EpidemicConfig) -> Epidemic {
+    use rand::SeedableRng;
+
+    let mut board = Board::new(5, 5, Health::Susceptible);
+    *board.at_mut(2, 2) = Health::Infected(cfg.infection_duration);
+
+    Epidemic::from_board(board, cfg, Box::new(StdRng::from_seed(&[0usize][..])))
+}
+
+#[test]
+fn test_epidemic_certain_transmission() {
+    let mut sim = test_epidemic(EpidemicConfig {
+        transmission_probability: 1.0,
+        infection_duration: 2,
+        .. EpidemicConfig::default()
+    });
+
+    sim.advance();
+    assert_eq!((16, 0, 9, 0), Epidemic::count_states(sim.board()));
+    assert_eq!(Health::Infected(1), *sim.board().at(2, 2));
+    assert_eq!(Health::Infected(2), *sim.board().at(1, 1));
+
+    sim.advance();
+    assert_eq!(Health::Recovered, *sim.board().at(2, 2));
+}
+
+#[test]
+fn test_epidemic_incubation() {
+    let mut sim = test_epidemic(EpidemicConfig {
+        transmission_probability: 1.0,
+        incubation_period: Some(2),
+        infection_duration: 5,
+        .. EpidemicConfig::default()
+    });
+
+    sim.advance();
+    assert_eq!(Health::Exposed(2), *sim.board().at(2, 1));
+    sim.advance();
+    assert_eq!(Health::Exposed(1), *sim.board().at(2, 1));
+    sim.advance();
+    assert_eq!(Health::Infected(5), *sim.board().at(2, 1));
+}
+
+#[test]
+fn test_epidemic_immunity_loss() {
+    let mut sim = test_epidemic(EpidemicConfig {
+        transmission_probability: 0.0,
+        infection_duration: 1,
+        immunity_loss_probability: 1.0,
+        .. EpidemicConfig::default()
+    });
+
+    sim.advance();
+    assert_eq!(Health::Recovered, *sim.board().at(2, 2));
+    sim.advance();
+    assert_eq!((25, 0, 0, 0), Epidemic::count_states(sim.board()));
+}
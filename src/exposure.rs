@@ -0,0 +1,131 @@
+//! Long-exposure summaries of runs: a single image per window of steps,
+//! with every cell colored by how often it was occupied during the window.
+//! A handful of such images sums up a whole run, e.g. for a report.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+
+use board::Board;
+use offscreen::Image;
+use palette::{BLACK, CellColor};
+use simulation::Simulation;
+
+/// Counts steps each cell was occupied, and after every `window` of them
+/// writes the frequencies as `exposure-00000.ppm`, `exposure-00001.ppm`...
+/// using the black - blue - cyan - white gradient. Cells drawn in anything
+/// but black, e.g. specimens, walls or live cells, count as occupied.
+pub struct Exposure {
+    dir: PathBuf,
+    window: usize,
+    /// Pixels per cell side.
+    cell_size: usize,
+    /// Steps each cell was occupied during the current window.
+    occupied: Option<Board<usize>>,
+    steps: usize,
+    images: usize
+}
+
+impl Exposure {
+    pub fn new(dir: &str,
+               window: usize,
+               cell_size: usize) -> Exposure {
+        assert!(window >= 1 && cell_size >= 1);
+
+        Exposure {
+            dir: PathBuf::from(dir),
+            window: window,
+            cell_size: cell_size,
+            occupied: None,
+            steps: 0,
+            images: 0
+        }
+    }
+
+    /// Exposes the board after the next step, writing an image if that
+    /// completes a window.
+    pub fn record<T, S>(&mut self,
+                        simulation: &S) -> Result<(), String> where S: Simulation<T>, T: CellColor {
+        let board = simulation.board();
+        if self.occupied.is_none() {
+            self.occupied = Some(Board::new(board.width, board.height, 0));
+        }
+        let occupied = self.occupied.as_mut().unwrap();
+        for (x, y) in board.indices() {
+            if board.at(x, y).color() != BLACK {
+                *occupied.at_mut(x, y) += 1;
+            }
+        }
+
+        self.steps += 1;
+        if self.steps == self.window {
+            self.write_image()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Writes an image of the last, incomplete window, if it has any steps.
+    pub fn finish(&mut self) -> Result<(), String> {
+        if self.steps > 0 {
+            self.write_image()
+        } else {
+            Ok(())
+        }
+    }
+
+    fn write_image(&mut self) -> Result<(), String> {
+        let occupied = match self.occupied.take() {
+            Some(occupied) => occupied,
+            None => return Ok(())
+        };
+        let mut frequency = Board::new(occupied.width, occupied.height, 0.0f32);
+        for (x, y) in occupied.indices() {
+            *frequency.at_mut(x, y) = *occupied.at(x, y) as f32 / self.steps as f32;
+        }
+        self.steps = 0;
+
+        let ppm = Image::render_board(&frequency, None, self.cell_size).to_ppm();
+        try!(fs::create_dir_all(&self.dir).map_err(|e| format!("{}: {}", self.dir.display(), e)));
+        let path = self.dir.join(format!("exposure-{:05}.ppm", self.images));
+        try!(File::create(&path).and_then(|mut f| f.write_all(ppm.as_bytes()))
+                                .map_err(|e| format!("{}: {}", path.display(), e)));
+        self.images += 1;
+        Ok(())
+    }
+
+    pub fn images(&self) -> usize {
+        self.images
+    }
+}
+
+#[test]
+fn test_exposure_writes_occupancy_frequency() {
+    use std::env;
+    use std::io::Read;
+    use simulation::GameOfLife;
+    use demo::board_from_pattern;
+
+    let dir = env::temp_dir().join("cell-test-exposure");
+    // a blinker: the middle cell is always alive, the others half the time
+    let mut simulation = GameOfLife::new(board_from_pattern((5, 5), &["OOO"], (1, 2), true, false));
+    let mut exposure = Exposure::new(dir.to_str().unwrap(), 4, 1);
+
+    for _ in 0..5 {
+        simulation.advance();
+        exposure.record(&simulation).unwrap();
+    }
+    assert_eq!(1, exposure.images());
+    exposure.finish().unwrap();
+    assert_eq!(2, exposure.images());
+
+    let mut contents = String::new();
+    File::open(dir.join("exposure-00000.ppm")).unwrap().read_to_string(&mut contents).unwrap();
+    let image = Image::from_ppm(&contents).unwrap();
+    assert_eq!(Image::render_board(&Board::new(1, 1, 1.0f32), None, 1).pixel(0, 0), image.pixel(2, 2));
+    assert_eq!(Image::render_board(&Board::new(1, 1, 0.5f32), None, 1).pixel(0, 0), image.pixel(1, 2));
+    assert_eq!(Image::render_board(&Board::new(1, 1, 0.5f32), None, 1).pixel(0, 0), image.pixel(2, 1));
+    assert_eq!([0, 0, 0], image.pixel(0, 0));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,158 @@
+//! Measuring whether the order in which specimens are updated gives some of
+//! them an edge in conflicts over cells.
+
+use std::cmp::min;
+use std::fmt;
+
+use simulation::Specimen;
+
+/// Arrival ranks above this one are reported together.
+const MAX_RANK: usize = 4;
+
+/// Order in which GoodEvil specimens move during a step. Specimens moving
+/// earlier get into piles first.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UpdateOrder {
+    /// Row by row, from the top left corner.
+    RowMajor,
+    /// Random, drawn anew every step.
+    Shuffled
+}
+
+impl UpdateOrder {
+    pub fn from_name(name: &str) -> Option<UpdateOrder> {
+        match name {
+            "row-major" => Some(UpdateOrder::RowMajor),
+            "shuffled" => Some(UpdateOrder::Shuffled),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            UpdateOrder::RowMajor => "row-major",
+            UpdateOrder::Shuffled => "shuffled"
+        }
+    }
+}
+
+/// Wins and conflicts taken part in, counted per board region the
+/// contestants came from and per their order of arrival. With fair ordering,
+/// win rates of all regions and ranks should be about the same.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FairnessAudit {
+    board_size: (usize, usize),
+    /// Number of regions along each axis.
+    regions: usize,
+    conflicts: usize,
+    region_contests: Vec<usize>,
+    region_wins: Vec<usize>,
+    rank_contests: [usize; MAX_RANK],
+    rank_wins: [usize; MAX_RANK]
+}
+
+impl FairnessAudit {
+    /// Splits the board into `regions` x `regions` rectangles.
+    pub fn new(board_size: (usize, usize),
+               regions: usize) -> FairnessAudit {
+        assert!(regions >= 1);
+
+        FairnessAudit {
+            board_size: board_size,
+            regions: regions,
+            conflicts: 0,
+            region_contests: vec![0; regions * regions],
+            region_wins: vec![0; regions * regions],
+            rank_contests: [0; MAX_RANK],
+            rank_wins: [0; MAX_RANK]
+        }
+    }
+
+    fn region_of(&self,
+                 x: usize,
+                 y: usize) -> usize {
+        let column = x * self.regions / self.board_size.0;
+        let row = y * self.regions / self.board_size.1;
+        row * self.regions + column
+    }
+
+    /// Records a conflict between `contestants` coming from `origins`,
+    /// both in order of arrival, won by the one at index `winner`.
+    pub fn record(&mut self,
+                  contestants: &[Specimen],
+                  origins: &[(usize, usize)],
+                  winner: usize) {
+        assert_eq!(contestants.len(), origins.len());
+
+        self.conflicts += 1;
+        for (rank, &(x, y)) in origins.iter().enumerate() {
+            let region = self.region_of(x, y);
+            self.region_contests[region] += 1;
+            self.rank_contests[min(rank, MAX_RANK - 1)] += 1;
+        }
+
+        let (x, y) = origins[winner];
+        let region = self.region_of(x, y);
+        self.region_wins[region] += 1;
+        self.rank_wins[min(winner, MAX_RANK - 1)] += 1;
+    }
+
+    /// Win rates of every region, row by row. Regions that took part in no
+    /// conflict have None.
+    pub fn region_win_rates(&self) -> Vec<Option<f32>> {
+        win_rates(&self.region_wins, &self.region_contests)
+    }
+
+    /// Win rates by order of arrival, the last one covering all later
+    /// arrivals too.
+    pub fn rank_win_rates(&self) -> Vec<Option<f32>> {
+        win_rates(&self.rank_wins, &self.rank_contests)
+    }
+}
+
+fn win_rates(wins: &[usize],
+             contests: &[usize]) -> Vec<Option<f32>> {
+    wins.iter()
+        .zip(contests.iter())
+        .map(|(&wins, &contests)| if contests > 0 { Some(wins as f32 / contests as f32) } else { None })
+        .collect()
+}
+
+fn format_rate(rate: Option<f32>) -> String {
+    match rate {
+        Some(rate) => format!("{:5.1}%", rate * 100.0),
+        None => "    -".to_string()
+    }
+}
+
+impl fmt::Display for FairnessAudit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let contests = self.rank_contests.iter().fold(0, |sum, c| sum + c);
+        let expected = if contests > 0 { Some(self.conflicts as f32 / contests as f32) } else { None };
+        try!(writeln!(f, "fairness: {} conflicts, expected win rate {}", self.conflicts, format_rate(expected)));
+
+        try!(writeln!(f, "win rate by region:"));
+        let rates = self.region_win_rates();
+        for row in rates.chunks(self.regions) {
+            let row: Vec<String> = row.iter().map(|&rate| format_rate(rate)).collect();
+            try!(writeln!(f, "  {}", row.join(" ")));
+        }
+
+        try!(write!(f, "win rate by arrival:"));
+        for (rank, &rate) in self.rank_win_rates().iter().enumerate() {
+            let suffix = if rank == MAX_RANK - 1 { "+" } else { "" };
+            try!(write!(f, " {}{}: {}", rank + 1, suffix, format_rate(rate)));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_fairness_win_rates() {
+    let mut audit = FairnessAudit::new((4, 4), 2);
+    audit.record(&[Specimen::new(1.0), Specimen::new(1.0)], &[(0, 0), (3, 0)], 1);
+    audit.record(&[Specimen::new(2.0), Specimen::new(1.0)], &[(0, 1), (3, 3)], 0);
+
+    assert_eq!(vec![Some(0.5), Some(1.0), None, Some(0.0)], audit.region_win_rates());
+    assert_eq!(vec![Some(0.5), Some(0.5), None, None], audit.rank_win_rates());
+}
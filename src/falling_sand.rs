@@ -0,0 +1,286 @@
+use board::Board;
+use rand::{Rng, StdRng};
+use simulation::Simulation;
+use stats::Stats;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Material {
+    Empty,
+    Sand,
+    Water,
+    Wall
+}
+
+impl Material {
+    /// Maps a brush number selected in the UI to a material. Brush 0 is
+    /// the eraser.
+    pub fn from_brush(brush: usize) -> Option<Material> {
+        match brush {
+            0 => Some(Material::Empty),
+            1 => Some(Material::Sand),
+            2 => Some(Material::Water),
+            3 => Some(Material::Wall),
+            _ => None
+        }
+    }
+
+    /// Checks whether a grain of this material can move into a cell
+    /// occupied by `other`. Sand sinks in water, water only flows into
+    /// empty cells.
+    fn can_displace(&self,
+                    other: Material) -> bool {
+        match (*self, other) {
+            (Material::Sand, Material::Empty) => true,
+            (Material::Sand, Material::Water) => true,
+            (Material::Water, Material::Empty) => true,
+            _ => false
+        }
+    }
+}
+
+/// Falling-sand automaton. Unlike the other simulations the board is not a
+/// torus: cells outside of it act as walls. Cells are updated bottom-up, so
+/// that a grain falls by at most one cell per step.
+#[derive(Clone)]
+pub struct FallingSand {
+    rng: Box<StdRng>,
+    board: Board<Material>,
+    iteration: usize,
+    last_moves: usize
+}
+
+/// Returns the cell at (x + dx, y + dy), or None if it lies outside of the
+/// board.
+fn bounded_offset(board: &Board<Material>,
+                  x: usize,
+                  y: usize,
+                  dx: isize,
+                  dy: isize) -> Option<(usize, usize)> {
+    let nx = x as isize + dx;
+    let ny = y as isize + dy;
+
+    if nx < 0 || ny < 0 || nx >= board.width as isize || ny >= board.height as isize {
+        None
+    } else {
+        Some((nx as usize, ny as usize))
+    }
+}
+
+impl FallingSand {
+    /// Fills the top third of the board with random sand and water above
+    /// two wall ledges.
+    pub fn new(width: usize,
+               height: usize,
+               mut rng: Box<StdRng>) -> FallingSand {
+        let mut board = Board::new(width, height, Material::Empty);
+
+        for y in 0..height / 3 {
+            for x in 0..width {
+                let r = rng.gen::<f32>();
+                *board.at_mut(x, y) = if r < 0.25 {
+                    Material::Sand
+                } else if r < 0.4 {
+                    Material::Water
+                } else {
+                    Material::Empty
+                };
+            }
+        }
+
+        for x in width / 4..width / 2 {
+            *board.at_mut(x, height / 2) = Material::Wall;
+        }
+        for x in width / 2..width * 3 / 4 {
+            *board.at_mut(x, height * 2 / 3) = Material::Wall;
+        }
+
+        FallingSand::from_board(board, rng)
+    }
+
+    pub fn from_board(board: Board<Material>,
+                      rng: Box<StdRng>) -> FallingSand {
+        FallingSand {
+            rng: rng,
+            board: board,
+            iteration: 0,
+            // nothing is known to be settled before the first step
+            last_moves: 1
+        }
+    }
+
+    /// Directions a grain tries, in order of preference.
+    fn directions(&mut self,
+                  material: Material) -> Vec<(isize, isize)> {
+        let side = if self.rng.gen() { 1 } else { -1 };
+
+        match material {
+            Material::Sand => vec![(0, 1), (side, 1), (-side, 1)],
+            Material::Water => vec![(0, 1), (side, 1), (-side, 1), (side, 0), (-side, 0)],
+            Material::Empty | Material::Wall => Vec::new()
+        }
+    }
+
+    pub fn count_materials(board: &Board<Material>) -> (usize, usize, usize) {
+        board.iter().fold((0, 0, 0), |(sand, water, wall), material| match *material {
+            Material::Sand => (sand + 1, water, wall),
+            Material::Water => (sand, water + 1, wall),
+            Material::Wall => (sand, water, wall + 1),
+            Material::Empty => (sand, water, wall)
+        })
+    }
+}
+
+impl Simulation<Material> for FallingSand {
+    fn advance(&mut self) {
+        let (width, height) = (self.board.width, self.board.height);
+        let mut moved = Board::new(width, height, false);
+        let mut moves = 0;
+
+        // alternate the row scan direction to avoid drifting to one side
+        let left_to_right = self.iteration % 2 == 0;
+
+        for y in (0..height).rev() {
+            for i in 0..width {
+                let x = if left_to_right { i } else { width - 1 - i };
+                if *moved.at(x, y) {
+                    continue;
+                }
+
+                let material = *self.board.at(x, y);
+                for (dx, dy) in self.directions(material) {
+                    let target = match bounded_offset(&self.board, x, y, dx, dy) {
+                        Some(target) => target,
+                        None => continue
+                    };
+
+                    // a displaced grain must not be pushed any further in
+                    // the same step
+                    let other = *self.board.at(target.0, target.1);
+                    if material.can_displace(other) && !*moved.at(target.0, target.1) {
+                        *self.board.at_mut(target.0, target.1) = material;
+                        *self.board.at_mut(x, y) = other;
+                        *moved.at_mut(target.0, target.1) = true;
+                        *moved.at_mut(x, y) = other != Material::Empty;
+                        moves += 1;
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.last_moves = moves;
+        self.iteration += 1;
+    }
+
+    fn board(&self) -> &Board<Material> {
+        &self.board
+    }
+
+    fn stats(&self) -> Stats {
+        let (sand, water, _) = FallingSand::count_materials(&self.board);
+
+        Stats::new(self.iteration).count("sand", sand)
+                                  .count("water", water)
+                                  .count("moves", self.last_moves)
+    }
+
+    /// Nothing will happen anymore once all grains settle.
+    fn is_extinct(&self) -> bool {
+        self.last_moves == 0
+    }
+
+    /// Paints a 3x3 square of the material selected by `brush`.
+    fn paint(&mut self,
+             x: usize,
+             y: usize,
+             brush: usize) {
+        let material = match Material::from_brush(brush) {
+            Some(material) => material,
+            None => return
+        };
+
+        for dy in -1..2 {
+            for dx in -1..2 {
+                if let Some((px, py)) = bounded_offset(&self.board, x, y, dx, dy) {
+                    *self.board.at_mut(px, py) = material;
+                }
+            }
+        }
+        self.last_moves = 1;
+    }
+}
+
+#[cfg(test)]
+fn sand_board(rows: &[&str]) -> Board<Material> {
+    let mut board = Board::new(rows[0].len(), rows.len(), Material::Empty);
+
+    for (y, row) in rows.iter().enumerate() {
+        for (x, c) in row.chars().enumerate() {
+            *board.at_mut(x, y) = match c {
+                's' => Material::Sand,
+                'w' => Material::Water,
+                '#' => Material::Wall,
+                _ => Material::Empty
+            };
+        }
+    }
+
+    board
+}
+
+#[cfg(test)]
+fn test_falling_sand(rows: &[&str]) -> FallingSand {
+    use rand::SeedableRng;
+
+    FallingSand::from_board(sand_board(rows), Box::new(StdRng::from_seed(&[0usize][..])))
+}
+
+#[test]
+fn test_sand_falls_one_cell_per_step_and_rests_on_floor() {
+    let mut sim = test_falling_sand(&[".s.",
+                                      "...",
+                                      "..."]);
+
+    sim.advance();
+    assert_eq!(Material::Sand, *sim.board().at(1, 1));
+    sim.advance();
+    assert_eq!(Material::Sand, *sim.board().at(1, 2));
+    assert!(!sim.is_extinct());
+
+    sim.advance();
+    assert_eq!(Material::Sand, *sim.board().at(1, 2));
+    assert!(sim.is_extinct());
+}
+
+#[test]
+fn test_sand_piles_up_and_sinks_in_water() {
+    let mut sim = test_falling_sand(&[".s.",
+                                      ".s.",
+                                      "#w#"]);
+
+    sim.advance();
+    assert_eq!(Material::Sand, *sim.board().at(1, 2));
+    assert_eq!(Material::Water, *sim.board().at(1, 1));
+}
+
+#[test]
+fn test_water_spreads_sideways() {
+    let mut sim = test_falling_sand(&["w..",
+                                      "###"]);
+
+    sim.advance();
+    assert_eq!(Material::Water, *sim.board().at(1, 0));
+    assert_eq!(Material::Empty, *sim.board().at(0, 0));
+}
+
+#[test]
+fn test_paint() {
+    let mut sim = test_falling_sand(&["...",
+                                      "...",
+                                      "..."]);
+
+    sim.paint(0, 0, 3);
+    assert_eq!((0, 0, 4), FallingSand::count_materials(sim.board()));
+    sim.paint(0, 0, 0);
+    assert_eq!((0, 0, 0), FallingSand::count_materials(sim.board()));
+}
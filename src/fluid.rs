@@ -0,0 +1,288 @@
+use board::Board;
+use i18n::{Language, Message};
+use simulation::Simulation;
+use stats::Stats;
+
+/// D2Q9 lattice velocities.
+const VELOCITIES: [(i64, i64); 9] = [(0, 0), (1, 0), (0, 1), (-1, 0), (0, -1),
+                                     (1, 1), (-1, 1), (-1, -1), (1, -1)];
+const WEIGHTS: [f32; 9] = [4.0 / 9.0, 1.0 / 9.0, 1.0 / 9.0, 1.0 / 9.0, 1.0 / 9.0,
+                           1.0 / 36.0, 1.0 / 36.0, 1.0 / 36.0, 1.0 / 36.0];
+/// Index of the velocity pointing the other way.
+const OPPOSITE: [usize; 9] = [0, 3, 4, 1, 2, 7, 8, 5, 6];
+
+#[derive(Clone, Copy, Debug)]
+pub struct FluidConfig {
+    /// BGK relaxation time; larger values make the fluid more viscous.
+    /// Must be above 0.5.
+    pub relaxation_time: f32,
+    /// Velocity of the fluid entering through the left edge. With 0 the
+    /// board is a closed torus.
+    pub inflow: f32
+}
+
+impl Default for FluidConfig {
+    fn default() -> FluidConfig {
+        FluidConfig {
+            relaxation_time: 0.7,
+            inflow: 0.1
+        }
+    }
+}
+
+/// Particle distribution over the nine lattice velocities.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FluidCell {
+    pub distribution: [f32; 9],
+    pub obstacle: bool
+}
+
+impl FluidCell {
+    pub fn equilibrium(density: f32,
+                       velocity: (f32, f32)) -> FluidCell {
+        let (ux, uy) = velocity;
+        let speed_sq = ux * ux + uy * uy;
+        let mut distribution = [0.0; 9];
+
+        for i in 0..9 {
+            let (ex, ey) = VELOCITIES[i];
+            let eu = ex as f32 * ux + ey as f32 * uy;
+            distribution[i] = WEIGHTS[i] * density * (1.0 + 3.0 * eu + 4.5 * eu * eu - 1.5 * speed_sq);
+        }
+
+        FluidCell {
+            distribution: distribution,
+            obstacle: false
+        }
+    }
+
+    pub fn obstacle() -> FluidCell {
+        FluidCell {
+            distribution: [0.0; 9],
+            obstacle: true
+        }
+    }
+
+    pub fn density(&self) -> f32 {
+        self.distribution.iter().fold(0.0, |sum, f| sum + f)
+    }
+
+    pub fn velocity(&self) -> (f32, f32) {
+        let density = self.density();
+        if density <= 0.0 {
+            return (0.0, 0.0);
+        }
+
+        let (mut ux, mut uy) = (0.0, 0.0);
+        for i in 0..9 {
+            ux += VELOCITIES[i].0 as f32 * self.distribution[i];
+            uy += VELOCITIES[i].1 as f32 * self.distribution[i];
+        }
+        (ux / density, uy / density)
+    }
+}
+
+/// Lattice-Boltzmann (D2Q9, BGK) fluid flowing around obstacles, which
+/// reflect particles back (bounce-back). Apart from the inflow on the left
+/// edge, the board wraps around.
+#[derive(Clone)]
+pub struct Fluid {
+    pub cfg: FluidConfig,
+    board: Board<FluidCell>,
+    iteration: usize
+}
+
+impl Fluid {
+    /// Uniform flow around a disc placed in the left part of the board.
+    pub fn new(width: usize,
+               height: usize,
+               cfg: FluidConfig) -> Fluid {
+        let mut board = Board::new(width, height, FluidCell::equilibrium(1.0, (cfg.inflow, 0.0)));
+
+        let (cx, cy, r) = (width as i64 / 4, height as i64 / 2, height as i64 / 10);
+        for (x, y) in board.indices() {
+            let (dx, dy) = (x as i64 - cx, y as i64 - cy);
+            if dx * dx + dy * dy <= r * r {
+                *board.at_mut(x, y) = FluidCell::obstacle();
+            }
+        }
+
+        Fluid::from_board(board, cfg)
+    }
+
+    pub fn from_board(board: Board<FluidCell>,
+                      cfg: FluidConfig) -> Fluid {
+        assert!(cfg.relaxation_time > 0.5);
+
+        Fluid {
+            cfg: cfg,
+            board: board,
+            iteration: 0
+        }
+    }
+
+    fn collide(&mut self) {
+        let omega = 1.0 / self.cfg.relaxation_time;
+
+        for (x, y) in self.board.indices() {
+            let cell = self.board.at_mut(x, y);
+            if cell.obstacle {
+                continue;
+            }
+
+            let eq = FluidCell::equilibrium(cell.density(), cell.velocity());
+            for i in 0..9 {
+                cell.distribution[i] += (eq.distribution[i] - cell.distribution[i]) * omega;
+            }
+        }
+    }
+
+    fn stream(&mut self) {
+        let (w, h) = (self.board.width as i64, self.board.height as i64);
+        let mut new = self.board.clone();
+        for (x, y) in self.board.indices() {
+            new.at_mut(x, y).distribution = [0.0; 9];
+        }
+
+        for (x, y) in self.board.indices() {
+            let cell = *self.board.at(x, y);
+            if cell.obstacle {
+                continue;
+            }
+
+            for i in 0..9 {
+                let (ex, ey) = VELOCITIES[i];
+                let tx = ((x as i64 + ex + w) % w) as usize;
+                let ty = ((y as i64 + ey + h) % h) as usize;
+
+                if self.board.at(tx, ty).obstacle {
+                    new.at_mut(x, y).distribution[OPPOSITE[i]] += cell.distribution[i];
+                } else {
+                    new.at_mut(tx, ty).distribution[i] += cell.distribution[i];
+                }
+            }
+        }
+
+        self.board = new;
+    }
+
+    fn apply_inflow(&mut self) {
+        if self.cfg.inflow == 0.0 {
+            return;
+        }
+
+        let inflow = FluidCell::equilibrium(1.0, (self.cfg.inflow, 0.0));
+        for y in 0..self.board.height {
+            if !self.board.at(0, y).obstacle {
+                *self.board.at_mut(0, y) = inflow;
+            }
+        }
+    }
+}
+
+impl Simulation<FluidCell> for Fluid {
+    fn advance(&mut self) {
+        self.collide();
+        self.stream();
+        self.apply_inflow();
+        self.iteration += 1;
+    }
+
+    fn board(&self) -> &Board<FluidCell> {
+        &self.board
+    }
+
+    fn stats(&self) -> Stats {
+        let (mut mass, mut max_speed, mut cells) = (0.0f32, 0.0f32, 0);
+
+        for cell in self.board.iter().filter(|cell| !cell.obstacle) {
+            let (ux, uy) = cell.velocity();
+            mass += cell.density();
+            max_speed = max_speed.max((ux * ux + uy * uy).sqrt());
+            cells += 1;
+        }
+
+        Stats::new(self.iteration).real("mass", mass)
+                                  .real("avg_density", if cells > 0 { mass / cells as f32 } else { 0.0 })
+                                  .real("max_speed", max_speed)
+    }
+
+    fn describe_cell(&self,
+                     x: usize,
+                     y: usize,
+                     lang: Language) -> Vec<String> {
+        let cell = self.board.at(x, y);
+        if cell.obstacle {
+            return vec![lang.tr(Message::CellObstacle).to_string()];
+        }
+        let (ux, uy) = cell.velocity();
+        vec![lang.format(Message::CellDensity, &[&format!("{:.3}", cell.density())]),
+             lang.format(Message::CellVelocity, &[&format!("{:.3}", ux), &format!("{:.3}", uy)])]
+    }
+
+    /// Any brush places a 3x3 obstacle, brush 0 removes it.
+    fn paint(&mut self,
+             x: usize,
+             y: usize,
+             brush: usize) {
+        let (w, h) = (self.board.width as i64, self.board.height as i64);
+
+        for dy in -1..2 {
+            for dx in -1..2 {
+                let (px, py) = (x as i64 + dx, y as i64 + dy);
+                if px < 0 || py < 0 || px >= w || py >= h {
+                    continue;
+                }
+
+                let cell = self.board.at_mut(px as usize, py as usize);
+                if brush == 0 {
+                    if cell.obstacle {
+                        *cell = FluidCell::equilibrium(1.0, (0.0, 0.0));
+                    }
+                } else {
+                    *cell = FluidCell::obstacle();
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_fluid_equilibrium_moments() {
+    let cell = FluidCell::equilibrium(1.2, (0.1, -0.05));
+    let (ux, uy) = cell.velocity();
+
+    assert!((cell.density() - 1.2).abs() < 1e-5);
+    assert!((ux - 0.1).abs() < 1e-5);
+    assert!((uy + 0.05).abs() < 1e-5);
+}
+
+#[test]
+fn test_fluid_conserves_mass_around_obstacles() {
+    let mut board = Board::new(8, 8, FluidCell::equilibrium(1.0, (0.1, 0.05)));
+    *board.at_mut(3, 3) = FluidCell::obstacle();
+    *board.at_mut(4, 3) = FluidCell::obstacle();
+
+    let cfg = FluidConfig { inflow: 0.0, .. FluidConfig::default() };
+    let mut sim = Fluid::from_board(board, cfg);
+    let mass = sim.stats().get("mass").unwrap().as_f32();
+
+    for _ in 0..20 {
+        sim.advance();
+    }
+
+    assert!((sim.stats().get("mass").unwrap().as_f32() - mass).abs() < 1e-2);
+    assert!(sim.board().at(3, 3).obstacle);
+}
+
+#[test]
+fn test_fluid_paint_obstacle() {
+    let mut sim = Fluid::from_board(Board::new(4, 4, FluidCell::equilibrium(1.0, (0.0, 0.0))),
+                                    FluidConfig::default());
+
+    sim.paint(0, 0, 1);
+    assert_eq!(4, sim.board().iter().filter(|cell| cell.obstacle).count());
+
+    sim.paint(1, 1, 0);
+    assert_eq!(0, sim.board().iter().filter(|cell| cell.obstacle).count());
+}
@@ -0,0 +1,73 @@
+use board::Board;
+use rand::{Rng, StdRng};
+
+/// Food growing on the GoodEvil board. When enabled, it is the only source
+/// of energy: energy lost by specimens is no longer recycled through
+/// collisions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FoodConfig {
+    /// Fraction of cells that start with food.
+    pub initial_density: f32,
+    /// Amount of food growing back on an empty cell each step; a cell holds
+    /// at most 1.
+    pub regrowth_rate: f32,
+    /// Energy gained from eating a full cell of food.
+    pub energy: f32
+}
+
+impl Default for FoodConfig {
+    fn default() -> FoodConfig {
+        FoodConfig {
+            initial_density: 0.5,
+            regrowth_rate: 0.01,
+            energy: 0.05
+        }
+    }
+}
+
+impl FoodConfig {
+    /// Food layer with `initial_density` of cells full and others bare.
+    pub fn initial_layer(&self,
+                         width: usize,
+                         height: usize,
+                         rng: &mut StdRng) -> Board<f32> {
+        let mut food = Board::new(width, height, 0.0);
+        for (x, y) in food.indices() {
+            if rng.gen::<f32>() < self.initial_density {
+                *food.at_mut(x, y) = 1.0;
+            }
+        }
+        food
+    }
+
+    /// Eats all food at (x, y), returning the energy gained.
+    pub fn eat(&self,
+               food: &mut Board<f32>,
+               x: usize,
+               y: usize) -> f32 {
+        let amount = *food.at(x, y);
+        *food.at_mut(x, y) = 0.0;
+        amount * self.energy
+    }
+
+    pub fn regrow(&self,
+                  food: &mut Board<f32>,
+                  x: usize,
+                  y: usize) {
+        let amount = food.at_mut(x, y);
+        *amount = (*amount + self.regrowth_rate).min(1.0);
+    }
+}
+
+#[test]
+fn test_food_eat_and_regrow() {
+    let cfg = FoodConfig { regrowth_rate: 0.6, energy: 2.0, .. FoodConfig::default() };
+    let mut food = Board::new(2, 2, 0.5);
+
+    assert_eq!(1.0, cfg.eat(&mut food, 0, 0));
+    assert_eq!(0.0, cfg.eat(&mut food, 0, 0));
+
+    cfg.regrow(&mut food, 0, 0);
+    cfg.regrow(&mut food, 0, 0);
+    assert_eq!(1.0, *food.at(0, 0));
+}
@@ -0,0 +1,165 @@
+use board::Board;
+use rand::{Rng, StdRng};
+use simulation::Simulation;
+use stats::Stats;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Forest {
+    Empty,
+    Tree,
+    Burning
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ForestFireConfig {
+    /// Fraction of cells initially covered with trees.
+    pub tree_density: f64,
+    /// Probability of a tree growing on an empty cell during one step.
+    pub growth_probability: f32,
+    /// Probability of a tree catching fire by itself during one step.
+    pub lightning_probability: f32
+}
+
+impl Default for ForestFireConfig {
+    fn default() -> ForestFireConfig {
+        ForestFireConfig {
+            tree_density: 0.65,
+            growth_probability: 0.0,
+            lightning_probability: 0.0
+        }
+    }
+}
+
+/// Forest fire model: a burning cell burns out in a single step, setting
+/// trees next to it on fire. With growth and lightning it becomes the
+/// Drossel-Schwabl model; without them a single fire front started on the
+/// left edge sweeps over the forest.
+#[derive(Clone)]
+pub struct ForestFire {
+    pub cfg: ForestFireConfig,
+    rng: Box<StdRng>,
+    board: Board<Forest>,
+    iteration: usize
+}
+
+impl ForestFire {
+    /// Random forest with the trees in the leftmost column on fire.
+    pub fn new(width: usize,
+               height: usize,
+               cfg: ForestFireConfig,
+               mut rng: Box<StdRng>) -> ForestFire {
+        let mut board = Board::new(width, height, Forest::Empty);
+        for (x, y) in board.indices() {
+            if rng.gen::<f64>() < cfg.tree_density {
+                *board.at_mut(x, y) = if x == 0 { Forest::Burning } else { Forest::Tree };
+            }
+        }
+
+        ForestFire::from_board(board, cfg, rng)
+    }
+
+    pub fn from_board(board: Board<Forest>,
+                      cfg: ForestFireConfig,
+                      rng: Box<StdRng>) -> ForestFire {
+        ForestFire {
+            cfg: cfg,
+            rng: rng,
+            board: board,
+            iteration: 0
+        }
+    }
+
+    /// Fire spreads to the four orthogonal neighbors and stops at the edges
+    /// of the board.
+    fn next_to_fire(&self,
+                    x: usize,
+                    y: usize) -> bool {
+        let (x, y) = (x as i64, y as i64);
+        [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)].iter()
+                                                         .any(|&(x, y)| self.board.get(x, y) == Some(&Forest::Burning))
+    }
+
+    /// Returns (empty, tree, burning) counts.
+    pub fn count_states(board: &Board<Forest>) -> (usize, usize, usize) {
+        board.iter().fold((0, 0, 0), |(e, t, b), cell| match *cell {
+            Forest::Empty => (e + 1, t, b),
+            Forest::Tree => (e, t + 1, b),
+            Forest::Burning => (e, t, b + 1)
+        })
+    }
+}
+
+impl Simulation<Forest> for ForestFire {
+    fn advance(&mut self) {
+        let mut new = Board::new(self.board.width, self.board.height, Forest::Empty);
+
+        for (x, y) in self.board.indices() {
+            *new.at_mut(x, y) = match *self.board.at(x, y) {
+                Forest::Burning => Forest::Empty,
+                Forest::Tree if self.next_to_fire(x, y) => Forest::Burning,
+                Forest::Tree if self.rng.gen::<f32>() < self.cfg.lightning_probability => Forest::Burning,
+                Forest::Tree => Forest::Tree,
+                Forest::Empty if self.rng.gen::<f32>() < self.cfg.growth_probability => Forest::Tree,
+                Forest::Empty => Forest::Empty
+            };
+        }
+
+        self.board = new;
+        self.iteration += 1;
+    }
+
+    fn board(&self) -> &Board<Forest> {
+        &self.board
+    }
+
+    fn stats(&self) -> Stats {
+        let (_, trees, burning) = ForestFire::count_states(&self.board);
+
+        Stats::new(self.iteration).count("trees", trees)
+                                  .count("burning", burning)
+    }
+
+    /// The fire is over once nothing burns and no lightning can start
+    /// another one.
+    fn is_extinct(&self) -> bool {
+        self.cfg.lightning_probability == 0.0 && !self.board.iter().any(|cell| *cell == Forest::Burning)
+    }
+}
+
+#[test]
+fn test_forest_fire_front() {
+    use rand::SeedableRng;
+
+    let mut board = Board::new(4, 2, Forest::Tree);
+    *board.at_mut(0, 0) = Forest::Burning;
+    *board.at_mut(2, 1) = Forest::Empty;
+    let rng = Box::new(StdRng::from_seed(&[0usize][..]));
+    let mut sim = ForestFire::from_board(board, ForestFireConfig::default(), rng);
+
+    sim.advance();
+    assert_eq!((2, 4, 2), ForestFire::count_states(sim.board()));
+    assert_eq!(Forest::Burning, *sim.board().at(1, 0));
+    assert_eq!(Forest::Burning, *sim.board().at(0, 1));
+
+    sim.advance();
+    assert_eq!(Forest::Burning, *sim.board().at(2, 0));
+    assert_eq!(Forest::Tree, *sim.board().at(3, 0));
+    assert_eq!(Forest::Tree, *sim.board().at(3, 1));
+
+    while !sim.is_extinct() {
+        sim.advance();
+    }
+    assert_eq!((8, 0, 0), ForestFire::count_states(sim.board()));
+}
+
+#[test]
+fn test_forest_fire_new_ignites_left_edge() {
+    use rand::SeedableRng;
+
+    let cfg = ForestFireConfig { tree_density: 1.0, .. ForestFireConfig::default() };
+    let sim = ForestFire::new(5, 3, cfg, Box::new(StdRng::from_seed(&[0usize][..])));
+
+    for (x, y) in sim.board().indices() {
+        assert_eq!(if x == 0 { Forest::Burning } else { Forest::Tree }, *sim.board().at(x, y));
+    }
+}
@@ -0,0 +1,102 @@
+use rand::{Rng, StdRng};
+
+/// Heritable traits of a GoodEvil specimen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Genome {
+    /// How hard the specimen fights for energy in collisions, 0 to 1.
+    pub aggression: f32,
+    /// Multiplier of the energy lost each step. Fast metabolism makes a
+    /// specimen stronger in collisions.
+    pub metabolism: f32,
+    /// Energy above which a specimen splits off a child.
+    pub reproduction_threshold: f32
+}
+
+impl Default for Genome {
+    fn default() -> Genome {
+        Genome {
+            aggression: 0.5,
+            metabolism: 1.0,
+            reproduction_threshold: 1.5
+        }
+    }
+}
+
+impl Genome {
+    /// Names of stats describing the distribution (average and standard
+    /// deviation) of each trait.
+    pub fn stat_names() -> &'static [(&'static str, &'static str)] {
+        const NAMES: &'static [(&'static str, &'static str)] = &[
+            ("aggression_avg", "aggression_stdev"),
+            ("metabolism_avg", "metabolism_stdev"),
+            ("reproduction_threshold_avg", "reproduction_threshold_stdev")
+        ];
+        NAMES
+    }
+
+    /// Trait values in the order of `stat_names`.
+    pub fn traits(&self) -> [f32; 3] {
+        [self.aggression, self.metabolism, self.reproduction_threshold]
+    }
+
+    /// Multiplier of energy when comparing specimens in collisions; 1 for
+    /// the default genome.
+    pub fn strength(&self) -> f32 {
+        (0.5 + self.aggression) * self.metabolism
+    }
+
+    /// Copy of the genome with every trait changed with probability
+    /// `cfg.mutation_rate` by up to `cfg.mutation_size`.
+    pub fn mutated(&self,
+                   cfg: &GeneticsConfig,
+                   rng: &mut StdRng) -> Genome {
+        let mut mutate = |value: f32, min: f32, max: f32| {
+            if rng.gen::<f32>() < cfg.mutation_rate {
+                (value + rng.gen_range(-cfg.mutation_size, cfg.mutation_size)).max(min).min(max)
+            } else {
+                value
+            }
+        };
+
+        Genome {
+            aggression: mutate(self.aggression, 0.0, 1.0),
+            metabolism: mutate(self.metabolism, 0.1, 3.0),
+            reproduction_threshold: mutate(self.reproduction_threshold, 0.5, 10.0)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GeneticsConfig {
+    /// Probability of each trait of a child mutating.
+    pub mutation_rate: f32,
+    /// Largest change of a trait in a single mutation.
+    pub mutation_size: f32
+}
+
+impl Default for GeneticsConfig {
+    fn default() -> GeneticsConfig {
+        GeneticsConfig {
+            mutation_rate: 0.1,
+            mutation_size: 0.1
+        }
+    }
+}
+
+#[test]
+fn test_genome_mutation() {
+    use rand::SeedableRng;
+
+    let mut rng = StdRng::from_seed(&[0usize][..]);
+    let genome = Genome::default();
+
+    let never = GeneticsConfig { mutation_rate: 0.0, mutation_size: 1.0 };
+    assert_eq!(genome, genome.mutated(&never, &mut rng));
+
+    let always = GeneticsConfig { mutation_rate: 1.0, mutation_size: 0.1 };
+    let mutated = genome.mutated(&always, &mut rng);
+    for (before, after) in genome.traits().iter().zip(mutated.traits().iter()) {
+        assert!(before != after);
+        assert!((before - after).abs() <= 0.1);
+    }
+}
@@ -0,0 +1,236 @@
+//! Minimal animated GIF encoder, enough to share recorded runs without
+//! external tools.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+
+use offscreen::Image;
+
+/// Largest LZW code allowed by the format.
+const MAX_CODES: usize = 4096;
+
+/// Writes frames of the same size as an endlessly looping animation.
+/// `finish` must be called to write the trailer.
+pub struct GifWriter<W: Write> {
+    out: W,
+    width: usize,
+    height: usize,
+    /// Time each frame is shown, in hundredths of a second.
+    delay: u16
+}
+
+impl<W: Write> GifWriter<W> {
+    pub fn new(mut out: W,
+               width: usize,
+               height: usize,
+               delay: u16) -> io::Result<GifWriter<W>> {
+        assert!(width > 0 && width <= 0xffff && height > 0 && height <= 0xffff);
+
+        try!(out.write_all(b"GIF89a"));
+        let mut screen = Vec::new();
+        push_u16(&mut screen, width as u16);
+        push_u16(&mut screen, height as u16);
+        // no global color table, background color, square pixels
+        screen.extend([0, 0, 0].iter().cloned());
+        try!(out.write_all(&screen));
+        // loop forever
+        try!(out.write_all(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00"));
+
+        Ok(GifWriter {
+            out: out,
+            width: width,
+            height: height,
+            delay: delay
+        })
+    }
+
+    /// Appends a frame with its own color table. Images with more than 256
+    /// colors are reduced to a 6x6x6 color cube.
+    pub fn add_frame(&mut self,
+                     image: &Image) -> io::Result<()> {
+        assert_eq!((self.width, self.height), (image.width, image.height));
+
+        let (palette, indices) = quantize(image);
+        let mut table_bits = 1;
+        while (1 << table_bits) < palette.len() {
+            table_bits += 1;
+        }
+
+        let mut frame = Vec::new();
+        // graphic control extension: no transparency, frame delay
+        frame.extend([0x21, 0xf9, 4, 0].iter().cloned());
+        push_u16(&mut frame, self.delay);
+        frame.extend([0, 0].iter().cloned());
+
+        frame.push(0x2c);
+        push_u16(&mut frame, 0);
+        push_u16(&mut frame, 0);
+        push_u16(&mut frame, self.width as u16);
+        push_u16(&mut frame, self.height as u16);
+        // local color table, not interlaced
+        frame.push(0x80 | (table_bits - 1) as u8);
+        for idx in 0..(1 << table_bits) {
+            let color = palette.get(idx).cloned().unwrap_or([0, 0, 0]);
+            frame.extend(color.iter().cloned());
+        }
+
+        let min_code_size = if table_bits < 2 { 2 } else { table_bits };
+        frame.push(min_code_size as u8);
+        for block in lzw_compress(&indices, min_code_size).chunks(255) {
+            frame.push(block.len() as u8);
+            frame.extend(block.iter().cloned());
+        }
+        frame.push(0);
+
+        self.out.write_all(&frame)
+    }
+
+    /// Writes the trailer and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        try!(self.out.write_all(&[0x3b]));
+        try!(self.out.flush());
+        Ok(self.out)
+    }
+}
+
+fn push_u16(bytes: &mut Vec<u8>,
+            value: u16) {
+    bytes.push((value & 0xff) as u8);
+    bytes.push((value >> 8) as u8);
+}
+
+/// Colors of the image and the index of each pixel's color.
+fn quantize(image: &Image) -> (Vec<[u8; 3]>, Vec<u8>) {
+    let mut colors = BTreeMap::new();
+    for y in 0..image.height {
+        for x in 0..image.width {
+            let next = colors.len();
+            colors.entry(image.pixel(x, y)).or_insert(next);
+        }
+    }
+
+    let pixels = (0..image.height).flat_map(|y| (0..image.width).map(move |x| (x, y)));
+    if colors.len() <= 256 {
+        let mut palette = vec![[0u8; 3]; colors.len()];
+        for (color, &idx) in colors.iter() {
+            palette[idx] = *color;
+        }
+        let indices = pixels.map(|(x, y)| colors[&image.pixel(x, y)] as u8).collect();
+        (palette, indices)
+    } else {
+        let level = |c: u8| c as usize * 6 / 256;
+        let palette = (0..216).map(|i| [(i / 36 * 51) as u8, (i / 6 % 6 * 51) as u8, (i % 6 * 51) as u8])
+                              .collect();
+        let indices = pixels.map(|(x, y)| {
+                                let p = image.pixel(x, y);
+                                (level(p[0]) * 36 + level(p[1]) * 6 + level(p[2])) as u8
+                            })
+                            .collect();
+        (palette, indices)
+    }
+}
+
+/// Packs variable-length codes into bytes, least significant bit first.
+struct BitWriter {
+    bytes: Vec<u8>,
+    pending: u32,
+    pending_bits: usize
+}
+
+impl BitWriter {
+    fn write(&mut self,
+             code: usize,
+             bits: usize) {
+        self.pending |= (code as u32) << self.pending_bits;
+        self.pending_bits += bits;
+        while self.pending_bits >= 8 {
+            self.bytes.push((self.pending & 0xff) as u8);
+            self.pending >>= 8;
+            self.pending_bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.pending_bits > 0 {
+            self.bytes.push((self.pending & 0xff) as u8);
+        }
+        self.bytes
+    }
+}
+
+/// GIF flavor of LZW: codes grow from `min_code_size + 1` up to 12 bits,
+/// and the table is cleared once it's full.
+fn lzw_compress(indices: &[u8],
+                min_code_size: usize) -> Vec<u8> {
+    let clear = 1 << min_code_size;
+    let end = clear + 1;
+    let mut bits = BitWriter { bytes: Vec::new(), pending: 0, pending_bits: 0 };
+    let mut table: HashMap<(usize, u8), usize> = HashMap::new();
+    let mut next = clear + 2;
+    let mut code_size = min_code_size + 1;
+
+    bits.write(clear, code_size);
+    let mut prefix = match indices.first() {
+        Some(&idx) => idx as usize,
+        None => {
+            bits.write(end, code_size);
+            return bits.finish();
+        }
+    };
+
+    for &idx in &indices[1..] {
+        if let Some(&code) = table.get(&(prefix, idx)) {
+            prefix = code;
+            continue;
+        }
+
+        bits.write(prefix, code_size);
+        // the decoder adds an entry after each code it reads, and widens
+        // codes once the table outgrows them
+        if next == (1 << code_size) && code_size < 12 {
+            code_size += 1;
+        }
+        if next < MAX_CODES {
+            table.insert((prefix, idx), next);
+            next += 1;
+        } else {
+            bits.write(clear, code_size);
+            table.clear();
+            next = clear + 2;
+            code_size = min_code_size + 1;
+        }
+        prefix = idx as usize;
+    }
+
+    bits.write(prefix, code_size);
+    if next == (1 << code_size) && code_size < 12 {
+        code_size += 1;
+    }
+    bits.write(end, code_size);
+    bits.finish()
+}
+
+#[test]
+fn test_gif_writer() {
+    use board::Board;
+
+    let mut board = Board::new(3, 2, false);
+    *board.at_mut(1, 1) = true;
+    let image = Image::render_board(&board, None, 1);
+
+    let mut gif = GifWriter::new(Vec::new(), 3, 2, 4).unwrap();
+    gif.add_frame(&image).unwrap();
+    gif.add_frame(&image).unwrap();
+    let bytes = gif.finish().unwrap();
+
+    assert_eq!(b"GIF89a", &bytes[..6]);
+    assert_eq!(&[3, 0, 2, 0], &bytes[6..10]);
+    assert_eq!(Some(&0x3b), bytes.last());
+    assert_eq!(2, bytes.windows(3).filter(|w| *w == [0x21, 0xf9, 4]).count());
+}
+
+#[test]
+fn test_lzw_compress() {
+    // clear, 1, 6 (1 1), 6, then the end code, already 4 bits wide
+    assert_eq!(vec![0x8c, 0x5d], lzw_compress(&[1, 1, 1, 1, 1], 2));
+}
@@ -0,0 +1,190 @@
+use graphics::{self, clear, line, polygon, rectangle, Line, Transformed};
+use graphics::character::CharacterCache;
+use graphics::text::Text;
+use opengl_graphics::{GlGraphics, GlyphCache};
+
+use cell::energy_histogram::EnergyHistogram;
+use cell::renderer::{Renderer, Scene, Viewport};
+
+/// Font of the HUD, bundled so that it looks the same everywhere.
+const HUD_FONT: &'static [u8] = include_bytes!("../assets/DejaVuSansMono.ttf");
+const HUD_FONT_SIZE: u32 = 14;
+const HUD_LINE_HEIGHT: f64 = 18.0;
+const HUD_MARGIN: f64 = 8.0;
+const SELECTION_COLOR: [f32; 4] = [1.0, 1.0, 0.0, 1.0];
+const MINIMAP_VIEW_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const PLOT_BACKGROUND: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+const HISTOGRAM_BAR_COLOR: [f32; 4] = [1.0, 0.8, 0.2, 1.0];
+
+/// Sides of a rectangle given as [x, y, width, height], as lines.
+fn outline(rect: [f64; 4]) -> [[f64; 4]; 4] {
+    let (left, top, right, bottom) = (rect[0], rect[1], rect[0] + rect[2], rect[1] + rect[3]);
+    [[left, top, right, top],
+     [right, top, right, bottom],
+     [right, bottom, left, bottom],
+     [left, bottom, left, top]]
+}
+
+/// Draws scenes in the window with OpenGL.
+pub struct GlRenderer {
+    gl: GlGraphics,
+    /// None if the font couldn't be loaded, in which case the HUD isn't
+    /// drawn.
+    glyphs: Option<GlyphCache<'static>>
+}
+
+impl GlRenderer {
+    pub fn new(gl: GlGraphics) -> GlRenderer {
+        let glyphs = match GlyphCache::from_bytes(HUD_FONT) {
+            Ok(glyphs) => Some(glyphs),
+            Err(e) => {
+                println!("cannot load the HUD font: {:?}", e);
+                None
+            }
+        };
+
+        GlRenderer {
+            gl: gl,
+            glyphs: glyphs
+        }
+    }
+}
+
+impl Renderer for GlRenderer {
+    fn render(&mut self,
+              scene: &Scene,
+              viewport: Viewport) {
+        let viewport_size = viewport.size();
+        let viewport = graphics::Viewport {
+            rect: [0, 0, viewport.draw_size[0] as i32, viewport.draw_size[1] as i32],
+            draw_size: viewport.draw_size,
+            window_size: viewport.window_size
+        };
+        let theme = scene.theme;
+        let (x_range, y_range) = scene.visible;
+        let elem_size = scene.cell_size;
+        let glyphs = &mut self.glyphs;
+
+        self.gl.draw(viewport, |ctx, gl| {
+            clear(theme.background, gl);
+
+            for y_idx in y_range.0..y_range.1 {
+                for x_idx in x_range.0..x_range.1 {
+                    let pos = scene.to_viewport(x_idx as f64, y_idx as f64);
+                    let rect = [pos[0], pos[1], elem_size[0], elem_size[1]];
+                    rectangle(scene.cell(x_idx, y_idx).color, rect, ctx.transform, gl);
+                }
+            }
+
+            for y_idx in y_range.0..y_range.1 {
+                for x_idx in x_range.0..x_range.1 {
+                    let value = scene.cell(x_idx, y_idx).value;
+                    if value > 0.0 {
+                        let pos = scene.to_viewport(x_idx as f64, y_idx as f64);
+                        let rect = [pos[0], pos[1], elem_size[0], elem_size[1]];
+                        let mut color = theme.density;
+                        color[3] *= value;
+                        rectangle(color, rect, ctx.transform, gl);
+                    }
+                }
+            }
+
+            if let Some(grid) = scene.grid {
+                let grid_line = Line::new(grid, 0.5);
+                for segment in scene.grid_lines() {
+                    grid_line.draw(segment, &ctx.draw_state, ctx.transform, gl);
+                }
+            }
+
+            for &(ref triangle, color) in scene.agents.iter() {
+                let triangle: Vec<[f64; 2]> = triangle.iter()
+                                                      .map(|v| scene.to_viewport(v[0], v[1]))
+                                                      .collect();
+                polygon(color, &triangle, ctx.transform, gl);
+            }
+
+            for streamline in scene.streamlines.iter() {
+                for segment in streamline.windows(2) {
+                    let (from, to) = (scene.to_viewport(segment[0].0, segment[0].1),
+                                      scene.to_viewport(segment[1].0, segment[1].1));
+                    line(theme.streamline, 0.5, [from[0], from[1], to[0], to[1]], ctx.transform, gl);
+                }
+            }
+
+            if let Some(selection) = scene.selection {
+                let top_left = scene.to_viewport(selection.x as f64, selection.y as f64);
+                let bottom_right = scene.to_viewport((selection.x + selection.width) as f64,
+                                                     (selection.y + selection.height) as f64);
+                let selection_line = Line::new(SELECTION_COLOR, 1.0);
+                for &segment in outline([top_left[0], top_left[1],
+                                         bottom_right[0] - top_left[0], bottom_right[1] - top_left[1]]).iter() {
+                    selection_line.draw(segment, &ctx.draw_state, ctx.transform, gl);
+                }
+            }
+
+            if let Some(ref minimap) = scene.minimap {
+                rectangle(theme.background, minimap.rect, ctx.transform, gl);
+                let cell_size = [minimap.rect[2] / minimap.width as f64, minimap.rect[3] / minimap.height as f64];
+                for (idx, &color) in minimap.colors.iter().enumerate() {
+                    let (x, y) = ((idx % minimap.width) as f64, (idx / minimap.width) as f64);
+                    rectangle(color,
+                              [minimap.rect[0] + x * cell_size[0], minimap.rect[1] + y * cell_size[1],
+                               cell_size[0], cell_size[1]],
+                              ctx.transform, gl);
+                }
+                let view_line = Line::new(MINIMAP_VIEW_COLOR, 1.0);
+                for &segment in outline(minimap.visible).iter() {
+                    view_line.draw(segment, &ctx.draw_state, ctx.transform, gl);
+                }
+            }
+
+            if let Some(ref plot) = scene.plot {
+                rectangle(PLOT_BACKGROUND, plot.rect, ctx.transform, gl);
+                let mut label_x = plot.rect[0] + HUD_MARGIN;
+                for series in plot.series.iter() {
+                    for &segment in series.lines.iter() {
+                        line(series.color, 1.0, segment, ctx.transform, gl);
+                    }
+                    if let Some(glyphs) = glyphs.as_mut() {
+                        let transform = ctx.transform.trans(label_x, plot.rect[1] + HUD_LINE_HEIGHT);
+                        Text::new_color(series.color, HUD_FONT_SIZE).draw(&series.label, glyphs, &ctx.draw_state,
+                                                                          transform, gl);
+                        label_x += glyphs.width(HUD_FONT_SIZE, &series.label) + 2.0 * HUD_MARGIN;
+                    }
+                }
+            }
+
+            if let Some(ref histogram) = scene.energy_histogram {
+                let rect = EnergyHistogram::rect(viewport_size);
+                rectangle(PLOT_BACKGROUND, rect, ctx.transform, gl);
+                for &bar in histogram.bars(rect).iter() {
+                    // a gap between bars so that they can be told apart
+                    rectangle(HISTOGRAM_BAR_COLOR, [bar[0] + 0.5, bar[1], (bar[2] - 1.0).max(0.5), bar[3]],
+                              ctx.transform, gl);
+                }
+                if let Some(glyphs) = glyphs.as_mut() {
+                    let label = format!("energy 0 - {:.2}", histogram.max);
+                    let transform = ctx.transform.trans(rect[0] + HUD_MARGIN, rect[1] + HUD_LINE_HEIGHT);
+                    Text::new_color([1.0, 1.0, 1.0, 1.0], HUD_FONT_SIZE).draw(&label, glyphs, &ctx.draw_state,
+                                                                              transform, gl);
+                }
+            }
+
+            if let (Some(glyphs), false) = (glyphs.as_mut(), scene.hud.is_empty()) {
+                let width = scene.hud.iter()
+                                     .map(|text| glyphs.width(HUD_FONT_SIZE, text))
+                                     .fold(0.0, f64::max);
+                let height = HUD_LINE_HEIGHT * scene.hud.len() as f64;
+                rectangle([0.0, 0.0, 0.0, 0.6],
+                          [0.0, 0.0, width + 2.0 * HUD_MARGIN, height + HUD_MARGIN],
+                          ctx.transform, gl);
+
+                let text = Text::new_color([1.0, 1.0, 1.0, 1.0], HUD_FONT_SIZE);
+                for (idx, row) in scene.hud.iter().enumerate() {
+                    let transform = ctx.transform.trans(HUD_MARGIN, HUD_LINE_HEIGHT * (idx + 1) as f64);
+                    text.draw(row, glyphs, &ctx.draw_state, transform, gl);
+                }
+            }
+        });
+    }
+}
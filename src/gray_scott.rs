@@ -0,0 +1,189 @@
+use board::Board;
+use i18n::Language;
+use rand::{Rng, StdRng};
+use simulation::Simulation;
+use stats::Stats;
+
+#[derive(Clone, Copy, Debug)]
+pub struct GrayScottConfig {
+    /// Rate at which chemical U is replenished.
+    pub feed_rate: f32,
+    /// Rate at which chemical V is removed.
+    pub kill_rate: f32,
+    pub diffusion_u: f32,
+    pub diffusion_v: f32,
+    /// Number of square V patches placed on the board initially.
+    pub initial_seeds: usize
+}
+
+impl Default for GrayScottConfig {
+    fn default() -> GrayScottConfig {
+        GrayScottConfig {
+            feed_rate: 0.055,
+            kill_rate: 0.062,
+            diffusion_u: 1.0,
+            diffusion_v: 0.5,
+            initial_seeds: 10
+        }
+    }
+}
+
+/// Gray-Scott reaction-diffusion model: U + 2V -> 3V, V -> P. Both
+/// chemicals are stored as separate scalar fields on a torus.
+#[derive(Clone)]
+pub struct GrayScott {
+    pub cfg: GrayScottConfig,
+    u: Board<f32>,
+    v: Board<f32>,
+    iteration: usize
+}
+
+/// Maximum concentration of V below which the pattern is considered dead.
+const DEAD_THRESHOLD: f32 = 1e-4;
+
+impl GrayScott {
+    pub fn new(width: usize,
+               height: usize,
+               cfg: GrayScottConfig,
+               mut rng: Box<StdRng>) -> GrayScott {
+        let mut u = Board::new(width, height, 1.0f32);
+        let mut v = Board::new(width, height, 0.0f32);
+
+        let seed_size = ::std::cmp::max(4, ::std::cmp::min(width, height) / 10);
+        for _ in 0..cfg.initial_seeds {
+            let x0 = rng.gen_range(0, width);
+            let y0 = rng.gen_range(0, height);
+
+            for dy in 0..seed_size {
+                for dx in 0..seed_size {
+                    let (x, y) = ((x0 + dx) % width, (y0 + dy) % height);
+                    *u.at_mut(x, y) = 0.0;
+                    *v.at_mut(x, y) = 1.0;
+                }
+            }
+        }
+
+        GrayScott::from_boards(u, v, cfg)
+    }
+
+    pub fn from_boards(u: Board<f32>,
+                       v: Board<f32>,
+                       cfg: GrayScottConfig) -> GrayScott {
+        assert!(u.width == v.width && u.height == v.height);
+
+        GrayScott {
+            cfg: cfg,
+            u: u,
+            v: v,
+            iteration: 0
+        }
+    }
+
+    /// 3x3 discrete Laplacian with weights 0.2 for orthogonal and 0.05 for
+    /// diagonal neighbors, wrapping around board edges.
+    fn laplacian(board: &Board<f32>,
+                 x: usize,
+                 y: usize) -> f32 {
+        let (w, h) = (board.width, board.height);
+        let (left, right) = ((x + w - 1) % w, (x + 1) % w);
+        let (up, down) = ((y + h - 1) % h, (y + 1) % h);
+
+        let orthogonal = *board.at(left, y) + *board.at(right, y)
+                       + *board.at(x, up) + *board.at(x, down);
+        let diagonal = *board.at(left, up) + *board.at(right, up)
+                     + *board.at(left, down) + *board.at(right, down);
+
+        0.2 * orthogonal + 0.05 * diagonal - *board.at(x, y)
+    }
+}
+
+impl Simulation<f32> for GrayScott {
+    fn advance(&mut self) {
+        let mut new_u = Board::new(self.u.width, self.u.height, 0.0f32);
+        let mut new_v = Board::new(self.v.width, self.v.height, 0.0f32);
+        let cfg = self.cfg;
+
+        for (x, y) in self.u.indices() {
+            let u = *self.u.at(x, y);
+            let v = *self.v.at(x, y);
+            let reaction = u * v * v;
+
+            let du = cfg.diffusion_u * GrayScott::laplacian(&self.u, x, y)
+                   - reaction + cfg.feed_rate * (1.0 - u);
+            let dv = cfg.diffusion_v * GrayScott::laplacian(&self.v, x, y)
+                   + reaction - (cfg.feed_rate + cfg.kill_rate) * v;
+
+            *new_u.at_mut(x, y) = (u + du).max(0.0).min(1.0);
+            *new_v.at_mut(x, y) = (v + dv).max(0.0).min(1.0);
+        }
+
+        self.u = new_u;
+        self.v = new_v;
+        self.iteration += 1;
+    }
+
+    /// Concentration of chemical V, which forms the visible pattern.
+    fn board(&self) -> &Board<f32> {
+        &self.v
+    }
+
+    fn stats(&self) -> Stats {
+        let cells = (self.u.width * self.u.height) as f32;
+        let sum_u = self.u.iter().fold(0.0f32, |sum, &c| sum + c);
+        let sum_v = self.v.iter().fold(0.0f32, |sum, &c| sum + c);
+        let max_v = self.v.iter().fold(0.0f32, |max, &c| max.max(c));
+
+        Stats::new(self.iteration).real("avg_u", sum_u / cells)
+                                  .real("avg_v", sum_v / cells)
+                                  .real("max_v", max_v)
+    }
+
+    fn is_extinct(&self) -> bool {
+        !self.v.iter().any(|&c| c > DEAD_THRESHOLD)
+    }
+
+    fn describe_cell(&self,
+                     x: usize,
+                     y: usize,
+                     _lang: Language) -> Vec<String> {
+        vec![format!("u {:.3}", self.u.at(x, y)),
+             format!("v {:.3}", self.v.at(x, y))]
+    }
+}
+
+#[test]
+fn test_gray_scott_laplacian() {
+    let mut board = Board::new(3, 3, 1.0f32);
+    assert!(GrayScott::laplacian(&board, 0, 0).abs() < 1e-6);
+
+    *board.at_mut(1, 1) = 0.0;
+    assert!((GrayScott::laplacian(&board, 1, 1) - 1.0).abs() < 1e-6);
+    assert!((GrayScott::laplacian(&board, 0, 1) + 0.2).abs() < 1e-6);
+    assert!((GrayScott::laplacian(&board, 0, 0) + 0.05).abs() < 1e-6);
+}
+
+#[test]
+fn test_gray_scott_homogeneous_state_is_stable() {
+    let mut sim = GrayScott::from_boards(Board::new(4, 4, 1.0f32),
+                                         Board::new(4, 4, 0.0f32),
+                                         GrayScottConfig::default());
+    sim.advance();
+
+    assert!(sim.u.iter().all(|&c| c == 1.0));
+    assert!(sim.is_extinct());
+}
+
+#[test]
+fn test_gray_scott_seeded_pattern_stays_bounded() {
+    use rand::SeedableRng;
+
+    let rng = Box::new(StdRng::from_seed(&[0usize][..]));
+    let mut sim = GrayScott::new(40, 40, GrayScottConfig::default(), rng);
+
+    for _ in 0..50 {
+        sim.advance();
+    }
+
+    assert!(!sim.is_extinct());
+    assert!(sim.board().iter().all(|&c| c >= 0.0 && c <= 1.0));
+}
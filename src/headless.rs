@@ -0,0 +1,356 @@
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(test)]
+use board::Board;
+use board_view::ViewPublisher;
+use diagnostics::{Crash, LogTail, PhaseTimings, panic_message};
+use energy_histogram::EnergyHistogram;
+use exposure::Exposure;
+use journal::EventLog;
+use merge::ScheduledMerge;
+use outcome::Outcome;
+use palette::CellColor;
+use quadtree::Quadtree;
+use recording::Recorder;
+use replay::ReplayVerifier;
+use schema::StatsLog;
+use simulation::Simulation;
+use snapshot::Snapshots;
+use stats::{Stats, StopCriterion};
+use symmetry::Region;
+use warnings::{WarningKind, WarningLog};
+
+/// Number of stats lines kept for the diagnostics bundle.
+const LOG_TAIL_LINES: usize = 50;
+/// Ticks between copies of the board kept for the diagnostics bundle.
+/// Copying the board on every tick would cost as much as the step itself
+/// for sparse simulations.
+pub const CRASH_SNAPSHOT_EVERY: usize = 100;
+
+/// Sent from the simulation thread as the run goes.
+enum Progress {
+    Tick(Stats),
+    Phase(&'static str, Duration)
+}
+
+pub struct HeadlessConfig {
+    pub iterations: usize,
+    pub stop_criterion: Option<StopCriterion>,
+    /// Replays every this many ticks from a snapshot and panics if the
+    /// replay diverges from the live simulation.
+    pub verify_every: Option<usize>,
+    /// Writes a frame after every step, finishing GIFs and videos when the
+    /// run ends. Recording stops at the first error, the run goes on.
+    pub record: Option<Recorder>,
+    /// Appends the simulation's journal after every step. Like recording,
+    /// logging stops at the first error.
+    pub event_log: Option<EventLog>,
+    /// Appends stats after every step. Like recording, logging stops at
+    /// the first error.
+    pub stats_log: Option<StatsLog>,
+    /// Writes long-exposure images, the last one when the run ends. Like
+    /// recording, exposing stops at the first error.
+    pub exposure: Option<Exposure>,
+    /// Regions merged into the board as the run goes.
+    pub merges: Vec<ScheduledMerge>,
+    /// Region whose occupied cells are counted in stats as `region`.
+    pub count_region: Option<Region>,
+    /// PNG snapshots taken as the run goes. Like recording, they stop at
+    /// the first error.
+    pub snapshots: Option<Snapshots>,
+    /// Buckets of the energy histogram added to stats, if any.
+    pub energy_histogram: Option<usize>
+}
+
+impl Default for HeadlessConfig {
+    fn default() -> HeadlessConfig {
+        HeadlessConfig {
+            iterations: 1000,
+            stop_criterion: None,
+            verify_every: None,
+            record: None,
+            event_log: None,
+            stats_log: None,
+            exposure: None,
+            merges: Vec::new(),
+            count_region: None,
+            snapshots: None,
+            energy_histogram: None
+        }
+    }
+}
+
+/// Applies merges scheduled after `iteration` steps.
+pub fn apply_merges<T, S>(simulation: &mut S,
+                          merges: &[ScheduledMerge],
+                          iteration: usize) where S: Simulation<T> {
+    for merge in merges.iter().filter(|merge| merge.iteration == iteration) {
+        let changed = simulation.merge(&merge.region, merge.offset, merge.policy);
+        println!("merged {} cells at ({}, {})", changed, merge.offset.0, merge.offset.1);
+    }
+}
+
+/// Advances the simulation without rendering, printing stats after each
+/// step. The simulation runs on a separate thread, so that a panic is
+/// reported as an `Outcome::Error` instead of aborting the process.
+///
+/// With `cfg.verify_every` set, nondeterminism detected by a
+/// `ReplayVerifier` is treated as a crash.
+///
+/// Returns the outcome along with the stats of the last completed step.
+/// The simulation is given back unless it panicked, in which case the
+/// returned `Crash` describes what led to it; its snapshot is the board as
+/// of the last multiple of `CRASH_SNAPSHOT_EVERY` steps. Warnings of the
+/// run, both the simulation's own and ones about recording, come last.
+pub fn run<T, S>(mut simulation: S,
+                 cfg: HeadlessConfig) -> (Outcome, Option<Stats>, Result<S, Crash<T>>, WarningLog)
+        where S: Simulation<T> + Clone + Send + 'static, T: CellColor + Clone + PartialEq + Send + Sync + 'static {
+    let (progress_tx, progress_rx) = mpsc::channel();
+    let publisher = ViewPublisher::new(simulation.board(), 0);
+    let observer = publisher.observer();
+
+    let handle = thread::spawn(move || {
+        let mut outcome = Outcome::Completed;
+        let mut verifier = cfg.verify_every.map(|interval| ReplayVerifier::new(&simulation, interval));
+        let mut recorder = cfg.record;
+        let mut event_log = cfg.event_log;
+        let mut stats_log = cfg.stats_log;
+        let mut exposure = cfg.exposure;
+        let mut snapshots = cfg.snapshots;
+        let mut warnings = WarningLog::new();
+        let mut occupancy = cfg.count_region.map(|_| Quadtree::of_board(simulation.board()));
+        apply_merges(&mut simulation, &cfg.merges, 0);
+
+        {
+            let mut ticks = simulation.run(cfg.iterations);
+            let mut steps = 0;
+            loop {
+                let started = Instant::now();
+                let report = match ticks.next() {
+                    Some(report) => report,
+                    None => break
+                };
+                let _ = progress_tx.send(Progress::Phase("advance", started.elapsed()));
+
+                let started = Instant::now();
+                let stats = match (cfg.count_region, occupancy.as_mut()) {
+                    (Some(region), Some(occupancy)) => {
+                        occupancy.sync(ticks.simulation().board());
+                        report.stats.clone().count("region", occupancy.count_in(region))
+                    },
+                    _ => report.stats.clone()
+                };
+                let stats = match cfg.energy_histogram {
+                    Some(buckets) => {
+                        stats.with_energy_histogram(EnergyHistogram::of_specimens(&ticks.simulation().specimens(), buckets))
+                    },
+                    None => stats
+                };
+                println!("{}", stats);
+                let _ = progress_tx.send(Progress::Tick(stats.clone()));
+                let _ = progress_tx.send(Progress::Phase("output", started.elapsed()));
+
+                if let Some(ref mut verifier) = verifier {
+                    let started = Instant::now();
+                    if let Err(e) = verifier.after_tick(ticks.simulation()) {
+                        panic!("nondeterminism detected: {}", e);
+                    }
+                    let _ = progress_tx.send(Progress::Phase("verify", started.elapsed()));
+                }
+
+                let failed = match recorder {
+                    Some(ref mut recorder) => {
+                        let started = Instant::now();
+                        let result = recorder.record(ticks.simulation());
+                        let _ = progress_tx.send(Progress::Phase("record", started.elapsed()));
+                        result.err()
+                    },
+                    None => None
+                };
+                if let Some(e) = failed {
+                    println!("{}", e);
+                    warnings.record(WarningKind::RecordingFailed, report.stats.iteration, e);
+                    recorder = None;
+                }
+
+                let failed = match (event_log.as_mut(), ticks.simulation().journal()) {
+                    (Some(log), Some(journal)) => log.write(journal).err(),
+                    _ => None
+                };
+                if let Some(e) = failed {
+                    println!("{}", e);
+                    warnings.record(WarningKind::EventLogFailed, report.stats.iteration, e);
+                    event_log = None;
+                }
+
+                let failed = stats_log.as_mut().and_then(|log| log.write(&stats).err());
+                if let Some(e) = failed {
+                    println!("{}", e);
+                    warnings.record(WarningKind::StatsLogFailed, report.stats.iteration, e);
+                    stats_log = None;
+                }
+
+                let failed = exposure.as_mut().and_then(|exposure| exposure.record(ticks.simulation()).err());
+                if let Some(e) = failed {
+                    println!("{}", e);
+                    warnings.record(WarningKind::ExposureFailed, report.stats.iteration, e);
+                    exposure = None;
+                }
+
+                let failed = snapshots.as_ref().and_then(|snapshots| {
+                    snapshots.after_step(ticks.simulation(), report.stats.iteration).err()
+                });
+                if let Some(e) = failed {
+                    println!("{}", e);
+                    warnings.record(WarningKind::SnapshotFailed, report.stats.iteration, e);
+                    snapshots = None;
+                }
+
+                steps += 1;
+                if steps % CRASH_SNAPSHOT_EVERY == 0 {
+                    publisher.publish(ticks.simulation().board(), report.stats.iteration);
+                }
+                apply_merges(ticks.simulation_mut(), &cfg.merges, steps);
+
+                if report.extinct {
+                    outcome = Outcome::Extinction;
+                    break;
+                }
+                match cfg.stop_criterion {
+                    Some(ref criterion) if criterion.is_met(&stats) => {
+                        outcome = Outcome::StoppedByCriterion(criterion.to_string());
+                        break;
+                    },
+                    _ => {}
+                }
+            }
+        }
+
+        if let Some(e) = recorder.as_mut().and_then(|recorder| recorder.finish().err()) {
+            println!("{}", e);
+            warnings.record(WarningKind::RecordingFailed, simulation.stats().iteration, e);
+        }
+        if let Some(e) = exposure.as_mut().and_then(|exposure| exposure.finish().err()) {
+            println!("{}", e);
+            warnings.record(WarningKind::ExposureFailed, simulation.stats().iteration, e);
+        }
+        if let Some(own) = simulation.warnings() {
+            warnings.merge(own);
+        }
+        (outcome, simulation, warnings)
+    });
+
+    let mut last_stats = None;
+    let mut log_tail = LogTail::new(LOG_TAIL_LINES);
+    let mut timings = PhaseTimings::new();
+    for progress in progress_rx.iter() {
+        match progress {
+            Progress::Tick(stats) => {
+                log_tail.push(stats.to_string());
+                last_stats = Some(stats);
+            },
+            Progress::Phase(name, duration) => timings.record(name, duration)
+        }
+    }
+
+    match handle.join() {
+        Ok((outcome, simulation, warnings)) => (outcome, last_stats, Ok(simulation), warnings),
+        Err(payload) => {
+            let message = panic_message(payload);
+            let crash = Crash {
+                message: message.clone(),
+                log_tail: log_tail,
+                timings: timings,
+                snapshot: Some(observer.latest())
+            };
+            (Outcome::Error(message), last_stats, Err(crash), WarningLog::new())
+        }
+    }
+}
+
+#[test]
+fn test_headless_stops_on_criterion() {
+    use rand::{SeedableRng, StdRng};
+    use sandpile::{Sandpile, SandpileConfig};
+
+    let rng = Box::new(StdRng::from_seed(&[0usize][..]));
+    let sim = Sandpile::new(10, 10, SandpileConfig { drop_rate: 1.0 }, rng);
+
+    let (outcome, stats, _, _) = run(sim, HeadlessConfig {
+        iterations: 100,
+        stop_criterion: StopCriterion::parse("grains>9"),
+        verify_every: Some(3),
+        .. HeadlessConfig::default()
+    });
+
+    assert_eq!(Outcome::StoppedByCriterion("grains>9".to_string()), outcome);
+    assert_eq!(10, stats.unwrap().iteration);
+}
+
+#[test]
+fn test_headless_completes() {
+    use simulation::GameOfLife;
+
+    let mut board = Board::new(4, 4, false);
+    for &(x, y) in [(1, 1), (2, 1), (1, 2), (2, 2)].iter() {
+        *board.at_mut(x, y) = true;
+    }
+
+    let (outcome, stats, _, _) = run(GameOfLife::new(board), HeadlessConfig {
+        iterations: 3,
+        .. HeadlessConfig::default()
+    });
+
+    assert_eq!(Outcome::Completed, outcome);
+    assert_eq!(3, stats.unwrap().iteration);
+}
+
+#[cfg(test)]
+#[derive(Clone)]
+struct FailsAfter {
+    board: Board<bool>,
+    iteration: usize,
+    limit: usize
+}
+
+#[cfg(test)]
+impl Simulation<bool> for FailsAfter {
+    fn advance(&mut self) {
+        assert!(self.iteration < self.limit, "invariant violated");
+        self.iteration += 1;
+        *self.board.at_mut(self.iteration, 0) = true;
+    }
+
+    fn board(&self) -> &Board<bool> {
+        &self.board
+    }
+
+    fn stats(&self) -> Stats {
+        Stats::new(self.iteration)
+    }
+}
+
+#[test]
+fn test_headless_reports_crash() {
+    let sim = FailsAfter {
+        board: Board::new(CRASH_SNAPSHOT_EVERY + 3, 1, false),
+        iteration: 0,
+        limit: CRASH_SNAPSHOT_EVERY + 2
+    };
+
+    let (outcome, stats, simulation, _) = run(sim, HeadlessConfig {
+        iterations: CRASH_SNAPSHOT_EVERY + 10,
+        .. HeadlessConfig::default()
+    });
+
+    assert_eq!(Outcome::Error("invariant violated".to_string()), outcome);
+    assert_eq!(CRASH_SNAPSHOT_EVERY + 2, stats.unwrap().iteration);
+
+    let crash = simulation.err().unwrap();
+    assert_eq!(LOG_TAIL_LINES, crash.log_tail.lines().len());
+    let snapshot = crash.snapshot.unwrap();
+    assert_eq!(CRASH_SNAPSHOT_EVERY, snapshot.iteration());
+    assert!(*snapshot.at(CRASH_SNAPSHOT_EVERY, 0) && !*snapshot.at(CRASH_SNAPSHOT_EVERY + 1, 0));
+}
@@ -0,0 +1,80 @@
+//! Text drawn over the board in the window, toggled with F1.
+
+use i18n::{Language, Message};
+use simulation::Specimen;
+
+/// Figures shown by the HUD, gathered every frame.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hud {
+    pub iteration: usize,
+    /// Occupied cells, i.e. live cells or specimens.
+    pub population: usize,
+    pub frames_per_second: f64,
+    pub updates_per_second: f64,
+    /// Speed multiplier of the speed ramp and replay, None at max speed.
+    pub speed: Option<f64>,
+    /// Specimen followed by the camera, if any.
+    pub followed: Option<Specimen>,
+    /// Cell under the cursor and the simulation's description of it.
+    pub hovered: Option<((usize, usize), Vec<String>)>
+}
+
+/// Rates are measured over a number of ticks, and undefined before any.
+fn rate(value: f64) -> String {
+    if value.is_finite() {
+        format!("{:.1}", value)
+    } else {
+        "-".to_string()
+    }
+}
+
+impl Hud {
+    pub fn lines(&self,
+                 lang: Language) -> Vec<String> {
+        let mut lines = vec![lang.format(Message::HudIteration, &[&self.iteration.to_string()]),
+                             lang.format(Message::HudPopulation, &[&self.population.to_string()]),
+                             lang.format(Message::HudFps, &[&rate(self.frames_per_second)]),
+                             lang.format(Message::HudUps, &[&rate(self.updates_per_second)]),
+                             match self.speed {
+                                 Some(speed) => lang.format(Message::HudSpeed, &[&speed.to_string()]),
+                                 None => lang.tr(Message::HudSpeedMax).to_string()
+                             }];
+        if let Some(specimen) = self.followed {
+            lines.push(lang.format(Message::HudFollowing, &[&format!("{:.2}", specimen.energy)]));
+            lines.push(lang.format(Message::HudAge, &[&specimen.age.to_string()]));
+            lines.push(lang.format(Message::HudLineage, &[&specimen.generation.to_string(),
+                                                          &specimen.species.to_string()]));
+        }
+        if let Some(((x, y), ref details)) = self.hovered {
+            lines.push(lang.format(Message::HudCell, &[&x.to_string(), &y.to_string()]));
+            lines.extend(details.iter().map(|detail| format!("  {}", detail)));
+        }
+        lines
+    }
+}
+
+#[test]
+fn test_hud_lines() {
+    let hud = Hud {
+        iteration: 120,
+        population: 37,
+        frames_per_second: 59.94,
+        updates_per_second: ::std::f64::INFINITY,
+        speed: Some(0.5),
+        followed: None,
+        hovered: None
+    };
+
+    let en = Language::English;
+    assert_eq!(vec!["iteration 120", "population 37", "fps 59.9", "ups -", "speed x0.5"], hud.lines(en));
+    assert_eq!("speed max", Hud { speed: None, ..hud.clone() }.lines(en)[4]);
+
+    let specimen = Specimen { age: 42, generation: 3, ..Specimen::new(1.254) };
+    assert_eq!(vec!["following: energy 1.25", "age 42", "generation 3, species 0"],
+               &Hud { followed: Some(specimen), ..hud.clone() }.lines(en)[5..]);
+    assert_eq!(vec!["śledzony: energia 1.25", "wiek 42", "pokolenie 3, gatunek 0"],
+               &Hud { followed: Some(specimen), ..hud.clone() }.lines(Language::Polish)[5..]);
+
+    let hovered = Some(((3, 4), vec!["specimen".to_string(), "energy 1.00".to_string()]));
+    assert_eq!(vec!["cell 3, 4", "  specimen", "  energy 1.00"], &Hud { hovered: hovered, ..hud }.lines(en)[5..]);
+}
@@ -0,0 +1,798 @@
+use std::env;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Language {
+    English,
+    Polish
+}
+
+/// Every user-facing message. Texts may contain `{}` placeholders, filled
+/// in order by `Language::format`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Message {
+    Usage,
+    AnalyzeUsage,
+    HelpAnalyzeOutput,
+    DemosHeader,
+    Configuration,
+    EffectiveConfig,
+    EstimatedMemory,
+    MissingOutputDir,
+    ConfigOk,
+
+    HelpBoardSize,
+    HelpSim,
+    HelpSeed,
+    HelpDemo,
+    HelpLang,
+    HelpUps,
+    HelpMaxStepsPerFrame,
+    HelpDropLag,
+    HelpHeadless,
+    HelpBench,
+    HelpProfile,
+    HelpRender,
+    HelpChecks,
+    HelpIterations,
+    HelpStopWhen,
+    HelpManifest,
+    HelpConfig,
+    HelpDumpConfig,
+    HelpSeedPopulation,
+    HelpEvent,
+    HelpRandomEvents,
+    HelpBoundary,
+    HelpMovement,
+    HelpUpdateOrder,
+    HelpConflicts,
+    HelpFairnessAudit,
+    HelpSpecimens,
+    HelpSpecimenEnergy,
+    HelpEnergyLoss,
+    HelpDeadlyMargin,
+    HelpActionCosts,
+    HelpInitialEnergy,
+    HelpImmigration,
+    HelpImmigrantEnergy,
+    HelpWind,
+    HelpWindNoise,
+    HelpWindFile,
+    HelpShowWind,
+    HelpReputation,
+    HelpDefection,
+    HelpReputationDecay,
+    HelpPredation,
+    HelpPredationEfficiency,
+    HelpSpecies,
+    HelpMaxCollision,
+    HelpInteractionRadius,
+    HelpPatternFile,
+    HelpPatternOffset,
+    HelpInit,
+    HelpStorage,
+    HelpScript,
+    HelpMutationRate,
+    HelpMutationSize,
+    HelpDormancy,
+    HelpDormantLoss,
+    HelpCrowding,
+    HelpCrowdingCapacity,
+    HelpShowDensity,
+    HelpGridLines,
+    HelpAspect,
+    HelpReproduction,
+    HelpReproductionThreshold,
+    HelpChildShare,
+    HelpReproductionCooldown,
+    HelpSymmetry,
+    HelpSymmetryRegion,
+    HelpCountRegion,
+    HelpRecordFrames,
+    HelpRecordVideo,
+    HelpRecordFps,
+    HelpRecordOnKey,
+    HelpRecordRegion,
+    HelpRecordScale,
+    HelpExposure,
+    HelpExposureWindow,
+    HelpEventLog,
+    HelpEventLogMaxSize,
+    HelpEventLogFiles,
+    HelpStatsLog,
+    HelpMetricsCsv,
+    HelpMetricsListen,
+    HelpHudMetrics,
+    HelpPlotLength,
+    HelpEnergyHistogram,
+    HelpTheme,
+    HelpPalette,
+    HelpNotebook,
+    HelpSnapshotEvery,
+    HelpSnapshotPath,
+    HelpMerge,
+    HelpRecord,
+    HelpReplay,
+    HelpSpeedRamp,
+    HelpFood,
+    HelpFoodRegrowth,
+    HelpFoodEnergy,
+    HelpCrashDir,
+    HelpSweep,
+    HelpSweepParam,
+    HelpSweepThreads,
+    HelpVerifyReplay,
+    HelpTransmission,
+    HelpIncubation,
+    HelpInfectionDuration,
+    HelpImmunityLoss,
+    HelpDropRate,
+    HelpFeedRate,
+    HelpKillRate,
+    HelpStates,
+    HelpThreshold,
+    HelpRule,
+    HelpRule3D,
+    HelpBoids,
+    HelpAnts,
+    HelpEvaporation,
+    HelpTemperature,
+    HelpRelaxation,
+    HelpInflow,
+    HelpTurmites,
+    HelpTurmiteTable,
+    HelpTreeDensity,
+    HelpGrowth,
+    HelpLightning,
+    HelpHelp,
+
+    UnknownDemo,
+    UnknownSimulation,
+    UnknownLanguage,
+    InvalidValue,
+    InvalidProbability,
+    InvalidSize,
+    ValueTooSmall,
+    ValueTooLarge,
+    InvalidStopCriterion,
+    InvalidSeedPopulation,
+    SeedPopulationNotSupported,
+    SeedPopulationWrongSimulation,
+    TooManySpecimens,
+    TooManySpecimensForWalls,
+    TooManyInfected,
+    PatternFileNotSupported,
+    InitNotSupported,
+    DepthNotSupported,
+    ScriptNotSupported,
+    RegionOutsideBoard,
+    PatternOffsetOutsideBoard,
+    InvalidEvent,
+    UnknownBoundary,
+    UnknownMovement,
+    UnknownUpdateOrder,
+    UnknownConflictResolver,
+    ConflictingWind,
+    CrashBundleWritten,
+    CrashBundleFailed,
+
+    MustBeFinite,
+    MustBeFiniteNonNegative,
+    MustBePositive,
+    MustBeFraction,
+    MustBeAboveHalf,
+    DeadlyMarginRange,
+    RecordVideoConflict,
+    ExpectedFormat,
+    ExpectedInit,
+    ExpectedStorage,
+    ExpectedPalette,
+    ExpectedAspect,
+    ExpectedUps,
+    ExpectedRender,
+    ExpectedChecks,
+    ExpectedActionCosts,
+    ExpectedInitialEnergy,
+    ExpectedRule3d,
+
+    LayerShown,
+    PaletteChanged,
+    LayerVisible,
+    LayerHidden,
+    UpdatesPerSecond,
+    UpdatesPerSecondMax,
+    RecordingStarted,
+    RecordingStopped,
+    RestartFailed,
+    RunRecordingStopped,
+    Restarted,
+    BrushTip,
+    PickSpecimen,
+    NoSpecimenThere,
+    FollowedSpecimenGone,
+    CellsCopied,
+    CopyNotSupported,
+    PatternCropped,
+    SweepResumed,
+    SweepSummary,
+
+    HudIteration,
+    HudPopulation,
+    HudFps,
+    HudUps,
+    HudSpeed,
+    HudSpeedMax,
+    HudFollowing,
+    HudAge,
+    HudLineage,
+    HudCell,
+    CellAlive,
+    CellDead,
+    CellEmpty,
+    CellWall,
+    CellSpecimen,
+    CellDormantSpecimen,
+    CellEnergy,
+    CellAge,
+    CellSpecies,
+    CellGeneration,
+    CellFood,
+    CellGrains,
+    CellDensity,
+    CellVelocity,
+    CellObstacle,
+    CellPheromone,
+    CellNest,
+
+    DemoLifeGliderGun,
+    DemoGoodEvilBoomBust,
+    DemoFireFront,
+    DemoEpidemicWave
+}
+
+impl Language {
+    pub fn from_code(code: &str) -> Option<Language> {
+        match code {
+            "en" => Some(Language::English),
+            "pl" => Some(Language::Polish),
+            _ => None
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match *self {
+            Language::English => "en",
+            Language::Polish => "pl"
+        }
+    }
+
+    /// Picks the language from the LANG environment variable, falling
+    /// back to English.
+    pub fn from_env() -> Language {
+        env::var("LANG").ok()
+                        .and_then(|lang| Language::from_code(&lang.chars().take(2).collect::<String>()))
+                        .unwrap_or(Language::English)
+    }
+
+    pub fn tr(&self,
+              msg: Message) -> &'static str {
+        match *self {
+            Language::English => english(msg),
+            Language::Polish => polish(msg)
+        }
+    }
+
+    pub fn format(&self,
+                  msg: Message,
+                  args: &[&str]) -> String {
+        let mut result = String::new();
+        let mut args = args.iter();
+
+        for (idx, part) in self.tr(msg).split("{}").enumerate() {
+            if idx > 0 {
+                result.push_str(args.next().unwrap_or(&""));
+            }
+            result.push_str(part);
+        }
+
+        result
+    }
+}
+
+fn english(msg: Message) -> &'static str {
+    match msg {
+        Message::Usage => "Usage: {} [options]",
+        Message::AnalyzeUsage => "Usage: {} analyze DIR [options]\n\nWrites an HTML report comparing the runs of a sweep in DIR.",
+        Message::HelpAnalyzeOutput => "path of the report (default: DIR/report.html)",
+        Message::DemosHeader => "Demos:",
+        Message::Configuration => "Configuration:",
+        Message::EffectiveConfig => "Effective configuration:",
+        Message::EstimatedMemory => "Estimated memory usage of the simulation: {}",
+        Message::MissingOutputDir => "--{}: directory {} does not exist",
+        Message::ConfigOk => "Configuration OK",
+
+        Message::HelpBoardSize => "set board size; life3d boards also have a depth (default 16)",
+        Message::HelpSim => "simulation to run (default goodevil): {}",
+        Message::HelpSeed => "seed for the random number generator",
+        Message::HelpDemo => "start one of the predefined demos (see below)",
+        Message::HelpLang => "language of the user interface: en or pl",
+        Message::HelpMaxStepsPerFrame => "steps done at most per frame while catching up after a stall (default 200)",
+        Message::HelpDropLag => "drop time that couldn't be caught up with instead of catching up later; the total shows as dropped_time in stats",
+        Message::HelpUps => "target updates per second (default 100), or max to run as fast as possible; [ and ] halve and double it, M toggles max",
+        Message::HelpHeadless => "run without a window and print a JSON outcome at the end",
+        Message::HelpProfile => "print the time spent rendering, updating and in each phase of the simulation's steps every second",
+        Message::HelpBench => "time every simulation on boards of 64x64, 256x256 and 1024x1024 cells and print steps per second and timings of each phase",
+        Message::HelpRender => "draw the simulation in a window (default) or as colored characters in the terminal (tui); Esc or Q quits the terminal view",
+        Message::HelpChecks => "internal invariants verified every step: off, cheap (default), expensive (energy audits) or paranoid (every step is also replayed and board digests compared)",
+        Message::HelpIterations => "headless: number of steps to run (default 1000)",
+        Message::HelpStopWhen => "headless: stop early once a stats value crosses a threshold, e.g. infected<1",
+        Message::HelpManifest => "write a JSON summary of the run, including surviving specimens, to PATH",
+        Message::HelpConfig => "load options from a TOML or JSON file; options given on the command line take precedence",
+        Message::HelpDumpConfig => "write the effective configuration to PATH, as JSON if it ends with .json, TOML otherwise",
+        Message::HelpSeedPopulation => "goodevil: start from specimens that survived a run saved with --manifest",
+        Message::HelpEvent => "goodevil: schedule a world event: meteor (PARAM = radius), famine (PARAM = duration) or bloom (PARAM = energy); may be repeated",
+        Message::HelpRandomEvents => "goodevil: probability of a random world event per step",
+        Message::HelpBoundary => "goodevil: board edges (default closed); with open edges specimens can leave and immigrate",
+        Message::HelpMovement => "goodevil: how specimens move (default random); gradient seeks food and weaker specimens and avoids stronger ones",
+        Message::HelpUpdateOrder => "goodevil: order in which specimens move each step (default row-major)",
+        Message::HelpConflicts => "goodevil: who gets a cell more specimens try to move into (default strongest)",
+        Message::HelpFairnessAudit => "goodevil: report conflict win rates in REGIONS x REGIONS board regions at the end of a headless run",
+        Message::HelpSpecimens => "goodevil: number of specimens (default: one per 20 cells)",
+        Message::HelpSpecimenEnergy => "goodevil: initial energy of specimens (default 1)",
+        Message::HelpEnergyLoss => "goodevil: energy lost by every specimen per step (default 0.001)",
+        Message::HelpDeadlyMargin => "goodevil: specimens with less energy die (default 0)",
+        Message::HelpActionCosts => "goodevil: energy spent on staying, orthogonal and diagonal moves, on top of the loss per step",
+        Message::HelpInitialEnergy => "goodevil: initial energy of specimens: uniform:MIN,MAX, normal:MEAN,STDEV,MIN,MAX or bimodal:POOR,RICH,FRACTION",
+        Message::HelpImmigration => "goodevil: average number of specimens entering through each open edge per step",
+        Message::HelpImmigrantEnergy => "goodevil: energy of immigrating specimens (default 1)",
+        Message::HelpWind => "goodevil: uniform wind biasing the direction of movement",
+        Message::HelpWindNoise => "goodevil: smooth random wind of given strength",
+        Message::HelpWindFile => "goodevil: load a per-cell wind field, one row of DX,DY vectors per line",
+        Message::HelpShowWind => "draw wind streamlines (toggled with W)",
+        Message::HelpReputation => "goodevil: track specimen reputation and share energy only with cooperators",
+        Message::HelpDefection => "goodevil: probability of defecting regardless of reputation (default 0.05)",
+        Message::HelpReputationDecay => "goodevil: fraction of reputation lost each step (default 0.01)",
+        Message::HelpPredation => "goodevil: in collisions, specimens with MARGIN more energy eat weaker ones",
+        Message::HelpPredationEfficiency => "goodevil: fraction of the prey's energy absorbed by a predator (default 0.5)",
+        Message::HelpSpecies => "goodevil: species interaction matrix, rows separated with ';', e.g. share,fight;fight,share (share, fight, ignore or convert)",
+        Message::HelpMaxCollision => "goodevil: maximum number of specimens in one collision, others are deflected",
+        Message::HelpInteractionRadius => "goodevil: distance within which specimens interact without sharing a cell",
+        Message::HelpPatternFile => "life: load the initial pattern from an RLE (.rle), plaintext (.cells) or Golly macrocell (.mc) file, with its top left corner at X,Y if given; goodevil: load walls from it; can be given multiple times",
+        Message::HelpPatternOffset => "place the top left corner of the --pattern-file pattern at given cell instead of centering it",
+        Message::HelpInit => "initial state: random:DENSITY, stripes:WIDTH, cluster:COUNT or center (default: depends on the simulation)",
+        Message::HelpStorage => "life: board storage: dense, sparse (only live cells, for huge mostly empty boards) or auto (default)",
+        Message::HelpScript => "life: Rhai script defining next(alive, neighbors); goodevil: one defining choose_move(energy, x, y, neighbors)",
+        Message::HelpMutationRate => "goodevil: enable reproduction with heritable traits, mutating with given probability",
+        Message::HelpMutationSize => "goodevil: largest change of a trait in a single mutation (default 0.1)",
+        Message::HelpDormancy => "goodevil: specimens below ENERGY with nothing to feed on nearby go dormant",
+        Message::HelpDormantLoss => "goodevil: fraction of the energy loss per step paid by dormant specimens (default 0.1)",
+        Message::HelpCrowding => "goodevil: extra energy lost per step for every neighbor above the capacity",
+        Message::HelpCrowdingCapacity => "goodevil: number of neighbors tolerated without crowding penalty (default 2)",
+        Message::HelpShowDensity => "draw local density of specimens (toggled with C)",
+        Message::HelpGridLines => "draw lines between cells at least 8 pixels wide (toggled with G)",
+        Message::HelpAspect => "fit the board to the window: stretch (default), letterbox, fit-width or fit-height; all but stretch keep cells square",
+        Message::HelpReproduction => "goodevil: let specimens with enough energy split off children (implied by --mutation-rate)",
+        Message::HelpReproductionThreshold => "goodevil: energy above which a specimen reproduces (default 1.5), unless genetics are enabled",
+        Message::HelpChildShare => "goodevil: fraction of the parent's energy given to a child (default 0.5)",
+        Message::HelpReproductionCooldown => "goodevil: steps before a specimen can reproduce again (default 10)",
+        Message::HelpSymmetry => "print the symmetry group of the final board (press Y to print it any time)",
+        Message::HelpSymmetryRegion => "analyze symmetry of given region instead of the pattern's bounding box",
+        Message::HelpCountRegion => "add the number of occupied cells in given region to stats, as region",
+        Message::HelpRecordFrames => "write a PPM frame of the board to given directory after every step",
+        Message::HelpRecordVideo => "record the run as an animated GIF (*.gif) or, through ffmpeg, a video in any other format",
+        Message::HelpRecordFps => "frames per second of recorded GIFs and videos (default: 25)",
+        Message::HelpRecordOnKey => "start recording paused; V starts and stops it",
+        Message::HelpRecordRegion => "record only given region of the board",
+        Message::HelpRecordScale => "pixels per cell side in recorded frames (default: 4)",
+        Message::HelpExposure => "write long-exposure images of cell occupancy frequency to given directory",
+        Message::HelpExposureWindow => "steps summed up in each long-exposure image (default: 100)",
+        Message::HelpEventLog => "write births, deaths, collisions and moves to given file as JSON lines",
+        Message::HelpEventLogMaxSize => "rotate the event log when it grows past given size, writing half as many events after every rotation",
+        Message::HelpEventLogFiles => "number of rotated event log files kept (default 3)",
+        Message::HelpStatsLog => "write stats after every step to given file as JSON lines",
+        Message::HelpMetricsCsv => "write metrics after every step to given file as CSV",
+        Message::HelpMetricsListen => "serve metrics of the last step in the Prometheus text format on given address, e.g. 127.0.0.1:9100",
+        Message::HelpHudMetrics => "comma-separated names of metrics to show in the HUD",
+        Message::HelpPlotLength => "number of last steps plotted at the bottom of the window, toggled with P (default: 500)",
+        Message::HelpEnergyHistogram => "write a histogram of specimen energies with given number of buckets to the stats log after every step; E shows it in the window (default: 16 buckets)",
+        Message::HelpTheme => "load background, grid, overlay and cell colors from a theme file",
+        Message::HelpPalette => "draw scalar cells (pheromone, density, energy) with a color map: default, viridis, inferno or jet; H switches it at runtime",
+        Message::HelpNotebook => "append the current parameters and a thumbnail to a Markdown lab notebook when N is pressed",
+        Message::HelpSnapshotEvery => "save a PNG image of the board every N steps; F12 saves one at any time",
+        Message::HelpSnapshotPath => "file name of PNG snapshots, with {iteration} replaced by the iteration (default snapshot-{iteration}.png)",
+        Message::HelpMerge => "merge a region file into the board at given iteration and position; POLICY is overwrite (default), skip-occupied or merge-energy; may be repeated",
+        Message::HelpRecord => "record options, seed and inputs of the run to given file",
+        Message::HelpReplay => "replay a run recorded with --record, ignoring other options",
+        Message::HelpSpeedRamp => "change playback and recording speed at given iterations, e.g. 0:1,90:0.25,120:4",
+        Message::HelpFood => "goodevil: feed specimens with food growing on the board, starting on given fraction of cells",
+        Message::HelpFoodRegrowth => "goodevil: amount of food growing back on an empty cell per step (default 0.01)",
+        Message::HelpFoodEnergy => "goodevil: energy gained from a full cell of food (default 0.05)",
+        Message::HelpCrashDir => "directory for diagnostics bundles written on a crash (default: current)",
+        Message::HelpSweep => "run a headless job for every combination of --sweep-param values, with manifests and logs in given directory; resumes the sweep already there",
+        Message::HelpSweepParam => "option to sweep over and its values, e.g. energy-loss=0.001,0.002; can be given multiple times",
+        Message::HelpSweepThreads => "number of sweep jobs run at the same time (default: 1)",
+        Message::HelpVerifyReplay => "headless: replay every TICKS ticks from a snapshot and fail if the result differs",
+        Message::HelpTransmission => "epidemic: probability of infection by a single infected neighbor per step",
+        Message::HelpIncubation => "epidemic: number of steps spent in the exposed state (enables the SEIR model)",
+        Message::HelpInfectionDuration => "epidemic: number of steps an infection lasts",
+        Message::HelpImmunityLoss => "epidemic: probability of a recovered cell becoming susceptible per step",
+        Message::HelpDropRate => "sandpile: average number of grains dropped per step",
+        Message::HelpFeedRate => "gray-scott: feed rate of chemical U",
+        Message::HelpKillRate => "gray-scott: kill rate of chemical V",
+        Message::HelpStates => "cyclic: number of states",
+        Message::HelpThreshold => "cyclic: number of neighbors in the successor state needed to advance",
+        Message::HelpRule => "elementary: rule number (default 30)",
+        Message::HelpRule3D => "life3d: neighbor counts out of 26 a cell is born and survives with (default B5/S4,5)",
+        Message::HelpBoids => "boids: number of boids (default 150)",
+        Message::HelpAnts => "ants: number of ants (default 100)",
+        Message::HelpEvaporation => "ants: fraction of pheromone evaporating per step (default 0.01)",
+        Message::HelpTemperature => "ising: initial temperature, changed with +/- (default 2.27)",
+        Message::HelpRelaxation => "fluid: relaxation time, above 0.5; higher is more viscous (default 0.7)",
+        Message::HelpInflow => "fluid: velocity of the flow entering through the left edge (default 0.1)",
+        Message::HelpTurmites => "turmites: number of turmites (default 1)",
+        Message::HelpTurmiteTable => "turmites: load the transition table, one STATE COLOR WRITE TURN NEXT_STATE rule per line",
+        Message::HelpTreeDensity => "forest-fire: fraction of cells initially covered with trees (default 0.65)",
+        Message::HelpGrowth => "forest-fire: probability of a tree growing on an empty cell per step (default 0)",
+        Message::HelpLightning => "forest-fire: probability of a tree catching fire by itself per step (default 0)",
+        Message::HelpHelp => "print this help message",
+
+        Message::UnknownDemo => "unknown demo: {}, available demos:\n{}",
+        Message::UnknownSimulation => "unknown simulation: {}",
+        Message::UnknownLanguage => "unknown language: {}, expected en or pl",
+        Message::InvalidValue => "invalid value of --{}: {}: {}",
+        Message::InvalidProbability => "invalid value of --{}: {}, expected a number in range [0, 1]",
+        Message::InvalidSize => "invalid argument format: {}, expected SIZE, WIDTH,HEIGHT or WIDTH,HEIGHT,DEPTH",
+        Message::ValueTooSmall => "invalid value of --{}: must be at least {}",
+        Message::ValueTooLarge => "invalid value of --{}: must be at most {}",
+        Message::InvalidStopCriterion => "invalid stop criterion: {}, expected NAME<VALUE or NAME>VALUE",
+        Message::InvalidSeedPopulation => "invalid seed population: {}, expected from-run PATH",
+        Message::SeedPopulationNotSupported => "--seed-population can only be used with --sim goodevil",
+        Message::SeedPopulationWrongSimulation => "{}: specimens can only be seeded from a goodevil run, not {}",
+        Message::TooManySpecimens => "{}: {} specimens do not fit on a {} board",
+        Message::TooManySpecimensForWalls => "{} specimens do not fit on the {} cells left free by --pattern-file walls",
+        Message::TooManyInfected => "{} initially infected cells do not fit on a {} board",
+        Message::PatternFileNotSupported => "--pattern-file can only be used with --sim life or goodevil",
+        Message::InitNotSupported => "--init can't be used with --sim cyclic, falling-sand, ants, fluid, forest-fire or life3d",
+        Message::DepthNotSupported => "a board depth can only be given with --sim life3d",
+        Message::ScriptNotSupported => "--script can only be used with --sim life or goodevil",
+        Message::RegionOutsideBoard => "region {} does not fit on a {} board",
+        Message::PatternOffsetOutsideBoard => "pattern offset {} lies outside a {} board",
+        Message::InvalidEvent => "invalid world event: {}, expected meteor, famine or bloom as NAME@ITERATION[:PARAM]",
+        Message::UnknownBoundary => "unknown boundary: {}, expected closed or open",
+        Message::UnknownMovement => "unknown movement policy: {}, expected random or gradient",
+        Message::UnknownUpdateOrder => "unknown update order: {}, expected row-major or shuffled",
+        Message::UnknownConflictResolver => "unknown conflict resolver: {}, expected strongest, random or first-come",
+        Message::ConflictingWind => "only one of --wind, --wind-noise and --wind-file can be used",
+        Message::CrashBundleWritten => "the simulation crashed, diagnostics written to {} - please attach them to the bug report",
+        Message::CrashBundleFailed => "the simulation crashed, but diagnostics could not be written: {}",
+
+        Message::MustBeFinite => "must be finite",
+        Message::MustBeFiniteNonNegative => "must be finite and not negative",
+        Message::MustBePositive => "must be positive",
+        Message::MustBeFraction => "must be between 0 and 1",
+        Message::MustBeAboveHalf => "must be above 0.5",
+        Message::DeadlyMarginRange => "must be non-negative and below the initial specimen energy",
+        Message::RecordVideoConflict => "cannot be combined with --record-frames",
+        Message::ExpectedFormat => "expected {}",
+        Message::ExpectedInit => "expected random:DENSITY, stripes:WIDTH, cluster:COUNT or center",
+        Message::ExpectedStorage => "expected dense, sparse or auto",
+        Message::ExpectedPalette => "expected default, viridis, inferno or jet",
+        Message::ExpectedAspect => "expected stretch, letterbox, fit-width or fit-height",
+        Message::ExpectedUps => "expected max or a rate between {} and {}",
+        Message::ExpectedRender => "expected window or tui",
+        Message::ExpectedChecks => "expected off, cheap, expensive or paranoid",
+        Message::ExpectedActionCosts => "expected three non-negative numbers: STAY,ORTHOGONAL,DIAGONAL",
+        Message::ExpectedInitialEnergy => "expected uniform:MIN,MAX, normal:MEAN,STDEV,MIN,MAX or bimodal:POOR,RICH,FRACTION",
+        Message::ExpectedRule3d => "expected BCOUNTS/SCOUNTS with comma separated counts up to 26, e.g. B5/S4,5",
+
+        Message::LayerShown => "layer {}",
+        Message::PaletteChanged => "palette {}",
+        Message::LayerVisible => "{} shown",
+        Message::LayerHidden => "{} hidden",
+        Message::UpdatesPerSecond => "updates per second: {}",
+        Message::UpdatesPerSecondMax => "updates per second: max",
+        Message::RecordingStarted => "recording started",
+        Message::RecordingStopped => "recording stopped",
+        Message::RestartFailed => "restart failed: {}",
+        Message::RunRecordingStopped => "run recording stopped",
+        Message::Restarted => "restarted with board size {}, {} and seed {}",
+        Message::BrushTip => "brush: {}, radius {}",
+        Message::PickSpecimen => "click a specimen to follow it",
+        Message::NoSpecimenThere => "no specimen there",
+        Message::FollowedSpecimenGone => "followed specimen is gone",
+        Message::CellsCopied => "copied {}x{} cells",
+        Message::CopyNotSupported => "{} can't be copied from",
+        Message::PatternCropped => "{}: {}x{} pattern cropped to its middle {}x{} cells",
+        Message::SweepResumed => "resuming the sweep in {} with the options it was started with",
+        Message::SweepSummary => "sweep: {} completed, {} skipped, {} failed",
+
+        Message::HudIteration => "iteration {}",
+        Message::HudPopulation => "population {}",
+        Message::HudFps => "fps {}",
+        Message::HudUps => "ups {}",
+        Message::HudSpeed => "speed x{}",
+        Message::HudSpeedMax => "speed max",
+        Message::HudFollowing => "following: energy {}",
+        Message::HudAge => "age {}",
+        Message::HudLineage => "generation {}, species {}",
+        Message::HudCell => "cell {}, {}",
+        Message::CellAlive => "alive",
+        Message::CellDead => "dead",
+        Message::CellEmpty => "empty",
+        Message::CellWall => "wall",
+        Message::CellSpecimen => "specimen",
+        Message::CellDormantSpecimen => "dormant specimen",
+        Message::CellEnergy => "energy {}",
+        Message::CellAge => "age {}",
+        Message::CellSpecies => "species {}",
+        Message::CellGeneration => "generation {}",
+        Message::CellFood => "food {}",
+        Message::CellGrains => "grains {}",
+        Message::CellDensity => "density {}",
+        Message::CellVelocity => "velocity {}, {}",
+        Message::CellObstacle => "obstacle",
+        Message::CellPheromone => "pheromone {}",
+        Message::CellNest => "nest",
+
+        Message::DemoLifeGliderGun => "Gosper glider gun emitting a stream of gliders",
+        Message::DemoGoodEvilBoomBust => "GoodEvil board collapsing to a few strong specimens",
+        Message::DemoFireFront => "fire front sweeping a forest from its left edge",
+        Message::DemoEpidemicWave => "infection spreading from a few initial cases as a circular wave"
+    }
+}
+
+fn polish(msg: Message) -> &'static str {
+    match msg {
+        Message::Usage => "Użycie: {} [opcje]",
+        Message::AnalyzeUsage => "Użycie: {} analyze KATALOG [opcje]\n\nZapisuje raport HTML porównujący przebiegi przeglądu z KATALOGU.",
+        Message::HelpAnalyzeOutput => "ścieżka raportu (domyślnie: KATALOG/report.html)",
+        Message::DemosHeader => "Dema:",
+        Message::Configuration => "Konfiguracja:",
+        Message::EffectiveConfig => "Efektywna konfiguracja:",
+        Message::EstimatedMemory => "Szacowane zużycie pamięci przez symulację: {}",
+        Message::MissingOutputDir => "--{}: katalog {} nie istnieje",
+        Message::ConfigOk => "Konfiguracja poprawna",
+
+        Message::HelpBoardSize => "rozmiar planszy; plansze life3d mają też głębokość (domyślnie 16)",
+        Message::HelpSim => "symulacja do uruchomienia (domyślnie goodevil): {}",
+        Message::HelpSeed => "ziarno generatora liczb losowych",
+        Message::HelpDemo => "uruchom jedno z przygotowanych dem (lista poniżej)",
+        Message::HelpLang => "język interfejsu: en lub pl",
+        Message::HelpMaxStepsPerFrame => "maksymalna liczba kroków na klatkę podczas nadrabiania po przestoju (domyślnie 200)",
+        Message::HelpDropLag => "porzuć czas, którego nie udało się nadrobić, zamiast nadrabiać go później; suma widoczna jako dropped_time w statystykach",
+        Message::HelpUps => "docelowa liczba aktualizacji na sekundę (domyślnie 100) lub max, by działać najszybciej jak się da; [ i ] zmniejszają i zwiększają ją dwukrotnie, M przełącza max",
+        Message::HelpHeadless => "uruchom bez okna i wypisz wynik w formacie JSON na końcu",
+        Message::HelpProfile => "co sekundę wypisuj czas rysowania, aktualizacji i poszczególnych faz kroków symulacji",
+        Message::HelpBench => "zmierz czas działania każdej symulacji na planszach 64x64, 256x256 i 1024x1024 i wypisz liczbę kroków na sekundę oraz czasy poszczególnych faz",
+        Message::HelpRender => "rysuj symulację w oknie (domyślnie) lub jako kolorowe znaki w terminalu (tui); Esc lub Q zamyka widok terminalowy",
+        Message::HelpChecks => "niezmienniki sprawdzane w każdym kroku: off, cheap (domyślnie), expensive (bilans energii) lub paranoid (każdy krok jest dodatkowo powtarzany i porównywane są skróty planszy)",
+        Message::HelpIterations => "tryb bez okna: liczba kroków (domyślnie 1000)",
+        Message::HelpStopWhen => "tryb bez okna: zatrzymaj, gdy statystyka przekroczy próg, np. infected<1",
+        Message::HelpManifest => "zapisz podsumowanie przebiegu w formacie JSON, razem z ocalałymi osobnikami, do pliku PATH",
+        Message::HelpConfig => "wczytaj opcje z pliku TOML lub JSON; opcje podane w linii poleceń mają pierwszeństwo",
+        Message::HelpDumpConfig => "zapisz użytą konfigurację do pliku PATH, jako JSON jeśli kończy się na .json, w przeciwnym razie jako TOML",
+        Message::HelpSeedPopulation => "goodevil: zacznij od osobników, które przetrwały przebieg zapisany przez --manifest",
+        Message::HelpEvent => "goodevil: zaplanuj zdarzenie: meteor (PARAM = promień), famine (PARAM = czas trwania) lub bloom (PARAM = energia); można powtarzać",
+        Message::HelpRandomEvents => "goodevil: prawdopodobieństwo losowego zdarzenia w kroku",
+        Message::HelpBoundary => "goodevil: brzegi planszy (domyślnie closed); przez otwarte brzegi osobniki mogą odchodzić i napływać",
+        Message::HelpMovement => "goodevil: sposób poruszania się osobników (domyślnie random); gradient kieruje je do pożywienia i słabszych osobników, z dala od silniejszych",
+        Message::HelpUpdateOrder => "goodevil: kolejność, w jakiej osobniki poruszają się w każdym kroku (domyślnie row-major)",
+        Message::HelpConflicts => "goodevil: kto zajmuje pole, na które próbuje wejść kilka osobników (domyślnie strongest)",
+        Message::HelpFairnessAudit => "goodevil: wypisz na końcu przebiegu bez okna odsetek wygranych konfliktów w REGIONS x REGIONS obszarach planszy",
+        Message::HelpSpecimens => "goodevil: liczba osobników (domyślnie jeden na 20 komórek)",
+        Message::HelpSpecimenEnergy => "goodevil: początkowa energia osobników (domyślnie 1)",
+        Message::HelpEnergyLoss => "goodevil: energia tracona przez każdego osobnika w kroku (domyślnie 0.001)",
+        Message::HelpDeadlyMargin => "goodevil: osobniki z mniejszą energią giną (domyślnie 0)",
+        Message::HelpActionCosts => "goodevil: energia zużywana na pozostanie w miejscu, ruch prosty i po skosie, niezależnie od straty w każdym kroku",
+        Message::HelpInitialEnergy => "goodevil: początkowa energia osobników: uniform:MIN,MAX, normal:ŚREDNIA,ODCHYLENIE,MIN,MAX lub bimodal:BIEDNI,BOGACI,UŁAMEK",
+        Message::HelpImmigration => "goodevil: średnia liczba osobników napływających przez każdy otwarty brzeg w kroku",
+        Message::HelpImmigrantEnergy => "goodevil: energia napływających osobników (domyślnie 1)",
+        Message::HelpWind => "goodevil: jednorodny wiatr wpływający na kierunek ruchu",
+        Message::HelpWindNoise => "goodevil: gładki losowy wiatr o zadanej sile",
+        Message::HelpWindFile => "goodevil: wczytaj pole wiatru, jeden wiersz wektorów DX,DY na linię",
+        Message::HelpShowWind => "rysuj linie prądu wiatru (przełączane klawiszem W)",
+        Message::HelpReputation => "goodevil: śledź reputację osobników i dziel energię tylko ze współpracującymi",
+        Message::HelpDefection => "goodevil: prawdopodobieństwo zdrady niezależnie od reputacji (domyślnie 0.05)",
+        Message::HelpReputationDecay => "goodevil: część reputacji tracona w każdym kroku (domyślnie 0.01)",
+        Message::HelpPredation => "goodevil: w zderzeniach osobniki o MARGIN więcej energii zjadają słabsze",
+        Message::HelpPredationEfficiency => "goodevil: część energii ofiary przejmowana przez drapieżnika (domyślnie 0.5)",
+        Message::HelpSpecies => "goodevil: macierz interakcji gatunków, wiersze oddzielone ';', np. share,fight;fight,share (share, fight, ignore lub convert)",
+        Message::HelpMaxCollision => "goodevil: maksymalna liczba osobników w jednym zderzeniu, pozostałe są odbijane",
+        Message::HelpInteractionRadius => "goodevil: odległość, w której osobniki oddziałują na siebie bez zajmowania tej samej komórki",
+        Message::HelpPatternFile => "life: wczytaj początkowy wzór z pliku w formacie RLE (.rle), tekstowym (.cells) lub macrocell (.mc) programu Golly, z lewym górnym rogiem w X,Y, jeśli podano; goodevil: wczytaj z niego ściany; można podać wiele razy",
+        Message::HelpPatternOffset => "umieść lewy górny róg wzoru z --pattern-file w danej komórce zamiast na środku",
+        Message::HelpInit => "stan początkowy: random:GĘSTOŚĆ, stripes:SZEROKOŚĆ, cluster:LICZBA lub center (domyślnie: zależy od symulacji)",
+        Message::HelpStorage => "life: przechowywanie planszy: dense, sparse (tylko żywe komórki, dla ogromnych, prawie pustych plansz) lub auto (domyślnie)",
+        Message::HelpScript => "life: skrypt Rhai definiujący next(alive, neighbors); goodevil: skrypt definiujący choose_move(energy, x, y, neighbors)",
+        Message::HelpMutationRate => "goodevil: włącz rozmnażanie z dziedziczonymi cechami, mutującymi z danym prawdopodobieństwem",
+        Message::HelpMutationSize => "goodevil: największa zmiana cechy w pojedynczej mutacji (domyślnie 0.1)",
+        Message::HelpDormancy => "goodevil: osobniki poniżej ENERGY, które nie mają w pobliżu pożywienia, zapadają w stan uśpienia",
+        Message::HelpDormantLoss => "goodevil: część straty energii w każdym kroku ponoszona przez uśpione osobniki (domyślnie 0.1)",
+        Message::HelpCrowding => "goodevil: dodatkowa strata energii w każdym kroku za każdego sąsiada ponad pojemność",
+        Message::HelpCrowdingCapacity => "goodevil: liczba sąsiadów tolerowana bez kary za tłok (domyślnie 2)",
+        Message::HelpShowDensity => "rysuj lokalne zagęszczenie osobników (przełączane klawiszem C)",
+        Message::HelpGridLines => "rysuj linie między komórkami szerokimi na co najmniej 8 pikseli (przełączane klawiszem G)",
+        Message::HelpAspect => "dopasowanie planszy do okna: stretch (domyślnie), letterbox, fit-width lub fit-height; wszystkie poza stretch zachowują kwadratowe komórki",
+        Message::HelpReproduction => "goodevil: osobniki z dostatkiem energii wydają potomstwo (włączane też przez --mutation-rate)",
+        Message::HelpReproductionThreshold => "goodevil: energia, powyżej której osobnik się rozmnaża (domyślnie 1.5), o ile genetyka jest wyłączona",
+        Message::HelpChildShare => "goodevil: część energii rodzica przekazywana potomkowi (domyślnie 0.5)",
+        Message::HelpReproductionCooldown => "goodevil: liczba kroków, po których osobnik może znów się rozmnożyć (domyślnie 10)",
+        Message::HelpSymmetry => "wypisz grupę symetrii końcowej planszy (klawisz Y wypisuje ją w dowolnej chwili)",
+        Message::HelpSymmetryRegion => "badaj symetrię danego obszaru zamiast prostokąta otaczającego wzór",
+        Message::HelpCountRegion => "dodaj do statystyk liczbę zajętych pól w danym obszarze, jako region",
+        Message::HelpRecordFrames => "zapisuj klatkę PPM planszy do danego katalogu po każdym kroku",
+        Message::HelpRecordVideo => "nagraj przebieg jako animowany GIF (*.gif) lub, przez ffmpeg, film w dowolnym innym formacie",
+        Message::HelpRecordFps => "liczba klatek na sekundę nagrywanych GIF-ów i filmów (domyślnie: 25)",
+        Message::HelpRecordOnKey => "zacznij z wstrzymanym nagrywaniem; V je włącza i wyłącza",
+        Message::HelpRecordRegion => "nagrywaj tylko dany obszar planszy",
+        Message::HelpRecordScale => "liczba pikseli na bok komórki w nagranych klatkach (domyślnie: 4)",
+        Message::HelpExposure => "zapisuj obrazy częstości zajęcia komórek z długim czasem naświetlania do danego katalogu",
+        Message::HelpExposureWindow => "liczba kroków podsumowanych w każdym obrazie z długim naświetlaniem (domyślnie: 100)",
+        Message::HelpEventLog => "zapisuj narodziny, śmierci, kolizje i ruchy do danego pliku jako linie JSON",
+        Message::HelpEventLogMaxSize => "zmieniaj plik dziennika zdarzeń, gdy przekroczy dany rozmiar, i po każdej zmianie zapisuj o połowę mniej zdarzeń",
+        Message::HelpEventLogFiles => "liczba zachowywanych starszych plików dziennika zdarzeń (domyślnie 3)",
+        Message::HelpStatsLog => "zapisuj statystyki po każdym kroku do danego pliku jako linie JSON",
+        Message::HelpMetricsCsv => "zapisuj metryki po każdym kroku do danego pliku jako CSV",
+        Message::HelpMetricsListen => "udostępniaj metryki ostatniego kroku w formacie tekstowym Prometheusa pod danym adresem, np. 127.0.0.1:9100",
+        Message::HelpHudMetrics => "nazwy metryk do pokazania w HUD-zie, oddzielone przecinkami",
+        Message::HelpPlotLength => "liczba ostatnich kroków na wykresie u dołu okna, przełączanym klawiszem P (domyślnie: 500)",
+        Message::HelpEnergyHistogram => "zapisuj po każdym kroku do logu statystyk histogram energii osobników o danej liczbie przedziałów; E pokazuje go w oknie (domyślnie: 16 przedziałów)",
+        Message::HelpTheme => "wczytaj kolory tła, siatki, nakładek i komórek z pliku motywu",
+        Message::HelpPalette => "rysuj komórki skalarne (feromon, gęstość, energia) mapą kolorów: default, viridis, inferno lub jet; H przełącza ją w trakcie działania",
+        Message::HelpNotebook => "po wciśnięciu N dopisz bieżące parametry i miniaturę do notatnika laboratoryjnego w formacie Markdown",
+        Message::HelpSnapshotEvery => "zapisuj obraz PNG planszy co N kroków; F12 zapisuje go w dowolnej chwili",
+        Message::HelpSnapshotPath => "nazwa plików PNG, w której {iteration} zastępowane jest numerem iteracji (domyślnie snapshot-{iteration}.png)",
+        Message::HelpMerge => "wstaw region z pliku do planszy w danej iteracji i miejscu; POLICY to overwrite (domyślnie), skip-occupied lub merge-energy; można powtarzać",
+        Message::HelpRecord => "nagraj opcje, ziarno i działania użytkownika do danego pliku",
+        Message::HelpReplay => "odtwórz przebieg nagrany przez --record, ignorując inne opcje",
+        Message::HelpSpeedRamp => "zmieniaj szybkość odtwarzania i nagrywania w danych iteracjach, np. 0:1,90:0.25,120:4",
+        Message::HelpFood => "goodevil: karm osobniki pożywieniem rosnącym na planszy, początkowo na danej części pól",
+        Message::HelpFoodRegrowth => "goodevil: ilość pożywienia odrastająca na pustym polu w kroku (domyślnie 0.01)",
+        Message::HelpFoodEnergy => "goodevil: energia z pola pełnego pożywienia (domyślnie 0.05)",
+        Message::HelpCrashDir => "katalog na pakiety diagnostyczne zapisywane po awarii (domyślnie bieżący)",
+        Message::HelpSweep => "uruchom zadanie bez okna dla każdej kombinacji wartości --sweep-param, z manifestami i logami w danym katalogu; wznawia znajdujący się tam przegląd",
+        Message::HelpSweepParam => "opcja, po której odbywa się przegląd, i jej wartości, np. energy-loss=0.001,0.002; można podać wiele razy",
+        Message::HelpSweepThreads => "liczba zadań przeglądu wykonywanych jednocześnie (domyślnie: 1)",
+        Message::HelpVerifyReplay => "headless: co TICKS kroków powtórz je od migawki i zakończ błędem, jeśli wynik się różni",
+        Message::HelpTransmission => "epidemia: prawdopodobieństwo zarażenia przez jednego chorego sąsiada w kroku",
+        Message::HelpIncubation => "epidemia: liczba kroków w stanie utajonym (włącza model SEIR)",
+        Message::HelpInfectionDuration => "epidemia: liczba kroków trwania choroby",
+        Message::HelpImmunityLoss => "epidemia: prawdopodobieństwo utraty odporności przez ozdrowiałego w kroku",
+        Message::HelpDropRate => "sandpile: średnia liczba ziaren upuszczanych w kroku",
+        Message::HelpFeedRate => "gray-scott: tempo dostarczania substancji U",
+        Message::HelpKillRate => "gray-scott: tempo usuwania substancji V",
+        Message::HelpStates => "cyclic: liczba stanów",
+        Message::HelpThreshold => "cyclic: liczba sąsiadów w następnym stanie potrzebna do zmiany stanu",
+        Message::HelpRule => "elementary: numer reguły (domyślnie 30)",
+        Message::HelpRule3D => "life3d: liczby sąsiadów spośród 26, przy których komórka się rodzi i przeżywa (domyślnie B5/S4,5)",
+        Message::HelpBoids => "boids: liczba ptaków (domyślnie 150)",
+        Message::HelpAnts => "ants: liczba mrówek (domyślnie 100)",
+        Message::HelpEvaporation => "ants: część feromonu odparowująca w kroku (domyślnie 0.01)",
+        Message::HelpTemperature => "ising: początkowa temperatura, zmieniana klawiszami +/- (domyślnie 2.27)",
+        Message::HelpRelaxation => "fluid: czas relaksacji, powyżej 0.5; większy oznacza większą lepkość (domyślnie 0.7)",
+        Message::HelpInflow => "fluid: prędkość przepływu wpływającego przez lewy brzeg (domyślnie 0.1)",
+        Message::HelpTurmites => "turmites: liczba turmitów (domyślnie 1)",
+        Message::HelpTurmiteTable => "turmites: wczytaj tablicę przejść, jedna reguła STAN KOLOR ZAPIS OBRÓT NOWY_STAN na linię",
+        Message::HelpTreeDensity => "forest-fire: część komórek początkowo porośniętych drzewami (domyślnie 0.65)",
+        Message::HelpGrowth => "forest-fire: prawdopodobieństwo wyrośnięcia drzewa na pustej komórce w jednym kroku (domyślnie 0)",
+        Message::HelpLightning => "forest-fire: prawdopodobieństwo samoistnego zapłonu drzewa w jednym kroku (domyślnie 0)",
+        Message::HelpHelp => "wyświetl tę pomoc",
+
+        Message::UnknownDemo => "nieznane demo: {}, dostępne dema:\n{}",
+        Message::UnknownSimulation => "nieznana symulacja: {}",
+        Message::UnknownLanguage => "nieznany język: {}, dostępne: en, pl",
+        Message::InvalidValue => "niepoprawna wartość --{}: {}: {}",
+        Message::InvalidProbability => "niepoprawna wartość --{}: {}, oczekiwano liczby z przedziału [0, 1]",
+        Message::InvalidSize => "niepoprawny format argumentu: {}, oczekiwano ROZMIAR, SZEROKOŚĆ,WYSOKOŚĆ lub SZEROKOŚĆ,WYSOKOŚĆ,GŁĘBOKOŚĆ",
+        Message::ValueTooSmall => "niepoprawna wartość --{}: musi wynosić co najmniej {}",
+        Message::ValueTooLarge => "niepoprawna wartość --{}: musi wynosić co najwyżej {}",
+        Message::InvalidStopCriterion => "niepoprawny warunek stopu: {}, oczekiwano NAZWA<WARTOŚĆ lub NAZWA>WARTOŚĆ",
+        Message::InvalidSeedPopulation => "niepoprawna populacja początkowa: {}, oczekiwano from-run ŚCIEŻKA",
+        Message::SeedPopulationNotSupported => "opcji --seed-population można użyć tylko z --sim goodevil",
+        Message::SeedPopulationWrongSimulation => "{}: osobniki można przenieść tylko z przebiegu goodevil, nie {}",
+        Message::TooManySpecimens => "{}: {} osobników nie mieści się na planszy {}",
+        Message::TooManySpecimensForWalls => "{} osobników nie mieści się na {} polach wolnych od ścian z --pattern-file",
+        Message::TooManyInfected => "{} początkowo zarażonych komórek nie mieści się na planszy {}",
+        Message::PatternFileNotSupported => "opcji --pattern-file można użyć tylko z --sim life lub goodevil",
+        Message::InitNotSupported => "opcji --init nie można użyć z --sim cyclic, falling-sand, ants, fluid, forest-fire ani life3d",
+        Message::DepthNotSupported => "głębokość planszy można podać tylko z --sim life3d",
+        Message::ScriptNotSupported => "opcji --script można użyć tylko z --sim life lub goodevil",
+        Message::RegionOutsideBoard => "obszar {} nie mieści się na planszy {}",
+        Message::PatternOffsetOutsideBoard => "przesunięcie wzoru {} wykracza poza planszę {}",
+        Message::InvalidEvent => "niepoprawne zdarzenie: {}, oczekiwano meteor, famine lub bloom jako NAZWA@ITERACJA[:PARAM]",
+        Message::UnknownBoundary => "nieznany rodzaj brzegów: {}, oczekiwano closed lub open",
+        Message::UnknownMovement => "nieznany sposób poruszania się: {}, oczekiwano random lub gradient",
+        Message::UnknownUpdateOrder => "nieznana kolejność aktualizacji: {}, oczekiwano row-major lub shuffled",
+        Message::UnknownConflictResolver => "nieznany sposób rozstrzygania konfliktów: {}, oczekiwano strongest, random lub first-come",
+        Message::ConflictingWind => "można użyć tylko jednej z opcji --wind, --wind-noise i --wind-file",
+        Message::CrashBundleWritten => "symulacja uległa awarii, dane diagnostyczne zapisano w {} - prosimy dołączyć je do zgłoszenia błędu",
+        Message::CrashBundleFailed => "symulacja uległa awarii, ale nie udało się zapisać danych diagnostycznych: {}",
+
+        Message::MustBeFinite => "musi być liczbą skończoną",
+        Message::MustBeFiniteNonNegative => "musi być skończona i nieujemna",
+        Message::MustBePositive => "musi być dodatnia",
+        Message::MustBeFraction => "musi należeć do przedziału [0, 1]",
+        Message::MustBeAboveHalf => "musi być większa od 0.5",
+        Message::DeadlyMarginRange => "musi być nieujemna i mniejsza od początkowej energii osobników",
+        Message::RecordVideoConflict => "nie można użyć razem z --record-frames",
+        Message::ExpectedFormat => "oczekiwano {}",
+        Message::ExpectedInit => "oczekiwano random:GĘSTOŚĆ, stripes:SZEROKOŚĆ, cluster:LICZBA lub center",
+        Message::ExpectedStorage => "oczekiwano dense, sparse lub auto",
+        Message::ExpectedPalette => "oczekiwano default, viridis, inferno lub jet",
+        Message::ExpectedAspect => "oczekiwano stretch, letterbox, fit-width lub fit-height",
+        Message::ExpectedUps => "oczekiwano max lub częstotliwości od {} do {}",
+        Message::ExpectedRender => "oczekiwano window lub tui",
+        Message::ExpectedChecks => "oczekiwano off, cheap, expensive lub paranoid",
+        Message::ExpectedActionCosts => "oczekiwano trzech nieujemnych liczb: POSTÓJ,PROSTO,UKOŚNIE",
+        Message::ExpectedInitialEnergy => "oczekiwano uniform:MIN,MAX, normal:ŚREDNIA,ODCHYLENIE,MIN,MAX lub bimodal:BIEDNI,BOGACI,UDZIAŁ",
+        Message::ExpectedRule3d => "oczekiwano BLICZBY/SLICZBY z liczbami do 26 rozdzielonymi przecinkami, np. B5/S4,5",
+
+        Message::LayerShown => "warstwa {}",
+        Message::PaletteChanged => "paleta {}",
+        Message::LayerVisible => "{} widoczna",
+        Message::LayerHidden => "{} ukryta",
+        Message::UpdatesPerSecond => "aktualizacje na sekundę: {}",
+        Message::UpdatesPerSecondMax => "aktualizacje na sekundę: maks.",
+        Message::RecordingStarted => "nagrywanie rozpoczęte",
+        Message::RecordingStopped => "nagrywanie zatrzymane",
+        Message::RestartFailed => "ponowne uruchomienie nie powiodło się: {}",
+        Message::RunRecordingStopped => "zapis przebiegu zatrzymany",
+        Message::Restarted => "uruchomiono ponownie z planszą {}, {} i ziarnem {}",
+        Message::BrushTip => "pędzel: {}, promień {}",
+        Message::PickSpecimen => "kliknij osobnika, aby go śledzić",
+        Message::NoSpecimenThere => "brak osobnika w tym miejscu",
+        Message::FollowedSpecimenGone => "śledzony osobnik zniknął",
+        Message::CellsCopied => "skopiowano komórki: {}x{}",
+        Message::CopyNotSupported => "z symulacji {} nie można kopiować",
+        Message::PatternCropped => "{}: wzór {}x{} przycięty do środkowych {}x{} komórek",
+        Message::SweepResumed => "wznawianie przeglądu w {} z opcjami, z którymi go rozpoczęto",
+        Message::SweepSummary => "przegląd: ukończone {}, pominięte {}, nieudane {}",
+
+        Message::HudIteration => "iteracja {}",
+        Message::HudPopulation => "populacja {}",
+        Message::HudFps => "kl./s {}",
+        Message::HudUps => "akt./s {}",
+        Message::HudSpeed => "prędkość x{}",
+        Message::HudSpeedMax => "prędkość maks.",
+        Message::HudFollowing => "śledzony: energia {}",
+        Message::HudAge => "wiek {}",
+        Message::HudLineage => "pokolenie {}, gatunek {}",
+        Message::HudCell => "komórka {}, {}",
+        Message::CellAlive => "żywa",
+        Message::CellDead => "martwa",
+        Message::CellEmpty => "pusta",
+        Message::CellWall => "ściana",
+        Message::CellSpecimen => "osobnik",
+        Message::CellDormantSpecimen => "uśpiony osobnik",
+        Message::CellEnergy => "energia {}",
+        Message::CellAge => "wiek {}",
+        Message::CellSpecies => "gatunek {}",
+        Message::CellGeneration => "pokolenie {}",
+        Message::CellFood => "pożywienie {}",
+        Message::CellGrains => "ziarna {}",
+        Message::CellDensity => "gęstość {}",
+        Message::CellVelocity => "prędkość {}, {}",
+        Message::CellObstacle => "przeszkoda",
+        Message::CellPheromone => "feromon {}",
+        Message::CellNest => "mrowisko",
+
+        Message::DemoLifeGliderGun => "działo Gospera wystrzeliwujące szybowce",
+        Message::DemoGoodEvilBoomBust => "plansza GoodEvil, na której przetrwa kilka najsilniejszych osobników",
+        Message::DemoFireFront => "front ognia przechodzący przez las od lewej krawędzi",
+        Message::DemoEpidemicWave => "infekcja rozchodząca się falą od kilku pierwszych przypadków"
+    }
+}
+
+#[test]
+fn test_format() {
+    assert_eq!("invalid value of --seed: x: oops",
+               Language::English.format(Message::InvalidValue, &["seed", "x", "oops"]));
+    assert_eq!("Użycie: cell [opcje]",
+               Language::Polish.format(Message::Usage, &["cell"]));
+}
+
+#[test]
+fn test_language_codes() {
+    assert_eq!(Some(Language::Polish), Language::from_code("pl"));
+    assert_eq!(None, Language::from_code("xx"));
+    assert_eq!("en", Language::English.code());
+}
@@ -0,0 +1,130 @@
+//! Distributions of the energy GoodEvil specimens start with, so that
+//! e.g. inequality experiments can start from controlled conditions.
+
+use std::fmt;
+
+use rand::StdRng;
+use rand::distributions::{IndependentSample, Normal, Range};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EnergyDistribution {
+    /// Uniformly distributed in [min, max).
+    Uniform {
+        min: f32,
+        max: f32
+    },
+    /// Normally distributed, clamped to [min, max].
+    Normal {
+        mean: f32,
+        stdev: f32,
+        min: f32,
+        max: f32
+    },
+    /// Exactly `rich_fraction` of specimens (rounded) start with `rich`
+    /// energy, the others with `poor`.
+    Bimodal {
+        poor: f32,
+        rich: f32,
+        rich_fraction: f32
+    }
+}
+
+impl EnergyDistribution {
+    /// Parses `uniform:MIN,MAX`, `normal:MEAN,STDEV,MIN,MAX` or
+    /// `bimodal:POOR,RICH,RICH_FRACTION`. Energies must be positive.
+    pub fn parse(s: &str) -> Option<EnergyDistribution> {
+        let mut parts = s.splitn(2, ':');
+        let name = parts.next().unwrap_or("");
+        let params: Vec<f32> = match parts.next().map(|p| p.split(',').map(|v| v.trim().parse()).collect()) {
+            Some(Ok(params)) => params,
+            _ => return None
+        };
+
+        match (name, &params[..]) {
+            ("uniform", &[min, max]) if 0.0 < min && min < max => {
+                Some(EnergyDistribution::Uniform { min: min, max: max })
+            },
+            ("normal", &[mean, stdev, min, max]) if stdev >= 0.0 && 0.0 < min && min <= max => {
+                Some(EnergyDistribution::Normal {
+                    mean: mean,
+                    stdev: stdev,
+                    min: min,
+                    max: max
+                })
+            },
+            ("bimodal", &[poor, rich, rich_fraction]) if poor > 0.0 && rich > 0.0
+                                                        && rich_fraction >= 0.0 && rich_fraction <= 1.0 => {
+                Some(EnergyDistribution::Bimodal {
+                    poor: poor,
+                    rich: rich,
+                    rich_fraction: rich_fraction
+                })
+            },
+            _ => None
+        }
+    }
+
+    /// Energies of `count` specimens.
+    pub fn energies(&self,
+                    count: usize,
+                    rng: &mut StdRng) -> Vec<f32> {
+        match *self {
+            EnergyDistribution::Uniform { min, max } => {
+                let range = Range::new(min, max);
+                (0..count).map(|_| range.ind_sample(rng)).collect()
+            },
+            EnergyDistribution::Normal { mean, stdev, min, max } => {
+                let normal = Normal::new(mean as f64, stdev as f64);
+                (0..count).map(|_| (normal.ind_sample(rng) as f32).max(min).min(max)).collect()
+            },
+            EnergyDistribution::Bimodal { poor, rich, rich_fraction } => {
+                let num_rich = (count as f32 * rich_fraction).round() as usize;
+                (0..count).map(|idx| if idx < num_rich { rich } else { poor }).collect()
+            }
+        }
+    }
+}
+
+impl fmt::Display for EnergyDistribution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EnergyDistribution::Uniform { min, max } => write!(f, "uniform:{},{}", min, max),
+            EnergyDistribution::Normal { mean, stdev, min, max } => {
+                write!(f, "normal:{},{},{},{}", mean, stdev, min, max)
+            },
+            EnergyDistribution::Bimodal { poor, rich, rich_fraction } => {
+                write!(f, "bimodal:{},{},{}", poor, rich, rich_fraction)
+            }
+        }
+    }
+}
+
+#[test]
+fn test_parse_energy_distribution() {
+    for s in ["uniform:0.5,1.5", "normal:1,0.25,0.1,2", "bimodal:0.5,4,0.1"].iter() {
+        assert_eq!(*s, EnergyDistribution::parse(s).unwrap().to_string());
+    }
+    assert_eq!(None, EnergyDistribution::parse("uniform:1.5,0.5"));
+    assert_eq!(None, EnergyDistribution::parse("uniform:0,1"));
+    assert_eq!(None, EnergyDistribution::parse("normal:1,0.25"));
+    assert_eq!(None, EnergyDistribution::parse("bimodal:0.5,4,1.5"));
+    assert_eq!(None, EnergyDistribution::parse("pareto:1,2"));
+}
+
+#[test]
+fn test_energy_distribution_energies() {
+    use rand::SeedableRng;
+
+    let mut rng = StdRng::from_seed(&[0usize][..]);
+
+    let energies = EnergyDistribution::parse("bimodal:0.5,4,0.25").unwrap().energies(10, &mut rng);
+    assert_eq!(3, energies.iter().filter(|&&e| e == 4.0).count());
+    assert_eq!(7, energies.iter().filter(|&&e| e == 0.5).count());
+
+    let energies = EnergyDistribution::parse("normal:1,1,0.5,1.5").unwrap().energies(100, &mut rng);
+    assert!(energies.iter().all(|&e| e >= 0.5 && e <= 1.5));
+    assert!(energies.iter().any(|&e| e == 0.5) && energies.iter().any(|&e| e == 1.5));
+
+    let energies = EnergyDistribution::parse("uniform:2,3").unwrap().energies(100, &mut rng);
+    assert!(energies.iter().all(|&e| e >= 2.0 && e < 3.0));
+}
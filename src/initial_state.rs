@@ -0,0 +1,194 @@
+//! Generators of the cells a simulation starts with, so that structured
+//! starts can be set up and reproduced from the command line.
+
+use std::cmp::{max, min};
+use std::fmt;
+
+use board::Board;
+use rand::{Rng, StdRng};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InitialState {
+    /// Every cell is seeded with given probability.
+    Random(f32),
+    /// Vertical stripes of given width, separated by gaps as wide.
+    Stripes(usize),
+    /// Given number of round clusters at random positions.
+    Clusters(usize),
+    /// A single cell in the middle of the board.
+    Center
+}
+
+impl InitialState {
+    /// Parses `random:DENSITY`, `stripes:WIDTH`, `cluster:COUNT` or
+    /// `center`.
+    pub fn parse(s: &str) -> Option<InitialState> {
+        let mut parts = s.splitn(2, ':');
+        match (parts.next().unwrap_or(""), parts.next()) {
+            ("random", Some(density)) => match density.trim().parse::<f32>() {
+                Ok(density) if density >= 0.0 && density <= 1.0 => Some(InitialState::Random(density)),
+                _ => None
+            },
+            ("stripes", Some(width)) => match width.trim().parse::<usize>() {
+                Ok(width) if width > 0 => Some(InitialState::Stripes(width)),
+                _ => None
+            },
+            ("cluster", Some(count)) => match count.trim().parse::<usize>() {
+                Ok(count) if count > 0 => Some(InitialState::Clusters(count)),
+                _ => None
+            },
+            ("center", None) => Some(InitialState::Center),
+            _ => None
+        }
+    }
+
+    /// Cells seeded on a `width` x `height` board.
+    pub fn mask(&self,
+                width: usize,
+                height: usize,
+                rng: &mut StdRng) -> Board<bool> {
+        let mut mask = Board::new(width, height, false);
+
+        match *self {
+            InitialState::Random(density) => {
+                for (x, y) in mask.indices() {
+                    *mask.at_mut(x, y) = rng.gen::<f32>() < density;
+                }
+            },
+            InitialState::Stripes(stripe_width) => {
+                for (x, y) in mask.indices() {
+                    *mask.at_mut(x, y) = (x / stripe_width) % 2 == 0;
+                }
+            },
+            InitialState::Clusters(count) => {
+                let radius = max(1, min(width, height) / 16) as i64;
+                for _ in 0..count {
+                    let cx = rng.gen_range(0, width) as i64;
+                    let cy = rng.gen_range(0, height) as i64;
+                    for y in max(0, cy - radius)..min(height as i64, cy + radius + 1) {
+                        for x in max(0, cx - radius)..min(width as i64, cx + radius + 1) {
+                            if (x - cx) * (x - cx) + (y - cy) * (y - cy) <= radius * radius {
+                                *mask.at_mut(x as usize, y as usize) = true;
+                            }
+                        }
+                    }
+                }
+            },
+            InitialState::Center => *mask.at_mut(width / 2, height / 2) = true
+        }
+
+        mask
+    }
+
+    /// Board with `seeded` on seeded cells and `other` everywhere else.
+    pub fn board<T: Clone>(&self,
+                           width: usize,
+                           height: usize,
+                           seeded: T,
+                           other: T,
+                           rng: &mut StdRng) -> Board<T> {
+        let mask = self.mask(width, height, rng);
+        let mut board = Board::new(width, height, other);
+        for (x, y) in mask.indices() {
+            if *mask.at(x, y) {
+                *board.at_mut(x, y) = seeded.clone();
+            }
+        }
+
+        board
+    }
+
+    /// Picks up to `count` distinct cells among those set in `free`, for
+    /// simulations starting with a fixed number of agents. Seeded cells come
+    /// first, followed by the free cells closest to them if there are not
+    /// enough of those.
+    pub fn place(&self,
+                 free: &Board<bool>,
+                 count: usize,
+                 rng: &mut StdRng) -> Vec<(usize, usize)> {
+        let (width, height) = (free.width, free.height);
+        let mut visited = self.mask(width, height, rng);
+        let mut ring: Vec<(usize, usize)> = visited.indices()
+                                                   .filter(|&(x, y)| *visited.at(x, y))
+                                                   .collect();
+        if ring.is_empty() {
+            ring.push((width / 2, height / 2));
+            *visited.at_mut(width / 2, height / 2) = true;
+        }
+
+        // grow the seeded area one step at a time, in random order within
+        // each step so that no direction is favored
+        let mut cells = Vec::with_capacity(count);
+        while cells.len() < count && !ring.is_empty() {
+            let mut candidates: Vec<(usize, usize)> = ring.iter()
+                                                          .cloned()
+                                                          .filter(|&(x, y)| *free.at(x, y))
+                                                          .collect();
+            rng.shuffle(&mut candidates);
+            let missing = count - cells.len();
+            cells.extend(candidates.into_iter().take(missing));
+
+            let mut next = Vec::new();
+            for &(x, y) in ring.iter() {
+                for &(dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)].iter() {
+                    let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+                    if nx < 0 || ny < 0 || nx >= width as i64 || ny >= height as i64 {
+                        continue;
+                    }
+                    let (nx, ny) = (nx as usize, ny as usize);
+                    if !*visited.at(nx, ny) {
+                        *visited.at_mut(nx, ny) = true;
+                        next.push((nx, ny));
+                    }
+                }
+            }
+            ring = next;
+        }
+
+        cells
+    }
+}
+
+impl fmt::Display for InitialState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            InitialState::Random(density) => write!(f, "random:{}", density),
+            InitialState::Stripes(width) => write!(f, "stripes:{}", width),
+            InitialState::Clusters(count) => write!(f, "cluster:{}", count),
+            InitialState::Center => write!(f, "center")
+        }
+    }
+}
+
+#[test]
+fn test_parse_initial_state() {
+    for s in ["random:0.3", "stripes:4", "cluster:5", "center"].iter() {
+        assert_eq!(*s, InitialState::parse(s).unwrap().to_string());
+    }
+    assert_eq!(None, InitialState::parse("random:1.5"));
+    assert_eq!(None, InitialState::parse("random"));
+    assert_eq!(None, InitialState::parse("stripes:0"));
+    assert_eq!(None, InitialState::parse("center:1"));
+    assert_eq!(None, InitialState::parse("checkerboard:2"));
+}
+
+#[test]
+fn test_initial_state_place() {
+    use rand::SeedableRng;
+
+    let mut rng = StdRng::from_seed(&[0usize][..]);
+
+    let mask = InitialState::Stripes(2).mask(6, 2, &mut rng);
+    let seeded: Vec<bool> = (0..6).map(|x| *mask.at(x, 1)).collect();
+    assert_eq!(vec![true, true, false, false, true, true], seeded);
+
+    // the center is taken, so its neighbors are picked instead
+    let mut free = Board::new(5, 5, true);
+    *free.at_mut(2, 2) = false;
+    let mut cells = InitialState::Center.place(&free, 4, &mut rng);
+    cells.sort();
+    assert_eq!(vec![(1, 2), (2, 1), (2, 3), (3, 2)], cells);
+
+    let cells = InitialState::Clusters(2).place(&free, 30, &mut rng);
+    assert_eq!(24, cells.len());
+}
@@ -0,0 +1,186 @@
+use board::Board;
+use rand::{Rng, StdRng};
+use simulation::Simulation;
+use stats::Stats;
+
+/// Change of temperature for a single `adjust` step.
+const TEMPERATURE_STEP: f32 = 0.1;
+
+#[derive(Clone, Copy, Debug)]
+pub struct IsingConfig {
+    /// In units of the coupling constant; the critical temperature of the
+    /// 2D model is about 2.269.
+    pub temperature: f32
+}
+
+impl Default for IsingConfig {
+    fn default() -> IsingConfig {
+        IsingConfig {
+            temperature: 2.27
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Spin {
+    Up,
+    Down
+}
+
+impl Spin {
+    pub fn value(&self) -> i32 {
+        match *self {
+            Spin::Up => 1,
+            Spin::Down => -1
+        }
+    }
+
+    pub fn flipped(&self) -> Spin {
+        match *self {
+            Spin::Up => Spin::Down,
+            Spin::Down => Spin::Up
+        }
+    }
+}
+
+/// Monte-Carlo (Metropolis) Ising model on a torus. A single step is one
+/// sweep: as many random spin flip attempts as there are cells.
+#[derive(Clone)]
+pub struct Ising {
+    pub cfg: IsingConfig,
+    rng: Box<StdRng>,
+    board: Board<Spin>,
+    iteration: usize
+}
+
+impl Ising {
+    pub fn new(width: usize,
+               height: usize,
+               cfg: IsingConfig,
+               mut rng: Box<StdRng>) -> Ising {
+        let mut board = Board::new(width, height, Spin::Up);
+        for (x, y) in board.indices() {
+            if rng.gen() {
+                *board.at_mut(x, y) = Spin::Down;
+            }
+        }
+
+        Ising::from_board(board, cfg, rng)
+    }
+
+    pub fn from_board(board: Board<Spin>,
+                      cfg: IsingConfig,
+                      rng: Box<StdRng>) -> Ising {
+        Ising {
+            cfg: cfg,
+            rng: rng,
+            board: board,
+            iteration: 0
+        }
+    }
+
+    /// Sum of the four von Neumann neighbors' spins.
+    fn neighbor_sum(&self,
+                    x: usize,
+                    y: usize) -> i32 {
+        let (w, h) = (self.board.width, self.board.height);
+
+        self.board.at((x + w - 1) % w, y).value()
+            + self.board.at((x + 1) % w, y).value()
+            + self.board.at(x, (y + h - 1) % h).value()
+            + self.board.at(x, (y + 1) % h).value()
+    }
+
+    /// Mean spin, in range [-1, 1].
+    pub fn magnetization(&self) -> f32 {
+        let sum = self.board.iter().fold(0, |sum, spin| sum + spin.value());
+        sum as f32 / (self.board.width * self.board.height) as f32
+    }
+
+    /// Mean energy per spin, counting every bond once.
+    pub fn energy(&self) -> f32 {
+        let (w, h) = (self.board.width, self.board.height);
+        let sum = self.board.indices().fold(0, |sum, (x, y)| {
+            let spin = self.board.at(x, y).value();
+            sum - spin * (self.board.at((x + 1) % w, y).value() + self.board.at(x, (y + 1) % h).value())
+        });
+
+        sum as f32 / (w * h) as f32
+    }
+}
+
+impl Simulation<Spin> for Ising {
+    fn advance(&mut self) {
+        for _ in 0..self.board.width * self.board.height {
+            let x = self.rng.gen_range(0, self.board.width);
+            let y = self.rng.gen_range(0, self.board.height);
+
+            let spin = *self.board.at(x, y);
+            let energy_change = 2 * spin.value() * self.neighbor_sum(x, y);
+
+            if energy_change <= 0
+                    || self.rng.gen::<f32>() < (-energy_change as f32 / self.cfg.temperature).exp() {
+                *self.board.at_mut(x, y) = spin.flipped();
+            }
+        }
+
+        self.iteration += 1;
+    }
+
+    fn board(&self) -> &Board<Spin> {
+        &self.board
+    }
+
+    fn stats(&self) -> Stats {
+        Stats::new(self.iteration).real("temperature", self.cfg.temperature)
+                                  .real("magnetization", self.magnetization())
+                                  .real("energy", self.energy())
+    }
+
+    fn parameters(&self) -> Vec<(&'static str, String)> {
+        vec![("temperature", self.cfg.temperature.to_string())]
+    }
+
+    fn adjust(&mut self,
+              steps: i32) {
+        self.cfg.temperature = (self.cfg.temperature + steps as f32 * TEMPERATURE_STEP).max(0.0);
+    }
+}
+
+#[cfg(test)]
+fn test_ising(board: Board<Spin>,
+              temperature: f32) -> Ising {
+    use rand::SeedableRng;
+
+    Ising::from_board(board, IsingConfig { temperature: temperature },
+                      Box::new(StdRng::from_seed(&[0usize][..])))
+}
+
+#[test]
+fn test_ising_stays_ordered_when_cold() {
+    let mut sim = test_ising(Board::new(8, 8, Spin::Up), 0.0);
+    assert_eq!(-2.0, sim.energy());
+
+    sim.advance();
+    assert_eq!(1.0, sim.magnetization());
+}
+
+#[test]
+fn test_ising_disorders_when_hot() {
+    let mut sim = test_ising(Board::new(16, 16, Spin::Up), 100.0);
+    for _ in 0..20 {
+        sim.advance();
+    }
+
+    assert!(sim.magnetization().abs() < 0.3);
+}
+
+#[test]
+fn test_ising_adjust_temperature() {
+    let mut sim = test_ising(Board::new(2, 2, Spin::Up), 0.15);
+
+    sim.adjust(1);
+    assert!((sim.cfg.temperature - 0.25).abs() < 1e-5);
+    sim.adjust(-5);
+    assert_eq!(0.0, sim.cfg.temperature);
+}
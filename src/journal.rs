@@ -0,0 +1,267 @@
+//! Structured record of what happened to individual specimens, as opposed
+//! to the aggregate `Stats`.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use rustc_serialize::json::Json;
+
+use schema::EventV1;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    /// A child split off its parent onto `position`.
+    Birth {
+        position: (usize, usize),
+        energy: f32
+    },
+    /// A specimen ran out of energy or was killed in a collision.
+    Death {
+        position: (usize, usize),
+        energy: f32
+    },
+    /// `contenders` specimens tried to move to the same cell.
+    Collision {
+        position: (usize, usize),
+        contenders: usize
+    },
+    /// A specimen changed cells.
+    Move {
+        from: (usize, usize),
+        to: (usize, usize)
+    }
+}
+
+impl Event {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Event::Birth { .. } => "birth",
+            Event::Death { .. } => "death",
+            Event::Collision { .. } => "collision",
+            Event::Move { .. } => "move"
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Entry {
+    pub iteration: usize,
+    pub event: Event
+}
+
+impl Entry {
+    /// Written as `schema::EventV1`.
+    pub fn to_json(&self) -> Json {
+        EventV1::of_entry(self).to_json()
+    }
+}
+
+/// Events of the step being computed, or the last one once it's done.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Journal {
+    iteration: usize,
+    entries: Vec<Entry>
+}
+
+impl Journal {
+    pub fn new() -> Journal {
+        Journal::default()
+    }
+
+    /// Forgets previous entries. New ones are tagged with `iteration`.
+    pub fn start(&mut self,
+                 iteration: usize) {
+        self.iteration = iteration;
+        self.entries.clear();
+    }
+
+    pub fn record(&mut self,
+                  event: Event) {
+        self.entries.push(Entry {
+            iteration: self.iteration,
+            event: event
+        });
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+}
+
+/// Appends journal entries to a file, one JSON object per line.
+///
+/// With a size limit set, a file that would grow past it is rotated: it's
+/// renamed to `PATH.1`, older ones shift to `PATH.2` and so on, and the
+/// oldest beyond the kept ones is removed. Every rotation also halves the
+/// share of events written from then on, down to every 2nd, 4th, 8th...
+/// event of each kind, so that the kept files cover more and more of the
+/// run. The current rate starts every rotated file as a `sampling` entry.
+pub struct EventLog {
+    path: String,
+    writer: BufWriter<File>,
+    /// Size of a single file in bytes, unbounded if not set.
+    max_bytes: Option<u64>,
+    /// Rotated files kept along with the current one.
+    keep: usize,
+    written: u64,
+    /// Only every `sample_every`-th event of each kind is written.
+    sample_every: usize,
+    /// Events of each kind seen so far, written or not.
+    seen: BTreeMap<&'static str, usize>
+}
+
+impl EventLog {
+    pub fn create(path: &str) -> Result<EventLog, String> {
+        let file = try!(File::create(path).map_err(|e| format!("{}: {}", path, e)));
+        Ok(EventLog {
+            path: path.to_string(),
+            writer: BufWriter::new(file),
+            max_bytes: None,
+            keep: 0,
+            written: 0,
+            sample_every: 1,
+            seen: BTreeMap::new()
+        })
+    }
+
+    /// Rotates files larger than `max_bytes`, keeping `keep` old ones.
+    pub fn with_rotation(self,
+                         max_bytes: u64,
+                         keep: usize) -> EventLog {
+        EventLog {
+            max_bytes: Some(max_bytes),
+            keep: keep,
+            .. self
+        }
+    }
+
+    pub fn sample_every(&self) -> usize {
+        self.sample_every
+    }
+
+    fn rotated_path(&self,
+                    idx: usize) -> String {
+        format!("{}.{}", self.path, idx)
+    }
+
+    fn rotate(&mut self,
+              iteration: usize) -> Result<(), String> {
+        try!(self.writer.flush().map_err(|e| format!("{}: {}", self.path, e)));
+
+        let oldest = self.rotated_path(self.keep + 1);
+        for idx in (0..self.keep).rev() {
+            let from = if idx == 0 { self.path.clone() } else { self.rotated_path(idx) };
+            let to = self.rotated_path(idx + 1);
+            if Path::new(&from).exists() {
+                try!(fs::rename(&from, &to).map_err(|e| format!("{}: {}", from, e)));
+            }
+        }
+        if Path::new(&oldest).exists() {
+            try!(fs::remove_file(&oldest).map_err(|e| format!("{}: {}", oldest, e)));
+        }
+
+        let file = try!(File::create(&self.path).map_err(|e| format!("{}: {}", self.path, e)));
+        self.writer = BufWriter::new(file);
+        self.written = 0;
+        self.sample_every *= 2;
+
+        let sampling = EventV1::sampling(iteration, self.sample_every);
+        self.write_line(&sampling.to_json().to_string())
+    }
+
+    fn write_line(&mut self,
+                  line: &str) -> Result<(), String> {
+        try!(writeln!(self.writer, "{}", line).map_err(|e| format!("{}: {}", self.path, e)));
+        self.written += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    pub fn write(&mut self,
+                 journal: &Journal) -> Result<(), String> {
+        for entry in journal.entries() {
+            let seen = self.seen.entry(entry.event.name()).or_insert(0);
+            *seen += 1;
+            if (*seen - 1) % self.sample_every != 0 {
+                continue;
+            }
+
+            let line = entry.to_json().to_string();
+            match self.max_bytes {
+                Some(max_bytes) if self.written > 0 && self.written + line.len() as u64 + 1 > max_bytes => {
+                    try!(self.rotate(entry.iteration))
+                },
+                _ => {}
+            }
+            try!(self.write_line(&line));
+        }
+        self.writer.flush().map_err(|e| format!("{}: {}", self.path, e))
+    }
+}
+
+#[test]
+fn test_entry_to_json() {
+    let entry = Entry {
+        iteration: 3,
+        event: Event::Move { from: (1, 2), to: (2, 2) }
+    };
+    assert_eq!(r#"{"event":"move","from":[1,2],"iteration":3,"to":[2,2]}"#, entry.to_json().to_string());
+
+    let entry = Entry {
+        iteration: 4,
+        event: Event::Collision { position: (0, 1), contenders: 2 }
+    };
+    assert_eq!(r#"{"contenders":2,"event":"collision","iteration":4,"position":[0,1]}"#,
+               entry.to_json().to_string());
+}
+
+#[test]
+fn test_journal_start_clears_entries() {
+    let mut journal = Journal::new();
+    journal.start(1);
+    journal.record(Event::Death { position: (0, 0), energy: -0.5 });
+    assert_eq!(1, journal.entries()[0].iteration);
+
+    journal.start(2);
+    assert!(journal.entries().is_empty());
+}
+
+#[test]
+fn test_event_log_rotation() {
+    use std::env;
+    use std::io::Read;
+
+    let path = env::temp_dir().join("cell-test-event-log.jsonl");
+    let path = path.to_str().unwrap();
+    let read = |path: &str| {
+        let mut text = String::new();
+        File::open(path).unwrap().read_to_string(&mut text).unwrap();
+        text
+    };
+
+    let mut journal = Journal::new();
+    // move entries take 55 bytes with the newline and sampling ones 45, so
+    // each file fits a sampling entry and two moves
+    let mut log = EventLog::create(path).unwrap().with_rotation(160, 1);
+    for iteration in 0..6 {
+        journal.start(iteration);
+        journal.record(Event::Move { from: (1, 2), to: (2, 2) });
+        journal.record(Event::Move { from: (2, 2), to: (3, 2) });
+        log.write(&journal).unwrap();
+    }
+
+    // the first file with all moves of iteration 0 is gone, the second has
+    // every 2nd move of iterations 1-2 and the current one every 4th
+    assert_eq!(4, log.sample_every());
+    let current = read(path);
+    assert_eq!(3, current.lines().count());
+    assert_eq!(r#"{"event":"sampling","every":4,"iteration":3}"#, current.lines().next().unwrap());
+    let previous = read(&format!("{}.1", path));
+    assert_eq!(3, previous.lines().count());
+    assert_eq!(r#"{"event":"sampling","every":2,"iteration":1}"#, previous.lines().next().unwrap());
+    assert!(!Path::new(&format!("{}.2", path)).exists());
+
+    fs::remove_file(path).unwrap();
+    fs::remove_file(format!("{}.1", path)).unwrap();
+}
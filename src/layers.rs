@@ -0,0 +1,182 @@
+use std::any::Any;
+use std::cmp::max;
+use std::marker::PhantomData;
+
+use board::Board;
+
+/// Board in a `Layers`, with its cell type erased.
+trait AnyBoard: Send + Sync {
+    fn as_any(&self) -> &Any;
+    fn as_any_mut(&mut self) -> &mut Any;
+    fn clone_box(&self) -> Box<AnyBoard>;
+}
+
+impl<T: Clone + Send + Sync + 'static> AnyBoard for Board<T> {
+    fn as_any(&self) -> &Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<AnyBoard> {
+        Box::new(self.clone())
+    }
+}
+
+/// Handle of a layer holding cells of type `T`, returned by `Layers::add`.
+pub struct Layer<T> {
+    index: usize,
+    cells: PhantomData<T>
+}
+
+// derived impls would require T: Clone
+impl<T> Clone for Layer<T> {
+    fn clone(&self) -> Layer<T> {
+        *self
+    }
+}
+
+impl<T> Copy for Layer<T> {}
+
+struct Entry {
+    name: &'static str,
+    board: Box<AnyBoard>,
+    visible: bool
+}
+
+impl Clone for Entry {
+    fn clone(&self) -> Entry {
+        Entry {
+            name: self.name,
+            board: self.board.clone_box(),
+            visible: self.visible
+        }
+    }
+}
+
+/// Boards of different cell types and the same size, e.g. the pheromone
+/// and food of an ant colony. Each layer can be hidden when rendering.
+#[derive(Clone)]
+pub struct Layers {
+    entries: Vec<Entry>,
+    pub width: usize,
+    pub height: usize
+}
+
+impl Layers {
+    pub fn new(width: usize,
+               height: usize) -> Layers {
+        Layers {
+            entries: Vec::new(),
+            width: width,
+            height: height
+        }
+    }
+
+    /// Adds a visible layer, as large as all others.
+    pub fn add<T: Clone + Send + Sync + 'static>(&mut self,
+                                                  name: &'static str,
+                                                  board: Board<T>) -> Layer<T> {
+        assert!(board.width == self.width && board.height == self.height,
+                "{}x{} layer {} added to {}x{} layers", board.width, board.height, name, self.width, self.height);
+        self.entries.push(Entry {
+            name: name,
+            board: Box::new(board),
+            visible: true
+        });
+
+        Layer {
+            index: self.entries.len() - 1,
+            cells: PhantomData
+        }
+    }
+
+    pub fn get<T: 'static>(&self,
+                           layer: Layer<T>) -> &Board<T> {
+        self.entries[layer.index].board.as_any().downcast_ref().expect("layer of another Layers")
+    }
+
+    pub fn get_mut<T: 'static>(&mut self,
+                               layer: Layer<T>) -> &mut Board<T> {
+        self.entries[layer.index].board.as_any_mut().downcast_mut().expect("layer of another Layers")
+    }
+
+    /// Two different layers, both mutable.
+    pub fn get_pair_mut<A: 'static, B: 'static>(&mut self,
+                                               a: Layer<A>,
+                                               b: Layer<B>) -> (&mut Board<A>, &mut Board<B>) {
+        assert!(a.index != b.index);
+        let (low, high) = self.entries.split_at_mut(max(a.index, b.index));
+        let (first, second) = if a.index < b.index {
+            (&mut low[a.index], &mut high[0])
+        } else {
+            (&mut high[0], &mut low[b.index])
+        };
+
+        (first.board.as_any_mut().downcast_mut().expect("layer of another Layers"),
+         second.board.as_any_mut().downcast_mut().expect("layer of another Layers"))
+    }
+
+    /// Coordinates of every cell along with its value in both layers, row
+    /// by row.
+    pub fn zip<'a, A: 'static, B: 'static>(&'a self,
+                                           a: Layer<A>,
+                                           b: Layer<B>) -> Box<Iterator<Item=((usize, usize), &'a A, &'a B)> + 'a> {
+        Box::new(self.get(a).enumerate()
+                            .zip(self.get(b).iter())
+                            .map(|((xy, a), b)| (xy, a, b)))
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.entries.iter().map(|entry| entry.name).collect()
+    }
+
+    pub fn is_visible<T>(&self,
+                         layer: Layer<T>) -> bool {
+        self.entries[layer.index].visible
+    }
+
+    /// Shows or hides the `index`-th layer added, returning its name and
+    /// whether it's visible now, or None if there are fewer layers.
+    pub fn toggle(&mut self,
+                  index: usize) -> Option<(&'static str, bool)> {
+        self.entries.get_mut(index).map(|entry| {
+            entry.visible = !entry.visible;
+            (entry.name, entry.visible)
+        })
+    }
+}
+
+#[test]
+fn test_layers_get() {
+    let mut layers = Layers::new(3, 2);
+    let food = layers.add("food", Board::new(3, 2, 0u32));
+    let pheromone = layers.add("pheromone", Board::new(3, 2, 0.0f32));
+
+    *layers.get_mut(food).at_mut(1, 1) = 5;
+    {
+        let (pheromone, food) = layers.get_pair_mut(pheromone, food);
+        *pheromone.at_mut(1, 1) = *food.at(1, 1) as f32 / 2.0;
+    }
+
+    assert_eq!(2.5, *layers.get(pheromone).at(1, 1));
+    let cells: Vec<((usize, usize), u32, f32)> = layers.zip(food, pheromone)
+                                                       .filter(|&(_, &food, _)| food > 0)
+                                                       .map(|(xy, &food, &pheromone)| (xy, food, pheromone))
+                                                       .collect();
+    assert_eq!(vec![((1, 1), 5, 2.5)], cells);
+    assert_eq!(vec!["food", "pheromone"], layers.names());
+}
+
+#[test]
+fn test_layers_toggle() {
+    let mut layers = Layers::new(1, 1);
+    let nest = layers.add("nest", Board::new(1, 1, false));
+
+    assert!(layers.is_visible(nest));
+    assert_eq!(Some(("nest", false)), layers.toggle(0));
+    assert!(!layers.is_visible(nest));
+    assert_eq!(None, layers.toggle(1));
+}
@@ -0,0 +1,98 @@
+//! Cellular automata and agent-based simulations behind the `cell` binary.
+//! Every simulation implements `simulation::Simulation` and can be driven
+//! without a window, e.g. with `Simulation::run`.
+
+extern crate rand;
+extern crate rustc_serialize;
+#[cfg(feature = "scripting")]
+extern crate rhai;
+#[cfg(feature = "zstd")]
+extern crate zstd;
+
+pub mod agent;
+pub mod analysis;
+pub mod ants;
+pub mod batch;
+pub mod bench;
+pub mod board;
+pub mod board3d;
+pub mod board_view;
+pub mod boids;
+pub mod boundary;
+pub mod brush;
+pub mod camera;
+pub mod checks;
+pub mod color_map;
+pub mod config_file;
+pub mod conflict;
+pub mod crowding;
+pub mod cyclic;
+pub mod demo;
+pub mod diagnostics;
+pub mod dormancy;
+pub mod drift;
+pub mod elementary;
+pub mod energy_histogram;
+pub mod epidemic;
+pub mod exposure;
+pub mod fairness;
+pub mod falling_sand;
+pub mod fluid;
+pub mod forest_fire;
+pub mod food;
+pub mod genome;
+pub mod gif;
+pub mod gray_scott;
+pub mod headless;
+pub mod hud;
+pub mod i18n;
+pub mod initial_energy;
+pub mod initial_state;
+pub mod journal;
+pub mod layers;
+pub mod ising;
+pub mod life3d;
+pub mod macrocell;
+pub mod manifest;
+pub mod merge;
+pub mod metrics;
+pub mod migration;
+pub mod minimap;
+pub mod movement;
+pub mod notebook;
+pub mod offscreen;
+pub mod outcome;
+pub mod palette;
+pub mod plaintext;
+pub mod plot;
+pub mod policy;
+pub mod predation;
+pub mod quadtree;
+pub mod renderer;
+pub mod replay;
+pub mod reproduction;
+pub mod recording;
+pub mod registry;
+pub mod reputation;
+pub mod rle;
+pub mod run_recording;
+pub mod sandpile;
+pub mod schema;
+pub mod scripting;
+pub mod simulation;
+pub mod snapshot;
+pub mod sparse_board;
+pub mod spatial;
+pub mod species;
+pub mod spectator;
+pub mod speed_ramp;
+pub mod stats;
+pub mod sweep;
+pub mod symmetry;
+pub mod theme;
+pub mod ticks;
+pub mod trails;
+pub mod tui;
+pub mod turmite;
+pub mod warnings;
+pub mod world_events;
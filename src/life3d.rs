@@ -0,0 +1,225 @@
+use std::fmt;
+
+use board::Board;
+use board3d::Board3D;
+use rand::{Rng, StdRng};
+use simulation::Simulation;
+use stats::Stats;
+
+/// Number of layers of a 3D Life board whose depth isn't given.
+pub const DEFAULT_DEPTH: usize = 16;
+
+/// Birth and survival rule of 3D Life, out of the 26 neighbors of a cell.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Life3DConfig {
+    /// Bit N is set if a dead cell with N live neighbors comes to life.
+    pub birth: u32,
+    /// Bit N is set if a live cell with N live neighbors stays alive.
+    pub survival: u32
+}
+
+impl Default for Life3DConfig {
+    /// Bays' 4555 rule: a cell is born with 5 neighbors and survives with 4
+    /// or 5, which supports gliders.
+    fn default() -> Life3DConfig {
+        Life3DConfig {
+            birth: 1 << 5,
+            survival: 1 << 4 | 1 << 5
+        }
+    }
+}
+
+impl Life3DConfig {
+    /// Parses "B5/S4,5": comma separated neighbor counts a cell is born and
+    /// survives with. Either list may be empty.
+    pub fn parse(s: &str) -> Option<Life3DConfig> {
+        let mut parts = s.split('/');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(birth), Some(survival), None) if birth.starts_with('B') && survival.starts_with('S') => {
+                match (Life3DConfig::parse_counts(&birth[1..]), Life3DConfig::parse_counts(&survival[1..])) {
+                    (Some(birth), Some(survival)) => Some(Life3DConfig {
+                        birth: birth,
+                        survival: survival
+                    }),
+                    _ => None
+                }
+            },
+            _ => None
+        }
+    }
+
+    fn parse_counts(s: &str) -> Option<u32> {
+        let mut counts = 0;
+        for count in s.split(',').filter(|count| !count.is_empty()) {
+            match count.parse::<u32>() {
+                Ok(count) if count <= 26 => counts |= 1 << count,
+                _ => return None
+            }
+        }
+        Some(counts)
+    }
+
+    pub fn next(&self,
+                alive: bool,
+                alive_neighbors: usize) -> bool {
+        let counts = if alive { self.survival } else { self.birth };
+        counts & (1 << alive_neighbors) != 0
+    }
+}
+
+impl fmt::Display for Life3DConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let counts = |bits: u32| (0..27).filter(|count| bits & (1 << count) != 0)
+                                        .map(|count: u32| count.to_string())
+                                        .collect::<Vec<String>>()
+                                        .join(",");
+        write!(f, "B{}/S{}", counts(self.birth), counts(self.survival))
+    }
+}
+
+/// Life on a 3D torus. `board` shows a single layer at a time, changed
+/// with `scrub_layer`.
+#[derive(Clone)]
+pub struct Life3D {
+    pub cfg: Life3DConfig,
+    cells: Board3D<bool>,
+    /// Copy of the layer shown.
+    view: Board<bool>,
+    layer: usize,
+    iteration: usize
+}
+
+impl Life3D {
+    /// Fills the middle third of the board along every axis at random.
+    pub fn new(width: usize,
+               height: usize,
+               depth: usize,
+               cfg: Life3DConfig,
+               rng: &mut StdRng) -> Life3D {
+        let mut cells = Board3D::new(width, height, depth, false);
+        for (x, y, z) in cells.indices() {
+            let inside = |v: usize, len: usize| v >= len / 3 && v < len - len / 3;
+            if inside(x, width) && inside(y, height) && inside(z, depth) {
+                *cells.at_mut(x, y, z) = rng.gen::<f32>() < 0.3;
+            }
+        }
+
+        Life3D::from_cells(cells, cfg)
+    }
+
+    /// Starts from given cells, showing the middle layer.
+    pub fn from_cells(cells: Board3D<bool>,
+                      cfg: Life3DConfig) -> Life3D {
+        assert!(cells.depth > 0);
+        let layer = cells.depth / 2;
+
+        Life3D {
+            cfg: cfg,
+            view: cells.layer(layer),
+            cells: cells,
+            layer: layer,
+            iteration: 0
+        }
+    }
+
+    pub fn cells(&self) -> &Board3D<bool> {
+        &self.cells
+    }
+
+    fn count_alive_neighbors(&self,
+                             x: usize,
+                             y: usize,
+                             z: usize) -> usize {
+        let (width, height, depth) = (self.cells.width, self.cells.height, self.cells.depth);
+        let mut alive = 0;
+
+        for dz in 0..3 {
+            for dy in 0..3 {
+                for dx in 0..3 {
+                    if (dx, dy, dz) == (1, 1, 1) {
+                        continue;
+                    }
+                    if *self.cells.at((x + width + dx - 1) % width,
+                                      (y + height + dy - 1) % height,
+                                      (z + depth + dz - 1) % depth) {
+                        alive += 1;
+                    }
+                }
+            }
+        }
+
+        alive
+    }
+}
+
+impl Simulation<bool> for Life3D {
+    fn advance(&mut self) {
+        let mut next = Board3D::new(self.cells.width, self.cells.height, self.cells.depth, false);
+        for (x, y, z) in self.cells.indices() {
+            let alive = self.count_alive_neighbors(x, y, z);
+            *next.at_mut(x, y, z) = self.cfg.next(*self.cells.at(x, y, z), alive);
+        }
+
+        self.cells = next;
+        self.view = self.cells.layer(self.layer);
+        self.iteration += 1;
+    }
+
+    fn board(&self) -> &Board<bool> {
+        &self.view
+    }
+
+    fn stats(&self) -> Stats {
+        let alive = self.cells.as_slice().iter().filter(|&&alive| alive).count();
+        let layer_alive = self.view.iter().filter(|&&alive| alive).count();
+        Stats::new(self.iteration).count("alive", alive)
+                                  .count("layer", self.layer)
+                                  .count("layer_alive", layer_alive)
+    }
+
+    fn is_extinct(&self) -> bool {
+        !self.cells.as_slice().iter().any(|&alive| alive)
+    }
+
+    fn parameters(&self) -> Vec<(&'static str, String)> {
+        vec![("rule3d", self.cfg.to_string())]
+    }
+
+    fn scrub_layer(&mut self,
+                   steps: i32) -> Option<usize> {
+        let layer = self.layer as i64 + steps as i64;
+        self.layer = layer.max(0).min(self.cells.depth as i64 - 1) as usize;
+        self.view = self.cells.layer(self.layer);
+        Some(self.layer)
+    }
+}
+
+#[test]
+fn test_life3d_rule_parse() {
+    assert_eq!(Some(Life3DConfig::default()), Life3DConfig::parse("B5/S4,5"));
+    assert_eq!("B5/S4,5", Life3DConfig::default().to_string());
+    assert_eq!(Some(Life3DConfig { birth: 0, survival: 1 << 26 }), Life3DConfig::parse("B/S26"));
+    assert_eq!(None, Life3DConfig::parse("B27/S4"));
+    assert_eq!(None, Life3DConfig::parse("S4/B5"));
+}
+
+#[test]
+fn test_life3d_advance() {
+    // a 2x2x2 cube, where every cell has 7 live neighbors, survives with
+    // S7 and grows nothing with B nothing
+    let mut cells = Board3D::new(6, 6, 6, false);
+    for &(x, y, z) in &[(2, 2, 2), (3, 2, 2), (2, 3, 2), (3, 3, 2), (2, 2, 3), (3, 2, 3), (2, 3, 3), (3, 3, 3)] {
+        *cells.at_mut(x, y, z) = true;
+    }
+    let cfg = Life3DConfig::parse("B/S7").unwrap();
+    let mut sim = Life3D::from_cells(cells, cfg);
+
+    sim.advance();
+    assert_eq!(8, sim.stats().get("alive").unwrap().as_f32() as usize);
+    assert_eq!(3, sim.stats().get("layer").unwrap().as_f32() as usize);
+    assert_eq!(4, sim.stats().get("layer_alive").unwrap().as_f32() as usize);
+
+    assert_eq!(Some(0), sim.scrub_layer(-5));
+    assert_eq!(0, sim.stats().get("layer_alive").unwrap().as_f32() as usize);
+    assert_eq!(Some(5), sim.scrub_layer(10));
+}
@@ -0,0 +1,393 @@
+//! Reader of Golly's macrocell (.mc) format, which stores Life patterns as
+//! a quadtree with shared nodes, so that huge sparse patterns stay small.
+
+use std::cmp::{max, min};
+use std::fs::File;
+use std::io::Read;
+
+use board::Board;
+use plaintext;
+use rle;
+
+/// Level of the 8x8 bitmap leaves.
+const LEAF_LEVEL: u32 = 3;
+/// Patterns with a bounding box larger than this would not fit on a dense
+/// board. Macrocell ones are cropped to their middle, others rejected.
+pub const MAX_SIDE: usize = 4096;
+
+enum Node {
+    /// Live cells of an 8x8 square.
+    Leaf(Vec<(u64, u64)>),
+    /// Children in nw, ne, sw, se order; 0 is an empty square.
+    Branch(u32, [usize; 4])
+}
+
+/// Live cells of a pattern, relative to its bounding box.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pattern {
+    pub width: usize,
+    pub height: usize,
+    pub cells: Vec<(usize, usize)>
+}
+
+fn parse_leaf(line: &str) -> Result<Node, String> {
+    let (mut x, mut y) = (0u64, 0u64);
+    let mut cells = Vec::new();
+
+    for c in line.chars() {
+        match c {
+            '.' => x += 1,
+            '*' => {
+                cells.push((x, y));
+                x += 1;
+            },
+            '$' => {
+                x = 0;
+                y += 1;
+            },
+            _ => return Err(format!("unexpected character in leaf: {}", c))
+        }
+        if x > 8 || y > 8 {
+            return Err(format!("leaf larger than 8x8: {}", line));
+        }
+    }
+
+    Ok(Node::Leaf(cells))
+}
+
+fn parse_branch(line: &str,
+                nodes: &[Node]) -> Result<Node, String> {
+    let numbers: Vec<usize> = try!(line.split_whitespace()
+                                       .map(|n| n.parse().map_err(|_| format!("invalid node: {}", line)))
+                                       .collect());
+    if numbers.len() != 5 {
+        return Err(format!("invalid node: {}", line));
+    }
+
+    let level = numbers[0] as u32;
+    if level <= LEAF_LEVEL || level > 63 {
+        return Err(format!("unsupported node level {} (only two-state patterns are supported)", level));
+    }
+
+    let mut children = [0; 4];
+    for (child, &idx) in children.iter_mut().zip(numbers[1..].iter()) {
+        if idx > nodes.len() {
+            return Err(format!("node {} refers to undefined node {}", nodes.len() + 1, idx));
+        }
+        if idx > 0 && node_level(&nodes[idx - 1]) != level - 1 {
+            return Err(format!("node {} has a child of wrong level", nodes.len() + 1));
+        }
+        *child = idx;
+    }
+
+    Ok(Node::Branch(level, children))
+}
+
+fn node_level(node: &Node) -> u32 {
+    match *node {
+        Node::Leaf(_) => LEAF_LEVEL,
+        Node::Branch(level, _) => level
+    }
+}
+
+/// Bounding box of live cells as [min_x, min_y, max_x, max_y], inclusive.
+type Bounds = [u64; 4];
+
+fn union(a: Option<Bounds>,
+         b: Option<Bounds>) -> Option<Bounds> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some([min(a[0], b[0]), min(a[1], b[1]), max(a[2], b[2]), max(a[3], b[3])]),
+        (a, None) => a,
+        (None, b) => b
+    }
+}
+
+/// Bounding boxes of the live cells of every node, relative to the node's
+/// top left corner, None for empty nodes. Computed from the children's
+/// without expanding any node, as shared nodes can describe more cells
+/// than fit in memory.
+fn node_bounds(nodes: &[Node]) -> Vec<Option<Bounds>> {
+    let mut bounds: Vec<Option<Bounds>> = Vec::with_capacity(nodes.len());
+    for node in nodes.iter() {
+        let node_bounds = match *node {
+            Node::Leaf(ref live) => live.iter().fold(None, |acc, &(x, y)| union(acc, Some([x, y, x, y]))),
+            Node::Branch(level, children) => {
+                let half = 1u64 << (level - 1);
+                let offsets = [(0, 0), (half, 0), (0, half), (half, half)];
+                children.iter().zip(offsets.iter()).fold(None, |acc, (&child, &(dx, dy))| {
+                    let child = if child == 0 { None } else { bounds[child - 1] };
+                    union(acc, child.map(|b| [b[0] + dx, b[1] + dy, b[2] + dx, b[3] + dy]))
+                })
+            }
+        };
+        bounds.push(node_bounds);
+    }
+    bounds
+}
+
+/// Appends live cells of node `idx` placed at (x, y) that lie within
+/// `window` to `cells`, skipping nodes with no live cells in it.
+fn collect_cells(nodes: &[Node],
+                 bounds: &[Option<Bounds>],
+                 idx: usize,
+                 x: u64,
+                 y: u64,
+                 window: Bounds,
+                 cells: &mut Vec<(u64, u64)>) {
+    let b = match if idx == 0 { None } else { bounds[idx - 1] } {
+        Some(b) => b,
+        None => return
+    };
+    if x + b[2] < window[0] || y + b[3] < window[1] || x + b[0] > window[2] || y + b[1] > window[3] {
+        return;
+    }
+
+    match nodes[idx - 1] {
+        Node::Leaf(ref live) => {
+            cells.extend(live.iter()
+                             .map(|&(dx, dy)| (x + dx, y + dy))
+                             .filter(|&(cx, cy)| cx >= window[0] && cy >= window[1] && cx <= window[2] && cy <= window[3]))
+        },
+        Node::Branch(level, children) => {
+            let half = 1u64 << (level - 1);
+            collect_cells(nodes, bounds, children[0], x, y, window, cells);
+            collect_cells(nodes, bounds, children[1], x + half, y, window, cells);
+            collect_cells(nodes, bounds, children[2], x, y + half, window, cells);
+            collect_cells(nodes, bounds, children[3], x + half, y + half, window, cells);
+        }
+    }
+}
+
+/// Range of `MAX_SIDE` coordinates in the middle of `from..=to`, or all of
+/// it if it's not longer.
+fn crop(from: u64,
+        to: u64) -> (u64, u64) {
+    let len = to - from + 1;
+    if len <= MAX_SIDE as u64 {
+        (from, to)
+    } else {
+        let start = from + (len - MAX_SIDE as u64) / 2;
+        (start, start + MAX_SIDE as u64 - 1)
+    }
+}
+
+/// Accepts only Conway's Life rule, the one `GameOfLife` simulates.
+pub fn check_rule(rule: &str) -> Result<(), String> {
+    match &rule.to_uppercase()[..] {
+        "B3/S23" | "23/3" => Ok(()),
+        _ => Err(format!("unsupported rule: {} (only B3/S23 is supported)", rule))
+    }
+}
+
+impl Pattern {
+    pub fn parse(text: &str) -> Result<Pattern, String> {
+        Pattern::parse_cropped(text).map(|(pattern, _)| pattern)
+    }
+
+    /// Parses a macrocell pattern, cropped to its middle `MAX_SIDE` cells
+    /// along sides longer than that. Returns the size of the whole pattern
+    /// as well if it was cropped.
+    pub fn parse_cropped(text: &str) -> Result<(Pattern, Option<(u64, u64)>), String> {
+        let mut nodes = Vec::new();
+
+        for line in text.lines().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+            if line.starts_with("#R") {
+                try!(check_rule(line[2..].trim()));
+            } else if line.starts_with('#') || line.starts_with('[') {
+                continue;
+            } else if line.starts_with(|c| c == '.' || c == '*' || c == '$') {
+                nodes.push(try!(parse_leaf(line)));
+            } else {
+                let node = try!(parse_branch(line, &nodes));
+                nodes.push(node);
+            }
+        }
+
+        if nodes.is_empty() {
+            return Err("no nodes in macrocell pattern".to_string());
+        }
+
+        let bounds = node_bounds(&nodes);
+        let whole = match bounds[nodes.len() - 1] {
+            Some(whole) => whole,
+            None => return Ok((Pattern { width: 0, height: 0, cells: Vec::new() }, None))
+        };
+        let ((min_x, max_x), (min_y, max_y)) = (crop(whole[0], whole[2]), crop(whole[1], whole[3]));
+        let cropped = if (min_x, min_y, max_x, max_y) == (whole[0], whole[1], whole[2], whole[3]) {
+            None
+        } else {
+            Some((whole[2] - whole[0] + 1, whole[3] - whole[1] + 1))
+        };
+
+        let mut cells = Vec::new();
+        collect_cells(&nodes, &bounds, nodes.len(), 0, 0, [min_x, min_y, max_x, max_y], &mut cells);
+        let pattern = Pattern::from_cells(cells.into_iter()
+                                               .map(|(x, y)| ((x - min_x) as usize, (y - min_y) as usize))
+                                               .collect());
+        Ok((pattern, cropped))
+    }
+
+    /// Pattern of `cells`, moved to the top left corner.
+    pub fn from_cells(cells: Vec<(usize, usize)>) -> Pattern {
+        if cells.is_empty() {
+            return Pattern { width: 0, height: 0, cells: Vec::new() };
+        }
+
+        let min_x = cells.iter().map(|&(x, _)| x).min().unwrap();
+        let min_y = cells.iter().map(|&(_, y)| y).min().unwrap();
+        Pattern {
+            width: cells.iter().map(|&(x, _)| x - min_x + 1).max().unwrap(),
+            height: cells.iter().map(|&(_, y)| y - min_y + 1).max().unwrap(),
+            cells: cells.into_iter().map(|(x, y)| (x - min_x, y - min_y)).collect()
+        }
+    }
+
+    /// Pattern made of `parts`, each with its top left corner at the given
+    /// offset, and the offset of the result.
+    pub fn composite(parts: &[(Pattern, (usize, usize))]) -> (Pattern, (usize, usize)) {
+        let cells: Vec<(usize, usize)> = parts.iter()
+                                              .flat_map(|&(ref pattern, offset)| {
+                                                  pattern.cells.iter().map(move |&(x, y)| (x + offset.0, y + offset.1))
+                                              })
+                                              .collect();
+        let origin = (cells.iter().map(|&(x, _)| x).min().unwrap_or(0),
+                      cells.iter().map(|&(_, y)| y).min().unwrap_or(0));
+        (Pattern::from_cells(cells), origin)
+    }
+
+    /// Loads an RLE pattern if `path` ends with `.rle`, a plaintext one if
+    /// it ends with `.cells` and a macrocell one otherwise.
+    pub fn load(path: &str) -> Result<Pattern, String> {
+        Pattern::load_cropped(path).map(|(pattern, _)| pattern)
+    }
+
+    /// Like `load`, also returning the size of the whole pattern if it was
+    /// cropped, see `parse_cropped`.
+    pub fn load_cropped(path: &str) -> Result<(Pattern, Option<(u64, u64)>), String> {
+        let mut contents = String::new();
+        try!(File::open(path).and_then(|mut f| f.read_to_string(&mut contents))
+                             .map_err(|e| format!("cannot read {}: {}", path, e)));
+
+        let lowercase = path.to_lowercase();
+        let pattern = if lowercase.ends_with(".rle") {
+            rle::parse(&contents).map(|pattern| (pattern, None))
+        } else if lowercase.ends_with(".cells") {
+            plaintext::parse(&contents).map(|pattern| (pattern, None))
+        } else {
+            Pattern::parse_cropped(&contents)
+        };
+        pattern.map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Smallest board at least as large as `board_size` that fits the
+    /// pattern with a margin around it.
+    pub fn fit_board_size(&self,
+                          board_size: (usize, usize)) -> (usize, usize) {
+        let margin = 16;
+        (if self.width + 2 * margin > board_size.0 { self.width + 2 * margin } else { board_size.0 },
+         if self.height + 2 * margin > board_size.1 { self.height + 2 * margin } else { board_size.1 })
+    }
+
+    /// Builds a board with the pattern in the middle. Cells that don't fit
+    /// are dropped.
+    pub fn to_board<T: Clone>(&self,
+                              board_size: (usize, usize),
+                              alive: T,
+                              dead: T) -> Board<T> {
+        let offset = ((board_size.0 as i64 - self.width as i64) / 2,
+                      (board_size.1 as i64 - self.height as i64) / 2);
+        self.place(board_size, offset, alive, dead)
+    }
+
+    /// Builds a board with the top left corner of the pattern at `offset`.
+    /// Cells that don't fit are dropped.
+    pub fn to_board_at<T: Clone>(&self,
+                                 board_size: (usize, usize),
+                                 offset: (usize, usize),
+                                 alive: T,
+                                 dead: T) -> Board<T> {
+        self.place(board_size, (offset.0 as i64, offset.1 as i64), alive, dead)
+    }
+
+    fn place<T: Clone>(&self,
+                       board_size: (usize, usize),
+                       offset: (i64, i64),
+                       alive: T,
+                       dead: T) -> Board<T> {
+        let mut board = Board::new(board_size.0, board_size.1, dead);
+        for &(x, y) in self.cells.iter() {
+            let (bx, by) = (x as i64 + offset.0, y as i64 + offset.1);
+            if bx >= 0 && by >= 0 && bx < board.width as i64 && by < board.height as i64 {
+                *board.at_mut(bx as usize, by as usize) = alive.clone();
+            }
+        }
+
+        board
+    }
+}
+
+#[cfg(test)]
+const GLIDER_MC: &'static str = "[M2] (golly 2.0)
+#R B3/S23
+.*$..*$***$
+4 0 1 0 0
+";
+
+#[test]
+fn test_macrocell_parse() {
+    let pattern = Pattern::parse(GLIDER_MC).unwrap();
+
+    assert_eq!((3, 3), (pattern.width, pattern.height));
+    let mut cells = pattern.cells.clone();
+    cells.sort();
+    assert_eq!(vec![(0, 2), (1, 0), (1, 2), (2, 1), (2, 2)], cells);
+}
+
+#[test]
+fn test_macrocell_rejects_invalid() {
+    assert!(Pattern::parse("#R B36/S23\n*$\n").is_err());
+    assert!(Pattern::parse("4 1 0 0 0\n").is_err());
+    assert!(Pattern::parse("*$\n5 1 0 0 0\n").is_err());
+}
+
+#[test]
+fn test_macrocell_crops_huge_pattern() {
+    // gliders 16 cells apart along the diagonal of a 2^21-sided square,
+    // described by a node per level
+    let mut text = GLIDER_MC.to_string();
+    for level in 5..22 {
+        text.push_str(&format!("{} {} 0 0 {}\n", level, level - 3, level - 3));
+    }
+    let (pattern, cropped) = Pattern::parse_cropped(&text).unwrap();
+
+    assert_eq!(Some((2097139, 2097139)), cropped);
+    assert!(pattern.width <= MAX_SIDE && pattern.height <= MAX_SIDE);
+    assert_eq!(256 * 5, pattern.cells.len());
+}
+
+#[test]
+fn test_macrocell_fit_board() {
+    let pattern = Pattern { width: 100, height: 3, cells: vec![(0, 0), (99, 2)] };
+    let size = pattern.fit_board_size((80, 60));
+    assert_eq!((132, 60), size);
+
+    let board = pattern.to_board(size, true, false);
+    assert!(*board.at(16, 28));
+    assert!(*board.at(115, 30));
+
+    let board = pattern.to_board_at(size, (2, 57), true, false);
+    assert!(*board.at(2, 57));
+    assert!(*board.at(101, 59));
+}
+
+#[test]
+fn test_pattern_composite() {
+    let dot = Pattern::from_cells(vec![(3, 4)]);
+    assert_eq!(Pattern { width: 1, height: 1, cells: vec![(0, 0)] }, dot);
+
+    let pair = Pattern { width: 2, height: 1, cells: vec![(0, 0), (1, 0)] };
+    let (pattern, origin) = Pattern::composite(&[(pair, (10, 5)), (dot, (4, 8))]);
+    assert_eq!((4, 5), origin);
+    assert_eq!((8, 4), (pattern.width, pattern.height));
+    assert_eq!(vec![(6, 0), (7, 0), (0, 3)], pattern.cells);
+}
@@ -1,3 +1,4 @@
+extern crate cell;
 extern crate rand;
 extern crate getopts;
 
@@ -7,8 +8,22 @@ extern crate glutin_window;
 extern crate opengl_graphics;
 extern crate time;
 
+use std::any::Any;
+use std::cmp::{max, min};
 use std::fmt;
-use rand::StdRng;
+use std::panic::{self, AssertUnwindSafe};
+use std::env;
+use std::fs::File;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+use std::io::{self, Read};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use rand::{Rng, SeedableRng, StdRng};
 
 use piston::window::WindowSettings;
 use piston::event_loop::*;
@@ -16,82 +31,1135 @@ use piston::input::*;
 use glutin_window::GlutinWindow as Window;
 use opengl_graphics::{GlGraphics, OpenGL};
 
-mod board;
+mod gl_renderer;
 mod time_accumulator;
 mod tick_meter;
-mod simulation;
 
+use cell::{bench, demo, drift, headless};
+use cell::agent::Agent;
+use cell::analysis::Analysis;
+use cell::ants::{AntColony, AntsConfig};
+use cell::bench::BenchResult;
+use cell::board::Board;
+use cell::boids::{Boids, BoidsConfig};
+use cell::boundary::Boundary;
+use cell::brush::{BrushShape, BrushTip};
+use cell::camera::{Aspect, Camera};
+use cell::checks::CheckLevel;
+use cell::color_map::ColorMap;
+use cell::config_file::{self, ConfigFile};
+use cell::conflict::{self, ConflictResolver};
+use cell::crowding::CrowdingConfig;
+use cell::cyclic::{Cyclic, CyclicConfig};
+use cell::diagnostics::{Crash, panic_message};
+use cell::dormancy::DormancyConfig;
+use cell::drift::{DriftField, DriftSource};
+use gl_renderer::GlRenderer;
 use time_accumulator::TimeAccumulator;
-use tick_meter::TickMeter;
-use simulation::{Simulation, Field, GoodEvil, GoodEvilConfig};
+use tick_meter::{Profiler, TickMeter, TickStats};
+use cell::sparse_board::Storage;
+use cell::simulation::{Simulation, SimulationKind, GameOfLife, GoodEvil, GoodEvilConfig, Specimen};
+use cell::elementary::{Elementary, ElementaryConfig};
+use cell::energy_histogram::{self, EnergyHistogram};
+use cell::life3d::{self, Life3D, Life3DConfig};
+use cell::epidemic::{Epidemic, EpidemicConfig, Health};
+use cell::exposure::Exposure;
+use cell::fairness::UpdateOrder;
+use cell::falling_sand::FallingSand;
+use cell::fluid::{Fluid, FluidConfig};
+use cell::forest_fire::{ForestFire, ForestFireConfig};
+use cell::food::FoodConfig;
+use cell::genome::GeneticsConfig;
+use cell::gray_scott::{GrayScott, GrayScottConfig};
+use cell::headless::HeadlessConfig;
+use cell::hud::Hud;
+use cell::i18n::{Language, Message};
+use cell::initial_energy::EnergyDistribution;
+use cell::metrics::{CsvSink, HudSink, Metrics, PrometheusSink, Sink, StdoutSink};
+use cell::plot::{self, Plot, PlotHistory};
+use cell::initial_state::InitialState;
+use cell::ising::{Ising, IsingConfig, Spin};
+use cell::journal::{Event, EventLog};
+use cell::schema::StatsLog;
+use cell::macrocell::Pattern;
+use cell::manifest::Manifest;
+use cell::merge::{self, ScheduledMerge};
+use cell::minimap::Minimap;
+use cell::notebook::Notebook;
+use cell::movement::{ActionCosts, MovementPolicy};
+use cell::outcome::{Outcome, RunReport};
+use cell::palette::CellColor;
+use cell::policy::Policies;
+use cell::predation::PredationConfig;
+use cell::quadtree::{self, Quadtree};
+use cell::reproduction::ReproductionConfig;
+use cell::run_recording::{Input, RunRecording, RunWriter, TimedInput};
+use cell::reputation::ReputationConfig;
+use cell::recording::Recorder;
+use cell::renderer::{Renderer, Scene, Viewport};
+use cell::registry::{Launcher, SimulationFactory};
+use cell::scripting::{self, CellRule, SpecimenBehavior};
+use cell::snapshot::Snapshots;
+use cell::species::SpeciesConfig;
+use cell::speed_ramp::SpeedRamp;
+use cell::stats::StopCriterion;
+use cell::sweep::{self, Param, Sweep};
+use cell::symmetry::{Region, SymmetryReport};
+use cell::theme::Theme;
+use cell::trails::Trails;
+use cell::tui::{self, Backend, TermKey, Terminal, TuiRenderer};
+use cell::turmite::{TransitionTable, Turmite, TurmiteConfig, Turmites};
+use cell::warnings::{WarningKind, WarningLog};
+use cell::world_events::WorldEvent;
+use cell::sandpile::{self, Sandpile, SandpileConfig};
 
-struct App {
-    gl: GlGraphics,
-    simulation: GoodEvil,
-    time_accumulator: TimeAccumulator
+struct App<S> {
+    renderer: Box<Renderer>,
+    simulation: S,
+    camera: Camera,
+    time_accumulator: TimeAccumulator,
+    updates_per_second: f64,
+    /// Steps as many times per update as MAX_SPEED_BUDGET_S allows,
+    /// ignoring `updates_per_second`.
+    max_speed: bool,
+    speed_ramp: Option<SpeedRamp>,
+    /// Steps done so far.
+    iteration: usize,
+    viewport_size: [f64; 2],
+    cursor: [f64; 2],
+    /// Brush selected with number keys.
+    brush: usize,
+    /// Brush used while a mouse button is held.
+    painting: Option<usize>,
+    /// Cells painted around the cursor.
+    brush_tip: BrushTip,
+    /// Cells selected by dragging with Shift held.
+    selection: Option<Region>,
+    /// Cell the selection being dragged started at.
+    selecting: Option<(usize, usize)>,
+    /// Cells copied or cut from the selection, pasted with Ctrl+V.
+    clipboard: Option<Arc<merge::Region>>,
+    shift: bool,
+    ctrl: bool,
+    show_drift: bool,
+    show_density: bool,
+    show_grid: bool,
+    /// Ages of occupied cells and trails of left ones, tracked while the
+    /// trail overlay is shown.
+    trails: Option<Trails>,
+    theme: Theme,
+    symmetry_region: Option<Region>,
+    /// Region whose occupied cells are counted in stats.
+    count_region: Option<Region>,
+    /// Occupied cells, built when first needed and updated after every
+    /// step from then on.
+    occupancy: Option<Quadtree>,
+    /// Keep the camera over the densest part of the board.
+    follow: bool,
+    /// Next click picks a specimen to follow instead of painting.
+    picking: bool,
+    /// Specimen the camera follows and where it is, as of the last step.
+    followed: Option<((usize, usize), Specimen)>,
+    recorder: Option<Recorder>,
+    event_log: Option<EventLog>,
+    stats_log: Option<StatsLog>,
+    exposure: Option<Exposure>,
+    notebook: Option<Notebook>,
+    snapshots: Option<Snapshots>,
+    /// Regions merged into the board as the run goes.
+    merges: Vec<ScheduledMerge>,
+    /// Configuration the run started with, for notebook entries.
+    config: ConfigFile,
+    run_writer: Option<RunWriter>,
+    /// Recording being replayed. User inputs are ignored while replaying.
+    replay: Option<RunRecording>,
+    /// State the replay started from, for seeking backwards.
+    initial: Option<S>,
+    paused: bool,
+    /// Factor of the update rate, changed while replaying.
+    playback_speed: f64,
+    show_hud: bool,
+    /// Population and mean energy of the last steps, plotted along the
+    /// bottom of the window while `show_plot` is set.
+    plot: PlotHistory,
+    show_plot: bool,
+    /// Draw the energies of specimens as bars.
+    show_energy_histogram: bool,
+    /// Frame and update durations measured by the window, for the HUD and
+    /// metrics.
+    tick_stats: (TickStats, TickStats),
+    /// Warnings about recording; the simulation keeps its own.
+    warnings: WarningLog,
+    /// Stats and whatever else the simulation measures, as of the last step.
+    metrics: Metrics,
+    /// Consumers of `metrics` after every step, dropped when they fail.
+    sinks: Vec<Box<Sink>>,
+    /// Options the run started with and the simulations they are built
+    /// with, so that the simulation can be restarted with another board size
+    /// or seed, see `restart`.
+    opts: Options,
+    factory: SimulationFactory<Options, Rebuilt>
 }
 
-fn color_from_field(field: &Field) -> [f32; 4] {
-    const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
-    const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+/// Smallest board every simulation can be built on.
+const MIN_BOARD_SIZE: usize = 2;
 
-    match *field {
-        Field::Empty => BLACK,
-        Field::Occupied(s) => {
-            match s.energy {
-                x if x >= 4.0 => WHITE,
-                x if x >= 3.0 => [1.0, 1.0, x - 3.0, 1.0],
-                x if x >= 2.0 => [1.0, x - 2.0, 0.0, 1.0],
-                x if x >= 1.0 => [x - 1.0, 0.0, 2.0 - x, 1.0],
-                x => [0.0, 0.0, x, 1.0]
-            }
-        },
-        Field::Collision(_) => panic!("should never happen")
+/// Smallest board `restart` shrinks the board to.
+const MIN_RESTART_BOARD_SIZE: usize = 4;
+
+/// Takes a simulation built by the registry without knowing its type, for
+/// `App::restart` to get it back as the type of the running one.
+struct Rebuilt {
+    simulation: Option<Box<Any>>
+}
+
+impl Launcher for Rebuilt {
+    fn launch<T, S>(&mut self,
+                    simulation: S) where S: Simulation<T> + Clone + Send + 'static,
+                                         T: CellColor + Clone + PartialEq + Send + Sync + 'static {
+        self.simulation = Some(Box::new(simulation));
+    }
+}
+
+/// Estimates the memory taken by a freshly built simulation, for
+/// check-config.
+struct MemoryEstimate {
+    bytes: usize
+}
+
+impl Launcher for MemoryEstimate {
+    fn launch<T, S>(&mut self,
+                    simulation: S) where S: Simulation<T> + Clone + Send + 'static,
+                                         T: CellColor + Clone + PartialEq + Send + Sync + 'static {
+        let board = simulation.board();
+        let food = simulation.food().map_or(0, |food| food.width * food.height * mem::size_of::<f32>());
+
+        // most simulations build the next board next to the current one
+        self.bytes = mem::size_of::<S>()
+                     + 2 * board.width * board.height * mem::size_of::<T>()
+                     + food
+                     + simulation.agents().len() * mem::size_of::<Agent>();
     }
 }
 
-impl App {
-    fn render(&mut self,
-              args: &RenderArgs) {
-        use graphics::*;
+/// Number of steps PageUp/PageDown seek by while replaying.
+const SEEK_STEPS: usize = 100;
+
+/// Range of the update rate, both on the command line and changed with
+/// [ and ].
+const MIN_UPDATES_PER_SECOND: f64 = 0.25;
+const MAX_UPDATES_PER_SECOND: f64 = 10000.0;
+
+/// Time a single update may spend stepping at max speed, and the number of
+/// steps it may do regardless of time.
+const MAX_SPEED_BUDGET_S: f64 = 0.015;
+const MAX_SPEED_STEPS: usize = 1000;
+
+impl<S> App<S> {
+    fn render<T>(&mut self,
+                 viewport: Viewport) where S: Simulation<T>, T: CellColor {
+        self.viewport_size = viewport.size();
+
+        let scene = Scene::of(&self.simulation, &self.theme, &self.camera, viewport,
+                              self.show_drift, self.show_density);
+        let scene = match self.trails {
+            Some(ref trails) => scene.with_trails(trails),
+            None => scene
+        };
+        let scene = if self.show_grid { scene.with_grid() } else { scene };
+        let scene = match self.selection {
+            Some(selection) => scene.with_selection(selection),
+            None => scene
+        };
+        let scene = if self.show_minimap() { scene.with_minimap(viewport) } else { scene };
+        let scene = if self.show_plot { scene.with_plot(Plot::of(&self.plot, viewport.size())) } else { scene };
+        let scene = if self.show_energy_histogram {
+            let buckets = self.opts.energy_histogram.unwrap_or(energy_histogram::DEFAULT_BUCKETS);
+            scene.with_energy_histogram(EnergyHistogram::of_specimens(&self.simulation.specimens(), buckets))
+        } else {
+            scene
+        };
+        let scene = if self.show_hud {
+            let mut lines = self.hud().lines(self.opts.lang);
+            lines.extend(self.sinks.iter().flat_map(|sink| sink.hud_lines()));
+            scene.with_hud(lines)
+        } else {
+            scene
+        };
+        self.renderer.render(&scene, viewport);
+    }
+
+    fn hud<T>(&self) -> Hud where S: Simulation<T>, T: CellColor {
+        let ramp_speed = self.speed_ramp.as_ref().map(|ramp| ramp.speed_at(self.iteration + 1)).unwrap_or(1.0);
+        Hud {
+            iteration: self.iteration,
+            population: self.simulation.board().iter().filter(|cell| quadtree::is_occupied(*cell)).count(),
+            frames_per_second: self.tick_stats.0.rate,
+            updates_per_second: self.tick_stats.1.rate,
+            speed: if self.max_speed { None } else { Some(ramp_speed * self.playback_speed) },
+            followed: self.followed.map(|(_, specimen)| specimen),
+            hovered: self.cursor_cell().map(|(x, y)| ((x, y), self.simulation.describe_cell(x, y, self.opts.lang)))
+        }
+    }
+
+    fn update<T>(&mut self,
+                 args: &UpdateArgs) where S: Simulation<T>, T: CellColor {
+        if self.paused {
+            return;
+        }
+
+        if self.max_speed {
+            let start_s = time::precise_time_s();
+            for _ in 0..MAX_SPEED_STEPS {
+                self.step();
+                if time::precise_time_s() - start_s >= MAX_SPEED_BUDGET_S {
+                    break;
+                }
+            }
+            return;
+        }
+
+        let speed = self.speed_ramp.as_ref().map(|ramp| ramp.speed_at(self.iteration + 1)).unwrap_or(1.0);
+        self.time_accumulator.set_step(1.0 / (self.updates_per_second * speed * self.playback_speed));
+
+        let steps = self.time_accumulator.update(args.dt).count();
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+
+    /// Shows another layer of a 3D simulation.
+    fn scrub_layer<T>(&mut self,
+                      steps: i32) where S: Simulation<T>, T: CellColor {
+        if let Some(layer) = self.simulation.scrub_layer(steps) {
+            println!("{}", self.opts.lang.format(Message::LayerShown, &[&layer.to_string()]));
+            if let Some(ref mut occupancy) = self.occupancy {
+                occupancy.sync(self.simulation.board());
+            }
+        }
+    }
+
+    /// Switches to the next heatmap color map, of the window only.
+    fn cycle_palette(&mut self) {
+        self.theme.heatmap = ColorMap::cycle(self.theme.heatmap);
+        let name = self.theme.heatmap.map(|map| map.name()).unwrap_or("default");
+        println!("{}", self.opts.lang.format(Message::PaletteChanged, &[name]));
+    }
+
+    fn toggle_layer<T>(&mut self,
+                       index: usize) where S: Simulation<T>, T: CellColor {
+        if let Some((name, visible)) = self.simulation.toggle_layer(index) {
+            let message = if visible { Message::LayerVisible } else { Message::LayerHidden };
+            println!("{}", self.opts.lang.format(message, &[name]));
+            if let Some(ref mut occupancy) = self.occupancy {
+                occupancy.sync(self.simulation.board());
+            }
+        }
+    }
 
-        const DARK_BLUE: [f32; 4] = [0.0, 0.0, 0.2, 1.0];
+    /// Multiplies the update rate by `factor`, within the allowed range.
+    fn change_speed(&mut self,
+                    factor: f64) {
+        self.updates_per_second = (self.updates_per_second * factor).max(MIN_UPDATES_PER_SECOND)
+                                                                    .min(MAX_UPDATES_PER_SECOND);
+        println!("{}", self.opts.lang.format(Message::UpdatesPerSecond, &[&self.updates_per_second.to_string()]));
+    }
 
-        let board = &self.simulation.board;
-        let viewport_rect = args.viewport().rect;
-        let elem_size = [viewport_rect[2] as f64 / board.width as f64,
-                         viewport_rect[3] as f64 / board.height as f64];
+    fn toggle_max_speed(&mut self) {
+        self.max_speed = !self.max_speed;
+        self.time_accumulator.reset();
+        if self.max_speed {
+            println!("{}", self.opts.lang.tr(Message::UpdatesPerSecondMax));
+        } else {
+            println!("{}", self.opts.lang.format(Message::UpdatesPerSecond, &[&self.updates_per_second.to_string()]));
+        }
+    }
 
-        self.gl.draw(args.viewport(), |ctx, gl| {
-            clear(DARK_BLUE, gl);
+    /// Applies replayed inputs due before the next step, if any.
+    fn apply_replayed_inputs<T>(&mut self) where S: Simulation<T> {
+        if let Some(ref replay) = self.replay {
+            for input in replay.inputs_at(self.iteration) {
+                input.apply(&mut self.simulation);
+            }
+        }
+    }
 
-            for (x_idx, y_idx) in board.indices() {
-                let color = color_from_field(&board.at(x_idx, y_idx));
+    fn step<T>(&mut self) where S: Simulation<T>, T: CellColor {
+        self.apply_replayed_inputs();
+        headless::apply_merges(&mut self.simulation, &self.merges, self.iteration);
+        self.simulation.advance();
+        self.iteration += 1;
 
-                let x = x_idx as f64;
-                let y = y_idx as f64;
+        if let Some(ref mut occupancy) = self.occupancy {
+            occupancy.sync(self.simulation.board());
+        }
+        if let Some(ref mut trails) = self.trails {
+            trails.update(self.simulation.board());
+        }
+        if self.follow {
+            self.follow_action();
+        }
+        self.track_specimen();
 
-                let rect = [
-                    x * elem_size[0],
-                    y * elem_size[1],
-                    (x + 1.0f64) * elem_size[0],
-                    (y + 1.0f64) * elem_size[1],
-                ];
-                rectangle(color, rect, ctx.transform, gl);
+        let mut stats = self.simulation.stats();
+        if self.time_accumulator.dropped() > 0.0 {
+            stats = stats.real("dropped_time", self.time_accumulator.dropped() as f32);
+        }
+        if let (Some(region), Some(occupancy)) = (self.count_region, self.occupancy.as_ref()) {
+            stats = stats.count("region", occupancy.count_in(region));
+        }
+        if let Some(buckets) = self.opts.energy_histogram {
+            stats = stats.with_energy_histogram(EnergyHistogram::of_specimens(&self.simulation.specimens(), buckets));
+        }
+        self.record_plot();
+        self.metrics.record_stats(&stats);
+        self.simulation.record_metrics(&mut self.metrics);
+        let (frames, updates) = self.tick_stats;
+        if frames.rate.is_finite() {
+            self.metrics.set("fps", frames.rate);
+            self.metrics.set("frame_p95_ms", frames.p95_s * 1000.0);
+        }
+        if updates.rate.is_finite() {
+            self.metrics.set("ups", updates.rate);
+            self.metrics.set("update_jitter_ms", updates.std_dev_s * 1000.0);
+        }
+        let mut failed = Vec::new();
+        for (idx, sink) in self.sinks.iter_mut().enumerate() {
+            if let Err(e) = sink.consume(&self.metrics) {
+                failed.push((idx, e));
             }
+        }
+        for (idx, e) in failed.into_iter().rev() {
+            println!("{}", e);
+            self.warnings.record(WarningKind::MetricsSinkFailed, self.iteration, e);
+            self.sinks.remove(idx);
+        }
+
+        let failed = match self.recorder {
+            Some(ref mut recorder) => recorder.record(&self.simulation).err(),
+            None => None
+        };
+        if let Some(e) = failed {
+            println!("{}", e);
+            self.warnings.record(WarningKind::RecordingFailed, self.iteration, e);
+            self.recorder = None;
+        }
+
+        let failed = match (self.event_log.as_mut(), self.simulation.journal()) {
+            (Some(log), Some(journal)) => log.write(journal).err(),
+            _ => None
+        };
+        if let Some(e) = failed {
+            println!("{}", e);
+            self.warnings.record(WarningKind::EventLogFailed, self.iteration, e);
+            self.event_log = None;
+        }
+
+        let failed = self.stats_log.as_mut().and_then(|log| log.write(&stats).err());
+        if let Some(e) = failed {
+            println!("{}", e);
+            self.warnings.record(WarningKind::StatsLogFailed, self.iteration, e);
+            self.stats_log = None;
+        }
+
+        let failed = match self.exposure {
+            Some(ref mut exposure) => exposure.record(&self.simulation).err(),
+            None => None
+        };
+        if let Some(e) = failed {
+            println!("{}", e);
+            self.warnings.record(WarningKind::ExposureFailed, self.iteration, e);
+            self.exposure = None;
+        }
+
+        let failed = self.snapshots.as_ref().and_then(|snapshots| {
+            snapshots.after_step(&self.simulation, self.iteration).err()
         });
+        if let Some(e) = failed {
+            println!("{}", e);
+            self.warnings.record(WarningKind::SnapshotFailed, self.iteration, e);
+            self.snapshots = None;
+        }
+    }
+
+    /// Adds the population and mean energy of specimens after a step to the
+    /// plot.
+    fn record_plot<T>(&mut self) where S: Simulation<T>, T: CellColor {
+        let population = self.simulation.board().iter().filter(|cell| quadtree::is_occupied(*cell)).count();
+        let specimens = self.simulation.specimens();
+        let mean_energy = if specimens.is_empty() {
+            None
+        } else {
+            Some(specimens.iter().fold(0.0, |sum, specimen| sum + specimen.energy) / specimens.len() as f32)
+        };
+        self.plot.record(population, mean_energy);
+    }
+
+    /// Saves a PNG image of the board right away.
+    fn save_png<T>(&mut self) where S: Simulation<T>, T: CellColor {
+        let result = match self.snapshots {
+            Some(ref snapshots) => snapshots.take(&self.simulation, self.iteration),
+            None => return
+        };
+        match result {
+            Ok(path) => println!("{}", path),
+            Err(e) => {
+                println!("{}", e);
+                self.warnings.record(WarningKind::SnapshotFailed, self.iteration, e);
+                self.snapshots = None;
+            }
+        }
+    }
+
+    /// Starts or stops recording, if there is a recorder.
+    fn toggle_recording(&mut self) {
+        if let Some(ref mut recorder) = self.recorder {
+            let paused = !recorder.is_paused();
+            recorder.set_paused(paused);
+            println!("{}", self.opts.lang.tr(if paused { Message::RecordingStopped } else { Message::RecordingStarted }));
+        }
+    }
+
+    /// Starts over from iteration 0 with a simulation built anew from the
+    /// run's options, on a board of `board_size` cells and with `seed`.
+    /// Recording and exposing are finished if the board size changes, as
+    /// their images can't change size, and the run recording stops, as it
+    /// can't describe a restart. Ignored while replaying.
+    fn restart<T>(&mut self,
+                  board_size: (usize, usize),
+                  seed: usize) where S: Simulation<T> + 'static, T: CellColor {
+        if self.replay.is_some() {
+            return;
+        }
+
+        let mut opts = self.opts.clone();
+        opts.board_size = board_size;
+        opts.seed = Some(seed);
+
+        // constructors assert their preconditions, e.g. that all specimens
+        // fit on the board, which a smaller board may break
+        let built = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut rebuilt = Rebuilt { simulation: None };
+            let rng = Box::new(StdRng::from_seed(&[seed][..]));
+            self.factory
+                .build(opts.simulation.name(), board_size.0, board_size.1, &opts, rng, &mut rebuilt)
+                .map(|_| rebuilt.simulation)
+        }));
+        let simulation = match built {
+            Ok(Ok(Some(simulation))) => simulation,
+            Ok(Ok(None)) => return,
+            Ok(Err(e)) => return println!("{}", opts.lang.format(Message::RestartFailed, &[&e])),
+            Err(payload) => return println!("{}", opts.lang.format(Message::RestartFailed, &[&panic_message(payload)]))
+        };
+        // the same name always builds the same type of simulation
+        self.simulation = match simulation.downcast::<S>() {
+            Ok(simulation) => *simulation,
+            Err(_) => return
+        };
+        self.iteration = 0;
+        self.metrics = Metrics::new();
+        self.plot.clear();
+        self.followed = None;
+        self.occupancy = self.count_region.map(|_| Quadtree::of_board(self.simulation.board()));
+        if self.trails.is_some() {
+            self.trails = Some(Trails::of_board(self.simulation.board()));
+        }
+
+        if board_size != self.opts.board_size {
+            if let Some(e) = self.recorder.take().and_then(|mut recorder| recorder.finish().err()) {
+                println!("{}", e);
+                self.warnings.record(WarningKind::RecordingFailed, self.iteration, e);
+            }
+            if let Some(e) = self.exposure.take().and_then(|mut exposure| exposure.finish().err()) {
+                println!("{}", e);
+                self.warnings.record(WarningKind::ExposureFailed, self.iteration, e);
+            }
+        }
+        if self.run_writer.take().is_some() {
+            println!("{}", self.opts.lang.tr(Message::RunRecordingStopped));
+        }
+
+        self.opts = opts;
+        self.config = self.opts.effective_config();
+        println!("{}", self.opts.lang.format(Message::Restarted, &[&board_size.0.to_string(),
+                                                                  &board_size.1.to_string(),
+                                                                  &seed.to_string()]));
+    }
+
+    /// Restarts on a board scaled by `numerator / denominator`, keeping the
+    /// seed.
+    fn restart_scaled<T>(&mut self,
+                         numerator: usize,
+                         denominator: usize) where S: Simulation<T> + 'static, T: CellColor {
+        let (width, height) = self.opts.board_size;
+        let scale = |size: usize| max(MIN_RESTART_BOARD_SIZE, size * numerator / denominator);
+        let seed = self.opts.seed.unwrap_or(0);
+        self.restart((scale(width), scale(height)), seed);
+    }
+
+    /// Appends the current parameters and a thumbnail to the lab notebook.
+    fn snapshot<T>(&mut self) where S: Simulation<T>, T: CellColor {
+        let mut parameters = self.config.clone();
+        let ups = if self.max_speed { "max".to_string() } else { self.updates_per_second.to_string() };
+        parameters.set("ups", config_file::Value::Text(ups));
+        for (name, value) in self.simulation.parameters() {
+            parameters.set(name, config_file::Value::Text(value));
+        }
+        let parameters: Vec<(String, String)> = parameters.entries.iter()
+                                                          .map(|&(ref name, ref value)| (name.clone(), value.to_string()))
+                                                          .collect();
+
+        let timestamp = time::strftime("%Y-%m-%d %H:%M:%S", &time::now()).unwrap();
+        let result = match self.notebook {
+            Some(ref mut notebook) => notebook.snapshot(&self.simulation, &timestamp, self.iteration, &parameters),
+            None => return
+        };
+        match result {
+            Ok(thumbnail) => println!("{}", thumbnail.display()),
+            Err(e) => {
+                println!("{}", e);
+                self.warnings.record(WarningKind::NotebookFailed, self.iteration, e);
+                self.notebook = None;
+            }
+        }
     }
 
-    fn update(&mut self,
-              args: &UpdateArgs) {
-        for _step in self.time_accumulator.update(args.dt) {
+    /// Replays the run from the start up to `target` steps, without
+    /// recording frames or events on the way.
+    fn seek<T>(&mut self,
+               target: usize) where S: Simulation<T> + Clone {
+        let initial = match self.initial {
+            Some(ref initial) => initial,
+            None => return
+        };
+        if target < self.iteration {
+            self.simulation = initial.clone();
+            self.iteration = 0;
+            self.metrics = Metrics::new();
+            self.plot.clear();
+        }
+
+        while self.iteration < target {
+            self.apply_replayed_inputs();
             self.simulation.advance();
+            self.iteration += 1;
+        }
+        self.followed = None;
+        println!("{}", self.simulation.stats());
+    }
+
+    /// Applies a user input and adds it to the run recording. Ignored
+    /// while replaying.
+    fn input<T>(&mut self,
+                input: Input) where S: Simulation<T> {
+        if self.replay.is_some() {
+            return;
+        }
+        input.apply(&mut self.simulation);
+
+        let failed = match self.run_writer {
+            Some(ref mut writer) => writer.write(TimedInput { iteration: self.iteration, input: input }).err(),
+            None => None
+        };
+        if let Some(e) = failed {
+            println!("{}", e);
+            self.run_writer = None;
+        }
+    }
+
+    fn paint_at_cursor<T>(&mut self) where S: Simulation<T> {
+        let brush = match self.painting {
+            Some(brush) => brush,
+            None => return
+        };
+
+        let board_size = (self.simulation.board().width, self.simulation.board().height);
+        if let Some((x, y)) = self.camera.board_position(board_size, self.viewport_size, self.cursor) {
+            let tip = self.brush_tip;
+            self.input(Input::Paint { x: x, y: y, brush: brush, tip: tip });
+        }
+    }
+
+    fn print_brush_tip(&self) {
+        println!("{}", self.opts.lang.format(Message::BrushTip, &[self.brush_tip.shape.name(),
+                                                                 &self.brush_tip.radius.to_string()]));
+    }
+
+    /// Scrolling with Ctrl held grows or shrinks the brush tip.
+    fn scroll(&mut self,
+              amount: [f64; 2]) {
+        if self.ctrl && amount[1] != 0.0 {
+            self.brush_tip = self.brush_tip.resized(amount[1].signum() as i32);
+            self.print_brush_tip();
+        }
+    }
+
+    fn cursor_cell<T>(&self) -> Option<(usize, usize)> where S: Simulation<T> {
+        let board_size = (self.simulation.board().width, self.simulation.board().height);
+        self.camera.board_position(board_size, self.viewport_size, self.cursor)
+    }
+
+    /// The minimap is only useful when part of the board is out of view.
+    fn show_minimap(&self) -> bool {
+        self.camera.zoom > 1.0
+    }
+
+    /// Board position under the cursor if it's on the minimap.
+    fn minimap_position<T>(&self) -> Option<(f64, f64)> where S: Simulation<T> {
+        if !self.show_minimap() {
+            return None;
+        }
+        let board_size = (self.simulation.board().width, self.simulation.board().height);
+        Minimap::board_position(board_size, self.viewport_size, self.cursor)
+    }
+
+    /// Centers the camera on the part of the board clicked on the minimap,
+    /// which stops following the densest part.
+    fn jump_to_minimap<T>(&mut self) where S: Simulation<T> {
+        if let Some(position) = self.minimap_position() {
+            self.camera.center = Some(position);
+            self.follow = false;
+            self.followed = None;
+        }
+    }
+
+    /// Follows the specimen under the cursor, if there is one, showing it
+    /// in the HUD.
+    fn pick_specimen<T>(&mut self) where S: Simulation<T> {
+        self.picking = false;
+        let picked = self.cursor_cell().and_then(|(x, y)| {
+            self.simulation.specimen_at(x, y).map(|specimen| ((x, y), specimen))
+        });
+        match picked {
+            Some(followed) => {
+                self.followed = Some(followed);
+                self.follow = false;
+                self.show_hud = true;
+                self.center_on_followed();
+            },
+            None => println!("{}", self.opts.lang.tr(Message::NoSpecimenThere))
+        }
+    }
+
+    /// Finds the followed specimen after a step: where the journal says it
+    /// moved, or where it was if it stayed. Whoever is there must have aged
+    /// by exactly the step, otherwise the followed one died or left the
+    /// board and another took its place.
+    fn track_specimen<T>(&mut self) where S: Simulation<T> {
+        let (position, specimen) = match self.followed {
+            Some(followed) => followed,
+            None => return
+        };
+        let moved = self.simulation.journal().and_then(|journal| {
+            journal.entries().iter().filter_map(|entry| match entry.event {
+                Event::Move { from, to } if from == position => Some(to),
+                _ => None
+            }).next()
+        });
+        let position = moved.unwrap_or(position);
+        self.followed = match self.simulation.specimen_at(position.0, position.1) {
+            Some(found) if found.age == specimen.age + 1 => Some((position, found)),
+            _ => {
+                println!("{}", self.opts.lang.tr(Message::FollowedSpecimenGone));
+                None
+            }
+        };
+        self.center_on_followed();
+    }
+
+    fn center_on_followed(&mut self) {
+        if let Some(((x, y), _)) = self.followed {
+            self.camera.center = Some((x as f64 + 0.5, y as f64 + 0.5));
+        }
+    }
+
+    /// Starts selecting cells from the one under the cursor.
+    fn start_selection<T>(&mut self) where S: Simulation<T> {
+        self.selecting = self.cursor_cell();
+        self.selection = self.selecting.map(|corner| Region::spanning(corner, corner));
+    }
+
+    fn extend_selection<T>(&mut self) where S: Simulation<T> {
+        if let (Some(corner), Some(cursor)) = (self.selecting, self.cursor_cell()) {
+            self.selection = Some(Region::spanning(corner, cursor));
+        }
+    }
+
+    /// Ctrl+C copies the selection and Ctrl+X cuts it. Ctrl+V pastes the
+    /// clipboard with its top left corner under the cursor, and Ctrl+R and
+    /// Ctrl+F rotate and flip it.
+    fn clipboard_key<T>(&mut self,
+                        key: Key) where S: Simulation<T> {
+        match key {
+            Key::C => self.copy_selection(),
+            Key::X => {
+                self.copy_selection();
+                self.delete_selection();
+            },
+            Key::V => {
+                if let (Some(region), Some((x, y))) = (self.clipboard.clone(), self.cursor_cell()) {
+                    self.input(Input::Paste { x: x, y: y, region: region });
+                }
+            },
+            Key::R => self.clipboard = self.clipboard.as_ref().map(|region| Arc::new(region.rotated())),
+            Key::F => self.clipboard = self.clipboard.as_ref().map(|region| Arc::new(region.flipped())),
+            _ => {}
+        }
+    }
+
+    fn copy_selection<T>(&mut self) where S: Simulation<T> {
+        let area = match self.selection {
+            Some(area) => area,
+            None => return
         };
+        match self.simulation.copy(area) {
+            Some(region) => {
+                println!("{}", self.opts.lang.format(Message::CellsCopied, &[&region.width.to_string(),
+                                                                            &region.height.to_string()]));
+                self.clipboard = Some(Arc::new(region));
+            },
+            None => println!("{}", self.opts.lang.format(Message::CopyNotSupported, &[self.opts.simulation.name()]))
+        }
+    }
+
+    /// Empties the selected cells.
+    fn delete_selection<T>(&mut self) where S: Simulation<T> {
+        if let Some(area) = self.selection {
+            self.input(Input::Paste {
+                x: area.x,
+                y: area.y,
+                region: Arc::new(merge::Region::blank(area.width, area.height))
+            });
+        }
+    }
+
+    fn occupancy<T>(&mut self) -> &Quadtree where S: Simulation<T>, T: CellColor {
+        if self.occupancy.is_none() {
+            self.occupancy = Some(Quadtree::of_board(self.simulation.board()));
+        }
+        self.occupancy.as_ref().unwrap()
+    }
+
+    /// Zooms the camera in on all occupied cells.
+    fn fit_to_pattern<T>(&mut self) where S: Simulation<T>, T: CellColor {
+        let board_size = (self.simulation.board().width, self.simulation.board().height);
+        if let Some(bounds) = self.occupancy().bounding_box() {
+            self.camera = Camera {
+                aspect: self.camera.aspect,
+                ..Camera::fit(bounds, board_size)
+            };
+        }
+    }
+
+    /// Centers the camera on the densest part of the board, keeping the
+    /// zoom. Parts of half the visible size are considered.
+    fn follow_action<T>(&mut self) where S: Simulation<T>, T: CellColor {
+        let board_size = (self.simulation.board().width, self.simulation.board().height);
+        let visible = min(board_size.0, board_size.1) as f64 / self.camera.zoom;
+        if let Some(densest) = self.occupancy().densest(max(1, (visible / 2.0) as usize)) {
+            self.camera.center = Some((densest.x as f64 + densest.width as f64 / 2.0,
+                                       densest.y as f64 + densest.height as f64 / 2.0));
+        }
+    }
+
+    /// Number keys select a brush, left mouse button paints with it and
+    /// the right one erases. B switches the brush tip between a square and
+    /// a circle, and scrolling with Ctrl held changes its radius. +/- adjust the simulation parameter, Y prints
+    /// the symmetry of the board, C toggles the density overlay, T the
+    /// trails of recently left cells, G the grid lines, H switches the
+    /// heatmap color map and N appends a snapshot to the lab notebook. [ and ] halve and double the
+    /// update rate and M toggles max speed. Z zooms in on the pattern and F
+    /// toggles following the densest part of the board. F12 saves a PNG
+    /// snapshot and V starts and stops recording. R restarts with a new
+    /// random seed, and , and . restart with the same seed on a board a
+    /// quarter smaller or a third larger.
+    ///
+    /// F1 toggles the HUD with the iteration, population, frame and update
+    /// rates and speed, P a plot of the population and mean energy over
+    /// the last --plot-length steps and E a histogram of specimen energies.
+    ///
+    /// When zoomed in, clicking the minimap moves the camera there.
+    ///
+    /// I followed by clicking a specimen makes the camera follow it, with
+    /// its energy, age and generation shown in the HUD, until I is pressed
+    /// again or the specimen dies.
+    ///
+    /// Dragging with Shift held selects cells, which Delete empties; see
+    /// `clipboard_key` for keys used with Ctrl.
+    ///
+    /// PageUp/PageDown show the next and previous layer of 3D simulations,
+    /// and F5 to F8 show or hide the layers of simulations made of several.
+    ///
+    /// While replaying, space pauses, [ and ] halve and double the playback
+    /// speed instead,
+    /// arrows step forward and back and PageUp/PageDown seek by
+    /// SEEK_STEPS.
+    fn press<T>(&mut self,
+                button: Button) where S: Simulation<T> + Clone + 'static, T: CellColor + PartialEq {
+        match button {
+            Button::Keyboard(Key::LShift) | Button::Keyboard(Key::RShift) => self.shift = true,
+            Button::Keyboard(Key::LCtrl) | Button::Keyboard(Key::RCtrl) => self.ctrl = true,
+            Button::Keyboard(key) if self.ctrl => return self.clipboard_key(key),
+            _ => {}
+        }
+        if self.replay.is_some() {
+            let iteration = self.iteration;
+            match button {
+                Button::Keyboard(Key::Space) => self.paused = !self.paused,
+                Button::Keyboard(Key::LeftBracket) => self.playback_speed /= 2.0,
+                Button::Keyboard(Key::RightBracket) => self.playback_speed *= 2.0,
+                Button::Keyboard(Key::Right) => self.step(),
+                Button::Keyboard(Key::Left) => self.seek(iteration.saturating_sub(1)),
+                Button::Keyboard(Key::PageUp) => self.seek(iteration.saturating_sub(SEEK_STEPS)),
+                Button::Keyboard(Key::PageDown) => self.seek(iteration + SEEK_STEPS),
+                _ => {}
+            }
+        }
+
+        match button {
+            Button::Keyboard(Key::D1) => self.brush = 1,
+            Button::Keyboard(Key::D2) => self.brush = 2,
+            Button::Keyboard(Key::D3) => self.brush = 3,
+            Button::Keyboard(Key::B) => {
+                self.brush_tip.shape = match self.brush_tip.shape {
+                    BrushShape::Square => BrushShape::Circle,
+                    BrushShape::Circle => BrushShape::Square
+                };
+                self.print_brush_tip();
+            },
+            Button::Keyboard(Key::W) => self.show_drift = !self.show_drift,
+            Button::Keyboard(Key::C) => self.show_density = !self.show_density,
+            Button::Keyboard(Key::G) => self.show_grid = !self.show_grid,
+            Button::Keyboard(Key::H) => self.cycle_palette(),
+            Button::Keyboard(Key::T) => {
+                self.trails = match self.trails {
+                    Some(_) => None,
+                    None => Some(Trails::of_board(self.simulation.board()))
+                }
+            },
+            Button::Keyboard(Key::N) => self.snapshot(),
+            Button::Keyboard(Key::M) => self.toggle_max_speed(),
+            Button::Keyboard(Key::Z) => self.fit_to_pattern(),
+            Button::Keyboard(Key::F12) => self.save_png(),
+            Button::Keyboard(Key::F1) => self.show_hud = !self.show_hud,
+            Button::Keyboard(Key::P) => self.show_plot = !self.show_plot,
+            Button::Keyboard(Key::E) => self.show_energy_histogram = !self.show_energy_histogram,
+            Button::Keyboard(Key::I) => {
+                if self.followed.take().is_none() {
+                    self.picking = !self.picking;
+                    if self.picking {
+                        println!("{}", self.opts.lang.tr(Message::PickSpecimen));
+                    }
+                }
+            },
+            Button::Keyboard(Key::F5) => self.toggle_layer(0),
+            Button::Keyboard(Key::F6) => self.toggle_layer(1),
+            Button::Keyboard(Key::F7) => self.toggle_layer(2),
+            Button::Keyboard(Key::F8) => self.toggle_layer(3),
+            Button::Keyboard(Key::V) => self.toggle_recording(),
+            Button::Keyboard(Key::R) => {
+                let board_size = self.opts.board_size;
+                self.restart(board_size, rand::random())
+            },
+            Button::Keyboard(Key::Comma) => self.restart_scaled(3, 4),
+            Button::Keyboard(Key::Period) => self.restart_scaled(4, 3),
+            Button::Keyboard(Key::F) => {
+                self.follow = !self.follow;
+                self.followed = None;
+                if self.follow {
+                    self.follow_action();
+                }
+            },
+            Button::Keyboard(Key::PageUp) if self.replay.is_none() => self.scrub_layer(1),
+            Button::Keyboard(Key::PageDown) if self.replay.is_none() => self.scrub_layer(-1),
+            Button::Keyboard(Key::LeftBracket) if self.replay.is_none() => self.change_speed(0.5),
+            Button::Keyboard(Key::RightBracket) if self.replay.is_none() => self.change_speed(2.0),
+            Button::Keyboard(Key::Y) => {
+                println!("{}", SymmetryReport::of_board(self.simulation.board(), self.symmetry_region))
+            },
+            Button::Keyboard(Key::Plus) | Button::Keyboard(Key::Equals)
+                | Button::Keyboard(Key::NumPadPlus) => self.input(Input::Adjust { steps: 1 }),
+            Button::Keyboard(Key::Minus)
+                | Button::Keyboard(Key::NumPadMinus) => self.input(Input::Adjust { steps: -1 }),
+            Button::Keyboard(Key::Delete) => self.delete_selection(),
+            Button::Mouse(MouseButton::Left) if self.minimap_position().is_some() => self.jump_to_minimap(),
+            Button::Mouse(MouseButton::Left) if self.picking => self.pick_specimen(),
+            Button::Mouse(MouseButton::Left) if self.shift => self.start_selection(),
+            Button::Mouse(MouseButton::Left) => self.painting = Some(self.brush),
+            Button::Mouse(MouseButton::Right) => self.painting = Some(0),
+            _ => {}
+        }
+        self.paint_at_cursor();
+    }
+
+    fn release(&mut self,
+               button: Button) {
+        match button {
+            Button::Mouse(MouseButton::Left) | Button::Mouse(MouseButton::Right) => {
+                self.painting = None;
+                self.selecting = None;
+            },
+            Button::Keyboard(Key::LShift) | Button::Keyboard(Key::RShift) => self.shift = false,
+            Button::Keyboard(Key::LCtrl) | Button::Keyboard(Key::RCtrl) => self.ctrl = false,
+            _ => {}
+        }
+    }
+
+    /// Lays the board out in the resized window right away, so that clicks
+    /// before the next frame land on the right cells.
+    fn resize(&mut self,
+              size: (u32, u32)) {
+        self.viewport_size = [size.0 as f64, size.1 as f64];
+    }
+
+    fn move_cursor<T>(&mut self,
+                      position: [f64; 2]) where S: Simulation<T> {
+        self.cursor = position;
+        self.paint_at_cursor();
+        self.extend_selection();
     }
 }
 
+#[derive(Clone)]
 struct Options {
-    board_size: (usize, usize)
+    lang: Language,
+    simulation: SimulationKind,
+    board_size: (usize, usize),
+    /// Number of layers of life3d boards, `life3d::DEFAULT_DEPTH` if not set.
+    board_depth: Option<usize>,
+    seed: Option<usize>,
+    updates_per_second: f64,
+    /// Run as fast as possible, see `App::max_speed`.
+    max_speed: bool,
+    /// Steps a single update may catch up with.
+    max_steps_per_frame: usize,
+    /// Drop time that couldn't be caught up with instead of catching up
+    /// with it later.
+    drop_lag: bool,
+    speed_ramp: Option<SpeedRamp>,
+    camera: Camera,
+    pattern: Option<(&'static [&'static str], (usize, usize))>,
+    /// Pattern loaded with --pattern-file, overriding `pattern`. Several
+    /// patterns are combined into one.
+    pattern_file: Option<Pattern>,
+    /// Top left corner of `pattern_file` on the board, centered if not set.
+    pattern_offset: Option<(usize, usize)>,
+    /// Generator of the initial state set with --init, replacing the
+    /// default start of the simulation.
+    init: Option<InitialState>,
+    storage: Storage,
+    /// Rules loaded with --script, for life and goodevil respectively.
+    cell_rule: Option<Arc<CellRule>>,
+    behavior: Option<Arc<SpecimenBehavior>>,
+    symmetry: bool,
+    symmetry_region: Option<Region>,
+    count_region: Option<Region>,
+    /// Directory frames are recorded to, see `recording::Recorder`.
+    record_frames: Option<String>,
+    /// GIF or video file the run is recorded to.
+    record_video: Option<String>,
+    record_fps: f64,
+    /// Recording starts paused, and is started and stopped with V.
+    record_on_key: bool,
+    record_region: Option<Region>,
+    record_scale: usize,
+    exposure: Option<String>,
+    exposure_window: usize,
+    /// File the simulation's journal is written to as JSON lines.
+    event_log: Option<String>,
+    /// Size the event log is rotated at, and the number of old files kept.
+    event_log_max_size: Option<u64>,
+    event_log_files: usize,
+    /// File stats are written to as JSON lines, see `schema::StatsRowV1`.
+    stats_log: Option<String>,
+    /// File metrics are written to as CSV, see `metrics::CsvSink`.
+    metrics_csv: Option<String>,
+    /// Address metrics are served on for Prometheus.
+    metrics_listen: Option<String>,
+    /// Metrics shown in the HUD.
+    hud_metrics: Vec<String>,
+    /// Steps plotted when P is pressed.
+    plot_length: usize,
+    /// Buckets of the energy histogram written to the stats log, and
+    /// drawn when E is pressed.
+    energy_histogram: Option<usize>,
+    /// Lab notebook snapshots are appended to, see `notebook`.
+    notebook: Option<String>,
+    /// Steps between PNG snapshots, and their file name template.
+    snapshot_every: Option<usize>,
+    snapshot_path: String,
+    theme: Theme,
+    /// Command line arguments, with --config expanded, without the program
+    /// name and --record.
+    args: Vec<String>,
+    /// Options given on the command line or in --config.
+    given: ConfigFile,
+    /// File the effective configuration is written to.
+    dump_config: Option<String>,
+    /// File the run is recorded to, see `run_recording`.
+    record: Option<String>,
+    replay: Option<RunRecording>,
+    headless: bool,
+    /// Time every simulation on standard workloads instead of running one.
+    bench: bool,
+    /// Print the time spent rendering and updating every second.
+    profile: bool,
+    render: Backend,
+    iterations: usize,
+    stop_criterion: Option<StopCriterion>,
+    manifest: Option<String>,
+    /// Diagnostics bundles are written to a new directory in here.
+    crash_dir: String,
+    checks: CheckLevel,
+    /// Directory of the parameter sweep run instead of a simulation.
+    sweep: Option<String>,
+    sweep_params: Vec<Param>,
+    sweep_threads: usize,
+    verify_replay: Option<usize>,
+    initial_specimens: Option<Vec<Specimen>>,
+    events: Vec<WorldEvent>,
+    merges: Vec<ScheduledMerge>,
+    random_event_probability: f32,
+    boundary: Boundary,
+    movement: MovementPolicy,
+    update_order: UpdateOrder,
+    conflicts: &'static ConflictResolver,
+    fairness_audit: Option<usize>,
+    /// Number of GoodEvil specimens, one per 20 cells if not set.
+    num_specimens: Option<usize>,
+    specimen_energy: f32,
+    energy_loss: f32,
+    deadly_energy_margin: f32,
+    action_costs: Option<ActionCosts>,
+    initial_energy: Option<EnergyDistribution>,
+    immigration_rate: f32,
+    immigrant_energy: f32,
+    drift: Option<DriftSource>,
+    show_drift: bool,
+    show_density: bool,
+    grid_lines: bool,
+    reputation: Option<ReputationConfig>,
+    predation: Option<PredationConfig>,
+    species: Option<SpeciesConfig>,
+    max_collision_size: Option<usize>,
+    interaction_radius: Option<usize>,
+    genetics: Option<GeneticsConfig>,
+    dormancy: Option<DormancyConfig>,
+    crowding: Option<CrowdingConfig>,
+    reproduction: Option<ReproductionConfig>,
+    food: Option<FoodConfig>,
+    epidemic: EpidemicConfig,
+    sandpile: SandpileConfig,
+    gray_scott: GrayScottConfig,
+    cyclic: CyclicConfig,
+    elementary: ElementaryConfig,
+    life3d: Life3DConfig,
+    boids: BoidsConfig,
+    ants: AntsConfig,
+    ising: IsingConfig,
+    fluid: FluidConfig,
+    turmites: TurmiteConfig,
+    forest_fire: ForestFireConfig
 }
 
 enum ParseResult {
@@ -113,107 +1181,2566 @@ impl Options {
         Ok(sizes)
     }
 
-    fn parse_size(string: Option<String>) -> Result<(usize,usize), String> {
-        match string {
-            None => Ok((80, 60)),
-            Some(s) => {
-                let sizes = try!(Options::parse_csv_ints(&s));
-
-                match sizes.len() {
-                    1 => Ok((sizes[0], sizes[0])),
-                    2 => Ok((sizes[0], sizes[1])),
-                    _ => {
-                        let msg = format!("invalid argument format: {}, expected SIZE or WIDTH,HEIGHT",
-                                          s);
-                        Err(msg)
-                    }
-                }
-            }
+    fn parse_value<T>(lang: Language,
+                      matches: &getopts::Matches,
+                      name: &str,
+                      default: T) -> Result<T, String> where T: FromStr, T::Err: fmt::Display {
+        match matches.opt_str(name) {
+            None => Ok(default),
+            Some(s) => s.parse::<T>().map_err(|e| {
+                lang.format(Message::InvalidValue, &[name, &s, &e.to_string()])
+            })
         }
     }
 
-    pub fn from_cmdline() -> ParseResult {
-        let args: Vec<String> = std::env::args().collect();
+    fn parse_probability(lang: Language,
+                         matches: &getopts::Matches,
+                         name: &str,
+                         default: f32) -> Result<f32, String> {
+        let value = try!(Options::parse_value(lang, matches, name, default));
+        if value >= 0.0 && value <= 1.0 {
+            Ok(value)
+        } else {
+            Err(lang.format(Message::InvalidProbability, &[name, &value.to_string()]))
+        }
+    }
 
-        let mut opts = getopts::Options::new();
-        opts.optopt("s", "board-size", "set board size", "WIDTH,HEIGHT");
-        opts.optflag("h", "help", "print this help message");
+    /// Like `parse_value`, rejecting infinities and NaN, which `f32` parses.
+    fn parse_finite(lang: Language,
+                    matches: &getopts::Matches,
+                    name: &str,
+                    default: f32) -> Result<f32, String> {
+        let value = try!(Options::parse_value(lang, matches, name, default));
+        if value.is_finite() {
+            Ok(value)
+        } else {
+            Err(lang.format(Message::InvalidValue, &[name, &value.to_string(), lang.tr(Message::MustBeFinite)]))
+        }
+    }
 
-        let matches = match opts.parse(&args[1..]) {
-            Ok(matches) => matches,
-            Err(e) => return ParseResult::Failure(e.to_string())
-        };
+    fn parse_epidemic_config(lang: Language,
+                             matches: &getopts::Matches) -> Result<EpidemicConfig, String> {
+        let default = EpidemicConfig::default();
 
-        if matches.opt_present("h") {
-            let msg = format!("Usage: {} [options]", args[0]);
-            print!("{}", opts.usage(&msg));
-            return ParseResult::Exit
+        let infection_duration = try!(Options::parse_value(lang, matches, "infection-duration",
+                                                           default.infection_duration));
+        if infection_duration == 0 {
+            return Err(lang.format(Message::ValueTooSmall, &["infection-duration", "1"]));
         }
 
-        let board_size = match Options::parse_size(matches.opt_str("s")) {
-            Ok(size) => size,
-            Err(e) => return ParseResult::Failure(e)
-        };
+        Ok(EpidemicConfig {
+            transmission_probability: try!(Options::parse_probability(lang, matches, "transmission",
+                                                                      default.transmission_probability)),
+            incubation_period: match matches.opt_str("incubation") {
+                None => default.incubation_period,
+                Some(_) => Some(try!(Options::parse_value(lang, matches, "incubation", 0)))
+            },
+            infection_duration: infection_duration,
+            immunity_loss_probability: try!(Options::parse_probability(lang, matches, "immunity-loss",
+                                                                       default.immunity_loss_probability)),
+            .. default
+        })
+    }
+
+    /// Drops are capped at one grain per cell and step.
+    fn parse_sandpile_config(lang: Language,
+                             matches: &getopts::Matches,
+                             board_size: (usize, usize)) -> Result<SandpileConfig, String> {
+        let default = SandpileConfig::default();
+
+        let drop_rate = try!(Options::parse_value(lang, matches, "drop-rate", default.drop_rate));
+        if !(drop_rate >= 0.0) {
+            return Err(lang.format(Message::ValueTooSmall, &["drop-rate", "0"]));
+        }
+        if !drop_rate.is_finite() {
+            return Err(lang.format(Message::InvalidValue, &["drop-rate", &drop_rate.to_string(),
+                                                            lang.tr(Message::MustBeFinite)]));
+        }
+        let area = board_size.0 * board_size.1;
+        if drop_rate > area as f32 {
+            return Err(lang.format(Message::ValueTooLarge, &["drop-rate", &area.to_string()]));
+        }
 
-        ParseResult::Success(Options {
-                board_size: board_size
+        Ok(SandpileConfig {
+            drop_rate: drop_rate
         })
     }
-}
 
-impl fmt::Display for Options {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "board_size: {}, {}", self.board_size.0, self.board_size.1)
+    fn parse_gray_scott_config(lang: Language,
+                               matches: &getopts::Matches) -> Result<GrayScottConfig, String> {
+        let default = GrayScottConfig::default();
+
+        Ok(GrayScottConfig {
+            feed_rate: try!(Options::parse_probability(lang, matches, "feed", default.feed_rate)),
+            kill_rate: try!(Options::parse_probability(lang, matches, "kill", default.kill_rate)),
+            .. default
+        })
     }
-}
 
-fn main() {
-    let opts = match Options::from_cmdline() {
-        ParseResult::Success(opts) => opts,
-        ParseResult::Failure(reason) => {
-            println!("{}", reason);
-            return
-        },
-        ParseResult::Exit => return
-    };
+    fn parse_forest_fire_config(lang: Language,
+                                matches: &getopts::Matches) -> Result<ForestFireConfig, String> {
+        let default = ForestFireConfig::default();
 
-    println!("Configuration:\n{}", opts);
+        let tree_density = try!(Options::parse_value(lang, matches, "tree-density", default.tree_density));
+        if !(tree_density >= 0.0 && tree_density <= 1.0) {
+            return Err(lang.format(Message::InvalidValue, &["tree-density", &tree_density.to_string(),
+                                                            lang.tr(Message::MustBeFraction)]));
+        }
 
-    let gl_version = OpenGL::V3_2;
+        Ok(ForestFireConfig {
+            tree_density: tree_density,
+            growth_probability: try!(Options::parse_probability(lang, matches, "growth", default.growth_probability)),
+            lightning_probability: try!(Options::parse_probability(lang, matches, "lightning",
+                                                                   default.lightning_probability))
+        })
+    }
 
-    let window: Window = WindowSettings::new("cell", [800, 600])
-            .opengl(gl_version)
-            .exit_on_esc(true)
-            .build()
-            .unwrap();
+    fn parse_cyclic_config(lang: Language,
+                           matches: &getopts::Matches) -> Result<CyclicConfig, String> {
+        let default = CyclicConfig::default();
 
-    let sim_cfg = GoodEvilConfig {
-        num_specimens: opts.board_size.0 * opts.board_size.1 / 20,
-        initial_specimen_energy: 1.0f32,
-        energy_loss_per_step: 0.001f32,
-        deadly_energy_margin: 0.0f32
-    };
-    let rng = Box::new(StdRng::new().unwrap());
+        let num_states = try!(Options::parse_value(lang, matches, "states", default.num_states));
+        if num_states < 2 {
+            return Err(lang.format(Message::ValueTooSmall, &["states", "2"]));
+        }
 
-    let mut app = App {
-        gl: GlGraphics::new(gl_version),
-        simulation: GoodEvil::new(opts.board_size.0,
-                                  opts.board_size.1,
-                                  sim_cfg, rng),
-        time_accumulator: TimeAccumulator::new(0.01f64)
-    };
+        let threshold = try!(Options::parse_value(lang, matches, "threshold", default.threshold));
+        if threshold < 1 {
+            return Err(lang.format(Message::ValueTooSmall, &["threshold", "1"]));
+        }
 
-    let mut fps_meter = TickMeter::new().with_auto_display("FPS: ");
-    let mut update_meter = TickMeter::new().with_auto_display("Updates/s: ");
+        Ok(CyclicConfig {
+            num_states: num_states,
+            threshold: threshold
+        })
+    }
 
-    for e in window.events() {
-        if let Some(render_args) = e.render_args() {
-            app.render(&render_args);
-            fps_meter.tick();
+    /// Fails if GoodEvil specimens don't fit on the cells left free by the
+    /// walls of --pattern-file.
+    fn check_free_cells(&self) -> Result<(), String> {
+        let pattern = match self.pattern_file {
+            Some(ref pattern) if self.simulation == SimulationKind::GoodEvil => pattern,
+            _ => return Ok(())
+        };
+        let count = match (self.initial_specimens.as_ref(), self.num_specimens) {
+            (Some(specimens), _) => specimens.len(),
+            (None, Some(count)) => count,
+            (None, None) => self.board_size.0 * self.board_size.1 / 20
+        };
+        let free = self.pattern_board(pattern).iter().filter(|&&wall| !wall).count();
+        if count > free {
+            return Err(self.lang.format(Message::TooManySpecimensForWalls, &[&count.to_string(), &free.to_string()]));
         }
-        if let Some(update_args) = e.update_args() {
-            app.update(&update_args);
-            update_meter.tick();
+        Ok(())
+    }
+
+    /// Supports `--seed-population from-run:PATH` as well as
+    /// `--seed-population from-run PATH`.
+    fn parse_seed_population(lang: Language,
+                             matches: &getopts::Matches,
+                             simulation: SimulationKind,
+                             board_size: (usize, usize)) -> Result<Option<Vec<Specimen>>, String> {
+        let source = match matches.opt_str("seed-population") {
+            None => return Ok(None),
+            Some(source) => source
+        };
+        if simulation != SimulationKind::GoodEvil {
+            return Err(lang.tr(Message::SeedPopulationNotSupported).to_string());
+        }
+
+        let path = if source.starts_with("from-run:") {
+            source["from-run:".len()..].to_string()
+        } else if source == "from-run" && matches.free.len() == 1 {
+            matches.free[0].clone()
+        } else {
+            return Err(lang.format(Message::InvalidSeedPopulation, &[&source]));
+        };
+
+        let manifest = try!(Manifest::load(&path));
+        if manifest.simulation != SimulationKind::GoodEvil.name() {
+            return Err(lang.format(Message::SeedPopulationWrongSimulation, &[&path, &manifest.simulation]));
+        }
+        if manifest.specimens.len() > board_size.0 * board_size.1 {
+            return Err(lang.format(Message::TooManySpecimens,
+                                   &[&path,
+                                     &manifest.specimens.len().to_string(),
+                                     &format!("{}x{}", board_size.0, board_size.1)]));
+        }
+
+        Ok(Some(manifest.specimens))
+    }
+
+    fn parse_ants_config(lang: Language,
+                         matches: &getopts::Matches) -> Result<AntsConfig, String> {
+        let default = AntsConfig::default();
+
+        Ok(AntsConfig {
+            num_ants: try!(Options::parse_value(lang, matches, "ants", default.num_ants)),
+            evaporation: try!(Options::parse_probability(lang, matches, "evaporation", default.evaporation)),
+            .. default
+        })
+    }
+
+    fn parse_fluid_config(lang: Language,
+                          matches: &getopts::Matches) -> Result<FluidConfig, String> {
+        let default = FluidConfig::default();
+
+        let relaxation_time = try!(Options::parse_value(lang, matches, "relaxation", default.relaxation_time));
+        if relaxation_time <= 0.5 {
+            return Err(lang.format(Message::InvalidValue, &["relaxation", &relaxation_time.to_string(),
+                                                             lang.tr(Message::MustBeAboveHalf)]));
+        }
+
+        Ok(FluidConfig {
+            relaxation_time: relaxation_time,
+            inflow: try!(Options::parse_value(lang, matches, "inflow", default.inflow))
+        })
+    }
+
+    fn parse_turmite_config(lang: Language,
+                            matches: &getopts::Matches) -> Result<TurmiteConfig, String> {
+        let default = TurmiteConfig::default();
+
+        Ok(TurmiteConfig {
+            num_turmites: try!(Options::parse_value(lang, matches, "turmites", default.num_turmites)),
+            table: match matches.opt_str("turmite-table") {
+                Some(path) => try!(TransitionTable::load(&path)),
+                None => default.table
+            }
+        })
+    }
+
+    /// Predation is enabled with --predation.
+    fn parse_predation_config(lang: Language,
+                              matches: &getopts::Matches) -> Result<Option<PredationConfig>, String> {
+        if !matches.opt_present("predation") {
+            return Ok(None);
+        }
+
+        let default = PredationConfig::default();
+        let margin = try!(Options::parse_value(lang, matches, "predation", default.margin));
+        if margin < 0.0 {
+            return Err(lang.format(Message::ValueTooSmall, &["predation", "0"]));
+        }
+
+        Ok(Some(PredationConfig {
+            margin: margin,
+            efficiency: try!(Options::parse_probability(lang, matches, "predation-efficiency", default.efficiency))
+        }))
+    }
+
+    /// Species are enabled with --species.
+    fn parse_species_config(lang: Language,
+                            matches: &getopts::Matches) -> Result<Option<SpeciesConfig>, String> {
+        match matches.opt_str("species") {
+            None => Ok(None),
+            Some(s) => SpeciesConfig::parse(&s)
+                           .map(Some)
+                           .map_err(|e| lang.format(Message::InvalidValue, &["species", &s, &e]))
+        }
+    }
+
+    /// Food is enabled with --food.
+    fn parse_food_config(lang: Language,
+                         matches: &getopts::Matches) -> Result<Option<FoodConfig>, String> {
+        if !matches.opt_present("food") {
+            return Ok(None);
+        }
+
+        let default = FoodConfig::default();
+        Ok(Some(FoodConfig {
+            initial_density: try!(Options::parse_probability(lang, matches, "food", default.initial_density)),
+            regrowth_rate: try!(Options::parse_probability(lang, matches, "food-regrowth", default.regrowth_rate)),
+            energy: try!(Options::parse_finite(lang, matches, "food-energy", default.energy))
+        }))
+    }
+
+    /// Genetics are enabled with --mutation-rate.
+    fn parse_genetics_config(lang: Language,
+                             matches: &getopts::Matches) -> Result<Option<GeneticsConfig>, String> {
+        if !matches.opt_present("mutation-rate") {
+            return Ok(None);
+        }
+
+        let default = GeneticsConfig::default();
+        let mutation_size = try!(Options::parse_value(lang, matches, "mutation-size", default.mutation_size));
+        if mutation_size <= 0.0 {
+            return Err(lang.format(Message::InvalidValue, &["mutation-size", &mutation_size.to_string(),
+                                                            lang.tr(Message::MustBePositive)]));
+        }
+
+        Ok(Some(GeneticsConfig {
+            mutation_rate: try!(Options::parse_probability(lang, matches, "mutation-rate", default.mutation_rate)),
+            mutation_size: mutation_size
+        }))
+    }
+
+    /// Dormancy is enabled with --dormancy.
+    fn parse_dormancy_config(lang: Language,
+                             matches: &getopts::Matches) -> Result<Option<DormancyConfig>, String> {
+        if !matches.opt_present("dormancy") {
+            return Ok(None);
+        }
+
+        let default = DormancyConfig::default();
+        Ok(Some(DormancyConfig {
+            threshold: try!(Options::parse_finite(lang, matches, "dormancy", default.threshold)),
+            loss_factor: try!(Options::parse_probability(lang, matches, "dormant-loss", default.loss_factor)),
+            .. default
+        }))
+    }
+
+    /// Crowding penalties are enabled with --crowding.
+    fn parse_crowding_config(lang: Language,
+                             matches: &getopts::Matches) -> Result<Option<CrowdingConfig>, String> {
+        if !matches.opt_present("crowding") {
+            return Ok(None);
+        }
+
+        let default = CrowdingConfig::default();
+        let coefficient = try!(Options::parse_value(lang, matches, "crowding", default.coefficient));
+        if coefficient < 0.0 {
+            return Err(lang.format(Message::ValueTooSmall, &["crowding", "0"]));
+        }
+        Ok(Some(CrowdingConfig {
+            coefficient: coefficient,
+            capacity: try!(Options::parse_value(lang, matches, "crowding-capacity", default.capacity))
+        }))
+    }
+
+    /// Reproduction is enabled with --reproduction, and implied by genetics.
+    fn parse_reproduction_config(lang: Language,
+                                 matches: &getopts::Matches,
+                                 genetics: bool) -> Result<Option<ReproductionConfig>, String> {
+        if !matches.opt_present("reproduction") && !genetics {
+            return Ok(None);
+        }
+
+        let default = ReproductionConfig::default();
+        Ok(Some(ReproductionConfig {
+            threshold: try!(Options::parse_finite(lang, matches, "reproduction-threshold", default.threshold)),
+            child_share: try!(Options::parse_probability(lang, matches, "child-share", default.child_share)),
+            cooldown: try!(Options::parse_value(lang, matches, "reproduction-cooldown", default.cooldown))
+        }))
+    }
+
+    /// --defection and --reputation-decay only make sense with --reputation.
+    fn parse_reputation_config(lang: Language,
+                               matches: &getopts::Matches) -> Result<Option<ReputationConfig>, String> {
+        if !matches.opt_present("reputation") {
+            return Ok(None);
+        }
+
+        let default = ReputationConfig::default();
+        Ok(Some(ReputationConfig {
+            decay: try!(Options::parse_probability(lang, matches, "reputation-decay", default.decay)),
+            defection_probability: try!(Options::parse_probability(lang, matches, "defection",
+                                                                   default.defection_probability)),
+            .. default
+        }))
+    }
+
+    /// At most one of --wind, --wind-noise and --wind-file may be given.
+    fn parse_drift(lang: Language,
+                   matches: &getopts::Matches,
+                   board_size: (usize, usize)) -> Result<Option<DriftSource>, String> {
+        let given = ["wind", "wind-noise", "wind-file"].iter()
+                                                       .filter(|name| matches.opt_present(name))
+                                                       .count();
+        if given > 1 {
+            return Err(lang.tr(Message::ConflictingWind).to_string());
+        }
+
+        if let Some(s) = matches.opt_str("wind") {
+            return match drift::parse_vector(&s) {
+                Some(drift) => Ok(Some(DriftSource::Uniform(drift))),
+                None => Err(lang.format(Message::InvalidValue, &[
+                    "wind", &s, &lang.format(Message::ExpectedFormat, &["DX,DY"])]))
+            };
+        }
+        if matches.opt_present("wind-noise") {
+            let strength = try!(Options::parse_value(lang, matches, "wind-noise", 0.0f32));
+            if !(strength >= 0.0 && strength.is_finite()) {
+                return Err(lang.format(Message::InvalidValue, &["wind-noise", &strength.to_string(),
+                                                                lang.tr(Message::MustBeFiniteNonNegative)]));
+            }
+            return Ok(Some(DriftSource::Noise(strength)));
+        }
+        if let Some(path) = matches.opt_str("wind-file") {
+            let field = try!(DriftField::load(&path, board_size.0, board_size.1));
+            return Ok(Some(DriftSource::Field(field)));
+        }
+
+        Ok(None)
+    }
+
+    /// The language has to be known before the usage message is built, so
+    /// --lang is looked up before the regular option parsing.
+    fn parse_language(args: &[String]) -> Result<Language, String> {
+        let mut code = None;
+
+        for (idx, arg) in args.iter().enumerate() {
+            if arg == "--lang" {
+                code = args.get(idx + 1).cloned();
+            } else if arg.starts_with("--lang=") {
+                code = Some(arg["--lang=".len()..].to_string());
+            }
+        }
+
+        match code {
+            None => Ok(Language::from_env()),
+            Some(code) => Language::from_code(&code).ok_or_else(|| {
+                Language::English.format(Message::UnknownLanguage, &[&code])
+            })
+        }
+    }
+
+    /// Parses SIZE, WIDTH,HEIGHT or WIDTH,HEIGHT,DEPTH, returning the depth
+    /// separately as only life3d boards have one.
+    fn parse_size(lang: Language,
+                  string: Option<String>,
+                  default: (usize, usize)) -> Result<((usize,usize), Option<usize>), String> {
+        match string {
+            None => Ok((default, None)),
+            Some(s) => {
+                let sizes = try!(Options::parse_csv_ints(&s));
+
+                match sizes.len() {
+                    1 => Ok(((sizes[0], sizes[0]), None)),
+                    2 => Ok(((sizes[0], sizes[1]), None)),
+                    3 => Ok(((sizes[0], sizes[1]), Some(sizes[2]))),
+                    _ => {
+                        Err(lang.format(Message::InvalidSize, &[&s]))
+                    }
+                }
+            }
+        }
+    }
+
+    /// Parses "X,Y,W,H" given as `name`, checking that the region lies on
+    /// the board.
+    fn parse_region(lang: Language,
+                    matches: &getopts::Matches,
+                    name: &str,
+                    board_size: (usize, usize)) -> Result<Option<Region>, String> {
+        match matches.opt_str(name) {
+            None => Ok(None),
+            Some(s) => match Region::parse(&s) {
+                Some(region) if region.x.checked_add(region.width).map_or(false, |end| end <= board_size.0)
+                                && region.y.checked_add(region.height).map_or(false, |end| end <= board_size.1) => {
+                    Ok(Some(region))
+                },
+                Some(_) => {
+                    let size = format!("{}x{}", board_size.0, board_size.1);
+                    Err(lang.format(Message::RegionOutsideBoard, &[&s, &size]))
+                },
+                None => Err(lang.format(Message::InvalidValue, &[
+                    name, &s, &lang.format(Message::ExpectedFormat, &["X,Y,W,H"])]))
+            }
+        }
+    }
+
+    /// Live cells of a --pattern-file pattern, at --pattern-offset or in
+    /// the middle of the board.
+    fn pattern_board(&self,
+                     pattern: &Pattern) -> Board<bool> {
+        match self.pattern_offset {
+            Some(offset) => pattern.to_board_at(self.board_size, offset, true, false),
+            None => pattern.to_board(self.board_size, true, false)
+        }
+    }
+
+    fn recorder(&self) -> Option<Recorder> {
+        let recorder = match (self.record_frames.as_ref(), self.record_video.as_ref()) {
+            (Some(dir), _) => Recorder::new(dir, self.record_region, self.record_scale),
+            (None, Some(path)) => Recorder::video(path, self.record_region, self.record_scale, self.record_fps),
+            (None, None) => return None
+        };
+        let recorder = recorder.with_theme(self.theme.clone())
+                               .with_paused(self.record_on_key);
+        Some(match self.speed_ramp {
+            Some(ref ramp) => recorder.with_speed_ramp(ramp.clone()),
+            None => recorder
+        })
+    }
+
+    /// Starts recording the run if --record is given, printing the error
+    /// and going on without recording if that fails.
+    fn run_writer(&self) -> Option<RunWriter> {
+        let seed = self.seed.expect("seed should be picked before the run starts");
+        match self.record.as_ref().map(|path| RunWriter::create(path, &self.args, seed)) {
+            Some(Ok(writer)) => Some(writer),
+            Some(Err(e)) => {
+                println!("{}", e);
+                None
+            },
+            None => None
+        }
+    }
+
+    fn notebook(&self) -> Option<Notebook> {
+        self.notebook.as_ref().map(|path| Notebook::new(path, self.theme.clone()))
+    }
+
+    /// Snapshots taken every --snapshot-every steps, if given, and with F12.
+    fn snapshots(&self) -> Snapshots {
+        let snapshots = Snapshots::new(&self.snapshot_path, self.record_scale).unwrap()
+                                  .with_theme(self.theme.clone());
+        match self.snapshot_every {
+            Some(every) => snapshots.with_interval(every),
+            None => snapshots
+        }
+    }
+
+    fn exposure(&self) -> Option<Exposure> {
+        self.exposure.as_ref().map(|dir| Exposure::new(dir, self.exposure_window, self.record_scale))
+    }
+
+    /// Opens --event-log, printing the error and going on without the log
+    /// if that fails.
+    fn event_log(&self) -> Option<EventLog> {
+        match self.event_log.as_ref().map(|path| EventLog::create(path)) {
+            Some(Ok(log)) => match self.event_log_max_size {
+                Some(max_size) => Some(log.with_rotation(max_size, self.event_log_files)),
+                None => Some(log)
+            },
+            Some(Err(e)) => {
+                println!("{}", e);
+                None
+            },
+            None => None
+        }
+    }
+
+    /// Opens --stats-log, printing the error and going on without the log
+    /// if that fails.
+    fn stats_log(&self) -> Option<StatsLog> {
+        match self.stats_log.as_ref().map(|path| StatsLog::create(path)) {
+            Some(Ok(log)) => Some(log),
+            Some(Err(e)) => {
+                println!("{}", e);
+                None
+            },
+            None => None
+        }
+    }
+
+    /// Sinks of the metrics of the window or terminal view: stdout in the
+    /// window, as the terminal view shows stats itself, and the ones given
+    /// by options. Sinks that can't be set up are printed and left out.
+    fn sinks(&self) -> Vec<Box<Sink>> {
+        let mut sinks: Vec<Box<Sink>> = Vec::new();
+        if self.render == Backend::Window {
+            sinks.push(Box::new(StdoutSink));
+        }
+        match self.metrics_csv.as_ref().map(|path| CsvSink::create(path)) {
+            Some(Ok(sink)) => sinks.push(Box::new(sink)),
+            Some(Err(e)) => println!("{}", e),
+            None => ()
+        }
+        match self.metrics_listen.as_ref().map(|address| PrometheusSink::bind(address)) {
+            Some(Ok(sink)) => sinks.push(Box::new(sink)),
+            Some(Err(e)) => println!("{}", e),
+            None => ()
+        }
+        if !self.hud_metrics.is_empty() {
+            sinks.push(Box::new(HudSink::new(self.hud_metrics.clone())));
+        }
+        sinks
+    }
+
+    /// Directories the files given by output options are created in.
+    /// Options naming directories are left out, as those get created.
+    fn output_dirs(&self) -> Vec<(&'static str, PathBuf)> {
+        let files = [("record-video", self.record_video.as_ref()),
+                     ("event-log", self.event_log.as_ref()),
+                     ("stats-log", self.stats_log.as_ref()),
+                     ("metrics-csv", self.metrics_csv.as_ref()),
+                     ("notebook", self.notebook.as_ref()),
+                     ("dump-config", self.dump_config.as_ref()),
+                     ("record", self.record.as_ref()),
+                     ("manifest", self.manifest.as_ref()),
+                     ("snapshot-path", self.snapshot_every.map(|_| &self.snapshot_path))];
+
+        files.iter()
+             .filter_map(|&(name, path)| path.map(|path| (name, path)))
+             .map(|(name, path)| match Path::new(path).parent() {
+                 Some(dir) if dir != Path::new("") => (name, dir.to_path_buf()),
+                 _ => (name, PathBuf::from("."))
+             })
+             .collect()
+    }
+
+    pub fn from_cmdline() -> ParseResult {
+        Options::from_args(std::env::args().collect())
+    }
+
+    /// Options set by `args`, for writing them back with --dump-config.
+    fn given_options(args: &[String],
+                     matches: &getopts::Matches) -> ConfigFile {
+        const NOT_DUMPED: &'static [&'static str] = &["config", "dump-config", "record", "replay"];
+        let mut given = ConfigFile::new();
+
+        for arg in args.iter().filter(|arg| arg.starts_with("--") && arg.len() > 2) {
+            let name = arg[2..].split('=').next().unwrap_or("");
+            if NOT_DUMPED.contains(&name) || given.get(name).is_some() || !matches.opts_present(&[name.to_string()]) {
+                continue;
+            }
+
+            let mut values = matches.opt_strs(name);
+            let value = match values.len() {
+                0 => config_file::Value::Flag(true),
+                1 => config_file::Value::Text(values.remove(0)),
+                _ => config_file::Value::List(values)
+            };
+            given.set(name, value);
+        }
+        given
+    }
+
+    /// Configuration the simulation runs with: the options given, and the
+    /// simulation, board size and seed actually used.
+    fn effective_config(&self) -> ConfigFile {
+        let mut config = self.given.clone();
+        config.set("sim", config_file::Value::Text(self.simulation.name().to_string()));
+        let board_size = match self.board_depth {
+            Some(depth) => format!("{},{},{}", self.board_size.0, self.board_size.1, depth),
+            None => format!("{},{}", self.board_size.0, self.board_size.1)
+        };
+        config.set("board-size", config_file::Value::Text(board_size));
+        if let Some(seed) = self.seed {
+            config.set("seed", config_file::Value::Text(seed.to_string()));
+        }
+        config
+    }
+
+    /// Parses `args` with the options from the --config file added, unless
+    /// they are given in `args` already.
+    fn from_config(args: &[String],
+                   matches: &getopts::Matches,
+                   path: &str) -> ParseResult {
+        let config = match ConfigFile::load(path) {
+            Ok(config) => config,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let mut merged = Options::without_option(args, "config");
+        merged.extend(config.to_args(|name| matches.opts_present(&[name.to_string()])));
+        Options::from_args(merged)
+    }
+
+    /// Loads a --pattern-file given as PATH[@X,Y].
+    fn load_pattern(lang: Language,
+                    spec: &str) -> Result<(Pattern, Option<(usize, usize)>), String> {
+        let (path, offset) = match spec.rfind('@') {
+            Some(pos) => {
+                let offset = spec[pos + 1..].to_string();
+                match Options::parse_csv_ints(&offset) {
+                    Ok(ref values) if values.len() == 2 => (&spec[..pos], Some((values[0], values[1]))),
+                    _ => return Err(lang.format(Message::InvalidValue, &[
+                        "pattern-file", spec, &lang.format(Message::ExpectedFormat, &["PATH[@X,Y]"])]))
+                }
+            },
+            None => (spec, None)
+        };
+        let (pattern, cropped) = try!(Pattern::load_cropped(path));
+        if let Some((width, height)) = cropped {
+            println!("{}", lang.format(Message::PatternCropped, &[path, &width.to_string(), &height.to_string(),
+                                                                  &pattern.width.to_string(),
+                                                                  &pattern.height.to_string()]));
+        }
+        Ok((pattern, offset))
+    }
+
+    /// `args` without `name` and its value.
+    fn without_option(args: &[String],
+                      name: &str) -> Vec<String> {
+        let flag = format!("--{}", name);
+        let prefix = format!("--{}=", name);
+        let mut result = Vec::new();
+        let mut skip_value = false;
+
+        for arg in args {
+            if skip_value {
+                skip_value = false;
+            } else if *arg == flag {
+                skip_value = true;
+            } else if !arg.starts_with(&prefix) {
+                result.push(arg.clone());
+            }
+        }
+        result
+    }
+
+    /// Loads a run recording and parses the options it was made with.
+    fn from_recording(program: &str,
+                      path: &str) -> ParseResult {
+        let recording = match RunRecording::load(path) {
+            Ok(recording) => recording,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let mut args = vec![program.to_string()];
+        args.extend(recording.args.iter().cloned());
+        match Options::from_args(args) {
+            ParseResult::Success(opts) => ParseResult::Success(Options {
+                seed: Some(recording.seed),
+                replay: Some(recording),
+                .. opts
+            }),
+            other => other
+        }
+    }
+
+    fn from_args(args: Vec<String>) -> ParseResult {
+        let lang = match Options::parse_language(&args[1..]) {
+            Ok(lang) => lang,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let mut opts = getopts::Options::new();
+        opts.optopt("s", "board-size", lang.tr(Message::HelpBoardSize), "WIDTH,HEIGHT[,DEPTH]");
+        let sim_names: Vec<&str> = SimulationKind::all().iter().map(|kind| kind.name()).collect();
+        opts.optopt("", "sim", &lang.format(Message::HelpSim, &[&sim_names.join(", ")]), "NAME");
+        opts.optopt("", "seed", lang.tr(Message::HelpSeed), "SEED");
+        opts.optopt("", "demo", lang.tr(Message::HelpDemo), "NAME");
+        opts.optopt("", "lang", lang.tr(Message::HelpLang), "LANG");
+        opts.optopt("", "ups", lang.tr(Message::HelpUps), "N|max");
+        opts.optopt("", "max-steps-per-frame", lang.tr(Message::HelpMaxStepsPerFrame), "N");
+        opts.optflag("", "drop-lag", lang.tr(Message::HelpDropLag));
+        opts.optopt("", "config", lang.tr(Message::HelpConfig), "PATH");
+        opts.optopt("", "dump-config", lang.tr(Message::HelpDumpConfig), "PATH");
+        opts.optflag("", "headless", lang.tr(Message::HelpHeadless));
+        opts.optflag("", "bench", lang.tr(Message::HelpBench));
+        opts.optflag("", "profile", lang.tr(Message::HelpProfile));
+        opts.optopt("", "render", lang.tr(Message::HelpRender), "window|tui");
+        opts.optopt("", "iterations", lang.tr(Message::HelpIterations), "N");
+        opts.optopt("", "stop-when", lang.tr(Message::HelpStopWhen), "NAME<VALUE");
+        opts.optopt("", "manifest", lang.tr(Message::HelpManifest), "PATH");
+        opts.optopt("", "crash-dir", lang.tr(Message::HelpCrashDir), "DIR");
+        opts.optopt("", "checks", lang.tr(Message::HelpChecks), "off|cheap|expensive|paranoid");
+        opts.optopt("", "sweep", lang.tr(Message::HelpSweep), "DIR");
+        opts.optmulti("", "sweep-param", lang.tr(Message::HelpSweepParam), "NAME=VALUE,...");
+        opts.optopt("", "sweep-threads", lang.tr(Message::HelpSweepThreads), "N");
+        opts.optopt("", "verify-replay", lang.tr(Message::HelpVerifyReplay), "TICKS");
+        opts.optopt("", "seed-population", lang.tr(Message::HelpSeedPopulation), "from-run PATH");
+        opts.optmulti("", "event", lang.tr(Message::HelpEvent), "NAME@ITERATION[:PARAM]");
+        opts.optmulti("", "merge", lang.tr(Message::HelpMerge), "PATH@ITERATION:X,Y[:POLICY]");
+        opts.optopt("", "random-events", lang.tr(Message::HelpRandomEvents), "PROBABILITY");
+        opts.optopt("", "boundary", lang.tr(Message::HelpBoundary), "closed|open");
+        opts.optopt("", "movement", lang.tr(Message::HelpMovement), "random|gradient");
+        opts.optopt("", "update-order", lang.tr(Message::HelpUpdateOrder), "row-major|shuffled");
+        opts.optopt("", "conflicts", lang.tr(Message::HelpConflicts), "strongest|random|first-come");
+        opts.optopt("", "fairness-audit", lang.tr(Message::HelpFairnessAudit), "REGIONS");
+        opts.optopt("", "specimens", lang.tr(Message::HelpSpecimens), "N");
+        opts.optopt("", "specimen-energy", lang.tr(Message::HelpSpecimenEnergy), "ENERGY");
+        opts.optopt("", "energy-loss", lang.tr(Message::HelpEnergyLoss), "ENERGY");
+        opts.optopt("", "deadly-margin", lang.tr(Message::HelpDeadlyMargin), "ENERGY");
+        opts.optopt("", "action-costs", lang.tr(Message::HelpActionCosts), "STAY,ORTHOGONAL,DIAGONAL");
+        opts.optopt("", "initial-energy", lang.tr(Message::HelpInitialEnergy), "DIST");
+        opts.optopt("", "immigration", lang.tr(Message::HelpImmigration), "RATE");
+        opts.optopt("", "immigrant-energy", lang.tr(Message::HelpImmigrantEnergy), "ENERGY");
+        opts.optopt("", "wind", lang.tr(Message::HelpWind), "DX,DY");
+        opts.optopt("", "wind-noise", lang.tr(Message::HelpWindNoise), "STRENGTH");
+        opts.optopt("", "wind-file", lang.tr(Message::HelpWindFile), "PATH");
+        opts.optflag("", "show-wind", lang.tr(Message::HelpShowWind));
+        opts.optflag("", "reputation", lang.tr(Message::HelpReputation));
+        opts.optopt("", "defection", lang.tr(Message::HelpDefection), "PROBABILITY");
+        opts.optopt("", "reputation-decay", lang.tr(Message::HelpReputationDecay), "FRACTION");
+        opts.optopt("", "predation", lang.tr(Message::HelpPredation), "MARGIN");
+        opts.optopt("", "predation-efficiency", lang.tr(Message::HelpPredationEfficiency), "FRACTION");
+        opts.optopt("", "species", lang.tr(Message::HelpSpecies), "MATRIX");
+        opts.optopt("", "max-collision", lang.tr(Message::HelpMaxCollision), "N");
+        opts.optopt("", "interaction-radius", lang.tr(Message::HelpInteractionRadius), "CELLS");
+        opts.optopt("", "mutation-rate", lang.tr(Message::HelpMutationRate), "PROBABILITY");
+        opts.optopt("", "mutation-size", lang.tr(Message::HelpMutationSize), "SIZE");
+        opts.optopt("", "dormancy", lang.tr(Message::HelpDormancy), "ENERGY");
+        opts.optopt("", "dormant-loss", lang.tr(Message::HelpDormantLoss), "FRACTION");
+        opts.optopt("", "crowding", lang.tr(Message::HelpCrowding), "COEFFICIENT");
+        opts.optopt("", "crowding-capacity", lang.tr(Message::HelpCrowdingCapacity), "NEIGHBORS");
+        opts.optflag("", "show-density", lang.tr(Message::HelpShowDensity));
+        opts.optflag("", "grid-lines", lang.tr(Message::HelpGridLines));
+        opts.optopt("", "aspect", lang.tr(Message::HelpAspect), "MODE");
+        opts.optflag("", "reproduction", lang.tr(Message::HelpReproduction));
+        opts.optopt("", "reproduction-threshold", lang.tr(Message::HelpReproductionThreshold), "ENERGY");
+        opts.optopt("", "child-share", lang.tr(Message::HelpChildShare), "FRACTION");
+        opts.optopt("", "reproduction-cooldown", lang.tr(Message::HelpReproductionCooldown), "STEPS");
+        opts.optopt("", "food", lang.tr(Message::HelpFood), "DENSITY");
+        opts.optopt("", "food-regrowth", lang.tr(Message::HelpFoodRegrowth), "RATE");
+        opts.optopt("", "food-energy", lang.tr(Message::HelpFoodEnergy), "ENERGY");
+        opts.optmulti("", "pattern-file", lang.tr(Message::HelpPatternFile), "PATH[@X,Y]");
+        opts.optopt("", "pattern-offset", lang.tr(Message::HelpPatternOffset), "X,Y");
+        opts.optopt("", "init", lang.tr(Message::HelpInit), "SPEC");
+        opts.optopt("", "storage", lang.tr(Message::HelpStorage), "STORAGE");
+        opts.optopt("", "script", lang.tr(Message::HelpScript), "PATH");
+        opts.optflag("", "symmetry", lang.tr(Message::HelpSymmetry));
+        opts.optopt("", "symmetry-region", lang.tr(Message::HelpSymmetryRegion), "X,Y,W,H");
+        opts.optopt("", "count-region", lang.tr(Message::HelpCountRegion), "X,Y,W,H");
+        opts.optopt("", "record-frames", lang.tr(Message::HelpRecordFrames), "DIR");
+        opts.optopt("", "record-video", lang.tr(Message::HelpRecordVideo), "PATH");
+        opts.optopt("", "record-fps", lang.tr(Message::HelpRecordFps), "FPS");
+        opts.optflag("", "record-on-key", lang.tr(Message::HelpRecordOnKey));
+        opts.optopt("", "record-region", lang.tr(Message::HelpRecordRegion), "X,Y,W,H");
+        opts.optopt("", "record-scale", lang.tr(Message::HelpRecordScale), "PIXELS");
+        opts.optopt("", "exposure", lang.tr(Message::HelpExposure), "DIR");
+        opts.optopt("", "exposure-window", lang.tr(Message::HelpExposureWindow), "STEPS");
+        opts.optopt("", "event-log", lang.tr(Message::HelpEventLog), "PATH");
+        opts.optopt("", "event-log-max-size", lang.tr(Message::HelpEventLogMaxSize), "BYTES");
+        opts.optopt("", "event-log-files", lang.tr(Message::HelpEventLogFiles), "N");
+        opts.optopt("", "stats-log", lang.tr(Message::HelpStatsLog), "PATH");
+        opts.optopt("", "metrics-csv", lang.tr(Message::HelpMetricsCsv), "PATH");
+        opts.optopt("", "metrics-listen", lang.tr(Message::HelpMetricsListen), "ADDRESS");
+        opts.optopt("", "hud-metrics", lang.tr(Message::HelpHudMetrics), "NAME,...");
+        opts.optopt("", "plot-length", lang.tr(Message::HelpPlotLength), "STEPS");
+        opts.optopt("", "energy-histogram", lang.tr(Message::HelpEnergyHistogram), "BUCKETS");
+        opts.optopt("", "theme", lang.tr(Message::HelpTheme), "PATH");
+        opts.optopt("", "palette", lang.tr(Message::HelpPalette), "NAME");
+        opts.optopt("", "notebook", lang.tr(Message::HelpNotebook), "PATH");
+        opts.optopt("", "snapshot-every", lang.tr(Message::HelpSnapshotEvery), "N");
+        opts.optopt("", "snapshot-path", lang.tr(Message::HelpSnapshotPath), "TEMPLATE");
+        opts.optopt("", "speed-ramp", lang.tr(Message::HelpSpeedRamp), "ITERATION:SPEED,...");
+        opts.optopt("", "record", lang.tr(Message::HelpRecord), "PATH");
+        opts.optopt("", "replay", lang.tr(Message::HelpReplay), "PATH");
+        opts.optopt("", "transmission", lang.tr(Message::HelpTransmission), "PROBABILITY");
+        opts.optopt("", "incubation", lang.tr(Message::HelpIncubation), "STEPS");
+        opts.optopt("", "infection-duration", lang.tr(Message::HelpInfectionDuration), "STEPS");
+        opts.optopt("", "immunity-loss", lang.tr(Message::HelpImmunityLoss), "PROBABILITY");
+        opts.optopt("", "drop-rate", lang.tr(Message::HelpDropRate), "GRAINS");
+        opts.optopt("", "feed", lang.tr(Message::HelpFeedRate), "RATE");
+        opts.optopt("", "kill", lang.tr(Message::HelpKillRate), "RATE");
+        opts.optopt("", "states", lang.tr(Message::HelpStates), "N");
+        opts.optopt("", "threshold", lang.tr(Message::HelpThreshold), "N");
+        opts.optopt("", "rule", lang.tr(Message::HelpRule), "0-255");
+        opts.optopt("", "rule3d", lang.tr(Message::HelpRule3D), "B.../S...");
+        opts.optopt("", "boids", lang.tr(Message::HelpBoids), "N");
+        opts.optopt("", "ants", lang.tr(Message::HelpAnts), "N");
+        opts.optopt("", "evaporation", lang.tr(Message::HelpEvaporation), "FRACTION");
+        opts.optopt("", "temperature", lang.tr(Message::HelpTemperature), "T");
+        opts.optopt("", "relaxation", lang.tr(Message::HelpRelaxation), "TAU");
+        opts.optopt("", "inflow", lang.tr(Message::HelpInflow), "VELOCITY");
+        opts.optopt("", "turmites", lang.tr(Message::HelpTurmites), "N");
+        opts.optopt("", "turmite-table", lang.tr(Message::HelpTurmiteTable), "PATH");
+        opts.optopt("", "tree-density", lang.tr(Message::HelpTreeDensity), "FRACTION");
+        opts.optopt("", "growth", lang.tr(Message::HelpGrowth), "PROBABILITY");
+        opts.optopt("", "lightning", lang.tr(Message::HelpLightning), "PROBABILITY");
+        opts.optflag("h", "help", lang.tr(Message::HelpHelp));
+
+        let matches = match opts.parse(&args[1..]) {
+            Ok(matches) => matches,
+            Err(e) => return ParseResult::Failure(e.to_string())
+        };
+
+        if matches.opt_present("h") {
+            let msg = lang.format(Message::Usage, &[&args[0]]);
+            print!("{}", opts.usage(&msg));
+            print!("\n{}\n{}", lang.tr(Message::DemosHeader), demo::list(lang));
+            return ParseResult::Exit
+        }
+
+        if let Some(path) = matches.opt_str("config") {
+            return Options::from_config(&args, &matches, &path);
+        }
+
+        if let Some(path) = matches.opt_str("replay") {
+            return Options::from_recording(&args[0], &path);
+        }
+
+        let mut defaults = Options {
+            lang: lang,
+            simulation: SimulationKind::GoodEvil,
+            board_size: (80, 60),
+            board_depth: None,
+            seed: None,
+            updates_per_second: 100.0,
+            max_speed: false,
+            max_steps_per_frame: 200,
+            drop_lag: false,
+            speed_ramp: None,
+            camera: Camera::new(),
+            pattern: None,
+            pattern_file: None,
+            pattern_offset: None,
+            init: None,
+            storage: Storage::Auto,
+            cell_rule: None,
+            behavior: None,
+            symmetry: false,
+            symmetry_region: None,
+            count_region: None,
+            record_frames: None,
+            record_video: None,
+            record_fps: 25.0,
+            record_on_key: false,
+            record_region: None,
+            record_scale: 4,
+            exposure: None,
+            exposure_window: 100,
+            event_log: None,
+            event_log_max_size: None,
+            event_log_files: 3,
+            stats_log: None,
+            metrics_csv: None,
+            metrics_listen: None,
+            hud_metrics: Vec::new(),
+            plot_length: plot::DEFAULT_PLOT_LENGTH,
+            energy_histogram: None,
+            notebook: None,
+            snapshot_every: None,
+            snapshot_path: "snapshot-{iteration}.png".to_string(),
+            theme: Theme::default(),
+            args: Options::without_option(&args[1..], "record"),
+            given: Options::given_options(&args[1..], &matches),
+            dump_config: None,
+            record: None,
+            replay: None,
+            headless: false,
+            bench: false,
+            profile: false,
+            render: Backend::Window,
+            iterations: 1000,
+            stop_criterion: None,
+            manifest: None,
+            crash_dir: ".".to_string(),
+            checks: CheckLevel::Cheap,
+            sweep: None,
+            sweep_params: Vec::new(),
+            sweep_threads: 1,
+            verify_replay: None,
+            initial_specimens: None,
+            events: Vec::new(),
+            merges: Vec::new(),
+            random_event_probability: 0.0,
+            boundary: Boundary::Closed,
+            movement: MovementPolicy::Random,
+            update_order: UpdateOrder::RowMajor,
+            conflicts: &conflict::STRONGEST_WINS,
+            fairness_audit: None,
+            num_specimens: None,
+            specimen_energy: 1.0,
+            energy_loss: 0.001,
+            deadly_energy_margin: 0.0,
+            action_costs: None,
+            initial_energy: None,
+            immigration_rate: 0.0,
+            immigrant_energy: 1.0,
+            drift: None,
+            show_drift: false,
+            show_density: false,
+            grid_lines: false,
+            reputation: None,
+            predation: None,
+            species: None,
+            max_collision_size: None,
+            interaction_radius: None,
+            genetics: None,
+            dormancy: None,
+            crowding: None,
+            reproduction: None,
+            food: None,
+            epidemic: EpidemicConfig::default(),
+            sandpile: SandpileConfig::default(),
+            gray_scott: GrayScottConfig::default(),
+            cyclic: CyclicConfig::default(),
+            elementary: ElementaryConfig::default(),
+            life3d: Life3DConfig::default(),
+            boids: BoidsConfig::default(),
+            ants: AntsConfig::default(),
+            ising: IsingConfig::default(),
+            fluid: FluidConfig::default(),
+            turmites: TurmiteConfig::default(),
+            forest_fire: ForestFireConfig::default()
+        };
+
+        if let Some(name) = matches.opt_str("demo") {
+            match demo::find(&name) {
+                Some(demo) => {
+                    defaults = Options {
+                        simulation: demo.simulation,
+                        board_size: demo.board_size,
+                        seed: Some(demo.seed),
+                        updates_per_second: demo.updates_per_second,
+                        camera: demo.camera,
+                        pattern: demo.pattern,
+                        .. defaults
+                    }
+                },
+                None => {
+                    let msg = lang.format(Message::UnknownDemo, &[&name, &demo::list(lang)]);
+                    return ParseResult::Failure(msg)
+                }
+            }
+        }
+
+        let simulation = match matches.opt_str("sim") {
+            None => defaults.simulation,
+            Some(name) => match SimulationKind::from_name(&name) {
+                Some(kind) => kind,
+                None => return ParseResult::Failure(lang.format(Message::UnknownSimulation, &[&name]))
+            }
+        };
+
+        let (board_size, board_depth) = match Options::parse_size(lang, matches.opt_str("s"), defaults.board_size) {
+            Ok(size) => size,
+            Err(e) => return ParseResult::Failure(e)
+        };
+        if board_size.0 < MIN_BOARD_SIZE || board_size.1 < MIN_BOARD_SIZE {
+            return ParseResult::Failure(lang.format(Message::ValueTooSmall, &["board-size", &MIN_BOARD_SIZE.to_string()]));
+        }
+        match board_depth {
+            Some(_) if simulation != SimulationKind::Life3D => {
+                return ParseResult::Failure(lang.tr(Message::DepthNotSupported).to_string())
+            },
+            Some(0) => return ParseResult::Failure(lang.format(Message::ValueTooSmall, &["board-size", "1"])),
+            _ => {}
+        }
+
+        let pattern_specs = matches.opt_strs("pattern-file");
+        if !pattern_specs.is_empty() && simulation != SimulationKind::Life && simulation != SimulationKind::GoodEvil {
+            return ParseResult::Failure(lang.tr(Message::PatternFileNotSupported).to_string())
+        }
+        let mut patterns = Vec::new();
+        for spec in pattern_specs.iter() {
+            match Options::load_pattern(lang, spec) {
+                Ok(pattern) => patterns.push(pattern),
+                Err(e) => return ParseResult::Failure(e)
+            }
+        }
+
+        let init = match matches.opt_str("init") {
+            None => None,
+            Some(s) => match InitialState::parse(&s) {
+                Some(init) => Some(init),
+                None => return ParseResult::Failure(lang.format(Message::InvalidValue, &[
+                    "init", &s, lang.tr(Message::ExpectedInit)]))
+            }
+        };
+        match (init, simulation) {
+            (Some(_), SimulationKind::Cyclic) | (Some(_), SimulationKind::FallingSand) |
+            (Some(_), SimulationKind::Ants) | (Some(_), SimulationKind::Fluid) |
+            (Some(_), SimulationKind::ForestFire) | (Some(_), SimulationKind::Life3D) => {
+                return ParseResult::Failure(lang.tr(Message::InitNotSupported).to_string())
+            },
+            _ => {}
+        }
+        let storage = match matches.opt_str("storage") {
+            None => defaults.storage,
+            Some(name) => match Storage::from_name(&name) {
+                Some(storage) => storage,
+                None => return ParseResult::Failure(lang.format(Message::InvalidValue, &[
+                    "storage", &name, lang.tr(Message::ExpectedStorage)]))
+            }
+        };
+
+        let (cell_rule, behavior) = match (matches.opt_str("script"), simulation) {
+            (None, _) => (None, None),
+            (Some(path), SimulationKind::Life) => match scripting::load_cell_rule(&path) {
+                Ok(rule) => (Some(rule), None),
+                Err(e) => return ParseResult::Failure(e)
+            },
+            (Some(path), SimulationKind::GoodEvil) => match scripting::load_specimen_behavior(&path) {
+                Ok(behavior) => (None, Some(behavior)),
+                Err(e) => return ParseResult::Failure(e)
+            },
+            (Some(_), _) => return ParseResult::Failure(lang.tr(Message::ScriptNotSupported).to_string())
+        };
+
+        let pattern_offset = match matches.opt_str("pattern-offset") {
+            None => None,
+            Some(s) => match Options::parse_csv_ints(&s) {
+                Ok(ref values) if values.len() == 2 => Some((values[0], values[1])),
+                Ok(_) => return ParseResult::Failure(lang.format(Message::InvalidValue, &[
+                    "pattern-offset", &s, &lang.format(Message::ExpectedFormat, &["X,Y"])])),
+                Err(e) => return ParseResult::Failure(e)
+            }
+        };
+
+        // offsets have to lie on the board; patterns sticking out past its
+        // edges still enlarge it
+        let offsets = pattern_offset.into_iter().chain(patterns.iter().filter_map(|&(_, offset)| offset));
+        if let Some((x, y)) = offsets.filter(|&(x, y)| x >= board_size.0 || y >= board_size.1).next() {
+            let size = format!("{}x{}", board_size.0, board_size.1);
+            return ParseResult::Failure(lang.format(Message::PatternOffsetOutsideBoard, &[&format!("{},{}", x, y), &size]));
+        }
+
+        // a single pattern without an offset is centered, others are
+        // combined at their offsets, --pattern-offset by default
+        let (pattern_file, pattern_offset) = match (patterns.len(), patterns.first()) {
+            (0, _) => (None, pattern_offset),
+            (1, Some(&(ref pattern, None))) => (Some(pattern.clone()), pattern_offset),
+            _ => {
+                let parts: Vec<(Pattern, (usize, usize))> = patterns.into_iter().map(|(pattern, offset)| {
+                    (pattern, offset.or(pattern_offset).unwrap_or((0, 0)))
+                }).collect();
+                let (pattern, origin) = Pattern::composite(&parts);
+                (Some(pattern), Some(origin))
+            }
+        };
+
+        // patterns larger than the board enlarge it
+        let board_size = match (pattern_file.as_ref(), pattern_offset) {
+            (Some(pattern), Some(offset)) => match (offset.0.checked_add(pattern.width),
+                                                    offset.1.checked_add(pattern.height)) {
+                (Some(right), Some(bottom)) => (max(board_size.0, right), max(board_size.1, bottom)),
+                _ => {
+                    let size = format!("{}x{}", board_size.0, board_size.1);
+                    return ParseResult::Failure(lang.format(Message::PatternOffsetOutsideBoard,
+                                                            &[&format!("{},{}", offset.0, offset.1), &size]));
+                }
+            },
+            (Some(pattern), None) => pattern.fit_board_size(board_size),
+            (None, _) => board_size
+        };
+
+        let symmetry_region = match Options::parse_region(lang, &matches, "symmetry-region", board_size) {
+            Ok(region) => region,
+            Err(e) => return ParseResult::Failure(e)
+        };
+        let count_region = match Options::parse_region(lang, &matches, "count-region", board_size) {
+            Ok(region) => region,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let record_region = match Options::parse_region(lang, &matches, "record-region", board_size) {
+            Ok(region) => region,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let speed_ramp = match matches.opt_str("speed-ramp") {
+            None => None,
+            Some(s) => match SpeedRamp::parse(&s) {
+                Some(ramp) => Some(ramp),
+                None => {
+                    return ParseResult::Failure(lang.format(Message::InvalidValue,
+                                                            &["speed-ramp", &s,
+                                                              &lang.format(Message::ExpectedFormat, &["ITERATION:SPEED,..."])]))
+                }
+            }
+        };
+
+        let record_scale = match Options::parse_value(lang, &matches, "record-scale", defaults.record_scale) {
+            Ok(scale) if scale >= 1 => scale,
+            Ok(_) => return ParseResult::Failure(lang.format(Message::ValueTooSmall, &["record-scale", "1"])),
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let record_video = matches.opt_str("record-video");
+        if let (Some(path), true) = (record_video.as_ref(), matches.opt_present("record-frames")) {
+            return ParseResult::Failure(lang.format(Message::InvalidValue, &[
+                "record-video", path, lang.tr(Message::RecordVideoConflict)]));
+        }
+
+        let record_fps = match Options::parse_value(lang, &matches, "record-fps", defaults.record_fps) {
+            Ok(fps) if fps > 0.0 => fps,
+            Ok(fps) => return ParseResult::Failure(lang.format(Message::InvalidValue, &[
+                "record-fps", &fps.to_string(), lang.tr(Message::MustBePositive)])),
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let exposure_window = match Options::parse_value(lang, &matches, "exposure-window", defaults.exposure_window) {
+            Ok(window) if window >= 1 => window,
+            Ok(_) => return ParseResult::Failure(lang.format(Message::ValueTooSmall, &["exposure-window", "1"])),
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let plot_length = match Options::parse_value(lang, &matches, "plot-length", defaults.plot_length) {
+            Ok(length) if length >= 2 => length,
+            Ok(_) => return ParseResult::Failure(lang.format(Message::ValueTooSmall, &["plot-length", "2"])),
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let energy_histogram = match matches.opt_str("energy-histogram") {
+            None => None,
+            Some(_) => match Options::parse_value(lang, &matches, "energy-histogram", 0) {
+                Ok(buckets) if buckets >= 1 => Some(buckets),
+                Ok(_) => return ParseResult::Failure(lang.format(Message::ValueTooSmall, &["energy-histogram", "1"])),
+                Err(e) => return ParseResult::Failure(e)
+            }
+        };
+
+        let snapshot_every = match matches.opt_str("snapshot-every") {
+            None => None,
+            Some(_) => match Options::parse_value(lang, &matches, "snapshot-every", 0) {
+                Ok(every) if every >= 1 => Some(every),
+                Ok(_) => return ParseResult::Failure(lang.format(Message::ValueTooSmall, &["snapshot-every", "1"])),
+                Err(e) => return ParseResult::Failure(e)
+            }
+        };
+        let snapshot_path = matches.opt_str("snapshot-path").unwrap_or(defaults.snapshot_path.clone());
+        if let Err(e) = Snapshots::new(&snapshot_path, 1) {
+            return ParseResult::Failure(lang.format(Message::InvalidValue, &["snapshot-path", &snapshot_path, &e]));
+        }
+
+        let event_log_max_size = match matches.opt_str("event-log-max-size") {
+            None => None,
+            Some(_) => match Options::parse_value(lang, &matches, "event-log-max-size", 0u64) {
+                Ok(size) if size >= 1 => Some(size),
+                Ok(_) => return ParseResult::Failure(lang.format(Message::ValueTooSmall, &["event-log-max-size", "1"])),
+                Err(e) => return ParseResult::Failure(e)
+            }
+        };
+        let event_log_files = match Options::parse_value(lang, &matches, "event-log-files", defaults.event_log_files) {
+            Ok(files) => files,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let mut theme = match matches.opt_str("theme") {
+            None => defaults.theme.clone(),
+            Some(path) => match Theme::load(&path) {
+                Ok(theme) => theme,
+                Err(e) => return ParseResult::Failure(e)
+            }
+        };
+        match matches.opt_str("palette") {
+            None => {},
+            Some(ref name) if name == "default" => theme.heatmap = None,
+            Some(name) => match ColorMap::from_name(&name) {
+                Some(map) => theme.heatmap = Some(map),
+                None => return ParseResult::Failure(lang.format(Message::InvalidValue, &[
+                    "palette", &name, lang.tr(Message::ExpectedPalette)]))
+            }
+        }
+
+        let aspect = match matches.opt_str("aspect") {
+            None => defaults.camera.aspect,
+            Some(name) => match Aspect::from_name(&name) {
+                Some(aspect) => aspect,
+                None => return ParseResult::Failure(lang.format(Message::InvalidValue, &[
+                    "aspect", &name, lang.tr(Message::ExpectedAspect)]))
+            }
+        };
+
+        let (updates_per_second, max_speed) = match matches.opt_str("ups") {
+            Some(ref s) if s == "max" => (defaults.updates_per_second, true),
+            _ => match Options::parse_value(lang, &matches, "ups", defaults.updates_per_second) {
+                Ok(ups) if ups >= MIN_UPDATES_PER_SECOND && ups <= MAX_UPDATES_PER_SECOND => (ups, false),
+                Ok(ups) => return ParseResult::Failure(lang.format(Message::InvalidValue, &[
+                    "ups", &ups.to_string(),
+                    &lang.format(Message::ExpectedUps, &[&MIN_UPDATES_PER_SECOND.to_string(),
+                                                         &MAX_UPDATES_PER_SECOND.to_string()])])),
+                Err(e) => return ParseResult::Failure(e)
+            }
+        };
+
+        let max_steps_per_frame = match Options::parse_value(lang, &matches, "max-steps-per-frame",
+                                                             defaults.max_steps_per_frame) {
+            Ok(steps) if steps >= 1 => steps,
+            Ok(_) => return ParseResult::Failure(lang.format(Message::ValueTooSmall, &["max-steps-per-frame", "1"])),
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let seed = match Options::parse_value(lang, &matches, "seed", 0) {
+            Ok(seed) if matches.opt_present("seed") => Some(seed),
+            Ok(_) => defaults.seed,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let epidemic = match Options::parse_epidemic_config(lang, &matches) {
+            Ok(cfg) => cfg,
+            Err(e) => return ParseResult::Failure(e)
+        };
+        if simulation == SimulationKind::Epidemic && epidemic.initial_infected > board_size.0 * board_size.1 {
+            return ParseResult::Failure(lang.format(Message::TooManyInfected,
+                                                    &[&epidemic.initial_infected.to_string(),
+                                                      &format!("{}x{}", board_size.0, board_size.1)]));
+        }
+
+        let iterations = match Options::parse_value(lang, &matches, "iterations", defaults.iterations) {
+            Ok(n) => n,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let stop_criterion = match matches.opt_str("stop-when") {
+            None => None,
+            Some(s) => match StopCriterion::parse(&s) {
+                Some(criterion) => Some(criterion),
+                None => return ParseResult::Failure(lang.format(Message::InvalidStopCriterion, &[&s]))
+            }
+        };
+
+        let initial_specimens = match Options::parse_seed_population(lang, &matches, simulation, board_size) {
+            Ok(specimens) => specimens,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let mut events = Vec::new();
+        for spec in matches.opt_strs("event") {
+            match WorldEvent::parse(&spec) {
+                Some(event) => events.push(event),
+                None => return ParseResult::Failure(lang.format(Message::InvalidEvent, &[&spec]))
+            }
+        }
+
+        let mut merges = Vec::new();
+        for spec in matches.opt_strs("merge") {
+            match ScheduledMerge::parse(&spec) {
+                Ok(merge) => merges.push(merge),
+                Err(e) => return ParseResult::Failure(lang.format(Message::InvalidValue, &["merge", &spec, &e]))
+            }
+        }
+
+        let mut sweep_params = Vec::new();
+        for spec in matches.opt_strs("sweep-param") {
+            match Param::parse(&spec) {
+                Ok(param) => sweep_params.push(param),
+                Err(e) => return ParseResult::Failure(lang.format(Message::InvalidValue, &["sweep-param", &spec, &e]))
+            }
+        }
+
+        let sweep_threads = match Options::parse_value(lang, &matches, "sweep-threads", defaults.sweep_threads) {
+            Ok(threads) if threads >= 1 => threads,
+            Ok(_) => return ParseResult::Failure(lang.format(Message::ValueTooSmall, &["sweep-threads", "1"])),
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let random_event_probability = match Options::parse_probability(lang, &matches, "random-events",
+                                                                        defaults.random_event_probability) {
+            Ok(p) => p,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let render = match matches.opt_str("render") {
+            None => defaults.render,
+            Some(name) => match Backend::from_name(&name) {
+                Some(render) => render,
+                None => return ParseResult::Failure(lang.format(Message::InvalidValue, &["render", &name, lang.tr(Message::ExpectedRender)]))
+            }
+        };
+
+        let checks = match matches.opt_str("checks") {
+            None => defaults.checks,
+            Some(name) => match CheckLevel::from_name(&name) {
+                Some(checks) => checks,
+                None => return ParseResult::Failure(lang.format(Message::InvalidValue, &["checks", &name, lang.tr(Message::ExpectedChecks)]))
+            }
+        };
+
+        let boundary = match matches.opt_str("boundary") {
+            None => defaults.boundary,
+            Some(name) => match Boundary::from_name(&name) {
+                Some(boundary) => boundary,
+                None => return ParseResult::Failure(lang.format(Message::UnknownBoundary, &[&name]))
+            }
+        };
+        let movement = match matches.opt_str("movement") {
+            None => defaults.movement,
+            Some(name) => match MovementPolicy::from_name(&name) {
+                Some(movement) => movement,
+                None => return ParseResult::Failure(lang.format(Message::UnknownMovement, &[&name]))
+            }
+        };
+        let update_order = match matches.opt_str("update-order") {
+            None => defaults.update_order,
+            Some(name) => match UpdateOrder::from_name(&name) {
+                Some(order) => order,
+                None => return ParseResult::Failure(lang.format(Message::UnknownUpdateOrder, &[&name]))
+            }
+        };
+        let conflicts = match matches.opt_str("conflicts") {
+            None => defaults.conflicts,
+            Some(name) => match conflict::from_name(&name) {
+                Some(resolver) => resolver,
+                None => return ParseResult::Failure(lang.format(Message::UnknownConflictResolver, &[&name]))
+            }
+        };
+        let fairness_audit = match matches.opt_str("fairness-audit") {
+            None => None,
+            Some(_) => match Options::parse_value(lang, &matches, "fairness-audit", 0) {
+                Ok(regions) if regions >= 1 => Some(regions),
+                Ok(_) => return ParseResult::Failure(lang.format(Message::ValueTooSmall, &["fairness-audit", "1"])),
+                Err(e) => return ParseResult::Failure(e)
+            }
+        };
+        let num_specimens = match matches.opt_str("specimens") {
+            None => None,
+            Some(_) => match Options::parse_value(lang, &matches, "specimens", 0) {
+                Ok(count) if count < 1 => return ParseResult::Failure(lang.format(Message::ValueTooSmall, &["specimens", "1"])),
+                Ok(count) if count > board_size.0 * board_size.1 => {
+                    return ParseResult::Failure(lang.format(Message::TooManySpecimens,
+                                                            &["--specimens",
+                                                              &count.to_string(),
+                                                              &format!("{}x{}", board_size.0, board_size.1)]));
+                },
+                Ok(count) => Some(count),
+                Err(e) => return ParseResult::Failure(e)
+            }
+        };
+
+        let specimen_energy = match Options::parse_finite(lang, &matches, "specimen-energy", defaults.specimen_energy) {
+            Ok(energy) if energy > 0.0 => energy,
+            Ok(energy) => return ParseResult::Failure(lang.format(Message::InvalidValue, &[
+                "specimen-energy", &energy.to_string(), lang.tr(Message::MustBePositive)])),
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let energy_loss = match Options::parse_finite(lang, &matches, "energy-loss", defaults.energy_loss) {
+            Ok(loss) if loss >= 0.0 => loss,
+            Ok(_) => return ParseResult::Failure(lang.format(Message::ValueTooSmall, &["energy-loss", "0"])),
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let deadly_energy_margin = match Options::parse_value(lang, &matches, "deadly-margin",
+                                                              defaults.deadly_energy_margin) {
+            Ok(margin) if margin >= 0.0 && margin < specimen_energy => margin,
+            Ok(margin) => return ParseResult::Failure(lang.format(Message::InvalidValue, &[
+                "deadly-margin", &margin.to_string(),
+                lang.tr(Message::DeadlyMarginRange)])),
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let action_costs = match matches.opt_str("action-costs") {
+            None => None,
+            Some(s) => match ActionCosts::parse(&s) {
+                Some(costs) => Some(costs),
+                None => return ParseResult::Failure(lang.format(Message::InvalidValue, &[
+                    "action-costs", &s, lang.tr(Message::ExpectedActionCosts)]))
+            }
+        };
+
+        let initial_energy = match matches.opt_str("initial-energy") {
+            None => None,
+            Some(s) => match EnergyDistribution::parse(&s) {
+                Some(distribution) => Some(distribution),
+                None => return ParseResult::Failure(lang.format(Message::InvalidValue, &[
+                    "initial-energy", &s,
+                    lang.tr(Message::ExpectedInitialEnergy)]))
+            }
+        };
+
+        // one immigrant per edge and round, so more rounds than edge cells
+        // would only crowd the border
+        let edge_cells = 2 * (board_size.0 + board_size.1);
+        let immigration_rate = match Options::parse_value(lang, &matches, "immigration",
+                                                          defaults.immigration_rate) {
+            Ok(rate) if rate >= 0.0 && rate <= edge_cells as f32 => rate,
+            Ok(rate) if rate >= 0.0 => return ParseResult::Failure(lang.format(Message::ValueTooLarge, &[
+                "immigration", &edge_cells.to_string()])),
+            Ok(_) => return ParseResult::Failure(lang.format(Message::ValueTooSmall, &["immigration", "0"])),
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let immigrant_energy = match Options::parse_value(lang, &matches, "immigrant-energy",
+                                                          defaults.immigrant_energy) {
+            Ok(energy) if energy >= 0.0 && energy.is_finite() => energy,
+            Ok(energy) => return ParseResult::Failure(lang.format(Message::InvalidValue, &[
+                "immigrant-energy", &energy.to_string(), lang.tr(Message::MustBeFiniteNonNegative)])),
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let drift = match Options::parse_drift(lang, &matches, board_size) {
+            Ok(drift) => drift,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let reputation = match Options::parse_reputation_config(lang, &matches) {
+            Ok(cfg) => cfg,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let predation = match Options::parse_predation_config(lang, &matches) {
+            Ok(cfg) => cfg,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let species = match Options::parse_species_config(lang, &matches) {
+            Ok(cfg) => cfg,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let max_collision_size = match matches.opt_str("max-collision") {
+            None => None,
+            Some(_) => match Options::parse_value(lang, &matches, "max-collision", 0) {
+                Ok(limit) if limit >= 1 => Some(limit),
+                Ok(_) => return ParseResult::Failure(lang.format(Message::ValueTooSmall, &["max-collision", "1"])),
+                Err(e) => return ParseResult::Failure(e)
+            }
+        };
+
+        let interaction_radius = match matches.opt_str("interaction-radius") {
+            None => None,
+            Some(_) => match Options::parse_value(lang, &matches, "interaction-radius", 0) {
+                // no two cells are farther apart than width + height
+                Ok(radius) if radius >= 1 => Some(min(radius, board_size.0 + board_size.1)),
+                Ok(_) => return ParseResult::Failure(lang.format(Message::ValueTooSmall, &["interaction-radius", "1"])),
+                Err(e) => return ParseResult::Failure(e)
+            }
+        };
+
+        let verify_replay = match matches.opt_str("verify-replay") {
+            None => None,
+            Some(_) => match Options::parse_value(lang, &matches, "verify-replay", 0) {
+                Ok(ticks) if ticks >= 1 => Some(ticks),
+                Ok(_) => return ParseResult::Failure(lang.format(Message::ValueTooSmall, &["verify-replay", "1"])),
+                Err(e) => return ParseResult::Failure(e)
+            }
+        };
+
+        let genetics = match Options::parse_genetics_config(lang, &matches) {
+            Ok(cfg) => cfg,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let dormancy = match Options::parse_dormancy_config(lang, &matches) {
+            Ok(cfg) => cfg,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let crowding = match Options::parse_crowding_config(lang, &matches) {
+            Ok(cfg) => cfg,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let reproduction = match Options::parse_reproduction_config(lang, &matches, genetics.is_some()) {
+            Ok(cfg) => cfg,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let food = match Options::parse_food_config(lang, &matches) {
+            Ok(cfg) => cfg,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let sandpile = match Options::parse_sandpile_config(lang, &matches, board_size) {
+            Ok(cfg) => cfg,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let gray_scott = match Options::parse_gray_scott_config(lang, &matches) {
+            Ok(cfg) => cfg,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let cyclic = match Options::parse_cyclic_config(lang, &matches) {
+            Ok(cfg) => cfg,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let elementary = match Options::parse_value(lang, &matches, "rule", defaults.elementary.rule) {
+            Ok(rule) => ElementaryConfig { rule: rule },
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let life3d = match matches.opt_str("rule3d") {
+            None => defaults.life3d,
+            Some(s) => match Life3DConfig::parse(&s) {
+                Some(cfg) => cfg,
+                None => return ParseResult::Failure(lang.format(Message::InvalidValue, &[
+                    "rule3d", &s, lang.tr(Message::ExpectedRule3d)]))
+            }
+        };
+
+        let boids = match Options::parse_value(lang, &matches, "boids", defaults.boids.num_boids) {
+            Ok(num_boids) => BoidsConfig { num_boids: num_boids, .. defaults.boids },
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let ants = match Options::parse_ants_config(lang, &matches) {
+            Ok(cfg) => cfg,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let ising = match Options::parse_finite(lang, &matches, "temperature", defaults.ising.temperature) {
+            Ok(temperature) if temperature >= 0.0 => IsingConfig { temperature: temperature },
+            Ok(_) => return ParseResult::Failure(lang.format(Message::ValueTooSmall, &["temperature", "0"])),
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let fluid = match Options::parse_fluid_config(lang, &matches) {
+            Ok(cfg) => cfg,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let turmites = match Options::parse_turmite_config(lang, &matches) {
+            Ok(cfg) => cfg,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let forest_fire = match Options::parse_forest_fire_config(lang, &matches) {
+            Ok(cfg) => cfg,
+            Err(e) => return ParseResult::Failure(e)
+        };
+
+        let options = Options {
+            simulation: simulation,
+            board_size: board_size,
+            board_depth: board_depth,
+            pattern_file: pattern_file,
+            pattern_offset: pattern_offset,
+            init: init,
+            storage: storage,
+            cell_rule: cell_rule,
+            behavior: behavior,
+            symmetry: matches.opt_present("symmetry"),
+            symmetry_region: symmetry_region,
+            count_region: count_region,
+            record_frames: matches.opt_str("record-frames"),
+            record_video: record_video,
+            record_fps: record_fps,
+            record_on_key: matches.opt_present("record-on-key"),
+            record_region: record_region,
+            record_scale: record_scale,
+            exposure: matches.opt_str("exposure"),
+            exposure_window: exposure_window,
+            event_log: matches.opt_str("event-log"),
+            event_log_max_size: event_log_max_size,
+            event_log_files: event_log_files,
+            stats_log: matches.opt_str("stats-log"),
+            metrics_csv: matches.opt_str("metrics-csv"),
+            metrics_listen: matches.opt_str("metrics-listen"),
+            hud_metrics: matches.opt_str("hud-metrics")
+                                .map(|names| names.split(',')
+                                                  .map(|name| name.trim().to_string())
+                                                  .filter(|name| !name.is_empty())
+                                                  .collect())
+                                .unwrap_or(Vec::new()),
+            plot_length: plot_length,
+            energy_histogram: energy_histogram,
+            notebook: matches.opt_str("notebook"),
+            snapshot_every: snapshot_every,
+            snapshot_path: snapshot_path,
+            theme: theme,
+            record: matches.opt_str("record"),
+            dump_config: matches.opt_str("dump-config"),
+            speed_ramp: speed_ramp,
+            updates_per_second: updates_per_second,
+            max_speed: max_speed,
+            max_steps_per_frame: max_steps_per_frame,
+            drop_lag: matches.opt_present("drop-lag"),
+            seed: seed,
+            headless: matches.opt_present("headless"),
+            bench: matches.opt_present("bench"),
+            profile: matches.opt_present("profile"),
+            render: render,
+            iterations: iterations,
+            stop_criterion: stop_criterion,
+            manifest: matches.opt_str("manifest"),
+            crash_dir: matches.opt_str("crash-dir").unwrap_or(defaults.crash_dir.clone()),
+            checks: checks,
+            sweep: matches.opt_str("sweep"),
+            sweep_params: sweep_params,
+            sweep_threads: sweep_threads,
+            verify_replay: verify_replay,
+            initial_specimens: initial_specimens,
+            events: events,
+            merges: merges,
+            random_event_probability: random_event_probability,
+            boundary: boundary,
+            movement: movement,
+            update_order: update_order,
+            conflicts: conflicts,
+            fairness_audit: fairness_audit,
+            num_specimens: num_specimens,
+            specimen_energy: specimen_energy,
+            energy_loss: energy_loss,
+            deadly_energy_margin: deadly_energy_margin,
+            action_costs: action_costs,
+            initial_energy: initial_energy,
+            immigration_rate: immigration_rate,
+            immigrant_energy: immigrant_energy,
+            drift: drift,
+            show_drift: matches.opt_present("show-wind"),
+            show_density: matches.opt_present("show-density"),
+            grid_lines: matches.opt_present("grid-lines"),
+            camera: Camera {
+                aspect: aspect,
+                ..defaults.camera
+            },
+            reputation: reputation,
+            predation: predation,
+            species: species,
+            max_collision_size: max_collision_size,
+            interaction_radius: interaction_radius,
+            genetics: genetics,
+            dormancy: dormancy,
+            crowding: crowding,
+            reproduction: reproduction,
+            food: food,
+            epidemic: epidemic,
+            sandpile: sandpile,
+            gray_scott: gray_scott,
+            cyclic: cyclic,
+            elementary: elementary,
+            life3d: life3d,
+            boids: boids,
+            ants: ants,
+            ising: ising,
+            fluid: fluid,
+            turmites: turmites,
+            forest_fire: forest_fire,
+            .. defaults
+        };
+        match options.check_free_cells() {
+            Ok(()) => ParseResult::Success(options),
+            Err(e) => ParseResult::Failure(e)
+        }
+    }
+}
+
+impl fmt::Display for Options {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(writeln!(f, "lang: {}", self.lang.code()));
+        try!(writeln!(f, "simulation: {}", self.simulation.name()));
+        match self.board_depth {
+            Some(depth) => try!(writeln!(f, "board_size: {}, {}, {}", self.board_size.0, self.board_size.1, depth)),
+            None => try!(writeln!(f, "board_size: {}, {}", self.board_size.0, self.board_size.1))
+        }
+        match self.seed {
+            Some(seed) => try!(writeln!(f, "seed: {}", seed)),
+            None => try!(writeln!(f, "seed: random"))
+        }
+        if let Some(init) = self.init {
+            try!(writeln!(f, "init: {}", init));
+        }
+        if self.simulation == SimulationKind::Life {
+            try!(writeln!(f, "storage: {}", self.storage.name()));
+        }
+        if self.simulation == SimulationKind::GoodEvil {
+            try!(writeln!(f, "boundary: {}", self.boundary.name()));
+            try!(writeln!(f, "movement: {}", self.movement.name()));
+            try!(writeln!(f, "update_order: {}", self.update_order.name()));
+            try!(writeln!(f, "conflicts: {}", self.conflicts.name()));
+        }
+        if self.max_speed {
+            write!(f, "updates_per_second: max")
+        } else {
+            write!(f, "updates_per_second: {}", self.updates_per_second)
+        }
+    }
+}
+
+fn run<T, S>(window: Window,
+             mut app: App<S>) -> App<S> where S: Simulation<T> + Clone + 'static, T: CellColor + PartialEq {
+    let mut fps_meter = TickMeter::new();
+    let mut update_meter = TickMeter::new();
+    let mut profiler = if app.opts.profile { Some(Profiler::new()) } else { None };
+
+    for e in window.events() {
+        if let Some(render_args) = e.render_args() {
+            app.tick_stats = (fps_meter.stats(), update_meter.stats());
+            let _timer = profiler.as_mut().map(|profiler| profiler.scope("render"));
+            app.render(Viewport {
+                window_size: [render_args.width, render_args.height],
+                draw_size: [render_args.draw_width, render_args.draw_height]
+            });
+            fps_meter.tick();
+        }
+        if let Some(update_args) = e.update_args() {
+            let _timer = profiler.as_mut().map(|profiler| profiler.scope("update"));
+            app.update(&update_args);
+            update_meter.tick();
+        }
+        if let Some(button) = e.press_args() {
+            app.press(button);
+        }
+        if let Some(button) = e.release_args() {
+            app.release(button);
+        }
+        if let Some(position) = e.mouse_cursor_args() {
+            app.move_cursor(position);
+        }
+        if let Some(amount) = e.mouse_scroll_args() {
+            app.scroll(amount);
+        }
+        if let Some(size) = e.resize_args() {
+            app.resize(size);
+        }
+        if let Some(report) = profiler.as_mut().and_then(|profiler| profiler.report(app.simulation.perf_report())) {
+            println!("{}", report);
+        }
+    }
+
+    app
+}
+
+/// Key of the window the terminal key stands for, so that both share
+/// bindings.
+fn window_key(key: TermKey) -> Option<Key> {
+    match key {
+        TermKey::Char(' ') => Some(Key::Space),
+        TermKey::Char('1') => Some(Key::D1),
+        TermKey::Char('2') => Some(Key::D2),
+        TermKey::Char('3') => Some(Key::D3),
+        TermKey::Char('[') => Some(Key::LeftBracket),
+        TermKey::Char(']') => Some(Key::RightBracket),
+        TermKey::Char('+') => Some(Key::Plus),
+        TermKey::Char('=') => Some(Key::Equals),
+        TermKey::Char('-') => Some(Key::Minus),
+        TermKey::Char('c') => Some(Key::C),
+        TermKey::Char('h') => Some(Key::H),
+        TermKey::Char('t') => Some(Key::T),
+        TermKey::Char('m') => Some(Key::M),
+        TermKey::Char('n') => Some(Key::N),
+        TermKey::Char('w') => Some(Key::W),
+        TermKey::Char('y') => Some(Key::Y),
+        TermKey::Char('f') => Some(Key::F),
+        TermKey::Char('z') => Some(Key::Z),
+        TermKey::Char('v') => Some(Key::V),
+        TermKey::Char('r') => Some(Key::R),
+        TermKey::Char(',') => Some(Key::Comma),
+        TermKey::Char('.') => Some(Key::Period),
+        TermKey::Left => Some(Key::Left),
+        TermKey::Right => Some(Key::Right),
+        TermKey::PageUp => Some(Key::PageUp),
+        TermKey::PageDown => Some(Key::PageDown),
+        TermKey::F5 => Some(Key::F5),
+        TermKey::F6 => Some(Key::F6),
+        TermKey::F7 => Some(Key::F7),
+        TermKey::F8 => Some(Key::F8),
+        TermKey::F12 => Some(Key::F12),
+        _ => None
+    }
+}
+
+/// Frames drawn per second by the terminal view, and how often it checks
+/// the terminal size.
+const TUI_FRAME_S: f64 = 1.0 / 30.0;
+const TUI_RESIZE_CHECK_S: f64 = 1.0;
+
+/// Like `run`, but draws in the terminal and reads keys from stdin.
+fn run_tui<T, S>(mut app: App<S>) -> App<S> where S: Simulation<T> + Clone + 'static, T: CellColor + PartialEq {
+    let terminal = match Terminal::enter() {
+        Ok(terminal) => terminal,
+        Err(e) => {
+            println!("{}", e);
+            return app;
+        }
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let stdin = io::stdin();
+        let mut buffer = [0u8; 16];
+        loop {
+            match stdin.lock().read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(len) => if sender.send(buffer[..len].to_vec()).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+
+    let mut size = tui::terminal_size().unwrap_or((80, 24));
+    let mut last_update_s = time::precise_time_s();
+    let mut last_resize_check_s = last_update_s;
+    'running: loop {
+        while let Ok(bytes) = receiver.try_recv() {
+            for key in tui::parse_keys(&bytes) {
+                match key {
+                    TermKey::Escape | TermKey::Char('q') => break 'running,
+                    key => if let Some(key) = window_key(key) {
+                        app.press(Button::Keyboard(key));
+                    }
+                }
+            }
+        }
+
+        let now_s = time::precise_time_s();
+        app.update(&UpdateArgs { dt: now_s - last_update_s });
+        last_update_s = now_s;
+
+        if now_s - last_resize_check_s >= TUI_RESIZE_CHECK_S {
+            size = tui::terminal_size().unwrap_or(size);
+            last_resize_check_s = now_s;
+        }
+        app.render(Viewport::new([size.0 as u32, size.1 as u32]));
+
+        thread::sleep(Duration::from_millis((TUI_FRAME_S * 1000.0) as u64));
+    }
+
+    drop(terminal);
+    app
+}
+
+fn write_manifest<T, S>(opts: &Options,
+                        path: &str,
+                        outcome: &Outcome,
+                        simulation: Option<&S>,
+                        warnings: &WarningLog) -> Result<(), String> where S: Simulation<T> {
+    let stats = simulation.map(|sim| sim.stats());
+
+    let manifest = Manifest {
+        simulation: opts.simulation.name().to_string(),
+        seed: opts.seed,
+        board_size: opts.board_size,
+        iterations: stats.as_ref().map(|s| s.iteration).unwrap_or(0),
+        outcome: outcome.name().to_string(),
+        stats: stats.map(|s| s.values.iter()
+                                     .map(|&(name, value)| (name.to_string(), value.as_f32() as f64))
+                                     .collect())
+                    .unwrap_or(Vec::new()),
+        specimens: simulation.map(|sim| sim.specimens()).unwrap_or(Vec::new()),
+        warnings: warnings.clone()
+    };
+
+    manifest.save(path)
+}
+
+/// `cell analyze DIR [--output PATH]`: writes an HTML report comparing the
+/// runs of a sweep in DIR, see `analysis`. Returns the exit code.
+fn run_analyze(args: &[String]) -> i32 {
+    let lang = match Options::parse_language(&args[2..]) {
+        Ok(lang) => lang,
+        Err(e) => {
+            println!("{}", e);
+            return Outcome::Error(e).exit_code();
+        }
+    };
+
+    let mut opts = getopts::Options::new();
+    opts.optopt("o", "output", lang.tr(Message::HelpAnalyzeOutput), "PATH");
+    opts.optopt("", "lang", lang.tr(Message::HelpLang), "LANG");
+    opts.optflag("h", "help", lang.tr(Message::HelpHelp));
+    let matches = match opts.parse(&args[2..]) {
+        Ok(matches) => matches,
+        Err(e) => {
+            println!("{}", e);
+            return Outcome::Error(e.to_string()).exit_code();
+        }
+    };
+    if matches.opt_present("h") || matches.free.len() != 1 {
+        print!("{}", opts.usage(&lang.format(Message::AnalyzeUsage, &[&args[0]])));
+        return if matches.opt_present("h") { 0 } else { Outcome::Error(String::new()).exit_code() };
+    }
+
+    let dir = &matches.free[0];
+    let output = matches.opt_str("output")
+                        .unwrap_or_else(|| Path::new(dir).join("report.html").to_string_lossy().into_owned());
+    match Analysis::load(dir).and_then(|analysis| analysis.save(&output)) {
+        Ok(()) => {
+            println!("{}", output);
+            0
+        },
+        Err(e) => {
+            println!("{}", e);
+            Outcome::Error(e).exit_code()
+        }
+    }
+}
+
+/// Parses the options of a run like `main` does, which loads every file
+/// they refer to, and prints the effective configuration and the estimated
+/// memory usage instead of running the simulation. Returns the exit code.
+fn run_check_config(args: &[String]) -> i32 {
+    // usage is printed as "cell check-config [options]"
+    let mut args = args.to_vec();
+    let subcommand = args.remove(1);
+    args[0] = format!("{} {}", args[0], subcommand);
+
+    let opts = match Options::from_args(args) {
+        ParseResult::Success(opts) => opts,
+        ParseResult::Failure(reason) => {
+            println!("{}", reason);
+            return Outcome::Error(reason).exit_code();
+        },
+        ParseResult::Exit => return 0
+    };
+    let lang = opts.lang;
+
+    println!("{}\n{}", lang.tr(Message::Configuration), opts);
+    println!("{}\n{}", lang.tr(Message::EffectiveConfig), opts.effective_config().to_toml());
+
+    let mut errors = Vec::new();
+    for (name, dir) in opts.output_dirs() {
+        if !dir.is_dir() {
+            errors.push(lang.format(Message::MissingOutputDir, &[name, &dir.display().to_string()]));
+        }
+    }
+
+    // a random seed is picked at the start of the run, any will do here
+    let (width, height) = opts.board_size;
+    let built = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut estimate = MemoryEstimate { bytes: 0 };
+        let rng = Box::new(StdRng::from_seed(&[opts.seed.unwrap_or(0)][..]));
+        simulations().build(opts.simulation.name(), width, height, &opts, rng, &mut estimate)
+                     .map(|_| estimate.bytes)
+    }));
+    match built {
+        Ok(Ok(bytes)) => println!("{}", lang.format(Message::EstimatedMemory, &[&format_bytes(bytes)])),
+        Ok(Err(e)) => errors.push(e),
+        Err(payload) => errors.push(panic_message(payload))
+    }
+
+    if errors.is_empty() {
+        println!("{}", lang.tr(Message::ConfigOk));
+        0
+    } else {
+        for e in &errors {
+            println!("{}", e);
+        }
+        Outcome::Error(errors.join("; ")).exit_code()
+    }
+}
+
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: &'static [&'static str] = &["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} B", bytes),
+        _ => format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Times a freshly built simulation, for --bench.
+struct Bench {
+    iterations: usize,
+    result: Option<BenchResult>
+}
+
+impl Launcher for Bench {
+    fn launch<T, S>(&mut self,
+                    simulation: S) where S: Simulation<T> + Clone + Send + 'static,
+                                         T: CellColor + Clone + PartialEq + Send + Sync + 'static {
+        self.result = Some(bench::run(simulation, self.iterations));
+    }
+}
+
+/// Runs every registered simulation on the standard workloads and prints
+/// their timings. The seed is 0 unless given, so that runs of different
+/// builds do the same work.
+fn run_bench(opts: &Options) -> i32 {
+    let factory = simulations();
+    for name in factory.names() {
+        for workload in bench::WORKLOADS {
+            let (width, height) = workload.board_size;
+            let mut bench = Bench { iterations: workload.iterations, result: None };
+            let rng = Box::new(StdRng::from_seed(&[opts.seed.unwrap_or(0)][..]));
+            if let Err(e) = factory.build(name, width, height, opts, rng, &mut bench) {
+                println!("{}", e);
+                return Outcome::Error(e).exit_code();
+            }
+            if let Some(result) = bench.result {
+                print!("{} {}x{}, {} steps: {}", name, width, height, workload.iterations, result);
+            }
+        }
+    }
+    0
+}
+
+/// Runs the sweep in `dir`, every job as a headless run of this program
+/// with its output in the job's log, and returns the exit code. A sweep
+/// started before in `dir` is resumed instead, with the jobs it was
+/// started with.
+fn run_sweep(opts: &Options,
+             dir: &str) -> i32 {
+    let sweep = if Path::new(dir).join(sweep::JOBS_FILE).exists() {
+        println!("{}", opts.lang.format(Message::SweepResumed, &[dir]));
+        Sweep::open(dir)
+    } else {
+        let mut args = opts.args.clone();
+        for name in ["sweep", "sweep-param", "sweep-threads", "manifest"].iter() {
+            args = Options::without_option(&args, name);
+        }
+        for param in opts.sweep_params.iter() {
+            args = Options::without_option(&args, &param.name);
+        }
+        if !args.iter().any(|arg| arg == "--headless") {
+            args.push("--headless".to_string());
+        }
+        // every job uses the same seed, unless seeds are swept over
+        if !args.iter().any(|arg| arg == "--seed") && !opts.sweep_params.iter().any(|param| param.name == "seed") {
+            args.push("--seed".to_string());
+            args.push(opts.seed.expect("seed should be picked before the sweep starts").to_string());
+        }
+        Sweep::create(dir, sweep::jobs(&args, &opts.sweep_params))
+    };
+    let sweep = match sweep {
+        Ok(sweep) => sweep,
+        Err(e) => {
+            println!("{}", e);
+            return Outcome::Error(e).exit_code();
+        }
+    };
+
+    let program = match env::current_exe() {
+        Ok(program) => program,
+        Err(e) => {
+            println!("{}", e);
+            return Outcome::Error(e.to_string()).exit_code();
+        }
+    };
+    let report = sweep.run(opts.sweep_threads, move |job, manifest, log| {
+        let log = try!(File::create(log).map_err(|e| format!("{}: {}", log.display(), e)));
+        let errors = try!(log.try_clone().map_err(|e| e.to_string()));
+        let status = try!(Command::new(&program).args(&job.args)
+                                                .arg("--manifest")
+                                                .arg(manifest)
+                                                .stdout(Stdio::from(log))
+                                                .stderr(Stdio::from(errors))
+                                                .status()
+                                                .map_err(|e| format!("cannot start {}: {}", program.display(), e)));
+        println!("{}: {}", job.name, status);
+        Ok(())
+    });
+
+    println!("{}", opts.lang.format(Message::SweepSummary, &[&report.completed.to_string(),
+                                                             &report.skipped.to_string(),
+                                                             &report.failed.len().to_string()]));
+    for &(ref name, ref e) in report.failed.iter() {
+        println!("{}: {}", name, e);
+    }
+    match report.failed.first() {
+        Some(&(_, ref e)) => Outcome::Error(e.clone()).exit_code(),
+        None => 0
+    }
+}
+
+fn write_crash_bundle<T: CellColor>(opts: &Options,
+                                    crash: &Crash<T>) {
+    let name = format!("crash-{}", time::strftime("%Y%m%d-%H%M%S", &time::now()).unwrap());
+    let dir = Path::new(&opts.crash_dir).join(name);
+    let dir = dir.to_string_lossy();
+
+    match crash.write_bundle(&dir, &opts.to_string()) {
+        Ok(()) => println!("{}", opts.lang.format(Message::CrashBundleWritten, &[&dir])),
+        Err(e) => println!("{}", opts.lang.format(Message::CrashBundleFailed, &[&e]))
+    }
+}
+
+fn start<T, S>(opts: &Options,
+               simulation: S) where S: Simulation<T> + Clone + Send + 'static,
+                                    T: CellColor + Clone + PartialEq + Send + Sync + 'static {
+    // without a window no inputs can be made, so headless recordings
+    // consist of just the header
+    let run_writer = opts.run_writer();
+
+    if opts.headless {
+        let (mut outcome, stats, result, warnings) = headless::run(simulation, HeadlessConfig {
+            iterations: opts.iterations,
+            stop_criterion: opts.stop_criterion.clone(),
+            verify_every: opts.verify_replay,
+            record: opts.recorder(),
+            event_log: opts.event_log(),
+            stats_log: opts.stats_log(),
+            exposure: opts.exposure(),
+            merges: opts.merges.clone(),
+            count_region: opts.count_region,
+            snapshots: opts.snapshot_every.map(|_| opts.snapshots()),
+            energy_histogram: opts.energy_histogram
+        });
+        let simulation = match result {
+            Ok(simulation) => Some(simulation),
+            Err(crash) => {
+                write_crash_bundle(opts, &crash);
+                None
+            }
+        };
+
+        if let Some(ref path) = opts.manifest {
+            if let Err(e) = write_manifest(opts, path, &outcome, simulation.as_ref(), &warnings) {
+                outcome = Outcome::Error(e);
+            }
+        }
+        print!("{}", warnings);
+        if let (true, Some(simulation)) = (opts.symmetry, simulation.as_ref()) {
+            println!("{}", SymmetryReport::of_board(simulation.board(), opts.symmetry_region));
+        }
+        if let Some(audit) = simulation.as_ref().and_then(|simulation| simulation.fairness()) {
+            println!("{}", audit);
+        }
+
+        let report = RunReport {
+            outcome: outcome,
+            simulation: opts.simulation.name(),
+            seed: opts.seed,
+            iterations: stats.as_ref().map(|s| s.iteration).unwrap_or(0),
+            stats: stats,
+            warnings: warnings.count()
+        };
+        println!("{}", report.to_json());
+        process::exit(report.outcome.exit_code());
+    }
+
+    let gl_version = OpenGL::V3_2;
+
+    let window: Option<Window> = match opts.render {
+        Backend::Window => Some(WindowSettings::new("cell", [800, 600])
+                                    .opengl(gl_version)
+                                    .exit_on_esc(true)
+                                    .build()
+                                    .unwrap()),
+        Backend::Tui => None
+    };
+
+    let initial = opts.replay.as_ref().map(|_| simulation.clone());
+    let occupancy = opts.count_region.map(|_| Quadtree::of_board(simulation.board()));
+    let app = App {
+        renderer: match window {
+            Some(_) => Box::new(GlRenderer::new(GlGraphics::new(gl_version))),
+            None => Box::new(TuiRenderer)
+        },
+        simulation: simulation,
+        camera: opts.camera,
+        time_accumulator: TimeAccumulator::new(1.0f64 / opts.updates_per_second)
+                              .with_max_steps(opts.max_steps_per_frame, opts.drop_lag),
+        updates_per_second: opts.updates_per_second,
+        max_speed: opts.max_speed,
+        speed_ramp: opts.speed_ramp.clone(),
+        iteration: 0,
+        viewport_size: [0.0, 0.0],
+        cursor: [0.0, 0.0],
+        brush: 1,
+        painting: None,
+        brush_tip: BrushTip::default(),
+        selection: None,
+        selecting: None,
+        clipboard: None,
+        shift: false,
+        ctrl: false,
+        show_drift: opts.show_drift,
+        show_density: opts.show_density,
+        show_grid: opts.grid_lines || opts.theme.grid.is_some(),
+        trails: None,
+        theme: opts.theme.clone(),
+        symmetry_region: opts.symmetry_region,
+        count_region: opts.count_region,
+        occupancy: occupancy,
+        follow: false,
+        recorder: opts.recorder(),
+        event_log: opts.event_log(),
+        stats_log: opts.stats_log(),
+        exposure: opts.exposure(),
+        notebook: opts.notebook(),
+        snapshots: Some(opts.snapshots()),
+        merges: opts.merges.clone(),
+        config: opts.effective_config(),
+        run_writer: run_writer,
+        replay: opts.replay.clone(),
+        initial: initial,
+        paused: false,
+        playback_speed: 1.0,
+        show_hud: false,
+        plot: PlotHistory::new(opts.plot_length),
+        show_plot: false,
+        show_energy_histogram: false,
+        picking: false,
+        followed: None,
+        tick_stats: (TickStats::of(&[]), TickStats::of(&[])),
+        warnings: WarningLog::new(),
+        metrics: Metrics::new(),
+        sinks: opts.sinks(),
+        opts: opts.clone(),
+        factory: simulations()
+    };
+    let app = match panic::catch_unwind(AssertUnwindSafe(|| match window {
+        Some(window) => run(window, app),
+        None => run_tui(app)
+    })) {
+        Ok(app) => app,
+        Err(payload) => {
+            let crash: Crash<T> = Crash::new(panic_message(payload));
+            write_crash_bundle(opts, &crash);
+            process::exit(Outcome::Error(crash.message).exit_code());
+        }
+    };
+
+    let (simulation, mut warnings) = (app.simulation, app.warnings);
+    if let Some(e) = app.recorder.and_then(|mut recorder| recorder.finish().err()) {
+        println!("{}", e);
+        warnings.record(WarningKind::RecordingFailed, app.iteration, e);
+    }
+    if let Some(e) = app.exposure.and_then(|mut exposure| exposure.finish().err()) {
+        println!("{}", e);
+        warnings.record(WarningKind::ExposureFailed, app.iteration, e);
+    }
+    if let Some(own) = simulation.warnings() {
+        warnings.merge(own);
+    }
+    print!("{}", warnings);
+
+    if opts.symmetry {
+        println!("{}", SymmetryReport::of_board(simulation.board(), opts.symmetry_region));
+    }
+
+    if let Some(ref path) = opts.manifest {
+        if let Err(e) = write_manifest(opts, path, &Outcome::Completed, Some(&simulation), &warnings) {
+            println!("{}", e);
+            process::exit(Outcome::Error(e).exit_code());
+        }
+    }
+}
+
+/// Starts simulations built by the registry, in a window or headless.
+struct Starter<'a> {
+    opts: &'a Options
+}
+
+impl<'a> Launcher for Starter<'a> {
+    fn launch<T, S>(&mut self,
+                    simulation: S) where S: Simulation<T> + Clone + Send + 'static,
+                                         T: CellColor + Clone + PartialEq + Send + Sync + 'static {
+        start(self.opts, simulation);
+    }
+}
+
+fn build_good_evil<L: Launcher>(width: usize,
+                                height: usize,
+                                opts: &Options,
+                                mut rng: Box<StdRng>,
+                                launcher: &mut L) {
+    let sim_cfg = GoodEvilConfig {
+        num_specimens: opts.num_specimens.unwrap_or(width * height / 20),
+        initial_specimen_energy: opts.specimen_energy,
+        initial_energy: opts.initial_energy,
+        initial_state: opts.init,
+        energy_loss_per_step: opts.energy_loss,
+        deadly_energy_margin: opts.deadly_energy_margin,
+        events: opts.events.clone(),
+        random_event_probability: opts.random_event_probability,
+        boundary: opts.boundary,
+        movement: opts.movement,
+        update_order: opts.update_order,
+        conflicts: opts.conflicts,
+        fairness_audit: opts.fairness_audit,
+        action_costs: opts.action_costs,
+        immigration_rate: opts.immigration_rate,
+        immigrant_energy: opts.immigrant_energy,
+        drift: opts.drift.as_ref().map(|source| source.build(width, height, &mut rng)),
+        reputation: opts.reputation,
+        predation: opts.predation,
+        species: opts.species.clone(),
+        max_collision_size: opts.max_collision_size,
+        interaction_radius: opts.interaction_radius,
+        genetics: opts.genetics,
+        dormancy: opts.dormancy,
+        crowding: opts.crowding,
+        behavior: opts.behavior.clone(),
+        policies: Policies::new(),
+        reproduction: opts.reproduction,
+        food: opts.food,
+        checks: opts.checks
+    };
+
+    let simulation = match (&opts.pattern_file, &opts.initial_specimens) {
+        (&Some(ref pattern), _) => {
+            let specimens = match opts.initial_specimens {
+                Some(ref specimens) => specimens.clone(),
+                None => GoodEvil::initial_specimens(&sim_cfg, &mut rng)
+            };
+            GoodEvil::with_walls(&opts.pattern_board(pattern), sim_cfg, specimens, rng)
+        },
+        (&None, &Some(ref specimens)) => GoodEvil::with_specimens(width, height, sim_cfg,
+                                                                  specimens.clone(), rng),
+        (&None, &None) => GoodEvil::new(width, height, sim_cfg, rng)
+    };
+
+    launcher.launch(simulation);
+}
+
+fn build_life<L: Launcher>(width: usize,
+                           height: usize,
+                           opts: &Options,
+                           mut rng: Box<StdRng>,
+                           launcher: &mut L) {
+    let mut simulation = match (&opts.pattern_file, opts.pattern) {
+        (&Some(ref pattern), _) => GameOfLife::new(opts.pattern_board(pattern)),
+        (&None, Some((pattern, offset))) => {
+            let board = demo::board_from_pattern(opts.board_size, pattern, offset,
+                                                 true, false);
+            GameOfLife::new(board)
+        },
+        (&None, None) => match opts.init {
+            Some(init) => GameOfLife::new(init.mask(width, height, &mut rng)),
+            None => GameOfLife::new_random(width, height, 0.3, &mut rng)
+        }
+    };
+    if let Some(ref rule) = opts.cell_rule {
+        simulation = simulation.with_rule(rule.clone());
+    }
+
+    launcher.launch(simulation.with_storage(opts.storage));
+}
+
+/// Every built-in simulation, registered under its `SimulationKind` name.
+fn simulations<L: Launcher>() -> SimulationFactory<Options, L> {
+    let mut factory: SimulationFactory<Options, L> = SimulationFactory::new();
+    factory.register(SimulationKind::GoodEvil.name(), build_good_evil);
+    factory.register(SimulationKind::Life.name(), build_life);
+    factory.register(SimulationKind::Epidemic.name(), |width, height, opts, mut rng, starter| {
+        starter.launch(match opts.init {
+            Some(init) => {
+                let infected = Health::Infected(opts.epidemic.infection_duration);
+                let board = init.board(width, height, infected, Health::Susceptible, &mut rng);
+                Epidemic::from_board(board, opts.epidemic, rng)
+            },
+            None => Epidemic::new(width, height, opts.epidemic, rng)
+        })
+    });
+    factory.register(SimulationKind::Sandpile.name(), |width, height, opts, mut rng, starter| {
+        starter.launch(match opts.init {
+            Some(init) => {
+                let board = init.board(width, height, sandpile::TOPPLE_THRESHOLD - 1, 0, &mut rng);
+                Sandpile::from_board(board, opts.sandpile, rng)
+            },
+            None => Sandpile::new(width, height, opts.sandpile, rng)
+        })
+    });
+    factory.register(SimulationKind::GrayScott.name(), |width, height, opts, mut rng, starter| {
+        starter.launch(match opts.init {
+            Some(init) => {
+                let v = init.board(width, height, 1.0f32, 0.0, &mut rng);
+                let mut u = Board::new(width, height, 1.0f32);
+                for (x, y) in v.indices() {
+                    *u.at_mut(x, y) -= *v.at(x, y);
+                }
+                GrayScott::from_boards(u, v, opts.gray_scott)
+            },
+            None => GrayScott::new(width, height, opts.gray_scott, rng)
+        })
+    });
+    factory.register(SimulationKind::Cyclic.name(), |width, height, opts, rng, starter| {
+        starter.launch(Cyclic::new(width, height, opts.cyclic, rng))
+    });
+    factory.register(SimulationKind::ForestFire.name(), |width, height, opts, rng, starter| {
+        starter.launch(ForestFire::new(width, height, opts.forest_fire, rng))
+    });
+    factory.register(SimulationKind::FallingSand.name(), |width, height, _, rng, starter| {
+        starter.launch(FallingSand::new(width, height, rng))
+    });
+    factory.register(SimulationKind::Elementary.name(), |width, height, opts, mut rng, starter| {
+        starter.launch(match opts.init {
+            Some(init) => {
+                // the row is generated on a board one cell high
+                let row = init.mask(width, 1, &mut rng).iter().cloned().collect::<Vec<bool>>();
+                Elementary::from_row(height, opts.elementary, &row)
+            },
+            None => Elementary::new(width, height, opts.elementary)
+        })
+    });
+    factory.register(SimulationKind::Boids.name(), |width, height, opts, mut rng, starter| {
+        starter.launch(match opts.init {
+            Some(init) => {
+                let cells = init.place(&Board::new(width, height, true), opts.boids.num_boids, &mut rng);
+                let boids = cells.into_iter().map(|(x, y)| {
+                                                 let angle = rng.gen::<f64>() * 2.0 * std::f64::consts::PI;
+                                                 Agent {
+                                                     position: (x as f64 + 0.5, y as f64 + 0.5),
+                                                     velocity: (angle.cos() * opts.boids.max_speed,
+                                                                angle.sin() * opts.boids.max_speed)
+                                                 }
+                                             })
+                                             .collect();
+                Boids::from_agents(width, height, opts.boids, boids)
+            },
+            None => Boids::new(width, height, opts.boids, rng)
+        })
+    });
+    factory.register(SimulationKind::Ants.name(), |width, height, opts, rng, starter| {
+        starter.launch(AntColony::new(width, height, opts.ants, rng))
+    });
+    factory.register(SimulationKind::Ising.name(), |width, height, opts, mut rng, starter| {
+        starter.launch(match opts.init {
+            Some(init) => {
+                let board = init.board(width, height, Spin::Down, Spin::Up, &mut rng);
+                Ising::from_board(board, opts.ising, rng)
+            },
+            None => Ising::new(width, height, opts.ising, rng)
+        })
+    });
+    factory.register(SimulationKind::Fluid.name(), |width, height, opts, _, starter| {
+        starter.launch(Fluid::new(width, height, opts.fluid))
+    });
+    factory.register(SimulationKind::Life3D.name(), |width, height, opts, mut rng, starter| {
+        let depth = opts.board_depth.unwrap_or(life3d::DEFAULT_DEPTH);
+        starter.launch(Life3D::new(width, height, depth, opts.life3d, &mut rng))
+    });
+    factory.register(SimulationKind::Turmites.name(), |width, height, opts, mut rng, starter| {
+        starter.launch(match opts.init {
+            Some(init) => {
+                let cells = init.place(&Board::new(width, height, true), opts.turmites.num_turmites, &mut rng);
+                let turmites = cells.into_iter().map(|(x, y)| Turmite {
+                                                    x: x,
+                                                    y: y,
+                                                    heading: rng.gen_range(0, 4),
+                                                    state: 0
+                                                })
+                                                .collect();
+                Turmites::with_turmites(width, height, opts.turmites.clone(), turmites)
+            },
+            None => Turmites::new(width, height, opts.turmites.clone(), rng)
+        })
+    });
+    factory
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map_or(false, |arg| arg == "analyze") {
+        process::exit(run_analyze(&args));
+    }
+    if args.get(1).map_or(false, |arg| arg == "check-config") {
+        process::exit(run_check_config(&args));
+    }
+
+    let mut opts = match Options::from_cmdline() {
+        ParseResult::Success(opts) => opts,
+        ParseResult::Failure(reason) => {
+            exit_with_error(std::env::args().any(|arg| arg == "--headless"), "", None, reason)
+        },
+        ParseResult::Exit => return
+    };
+
+    if opts.bench {
+        process::exit(run_bench(&opts));
+    }
+
+    // pick the seed up front, so that it can be reported and reused
+    let seed = opts.seed.unwrap_or_else(rand::random);
+    opts.seed = Some(seed);
+
+    if let Some(ref dir) = opts.sweep {
+        process::exit(run_sweep(&opts, dir));
+    }
+
+    println!("{}\n{}", opts.lang.tr(Message::Configuration), opts);
+
+    if let Some(ref path) = opts.dump_config {
+        if let Err(e) = opts.effective_config().save(path) {
+            println!("{}", e);
         }
     }
+
+    let rng = Box::new(StdRng::from_seed(&[seed][..]));
+    let (width, height) = opts.board_size;
+
+    // constructors assert on configurations parsing doesn't rule out, and
+    // those must end a run like any other error
+    let built = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut starter = Starter { opts: &opts };
+        simulations().build(opts.simulation.name(), width, height, &opts, rng, &mut starter)
+    }));
+    let reason = match built {
+        Ok(Ok(())) => return,
+        Ok(Err(e)) => e,
+        Err(payload) => panic_message(payload)
+    };
+    exit_with_error(opts.headless, opts.simulation.name(), Some(seed), reason)
+}
+
+/// Prints why the run can't go on, along with a report of the failed run
+/// for headless ones, and exits with the exit code of errors.
+fn exit_with_error(headless: bool,
+                   simulation: &'static str,
+                   seed: Option<usize>,
+                   reason: String) -> ! {
+    println!("{}", reason);
+
+    let outcome = Outcome::Error(reason);
+    if headless {
+        let report = RunReport {
+            outcome: outcome.clone(),
+            simulation: simulation,
+            seed: seed,
+            iterations: 0,
+            stats: None,
+            warnings: 0
+        };
+        println!("{}", report.to_json());
+    }
+    process::exit(outcome.exit_code());
 }
@@ -0,0 +1,292 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Write};
+
+use rustc_serialize::json::{Json, ToJson};
+
+use genome::Genome;
+use migration::{self, Object, Schema};
+use simulation::Specimen;
+use warnings::WarningLog;
+#[cfg(test)]
+use warnings::WarningKind;
+
+pub const FORMAT_VERSION: u64 = 1;
+
+/// Fields of stored specimens: energy only in version 1, reputation added
+/// in 2, genome in 3 and species in 4. Manifests without a schema version
+/// are treated as version 1; builds that wrote them stored whatever fields
+/// `Specimen` had at the time, which migrations keep.
+pub const SPECIMEN_SCHEMA: Schema = Schema {
+    name: "specimen",
+    migrations: &[add_reputation, add_genome, add_species]
+};
+
+fn add_reputation(specimen: &mut Object) {
+    migration::insert_default(specimen, "reputation", 0.0f64.to_json());
+}
+
+fn add_genome(specimen: &mut Object) {
+    migration::insert_default(specimen, "genome", genome_to_json(&Genome::default()));
+}
+
+fn add_species(specimen: &mut Object) {
+    migration::insert_default(specimen, "species", 0u64.to_json());
+}
+
+/// Summary of a finished run, written with --manifest. Besides describing
+/// the run, it stores the surviving specimens, so that a following run can
+/// start from them (--seed-population from-run).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Manifest {
+    pub simulation: String,
+    pub seed: Option<usize>,
+    pub board_size: (usize, usize),
+    pub iterations: usize,
+    pub outcome: String,
+    pub stats: Vec<(String, f64)>,
+    pub specimens: Vec<Specimen>,
+    /// Anything that degraded the run, see `warnings`.
+    pub warnings: WarningLog
+}
+
+pub fn field<'a>(obj: &'a BTreeMap<String, Json>,
+                 name: &str) -> Result<&'a Json, String> {
+    obj.get(name).ok_or_else(|| format!("missing field: {}", name))
+}
+
+pub fn as_usize(json: &Json,
+                name: &str) -> Result<usize, String> {
+    json.as_u64()
+        .map(|n| n as usize)
+        .ok_or_else(|| format!("field {} is not a non-negative integer", name))
+}
+
+fn as_f64(json: &Json,
+          name: &str) -> Result<f64, String> {
+    json.as_f64().ok_or_else(|| format!("field {} is not a number", name))
+}
+
+pub fn as_str<'a>(json: &'a Json,
+                  name: &str) -> Result<&'a str, String> {
+    json.as_string().ok_or_else(|| format!("field {} is not a string", name))
+}
+
+fn genome_to_json(genome: &Genome) -> Json {
+    let mut obj = BTreeMap::new();
+    obj.insert("aggression".to_string(), (genome.aggression as f64).to_json());
+    obj.insert("metabolism".to_string(), (genome.metabolism as f64).to_json());
+    obj.insert("reproduction_threshold".to_string(), (genome.reproduction_threshold as f64).to_json());
+    Json::Object(obj)
+}
+
+fn genome_from_json(json: &Json) -> Result<Genome, String> {
+    let obj = try!(json.as_object().ok_or("genome is not a JSON object".to_string()));
+    let get = |name: &str| as_f64(try!(field(obj, name)), name).map(|value| value as f32);
+
+    Ok(Genome {
+        aggression: try!(get("aggression")),
+        metabolism: try!(get("metabolism")),
+        reproduction_threshold: try!(get("reproduction_threshold"))
+    })
+}
+
+/// Reads a specimen of the current schema version.
+fn specimen_from_json(specimen: &Object) -> Result<Specimen, String> {
+    Ok(Specimen {
+        energy: try!(as_f64(try!(field(specimen, "energy")), "energy")) as f32,
+        reputation: try!(as_f64(try!(field(specimen, "reputation")), "reputation")) as f32,
+        genome: try!(genome_from_json(try!(field(specimen, "genome")))),
+        cooldown: 0,
+        dormant: false,
+        species: try!(as_usize(try!(field(specimen, "species")), "species")) as u8,
+        age: 0,
+        generation: 0
+    })
+}
+
+impl Manifest {
+    pub fn to_json(&self) -> Json {
+        let mut obj = BTreeMap::new();
+
+        obj.insert("format_version".to_string(), FORMAT_VERSION.to_json());
+        obj.insert("simulation".to_string(), self.simulation.to_json());
+        obj.insert("seed".to_string(), match self.seed {
+            Some(seed) => (seed as u64).to_json(),
+            None => Json::Null
+        });
+        obj.insert("board_size".to_string(),
+                   vec![self.board_size.0 as u64, self.board_size.1 as u64].to_json());
+        obj.insert("iterations".to_string(), (self.iterations as u64).to_json());
+        obj.insert("outcome".to_string(), self.outcome.to_json());
+
+        let stats: BTreeMap<String, Json> = self.stats.iter()
+                                                .map(|&(ref name, value)| (name.clone(), value.to_json()))
+                                                .collect();
+        obj.insert("stats".to_string(), Json::Object(stats));
+
+        let specimens: Vec<Json> = self.specimens.iter().map(|s| {
+            let mut specimen = BTreeMap::new();
+            specimen.insert("energy".to_string(), (s.energy as f64).to_json());
+            specimen.insert("reputation".to_string(), (s.reputation as f64).to_json());
+            specimen.insert("species".to_string(), (s.species as u64).to_json());
+            specimen.insert("genome".to_string(), genome_to_json(&s.genome));
+            Json::Object(specimen)
+        }).collect();
+        obj.insert("specimen_schema".to_string(), SPECIMEN_SCHEMA.version().to_json());
+        obj.insert("specimens".to_string(), Json::Array(specimens));
+        obj.insert("warnings".to_string(), self.warnings.to_json());
+
+        Json::Object(obj)
+    }
+
+    pub fn from_json(json: &Json) -> Result<Manifest, String> {
+        let obj = try!(json.as_object().ok_or("manifest is not a JSON object".to_string()));
+
+        let version = try!(as_usize(try!(field(obj, "format_version")), "format_version"));
+        if version as u64 > FORMAT_VERSION {
+            return Err(format!("unsupported manifest format version: {}", version));
+        }
+
+        let board_size = try!(try!(field(obj, "board_size")).as_array()
+                                  .ok_or("field board_size is not an array".to_string()));
+        if board_size.len() != 2 {
+            return Err("field board_size must have 2 elements".to_string());
+        }
+
+        let mut stats = Vec::new();
+        let stats_obj = try!(try!(field(obj, "stats")).as_object()
+                                 .ok_or("field stats is not an object".to_string()));
+        for (name, value) in stats_obj.iter() {
+            stats.push((name.clone(), try!(as_f64(value, name))));
+        }
+
+        let schema_version = match obj.get("specimen_schema") {
+            Some(version) => try!(as_usize(version, "specimen_schema")) as u64,
+            None => 1
+        };
+        let mut specimens = Vec::new();
+        let specimens_arr = try!(try!(field(obj, "specimens")).as_array()
+                                     .ok_or("field specimens is not an array".to_string()));
+        for specimen in specimens_arr.iter() {
+            let mut specimen = try!(specimen.as_object()
+                                            .ok_or("specimen is not a JSON object".to_string()))
+                                   .clone();
+            try!(SPECIMEN_SCHEMA.migrate(&mut specimen, schema_version));
+            specimens.push(try!(specimen_from_json(&specimen)));
+        }
+
+        Ok(Manifest {
+            simulation: try!(as_str(try!(field(obj, "simulation")), "simulation")).to_string(),
+            seed: match try!(field(obj, "seed")) {
+                &Json::Null => None,
+                seed => Some(try!(as_usize(seed, "seed")))
+            },
+            board_size: (try!(as_usize(&board_size[0], "board_size")),
+                         try!(as_usize(&board_size[1], "board_size"))),
+            iterations: try!(as_usize(try!(field(obj, "iterations")), "iterations")),
+            outcome: try!(as_str(try!(field(obj, "outcome")), "outcome")).to_string(),
+            stats: stats,
+            specimens: specimens,
+            // not stored by older versions
+            warnings: match obj.get("warnings") {
+                Some(warnings) => try!(WarningLog::from_json(warnings)),
+                None => WarningLog::new()
+            }
+        })
+    }
+
+    pub fn save(&self,
+                path: &str) -> Result<(), String> {
+        let mut file = try!(File::create(path).map_err(|e| format!("cannot create {}: {}", path, e)));
+        writeln!(file, "{}", self.to_json().pretty())
+            .map_err(|e| format!("cannot write {}: {}", path, e))
+    }
+
+    pub fn load(path: &str) -> Result<Manifest, String> {
+        let mut contents = String::new();
+        try!(File::open(path).and_then(|mut f| f.read_to_string(&mut contents))
+                             .map_err(|e| format!("cannot read {}: {}", path, e)));
+
+        let json = try!(Json::from_str(&contents).map_err(|e| format!("{}: {}", path, e)));
+        Manifest::from_json(&json).map_err(|e| format!("{}: {}", path, e))
+    }
+}
+
+#[cfg(test)]
+fn test_manifest() -> Manifest {
+    Manifest {
+        simulation: "goodevil".to_string(),
+        seed: Some(42),
+        board_size: (80, 60),
+        iterations: 1000,
+        outcome: "completed".to_string(),
+        stats: vec![("avg".to_string(), 1.5), ("specimens".to_string(), 2.0)],
+        specimens: vec![Specimen {
+                            energy: 1.25,
+                            reputation: -0.5,
+                            genome: Genome { aggression: 0.75, metabolism: 1.5, reproduction_threshold: 2.0 },
+                            cooldown: 0,
+                            dormant: false,
+                            species: 2,
+                            age: 0,
+                            generation: 0
+                        },
+                        Specimen::new(0.5)],
+        warnings: {
+            let mut warnings = WarningLog::new();
+            warnings.record(WarningKind::CollisionLimitExceeded, 12, "all cells around (1, 2) are full".to_string());
+            warnings
+        }
+    }
+}
+
+#[test]
+fn test_manifest_json_round_trip() {
+    let manifest = test_manifest();
+    assert_eq!(Ok(manifest.clone()), Manifest::from_json(&manifest.to_json()));
+}
+
+#[test]
+fn test_manifest_rejects_missing_fields() {
+    let json = Json::from_str("{\"format_version\": 1, \"simulation\": \"goodevil\"}").unwrap();
+    assert!(Manifest::from_json(&json).is_err());
+}
+
+#[test]
+fn test_manifest_rejects_newer_format() {
+    let mut json = test_manifest().to_json();
+    if let Json::Object(ref mut obj) = json {
+        obj.insert("format_version".to_string(), Json::U64(FORMAT_VERSION + 1));
+    }
+
+    assert!(Manifest::from_json(&json).is_err());
+}
+
+#[test]
+fn test_manifest_reputation_defaults_to_zero() {
+    let json = Json::from_str("{\"format_version\": 1, \"simulation\": \"goodevil\", \"seed\": null, \
+                               \"board_size\": [4, 4], \"iterations\": 1, \"outcome\": \"completed\", \
+                               \"stats\": {}, \"specimens\": [{\"energy\": 1.0}]}").unwrap();
+    assert_eq!(vec![Specimen::new(1.0)], Manifest::from_json(&json).unwrap().specimens);
+}
+
+#[test]
+fn test_manifest_migrates_old_specimens() {
+    let json = Json::from_str("{\"format_version\": 1, \"simulation\": \"goodevil\", \"seed\": null, \
+                               \"board_size\": [4, 4], \"iterations\": 1, \"outcome\": \"completed\", \
+                               \"stats\": {}, \"specimen_schema\": 2, \
+                               \"specimens\": [{\"energy\": 1.0, \"reputation\": 0.5}]}").unwrap();
+    let specimen = Specimen { reputation: 0.5, ..Specimen::new(1.0) };
+    assert_eq!(vec![specimen], Manifest::from_json(&json).unwrap().specimens);
+}
+
+#[test]
+fn test_manifest_rejects_newer_specimen_schema() {
+    let mut json = test_manifest().to_json();
+    if let Json::Object(ref mut obj) = json {
+        obj.insert("specimen_schema".to_string(), Json::U64(SPECIMEN_SCHEMA.version() + 1));
+    }
+
+    assert!(Manifest::from_json(&json).is_err());
+}
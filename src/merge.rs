@@ -0,0 +1,393 @@
+//! Merging regions loaded from files into a running simulation, e.g. to
+//! inject a structure in the middle of an experiment with
+//! `--merge PATH@ITERATION:X,Y[:POLICY]`.
+//!
+//! Region files are plain text, one row of cells per line. `.` is an empty
+//! cell, and every simulation that supports merging maps other characters
+//! to its own cells (see `Mergeable`); characters it doesn't know leave the
+//! cell under them alone. Golly macrocell (`.mc`) files are read as regions
+//! with `O` on live cells.
+
+use std::cmp::{max, min};
+use std::fs::File;
+use std::io::Read;
+use std::sync::Arc;
+
+use board::Board;
+use macrocell::Pattern;
+use simulation::{Field, Specimen};
+use symmetry;
+
+/// What happens to cells of the board covered by the region.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MergePolicy {
+    /// Cells are replaced with the region's ones.
+    Overwrite,
+    /// Only vacant cells are replaced.
+    SkipOccupied,
+    /// Occupied cells covered by occupied ones are combined, e.g. by adding
+    /// energies; vacant ones are replaced.
+    MergeEnergy
+}
+
+impl MergePolicy {
+    pub fn from_name(name: &str) -> Option<MergePolicy> {
+        match name {
+            "overwrite" => Some(MergePolicy::Overwrite),
+            "skip-occupied" => Some(MergePolicy::SkipOccupied),
+            "merge-energy" => Some(MergePolicy::MergeEnergy),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            MergePolicy::Overwrite => "overwrite",
+            MergePolicy::SkipOccupied => "skip-occupied",
+            MergePolicy::MergeEnergy => "merge-energy"
+        }
+    }
+}
+
+/// Cells that can be read from region files and merged into a board.
+pub trait Mergeable: Clone {
+    /// Cell written as `c` in a region file, None if `c` means nothing to
+    /// this kind of cell.
+    fn from_char(c: char) -> Option<Self>;
+    /// Character `from_char` turns back into this cell, or a similar one.
+    fn to_char(&self) -> char;
+    fn is_vacant(&self) -> bool;
+    /// Cell resulting from merging `incoming` into this occupied cell with
+    /// `MergePolicy::MergeEnergy`.
+    fn combine(&self,
+               incoming: &Self) -> Self {
+        incoming.clone()
+    }
+}
+
+/// Characters of a region file, independent of the simulation it's merged
+/// into.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Region {
+    pub width: usize,
+    pub height: usize,
+    /// Row by row; rows shorter than `width` are padded with spaces.
+    cells: Vec<char>
+}
+
+impl Region {
+    pub fn parse(text: &str) -> Result<Region, String> {
+        let rows: Vec<&str> = text.lines()
+                                  .map(|line| line.trim_right())
+                                  .filter(|line| !line.is_empty() && !line.starts_with('!'))
+                                  .collect();
+        let width = rows.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+        if width == 0 {
+            return Err("empty region".to_string());
+        }
+
+        let mut cells = Vec::with_capacity(width * rows.len());
+        for row in rows.iter() {
+            let len = row.chars().count();
+            cells.extend(row.chars());
+            cells.extend((len..width).map(|_| ' '));
+        }
+
+        Ok(Region {
+            width: width,
+            height: rows.len(),
+            cells: cells
+        })
+    }
+
+    pub fn from_pattern(pattern: &Pattern) -> Region {
+        let mut cells = vec!['.'; pattern.width * pattern.height];
+        for &(x, y) in pattern.cells.iter() {
+            cells[y * pattern.width + x] = 'O';
+        }
+
+        Region {
+            width: pattern.width,
+            height: pattern.height,
+            cells: cells
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Region, String> {
+        if path.ends_with(".mc") {
+            return Pattern::load(path).map(|pattern| Region::from_pattern(&pattern));
+        }
+
+        let mut contents = String::new();
+        try!(File::open(path).and_then(|mut f| f.read_to_string(&mut contents))
+                             .map_err(|e| format!("cannot read {}: {}", path, e)));
+        Region::parse(&contents).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Cells of `area` of `board`, cut to the part inside the board.
+    pub fn copy_from<T: Mergeable>(board: &Board<T>,
+                                   area: symmetry::Region) -> Region {
+        let width = min(area.width, board.width.saturating_sub(area.x));
+        let height = min(area.height, board.height.saturating_sub(area.y));
+        let mut cells = Vec::with_capacity(width * height);
+        for y in area.y..area.y + height {
+            for x in area.x..area.x + width {
+                cells.push(board.at(x, y).to_char());
+            }
+        }
+
+        Region {
+            width: width,
+            height: height,
+            cells: cells
+        }
+    }
+
+    /// Region of empty cells, which clears whatever it's merged over.
+    pub fn blank(width: usize,
+                 height: usize) -> Region {
+        Region {
+            width: width,
+            height: height,
+            cells: vec!['.'; width * height]
+        }
+    }
+
+    pub fn at(&self,
+              x: usize,
+              y: usize) -> char {
+        self.cells[y * self.width + x]
+    }
+
+    /// The region rotated by 90 degrees clockwise.
+    pub fn rotated(&self) -> Region {
+        let mut cells = Vec::with_capacity(self.cells.len());
+        for y in 0..self.width {
+            for x in 0..self.height {
+                cells.push(self.at(y, self.height - 1 - x));
+            }
+        }
+
+        Region {
+            width: self.height,
+            height: self.width,
+            cells: cells
+        }
+    }
+
+    /// The region mirrored left to right.
+    pub fn flipped(&self) -> Region {
+        let mut cells = Vec::with_capacity(self.cells.len());
+        for y in 0..self.height {
+            for x in (0..self.width).rev() {
+                cells.push(self.at(x, y));
+            }
+        }
+
+        Region {
+            width: self.width,
+            height: self.height,
+            cells: cells
+        }
+    }
+
+    /// Rows of the region, in the format `parse` reads.
+    pub fn to_text(&self) -> String {
+        self.cells.chunks(max(self.width, 1))
+                  .map(|row| row.iter().cloned().collect::<String>())
+                  .collect::<Vec<String>>()
+                  .join("\n")
+    }
+}
+
+/// Merges `region` into `board` with its top left corner at `offset`.
+/// Parts of the region outside the board are ignored. Returns the number
+/// of cells changed.
+pub fn merge_into<T>(board: &mut Board<T>,
+                     region: &Region,
+                     offset: (usize, usize),
+                     policy: MergePolicy) -> usize where T: Mergeable + PartialEq {
+    let mut changed = 0;
+
+    for y in 0..region.height {
+        for x in 0..region.width {
+            let (bx, by) = (offset.0 + x, offset.1 + y);
+            if bx >= board.width || by >= board.height {
+                continue;
+            }
+            let incoming = match T::from_char(region.at(x, y)) {
+                Some(cell) => cell,
+                None => continue
+            };
+
+            let current = board.at(bx, by).clone();
+            let merged = match policy {
+                MergePolicy::Overwrite => incoming,
+                MergePolicy::SkipOccupied if current.is_vacant() => incoming,
+                MergePolicy::SkipOccupied => continue,
+                MergePolicy::MergeEnergy if current.is_vacant() => incoming,
+                MergePolicy::MergeEnergy if incoming.is_vacant() => continue,
+                MergePolicy::MergeEnergy => current.combine(&incoming)
+            };
+            if merged != current {
+                *board.at_mut(bx, by) = merged;
+                changed += 1;
+            }
+        }
+    }
+
+    changed
+}
+
+/// Region merged into the board after given number of steps.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScheduledMerge {
+    pub region: Arc<Region>,
+    pub iteration: usize,
+    pub offset: (usize, usize),
+    pub policy: MergePolicy
+}
+
+impl ScheduledMerge {
+    /// Parses `PATH@ITERATION:X,Y[:POLICY]`, loading the region from PATH.
+    /// The policy defaults to overwrite.
+    pub fn parse(s: &str) -> Result<ScheduledMerge, String> {
+        let expected = || "expected PATH@ITERATION:X,Y[:POLICY]".to_string();
+
+        let at = try!(s.rfind('@').ok_or_else(&expected));
+        let (path, schedule) = (&s[..at], &s[at + 1..]);
+        let parts: Vec<&str> = schedule.split(':').collect();
+        if parts.len() < 2 || parts.len() > 3 {
+            return Err(expected());
+        }
+
+        let iteration = try!(parts[0].parse().map_err(|_| expected()));
+        let offset: Vec<usize> = try!(parts[1].split(',')
+                                              .map(|n| n.parse().map_err(|_| expected()))
+                                              .collect());
+        if offset.len() != 2 {
+            return Err(expected());
+        }
+        let policy = match parts.get(2) {
+            None => MergePolicy::Overwrite,
+            Some(name) => try!(MergePolicy::from_name(name).ok_or_else(|| {
+                format!("unknown merge policy: {}, expected overwrite, skip-occupied or merge-energy", name)
+            }))
+        };
+
+        Ok(ScheduledMerge {
+            region: Arc::new(try!(Region::load(path))),
+            iteration: iteration,
+            offset: (offset[0], offset[1]),
+            policy: policy
+        })
+    }
+}
+
+impl Mergeable for bool {
+    fn from_char(c: char) -> Option<bool> {
+        match c {
+            '.' => Some(false),
+            'O' | '*' => Some(true),
+            _ => None
+        }
+    }
+
+    fn to_char(&self) -> char {
+        if *self { 'O' } else { '.' }
+    }
+
+    fn is_vacant(&self) -> bool {
+        !*self
+    }
+}
+
+/// `#` is a wall, `O` a specimen with energy 1 and digits specimens with
+/// that much energy. Merging energy adds the incoming specimen's energy to
+/// the one already there.
+impl Mergeable for Field {
+    fn from_char(c: char) -> Option<Field> {
+        match c {
+            '.' => Some(Field::Empty),
+            '#' => Some(Field::Wall),
+            'O' => Some(Field::Occupied(Specimen::new(1.0))),
+            '1'...'9' => c.to_digit(10).map(|energy| Field::Occupied(Specimen::new(energy as f32))),
+            _ => None
+        }
+    }
+
+    /// Specimens are written as their energy, rounded into range [1, 9].
+    fn to_char(&self) -> char {
+        match *self {
+            Field::Empty => '.',
+            Field::Wall => '#',
+            Field::Occupied(s) => {
+                let energy = s.energy.round().max(1.0).min(9.0) as u32;
+                ::std::char::from_digit(energy, 10).unwrap()
+            }
+        }
+    }
+
+    fn is_vacant(&self) -> bool {
+        *self == Field::Empty
+    }
+
+    fn combine(&self,
+               incoming: &Field) -> Field {
+        match (self, incoming) {
+            (&Field::Occupied(ref current), &Field::Occupied(ref incoming)) => Field::Occupied(Specimen {
+                energy: current.energy + incoming.energy,
+                ..*current
+            }),
+            _ => incoming.clone()
+        }
+    }
+}
+
+#[test]
+fn test_region_parse() {
+    let region = Region::parse("!comment\n.O\nOOO\n").unwrap();
+
+    assert_eq!((3, 2), (region.width, region.height));
+    assert_eq!('O', region.at(1, 0));
+    assert_eq!(' ', region.at(2, 0));
+    assert!(Region::parse("\n!only a comment\n").is_err());
+}
+
+#[test]
+fn test_region_copy_and_transform() {
+    let mut board = Board::new(4, 3, false);
+    *board.at_mut(1, 0) = true;
+    *board.at_mut(3, 1) = true;
+
+    let region = Region::copy_from(&board, symmetry::Region { x: 1, y: 0, width: 5, height: 2 });
+    assert_eq!("O..\n..O", region.to_text());
+    assert_eq!(region, Region::parse(&region.to_text()).unwrap());
+    assert_eq!(".O\n..\nO.", region.rotated().to_text());
+    assert_eq!("..O\nO..", region.flipped().to_text());
+    assert_eq!(region, region.rotated().rotated().rotated().rotated());
+
+    let mut cleared = board.clone();
+    assert_eq!(2, merge_into(&mut cleared, &Region::blank(3, 2), (1, 0), MergePolicy::Overwrite));
+    assert!(!cleared.iter().any(|&alive| alive));
+}
+
+#[test]
+fn test_merge_policies() {
+    let region = Region::parse("O.\n?O").unwrap();
+    let mut board = Board::new(3, 2, false);
+    *board.at_mut(1, 0) = true;
+    *board.at_mut(0, 1) = true;
+
+    let mut overwritten = board.clone();
+    assert_eq!(3, merge_into(&mut overwritten, &region, (0, 0), MergePolicy::Overwrite));
+    assert_eq!(vec![true, false, false, true, true, false], overwritten.iter().cloned().collect::<Vec<_>>());
+
+    let mut skipped = board.clone();
+    assert_eq!(1, merge_into(&mut skipped, &region, (1, 0), MergePolicy::SkipOccupied));
+    assert_eq!(vec![false, true, false, true, false, true], skipped.iter().cloned().collect::<Vec<_>>());
+
+    let mut merged = board.clone();
+    assert_eq!(2, merge_into(&mut merged, &region, (0, 0), MergePolicy::MergeEnergy));
+    assert_eq!(vec![true, true, false, true, true, false], merged.iter().cloned().collect::<Vec<_>>());
+}
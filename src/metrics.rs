@@ -0,0 +1,454 @@
+//! Named measurements of a run, gathered after every step and handed to
+//! sinks that print, log or serve them.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use stats::Stats;
+
+/// Prefix of metric names served to Prometheus.
+const PROMETHEUS_PREFIX: &'static str = "cell_";
+/// How long a scraper may take to send its request or read the response.
+const PROMETHEUS_TIMEOUT_S: u64 = 5;
+
+/// Distribution of values among buckets with upper bounds `bounds`, plus
+/// one for values above all of them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Histogram {
+    pub bounds: Vec<f64>,
+    pub counts: Vec<u64>,
+    pub sum: f64,
+    pub count: u64
+}
+
+impl Histogram {
+    pub fn new(bounds: &[f64]) -> Histogram {
+        Histogram {
+            bounds: bounds.to_vec(),
+            counts: vec![0; bounds.len() + 1],
+            sum: 0.0,
+            count: 0
+        }
+    }
+
+    pub fn observe(&mut self,
+                   value: f64) {
+        let bucket = self.bounds.iter().position(|&bound| value <= bound).unwrap_or(self.bounds.len());
+        self.counts[bucket] += 1;
+        self.sum += value;
+        self.count += 1;
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.sum / self.count as f64
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Metric {
+    /// Total since the start of the run, e.g. deaths.
+    Counter(u64),
+    /// Value as of the last step, e.g. population.
+    Gauge(f64),
+    /// Values observed during the last step, e.g. energies of specimens.
+    Histogram(Histogram)
+}
+
+impl Metric {
+    pub fn type_name(&self) -> &'static str {
+        match *self {
+            Metric::Counter(_) => "counter",
+            Metric::Gauge(_) => "gauge",
+            Metric::Histogram(_) => "histogram"
+        }
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Metric::Counter(n) => write!(f, "{}", n),
+            Metric::Gauge(x) => write!(f, "{}", x),
+            Metric::Histogram(ref histogram) => write!(f, "mean {:.2} of {}", histogram.mean(), histogram.count)
+        }
+    }
+}
+
+/// Metrics registered so far, in order of registration.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metrics {
+    /// Step the metrics are as of, 1 after the first step.
+    pub iteration: usize,
+    metrics: Vec<(&'static str, Metric)>,
+    /// Notable things that happened during the last step.
+    pub events: Vec<String>
+}
+
+impl Metrics {
+    pub fn new() -> Metrics {
+        Metrics::default()
+    }
+
+    /// Starts a step: stats become gauges and histograms are emptied, so
+    /// that they only describe the step.
+    pub fn record_stats(&mut self,
+                        stats: &Stats) {
+        self.iteration = stats.iteration;
+        self.events = stats.events.clone();
+        for &mut (_, ref mut metric) in self.metrics.iter_mut() {
+            if let Metric::Histogram(ref mut histogram) = *metric {
+                *histogram = Histogram::new(&histogram.bounds);
+            }
+        }
+        for &(name, value) in stats.values.iter() {
+            // going through the shortest decimal form keeps e.g. 0.1f32
+            // from turning into 0.10000000149011612
+            self.set(name, value.to_string().parse().unwrap_or(value.as_f32() as f64));
+        }
+    }
+
+    fn entry(&mut self,
+             name: &'static str,
+             default: Metric) -> &mut Metric {
+        let idx = match self.metrics.iter().position(|&(n, _)| n == name) {
+            Some(idx) => idx,
+            None => {
+                self.metrics.push((name, default));
+                self.metrics.len() - 1
+            }
+        };
+        &mut self.metrics[idx].1
+    }
+
+    /// Adds `by` to counter `name`, registering it at 0 first if needed.
+    pub fn increment(&mut self,
+                     name: &'static str,
+                     by: u64) {
+        match *self.entry(name, Metric::Counter(0)) {
+            Metric::Counter(ref mut n) => *n += by,
+            ref mut other => *other = Metric::Counter(by)
+        }
+    }
+
+    pub fn set(&mut self,
+               name: &'static str,
+               value: f64) {
+        *self.entry(name, Metric::Gauge(value)) = Metric::Gauge(value);
+    }
+
+    /// Adds `value` to histogram `name`, registering it with buckets up to
+    /// `bounds` first if needed.
+    pub fn observe(&mut self,
+                   name: &'static str,
+                   bounds: &[f64],
+                   value: f64) {
+        match *self.entry(name, Metric::Histogram(Histogram::new(bounds))) {
+            Metric::Histogram(ref mut histogram) => histogram.observe(value),
+            ref mut other => {
+                let mut histogram = Histogram::new(bounds);
+                histogram.observe(value);
+                *other = Metric::Histogram(histogram);
+            }
+        }
+    }
+
+    pub fn get(&self,
+               name: &str) -> Option<&Metric> {
+        self.metrics.iter()
+                    .find(|&&(n, _)| n == name)
+                    .map(|&(_, ref metric)| metric)
+    }
+
+    pub fn iter(&self) -> ::std::slice::Iter<(&'static str, Metric)> {
+        self.metrics.iter()
+    }
+}
+
+/// Same as `Stats`: `iter N name value ... event ...`.
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f, "iter {}", self.iteration));
+        for &(name, ref metric) in self.metrics.iter() {
+            try!(write!(f, " {} {}", name, metric));
+        }
+        for event in self.events.iter() {
+            try!(write!(f, " event {}", event));
+        }
+        Ok(())
+    }
+}
+
+/// Consumes metrics after every step.
+pub trait Sink {
+    /// Fails if the sink can't go on, e.g. when its file can't be written.
+    fn consume(&mut self,
+               metrics: &Metrics) -> Result<(), String>;
+
+    /// Lines to show in the HUD, for sinks that show any.
+    fn hud_lines(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// Prints metrics after every step.
+pub struct StdoutSink;
+
+impl Sink for StdoutSink {
+    fn consume(&mut self,
+               metrics: &Metrics) -> Result<(), String> {
+        println!("{}", metrics);
+        Ok(())
+    }
+}
+
+/// Writes a row of metrics per step. Columns are the metrics registered by
+/// the first step, histograms as their count and sum; metrics registered
+/// later are left out and ones missing from a step are left empty.
+pub struct CsvSink<W: Write> {
+    path: String,
+    out: W,
+    columns: Option<Vec<&'static str>>
+}
+
+impl CsvSink<BufWriter<File>> {
+    pub fn create(path: &str) -> Result<CsvSink<BufWriter<File>>, String> {
+        let file = try!(File::create(path).map_err(|e| format!("cannot create {}: {}", path, e)));
+        Ok(CsvSink::new(path, BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> CsvSink<W> {
+    /// `path` is only used in error messages.
+    pub fn new(path: &str,
+               out: W) -> CsvSink<W> {
+        CsvSink {
+            path: path.to_string(),
+            out: out,
+            columns: None
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+impl<W: Write> Sink for CsvSink<W> {
+    fn consume(&mut self,
+               metrics: &Metrics) -> Result<(), String> {
+        let mut line = String::new();
+        if self.columns.is_none() {
+            let columns: Vec<&'static str> = metrics.iter().map(|&(name, _)| name).collect();
+            line.push_str("iteration");
+            for &(name, ref metric) in metrics.iter() {
+                match *metric {
+                    Metric::Histogram(_) => line.push_str(&format!(",{}_count,{}_sum", name, name)),
+                    _ => line.push_str(&format!(",{}", name))
+                }
+            }
+            line.push('\n');
+            self.columns = Some(columns);
+        }
+
+        line.push_str(&metrics.iteration.to_string());
+        for &name in self.columns.as_ref().unwrap().iter() {
+            match metrics.get(name) {
+                Some(&Metric::Histogram(ref histogram)) => line.push_str(&format!(",{},{}", histogram.count, histogram.sum)),
+                Some(metric) => line.push_str(&format!(",{}", metric)),
+                None => line.push(',')
+            }
+        }
+        line.push('\n');
+
+        self.out.write_all(line.as_bytes())
+                .and_then(|_| self.out.flush())
+                .map_err(|e| format!("cannot write {}: {}", self.path, e))
+    }
+}
+
+/// Shows the chosen metrics in the HUD, one per line.
+pub struct HudSink {
+    names: Vec<String>,
+    lines: Vec<String>
+}
+
+impl HudSink {
+    pub fn new(names: Vec<String>) -> HudSink {
+        HudSink {
+            names: names,
+            lines: Vec::new()
+        }
+    }
+}
+
+impl Sink for HudSink {
+    fn consume(&mut self,
+               metrics: &Metrics) -> Result<(), String> {
+        self.lines = self.names.iter()
+                               .filter_map(|name| metrics.get(name).map(|metric| format!("{} {}", name, metric)))
+                               .collect();
+        Ok(())
+    }
+
+    fn hud_lines(&self) -> Vec<String> {
+        self.lines.clone()
+    }
+}
+
+/// Serves the metrics of the last step over HTTP in the Prometheus text
+/// format, to whatever path is requested.
+pub struct PrometheusSink {
+    body: Arc<Mutex<String>>
+}
+
+impl PrometheusSink {
+    /// Listens on `address`, e.g. `127.0.0.1:9100`, in the background.
+    pub fn bind(address: &str) -> Result<PrometheusSink, String> {
+        let listener = try!(TcpListener::bind(address).map_err(|e| format!("cannot listen on {}: {}", address, e)));
+        let body = Arc::new(Mutex::new(String::new()));
+        let served = body.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if let Ok(stream) = stream {
+                    // every scrape on its own thread, so that a client
+                    // that never sends its request holds up nobody else
+                    let served = served.clone();
+                    thread::spawn(move || PrometheusSink::serve(stream, &served));
+                }
+            }
+        });
+
+        Ok(PrometheusSink {
+            body: body
+        })
+    }
+
+    fn serve(mut stream: TcpStream,
+             body: &Mutex<String>) {
+        let timeout = Some(Duration::from_secs(PROMETHEUS_TIMEOUT_S));
+        if stream.set_read_timeout(timeout).and_then(|_| stream.set_write_timeout(timeout)).is_err() {
+            return;
+        }
+        // the request doesn't matter, as long as it's read
+        let mut request = [0u8; 1024];
+        let _ = stream.read(&mut request);
+        let body = body.lock().unwrap().clone();
+        let _ = write!(stream,
+                       "HTTP/1.0 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\n\r\n{}",
+                       body.len(), body);
+    }
+
+    /// Metrics in the Prometheus text exposition format, names prefixed
+    /// with `cell_`.
+    pub fn render(metrics: &Metrics) -> String {
+        let mut body = format!("# TYPE {}iteration gauge\n{}iteration {}\n",
+                               PROMETHEUS_PREFIX, PROMETHEUS_PREFIX, metrics.iteration);
+        for &(name, ref metric) in metrics.iter() {
+            let name = format!("{}{}", PROMETHEUS_PREFIX, name);
+            body.push_str(&format!("# TYPE {} {}\n", name, metric.type_name()));
+            match *metric {
+                Metric::Histogram(ref histogram) => {
+                    let mut cumulative = 0;
+                    for (bound, count) in histogram.bounds.iter().zip(histogram.counts.iter()) {
+                        cumulative += *count;
+                        body.push_str(&format!("{}_bucket{{le=\"{}\"}} {}\n", name, bound, cumulative));
+                    }
+                    body.push_str(&format!("{}_bucket{{le=\"+Inf\"}} {}\n", name, histogram.count));
+                    body.push_str(&format!("{}_sum {}\n{}_count {}\n", name, histogram.sum, name, histogram.count));
+                },
+                _ => body.push_str(&format!("{} {}\n", name, metric))
+            }
+        }
+        body
+    }
+}
+
+impl Sink for PrometheusSink {
+    fn consume(&mut self,
+               metrics: &Metrics) -> Result<(), String> {
+        *self.body.lock().unwrap() = PrometheusSink::render(metrics);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+fn test_metrics() -> Metrics {
+    let stats = Stats::new(3).count("specimens", 5)
+                             .real("energy", 0.1)
+                             .event("drought".to_string());
+    let mut metrics = Metrics::new();
+    metrics.record_stats(&stats);
+    metrics.increment("deaths", 2);
+    metrics.increment("deaths", 1);
+    metrics.observe("age", &[1.0, 10.0], 0.5);
+    metrics.observe("age", &[1.0, 10.0], 4.0);
+    metrics.observe("age", &[1.0, 10.0], 40.0);
+    metrics
+}
+
+#[test]
+fn test_metrics_registry() {
+    let mut metrics = test_metrics();
+
+    assert_eq!(Some(&Metric::Gauge(5.0)), metrics.get("specimens"));
+    assert_eq!(Some(&Metric::Counter(3)), metrics.get("deaths"));
+    assert_eq!(Some(&Metric::Histogram(Histogram {
+        bounds: vec![1.0, 10.0],
+        counts: vec![1, 1, 1],
+        sum: 44.5,
+        count: 3
+    })), metrics.get("age"));
+    assert_eq!("iter 3 specimens 5 energy 0.1 deaths 3 age mean 14.83 of 3 event drought", metrics.to_string());
+
+    // counters go on, histograms start over
+    metrics.record_stats(&Stats::new(4).count("specimens", 4));
+    metrics.increment("deaths", 1);
+    assert_eq!("iter 4 specimens 4 energy 0.1 deaths 4 age mean NaN of 0", metrics.to_string());
+}
+
+#[test]
+fn test_csv_sink() {
+    let mut sink = CsvSink::new("metrics.csv", Vec::new());
+    let mut metrics = test_metrics();
+    sink.consume(&metrics).unwrap();
+    metrics.record_stats(&Stats::new(4).count("specimens", 4));
+    metrics.set("fps", 60.0);
+    sink.consume(&metrics).unwrap();
+
+    assert_eq!("iteration,specimens,energy,deaths,age_count,age_sum\n\
+                3,5,0.1,3,3,44.5\n\
+                4,4,0.1,3,0,0\n",
+               String::from_utf8(sink.into_inner()).unwrap());
+}
+
+#[test]
+fn test_hud_sink() {
+    let mut sink = HudSink::new(vec!["deaths".to_string(), "missing".to_string(), "specimens".to_string()]);
+    assert!(sink.hud_lines().is_empty());
+    sink.consume(&test_metrics()).unwrap();
+    assert_eq!(vec!["deaths 3", "specimens 5"], sink.hud_lines());
+}
+
+#[test]
+fn test_prometheus_render() {
+    assert_eq!("# TYPE cell_iteration gauge\n\
+                cell_iteration 3\n\
+                # TYPE cell_specimens gauge\n\
+                cell_specimens 5\n\
+                # TYPE cell_energy gauge\n\
+                cell_energy 0.1\n\
+                # TYPE cell_deaths counter\n\
+                cell_deaths 3\n\
+                # TYPE cell_age histogram\n\
+                cell_age_bucket{le=\"1\"} 1\n\
+                cell_age_bucket{le=\"10\"} 2\n\
+                cell_age_bucket{le=\"+Inf\"} 3\n\
+                cell_age_sum 44.5\n\
+                cell_age_count 3\n",
+               PrometheusSink::render(&test_metrics()));
+}
@@ -0,0 +1,92 @@
+//! Upgrading JSON objects stored by older builds, e.g. specimens saved in a
+//! manifest before `Specimen` grew new fields. Every kind of stored object
+//! has a schema version, bumped whenever its fields change together with a
+//! migration from the previous version, so that old files stay loadable.
+
+use std::collections::BTreeMap;
+
+use rustc_serialize::json::Json;
+
+pub type Object = BTreeMap<String, Json>;
+
+/// Upgrades an object by a single schema version.
+pub type Migration = fn(&mut Object);
+
+pub struct Schema {
+    /// Kind of objects, used in errors.
+    pub name: &'static str,
+    /// `migrations[i]` upgrades objects from version `i + 1` to `i + 2`.
+    pub migrations: &'static [Migration]
+}
+
+impl Schema {
+    /// Version objects are written with.
+    pub fn version(&self) -> u64 {
+        self.migrations.len() as u64 + 1
+    }
+
+    /// Upgrades `obj` from schema `version` to the current one.
+    pub fn migrate(&self,
+                   obj: &mut Object,
+                   version: u64) -> Result<(), String> {
+        if version < 1 || version > self.version() {
+            return Err(format!("unsupported {} schema version: {}", self.name, version));
+        }
+
+        for migration in &self.migrations[version as usize - 1..] {
+            migration(obj);
+        }
+        Ok(())
+    }
+}
+
+/// Sets `name` to `value` unless the object has it already, for migrations
+/// adding fields that older builds wrote anyway.
+pub fn insert_default(obj: &mut Object,
+                      name: &str,
+                      value: Json) {
+    obj.entry(name.to_string()).or_insert(value);
+}
+
+#[cfg(test)]
+fn add_size(obj: &mut Object) {
+    insert_default(obj, "size", Json::U64(1));
+}
+
+#[cfg(test)]
+fn rename_name_to_label(obj: &mut Object) {
+    if let Some(name) = obj.remove("name") {
+        obj.insert("label".to_string(), name);
+    }
+}
+
+#[cfg(test)]
+const TEST_SCHEMA: Schema = Schema {
+    name: "test",
+    migrations: &[add_size, rename_name_to_label]
+};
+
+#[test]
+fn test_schema_migrate() {
+    let mut obj = Object::new();
+    obj.insert("name".to_string(), Json::String("a".to_string()));
+    TEST_SCHEMA.migrate(&mut obj, 1).unwrap();
+    assert_eq!(Some(&Json::U64(1)), obj.get("size"));
+    assert_eq!(Some(&Json::String("a".to_string())), obj.get("label"));
+    assert!(obj.get("name").is_none());
+
+    let mut obj = Object::new();
+    obj.insert("name".to_string(), Json::String("b".to_string()));
+    obj.insert("size".to_string(), Json::U64(5));
+    TEST_SCHEMA.migrate(&mut obj, 2).unwrap();
+    assert_eq!(Some(&Json::U64(5)), obj.get("size"));
+    assert!(obj.get("label").is_some());
+}
+
+#[test]
+fn test_schema_rejects_unknown_versions() {
+    assert_eq!(3, TEST_SCHEMA.version());
+    assert!(TEST_SCHEMA.migrate(&mut Object::new(), 3).is_ok());
+    assert!(TEST_SCHEMA.migrate(&mut Object::new(), 4).is_err());
+    assert!(TEST_SCHEMA.migrate(&mut Object::new(), 0).is_err());
+}
@@ -0,0 +1,142 @@
+//! Whole board at low resolution, drawn in a corner of the window while
+//! zoomed in, with the part shown by the camera outlined.
+
+use std::cmp::max;
+
+use renderer::Scene;
+
+/// Length of the longer side of the minimap, in pixels.
+const MINIMAP_SIZE: f64 = 160.0;
+/// Space between the minimap and the edges of the window, in pixels.
+const MINIMAP_MARGIN: f64 = 8.0;
+/// Cells of the minimap along its longer side, each averaging a block of
+/// board cells.
+const MINIMAP_CELLS: usize = 80;
+/// Board cells sampled along each side of a block, so that huge boards
+/// take as long as smaller ones.
+const BLOCK_SAMPLES: usize = 4;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Minimap {
+    pub width: usize,
+    pub height: usize,
+    /// Row by row.
+    pub colors: Vec<[f32; 4]>,
+    /// Where the minimap is drawn, as [x, y, width, height] in the viewport.
+    pub rect: [f64; 4],
+    /// Part of the board visible in the viewport, in the same coordinates.
+    pub visible: [f64; 4]
+}
+
+impl Minimap {
+    /// Downsamples the cells of `scene`, as drawn, into blocks of average
+    /// color.
+    pub fn of(scene: &Scene,
+              viewport_size: [f64; 2]) -> Minimap {
+        let (board_width, board_height) = scene.board_size;
+        let block = max(1, (max(board_width, board_height) + MINIMAP_CELLS - 1) / MINIMAP_CELLS);
+        let step = max(1, block / BLOCK_SAMPLES);
+        let (width, height) = ((board_width + block - 1) / block, (board_height + block - 1) / block);
+
+        let mut colors = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0.0; 4];
+                let mut samples = 0;
+                for by in (y * block..min_end(y, block, board_height)).filter(|by| (by - y * block) % step == 0) {
+                    for bx in (x * block..min_end(x, block, board_width)).filter(|bx| (bx - x * block) % step == 0) {
+                        let color = scene.cell(bx, by).color;
+                        for channel in 0..4 {
+                            sum[channel] += color[channel];
+                        }
+                        samples += 1;
+                    }
+                }
+                colors.push([sum[0] / samples as f32, sum[1] / samples as f32,
+                             sum[2] / samples as f32, sum[3] / samples as f32]);
+            }
+        }
+
+        let rect = Minimap::rect(scene.board_size, viewport_size);
+        let scale = [rect[2] / board_width as f64, rect[3] / board_height as f64];
+        let top_left = [(-scene.origin[0] / scene.cell_size[0]).max(0.0),
+                        (-scene.origin[1] / scene.cell_size[1]).max(0.0)];
+        let bottom_right = [((viewport_size[0] - scene.origin[0]) / scene.cell_size[0]).min(board_width as f64),
+                            ((viewport_size[1] - scene.origin[1]) / scene.cell_size[1]).min(board_height as f64)];
+
+        Minimap {
+            width: width,
+            height: height,
+            colors: colors,
+            rect: rect,
+            visible: [rect[0] + top_left[0] * scale[0],
+                      rect[1] + top_left[1] * scale[1],
+                      (bottom_right[0] - top_left[0]).max(0.0) * scale[0],
+                      (bottom_right[1] - top_left[1]).max(0.0) * scale[1]]
+        }
+    }
+
+    /// Where the minimap of a board is drawn: in the top right corner, with
+    /// the board's proportions.
+    pub fn rect(board_size: (usize, usize),
+                viewport_size: [f64; 2]) -> [f64; 4] {
+        let longer = max(max(board_size.0, board_size.1), 1) as f64;
+        let (width, height) = (MINIMAP_SIZE * board_size.0 as f64 / longer,
+                               MINIMAP_SIZE * board_size.1 as f64 / longer);
+        [viewport_size[0] - MINIMAP_MARGIN - width, MINIMAP_MARGIN, width, height]
+    }
+
+    /// Board coordinates of the point under `position` in the viewport, if
+    /// it's on the minimap.
+    pub fn board_position(board_size: (usize, usize),
+                          viewport_size: [f64; 2],
+                          position: [f64; 2]) -> Option<(f64, f64)> {
+        let rect = Minimap::rect(board_size, viewport_size);
+        let (x, y) = ((position[0] - rect[0]) / rect[2], (position[1] - rect[1]) / rect[3]);
+        if x >= 0.0 && y >= 0.0 && x < 1.0 && y < 1.0 {
+            Some((x * board_size.0 as f64, y * board_size.1 as f64))
+        } else {
+            None
+        }
+    }
+}
+
+/// End of the `idx`-th block of `block` cells, cut to `len`.
+fn min_end(idx: usize,
+           block: usize,
+           len: usize) -> usize {
+    ::std::cmp::min((idx + 1) * block, len)
+}
+
+#[test]
+fn test_minimap() {
+    use board::Board;
+    use camera::{Aspect, Camera};
+    use palette::WHITE;
+    use renderer::Viewport;
+    use simulation::GameOfLife;
+    use theme::Theme;
+
+    let mut board = Board::new(160, 80, false);
+    for x in 0..2 {
+        for y in 0..2 {
+            *board.at_mut(x, y) = true;
+        }
+    }
+    *board.at_mut(2, 0) = true;
+    *board.at_mut(3, 0) = true;
+    let simulation = GameOfLife::new(board);
+    let theme = Theme::default();
+    let camera = Camera { center: Some((40.0, 20.0)), zoom: 2.0, aspect: Aspect::Stretch };
+    let scene = Scene::of(&simulation, &theme, &camera, Viewport::new([800, 400]), false, false);
+    let minimap = Minimap::of(&scene, [800.0, 400.0]);
+
+    assert_eq!((80, 40), (minimap.width, minimap.height));
+    assert_eq!(WHITE, minimap.colors[0]);
+    assert_eq!([0.5, 0.5, 0.5, 1.0], minimap.colors[1]);
+    assert_eq!([632.0, 8.0, 160.0, 80.0], minimap.rect);
+    assert_eq!([632.0, 8.0, 80.0, 40.0], minimap.visible);
+
+    assert_eq!(Some((80.0, 40.0)), Minimap::board_position((160, 80), [800.0, 400.0], [712.0, 48.0]));
+    assert_eq!(None, Minimap::board_position((160, 80), [800.0, 400.0], [400.0, 48.0]));
+}
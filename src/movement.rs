@@ -0,0 +1,177 @@
+use rand::{Rng, StdRng};
+
+use board::Board;
+use simulation::Field;
+
+/// Weight of a full cell of food in the gradient policy score.
+const FOOD_ATTRACTION: f32 = 3.0;
+/// Weight of a unit of energy held by weaker specimens on the target cell.
+const PREY_ATTRACTION: f32 = 1.0;
+/// Penalty for every stronger specimen on or next to the target cell.
+const THREAT_AVOIDANCE: f32 = 2.0;
+
+/// How GoodEvil specimens choose the cell to move to. Every candidate
+/// cell is weighted with exp(score), multiplied by the drift weight if
+/// wind is enabled.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MovementPolicy {
+    /// All neighboring cells are equally likely.
+    Random,
+    /// Specimens prefer cells with food or weaker specimens to feed on, and
+    /// avoid the vicinity of stronger ones.
+    Gradient
+}
+
+impl MovementPolicy {
+    pub fn from_name(name: &str) -> Option<MovementPolicy> {
+        match name {
+            "random" => Some(MovementPolicy::Random),
+            "gradient" => Some(MovementPolicy::Gradient),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            MovementPolicy::Random => "random",
+            MovementPolicy::Gradient => "gradient"
+        }
+    }
+
+    /// Score of moving from `from` to (x, y) for a specimen of given
+    /// strength. The specimen itself is not considered a neighbor.
+    pub fn score(&self,
+                 board: &Board<Field>,
+                 food: Option<&Board<f32>>,
+                 from: (usize, usize),
+                 x: usize,
+                 y: usize,
+                 strength: f32) -> f32 {
+        if *self == MovementPolicy::Random {
+            return 0.0;
+        }
+
+        let food = food.map(|food| *food.at(x, y)).unwrap_or(0.0);
+        let mut prey = 0.0;
+        let mut threats = 0;
+
+        for ny in y.saturating_sub(1)..(y + 2) {
+            for nx in x.saturating_sub(1)..(x + 2) {
+                if nx >= board.width || ny >= board.height || (nx, ny) == from {
+                    continue;
+                }
+
+                for other in board.at(nx, ny).specimens() {
+                    if other.strength() > strength {
+                        threats += 1;
+                    } else if (nx, ny) == (x, y) {
+                        prey += other.energy;
+                    }
+                }
+            }
+        }
+
+        FOOD_ATTRACTION * food + PREY_ATTRACTION * prey - THREAT_AVOIDANCE * threats as f32
+    }
+}
+
+/// Energy spent on moving, on top of the metabolic loss every specimen pays
+/// each step regardless of what it does.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ActionCosts {
+    pub stay: f32,
+    pub orthogonal: f32,
+    pub diagonal: f32
+}
+
+impl Default for ActionCosts {
+    fn default() -> ActionCosts {
+        ActionCosts {
+            stay: 0.0,
+            orthogonal: 0.001,
+            diagonal: 0.0015
+        }
+    }
+}
+
+impl ActionCosts {
+    /// Parses "STAY,ORTHOGONAL,DIAGONAL".
+    pub fn parse(s: &str) -> Option<ActionCosts> {
+        let costs: Vec<f32> = match s.split(',').map(|c| c.trim().parse::<f32>()).collect() {
+            Ok(costs) => costs,
+            Err(_) => return None
+        };
+        if costs.len() != 3 || costs.iter().any(|&c| c < 0.0) {
+            return None;
+        }
+
+        Some(ActionCosts {
+            stay: costs[0],
+            orthogonal: costs[1],
+            diagonal: costs[2]
+        })
+    }
+
+    /// Cost of moving by `offset`, which is at most one cell on each axis.
+    pub fn cost(&self,
+                offset: (i64, i64)) -> f32 {
+        match (offset.0 != 0, offset.1 != 0) {
+            (false, false) => self.stay,
+            (true, true) => self.diagonal,
+            _ => self.orthogonal
+        }
+    }
+}
+
+/// exp() of every exponent, shifted so that the largest weight is 1. Huge
+/// exponents would otherwise overflow to infinity and make picks meaningless.
+pub fn exp_weights(exponents: &[f32]) -> Vec<f32> {
+    let max = exponents.iter().fold(::std::f32::NEG_INFINITY, |max, &exponent| max.max(exponent));
+    exponents.iter().map(|&exponent| (exponent - max).exp()).collect()
+}
+
+/// Picks one of `items` with probability proportional to its weight.
+pub fn pick_weighted<T: Copy>(items: &[T],
+                              weights: &[f32],
+                              rng: &mut StdRng) -> T {
+    let total = weights.iter().fold(0.0, |sum, w| sum + w);
+
+    let mut choice = rng.gen::<f32>() * total;
+    for (&item, &weight) in items.iter().zip(weights.iter()) {
+        if choice < weight {
+            return item;
+        }
+        choice -= weight;
+    }
+
+    *items.last().unwrap()
+}
+
+#[test]
+fn test_gradient_score() {
+    use simulation::Specimen;
+
+    let mut board = Board::new(4, 3, Field::Empty);
+    *board.at_mut(1, 1) = Field::Occupied(Specimen::new(1.0));
+    *board.at_mut(2, 1) = Field::Occupied(Specimen::new(0.5));
+    *board.at_mut(0, 0) = Field::Occupied(Specimen::new(2.0));
+    let mut food = Board::new(4, 3, 0.0);
+    *food.at_mut(1, 2) = 1.0;
+
+    let score = |x, y| MovementPolicy::Gradient.score(&board, Some(&food), (1, 1), x, y, 1.0);
+    assert_eq!(FOOD_ATTRACTION, score(1, 2));
+    assert_eq!(PREY_ATTRACTION * 0.5, score(2, 1));
+    assert_eq!(-THREAT_AVOIDANCE, score(0, 1));
+    assert_eq!(0.0, MovementPolicy::Random.score(&board, Some(&food), (1, 1), 1, 2, 1.0));
+}
+
+#[test]
+fn test_action_costs() {
+    let costs = ActionCosts::parse("0, 0.5,1").unwrap();
+    assert_eq!(0.0, costs.cost((0, 0)));
+    assert_eq!(0.5, costs.cost((0, -1)));
+    assert_eq!(1.0, costs.cost((1, -1)));
+
+    assert_eq!(None, ActionCosts::parse("0,1"));
+    assert_eq!(None, ActionCosts::parse("0,-1,1"));
+}
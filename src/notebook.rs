@@ -0,0 +1,123 @@
+//! Lab notebook for interactive sessions: a Markdown file to which the
+//! parameters the simulation currently runs with are appended on demand,
+//! each entry with a thumbnail of the board, so that the trail of tuning a
+//! simulation by hand isn't lost.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use offscreen::Image;
+use palette::CellColor;
+use simulation::Simulation;
+use symmetry::Region;
+use theme::Theme;
+
+pub struct Notebook {
+    path: PathBuf,
+    theme: Theme,
+    entries: usize
+}
+
+/// Markdown entry for a snapshot taken at `timestamp`.
+pub fn format_entry(timestamp: &str,
+                    iteration: usize,
+                    parameters: &[(String, String)],
+                    thumbnail: &str) -> String {
+    let mut entry = format!("## {}, iteration {}\n\n", timestamp, iteration);
+    entry.push_str("| parameter | value |\n|---|---|\n");
+    for &(ref name, ref value) in parameters {
+        entry.push_str(&format!("| {} | {} |\n", name, value.replace('|', "\\|")));
+    }
+    entry.push_str(&format!("\n![iteration {}]({})\n\n", iteration, thumbnail));
+    entry
+}
+
+impl Notebook {
+    pub fn new(path: &str,
+               theme: Theme) -> Notebook {
+        Notebook {
+            path: PathBuf::from(path),
+            theme: theme,
+            entries: 0
+        }
+    }
+
+    /// Entries appended so far.
+    pub fn entries(&self) -> usize {
+        self.entries
+    }
+
+    /// Thumbnail file name for a snapshot, next to the notebook: e.g.
+    /// `lab-20160102-030405-000123.ppm` for `lab.md`.
+    fn thumbnail_name(&self,
+                      timestamp: &str,
+                      iteration: usize) -> String {
+        let stem = self.path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("notebook");
+        let stamp: String = timestamp.chars().filter(|c| c.is_alphanumeric()).collect();
+        let (date, time) = stamp.split_at(stamp.len().min(8));
+        format!("{}-{}-{}-{:06}.ppm", stem, date, time, iteration)
+    }
+
+    /// Writes a thumbnail of the board, one pixel per cell, and appends an
+    /// entry with `parameters` linking to it. Returns the thumbnail path.
+    pub fn snapshot<T, S>(&mut self,
+                          simulation: &S,
+                          timestamp: &str,
+                          iteration: usize,
+                          parameters: &[(String, String)]) -> Result<PathBuf, String> where S: Simulation<T>, T: CellColor {
+        let name = self.thumbnail_name(timestamp, iteration);
+        let thumbnail = self.path.parent().unwrap_or(Path::new("")).join(&name);
+
+        let image = Image::render_themed(simulation, Region::whole(simulation.board()), 1, &self.theme);
+        try!(image.save(&thumbnail.to_string_lossy()));
+
+        let entry = format_entry(timestamp, iteration, parameters, &name);
+        try!(OpenOptions::new().create(true).append(true).open(&self.path)
+                               .and_then(|mut f| f.write_all(entry.as_bytes()))
+                               .map_err(|e| format!("{}: {}", self.path.display(), e)));
+        self.entries += 1;
+        Ok(thumbnail)
+    }
+}
+
+#[test]
+fn test_format_entry() {
+    let parameters = vec![("sim".to_string(), "ising".to_string()),
+                          ("stop-when".to_string(), "a|b".to_string())];
+    let entry = format_entry("2016-01-02 03:04:05", 7, &parameters, "lab.ppm");
+
+    assert_eq!("## 2016-01-02 03:04:05, iteration 7\n\n\
+                | parameter | value |\n|---|---|\n\
+                | sim | ising |\n\
+                | stop-when | a\\|b |\n\
+                \n![iteration 7](lab.ppm)\n\n", entry);
+}
+
+#[test]
+fn test_notebook_snapshot() {
+    use std::env;
+    use std::fs::{self, File};
+    use std::io::Read;
+    use board::Board;
+    use simulation::GameOfLife;
+
+    let dir = env::temp_dir().join("cell-test-notebook");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("lab.md");
+
+    let sim = GameOfLife::new(Board::new(3, 2, false));
+    let mut notebook = Notebook::new(path.to_str().unwrap(), Theme::default());
+    let thumbnail = notebook.snapshot(&sim, "2016-01-02 03:04:05", 7, &[]).unwrap();
+    notebook.snapshot(&sim, "2016-01-02 03:04:06", 8, &[]).unwrap();
+
+    assert_eq!(dir.join("lab-20160102-030405-000007.ppm"), thumbnail);
+    assert_eq!(2, notebook.entries());
+    let mut text = String::new();
+    File::open(&path).unwrap().read_to_string(&mut text).unwrap();
+    assert_eq!(2, text.matches("## 2016-01-02").count());
+    assert!(text.contains("](lab-20160102-030406-000008.ppm)"));
+    let image = Image::load(thumbnail.to_str().unwrap()).unwrap();
+    assert_eq!((3, 2), (image.width, image.height));
+}
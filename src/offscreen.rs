@@ -0,0 +1,393 @@
+//! Rendering simulations to an in-memory image, without a window. Used for
+//! visual regression tests comparing the output with reference images.
+
+use std::env;
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::{Read, Write};
+
+use board::Board;
+use palette::{CellColor, hue_to_color};
+use simulation::Simulation;
+use symmetry::Region;
+use theme::Theme;
+
+/// Set to write missing or mismatching reference images instead of
+/// failing the comparison.
+pub const UPDATE_REFERENCES_VAR: &'static str = "UPDATE_REFERENCES";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    /// RGB pixels, row by row.
+    pixels: Vec<[u8; 3]>
+}
+
+pub fn to_rgb(color: [f32; 4]) -> [u8; 3] {
+    let channel = |c: f32| (c.max(0.0).min(1.0) * 255.0).round() as u8;
+    [channel(color[0]), channel(color[1]), channel(color[2])]
+}
+
+impl Image {
+    pub fn new(width: usize,
+               height: usize,
+               color: [f32; 4]) -> Image {
+        Image {
+            width: width,
+            height: height,
+            pixels: vec![to_rgb(color); width * height]
+        }
+    }
+
+    /// Draws the board with `cell_size` pixels per cell side the same way
+    /// the window does: food under the cells, and agents as single pixels
+    /// colored by heading on top. Wind streamlines are not drawn.
+    pub fn render<T, S>(simulation: &S,
+                        cell_size: usize) -> Image where S: Simulation<T>, T: CellColor {
+        Image::render_region(simulation, Region::whole(simulation.board()), cell_size)
+    }
+
+    /// Like `render`, but draws only cells in `region`, which must lie on
+    /// the board.
+    pub fn render_region<T, S>(simulation: &S,
+                               region: Region,
+                               cell_size: usize) -> Image where S: Simulation<T>, T: CellColor {
+        Image::render_themed(simulation, region, cell_size, &Theme::default())
+    }
+
+    /// Like `render_region`, but with cell colors replaced according to
+    /// `theme`, and with its grid drawn over cells at least 3 pixels wide.
+    pub fn render_themed<T, S>(simulation: &S,
+                               region: Region,
+                               cell_size: usize,
+                               theme: &Theme) -> Image where S: Simulation<T>, T: CellColor {
+        let mut image = Image::render_board_region(simulation.board(), simulation.food(), region, cell_size, theme);
+
+        for agent in simulation.agents() {
+            let px = ((agent.position.0 - region.x as f64) * cell_size as f64).floor();
+            let py = ((agent.position.1 - region.y as f64) * cell_size as f64).floor();
+            let (px, py) = (px as i64, py as i64);
+            if px >= 0 && py >= 0 && (px as usize) < image.width && (py as usize) < image.height {
+                let (px, py) = (px as usize, py as usize);
+                let hue = agent.heading() / (2.0 * PI);
+                image.pixels[py * image.width + px] = to_rgb(hue_to_color(hue as f32));
+            }
+        }
+
+        image
+    }
+
+    /// Draws just the cells of `board`, with `food` under them.
+    pub fn render_board<T: CellColor>(board: &Board<T>,
+                                      food: Option<&Board<f32>>,
+                                      cell_size: usize) -> Image {
+        Image::render_board_region(board, food, Region::whole(board), cell_size, &Theme::default())
+    }
+
+    fn render_board_region<T: CellColor>(board: &Board<T>,
+                                         food: Option<&Board<f32>>,
+                                         region: Region,
+                                         cell_size: usize,
+                                         theme: &Theme) -> Image {
+        assert!(region.x + region.width <= board.width && region.y + region.height <= board.height);
+        let mut image = Image::new(region.width * cell_size, region.height * cell_size, [0.0, 0.0, 0.0, 1.0]);
+
+        for y in 0..region.height {
+            for x in 0..region.width {
+                let color = to_rgb(theme.cell_color(board, food, region.x + x, region.y + y));
+                for py in y * cell_size..(y + 1) * cell_size {
+                    for px in x * cell_size..(x + 1) * cell_size {
+                        image.pixels[py * image.width + px] = color;
+                    }
+                }
+            }
+        }
+
+        if let (Some(grid), true) = (theme.grid, cell_size >= 3) {
+            let grid = to_rgb(grid);
+            for py in 0..image.height {
+                for px in 0..image.width {
+                    if px % cell_size == cell_size - 1 || py % cell_size == cell_size - 1 {
+                        image.pixels[py * image.width + px] = grid;
+                    }
+                }
+            }
+        }
+
+        image
+    }
+
+    pub fn pixel(&self,
+                 x: usize,
+                 y: usize) -> [u8; 3] {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Largest difference of a single color channel, or None if the images
+    /// have different sizes.
+    pub fn max_difference(&self,
+                          other: &Image) -> Option<u8> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return None;
+        }
+
+        Some(self.pixels.iter().zip(other.pixels.iter())
+                 .flat_map(|(a, b)| (0..3).map(move |c| (a[c] as i32 - b[c] as i32).abs() as u8))
+                 .max()
+                 .unwrap_or(0))
+    }
+
+    /// Plain (ASCII) PPM, so that reference images can be diffed as text.
+    pub fn to_ppm(&self) -> String {
+        let mut ppm = format!("P3\n{} {}\n255\n", self.width, self.height);
+        for row in self.pixels.chunks(self.width) {
+            let values: Vec<String> = row.iter()
+                                         .map(|p| format!("{} {} {}", p[0], p[1], p[2]))
+                                         .collect();
+            ppm.push_str(&values.join("  "));
+            ppm.push('\n');
+        }
+        ppm
+    }
+
+    /// Binary PPM, compact enough to stream frames to other programs.
+    pub fn to_raw_ppm(&self) -> Vec<u8> {
+        let mut ppm = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        for pixel in self.pixels.iter() {
+            ppm.extend(pixel.iter().cloned());
+        }
+        ppm
+    }
+
+    pub fn from_ppm(text: &str) -> Result<Image, String> {
+        let mut tokens = text.lines()
+                             .filter(|line| !line.starts_with('#'))
+                             .flat_map(|line| line.split_whitespace());
+
+        if tokens.next() != Some("P3") {
+            return Err("not a plain PPM image".to_string());
+        }
+
+        let mut numbers = Vec::new();
+        for token in tokens {
+            numbers.push(try!(token.parse::<usize>().map_err(|_| format!("invalid number: {}", token))));
+        }
+        if numbers.len() < 3 || numbers[2] != 255 {
+            return Err("invalid PPM header, expected WIDTH HEIGHT 255".to_string());
+        }
+
+        let (width, height) = (numbers[0], numbers[1]);
+        let values = &numbers[3..];
+        if values.len() != width * height * 3 || values.iter().any(|&v| v > 255) {
+            return Err(format!("expected {} pixel values in range 0-255", width * height * 3));
+        }
+
+        Ok(Image {
+            width: width,
+            height: height,
+            pixels: values.chunks(3).map(|p| [p[0] as u8, p[1] as u8, p[2] as u8]).collect()
+        })
+    }
+
+    pub fn save(&self,
+                path: &str) -> Result<(), String> {
+        let mut file = try!(File::create(path).map_err(|e| format!("cannot create {}: {}", path, e)));
+        file.write_all(self.to_ppm().as_bytes())
+            .map_err(|e| format!("cannot write {}: {}", path, e))
+    }
+
+    /// PNG with 8-bit RGB pixels. The image data is stored without
+    /// compression, which keeps the encoder simple at the cost of size.
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut raw = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        for row in self.pixels.chunks(self.width) {
+            // no filter
+            raw.push(0);
+            for pixel in row {
+                raw.extend(pixel.iter().cloned());
+            }
+        }
+
+        let mut header = Vec::new();
+        push_u32(&mut header, self.width as u32);
+        push_u32(&mut header, self.height as u32);
+        // bit depth, truecolor, deflate, adaptive filtering, no interlace
+        header.extend([8, 2, 0, 0, 0].iter().cloned());
+
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+        push_chunk(&mut png, b"IHDR", &header);
+        push_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+        push_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    pub fn save_png(&self,
+                    path: &str) -> Result<(), String> {
+        let mut file = try!(File::create(path).map_err(|e| format!("cannot create {}: {}", path, e)));
+        file.write_all(&self.to_png())
+            .map_err(|e| format!("cannot write {}: {}", path, e))
+    }
+
+    pub fn load(path: &str) -> Result<Image, String> {
+        let mut contents = String::new();
+        try!(File::open(path).and_then(|mut f| f.read_to_string(&mut contents))
+                             .map_err(|e| format!("cannot read {}: {}", path, e)));
+
+        Image::from_ppm(&contents).map_err(|e| format!("{}: {}", path, e))
+    }
+}
+
+fn push_u32(out: &mut Vec<u8>,
+            value: u32) {
+    out.extend([(value >> 24) as u8, (value >> 16) as u8, (value >> 8) as u8, value as u8].iter().cloned());
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffffffffu32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb88320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn push_chunk(png: &mut Vec<u8>,
+              kind: &[u8],
+              data: &[u8]) {
+    push_u32(png, data.len() as u32);
+    let start = png.len();
+    png.extend(kind.iter().cloned());
+    png.extend(data.iter().cloned());
+    let crc = crc32(&png[start..]);
+    push_u32(png, crc);
+}
+
+/// zlib stream holding `data` in uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 65535;
+
+    let mut out = vec![0x78, 0x01];
+    let blocks: Vec<&[u8]> = if data.is_empty() { vec![data] } else { data.chunks(MAX_BLOCK).collect() };
+    for (idx, block) in blocks.iter().enumerate() {
+        let len = block.len() as u16;
+        out.push(if idx + 1 == blocks.len() { 1 } else { 0 });
+        out.extend([len as u8, (len >> 8) as u8, !len as u8, (!len >> 8) as u8].iter().cloned());
+        out.extend(block.iter().cloned());
+    }
+
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    push_u32(&mut out, (b << 16) | a);
+    out
+}
+
+/// Checks that no color channel of `image` differs from the reference
+/// image stored at `path` by more than `tolerance`. With UPDATE_REFERENCES
+/// set in the environment, the reference is overwritten instead.
+pub fn compare_with_reference(image: &Image,
+                              path: &str,
+                              tolerance: u8) -> Result<(), String> {
+    if env::var(UPDATE_REFERENCES_VAR).is_ok() {
+        return image.save(path);
+    }
+
+    let reference = try!(Image::load(path));
+    match image.max_difference(&reference) {
+        Some(diff) if diff <= tolerance => Ok(()),
+        Some(diff) => Err(format!("{}: rendered image differs by {} (tolerance {})", path, diff, tolerance)),
+        None => Err(format!("{}: rendered image is {}x{}, reference is {}x{}", path,
+                            image.width, image.height, reference.width, reference.height))
+    }
+}
+
+#[test]
+fn test_image_ppm_round_trip() {
+    let mut image = Image::new(2, 1, [1.0, 0.5, 0.0, 1.0]);
+    image.pixels[1] = [0, 0, 255];
+
+    assert_eq!(Ok(image.clone()), Image::from_ppm(&image.to_ppm()));
+    assert!(Image::from_ppm("P3\n2 1\n255\n0 0 0\n").is_err());
+}
+
+#[test]
+fn test_image_max_difference() {
+    let image = Image::new(2, 2, [0.0, 0.0, 0.0, 1.0]);
+    let mut other = image.clone();
+    other.pixels[3] = [0, 3, 1];
+
+    assert_eq!(Some(3), image.max_difference(&other));
+    assert_eq!(None, image.max_difference(&Image::new(2, 1, [0.0, 0.0, 0.0, 1.0])));
+}
+
+#[test]
+fn test_render_life_reference() {
+    use simulation::GameOfLife;
+
+    let mut board = Board::new(5, 5, false);
+    for &(x, y) in [(1, 2), (2, 2), (3, 2)].iter() {
+        *board.at_mut(x, y) = true;
+    }
+    let mut sim = GameOfLife::new(board);
+    sim.advance();
+
+    compare_with_reference(&Image::render(&sim, 2), "references/life-blinker.ppm", 0).unwrap();
+}
+
+#[test]
+fn test_render_falling_sand_reference() {
+    use falling_sand::{FallingSand, Material};
+    use rand::{SeedableRng, StdRng};
+
+    let mut board = Board::new(6, 4, Material::Empty);
+    *board.at_mut(1, 0) = Material::Sand;
+    *board.at_mut(4, 0) = Material::Water;
+    for x in 0..6 {
+        *board.at_mut(x, 3) = Material::Wall;
+    }
+    let mut sim = FallingSand::from_board(board, Box::new(StdRng::from_seed(&[0usize][..])));
+    sim.advance();
+
+    compare_with_reference(&Image::render(&sim, 3), "references/falling-sand.ppm", 2).unwrap();
+}
+
+#[test]
+fn test_render_themed() {
+    use simulation::GameOfLife;
+
+    let mut board = Board::new(2, 1, false);
+    *board.at_mut(1, 0) = true;
+    let sim = GameOfLife::new(board);
+    let theme = Theme {
+        grid: Some([1.0, 0.0, 0.0, 1.0]),
+        palette: vec![([0.0, 0.0, 0.0, 1.0], [0.0, 1.0, 0.0, 1.0])],
+        ..Theme::default()
+    };
+
+    let image = Image::render_themed(&sim, Region::whole(sim.board()), 3, &theme);
+    assert_eq!([0, 255, 0], image.pixel(0, 0));
+    assert_eq!([255, 255, 255], image.pixel(3, 0));
+    assert_eq!([255, 0, 0], image.pixel(2, 0));
+    assert_eq!([255, 0, 0], image.pixel(4, 2));
+}
+
+#[test]
+fn test_to_png() {
+    let mut image = Image::new(2, 1, [1.0, 0.0, 0.0, 1.0]);
+    image.pixels[1] = [0, 0, 255];
+    let png = image.to_png();
+
+    assert_eq!(&[0x89, b'P', b'N', b'G'], &png[..4]);
+    // IHDR with its CRC, as written by other encoders
+    assert_eq!(&[0, 0, 0, 13, b'I', b'H', b'D', b'R', 0, 0, 0, 2, 0, 0, 0, 1, 8, 2, 0, 0, 0, 0x7b, 0x40, 0xe8, 0xdd],
+               &png[8..33]);
+    // filter byte and both pixels, stored as is
+    let raw = [0, 255, 0, 0, 0, 0, 255];
+    assert!(png.windows(raw.len()).any(|window| window == raw));
+    assert_eq!(b"IEND", &png[png.len() - 8..png.len() - 4]);
+}
@@ -0,0 +1,125 @@
+use stats::Stats;
+
+/// How a headless run ended. Each outcome maps to a distinct process exit
+/// code so scripts can branch on it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    Completed,
+    Extinction,
+    StoppedByCriterion(String),
+    Error(String)
+}
+
+impl Outcome {
+    pub fn exit_code(&self) -> i32 {
+        match *self {
+            Outcome::Completed => 0,
+            Outcome::Extinction => 2,
+            Outcome::StoppedByCriterion(_) => 3,
+            Outcome::Error(_) => 4
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Outcome::Completed => "completed",
+            Outcome::Extinction => "extinction",
+            Outcome::StoppedByCriterion(_) => "stopped",
+            Outcome::Error(_) => "error"
+        }
+    }
+}
+
+pub struct RunReport {
+    pub outcome: Outcome,
+    pub simulation: &'static str,
+    pub seed: Option<usize>,
+    pub iterations: usize,
+    pub stats: Option<Stats>,
+    /// Number of warnings raised during the run.
+    pub warnings: usize
+}
+
+pub fn json_string(string: &str) -> String {
+    let mut result = String::with_capacity(string.len() + 2);
+    result.push('"');
+
+    for c in string.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c)
+        }
+    }
+
+    result.push('"');
+    result
+}
+
+impl RunReport {
+    /// Single-line JSON representation of the report.
+    pub fn to_json(&self) -> String {
+        let mut fields = vec![
+            format!("\"outcome\":{}", json_string(self.outcome.name())),
+            format!("\"exit_code\":{}", self.outcome.exit_code()),
+            format!("\"simulation\":{}", json_string(self.simulation)),
+            format!("\"seed\":{}", match self.seed {
+                Some(seed) => seed.to_string(),
+                None => "null".to_string()
+            }),
+            format!("\"iterations\":{}", self.iterations),
+            format!("\"warnings\":{}", self.warnings)
+        ];
+
+        match self.outcome {
+            Outcome::StoppedByCriterion(ref criterion) => {
+                fields.push(format!("\"criterion\":{}", json_string(criterion)))
+            },
+            Outcome::Error(ref message) => {
+                fields.push(format!("\"error\":{}", json_string(message)))
+            },
+            _ => ()
+        }
+
+        if let Some(ref stats) = self.stats {
+            let values: Vec<String> = stats.values.iter()
+                                           .map(|&(name, value)| {
+                                               let value = match value.as_f32() {
+                                                   x if x.is_finite() => value.to_string(),
+                                                   _ => "null".to_string()
+                                               };
+                                               format!("{}:{}", json_string(name), value)
+                                           })
+                                           .collect();
+            fields.push(format!("\"stats\":{{{}}}", values.join(",")));
+        }
+
+        format!("{{{}}}", fields.join(","))
+    }
+}
+
+#[test]
+fn test_json_string() {
+    assert_eq!("\"a\\\"b\\\\c\\nd\"", json_string("a\"b\\c\nd"));
+}
+
+#[test]
+fn test_report_to_json() {
+    let report = RunReport {
+        outcome: Outcome::StoppedByCriterion("infected<1".to_string()),
+        simulation: "epidemic",
+        seed: Some(7),
+        iterations: 42,
+        stats: Some(Stats::new(42).count("infected", 0).real("avg", 0.5)),
+        warnings: 2
+    };
+
+    assert_eq!(3, report.outcome.exit_code());
+    assert_eq!("{\"outcome\":\"stopped\",\"exit_code\":3,\"simulation\":\"epidemic\",\"seed\":7,\
+                \"iterations\":42,\"warnings\":2,\"criterion\":\"infected<1\",\"stats\":{\"infected\":0,\"avg\":0.5}}",
+               report.to_json());
+}
@@ -0,0 +1,220 @@
+//! Colors of cells, shared by the window and offscreen renderers.
+
+use ants::AntCell;
+use board::Board;
+use color_map::normalize;
+use cyclic::CyclicState;
+use epidemic::Health;
+use falling_sand::Material;
+use fluid::FluidCell;
+use forest_fire::Forest;
+use ising::Spin;
+use simulation::Field;
+use turmite::TurmiteCell;
+
+pub const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+pub const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+pub const DORMANT: [f32; 4] = [0.4, 0.4, 0.4, 1.0];
+
+/// Color used to draw a cell in a given state.
+pub trait CellColor {
+    fn color(&self) -> [f32; 4];
+
+    /// Value in range [0, 1] drawn with the `--palette` color map instead
+    /// of `color`, for cells holding a scalar.
+    fn heat(&self) -> Option<f32> {
+        None
+    }
+}
+
+impl CellColor for Field {
+    fn color(&self) -> [f32; 4] {
+        match *self {
+            Field::Empty => BLACK,
+            Field::Wall => [0.6, 0.45, 0.3, 1.0],
+            Field::Occupied(s) if s.dormant => DORMANT,
+            // species other than the first one get hues spread by the golden
+            // ratio, brighter with more energy
+            Field::Occupied(s) if s.species > 0 => {
+                let base = hue_to_color(s.species as f32 * 0.618);
+                let brightness = 0.3 + 0.7 * (s.energy / 2.0).max(0.0).min(1.0);
+                [base[0] * brightness, base[1] * brightness, base[2] * brightness, 1.0]
+            },
+            Field::Occupied(s) => {
+                match s.energy {
+                    x if x >= 4.0 => WHITE,
+                    x if x >= 3.0 => [1.0, 1.0, x - 3.0, 1.0],
+                    x if x >= 2.0 => [1.0, x - 2.0, 0.0, 1.0],
+                    x if x >= 1.0 => [x - 1.0, 0.0, 2.0 - x, 1.0],
+                    x => [0.0, 0.0, x, 1.0]
+                }
+            }
+        }
+    }
+
+    /// Energy of active specimens, in the range of the default colors.
+    fn heat(&self) -> Option<f32> {
+        match *self {
+            Field::Occupied(s) if !s.dormant => Some(normalize(s.energy, 0.0, 4.0)),
+            _ => None
+        }
+    }
+}
+
+impl CellColor for bool {
+    fn color(&self) -> [f32; 4] {
+        if *self { WHITE } else { BLACK }
+    }
+}
+
+impl CellColor for Health {
+    fn color(&self) -> [f32; 4] {
+        match *self {
+            Health::Susceptible => [0.1, 0.1, 0.1, 1.0],
+            Health::Exposed(_) => [1.0, 0.8, 0.0, 1.0],
+            Health::Infected(_) => [1.0, 0.0, 0.0, 1.0],
+            Health::Recovered => [0.2, 0.4, 1.0, 1.0]
+        }
+    }
+}
+
+impl CellColor for u32 {
+    fn color(&self) -> [f32; 4] {
+        match *self {
+            0 => BLACK,
+            1 => [0.0, 0.2, 0.6, 1.0],
+            2 => [0.0, 0.7, 0.8, 1.0],
+            3 => [1.0, 0.9, 0.2, 1.0],
+            _ => [1.0, 0.0, 0.0, 1.0]
+        }
+    }
+}
+
+/// Maps values in range [0, 1] to a black - blue - cyan - white gradient.
+impl CellColor for f32 {
+    fn color(&self) -> [f32; 4] {
+        let t = self.max(0.0).min(1.0) * 3.0;
+
+        match t {
+            t if t >= 2.0 => [t - 2.0, 1.0, 1.0, 1.0],
+            t if t >= 1.0 => [0.0, t - 1.0, 1.0, 1.0],
+            t => [0.0, 0.0, t, 1.0]
+        }
+    }
+
+    fn heat(&self) -> Option<f32> {
+        Some(self.max(0.0).min(1.0))
+    }
+}
+
+/// Converts a hue in range [0, 1) to a fully saturated color.
+pub fn hue_to_color(hue: f32) -> [f32; 4] {
+    let h = (hue - hue.floor()) * 6.0;
+    let x = 1.0 - (h % 2.0 - 1.0).abs();
+
+    match h as u32 {
+        0 => [1.0, x, 0.0, 1.0],
+        1 => [x, 1.0, 0.0, 1.0],
+        2 => [0.0, 1.0, x, 1.0],
+        3 => [0.0, x, 1.0, 1.0],
+        4 => [x, 0.0, 1.0, 1.0],
+        _ => [1.0, 0.0, x, 1.0]
+    }
+}
+
+impl CellColor for CyclicState {
+    fn color(&self) -> [f32; 4] {
+        hue_to_color(self.value as f32 / self.num_states as f32)
+    }
+}
+
+impl CellColor for Material {
+    fn color(&self) -> [f32; 4] {
+        match *self {
+            Material::Empty => BLACK,
+            Material::Sand => [0.9, 0.8, 0.4, 1.0],
+            Material::Water => [0.1, 0.3, 0.9, 1.0],
+            Material::Wall => [0.5, 0.5, 0.5, 1.0]
+        }
+    }
+}
+
+impl CellColor for Forest {
+    fn color(&self) -> [f32; 4] {
+        match *self {
+            Forest::Empty => [0.2, 0.15, 0.1, 1.0],
+            Forest::Tree => [0.1, 0.6, 0.1, 1.0],
+            Forest::Burning => [1.0, 0.5, 0.0, 1.0]
+        }
+    }
+}
+
+impl CellColor for Spin {
+    fn color(&self) -> [f32; 4] {
+        match *self {
+            Spin::Up => WHITE,
+            Spin::Down => BLACK
+        }
+    }
+}
+
+/// Density is drawn as a heatmap of range [0.9, 1.1].
+impl CellColor for FluidCell {
+    fn color(&self) -> [f32; 4] {
+        if self.obstacle {
+            [0.5, 0.5, 0.5, 1.0]
+        } else {
+            normalize(self.density(), 0.9, 1.1).color()
+        }
+    }
+
+    fn heat(&self) -> Option<f32> {
+        if self.obstacle { None } else { Some(normalize(self.density(), 0.9, 1.1)) }
+    }
+}
+
+/// Every turmite's trail has its own hue, brighter for higher colors.
+impl CellColor for TurmiteCell {
+    fn color(&self) -> [f32; 4] {
+        match self.painter {
+            Some((idx, count)) if self.color > 0 => {
+                let brightness = self.color as f32 / (self.num_colors - 1) as f32;
+                let hue = hue_to_color(idx as f32 / count as f32);
+                [hue[0] * brightness, hue[1] * brightness, hue[2] * brightness, 1.0]
+            },
+            _ => BLACK
+        }
+    }
+}
+
+/// Pheromone is drawn as a heatmap under food and the nest.
+impl CellColor for AntCell {
+    fn color(&self) -> [f32; 4] {
+        if self.nest {
+            [0.8, 0.4, 0.1, 1.0]
+        } else if self.food > 0 {
+            [0.1, 0.8, 0.1, 1.0]
+        } else {
+            normalize(self.pheromone, 0.0, 5.0).color()
+        }
+    }
+
+    fn heat(&self) -> Option<f32> {
+        if self.nest || self.food > 0 { None } else { Some(normalize(self.pheromone, 0.0, 5.0)) }
+    }
+}
+
+
+/// Color of the (x, y) cell, with food tinting empty cells green.
+pub fn cell_color<T: CellColor>(board: &Board<T>,
+                                food: Option<&Board<f32>>,
+                                x: usize,
+                                y: usize) -> [f32; 4] {
+    let color = board.at(x, y).color();
+
+    match food {
+        // food shows on empty cells only
+        Some(food) if *food.at(x, y) > 0.0 && color == BLACK => [0.0, 0.5 * *food.at(x, y), 0.0, 1.0],
+        _ => color
+    }
+}
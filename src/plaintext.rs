@@ -0,0 +1,42 @@
+//! Reader of the plaintext (.cells) format of the LifeWiki pattern
+//! collection: a row of text per row of cells, with `O` for live cells.
+
+use macrocell::{Pattern, MAX_SIDE};
+
+pub fn parse(text: &str) -> Result<Pattern, String> {
+    let mut cells = Vec::new();
+    let rows = text.lines()
+                   .map(|line| line.trim_right())
+                   .filter(|line| !line.starts_with('!'));
+
+    for (y, row) in rows.enumerate() {
+        for (x, c) in row.chars().enumerate() {
+            match c {
+                '.' => (),
+                'O' | '*' => cells.push((x, y)),
+                _ => return Err(format!("unexpected character in plaintext pattern: {}", c))
+            }
+            if x >= MAX_SIDE || y >= MAX_SIDE {
+                return Err(format!("pattern is larger than {}x{}", MAX_SIDE, MAX_SIDE));
+            }
+        }
+    }
+
+    Ok(Pattern::from_cells(cells))
+}
+
+#[test]
+fn test_plaintext_parse() {
+    let glider = parse("!Name: Glider\n!\n.O\n..O\nOOO\n").unwrap();
+    assert_eq!((3, 3), (glider.width, glider.height));
+    assert_eq!(vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)], glider.cells);
+
+    // blank lines are empty rows
+    let blocks = parse("OO\nOO\n\n\nOO\nOO").unwrap();
+    assert_eq!((2, 6), (blocks.width, blocks.height));
+}
+
+#[test]
+fn test_plaintext_rejects_invalid() {
+    assert!(parse("O.x\n").is_err());
+}
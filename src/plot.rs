@@ -0,0 +1,148 @@
+//! Population and mean energy over the last iterations, drawn as lines in a
+//! strip along the bottom of the window.
+
+use std::collections::VecDeque;
+
+/// Iterations plotted unless set otherwise.
+pub const DEFAULT_PLOT_LENGTH: usize = 500;
+/// Height of the strip, in pixels.
+pub const PLOT_HEIGHT: f64 = 120.0;
+/// Space between the strip and the edges of the window, in pixels.
+pub const PLOT_MARGIN: f64 = 8.0;
+const POPULATION_COLOR: [f32; 4] = [0.3, 0.9, 0.3, 1.0];
+const ENERGY_COLOR: [f32; 4] = [1.0, 0.8, 0.2, 1.0];
+
+/// Values of the last iterations, the oldest first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlotHistory {
+    length: usize,
+    /// Population and the mean energy of specimens, None for simulations
+    /// without specimens and when there are none.
+    samples: VecDeque<(f64, Option<f64>)>
+}
+
+impl PlotHistory {
+    /// History of the last `length` iterations, at least two.
+    pub fn new(length: usize) -> PlotHistory {
+        let length = ::std::cmp::max(length, 2);
+        PlotHistory {
+            length: length,
+            samples: VecDeque::with_capacity(length)
+        }
+    }
+
+    pub fn record(&mut self,
+                  population: usize,
+                  mean_energy: Option<f32>) {
+        if self.samples.len() == self.length {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((population as f64, mean_energy.map(|energy| energy as f64)));
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+/// A single time series as drawn.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Series {
+    /// Name and last value, drawn in `color` above the lines.
+    pub label: String,
+    pub color: [f32; 4],
+    /// Segments as [x1, y1, x2, y2] in the viewport.
+    pub lines: Vec<[f64; 4]>
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Plot {
+    /// Where the plot is drawn, as [x, y, width, height] in the viewport.
+    pub rect: [f64; 4],
+    pub series: Vec<Series>
+}
+
+impl Plot {
+    /// Plots `history` with the newest iteration on the right edge. Every
+    /// series is scaled on its own from 0 to its maximum, as population and
+    /// energy have nothing in common.
+    pub fn of(history: &PlotHistory,
+              viewport_size: [f64; 2]) -> Plot {
+        let rect = [PLOT_MARGIN,
+                    viewport_size[1] - PLOT_MARGIN - PLOT_HEIGHT,
+                    (viewport_size[0] - 2.0 * PLOT_MARGIN).max(0.0),
+                    PLOT_HEIGHT];
+        let population: Vec<Option<f64>> = history.samples.iter().map(|&(population, _)| Some(population)).collect();
+        let energy: Vec<Option<f64>> = history.samples.iter().map(|&(_, energy)| energy).collect();
+
+        let mut series = vec![Plot::series("population", POPULATION_COLOR, &population, history.length, rect)];
+        if energy.iter().any(Option::is_some) {
+            series.push(Plot::series("energy", ENERGY_COLOR, &energy, history.length, rect));
+        }
+
+        Plot {
+            rect: rect,
+            series: series
+        }
+    }
+
+    /// Lines between consecutive known values.
+    fn series(name: &str,
+              color: [f32; 4],
+              values: &[Option<f64>],
+              length: usize,
+              rect: [f64; 4]) -> Series {
+        let max = values.iter().filter_map(|&value| value).fold(0.0, f64::max);
+        let step = rect[2] / (length - 1) as f64;
+        let right = rect[0] + rect[2];
+        let point = |idx: usize, value: f64| {
+            let x = right - (values.len() - 1 - idx) as f64 * step;
+            let y = rect[1] + rect[3] - if max > 0.0 { value / max * rect[3] } else { 0.0 };
+            (x, y)
+        };
+
+        let lines = values.windows(2)
+                          .enumerate()
+                          .filter_map(|(idx, pair)| match (pair[0], pair[1]) {
+                              (Some(from), Some(to)) => {
+                                  let (from, to) = (point(idx, from), point(idx + 1, to));
+                                  Some([from.0, from.1, to.0, to.1])
+                              },
+                              _ => None
+                          })
+                          .collect();
+        let label = match values.last() {
+            Some(&Some(value)) => format!("{} {}", name, (value * 100.0).round() / 100.0),
+            _ => format!("{} -", name)
+        };
+
+        Series {
+            label: label,
+            color: color,
+            lines: lines
+        }
+    }
+}
+
+#[test]
+fn test_plot() {
+    let mut history = PlotHistory::new(3);
+    history.record(10, None);
+    history.record(20, Some(0.5));
+    history.record(40, Some(1.0));
+    history.record(20, Some(2.0));
+    let plot = Plot::of(&history, [216.0, 400.0]);
+
+    assert_eq!([8.0, 272.0, 200.0, 120.0], plot.rect);
+    assert_eq!(2, plot.series.len());
+    assert_eq!("population 20", plot.series[0].label);
+    assert_eq!(vec![[8.0, 332.0, 108.0, 272.0], [108.0, 272.0, 208.0, 332.0]], plot.series[0].lines);
+    assert_eq!("energy 2", plot.series[1].label);
+    assert_eq!(vec![[8.0, 362.0, 108.0, 332.0], [108.0, 332.0, 208.0, 272.0]], plot.series[1].lines);
+
+    history.clear();
+    history.record(5, None);
+    let plot = Plot::of(&history, [216.0, 400.0]);
+    assert_eq!(1, plot.series.len());
+    assert!(plot.series[0].lines.is_empty());
+}
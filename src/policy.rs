@@ -0,0 +1,146 @@
+//! Decisions of individual GoodEvil specimens made by Rust code, so that
+//! researchers can try out agents of their own against a small interface
+//! that doesn't change along with the engine internals. Policies are
+//! registered per species; specimens without one move according to the
+//! movement policy as usual.
+
+use std::sync::Arc;
+
+use rand::StdRng;
+
+/// Contents of a cell as seen by a specimen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Neighbor {
+    Empty,
+    Wall,
+    /// Outside of the board.
+    Edge,
+    Specimen {
+        energy: f32,
+        species: u8
+    }
+}
+
+/// What a specimen knows when deciding.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Senses {
+    pub position: (usize, usize),
+    pub board_size: (usize, usize),
+    pub energy: f32,
+    pub reputation: f32,
+    pub species: u8,
+    /// False during the cooldown after reproducing or being born, when
+    /// `Action::Reproduce` does nothing.
+    pub can_reproduce: bool,
+    /// Cells around the specimen, indexed by `[dy + 1][dx + 1]`. The middle
+    /// one is the specimen itself.
+    pub neighborhood: [[Neighbor; 3]; 3],
+    /// Food on the specimen's cell, 0 without a food layer.
+    pub food: f32
+}
+
+impl Senses {
+    /// Contents of the cell at offset (dx, dy), each in [-1, 1].
+    pub fn neighbor(&self,
+                    dx: i64,
+                    dy: i64) -> Neighbor {
+        self.neighborhood[(dy + 1) as usize][(dx + 1) as usize]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    /// Moves by the offset, each coordinate clamped to [-1, 1]. Moves into
+    /// walls, or off a closed board, leave the specimen in place.
+    Move(i64, i64),
+    Stay,
+    /// Stays in place and splits off a child onto an empty neighboring
+    /// cell once everyone moved, like with reproduction enabled, but
+    /// regardless of the energy threshold.
+    Reproduce,
+    /// Stays in place and gives up to `amount` of energy to the specimen on
+    /// the cell at offset (dx, dy) once everyone moved, if there is one.
+    Share {
+        dx: i64,
+        dy: i64,
+        amount: f32
+    }
+}
+
+/// Decides what a specimen does in a step. The generator is the
+/// simulation's own, so that runs stay reproducible from their seed.
+pub trait Policy: Send + Sync {
+    fn decide(&self,
+              senses: &Senses,
+              rng: &mut StdRng) -> Action;
+}
+
+/// Policies of species, indexed by `Specimen::species`.
+#[derive(Clone, Default)]
+pub struct Policies {
+    by_species: Vec<Option<Arc<Policy>>>
+}
+
+impl Policies {
+    pub fn new() -> Policies {
+        Policies::default()
+    }
+
+    /// Makes specimens of `species` decide with `policy`, replacing the
+    /// one registered before.
+    pub fn register(&mut self,
+                    species: u8,
+                    policy: Arc<Policy>) {
+        let idx = species as usize;
+        while self.by_species.len() <= idx {
+            self.by_species.push(None);
+        }
+        self.by_species[idx] = Some(policy);
+    }
+
+    pub fn get(&self,
+               species: u8) -> Option<&Arc<Policy>> {
+        self.by_species.get(species as usize).and_then(|policy| policy.as_ref())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_species.iter().all(|policy| policy.is_none())
+    }
+}
+
+#[cfg(test)]
+struct Always(Action);
+
+#[cfg(test)]
+impl Policy for Always {
+    fn decide(&self,
+              _senses: &Senses,
+              _rng: &mut StdRng) -> Action {
+        self.0
+    }
+}
+
+#[test]
+fn test_policies_register() {
+    let mut policies = Policies::new();
+    assert!(policies.is_empty());
+
+    policies.register(2, Arc::new(Always(Action::Stay)));
+    assert!(!policies.is_empty());
+    assert!(policies.get(0).is_none());
+    assert!(policies.get(3).is_none());
+
+    policies.register(2, Arc::new(Always(Action::Reproduce)));
+    let senses = Senses {
+        position: (0, 0),
+        board_size: (1, 1),
+        energy: 1.0,
+        reputation: 0.0,
+        species: 2,
+        can_reproduce: true,
+        neighborhood: [[Neighbor::Edge; 3]; 3],
+        food: 0.0
+    };
+    let mut rng = ::rand::SeedableRng::from_seed(&[0usize][..]);
+    assert_eq!(Action::Reproduce, policies.get(2).unwrap().decide(&senses, &mut rng));
+}
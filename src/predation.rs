@@ -0,0 +1,74 @@
+use simulation::Specimen;
+
+/// Settings of predation in GoodEvil collisions: a specimen with enough
+/// more energy than the others eats them instead of sharing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PredationConfig {
+    /// How much more energy the predator needs to have than its prey.
+    pub margin: f32,
+    /// Fraction of the prey's energy absorbed by the predator; the rest is
+    /// lost.
+    pub efficiency: f32
+}
+
+impl Default for PredationConfig {
+    fn default() -> PredationConfig {
+        PredationConfig {
+            margin: 0.5,
+            efficiency: 0.5
+        }
+    }
+}
+
+impl PredationConfig {
+    /// The specimen with the most energy eats everyone weaker by more than
+    /// the margin. Returns the specimens in their original order, with None
+    /// in place of the eaten ones, and the number of specimens eaten.
+    pub fn hunt(&self,
+                specimens: &[Specimen]) -> (Vec<Option<Specimen>>, usize) {
+        let mut predator = 0;
+        for (idx, s) in specimens.iter().enumerate() {
+            if s.energy > specimens[predator].energy {
+                predator = idx;
+            }
+        }
+
+        let threshold = match specimens.get(predator) {
+            Some(s) => s.energy - self.margin,
+            None => return (Vec::new(), 0)
+        };
+        let is_prey = |idx: usize, s: &Specimen| idx != predator && s.energy < threshold;
+
+        let eaten = specimens.iter()
+                             .enumerate()
+                             .filter(|&(idx, s)| is_prey(idx, s))
+                             .fold(0.0, |sum, (_, s)| sum + s.energy);
+        let survivors: Vec<Option<Specimen>> = specimens.iter()
+                                                        .enumerate()
+                                                        .map(|(idx, s)| if idx == predator {
+                                                            Some(Specimen { energy: s.energy + eaten * self.efficiency, ..*s })
+                                                        } else if is_prey(idx, s) {
+                                                            None
+                                                        } else {
+                                                            Some(*s)
+                                                        })
+                                                        .collect();
+
+        let kills = survivors.iter().filter(|s| s.is_none()).count();
+        (survivors, kills)
+    }
+}
+
+#[test]
+fn test_predation_hunt() {
+    let cfg = PredationConfig { margin: 0.5, efficiency: 0.5 };
+    let specimens = [Specimen::new(1.0), Specimen::new(2.0), Specimen::new(1.75), Specimen::new(0.5)];
+
+    let (survivors, kills) = cfg.hunt(&specimens);
+    assert_eq!(2, kills);
+    assert_eq!(vec![None, Some(Specimen::new(2.75)), Some(Specimen::new(1.75)), None], survivors);
+
+    let (survivors, kills) = cfg.hunt(&[Specimen::new(1.0), Specimen::new(1.25)]);
+    assert_eq!(0, kills);
+    assert_eq!(2, survivors.len());
+}
@@ -0,0 +1,270 @@
+//! Counts of occupied cells kept in a quadtree, for answering questions
+//! about where things are on sparse boards without scanning them: how many
+//! cells are occupied in a rectangle, the bounding box of all of them, and
+//! where they're the densest.
+//!
+//! A cell is occupied if it isn't drawn black, the same way food only
+//! shows on black cells.
+
+use std::cmp::{max, min};
+
+use board::Board;
+use palette::{BLACK, CellColor};
+use symmetry::Region;
+
+pub fn is_occupied<T: CellColor>(cell: &T) -> bool {
+    cell.color() != BLACK
+}
+
+pub struct Quadtree {
+    width: usize,
+    height: usize,
+    /// Depth of the leaves, which are single cells.
+    depth: usize,
+    /// Counts of occupied cells in nodes, level by level from the root.
+    /// Level `l` is a 2^l by 2^l grid, row by row.
+    levels: Vec<Vec<u32>>
+}
+
+impl Quadtree {
+    pub fn new(width: usize,
+               height: usize) -> Quadtree {
+        let mut depth = 0;
+        while (1 << depth) < width || (1 << depth) < height {
+            depth += 1;
+        }
+
+        Quadtree {
+            width: width,
+            height: height,
+            depth: depth,
+            levels: (0..depth + 1).map(|level| vec![0; 1 << (2 * level)]).collect()
+        }
+    }
+
+    pub fn of_board<T: CellColor>(board: &Board<T>) -> Quadtree {
+        let mut tree = Quadtree::new(board.width, board.height);
+        tree.sync(board);
+        tree
+    }
+
+    /// Updates the tree to match `board`. Every cell is looked at, but only
+    /// the changed ones touch the tree.
+    pub fn sync<T: CellColor>(&mut self,
+                              board: &Board<T>) {
+        assert!(board.width == self.width && board.height == self.height);
+        for (x, y) in board.indices() {
+            self.set(x, y, is_occupied(board.at(x, y)));
+        }
+    }
+
+    pub fn set(&mut self,
+               x: usize,
+               y: usize,
+               occupied: bool) {
+        let depth = self.depth;
+        let leaf = y * (1 << depth) + x;
+        if (self.levels[depth][leaf] > 0) == occupied {
+            return;
+        }
+
+        for level in 0..depth + 1 {
+            let shift = depth - level;
+            let idx = (y >> shift) * (1 << level) + (x >> shift);
+            if occupied {
+                self.levels[level][idx] += 1;
+            } else {
+                self.levels[level][idx] -= 1;
+            }
+        }
+    }
+
+    /// Number of occupied cells on the whole board.
+    pub fn count(&self) -> usize {
+        self.levels[0][0] as usize
+    }
+
+    /// Part of the board covered by the node at (`col`, `row`) of `level`,
+    /// clipped to the board.
+    fn node_region(&self,
+                   level: usize,
+                   col: usize,
+                   row: usize) -> Region {
+        let size = 1 << (self.depth - level);
+        let (x, y) = (col * size, row * size);
+        Region {
+            x: x,
+            y: y,
+            width: if x < self.width { min(size, self.width - x) } else { 0 },
+            height: if y < self.height { min(size, self.height - y) } else { 0 }
+        }
+    }
+
+    fn node_count(&self,
+                  level: usize,
+                  col: usize,
+                  row: usize) -> u32 {
+        self.levels[level][row * (1 << level) + col]
+    }
+
+    fn children(level: usize,
+                col: usize,
+                row: usize) -> [(usize, usize, usize); 4] {
+        [(level + 1, col * 2, row * 2),
+         (level + 1, col * 2 + 1, row * 2),
+         (level + 1, col * 2, row * 2 + 1),
+         (level + 1, col * 2 + 1, row * 2 + 1)]
+    }
+
+    /// Number of occupied cells in `region`.
+    pub fn count_in(&self,
+                    region: Region) -> usize {
+        self.count_in_node(0, 0, 0, region)
+    }
+
+    fn count_in_node(&self,
+                     level: usize,
+                     col: usize,
+                     row: usize,
+                     region: Region) -> usize {
+        let node = self.node_region(level, col, row);
+        let count = self.node_count(level, col, row) as usize;
+        if count == 0 || !overlaps(node, region) {
+            0
+        } else if contains(region, node) {
+            count
+        } else {
+            Quadtree::children(level, col, row).iter()
+                                              .map(|&(level, col, row)| self.count_in_node(level, col, row, region))
+                                              .fold(0, |sum, count| sum + count)
+        }
+    }
+
+    /// Smallest region containing all occupied cells.
+    pub fn bounding_box(&self) -> Option<Region> {
+        let mut bounds = None;
+        self.extend_bounds(0, 0, 0, &mut bounds);
+        bounds.map(|(min_x, min_y, max_x, max_y)| Region {
+            x: min_x,
+            y: min_y,
+            width: max_x - min_x + 1,
+            height: max_y - min_y + 1
+        })
+    }
+
+    fn extend_bounds(&self,
+                     level: usize,
+                     col: usize,
+                     row: usize,
+                     bounds: &mut Option<(usize, usize, usize, usize)>) {
+        if self.node_count(level, col, row) == 0 {
+            return;
+        }
+        let node = self.node_region(level, col, row);
+        if let Some((min_x, min_y, max_x, max_y)) = *bounds {
+            // nothing inside can extend the bounds any further
+            if node.x >= min_x && node.y >= min_y
+                    && node.x + node.width <= max_x + 1 && node.y + node.height <= max_y + 1 {
+                return;
+            }
+        }
+
+        if level == self.depth {
+            *bounds = Some(match *bounds {
+                Some((min_x, min_y, max_x, max_y)) => (min(min_x, node.x), min(min_y, node.y),
+                                                       max(max_x, node.x), max(max_y, node.y)),
+                None => (node.x, node.y, node.x, node.y)
+            });
+            return;
+        }
+        for &(level, col, row) in Quadtree::children(level, col, row).iter() {
+            self.extend_bounds(level, col, row, bounds);
+        }
+    }
+
+    /// Square of at least `size` cells with the most occupied cells in it,
+    /// clipped to the board. It's found greedily, by descending into the
+    /// fullest quarter of the board, then the fullest quarter of that and
+    /// so on, so it's only aligned to powers of two and may miss clusters
+    /// split between quarters. None if nothing is occupied.
+    pub fn densest(&self,
+                   size: usize) -> Option<Region> {
+        if self.count() == 0 {
+            return None;
+        }
+
+        let (mut level, mut col, mut row) = (0, 0, 0);
+        while level < self.depth && (1 << (self.depth - level - 1)) >= size {
+            let children = Quadtree::children(level, col, row);
+            let mut fullest = children[0];
+            for &child in children[1..].iter() {
+                if self.node_count(child.0, child.1, child.2) > self.node_count(fullest.0, fullest.1, fullest.2) {
+                    fullest = child;
+                }
+            }
+            level = fullest.0;
+            col = fullest.1;
+            row = fullest.2;
+        }
+        Some(self.node_region(level, col, row))
+    }
+}
+
+fn overlaps(a: Region,
+            b: Region) -> bool {
+    a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+}
+
+fn contains(outer: Region,
+            inner: Region) -> bool {
+    inner.x >= outer.x && inner.y >= outer.y
+        && inner.x + inner.width <= outer.x + outer.width
+        && inner.y + inner.height <= outer.y + outer.height
+}
+
+#[cfg(test)]
+fn region(x: usize,
+          y: usize,
+          width: usize,
+          height: usize) -> Region {
+    Region { x: x, y: y, width: width, height: height }
+}
+
+#[test]
+fn test_quadtree_counts() {
+    let mut board = Board::new(5, 3, false);
+    for &(x, y) in [(0, 0), (3, 1), (4, 2), (2, 2)].iter() {
+        *board.at_mut(x, y) = true;
+    }
+    let mut tree = Quadtree::of_board(&board);
+
+    assert_eq!(4, tree.count());
+    assert_eq!(3, tree.count_in(region(2, 1, 3, 2)));
+    assert_eq!(1, tree.count_in(region(0, 0, 1, 1)));
+    assert_eq!(0, tree.count_in(region(1, 0, 1, 3)));
+    assert_eq!(Some(region(0, 0, 5, 3)), tree.bounding_box());
+
+    *board.at_mut(0, 0) = false;
+    tree.sync(&board);
+    assert_eq!(3, tree.count());
+    assert_eq!(Some(region(2, 1, 3, 2)), tree.bounding_box());
+
+    tree.set(2, 2, false);
+    tree.set(3, 1, false);
+    tree.set(4, 2, false);
+    assert_eq!(None, tree.bounding_box());
+}
+
+#[test]
+fn test_quadtree_densest() {
+    let mut board = Board::new(16, 16, false);
+    for &(x, y) in [(1, 1), (12, 9), (13, 9), (12, 10), (9, 14)].iter() {
+        *board.at_mut(x, y) = true;
+    }
+    let tree = Quadtree::of_board(&board);
+
+    assert_eq!(Some(region(12, 8, 4, 4)), tree.densest(4));
+    assert_eq!(Some(region(12, 8, 4, 4)), tree.densest(3));
+    assert_eq!(Some(region(8, 8, 8, 8)), tree.densest(5));
+    assert_eq!(None, Quadtree::new(4, 4).densest(2));
+}
@@ -0,0 +1,295 @@
+//! Writing frames of a running simulation to image files, an animated GIF
+//! or a video encoded by ffmpeg.
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+
+use gif::GifWriter;
+use offscreen::Image;
+use palette::CellColor;
+use simulation::Simulation;
+use speed_ramp::SpeedRamp;
+use symmetry::Region;
+use theme::Theme;
+
+/// Where recorded frames go. Files and processes are only created with
+/// the first frame, when its size is known.
+enum Output {
+    /// PPM files in a directory.
+    Frames(PathBuf),
+    Gif(PathBuf, Option<GifWriter<BufWriter<File>>>),
+    /// Any format ffmpeg can write, fed with PPM frames through its
+    /// standard input.
+    Ffmpeg(PathBuf, Option<Child>)
+}
+
+/// Writes an image of the board, or just a region of it, after every step.
+/// Frames written to a directory are PPM files numbered from 0:
+/// `frame-00000.ppm`, `frame-00001.ppm`...
+///
+/// With a speed ramp, frames are meant to be played at a constant rate, so
+/// steps played slower are written several times and ones played faster
+/// are partly skipped.
+///
+/// A paused recorder skips steps, so that a video only shows the parts of
+/// a run between pressing start and stop.
+pub struct Recorder {
+    output: Output,
+    /// Recorded part of the board, the whole board if not set.
+    region: Option<Region>,
+    /// Pixels per cell side.
+    cell_size: usize,
+    speed_ramp: Option<SpeedRamp>,
+    theme: Theme,
+    /// Playback rate of GIFs and videos.
+    fps: f64,
+    paused: bool,
+    steps: usize,
+    /// Frames owed for steps recorded so far, minus frames written.
+    frames_due: f64,
+    frames: usize
+}
+
+impl Recorder {
+    pub fn new(dir: &str,
+               region: Option<Region>,
+               cell_size: usize) -> Recorder {
+        Recorder::with_output(Output::Frames(PathBuf::from(dir)), region, cell_size, 1.0)
+    }
+
+    /// Records an animated GIF if `path` ends with `.gif`, or any other
+    /// video format by piping frames to ffmpeg.
+    pub fn video(path: &str,
+                 region: Option<Region>,
+                 cell_size: usize,
+                 fps: f64) -> Recorder {
+        let output = if path.to_lowercase().ends_with(".gif") {
+            Output::Gif(PathBuf::from(path), None)
+        } else {
+            Output::Ffmpeg(PathBuf::from(path), None)
+        };
+        Recorder::with_output(output, region, cell_size, fps)
+    }
+
+    fn with_output(output: Output,
+                   region: Option<Region>,
+                   cell_size: usize,
+                   fps: f64) -> Recorder {
+        assert!(cell_size >= 1);
+        assert!(fps > 0.0);
+
+        Recorder {
+            output: output,
+            region: region,
+            cell_size: cell_size,
+            speed_ramp: None,
+            theme: Theme::default(),
+            fps: fps,
+            paused: false,
+            steps: 0,
+            frames_due: 0.0,
+            frames: 0
+        }
+    }
+
+    pub fn with_speed_ramp(self,
+                           speed_ramp: SpeedRamp) -> Recorder {
+        Recorder {
+            speed_ramp: Some(speed_ramp),
+            ..self
+        }
+    }
+
+    pub fn with_theme(self,
+                      theme: Theme) -> Recorder {
+        Recorder {
+            theme: theme,
+            ..self
+        }
+    }
+
+    pub fn with_paused(self,
+                       paused: bool) -> Recorder {
+        Recorder {
+            paused: paused,
+            ..self
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self,
+                      paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Writes frames due after the next step, creating the output if
+    /// needed.
+    pub fn record<T, S>(&mut self,
+                        simulation: &S) -> Result<(), String> where S: Simulation<T>, T: CellColor {
+        self.steps += 1;
+        if self.paused {
+            return Ok(());
+        }
+        self.frames_due += match self.speed_ramp {
+            Some(ref ramp) => 1.0 / ramp.speed_at(self.steps),
+            None => 1.0
+        };
+        if self.frames_due < 1.0 {
+            return Ok(());
+        }
+
+        let region = self.region.unwrap_or_else(|| Region::whole(simulation.board()));
+        let image = Image::render_themed(simulation, region, self.cell_size, &self.theme);
+
+        while self.frames_due >= 1.0 {
+            try!(self.write_frame(&image));
+            self.frames += 1;
+            self.frames_due -= 1.0;
+        }
+        Ok(())
+    }
+
+    fn write_frame(&mut self,
+                   image: &Image) -> Result<(), String> {
+        match self.output {
+            Output::Frames(ref dir) => {
+                try!(fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e)));
+                let path = dir.join(format!("frame-{:05}.ppm", self.frames));
+                File::create(&path).and_then(|mut f| f.write_all(image.to_ppm().as_bytes()))
+                                   .map_err(|e| format!("{}: {}", path.display(), e))
+            },
+            Output::Gif(ref path, ref mut writer) => {
+                if writer.is_none() {
+                    let delay = (100.0 / self.fps).round().max(1.0) as u16;
+                    *writer = Some(try!(File::create(path).and_then(|file| {
+                                            GifWriter::new(BufWriter::new(file), image.width, image.height, delay)
+                                        })
+                                        .map_err(|e| format!("{}: {}", path.display(), e))));
+                }
+                writer.as_mut().unwrap().add_frame(image).map_err(|e| format!("{}: {}", path.display(), e))
+            },
+            Output::Ffmpeg(ref path, ref mut child) => {
+                if child.is_none() {
+                    *child = Some(try!(Command::new("ffmpeg").args(&["-y", "-loglevel", "error",
+                                                                     "-f", "image2pipe", "-c:v", "ppm",
+                                                                     "-framerate", &self.fps.to_string(),
+                                                                     "-i", "-"])
+                                                             .arg(path)
+                                                             .stdin(Stdio::piped())
+                                                             .spawn()
+                                                             .map_err(|e| format!("cannot start ffmpeg: {}", e))));
+                }
+                let stdin = child.as_mut().and_then(|child| child.stdin.as_mut()).unwrap();
+                stdin.write_all(&image.to_raw_ppm()).map_err(|e| format!("{}: ffmpeg: {}", path.display(), e))
+            }
+        }
+    }
+
+    /// Completes a GIF or video. Frames written to a directory need no
+    /// finishing.
+    pub fn finish(&mut self) -> Result<(), String> {
+        match self.output {
+            Output::Frames(_) => Ok(()),
+            Output::Gif(ref path, ref mut writer) => match writer.take() {
+                Some(writer) => writer.finish().map(|_| ()).map_err(|e| format!("{}: {}", path.display(), e)),
+                None => Ok(())
+            },
+            Output::Ffmpeg(ref path, ref mut child) => match child.take() {
+                Some(mut child) => {
+                    // closing the input lets ffmpeg finish encoding
+                    drop(child.stdin.take());
+                    match child.wait() {
+                        Ok(ref status) if status.success() => Ok(()),
+                        Ok(status) => Err(format!("{}: ffmpeg exited with {}", path.display(), status)),
+                        Err(e) => Err(format!("{}: ffmpeg: {}", path.display(), e))
+                    }
+                },
+                None => Ok(())
+            }
+        }
+    }
+
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+}
+
+#[test]
+fn test_recorder_writes_region_frames() {
+    use std::env;
+    use std::io::Read;
+    use simulation::GameOfLife;
+    use demo::board_from_pattern;
+
+    let dir = env::temp_dir().join("cell-test-recording");
+    let board = board_from_pattern((8, 8), &["O.", ".O"], (2, 2), true, false);
+    let simulation = GameOfLife::new(board);
+    let mut recorder = Recorder::new(dir.to_str().unwrap(),
+                                     Some(Region { x: 2, y: 2, width: 2, height: 3 }), 2);
+
+    recorder.record(&simulation).unwrap();
+    recorder.record(&simulation).unwrap();
+    assert_eq!(2, recorder.frames());
+
+    let mut contents = String::new();
+    File::open(dir.join("frame-00001.ppm")).unwrap().read_to_string(&mut contents).unwrap();
+    let image = Image::from_ppm(&contents).unwrap();
+    assert_eq!((4, 6), (image.width, image.height));
+    assert_eq!(Image::render_region(&simulation, Region { x: 2, y: 2, width: 2, height: 3 }, 2), image);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_recorder_follows_speed_ramp() {
+    use std::env;
+    use simulation::GameOfLife;
+    use board::Board;
+
+    let dir = env::temp_dir().join("cell-test-recording-ramp");
+    let simulation = GameOfLife::new(Board::new(2, 2, false));
+    let mut recorder = Recorder::new(dir.to_str().unwrap(), None, 1)
+                           .with_speed_ramp(SpeedRamp::parse("0:0.5,2:0.5,3:2").unwrap());
+
+    // two steps at half speed, then one frame per two steps
+    let frames: Vec<usize> = (0..6).map(|_| {
+        recorder.record(&simulation).unwrap();
+        recorder.frames()
+    }).collect();
+    assert_eq!(vec![2, 4, 4, 5, 5, 6], frames);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_recorder_writes_gif_while_not_paused() {
+    use std::env;
+    use std::io::Read;
+    use simulation::GameOfLife;
+    use board::Board;
+
+    let path = env::temp_dir().join("cell-test-recording.gif");
+    let simulation = GameOfLife::new(Board::new(4, 3, false));
+    let mut recorder = Recorder::video(path.to_str().unwrap(), None, 2, 10.0);
+
+    recorder.record(&simulation).unwrap();
+    recorder.set_paused(true);
+    recorder.record(&simulation).unwrap();
+    recorder.set_paused(false);
+    recorder.record(&simulation).unwrap();
+    recorder.finish().unwrap();
+    assert_eq!(2, recorder.frames());
+
+    let mut gif = Vec::new();
+    File::open(&path).unwrap().read_to_end(&mut gif).unwrap();
+    assert_eq!(b"GIF89a", &gif[..6]);
+    assert_eq!(&[8, 0, 6, 0], &gif[6..10]);
+    assert_eq!(Some(&0x3b), gif.last());
+
+    fs::remove_file(&path).unwrap();
+}
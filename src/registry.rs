@@ -0,0 +1,110 @@
+//! Simulations registered by name, so that they can be built from a config
+//! without matching over every kind of simulation. The binary registers the
+//! built-in ones; embedders can add their own next to them.
+
+use rand::StdRng;
+
+use palette::CellColor;
+use simulation::Simulation;
+
+/// Receives freshly built simulations. Simulations differ in their cell
+/// types, so rather than returning them type-erased, constructors hand them
+/// over to a launcher, which keeps them statically typed.
+pub trait Launcher {
+    fn launch<T, S>(&mut self,
+                    simulation: S) where S: Simulation<T> + Clone + Send + 'static,
+                                         T: CellColor + Clone + PartialEq + Send + Sync + 'static;
+}
+
+/// Builds a simulation of the given width and height from config `C`,
+/// using the given random number generator, and passes it to `launcher`.
+pub type Constructor<C, L> = fn(usize, usize, &C, Box<StdRng>, &mut L);
+
+pub struct SimulationFactory<C, L> {
+    constructors: Vec<(&'static str, Constructor<C, L>)>
+}
+
+impl<C, L: Launcher> SimulationFactory<C, L> {
+    pub fn new() -> SimulationFactory<C, L> {
+        SimulationFactory {
+            constructors: Vec::new()
+        }
+    }
+
+    /// Registers `constructor` under `name`, replacing any registered
+    /// before.
+    pub fn register(&mut self,
+                    name: &'static str,
+                    constructor: Constructor<C, L>) {
+        self.constructors.retain(|&(registered, _)| registered != name);
+        self.constructors.push((name, constructor));
+    }
+
+    /// Names of registered simulations, in order of registration.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.constructors.iter().map(|&(name, _)| name).collect()
+    }
+
+    pub fn build(&self,
+                 name: &str,
+                 width: usize,
+                 height: usize,
+                 cfg: &C,
+                 rng: Box<StdRng>,
+                 launcher: &mut L) -> Result<(), String> {
+        match self.constructors.iter().find(|&&(registered, _)| registered == name) {
+            Some(&(_, constructor)) => {
+                constructor(width, height, cfg, rng, launcher);
+                Ok(())
+            },
+            None => Err(format!("unknown simulation: {}", name))
+        }
+    }
+}
+
+#[cfg(test)]
+struct AliveCounter {
+    alive: Option<usize>
+}
+
+#[cfg(test)]
+impl Launcher for AliveCounter {
+    fn launch<T, S>(&mut self,
+                    mut simulation: S) where S: Simulation<T> + Clone + Send + 'static,
+                                             T: CellColor + Clone + PartialEq + Send + Sync + 'static {
+        use simulation::GameOfLife;
+
+        simulation.advance();
+        // only Life is known to keep track of alive cells
+        if simulation.as_any().downcast_ref::<GameOfLife>().is_some() {
+            self.alive = simulation.stats().get("alive").map(|alive| alive.as_f32() as usize);
+        }
+    }
+}
+
+#[test]
+fn test_simulation_factory() {
+    use board::Board;
+    use rand::SeedableRng;
+    use sandpile::{Sandpile, SandpileConfig};
+    use simulation::GameOfLife;
+
+    let mut factory: SimulationFactory<usize, AliveCounter> = SimulationFactory::new();
+    factory.register("life", |width, height, _, _, launcher| {
+        launcher.launch(GameOfLife::new(Board::new(width, height, true)))
+    });
+    factory.register("sandpile", |width, height, &drop_rate, rng, launcher| {
+        launcher.launch(Sandpile::new(width, height, SandpileConfig { drop_rate: drop_rate as f32 }, rng))
+    });
+    assert_eq!(vec!["life", "sandpile"], factory.names());
+
+    let rng = || Box::new(StdRng::from_seed(&[0usize][..]));
+    let mut counter = AliveCounter { alive: None };
+    factory.build("sandpile", 3, 3, &1, rng(), &mut counter).unwrap();
+    assert_eq!(None, counter.alive);
+    // every cell of a full board has 8 neighbors and dies
+    factory.build("life", 3, 3, &0, rng(), &mut counter).unwrap();
+    assert_eq!(Some(0), counter.alive);
+
+    assert!(factory.build("no-such-simulation", 3, 3, &0, rng(), &mut counter).is_err());
+}
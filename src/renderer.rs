@@ -0,0 +1,358 @@
+//! Drawing simulations, independent of what they're drawn to. The window,
+//! the terminal view and anything else that shows a running simulation
+//! implements `Renderer`, and gets a `Scene` describing the frame.
+
+use std::cmp::max;
+use std::f64::consts::PI;
+
+use board_view::BoardView;
+use camera::Camera;
+use energy_histogram::EnergyHistogram;
+use minimap::Minimap;
+use plot::Plot;
+use palette::{CellColor, hue_to_color};
+use simulation::Simulation;
+use stats::Stats;
+use symmetry::Region;
+use theme::Theme;
+use trails::Trails;
+
+/// Grid lines are drawn between cells at least this many pixels wide and
+/// high, as they'd hide smaller ones.
+pub const MIN_GRID_CELL_SIZE: f64 = 8.0;
+/// Color of grid lines if the theme doesn't set one.
+const DEFAULT_GRID: [f32; 4] = [0.3, 0.3, 0.3, 1.0];
+
+/// How a single cell is drawn, as given by the simulation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CellView {
+    pub color: [f32; 4],
+    /// Value shown by the density overlay, from 0 to 1.
+    pub value: f32
+}
+
+/// Area drawn into, in pixels, or characters in a terminal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub window_size: [u32; 2],
+    /// Differs from `window_size` on high DPI screens.
+    pub draw_size: [u32; 2]
+}
+
+impl Viewport {
+    pub fn new(size: [u32; 2]) -> Viewport {
+        Viewport {
+            window_size: size,
+            draw_size: size
+        }
+    }
+
+    pub fn size(&self) -> [f64; 2] {
+        [self.draw_size[0] as f64, self.draw_size[1] as f64]
+    }
+}
+
+/// Everything drawn in a single frame, in board coordinates, along with
+/// where the camera puts them in the viewport.
+pub struct Scene<'a> {
+    pub board_size: (usize, usize),
+    cells: Box<Fn(usize, usize) -> CellView + 'a>,
+    /// Ranges of visible columns and rows.
+    pub visible: ((usize, usize), (usize, usize)),
+    pub origin: [f64; 2],
+    pub cell_size: [f64; 2],
+    pub theme: &'a Theme,
+    /// Triangles pointing where the agents head, with their colors.
+    pub agents: Vec<([[f64; 2]; 3], [f32; 4])>,
+    pub streamlines: Vec<Vec<(f64, f64)>>,
+    /// Stats of the last step, for renderers that show them.
+    pub status: String,
+    /// Lines of the HUD, drawn over the board if any.
+    pub hud: Vec<String>,
+    /// Color of lines between cells, not drawn if not set.
+    pub grid: Option<[f32; 4]>,
+    /// Cells selected for copying, outlined.
+    pub selection: Option<Region>,
+    /// Whole board drawn small in a corner, if zoomed in.
+    pub minimap: Option<Minimap>,
+    /// Time series of stats drawn along the bottom, if shown.
+    pub plot: Option<Plot>,
+    /// Energies of specimens drawn as bars above the plot, if shown.
+    pub energy_histogram: Option<EnergyHistogram>
+}
+
+impl<'a> Scene<'a> {
+    pub fn of<T, S>(simulation: &'a S,
+                    theme: &'a Theme,
+                    camera: &Camera,
+                    viewport: Viewport,
+                    show_drift: bool,
+                    show_density: bool) -> Scene<'a> where S: Simulation<T>, T: CellColor + 'a {
+        let board = simulation.board();
+        let board_size = (board.width, board.height);
+        let food = simulation.food();
+        let density = if show_density { simulation.density() } else { None };
+
+        Scene {
+            board_size: board_size,
+            cells: Box::new(move |x, y| CellView {
+                color: theme.cell_color(board, food, x, y),
+                value: density.as_ref().map(|density| *density.at(x, y)).unwrap_or(0.0)
+            }),
+            visible: camera.visible_cells(board_size, viewport.size()),
+            origin: camera.origin(board_size, viewport.size()),
+            cell_size: camera.cell_size(board_size, viewport.size()),
+            theme: theme,
+            agents: simulation.agents().iter()
+                              .map(|agent| {
+                                  let hue = agent.heading() / (2.0 * PI);
+                                  (agent.triangle(1.5), hue_to_color(hue as f32))
+                              })
+                              .collect(),
+            streamlines: match simulation.drift() {
+                Some(drift) if show_drift => drift.streamlines(max(2, board_size.0 / 24), 12),
+                _ => Vec::new()
+            },
+            status: simulation.stats().to_string(),
+            hud: Vec::new(),
+            grid: None,
+            selection: None,
+            minimap: None,
+            plot: None,
+            energy_histogram: None
+        }
+    }
+
+    /// Scene of a snapshot taken with `Ticks::publish_to`, for renderers
+    /// running on another thread than the simulation. Only cells are drawn,
+    /// as food, agents and the like aren't part of the snapshot.
+    pub fn of_view<T>(view: &'a BoardView<T>,
+                      theme: &'a Theme,
+                      camera: &Camera,
+                      viewport: Viewport) -> Scene<'a> where T: CellColor + 'a {
+        let board = &**view;
+        let board_size = (board.width, board.height);
+
+        Scene {
+            board_size: board_size,
+            cells: Box::new(move |x, y| CellView {
+                color: theme.cell_color(board, None, x, y),
+                value: 0.0
+            }),
+            visible: camera.visible_cells(board_size, viewport.size()),
+            origin: camera.origin(board_size, viewport.size()),
+            cell_size: camera.cell_size(board_size, viewport.size()),
+            theme: theme,
+            agents: Vec::new(),
+            streamlines: Vec::new(),
+            status: Stats::new(view.iteration()).to_string(),
+            hud: Vec::new(),
+            grid: None,
+            selection: None,
+            minimap: None,
+            plot: None,
+            energy_histogram: None
+        }
+    }
+
+    /// Draws lines between cells, in the color of the theme's grid.
+    pub fn with_grid(self) -> Scene<'a> {
+        Scene {
+            grid: Some(self.theme.grid.unwrap_or(DEFAULT_GRID)),
+            ..self
+        }
+    }
+
+    /// Grid lines between visible cells as [x1, y1, x2, y2] in the viewport,
+    /// none if the grid isn't drawn or the cells are too small for it.
+    pub fn grid_lines(&self) -> Vec<[f64; 4]> {
+        if self.grid.is_none() || self.cell_size[0] < MIN_GRID_CELL_SIZE || self.cell_size[1] < MIN_GRID_CELL_SIZE {
+            return Vec::new();
+        }
+
+        let ((x_from, x_to), (y_from, y_to)) = self.visible;
+        let top_left = self.to_viewport(x_from as f64, y_from as f64);
+        let bottom_right = self.to_viewport(x_to as f64, y_to as f64);
+        let mut lines = Vec::with_capacity(x_to - x_from + y_to - y_from + 2);
+        for x_idx in x_from..x_to + 1 {
+            let x = self.to_viewport(x_idx as f64, 0.0)[0];
+            lines.push([x, top_left[1], x, bottom_right[1]]);
+        }
+        for y_idx in y_from..y_to + 1 {
+            let y = self.to_viewport(0.0, y_idx as f64)[1];
+            lines.push([top_left[0], y, bottom_right[0], y]);
+        }
+        lines
+    }
+
+    pub fn with_selection(self,
+                          selection: Region) -> Scene<'a> {
+        Scene {
+            selection: Some(selection),
+            ..self
+        }
+    }
+
+    /// Draws the minimap, of the cells as drawn so far.
+    pub fn with_minimap(self,
+                        viewport: Viewport) -> Scene<'a> {
+        let minimap = Minimap::of(&self, viewport.size());
+        Scene {
+            minimap: Some(minimap),
+            ..self
+        }
+    }
+
+    pub fn with_plot(self,
+                     plot: Plot) -> Scene<'a> {
+        Scene {
+            plot: Some(plot),
+            ..self
+        }
+    }
+
+    pub fn with_energy_histogram(self,
+                                 histogram: EnergyHistogram) -> Scene<'a> {
+        Scene {
+            energy_histogram: Some(histogram),
+            ..self
+        }
+    }
+
+    pub fn with_hud(self,
+                    hud: Vec<String>) -> Scene<'a> {
+        Scene {
+            hud: hud,
+            ..self
+        }
+    }
+
+    /// Draws the trails of recently left cells over them.
+    pub fn with_trails(self,
+                       trails: &'a Trails) -> Scene<'a> {
+        let cells = self.cells;
+        let trail_color = self.theme.trail;
+        Scene {
+            cells: Box::new(move |x, y| {
+                let mut view = cells(x, y);
+                let alpha = trail_color[3] * trails.trail(x, y);
+                for channel in 0..3 {
+                    view.color[channel] += (trail_color[channel] - view.color[channel]) * alpha;
+                }
+                view
+            }),
+            ..self
+        }
+    }
+
+    pub fn cell(&self,
+                x: usize,
+                y: usize) -> CellView {
+        (self.cells)(x, y)
+    }
+
+    /// Position of a point given in board coordinates in the viewport.
+    pub fn to_viewport(&self,
+                       x: f64,
+                       y: f64) -> [f64; 2] {
+        [self.origin[0] + x * self.cell_size[0],
+         self.origin[1] + y * self.cell_size[1]]
+    }
+}
+
+pub trait Renderer {
+    fn render(&mut self,
+              scene: &Scene,
+              viewport: Viewport);
+}
+
+/// Draws nothing, e.g. for benchmarking everything but drawing.
+pub struct NullRenderer;
+
+impl Renderer for NullRenderer {
+    fn render(&mut self,
+              _scene: &Scene,
+              _viewport: Viewport) {}
+}
+
+#[test]
+fn test_scene() {
+    use board::Board;
+    use palette::{BLACK, WHITE};
+    use simulation::GameOfLife;
+
+    let mut board = Board::new(4, 2, false);
+    *board.at_mut(1, 0) = true;
+    let simulation = GameOfLife::new(board);
+    let theme = Theme::default();
+    let scene = Scene::of(&simulation, &theme, &Camera::new(), Viewport::new([40, 20]), false, true);
+
+    assert_eq!(CellView { color: WHITE, value: 0.0 }, scene.cell(1, 0));
+    assert_eq!(BLACK, scene.cell(0, 0).color);
+    assert_eq!(((0, 4), (0, 2)), scene.visible);
+    assert_eq!([10.0, 10.0], scene.cell_size);
+    assert_eq!([15.0, 10.0], scene.to_viewport(1.5, 1.0));
+}
+
+#[test]
+fn test_scene_of_view() {
+    use board::Board;
+    use palette::{BLACK, WHITE};
+
+    let mut board = Board::new(4, 2, false);
+    *board.at_mut(1, 0) = true;
+    let view = BoardView::new(board, 3);
+    let theme = Theme::default();
+    let scene = Scene::of_view(&view, &theme, &Camera::new(), Viewport::new([40, 20]));
+
+    assert_eq!(WHITE, scene.cell(1, 0).color);
+    assert_eq!(BLACK, scene.cell(0, 0).color);
+    assert_eq!([10.0, 10.0], scene.cell_size);
+    assert_eq!(Stats::new(3).to_string(), scene.status);
+}
+
+#[test]
+fn test_scene_grid_lines() {
+    use board::Board;
+    use simulation::GameOfLife;
+
+    let simulation = GameOfLife::new(Board::new(4, 2, false));
+    let theme = Theme::default();
+    let scene = Scene::of(&simulation, &theme, &Camera::new(), Viewport::new([40, 20]), false, false);
+    assert!(scene.grid_lines().is_empty());
+
+    let scene = scene.with_grid();
+    let lines = scene.grid_lines();
+    assert_eq!(5 + 3, lines.len());
+    assert_eq!([0.0, 0.0, 0.0, 20.0], lines[0]);
+    assert_eq!([0.0, 10.0, 40.0, 10.0], lines[6]);
+
+    let small = Scene::of(&simulation, &theme, &Camera::new(), Viewport::new([20, 10]), false, false).with_grid();
+    assert!(small.grid_lines().is_empty());
+}
+
+#[test]
+fn test_scene_with_trails() {
+    use board::Board;
+    use palette::{BLACK, WHITE};
+    use simulation::GameOfLife;
+
+    let mut board = Board::new(3, 1, false);
+    *board.at_mut(1, 0) = true;
+    let mut trails = Trails::of_board(&board);
+    *board.at_mut(1, 0) = false;
+    *board.at_mut(2, 0) = true;
+    trails.update(&board);
+
+    let simulation = GameOfLife::new(board);
+    let theme = Theme {
+        trail: [1.0, 0.0, 0.0, 1.0],
+        ..Theme::default()
+    };
+    let scene = Scene::of(&simulation, &theme, &Camera::new(), Viewport::new([30, 10]), false, false)
+        .with_trails(&trails);
+
+    assert_eq!(BLACK, scene.cell(0, 0).color);
+    assert_eq!([1.0, 0.0, 0.0, 1.0], scene.cell(1, 0).color);
+    assert_eq!(WHITE, scene.cell(2, 0).color);
+}
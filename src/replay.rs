@@ -0,0 +1,135 @@
+//! Detecting hidden nondeterminism, such as dependence on HashMap iteration
+//! order, while a simulation runs. Every few ticks the simulation state is
+//! snapshotted; once the next snapshot is due, the previous one is replayed
+//! up to the current tick and compared with the live simulation. All
+//! randomness comes from the simulation's own seeded generator, which is
+//! part of the snapshot, so a correct simulation always replays exactly.
+
+use std::marker::PhantomData;
+
+use simulation::Simulation;
+
+pub struct ReplayVerifier<S, T> {
+    interval: usize,
+    snapshot: S,
+    ticks_since_snapshot: usize,
+    cell: PhantomData<T>
+}
+
+impl<S, T> ReplayVerifier<S, T> where S: Simulation<T> + Clone, T: PartialEq {
+    /// Verifies the last `interval` ticks every `interval` ticks, starting
+    /// with the current state of `simulation`.
+    pub fn new(simulation: &S,
+               interval: usize) -> ReplayVerifier<S, T> {
+        ReplayVerifier {
+            interval: interval,
+            snapshot: simulation.clone(),
+            ticks_since_snapshot: 0,
+            cell: PhantomData
+        }
+    }
+
+    /// To be called after every tick of `live`. Describes the divergence
+    /// if replaying from the last snapshot ended up in a different state.
+    pub fn after_tick(&mut self,
+                      live: &S) -> Result<(), String> {
+        self.ticks_since_snapshot += 1;
+        if self.ticks_since_snapshot < self.interval {
+            return Ok(());
+        }
+
+        let start = self.snapshot.stats().iteration;
+        for _ in 0..self.ticks_since_snapshot {
+            self.snapshot.advance();
+        }
+
+        let result = ReplayVerifier::compare(&self.snapshot, live)
+                         .map_err(|e| format!("replay of iterations {}-{} diverged: {}",
+                                              start, live.stats().iteration, e));
+
+        self.snapshot = live.clone();
+        self.ticks_since_snapshot = 0;
+        result
+    }
+
+    fn compare(replayed: &S,
+               live: &S) -> Result<(), String> {
+        let (replayed_board, live_board) = (replayed.board(), live.board());
+
+        if let Some((x, y)) = live_board.indices().find(|&(x, y)| replayed_board.at(x, y) != live_board.at(x, y)) {
+            return Err(format!("cell ({}, {}) differs", x, y));
+        }
+
+        let (replayed_stats, live_stats) = (replayed.stats().to_string(), live.stats().to_string());
+        if replayed_stats != live_stats {
+            return Err(format!("stats differ, replayed: {}, live: {}", replayed_stats, live_stats));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+use board::Board;
+#[cfg(test)]
+use stats::Stats;
+
+/// Simulation whose state depends on a counter shared between all its
+/// copies, which a replay can't reproduce.
+#[cfg(test)]
+#[derive(Clone)]
+struct Leaky {
+    board: Board<usize>,
+    iteration: usize,
+    shared: ::std::rc::Rc<::std::cell::Cell<usize>>
+}
+
+#[cfg(test)]
+impl Simulation<usize> for Leaky {
+    fn advance(&mut self) {
+        self.iteration += 1;
+        self.shared.set(self.shared.get() + 1);
+        *self.board.at_mut(0, 0) = self.shared.get();
+    }
+
+    fn board(&self) -> &Board<usize> {
+        &self.board
+    }
+
+    fn stats(&self) -> Stats {
+        Stats::new(self.iteration)
+    }
+}
+
+#[test]
+fn test_replay_matches_deterministic_simulation() {
+    use rand::{SeedableRng, StdRng};
+    use sandpile::{Sandpile, SandpileConfig};
+
+    let rng = Box::new(StdRng::from_seed(&[0usize][..]));
+    let mut sim = Sandpile::new(8, 8, SandpileConfig { drop_rate: 1.0 }, rng);
+    let mut verifier = ReplayVerifier::new(&sim, 5);
+
+    for _ in 0..20 {
+        sim.advance();
+        assert_eq!(Ok(()), verifier.after_tick(&sim));
+    }
+}
+
+#[test]
+fn test_replay_detects_divergence() {
+    let mut sim = Leaky {
+        board: Board::new(2, 2, 0),
+        iteration: 0,
+        shared: Default::default()
+    };
+    let mut verifier = ReplayVerifier::new(&sim, 3);
+
+    for _ in 0..2 {
+        sim.advance();
+        assert_eq!(Ok(()), verifier.after_tick(&sim));
+    }
+    sim.advance();
+    assert_eq!(Err("replay of iterations 0-3 diverged: cell (0, 0) differs".to_string()),
+               verifier.after_tick(&sim));
+}
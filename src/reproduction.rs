@@ -0,0 +1,39 @@
+/// Asexual reproduction of GoodEvil specimens, checked every step for every
+/// specimen on its own cell.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReproductionConfig {
+    /// Energy above which a specimen splits off a child. With genetics
+    /// enabled the heritable `Genome::reproduction_threshold` is used
+    /// instead.
+    pub threshold: f32,
+    /// Fraction of the parent's energy given to the child.
+    pub child_share: f32,
+    /// Number of steps after reproducing (or being born) during which a
+    /// specimen can't reproduce.
+    pub cooldown: usize
+}
+
+impl Default for ReproductionConfig {
+    fn default() -> ReproductionConfig {
+        ReproductionConfig {
+            threshold: 1.5,
+            child_share: 0.5,
+            cooldown: 10
+        }
+    }
+}
+
+impl ReproductionConfig {
+    /// Energy of the parent and the child after splitting `energy`.
+    pub fn split(&self,
+                 energy: f32) -> (f32, f32) {
+        let child = energy * self.child_share;
+        (energy - child, child)
+    }
+}
+
+#[test]
+fn test_reproduction_split() {
+    let cfg = ReproductionConfig { child_share: 0.25, .. ReproductionConfig::default() };
+    assert_eq!((1.5, 0.5), cfg.split(2.0));
+}
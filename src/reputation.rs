@@ -0,0 +1,73 @@
+/// Settings of reputation tracking in GoodEvil collisions.
+///
+/// In every collision the strongest specimen decides whether to share the
+/// energy equally with the others (cooperate) or to take all of it
+/// (defect). It cooperates with partners in good standing and refuses
+/// those with a bad reputation; with `defection_probability` it defects
+/// anyway.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReputationConfig {
+    /// Fraction of reputation lost each step, pulling it back towards 0.
+    pub decay: f32,
+    /// How far a single collision moves the reputation towards +1
+    /// (cooperation) or -1 (defection).
+    pub learning_rate: f32,
+    pub defection_probability: f32
+}
+
+impl Default for ReputationConfig {
+    fn default() -> ReputationConfig {
+        ReputationConfig {
+            decay: 0.01,
+            learning_rate: 0.2,
+            defection_probability: 0.05
+        }
+    }
+}
+
+impl ReputationConfig {
+    /// Reputation after taking part in a collision. Refusing to share with
+    /// partners of bad reputation is a justified defection and leaves it
+    /// unchanged.
+    pub fn after_collision(&self,
+                           reputation: f32,
+                           cooperated: bool,
+                           partners_reputation: f32) -> f32 {
+        let target = if cooperated {
+            1.0
+        } else if partners_reputation >= 0.0 {
+            -1.0
+        } else {
+            return reputation;
+        };
+
+        reputation + (target - reputation) * self.learning_rate
+    }
+
+    pub fn decayed(&self,
+                   reputation: f32) -> f32 {
+        reputation * (1.0 - self.decay)
+    }
+}
+
+/// Number of cooperations and defections during a single step.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CollisionLog {
+    pub cooperations: usize,
+    pub defections: usize
+}
+
+#[test]
+fn test_reputation_after_collision() {
+    let cfg = ReputationConfig { learning_rate: 0.5, .. ReputationConfig::default() };
+
+    assert_eq!(0.5, cfg.after_collision(0.0, true, 0.0));
+    assert_eq!(-0.5, cfg.after_collision(0.0, false, 0.5));
+    assert_eq!(0.25, cfg.after_collision(0.25, false, -0.5));
+}
+
+#[test]
+fn test_reputation_decay() {
+    let cfg = ReputationConfig { decay: 0.5, .. ReputationConfig::default() };
+    assert_eq!(-0.25, cfg.decayed(-0.5));
+}
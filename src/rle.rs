@@ -0,0 +1,85 @@
+//! Reader of the run-length encoded (.rle) format used by Golly and most
+//! collections of Life patterns.
+
+use macrocell::{self, Pattern, MAX_SIDE};
+
+/// Checks the `x = 3, y = 3, rule = B3/S23` header line. The size is
+/// recomputed from the cells, so it's not checked.
+fn parse_header(line: &str) -> Result<(), String> {
+    for field in line.split(',') {
+        let mut parts = field.splitn(2, '=');
+        match (parts.next().unwrap().trim(), parts.next().map(|value| value.trim())) {
+            ("x", Some(_)) | ("y", Some(_)) => (),
+            ("rule", Some(rule)) => try!(macrocell::check_rule(rule)),
+            _ => return Err(format!("invalid RLE header: {}", line))
+        }
+    }
+    Ok(())
+}
+
+pub fn parse(text: &str) -> Result<Pattern, String> {
+    let mut lines = text.lines()
+                        .map(|line| line.trim())
+                        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+    match lines.next() {
+        Some(header) => try!(parse_header(header)),
+        None => return Err("no header in RLE pattern".to_string())
+    }
+
+    let (mut x, mut y) = (0, 0);
+    let mut count = 0;
+    let mut cells = Vec::new();
+
+    'body: for line in lines {
+        for c in line.chars() {
+            if let Some(digit) = c.to_digit(10) {
+                count = count * 10 + digit as usize;
+                if count > MAX_SIDE {
+                    return Err(format!("run longer than {} cells", MAX_SIDE));
+                }
+                continue;
+            }
+
+            let run = if count == 0 { 1 } else { count };
+            count = 0;
+            match c {
+                'b' => x += run,
+                'o' => {
+                    cells.extend((x..x + run).map(|x| (x, y)));
+                    x += run;
+                },
+                '$' => {
+                    x = 0;
+                    y += run;
+                },
+                '!' => break 'body,
+                _ => return Err(format!("unexpected character in RLE pattern: {}", c))
+            }
+            if x > MAX_SIDE || y >= MAX_SIDE {
+                return Err(format!("pattern is larger than {}x{}", MAX_SIDE, MAX_SIDE));
+            }
+        }
+    }
+
+    Ok(Pattern::from_cells(cells))
+}
+
+#[test]
+fn test_rle_parse() {
+    let glider = parse("#N Glider\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n").unwrap();
+    assert_eq!((3, 3), (glider.width, glider.height));
+    assert_eq!(vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)], glider.cells);
+
+    // runs of rows and lines split anywhere
+    let blocks = parse("x = 2, y = 5\n2o$2o3$\n2o$2o!").unwrap();
+    assert_eq!((2, 6), (blocks.width, blocks.height));
+    assert_eq!(8, blocks.cells.len());
+}
+
+#[test]
+fn test_rle_rejects_invalid() {
+    assert!(parse("").is_err());
+    assert!(parse("x = 1, y = 1, rule = B36/S23\no!").is_err());
+    assert!(parse("x = 1, y = 1\n3q!").is_err());
+    assert!(parse("x = 1, y = 1\n99999o!").is_err());
+}
@@ -0,0 +1,280 @@
+//! Recording interactive runs, so that they can be replayed exactly. A run
+//! is determined by its options, seed and the inputs made while it ran;
+//! everything else follows from the simulation being deterministic.
+//!
+//! Recordings are written as they go, one JSON object per line: a header
+//! with the command line arguments and the seed, followed by the inputs.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::sync::Arc;
+
+use rustc_serialize::json::{Json, ToJson};
+
+use brush::{BrushShape, BrushTip};
+use manifest::{as_usize, as_str, field};
+use merge::{MergePolicy, Region};
+use simulation::Simulation;
+
+pub const FORMAT_VERSION: u64 = 1;
+
+/// Interactive action changing the simulation.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Input {
+    /// Paints the cells under `tip` centered at (x, y).
+    Paint {
+        x: usize,
+        y: usize,
+        brush: usize,
+        tip: BrushTip
+    },
+    Adjust {
+        steps: i32
+    },
+    /// Overwrites cells with the ones of `region`, from the clipboard or
+    /// blank to delete a selection.
+    Paste {
+        x: usize,
+        y: usize,
+        region: Arc<Region>
+    }
+}
+
+impl Input {
+    pub fn apply<T, S>(&self,
+                       simulation: &mut S) where S: Simulation<T> {
+        match *self {
+            Input::Paint { x, y, brush, tip } => {
+                let (width, height) = (simulation.board().width, simulation.board().height);
+                for (px, py) in tip.cells(x, y, width, height) {
+                    simulation.paint(px, py, brush);
+                }
+            },
+            Input::Adjust { steps } => simulation.adjust(steps),
+            Input::Paste { x, y, ref region } => {
+                simulation.merge(region, (x, y), MergePolicy::Overwrite);
+            }
+        }
+    }
+}
+
+/// Input made after `iteration` steps.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimedInput {
+    pub iteration: usize,
+    pub input: Input
+}
+
+impl TimedInput {
+    pub fn to_json(&self) -> Json {
+        let mut obj = BTreeMap::new();
+        obj.insert("iteration".to_string(), (self.iteration as u64).to_json());
+
+        match self.input {
+            Input::Paint { x, y, brush, tip } => {
+                obj.insert("input".to_string(), "paint".to_json());
+                obj.insert("x".to_string(), (x as u64).to_json());
+                obj.insert("y".to_string(), (y as u64).to_json());
+                obj.insert("brush".to_string(), (brush as u64).to_json());
+                obj.insert("shape".to_string(), tip.shape.name().to_json());
+                obj.insert("radius".to_string(), (tip.radius as u64).to_json());
+            },
+            Input::Adjust { steps } => {
+                obj.insert("input".to_string(), "adjust".to_json());
+                obj.insert("steps".to_string(), (steps as i64).to_json());
+            },
+            Input::Paste { x, y, ref region } => {
+                obj.insert("input".to_string(), "paste".to_json());
+                obj.insert("x".to_string(), (x as u64).to_json());
+                obj.insert("y".to_string(), (y as u64).to_json());
+                obj.insert("cells".to_string(), region.to_text().to_json());
+            }
+        }
+
+        Json::Object(obj)
+    }
+
+    pub fn from_json(json: &Json) -> Result<TimedInput, String> {
+        let obj = try!(json.as_object().ok_or("input is not a JSON object".to_string()));
+        let get = |name: &str| field(obj, name).and_then(|value| as_usize(value, name));
+
+        let input = match try!(as_str(try!(field(obj, "input")), "input")) {
+            "paint" => {
+                // recordings made before brush tips paint single cells
+                let shape = match obj.get("shape") {
+                    Some(shape) => {
+                        let name = try!(as_str(shape, "shape"));
+                        try!(BrushShape::from_name(name).ok_or(format!("unknown brush shape: {}", name)))
+                    },
+                    None => BrushShape::Square
+                };
+                let radius = match obj.get("radius") {
+                    Some(radius) => try!(as_usize(radius, "radius")),
+                    None => 0
+                };
+                Input::Paint {
+                    x: try!(get("x")),
+                    y: try!(get("y")),
+                    brush: try!(get("brush")),
+                    tip: BrushTip { shape: shape, radius: radius }
+                }
+            },
+            "adjust" => {
+                let steps = try!(try!(field(obj, "steps")).as_i64()
+                                     .ok_or("field steps is not an integer".to_string()));
+                Input::Adjust { steps: steps as i32 }
+            },
+            "paste" => {
+                let cells = try!(as_str(try!(field(obj, "cells")), "cells"));
+                Input::Paste { x: try!(get("x")), y: try!(get("y")), region: Arc::new(try!(Region::parse(cells))) }
+            },
+            other => return Err(format!("unknown input: {}", other))
+        };
+
+        Ok(TimedInput {
+            iteration: try!(get("iteration")),
+            input: input
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunRecording {
+    /// Command line arguments, without the program name.
+    pub args: Vec<String>,
+    pub seed: usize,
+    /// Sorted by iteration.
+    pub inputs: Vec<TimedInput>
+}
+
+impl RunRecording {
+    fn header_to_json(args: &[String],
+                      seed: usize) -> Json {
+        let mut obj = BTreeMap::new();
+        obj.insert("format_version".to_string(), FORMAT_VERSION.to_json());
+        obj.insert("args".to_string(), args.to_json());
+        obj.insert("seed".to_string(), (seed as u64).to_json());
+        Json::Object(obj)
+    }
+
+    pub fn parse(text: &str) -> Result<RunRecording, String> {
+        let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+
+        let header = try!(lines.next().ok_or("empty recording".to_string()));
+        let header = try!(Json::from_str(header).map_err(|e| e.to_string()));
+        let obj = try!(header.as_object().ok_or("header is not a JSON object".to_string()));
+
+        let version = try!(as_usize(try!(field(obj, "format_version")), "format_version"));
+        if version as u64 > FORMAT_VERSION {
+            return Err(format!("unsupported recording format version: {}", version));
+        }
+
+        let args = try!(try!(field(obj, "args")).as_array()
+                            .ok_or("field args is not an array".to_string()));
+        let args: Vec<String> = try!(args.iter().map(|arg| as_str(arg, "args").map(|s| s.to_string())).collect());
+
+        let mut inputs = Vec::new();
+        for line in lines {
+            let json = try!(Json::from_str(line).map_err(|e| e.to_string()));
+            inputs.push(try!(TimedInput::from_json(&json)));
+        }
+
+        Ok(RunRecording {
+            args: args,
+            seed: try!(as_usize(try!(field(obj, "seed")), "seed")),
+            inputs: inputs
+        })
+    }
+
+    pub fn load(path: &str) -> Result<RunRecording, String> {
+        let mut contents = String::new();
+        try!(File::open(path).and_then(|mut f| f.read_to_string(&mut contents))
+                             .map_err(|e| format!("cannot read {}: {}", path, e)));
+
+        RunRecording::parse(&contents).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Inputs to apply before the step following `iteration` ones.
+    pub fn inputs_at(&self,
+                     iteration: usize) -> Vec<Input> {
+        self.inputs.iter()
+                   .filter(|input| input.iteration == iteration)
+                   .map(|input| input.input.clone())
+                   .collect()
+    }
+}
+
+/// Writes a recording as the run goes, so that it survives a crash.
+pub struct RunWriter {
+    path: String,
+    writer: BufWriter<File>
+}
+
+impl RunWriter {
+    pub fn create(path: &str,
+                  args: &[String],
+                  seed: usize) -> Result<RunWriter, String> {
+        let file = try!(File::create(path).map_err(|e| format!("cannot create {}: {}", path, e)));
+        let mut writer = RunWriter {
+            path: path.to_string(),
+            writer: BufWriter::new(file)
+        };
+        try!(writer.write_line(RunRecording::header_to_json(args, seed)));
+        Ok(writer)
+    }
+
+    fn write_line(&mut self,
+                  json: Json) -> Result<(), String> {
+        try!(writeln!(self.writer, "{}", json).map_err(|e| format!("cannot write {}: {}", self.path, e)));
+        self.writer.flush().map_err(|e| format!("cannot write {}: {}", self.path, e))
+    }
+
+    pub fn write(&mut self,
+                 input: TimedInput) -> Result<(), String> {
+        self.write_line(input.to_json())
+    }
+}
+
+#[test]
+fn test_run_recording_round_trip() {
+    use std::env;
+    use std::fs;
+
+    let path = env::temp_dir().join("cell-test-run-recording.jsonl");
+    let path = path.to_str().unwrap();
+    let args = vec!["--sim".to_string(), "life".to_string()];
+    let region = Arc::new(Region::parse("O.\n.#").unwrap());
+    let tip = BrushTip { shape: BrushShape::Circle, radius: 2 };
+    let inputs = vec![TimedInput { iteration: 0, input: Input::Paint { x: 1, y: 2, brush: 3, tip: tip } },
+                      TimedInput { iteration: 5, input: Input::Adjust { steps: -1 } },
+                      TimedInput { iteration: 7, input: Input::Paste { x: 3, y: 4, region: region } }];
+
+    {
+        let mut writer = RunWriter::create(path, &args, 42).unwrap();
+        for input in inputs.iter() {
+            writer.write(input.clone()).unwrap();
+        }
+    }
+
+    let recording = RunRecording::load(path).unwrap();
+    assert_eq!(RunRecording { args: args, seed: 42, inputs: inputs }, recording);
+    assert_eq!(vec![Input::Adjust { steps: -1 }], recording.inputs_at(5));
+    assert!(recording.inputs_at(3).is_empty());
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_run_recording_paint_without_tip() {
+    let text = "{\"args\":[],\"format_version\":1,\"seed\":1}\n\
+                {\"brush\":1,\"input\":\"paint\",\"iteration\":0,\"x\":2,\"y\":3}\n";
+    let recording = RunRecording::parse(text).unwrap();
+    assert_eq!(vec![Input::Paint { x: 2, y: 3, brush: 1, tip: BrushTip::default() }], recording.inputs_at(0));
+}
+
+#[test]
+fn test_run_recording_rejects_unknown_input() {
+    let text = "{\"args\":[],\"format_version\":1,\"seed\":1}\n{\"input\":\"jump\",\"iteration\":0}\n";
+    assert_eq!(Err("unknown input: jump".to_string()), RunRecording::parse(text));
+}
@@ -0,0 +1,223 @@
+use board::Board;
+use i18n::{Language, Message};
+use rand::{Rng, StdRng};
+use simulation::Simulation;
+use stats::Stats;
+
+/// Number of grains that makes a cell topple, sending one grain to each of
+/// its 4 orthogonal neighbors.
+pub const TOPPLE_THRESHOLD: u32 = 4;
+
+#[derive(Clone, Copy, Debug)]
+pub struct SandpileConfig {
+    /// Average number of grains dropped onto random cells in each step.
+    pub drop_rate: f32
+}
+
+impl Default for SandpileConfig {
+    fn default() -> SandpileConfig {
+        SandpileConfig {
+            drop_rate: 1.0
+        }
+    }
+}
+
+/// Bak-Tang-Wiesenfeld sandpile. Grains toppled over the board edge are
+/// lost, which lets the pile settle in a critical state.
+#[derive(Clone)]
+pub struct Sandpile {
+    pub cfg: SandpileConfig,
+    rng: Box<StdRng>,
+    board: Board<u32>,
+    iteration: usize,
+    drop_accumulator: f32,
+    /// Topplings during the last step, over all of its avalanches.
+    last_avalanche: usize,
+    max_avalanche: usize,
+    num_avalanches: usize,
+    total_toppled: usize
+}
+
+impl Sandpile {
+    pub fn new(width: usize,
+               height: usize,
+               cfg: SandpileConfig,
+               rng: Box<StdRng>) -> Sandpile {
+        Sandpile::from_board(Board::new(width, height, 0), cfg, rng)
+    }
+
+    pub fn from_board(board: Board<u32>,
+                      cfg: SandpileConfig,
+                      rng: Box<StdRng>) -> Sandpile {
+        Sandpile {
+            cfg: cfg,
+            rng: rng,
+            board: board,
+            iteration: 0,
+            drop_accumulator: 0.0,
+            last_avalanche: 0,
+            max_avalanche: 0,
+            num_avalanches: 0,
+            total_toppled: 0
+        }
+    }
+
+    fn orthogonal_neighbors(x: usize,
+                            y: usize,
+                            board: &Board<u32>) -> Vec<(usize, usize)> {
+        let mut nbrs = Vec::with_capacity(4);
+
+        if x > 0 { nbrs.push((x - 1, y)); }
+        if x + 1 < board.width { nbrs.push((x + 1, y)); }
+        if y > 0 { nbrs.push((x, y - 1)); }
+        if y + 1 < board.height { nbrs.push((x, y + 1)); }
+
+        nbrs
+    }
+
+    /// Topples all unstable cells until the board settles. Returns the
+    /// number of topplings, i.e. the avalanche size.
+    fn relax(board: &mut Board<u32>,
+             mut unstable: Vec<(usize, usize)>) -> usize {
+        let mut topplings = 0;
+
+        while let Some((x, y)) = unstable.pop() {
+            let grains = *board.at(x, y);
+            if grains < TOPPLE_THRESHOLD {
+                continue;
+            }
+
+            let topples = grains / TOPPLE_THRESHOLD;
+            *board.at_mut(x, y) -= topples * TOPPLE_THRESHOLD;
+            topplings += topples as usize;
+
+            for (nbr_x, nbr_y) in Sandpile::orthogonal_neighbors(x, y, board) {
+                let nbr = board.at_mut(nbr_x, nbr_y);
+                *nbr += topples;
+                if *nbr >= TOPPLE_THRESHOLD {
+                    unstable.push((nbr_x, nbr_y));
+                }
+            }
+        }
+
+        topplings
+    }
+
+    /// Drops the grains of a step, letting the board settle after each one
+    /// so that every grain's avalanche is counted on its own. Returns the
+    /// number of topplings.
+    fn drop_grains(&mut self) -> usize {
+        self.drop_accumulator += self.cfg.drop_rate;
+        // subtracting grains one by one would get stuck on large rates,
+        // where f32 can't represent the accumulator minus one
+        let drops = self.drop_accumulator.floor();
+        self.drop_accumulator -= drops;
+
+        let mut topplings = 0;
+        for _ in 0..drops as usize {
+            let x = self.rng.gen_range(0, self.board.width);
+            let y = self.rng.gen_range(0, self.board.height);
+            *self.board.at_mut(x, y) += 1;
+
+            let avalanche = Sandpile::relax(&mut self.board, vec![(x, y)]);
+            if avalanche > 0 {
+                self.num_avalanches += 1;
+                self.total_toppled += avalanche;
+            }
+            if avalanche > self.max_avalanche {
+                self.max_avalanche = avalanche;
+            }
+            topplings += avalanche;
+        }
+
+        topplings
+    }
+}
+
+impl Simulation<u32> for Sandpile {
+    fn advance(&mut self) {
+        self.last_avalanche = self.drop_grains();
+        self.iteration += 1;
+    }
+
+    fn board(&self) -> &Board<u32> {
+        &self.board
+    }
+
+    fn stats(&self) -> Stats {
+        let grains = self.board.iter().fold(0, |sum, &g| sum + g as usize);
+        let mean_avalanche = if self.num_avalanches > 0 {
+            self.total_toppled as f32 / self.num_avalanches as f32
+        } else {
+            0.0
+        };
+
+        Stats::new(self.iteration).count("grains", grains)
+                                  .count("avalanche", self.last_avalanche)
+                                  .count("avalanches", self.num_avalanches)
+                                  .count("max_avalanche", self.max_avalanche)
+                                  .real("mean_avalanche", mean_avalanche)
+    }
+
+    fn describe_cell(&self,
+                     x: usize,
+                     y: usize,
+                     lang: Language) -> Vec<String> {
+        vec![lang.format(Message::CellGrains, &[&self.board.at(x, y).to_string()])]
+    }
+}
+
+#[test]
+fn test_sandpile_single_topple() {
+    let mut board = Board::new(3, 3, 0);
+    *board.at_mut(1, 1) = 4;
+
+    assert_eq!(1, Sandpile::relax(&mut board, vec![(1, 1)]));
+    assert_eq!(0, *board.at(1, 1));
+    assert_eq!(1, *board.at(0, 1));
+    assert_eq!(1, *board.at(2, 1));
+    assert_eq!(1, *board.at(1, 0));
+    assert_eq!(1, *board.at(1, 2));
+    assert_eq!(0, *board.at(0, 0));
+}
+
+#[test]
+fn test_sandpile_grains_fall_off_edge() {
+    let mut board = Board::new(1, 1, 0);
+    *board.at_mut(0, 0) = 9;
+
+    assert_eq!(2, Sandpile::relax(&mut board, vec![(0, 0)]));
+    assert_eq!(1, *board.at(0, 0));
+}
+
+#[test]
+fn test_sandpile_drop_rate() {
+    use rand::SeedableRng;
+
+    let rng = Box::new(StdRng::from_seed(&[0usize][..]));
+    let mut sim = Sandpile::new(20, 20, SandpileConfig { drop_rate: 0.5 }, rng);
+
+    for _ in 0..10 {
+        sim.advance();
+    }
+
+    assert_eq!(Some(::stats::Value::Count(5)), sim.stats().get("grains"));
+}
+
+#[test]
+fn test_sandpile_avalanche_per_grain() {
+    use rand::SeedableRng;
+
+    // all grains land on the only cell, toppling it with the 1st and 5th
+    // one; dropped at once they would make a single avalanche of 2
+    let rng = Box::new(StdRng::from_seed(&[0usize][..]));
+    let mut board = Board::new(1, 1, 0);
+    *board.at_mut(0, 0) = 3;
+    let mut sim = Sandpile::from_board(board, SandpileConfig { drop_rate: 5.0 }, rng);
+    sim.advance();
+
+    let stats = sim.stats();
+    assert_eq!(Some(::stats::Value::Count(0)), stats.get("grains"));
+    assert_eq!(Some(::stats::Value::Count(2)), stats.get("avalanches"));
+    assert_eq!(Some(::stats::Value::Count(1)), stats.get("max_avalanche"));
+}
@@ -0,0 +1,346 @@
+//! Public schema of the stats and events written for external tools.
+//! Internal types like `Stats` and `journal::Event` are free to change;
+//! outputs go through the versioned types defined here instead, which only
+//! ever gain optional fields. Readers should ignore fields they don't know.
+//! An incompatible change gets new types with the next version number and a
+//! `schema_version` field on every line, so that lines without one are
+//! always version 1.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use rustc_serialize::json::{Json, ToJson};
+
+use energy_histogram::EnergyHistogram;
+use journal::{Entry, Event};
+use manifest::{as_str, as_usize, field};
+use stats::Stats;
+
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// Stats of a simulation after a single step, written as
+/// `{"events":[...],"iteration":N,"values":{"NAME":VALUE,...}}`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatsRowV1 {
+    /// Number of steps done so far, 1 after the first step.
+    pub iteration: u64,
+    /// Named values, sorted by name. Names depend on the simulation, e.g.
+    /// `specimens` or `infected`; counts are whole numbers.
+    pub values: Vec<(String, f64)>,
+    /// Human readable descriptions of notable things that happened during
+    /// the step, such as world events.
+    pub events: Vec<String>,
+    /// Energies of specimens after the step, only with `--energy-histogram`.
+    pub energy_histogram: Option<EnergyHistogramV1>
+}
+
+/// Numbers of specimens with energies in buckets of equal width from 0 to
+/// `max`, written as `{"counts":[...],"max":MAX}`. Specimens with no energy
+/// left count into the first bucket.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnergyHistogramV1 {
+    pub max: f64,
+    pub counts: Vec<u64>
+}
+
+impl EnergyHistogramV1 {
+    pub fn of_histogram(histogram: &EnergyHistogram) -> EnergyHistogramV1 {
+        EnergyHistogramV1 {
+            max: histogram.max.to_string().parse().unwrap_or(histogram.max as f64),
+            counts: histogram.counts.iter().map(|&count| count as u64).collect()
+        }
+    }
+
+    pub fn to_json(&self) -> Json {
+        let mut obj = BTreeMap::new();
+        obj.insert("max".to_string(), self.max.to_json());
+        obj.insert("counts".to_string(), self.counts.to_json());
+        Json::Object(obj)
+    }
+
+    pub fn from_json(json: &Json) -> Result<EnergyHistogramV1, String> {
+        let obj = try!(json.as_object().ok_or("field energy_histogram is not an object".to_string()));
+        let max = try!(try!(field(obj, "max")).as_f64().ok_or("field max is not a number".to_string()));
+        let counts = try!(try!(field(obj, "counts")).as_array()
+                              .ok_or("field counts is not an array".to_string()));
+        let counts: Vec<u64> = try!(counts.iter()
+                                          .map(|count| as_usize(count, "counts").map(|count| count as u64))
+                                          .collect());
+        Ok(EnergyHistogramV1 {
+            max: max,
+            counts: counts
+        })
+    }
+}
+
+impl StatsRowV1 {
+    pub fn of_stats(stats: &Stats) -> StatsRowV1 {
+        // going through the shortest decimal form keeps e.g. 0.1f32 from
+        // turning into 0.10000000149011612
+        let mut values: Vec<(String, f64)> = stats.values
+                                                  .iter()
+                                                  .map(|&(name, value)| {
+                                                      let real = value.to_string().parse().unwrap_or(value.as_f32() as f64);
+                                                      (name.to_string(), real)
+                                                  })
+                                                  .collect();
+        values.sort_by(|a, b| a.0.cmp(&b.0));
+
+        StatsRowV1 {
+            iteration: stats.iteration as u64,
+            values: values,
+            events: stats.events.clone(),
+            energy_histogram: stats.energy_histogram.as_ref().map(EnergyHistogramV1::of_histogram)
+        }
+    }
+
+    pub fn to_json(&self) -> Json {
+        let values: BTreeMap<String, Json> = self.values
+                                                 .iter()
+                                                 .map(|&(ref name, value)| {
+                                                     let value = if value.fract() == 0.0 && value >= 0.0 {
+                                                         (value as u64).to_json()
+                                                     } else {
+                                                         value.to_json()
+                                                     };
+                                                     (name.clone(), value)
+                                                 })
+                                                 .collect();
+
+        let mut obj = BTreeMap::new();
+        obj.insert("iteration".to_string(), self.iteration.to_json());
+        obj.insert("values".to_string(), Json::Object(values));
+        obj.insert("events".to_string(), self.events.to_json());
+        if let Some(ref histogram) = self.energy_histogram {
+            obj.insert("energy_histogram".to_string(), histogram.to_json());
+        }
+        Json::Object(obj)
+    }
+
+    pub fn from_json(json: &Json) -> Result<StatsRowV1, String> {
+        let obj = try!(json.as_object().ok_or("stats row is not a JSON object".to_string()));
+
+        let values_obj = try!(try!(field(obj, "values")).as_object()
+                                  .ok_or("field values is not an object".to_string()));
+        let mut values = Vec::new();
+        for (name, value) in values_obj.iter() {
+            let value = try!(value.as_f64().ok_or(format!("field {} is not a number", name)));
+            values.push((name.clone(), value));
+        }
+
+        let events = try!(try!(field(obj, "events")).as_array()
+                              .ok_or("field events is not an array".to_string()));
+        let events: Vec<String> = try!(events.iter()
+                                             .map(|event| as_str(event, "events").map(|s| s.to_string()))
+                                             .collect());
+
+        let energy_histogram = match obj.get("energy_histogram") {
+            Some(json) => Some(try!(EnergyHistogramV1::from_json(json))),
+            None => None
+        };
+
+        Ok(StatsRowV1 {
+            iteration: try!(as_usize(try!(field(obj, "iteration")), "iteration")) as u64,
+            values: values,
+            events: events,
+            energy_histogram: energy_histogram
+        })
+    }
+}
+
+/// Line of an event log. Fields other than `iteration` and `event` are
+/// only present for the kinds of events they describe.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventV1 {
+    /// Step during which the event happened, 0 for the first one.
+    pub iteration: u64,
+    /// `birth`, `death`, `collision` or `move`. Logs that rotate also start
+    /// every file with a `sampling` line.
+    pub event: String,
+    /// Cell of a birth, death or collision.
+    pub position: Option<(u64, u64)>,
+    /// Energy of a specimen being born, or left to one that died.
+    pub energy: Option<f64>,
+    /// Number of specimens trying to move to the cell of a collision.
+    pub contenders: Option<u64>,
+    /// Cells a specimen moved between.
+    pub from: Option<(u64, u64)>,
+    pub to: Option<(u64, u64)>,
+    /// Only every `every`-th event of each kind is logged from a `sampling`
+    /// line on.
+    pub every: Option<u64>
+}
+
+fn position_to_json(position: (u64, u64)) -> Json {
+    vec![position.0, position.1].to_json()
+}
+
+fn position_from_json(json: &Json,
+                      name: &str) -> Result<(u64, u64), String> {
+    let coords = try!(json.as_array().ok_or(format!("field {} is not an array", name)));
+    if coords.len() != 2 {
+        return Err(format!("field {} is not a pair of coordinates", name));
+    }
+    Ok((try!(as_usize(&coords[0], name)) as u64, try!(as_usize(&coords[1], name)) as u64))
+}
+
+impl EventV1 {
+    fn new(iteration: usize,
+           event: &str) -> EventV1 {
+        EventV1 {
+            iteration: iteration as u64,
+            event: event.to_string(),
+            position: None,
+            energy: None,
+            contenders: None,
+            from: None,
+            to: None,
+            every: None
+        }
+    }
+
+    pub fn of_entry(entry: &Entry) -> EventV1 {
+        let pos = |(x, y): (usize, usize)| Some((x as u64, y as u64));
+        let event = EventV1::new(entry.iteration, entry.event.name());
+
+        match entry.event {
+            Event::Birth { position, energy } | Event::Death { position, energy } => EventV1 {
+                position: pos(position),
+                energy: Some(energy as f64),
+                .. event
+            },
+            Event::Collision { position, contenders } => EventV1 {
+                position: pos(position),
+                contenders: Some(contenders as u64),
+                .. event
+            },
+            Event::Move { from, to } => EventV1 {
+                from: pos(from),
+                to: pos(to),
+                .. event
+            }
+        }
+    }
+
+    pub fn sampling(iteration: usize,
+                    every: usize) -> EventV1 {
+        EventV1 {
+            every: Some(every as u64),
+            .. EventV1::new(iteration, "sampling")
+        }
+    }
+
+    pub fn to_json(&self) -> Json {
+        let mut obj = BTreeMap::new();
+        obj.insert("iteration".to_string(), self.iteration.to_json());
+        obj.insert("event".to_string(), self.event.to_json());
+        if let Some(position) = self.position {
+            obj.insert("position".to_string(), position_to_json(position));
+        }
+        if let Some(energy) = self.energy {
+            obj.insert("energy".to_string(), energy.to_json());
+        }
+        if let Some(contenders) = self.contenders {
+            obj.insert("contenders".to_string(), contenders.to_json());
+        }
+        if let Some(from) = self.from {
+            obj.insert("from".to_string(), position_to_json(from));
+        }
+        if let Some(to) = self.to {
+            obj.insert("to".to_string(), position_to_json(to));
+        }
+        if let Some(every) = self.every {
+            obj.insert("every".to_string(), every.to_json());
+        }
+        Json::Object(obj)
+    }
+
+    pub fn from_json(json: &Json) -> Result<EventV1, String> {
+        let obj = try!(json.as_object().ok_or("event is not a JSON object".to_string()));
+        let count = |name: &str| match obj.get(name) {
+            Some(value) => as_usize(value, name).map(|n| Some(n as u64)),
+            None => Ok(None)
+        };
+        let position = |name: &str| match obj.get(name) {
+            Some(value) => position_from_json(value, name).map(Some),
+            None => Ok(None)
+        };
+        let energy = match obj.get("energy") {
+            Some(value) => Some(try!(value.as_f64().ok_or("field energy is not a number".to_string()))),
+            None => None
+        };
+
+        Ok(EventV1 {
+            iteration: try!(as_usize(try!(field(obj, "iteration")), "iteration")) as u64,
+            event: try!(as_str(try!(field(obj, "event")), "event")).to_string(),
+            position: try!(position("position")),
+            energy: energy,
+            contenders: try!(count("contenders")),
+            from: try!(position("from")),
+            to: try!(position("to")),
+            every: try!(count("every"))
+        })
+    }
+}
+
+/// Appends a `StatsRowV1` per step to a file, one JSON object per line.
+pub struct StatsLog {
+    path: String,
+    writer: BufWriter<File>
+}
+
+impl StatsLog {
+    pub fn create(path: &str) -> Result<StatsLog, String> {
+        let file = try!(File::create(path).map_err(|e| format!("{}: {}", path, e)));
+        Ok(StatsLog {
+            path: path.to_string(),
+            writer: BufWriter::new(file)
+        })
+    }
+
+    pub fn write(&mut self,
+                 stats: &Stats) -> Result<(), String> {
+        let line = StatsRowV1::of_stats(stats).to_json();
+        try!(writeln!(self.writer, "{}", line).map_err(|e| format!("{}: {}", self.path, e)));
+        self.writer.flush().map_err(|e| format!("{}: {}", self.path, e))
+    }
+}
+
+#[test]
+fn test_stats_row_round_trip() {
+    let stats = Stats::new(3).count("specimens", 10).real("avg", 0.1).event("meteor:5".to_string());
+    let row = StatsRowV1::of_stats(&stats);
+
+    assert_eq!(r#"{"events":["meteor:5"],"iteration":3,"values":{"avg":0.1,"specimens":10}}"#,
+               row.to_json().to_string());
+    assert_eq!(Ok(row.clone()), StatsRowV1::from_json(&row.to_json()));
+
+    let stats = stats.with_energy_histogram(EnergyHistogram { max: 2.5, counts: vec![3, 0, 1] });
+    let row = StatsRowV1::of_stats(&stats);
+    assert_eq!(r#"{"energy_histogram":{"counts":[3,0,1],"max":2.5},"events":["meteor:5"],"iteration":3,"values":{"avg":0.1,"specimens":10}}"#,
+               row.to_json().to_string());
+    assert_eq!(Ok(row.clone()), StatsRowV1::from_json(&row.to_json()));
+
+    // fields added later are ignored
+    let json = Json::from_str(r#"{"events":[],"iteration":1,"values":{},"phase":"x"}"#).unwrap();
+    assert_eq!(1, StatsRowV1::from_json(&json).unwrap().iteration);
+}
+
+#[test]
+fn test_event_round_trip() {
+    let entry = Entry {
+        iteration: 4,
+        event: Event::Death { position: (0, 1), energy: -0.5 }
+    };
+    let event = EventV1::of_entry(&entry);
+    assert_eq!(r#"{"energy":-0.5,"event":"death","iteration":4,"position":[0,1]}"#,
+               event.to_json().to_string());
+    assert_eq!(Ok(event.clone()), EventV1::from_json(&event.to_json()));
+
+    let sampling = EventV1::sampling(3, 4);
+    assert_eq!(Ok(sampling.clone()), EventV1::from_json(&sampling.to_json()));
+
+    let json = Json::from_str(r#"{"event":"move","iteration":1,"from":[1]}"#).unwrap();
+    assert!(EventV1::from_json(&json).is_err());
+}
@@ -0,0 +1,164 @@
+//! Rules defined outside of the crate, so that new ones can be tried out
+//! without recompiling. Any implementation of the traits below can be
+//! plugged in; with the `scripting` feature, they can also be loaded from
+//! Rhai scripts:
+//!
+//! ```text
+//! // life: next state of a cell
+//! fn next(alive, neighbors) { neighbors == 3 || (alive && neighbors == 2) }
+//!
+//! // goodevil: offset to move by, or () to let the movement policy decide
+//! fn choose_move(energy, x, y, neighbors) { if energy > 1.0 { [1, 0] } else { () } }
+//! ```
+
+use std::sync::Arc;
+#[cfg(feature = "scripting")]
+use std::io::{self, Write};
+#[cfg(feature = "scripting")]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use simulation::Specimen;
+
+/// Transition function of a two-state grid automaton.
+pub trait CellRule: Send + Sync {
+    /// Next state of a cell, given its current state and the number of its
+    /// 8 neighbors that are alive.
+    fn next(&self,
+            alive: bool,
+            alive_neighbors: usize) -> bool;
+}
+
+/// Movement of a GoodEvil specimen.
+pub trait SpecimenBehavior: Send + Sync {
+    /// Offset the specimen at (x, y) moves by, each coordinate clamped to
+    /// [-1, 1], or None to let the movement policy decide. `neighbors` is
+    /// the number of occupied neighboring cells.
+    fn choose_move(&self,
+                   specimen: &Specimen,
+                   x: usize,
+                   y: usize,
+                   neighbors: usize) -> Option<(i64, i64)>;
+}
+
+#[cfg(feature = "scripting")]
+struct Script {
+    path: String,
+    engine: ::rhai::Engine,
+    ast: ::rhai::AST,
+    /// Set once an error was reported, so that a broken script doesn't
+    /// report it again for every cell.
+    failed: AtomicBool
+}
+
+#[cfg(feature = "scripting")]
+impl Script {
+    fn load(path: &str,
+            function: &str,
+            arity: usize) -> Result<Script, String> {
+        let engine = ::rhai::Engine::new();
+        let ast = try!(engine.compile_file(path.into()).map_err(|e| format!("cannot load {}: {}", path, e)));
+        if !ast.iter_functions().any(|f| f.name == function && f.params.len() == arity) {
+            return Err(format!("{} does not define {}() taking {} arguments", path, function, arity));
+        }
+
+        Ok(Script {
+            path: path.to_string(),
+            engine: engine,
+            ast: ast,
+            failed: AtomicBool::new(false)
+        })
+    }
+
+    /// Prints the first error of the script to stderr; callers fall back
+    /// to what they would do without it.
+    fn report(&self,
+              error: String) {
+        if !self.failed.swap(true, Ordering::Relaxed) {
+            let _ = writeln!(io::stderr(), "{}: {}, falling back to built-in rules", self.path, error);
+        }
+    }
+
+    fn call<A: ::rhai::FuncArgs>(&self,
+                                 function: &str,
+                                 args: A) -> Option<::rhai::Dynamic> {
+        match self.engine.call_fn(&mut ::rhai::Scope::new(), &self.ast, function, args) {
+            Ok(result) => Some(result),
+            Err(e) => {
+                self.report(format!("{}() failed: {}", function, e));
+                None
+            }
+        }
+    }
+}
+
+#[cfg(feature = "scripting")]
+impl CellRule for Script {
+    /// Follows the rules of Life if the script fails.
+    fn next(&self,
+            alive: bool,
+            alive_neighbors: usize) -> bool {
+        let life = alive_neighbors == 3 || (alive && alive_neighbors == 2);
+        match self.call("next", (alive, alive_neighbors as i64)).map(|result| result.as_bool()) {
+            Some(Ok(next)) => next,
+            Some(Err(type_name)) => {
+                self.report(format!("next() returned {} instead of bool", type_name));
+                life
+            },
+            None => life
+        }
+    }
+}
+
+#[cfg(feature = "scripting")]
+impl SpecimenBehavior for Script {
+    /// Leaves the move to the movement policy if the script fails.
+    fn choose_move(&self,
+                   specimen: &Specimen,
+                   x: usize,
+                   y: usize,
+                   neighbors: usize) -> Option<(i64, i64)> {
+        let result = match self.call("choose_move", (specimen.energy as f64, x as i64, y as i64, neighbors as i64)) {
+            Some(result) => result,
+            None => return None
+        };
+        if result.is_unit() {
+            return None;
+        }
+
+        let type_name = result.type_name();
+        let offset = result.try_cast::<::rhai::Array>().and_then(|offset| match offset.len() {
+            2 => offset[0].as_int().and_then(|dx| offset[1].as_int().map(|dy| (dx, dy))).ok(),
+            _ => None
+        });
+        if offset.is_none() {
+            self.report(format!("choose_move() returned {} instead of [dx, dy] or ()", type_name));
+        }
+        offset
+    }
+}
+
+#[cfg(feature = "scripting")]
+pub fn load_cell_rule(path: &str) -> Result<Arc<CellRule>, String> {
+    Script::load(path, "next", 2).map(|script| Arc::new(script) as Arc<CellRule>)
+}
+
+#[cfg(feature = "scripting")]
+pub fn load_specimen_behavior(path: &str) -> Result<Arc<SpecimenBehavior>, String> {
+    Script::load(path, "choose_move", 4).map(|script| Arc::new(script) as Arc<SpecimenBehavior>)
+}
+
+#[cfg(not(feature = "scripting"))]
+pub fn load_cell_rule(path: &str) -> Result<Arc<CellRule>, String> {
+    Err(format!("cannot load {}: built without scripting support", path))
+}
+
+#[cfg(not(feature = "scripting"))]
+pub fn load_specimen_behavior(path: &str) -> Result<Arc<SpecimenBehavior>, String> {
+    Err(format!("cannot load {}: built without scripting support", path))
+}
+
+#[test]
+fn test_load_missing_script() {
+    assert!(load_cell_rule("no-such-rules.rhai").is_err());
+    assert!(load_specimen_behavior("no-such-rules.rhai").is_err());
+}
@@ -1,12 +1,277 @@
-use board::Board;
+use agent::Agent;
+use board::{self, Board};
+use boundary::{Boundary, Edge};
+use checks::{self, CheckLevel};
+use dormancy::DormancyConfig;
+use drift::DriftField;
+use conflict::ConflictResolver;
+use crowding::CrowdingConfig;
+use diagnostics::{PerfReport, PhaseTimings};
+use fairness::{FairnessAudit, UpdateOrder};
+use food::FoodConfig;
+use genome::{GeneticsConfig, Genome};
+use i18n::{Language, Message};
+use initial_energy::EnergyDistribution;
+use initial_state::InitialState;
+use journal::{Event, Journal};
+use merge::{self, MergePolicy, Region};
+use metrics::Metrics;
+use movement::{ActionCosts, MovementPolicy, exp_weights, pick_weighted};
+use policy::{Action, Neighbor, Policies, Senses};
+use predation::PredationConfig;
+use reproduction::ReproductionConfig;
+use reputation::{CollisionLog, ReputationConfig};
+use scripting::{CellRule, SpecimenBehavior};
+use sparse_board::{SparseBoard, Storage};
+use spatial::SpatialIndex;
+use species::{self, SpeciesConfig};
+use stats::Stats;
+use symmetry;
+use ticks::Ticks;
+use warnings::{WarningKind, WarningLog};
+use world_events::{EventKind, WorldEvent};
+#[cfg(test)]
+use board::assert_point_iterables_eq;
+#[cfg(test)]
+use conflict::STRONGEST_WINS;
+#[cfg(test)]
+use stats::Value;
 use rand::{Rng, StdRng};
+use std::any::Any;
 use std::cmp::{min, max, Ordering};
-use std::iter::Iterator;
 use std::collections::HashMap;
+use std::iter::Iterator;
 use std::mem;
+use std::slice;
+use std::sync::Arc;
+use std::time::Instant;
 
 pub trait Simulation<T> {
     fn advance(&mut self);
+    fn board(&self) -> &Board<T>;
+    fn stats(&self) -> Stats;
+
+    /// Whether the simulation reached a state it can never leave, e.g. all
+    /// specimens died.
+    fn is_extinct(&self) -> bool {
+        false
+    }
+
+    /// Advances the simulation up to `ticks` times, yielding a report
+    /// after each step.
+    fn run<'a>(&'a mut self,
+               ticks: usize) -> Ticks<'a, Self, T> where Self: Sized {
+        Ticks::new(self, ticks)
+    }
+
+    /// Agents moving in continuous space, drawn over the board.
+    fn agents(&self) -> &[Agent] {
+        &[]
+    }
+
+    /// Field biasing movement, if the simulation has one.
+    fn drift(&self) -> Option<&DriftField> {
+        None
+    }
+
+    /// Amount of food on each cell, in range [0, 1], drawn under the board.
+    fn food(&self) -> Option<&Board<f32>> {
+        None
+    }
+
+    /// Fraction of neighboring cells occupied, in range [0, 1], for
+    /// simulations of specimens. Can be drawn over the board.
+    fn density(&self) -> Option<Board<f32>> {
+        None
+    }
+
+    /// Specimens currently alive, for simulations that have any.
+    fn specimens(&self) -> Vec<Specimen> {
+        Vec::new()
+    }
+
+    /// What's on (x, y), one detail per line, for the cell inspector.
+    fn describe_cell(&self,
+                     _x: usize,
+                     _y: usize,
+                     _lang: Language) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Specimen on (x, y), if any.
+    fn specimen_at(&self,
+                   _x: usize,
+                   _y: usize) -> Option<Specimen> {
+        None
+    }
+
+    /// Win rates in conflicts gathered so far, if the simulation audits
+    /// them.
+    fn fairness(&self) -> Option<&FairnessAudit> {
+        None
+    }
+
+    /// Events of the last step, for simulations that keep track of
+    /// individual specimens.
+    fn journal(&self) -> Option<&Journal> {
+        None
+    }
+
+    /// Warnings raised so far, for simulations that can degrade without
+    /// failing.
+    fn warnings(&self) -> Option<&WarningLog> {
+        None
+    }
+
+    /// Profiler counters gathered so far, for simulations that time the
+    /// phases of their steps.
+    fn perf_report(&self) -> Option<PerfReport> {
+        None
+    }
+
+    /// Adds metrics of the last step beyond its stats, which `Metrics`
+    /// records on its own.
+    fn record_metrics(&self,
+                      _metrics: &mut Metrics) {}
+
+    /// The simulation itself, for code generic over simulations that wants
+    /// what only a specific one offers, e.g. the config of a `GoodEvil`.
+    fn as_any(&self) -> &Any where Self: Sized + 'static {
+        self
+    }
+
+    /// Sets cells around (x, y) to the state selected with `brush`. Brush 0
+    /// erases; simulations that can't be painted on ignore this.
+    fn paint(&mut self,
+             _x: usize,
+             _y: usize,
+             _brush: usize) {
+    }
+
+    /// Merges `region` into the board with its top left corner at `offset`,
+    /// returning the number of cells changed; simulations whose boards
+    /// can't be merged into ignore this.
+    fn merge(&mut self,
+             _region: &Region,
+             _offset: (usize, usize),
+             _policy: MergePolicy) -> usize {
+        0
+    }
+
+    /// Cells of `area` in the format of `merge`, None for simulations whose
+    /// boards can't be merged into.
+    fn copy(&self,
+            _area: symmetry::Region) -> Option<Region> {
+        None
+    }
+
+    /// Parameters that can change while the simulation runs, e.g. with
+    /// `adjust`, by name.
+    fn parameters(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+
+    /// Nudges the main parameter of the simulation (e.g. temperature) up or
+    /// down by `steps`; simulations without one ignore this.
+    fn adjust(&mut self,
+              _steps: i32) {
+    }
+
+    /// Moves the layer `board` shows by `steps` and returns the one shown
+    /// now, for 3D simulations; 2D ones return None.
+    fn scrub_layer(&mut self,
+                   _steps: i32) -> Option<usize> {
+        None
+    }
+
+    /// Shows or hides the `index`-th layer of simulations made of several,
+    /// see `layers::Layers`, returning its name and whether it's visible
+    /// now; others return None.
+    fn toggle_layer(&mut self,
+                    _index: usize) -> Option<(&'static str, bool)> {
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SimulationKind {
+    GoodEvil,
+    Life,
+    Epidemic,
+    Sandpile,
+    GrayScott,
+    Cyclic,
+    FallingSand,
+    Elementary,
+    Boids,
+    Ants,
+    Ising,
+    Fluid,
+    Turmites,
+    ForestFire,
+    Life3D
+}
+
+impl SimulationKind {
+    pub fn all() -> &'static [SimulationKind] {
+        const ALL: &'static [SimulationKind] = &[
+            SimulationKind::GoodEvil,
+            SimulationKind::Life,
+            SimulationKind::Epidemic,
+            SimulationKind::Sandpile,
+            SimulationKind::GrayScott,
+            SimulationKind::Cyclic,
+            SimulationKind::FallingSand,
+            SimulationKind::Elementary,
+            SimulationKind::Boids,
+            SimulationKind::Ants,
+            SimulationKind::Ising,
+            SimulationKind::Fluid,
+            SimulationKind::Turmites,
+            SimulationKind::ForestFire,
+            SimulationKind::Life3D
+        ];
+
+        ALL
+    }
+
+
+    pub fn from_name(name: &str) -> Option<SimulationKind> {
+        SimulationKind::all().iter()
+                             .find(|kind| kind.name() == name)
+                             .cloned()
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            SimulationKind::GoodEvil => "goodevil",
+            SimulationKind::Life => "life",
+            SimulationKind::Epidemic => "epidemic",
+            SimulationKind::Sandpile => "sandpile",
+            SimulationKind::GrayScott => "gray-scott",
+            SimulationKind::Cyclic => "cyclic",
+            SimulationKind::FallingSand => "falling-sand",
+            SimulationKind::Elementary => "elementary",
+            SimulationKind::Boids => "boids",
+            SimulationKind::Ants => "ants",
+            SimulationKind::Ising => "ising",
+            SimulationKind::Fluid => "fluid",
+            SimulationKind::Turmites => "turmites",
+            SimulationKind::ForestFire => "forest-fire",
+            SimulationKind::Life3D => "life3d"
+        }
+    }
+}
+
+/// Returns (0, 0) for no values.
+fn mean_and_stdev(values: &[f32]) -> (f32, f32) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+
+    let mean = values.iter().fold(0.0, |sum, v| sum + v) / values.len() as f32;
+    let variance = values.iter().fold(0.0, |sum, v| sum + (v - mean) * (v - mean)) / values.len() as f32;
+    (mean, variance.sqrt())
 }
 
 pub struct TorusNeighbors {
@@ -17,18 +282,6 @@ pub struct TorusNeighbors {
     y_end: usize
 }
 
-fn torus_sub_1(idx: usize,
-               idx_end: usize) -> usize {
-    if idx == 0 {
-        idx_end - 1
-    } else if idx > idx_end {
-        assert!(idx == idx_end + 1);
-        0
-    } else {
-        idx - 1
-    }
-}
-
 impl Iterator for TorusNeighbors {
     type Item = (usize, usize);
 
@@ -36,15 +289,14 @@ impl Iterator for TorusNeighbors {
         if self.idx == 9 {
             None
         } else {
-            let x = torus_sub_1(self.x + (self.idx % 3), self.x_end);
-            let y = torus_sub_1(self.y + (self.idx / 3), self.y_end);
+            let (dx, dy) = ((self.idx % 3) as isize - 1, (self.idx / 3) as isize - 1);
 
             self.idx += 1;
             if self.idx == 4 {
                 self.idx += 1;
             }
 
-            Some((x, y))
+            board::offset(self.x, self.y, dx, dy, self.x_end, self.y_end, true)
         }
     }
 }
@@ -97,11 +349,66 @@ fn test_torus_neighbors_end() {
     assert_point_iterables_eq(&expected_output,
                               &mut torus_neighbors(2, 2, 3, 3));
 }
-struct GameOfLife {
-    board: Board<bool>
+#[derive(Clone)]
+pub struct GameOfLife {
+    board: Board<bool>,
+    iteration: usize,
+    /// Replaces the rules of Life if set.
+    rule: Option<Arc<CellRule>>,
+    /// Live cells, if the board is stored sparsely. `board` is then only
+    /// updated where cells change.
+    live: Option<SparseBoard<bool>>
 }
 
 impl GameOfLife {
+    pub fn new(board: Board<bool>) -> GameOfLife {
+        GameOfLife {
+            board: board,
+            iteration: 0,
+            rule: None,
+            live: None
+        }
+    }
+
+    pub fn with_rule(self,
+                     rule: Arc<CellRule>) -> GameOfLife {
+        GameOfLife {
+            rule: Some(rule),
+            ..self
+        }
+    }
+
+    /// Stores the board as `storage` says. Rules bringing cells without
+    /// live neighbors to life need every cell updated, so they keep the
+    /// board dense.
+    pub fn with_storage(self,
+                        storage: Storage) -> GameOfLife {
+        let alive = self.board.iter().filter(|&&alive| alive).count();
+        let sparse = storage.is_sparse(self.board.width * self.board.height, alive)
+                     && !GameOfLife::next_state(self.rule.as_ref(), false, 0);
+
+        GameOfLife {
+            live: if sparse { Some(SparseBoard::from_board(&self.board, false)) } else { None },
+            ..self
+        }
+    }
+
+    pub fn is_sparse(&self) -> bool {
+        self.live.is_some()
+    }
+
+    pub fn new_random(width: usize,
+                      height: usize,
+                      alive_probability: f32,
+                      rng: &mut StdRng) -> GameOfLife {
+        let mut board = Board::new(width, height, false);
+        for (_, cell) in board.enumerate_mut() {
+            *cell = rng.gen::<f32>() < alive_probability;
+        }
+
+        GameOfLife::new(board)
+    }
+
     fn count_alive_neighbors(board: &Board<bool>,
                              x: usize,
                              y: usize) -> usize {
@@ -116,59 +423,337 @@ impl GameOfLife {
         nbrs_alive
     }
 
-    fn advance_board(old: &Board<bool>) -> Board<bool> {
+    fn next_state(rule: Option<&Arc<CellRule>>,
+                  is_alive: bool,
+                  nbrs_alive: usize) -> bool {
+        match rule {
+            Some(rule) => rule.next(is_alive, nbrs_alive),
+            None => (!is_alive && nbrs_alive == 3)
+                    || (is_alive && (nbrs_alive == 2 || nbrs_alive == 3))
+        }
+    }
+
+    fn advance_board(old: &Board<bool>,
+                     rule: Option<&Arc<CellRule>>) -> Board<bool> {
         let mut new = Board::new(old.width, old.height, false);
 
-        for (x, y) in old.indices() {
-            let is_alive = *old.at(x, y);
+        for ((x, y), &is_alive) in old.enumerate() {
             let nbrs_alive = GameOfLife::count_alive_neighbors(old, x, y);
 
-            *new.at_mut(x, y) = (!is_alive && nbrs_alive == 3)
-                             || (is_alive && (nbrs_alive == 2 || nbrs_alive == 3));
+            *new.at_mut(x, y) = GameOfLife::next_state(rule, is_alive, nbrs_alive);
+        }
+
+        new
+    }
+
+    /// Advances only the live cells and their neighbors, the only ones that
+    /// can change, and updates `board` where they did.
+    fn advance_sparse(board: &mut Board<bool>,
+                      live: &SparseBoard<bool>,
+                      rule: Option<&Arc<CellRule>>) -> SparseBoard<bool> {
+        let mut nbrs_alive: HashMap<(usize, usize), usize> = HashMap::new();
+        for (&(x, y), _) in live.occupied() {
+            nbrs_alive.entry((x, y)).or_insert(0);
+            for nbr in torus_neighbors(x, y, live.width, live.height) {
+                *nbrs_alive.entry(nbr).or_insert(0) += 1;
+            }
+        }
+
+        let mut new = SparseBoard::new(live.width, live.height, false);
+        for (&(x, y), &count) in &nbrs_alive {
+            new.set(x, y, GameOfLife::next_state(rule, *live.at(x, y), count));
         }
 
+        for (&(x, y), _) in live.occupied() {
+            *board.at_mut(x, y) = false;
+        }
+        for (&(x, y), _) in new.occupied() {
+            *board.at_mut(x, y) = true;
+        }
         new
     }
 }
 
 impl Simulation<bool> for GameOfLife {
     fn advance(&mut self) {
-        self.board = GameOfLife::advance_board(&self.board);
+        match self.live {
+            Some(ref mut live) => *live = GameOfLife::advance_sparse(&mut self.board, live, self.rule.as_ref()),
+            None => self.board = GameOfLife::advance_board(&self.board, self.rule.as_ref())
+        }
+        self.iteration += 1;
+    }
+
+    fn board(&self) -> &Board<bool> {
+        &self.board
+    }
+
+    fn stats(&self) -> Stats {
+        let alive = match self.live {
+            Some(ref live) => live.len(),
+            None => self.board.iter().filter(|&&alive| alive).count()
+        };
+        Stats::new(self.iteration).count("alive", alive)
+    }
+
+    fn is_extinct(&self) -> bool {
+        match self.live {
+            Some(ref live) => live.len() == 0,
+            None => !self.board.iter().any(|&alive| alive)
+        }
+    }
+
+    fn merge(&mut self,
+             region: &Region,
+             offset: (usize, usize),
+             policy: MergePolicy) -> usize {
+        let changed = merge::merge_into(&mut self.board, region, offset, policy);
+        if self.live.is_some() {
+            self.live = Some(SparseBoard::from_board(&self.board, false));
+        }
+        changed
+    }
+
+    fn copy(&self,
+            area: symmetry::Region) -> Option<Region> {
+        Some(Region::copy_from(&self.board, area))
+    }
+
+    fn describe_cell(&self,
+                     x: usize,
+                     y: usize,
+                     lang: Language) -> Vec<String> {
+        vec![lang.tr(if *self.board.at(x, y) { Message::CellAlive } else { Message::CellDead }).to_string()]
+    }
+
+    /// Brush 0 kills cells, any other brings them to life.
+    fn paint(&mut self,
+             x: usize,
+             y: usize,
+             brush: usize) {
+        *self.board.at_mut(x, y) = brush != 0;
+        if let Some(ref mut live) = self.live {
+            live.set(x, y, brush != 0);
+        }
     }
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Specimen {
-    pub energy: f32
+    pub energy: f32,
+    /// Record of past behavior in collisions, from -1 (defector) to +1
+    /// (cooperator). Stays 0 unless reputation is tracked.
+    pub reputation: f32,
+    pub genome: Genome,
+    /// Steps left until the specimen can reproduce again.
+    pub cooldown: usize,
+    pub dormant: bool,
+    /// Index into `SpeciesConfig::interactions`; always 0 without species.
+    pub species: u8,
+    /// Steps survived so far.
+    pub age: usize,
+    /// Number of ancestors, 0 for specimens placed at the start, painted or
+    /// immigrated.
+    pub generation: usize
+}
+
+impl Specimen {
+    pub fn new(energy: f32) -> Specimen {
+        Specimen {
+            energy: energy,
+            reputation: 0.0,
+            genome: Genome::default(),
+            cooldown: 0,
+            dormant: false,
+            species: 0,
+            age: 0,
+            generation: 0
+        }
+    }
+
+    /// Energy weighted by the genome, deciding who is the strongest in a
+    /// collision.
+    pub fn strength(&self) -> f32 {
+        self.energy * self.genome.strength()
+    }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Field {
     Empty,
     Occupied(Specimen),
-    Collision(Vec<Specimen>)
+    /// Obstacle no specimen can enter. Walls never move.
+    Wall
+}
+
+impl Field {
+    pub fn specimens(&self) -> &[Specimen] {
+        match *self {
+            Field::Empty | Field::Wall => &[],
+            Field::Occupied(ref specimen) => slice::from_ref(specimen)
+        }
+    }
+}
+
+/// Move proposed by a GoodEvil specimen during a step.
+struct Intent {
+    specimen: Specimen,
+    origin: (usize, usize),
+    target: (usize, usize)
+}
+
+/// Moves proposed during a step, grouped by their targets.
+struct Intents {
+    intents: Vec<Intent>,
+    /// Indices into `intents` of specimens trying to move into each cell,
+    /// in order of arrival.
+    by_target: Board<Vec<usize>>,
+    /// Reproducing and sharing chosen by policies, along with the cell the
+    /// specimen stays on, done once moves are resolved.
+    actions: Vec<((usize, usize), Action)>
+}
+
+impl Intents {
+    fn new(width: usize,
+           height: usize) -> Intents {
+        Intents {
+            intents: Vec::new(),
+            by_target: Board::new(width, height, Vec::new()),
+            actions: Vec::new()
+        }
+    }
+
+    fn push(&mut self,
+            intent: Intent) {
+        self.by_target.at_mut(intent.target.0, intent.target.1).push(self.intents.len());
+        self.intents.push(intent);
+    }
+
+    fn contenders(&self,
+                  x: usize,
+                  y: usize) -> &[usize] {
+        self.by_target.at(x, y)
+    }
 }
 
+#[derive(Clone)]
 pub struct GoodEvilConfig {
     pub num_specimens: usize,
     pub initial_specimen_energy: f32,
+    /// Spread of the initial energy of specimens. Everyone starts with
+    /// `initial_specimen_energy` if not set.
+    pub initial_energy: Option<EnergyDistribution>,
+    /// Where specimens start. They are spread uniformly at random over the
+    /// board if not set.
+    pub initial_state: Option<InitialState>,
     pub energy_loss_per_step: f32,
-    pub deadly_energy_margin: f32
+    pub deadly_energy_margin: f32,
+    /// Events scheduled at fixed iterations.
+    pub events: Vec<WorldEvent>,
+    /// Probability of a random event happening in a single step.
+    pub random_event_probability: f32,
+    pub boundary: Boundary,
+    /// Average number of specimens entering through each edge per step,
+    /// used with open boundary only.
+    pub immigration_rate: f32,
+    pub immigrant_energy: f32,
+    /// Wind/current biasing the direction of movement. Specimens move
+    /// uniformly at random if not set.
+    pub drift: Option<DriftField>,
+    pub movement: MovementPolicy,
+    pub update_order: UpdateOrder,
+    /// Decides who gets a cell more specimens try to move into.
+    pub conflicts: &'static ConflictResolver,
+    /// Number of regions along each axis to gather conflict win rates for.
+    /// Win rates are not gathered if not set.
+    pub fairness_audit: Option<usize>,
+    /// Energy spent on moving, charged after the metabolic loss.
+    pub action_costs: Option<ActionCosts>,
+    /// Makes collisions consult and update specimen reputation. Energy is
+    /// split by the original rules if not set.
+    pub reputation: Option<ReputationConfig>,
+    /// Lets much stronger specimens eat weaker ones in collisions.
+    pub predation: Option<PredationConfig>,
+    /// Splits the population into species, which may treat each other
+    /// differently in collisions.
+    pub species: Option<SpeciesConfig>,
+    /// Maximum number of specimens trying to move into a single cell.
+    /// Specimens moving into a full cell are deflected to a neighboring one
+    /// with room left.
+    pub max_collision_size: Option<usize>,
+    /// Distance within which specimens interact as if they collided, even
+    /// without moving into the same cell. Only specimens moving into the
+    /// same cell interact if not set.
+    pub interaction_radius: Option<usize>,
+    /// Makes children inherit mutated copies of their parents' genomes,
+    /// including the reproduction threshold.
+    pub genetics: Option<GeneticsConfig>,
+    /// Lets specimens low on energy go dormant instead of wandering around.
+    pub dormancy: Option<DormancyConfig>,
+    /// Makes specimens with many neighbors lose more energy.
+    pub crowding: Option<CrowdingConfig>,
+    /// Decides where specimens move, e.g. according to a script. The
+    /// movement policy is used wherever the behavior leaves it undecided.
+    pub behavior: Option<Arc<SpecimenBehavior>>,
+    /// Policies deciding what specimens of each species do, overriding both
+    /// the behavior and the movement policy.
+    pub policies: Policies,
+    /// Lets specimens reproduce. The population only changes through
+    /// immigration and deaths if not set.
+    pub reproduction: Option<ReproductionConfig>,
+    /// Food layer feeding the specimens, replacing recycling of lost
+    /// energy through collisions.
+    pub food: Option<FoodConfig>,
+    /// Internal invariants verified on every step.
+    pub checks: CheckLevel
 }
 
-type CollisionMap = HashMap<(usize, usize), Vec<Specimen>>;
-
+#[derive(Clone)]
 pub struct GoodEvil {
     pub cfg: GoodEvilConfig,
     rng: Box<StdRng>,
     collision_energy: f32,
     pub board: Board<Field>,
-    iteration: usize
+    iteration: usize,
+    famine_steps_left: usize,
+    /// Events that happened during the last step.
+    last_events: Vec<EventKind>,
+    immigration_accumulator: f32,
+    /// Number of specimens that entered/left through each edge during the
+    /// last step, indexed with Edge::index.
+    flux_in: [usize; 4],
+    flux_out: [usize; 4],
+    /// Cooperations and defections during the last step.
+    collisions: CollisionLog,
+    /// Number of specimens deflected from full cells during the last step.
+    deflections: usize,
+    food: Option<Board<f32>>,
+    /// Energy gained from food during the last step.
+    eaten: f32,
+    /// Children born during the last step.
+    births: usize,
+    /// Energy given away by specimens sharing it during the last step.
+    shared: f32,
+    /// Specimens killed in collisions during the last step.
+    kills: usize,
+    /// Groups of specimens that interacted within cfg.interaction_radius
+    /// during the last step.
+    range_groups: usize,
+    /// Energy spent on moving during the last step.
+    action_energy: f32,
+    /// Energy lost to crowding during the last step.
+    crowding_energy: f32,
+    audit: Option<FairnessAudit>,
+    /// Births, deaths, collisions and moves during the last step.
+    journal: Journal,
+    warnings: WarningLog,
+    /// Time spent in each phase of `advance` so far.
+    timings: PhaseTimings
 }
 
 impl GoodEvil {
     fn find_empty_field(board: &Board<Field>,
-                        rng: &mut StdRng) -> (usize, usize) {
+                        rng: &mut StdRng,
+                        warnings: &mut WarningLog) -> (usize, usize) {
         let loop_limit = board.width * board.height;
         let mut coords = None;
 
@@ -183,9 +768,19 @@ impl GoodEvil {
         }
 
         if let Some(xy) = coords {
-            xy
-        } else {
-            panic!("could not find an empty field in {} iterations", loop_limit);
+            return xy;
+        }
+
+        // crowded boards, e.g. mostly walls, are searched exhaustively
+        warnings.record(WarningKind::EmptyFieldFallback, 0,
+                        format!("no empty cell found in {} random tries", loop_limit));
+        let empty: Vec<(usize, usize)> = board.enumerate()
+                                              .filter(|&(_, field)| *field == Field::Empty)
+                                              .map(|(xy, _)| xy)
+                                              .collect();
+        match rng.choose(&empty) {
+            Some(&xy) => xy,
+            None => panic!("no empty field left on the board")
         }
     }
 
@@ -193,190 +788,768 @@ impl GoodEvil {
                height: usize,
                cfg: GoodEvilConfig,
                mut rng: Box<StdRng>) -> GoodEvil {
+        let specimens = GoodEvil::initial_specimens(&cfg, &mut rng);
+        GoodEvil::with_specimens(width, height, cfg, specimens, rng)
+    }
+
+    /// Specimens a new simulation starts with, evenly split among species.
+    pub fn initial_specimens(cfg: &GoodEvilConfig,
+                             rng: &mut StdRng) -> Vec<Specimen> {
+        let num_species = cfg.species.as_ref().map(|species| species.count()).unwrap_or(1);
+        let energies = match cfg.initial_energy {
+            Some(ref distribution) => distribution.energies(cfg.num_specimens, rng),
+            None => vec![cfg.initial_specimen_energy; cfg.num_specimens]
+        };
+        energies.into_iter()
+                .enumerate()
+                .map(|(idx, energy)| Specimen {
+                         species: (idx % num_species) as u8,
+                         ..Specimen::new(energy)
+                     })
+                .collect()
+    }
+
+    /// Places given specimens on an empty board.
+    pub fn with_specimens(width: usize,
+                          height: usize,
+                          cfg: GoodEvilConfig,
+                          specimens: Vec<Specimen>,
+                          rng: Box<StdRng>) -> GoodEvil {
+        GoodEvil::with_walls(&Board::new(width, height, false), cfg, specimens, rng)
+    }
+
+    /// Places given specimens on a board with walls on cells set in `walls`,
+    /// according to `cfg.initial_state`.
+    pub fn with_walls(walls: &Board<bool>,
+                      cfg: GoodEvilConfig,
+                      specimens: Vec<Specimen>,
+                      mut rng: Box<StdRng>) -> GoodEvil {
+        let (width, height) = (walls.width, walls.height);
         assert!(width >= 2);
         assert!(height >= 2);
-        assert!(cfg.num_specimens <= width * height);
+        assert!(specimens.len() <= walls.iter().filter(|&&wall| !wall).count());
 
         let mut board = Board::new(width, height, Field::Empty);
+        for ((x, y), &wall) in walls.enumerate() {
+            if wall {
+                *board.at_mut(x, y) = Field::Wall;
+            }
+        }
 
-        for _ in 0..cfg.num_specimens {
-            let (x, y) = GoodEvil::find_empty_field(&board, &mut rng);
-            *board.at_mut(x, y) = Field::Occupied(Specimen {
-                                                      energy: cfg.initial_specimen_energy
-                                                  });
+        let mut warnings = WarningLog::new();
+        match cfg.initial_state {
+            Some(state) => {
+                let mut free = Board::new(width, height, true);
+                for ((x, y), &wall) in walls.enumerate() {
+                    *free.at_mut(x, y) = !wall;
+                }
+                let cells = state.place(&free, specimens.len(), &mut rng);
+                for (specimen, (x, y)) in specimens.into_iter().zip(cells) {
+                    *board.at_mut(x, y) = Field::Occupied(specimen);
+                }
+            },
+            None => for specimen in specimens {
+                let (x, y) = GoodEvil::find_empty_field(&board, &mut rng, &mut warnings);
+                *board.at_mut(x, y) = Field::Occupied(specimen);
+            }
         }
 
+        let food = cfg.food.map(|food| food.initial_layer(width, height, &mut rng));
+        let audit = cfg.fairness_audit.map(|regions| FairnessAudit::new((width, height), regions));
+
         GoodEvil {
             cfg: cfg,
             rng: rng,
             collision_energy: 0.0f32,
             board: board,
-            iteration: 0
+            iteration: 0,
+            famine_steps_left: 0,
+            last_events: Vec::new(),
+            immigration_accumulator: 0.0,
+            flux_in: [0; 4],
+            flux_out: [0; 4],
+            collisions: CollisionLog::default(),
+            deflections: 0,
+            food: food,
+            eaten: 0.0,
+            births: 0,
+            shared: 0.0,
+            kills: 0,
+            range_groups: 0,
+            action_energy: 0.0,
+            crowding_energy: 0.0,
+            audit: audit,
+            journal: Journal::new(),
+            warnings: warnings,
+            timings: PhaseTimings::new()
         }
     }
 
-    fn get_new_coords(x: usize,
-                      y: usize,
-                      board: &Board<Field>,
-                      rng: &mut StdRng) -> (usize, usize) {
-        let min_x = max(0i64, x as i64 - 1) as usize;
-        let max_x = min(x + 2, board.width);
+    fn energy_loss_per_step(&self) -> f32 {
+        if self.famine_steps_left > 0 {
+            self.cfg.energy_loss_per_step * 2.0
+        } else {
+            self.cfg.energy_loss_per_step
+        }
+    }
 
-        let min_y = max(0i64, y as i64 - 1) as usize;
-        let max_y = min(y + 2, board.height);
+    /// Specimens past their cooldown with energy above the reproduction
+    /// threshold split off a child onto a random empty neighboring cell, if
+    /// there is one. The child inherits the genome, mutated if genetics are
+    /// enabled. Parents are picked before any split, so children born in
+    /// this step don't split again.
+    fn reproduce(&mut self) {
+        let cfg = match self.cfg.reproduction {
+            Some(cfg) => cfg,
+            None => return
+        };
+
+        let parents: Vec<(usize, usize, Specimen)> =
+            self.board.indices()
+                      .filter_map(|(x, y)| match *self.board.at(x, y) {
+                          Field::Occupied(specimen) if self.can_reproduce(&specimen, cfg) => Some((x, y, specimen)),
+                          _ => None
+                      })
+                      .collect();
+        for (x, y, parent) in parents {
+            self.split_off_child(x, y, parent, cfg);
+        }
+    }
 
-        (rng.gen_range(min_x, max_x),
-         rng.gen_range(min_y, max_y))
+    /// Whether `specimen` is past its cooldown and has energy above the
+    /// reproduction threshold, taken from its genome if genetics are enabled.
+    fn can_reproduce(&self,
+                     specimen: &Specimen,
+                     cfg: ReproductionConfig) -> bool {
+        let threshold = match self.cfg.genetics {
+            Some(_) => specimen.genome.reproduction_threshold,
+            None => cfg.threshold
+        };
+        specimen.cooldown == 0 && specimen.energy > threshold
     }
 
-    fn move_specimen(specimen: Specimen,
-                     dst_x: usize,
-                     dst_y: usize,
-                     new: &mut Board<Field>) {
-        let target_field: &mut Field = new.at_mut(dst_x, dst_y);
+    /// Splits a child off `parent` at (x, y) onto a random empty
+    /// neighboring cell, if there is one.
+    fn split_off_child(&mut self,
+                       x: usize,
+                       y: usize,
+                       parent: Specimen,
+                       cfg: ReproductionConfig) {
+        let empty: Vec<(usize, usize)> = GoodEvil::surrounding_fields(x, y, &self.board)
+                                             .into_iter()
+                                             .filter(|&(x, y)| *self.board.at(x, y) == Field::Empty)
+                                             .collect();
+        let (child_x, child_y) = match self.rng.choose(&empty) {
+            Some(&xy) => xy,
+            None => return
+        };
+
+        let (parent_energy, child_energy) = cfg.split(parent.energy);
+        let genome = match self.cfg.genetics {
+            Some(ref genetics) => parent.genome.mutated(genetics, &mut self.rng),
+            None => parent.genome
+        };
+        *self.board.at_mut(x, y) = Field::Occupied(Specimen {
+            energy: parent_energy,
+            cooldown: cfg.cooldown,
+            ..parent
+        });
+        *self.board.at_mut(child_x, child_y) = Field::Occupied(Specimen {
+            energy: child_energy,
+            genome: genome,
+            cooldown: cfg.cooldown,
+            species: parent.species,
+            generation: parent.generation + 1,
+            ..Specimen::new(0.0)
+        });
+        self.births += 1;
+        self.journal.record(Event::Birth { position: (child_x, child_y), energy: child_energy });
+    }
 
-        match target_field {
-            &mut Field::Empty => {
-                *target_field = Field::Occupied(specimen);
-            },
-            &mut Field::Occupied(tgt_specimen) => {
-                let specimens = vec!(tgt_specimen, specimen);
-                //println!("collision, create");
-                *target_field = Field::Collision(specimens.clone());
-            },
-            &mut Field::Collision(ref mut specimens) => {
-                specimens.push(specimen);
+    /// Carries out the reproducing and sharing chosen by policies. Each is
+    /// done by the specimen on the cell after moves are resolved, which may
+    /// be another one if the chooser lost a collision. Reproducing follows
+    /// the same rules as without a policy, and is ignored if reproduction is
+    /// disabled.
+    fn act(&mut self,
+           actions: Vec<((usize, usize), Action)>) {
+
+        for ((x, y), action) in actions {
+            let specimen = match *self.board.at(x, y) {
+                Field::Occupied(specimen) => specimen,
+                _ => continue
+            };
+
+            match action {
+                Action::Reproduce => match self.cfg.reproduction {
+                    Some(cfg) if self.can_reproduce(&specimen, cfg) => self.split_off_child(x, y, specimen, cfg),
+                    _ => {}
+                },
+                Action::Share { dx, dy, amount } => {
+                    let (dx, dy) = (max(-1, min(1, dx)), max(-1, min(1, dy)));
+                    let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+                    if (dx, dy) == (0, 0) {
+                        continue;
+                    }
+
+                    let amount = amount.max(0.0).min(specimen.energy);
+                    match self.board.get_mut(nx, ny) {
+                        Some(&mut Field::Occupied(ref mut receiver)) => receiver.energy += amount,
+                        _ => continue
+                    }
+                    *self.board.at_mut(x, y) = Field::Occupied(Specimen {
+                        energy: specimen.energy - amount,
+                        ..specimen
+                    });
+                    self.shared += amount;
+                },
+                _ => {}
             }
         }
     }
 
-    fn update_specimen(&mut self,
-                       x: usize,
-                       y: usize,
-                       new: &mut Board<Field>) {
-        match self.board.at(x, y) {
-            &Field::Empty => (),
-            &Field::Occupied(specimen) => {
-                self.collision_energy += self.cfg.energy_loss_per_step;
-                let new_specimen = Specimen {
-                    energy: specimen.energy - self.cfg.energy_loss_per_step,
-                    ..specimen
-                };
+    /// Specimens eat the food under them, and it grows back on empty cells.
+    fn feed(&mut self) {
+        let (cfg, food) = match (self.cfg.food, self.food.as_mut()) {
+            (Some(cfg), Some(food)) => (cfg, food),
+            _ => return
+        };
+
+        self.eaten = 0.0;
+        for ((x, y), field) in self.board.enumerate_mut() {
+            match *field {
+                Field::Occupied(ref mut specimen) => {
+                    let energy = cfg.eat(food, x, y);
+                    specimen.energy += energy;
+                    self.eaten += energy;
+                },
+                Field::Wall => (),
+                _ => cfg.regrow(food, x, y)
+            }
+        }
+    }
 
-                if new_specimen.energy < self.cfg.deadly_energy_margin {
-                    println!("specimen died (energy = {} < {}",
-                             new_specimen.energy, self.cfg.deadly_energy_margin);
-                    self.collision_energy += new_specimen.energy;
-                } else {
-                    let (target_x, target_y) = GoodEvil::get_new_coords(x, y, &new, &mut self.rng);
-                    GoodEvil::move_specimen(new_specimen, target_x, target_y, new);
+    fn apply_event(&mut self,
+                   kind: EventKind) {
+        match kind {
+            EventKind::Meteor { radius } => {
+                let center_x = self.rng.gen_range(0, self.board.width) as i64;
+                let center_y = self.rng.gen_range(0, self.board.height) as i64;
+                // radii larger than the board just cover all of it
+                let radius_sq = radius.saturating_mul(radius) as u64;
+
+                for ((x, y), field) in self.board.enumerate_mut() {
+                    let (dx, dy) = (x as i64 - center_x, y as i64 - center_y);
+                    if (dx * dx + dy * dy) as u64 > radius_sq || *field == Field::Wall {
+                        continue;
+                    }
+                    if let Field::Occupied(specimen) = *field {
+                        self.journal.record(Event::Death { position: (x, y), energy: specimen.energy });
+                    }
+                    *field = Field::Empty;
                 }
             },
-            &Field::Collision(_) => panic!("should never happen")
+            EventKind::Famine { duration } => {
+                self.famine_steps_left = max(self.famine_steps_left, duration);
+            },
+            EventKind::Bloom { energy } => {
+                self.collision_energy += energy;
+            }
         }
     }
 
-    fn surrounding_fields(x: usize,
-                          y: usize,
-                          board: &Board<Field>) -> Vec<(usize, usize)> {
-        let mut fields = vec!();
-
-        let min_x = max(0i64, x as i64 - 1) as usize;
-        let max_x = min(x + 2, board.width);
+    /// Applies events scheduled for the upcoming iteration and possibly a
+    /// random one.
+    fn trigger_events(&mut self) {
+        let iteration = self.iteration + 1;
+        let mut events: Vec<EventKind> = self.cfg.events.iter()
+                                                 .filter(|event| event.iteration == iteration)
+                                                 .map(|event| event.kind)
+                                                 .collect();
+
+        if self.cfg.random_event_probability > 0.0
+                && self.rng.gen::<f32>() < self.cfg.random_event_probability {
+            events.push(EventKind::random(&mut self.rng));
+        }
 
-        let min_y = max(0i64, y as i64 - 1) as usize;
-        let max_y = min(y + 2, board.height);
+        for &kind in events.iter() {
+            self.apply_event(kind);
+        }
+        self.last_events = events;
+    }
 
-        for x in min_x..max_x {
-            for y in min_y..max_y {
-                fields.push((x, y))
+    /// Picks a random cell next to (x, y), skipping walls. The specimen's
+    /// own cell is never a wall, so this always finds one.
+    fn get_new_coords(x: usize,
+                      y: usize,
+                      board: &Board<Field>,
+                      rng: &mut StdRng) -> (usize, usize) {
+        let (min_x, min_y) = GoodEvil::clamped_offset(x, y, -1, board);
+        let (max_x, max_y) = GoodEvil::clamped_offset(x, y, 1, board);
+        let (max_x, max_y) = (max_x + 1, max_y + 1);
+
+        loop {
+            let target = (rng.gen_range(min_x, max_x),
+                          rng.gen_range(min_y, max_y));
+            if *board.at(target.0, target.1) != Field::Wall {
+                return target;
             }
         }
-
-        fields
     }
 
-    fn assign_neighbors(x: usize,
-                        y: usize,
-                        num_elems: usize,
-                        board: &Board<Field>,
-                        rng: &mut StdRng) -> Vec<(usize, usize)> {
-        let mut fields = GoodEvil::surrounding_fields(x, y, board);
-        assert!(num_elems <= fields.len());
-
-        rng.shuffle(&mut fields[..]); 
-        fields.resize(num_elems, (-1i32 as usize, -1i32 as usize));
-        fields
-    }
+    /// Picks a target cell next to (x, y) for `specimen` according to the
+    /// movement policy and the drift field. Off board cells are considered
+    /// only if `allow_off_board` is set, and are scored as neutral.
+    fn get_new_coords_directed(x: usize,
+                               y: usize,
+                               specimen: &Specimen,
+                               board: &Board<Field>,
+                               food: Option<&Board<f32>>,
+                               cfg: &GoodEvilConfig,
+                               allow_off_board: bool,
+                               rng: &mut StdRng) -> (i64, i64) {
+        let mut offsets = Vec::with_capacity(9);
+        let mut exponents = Vec::with_capacity(9);
+        let strength = specimen.strength();
+
+        for oy in -1..2 {
+            for ox in -1..2 {
+                let (tx, ty) = (x as i64 + ox, y as i64 + oy);
+                let score = match board.get(tx, ty) {
+                    Some(&Field::Wall) => continue,
+                    Some(_) => cfg.movement.score(board, food, (x, y), tx as usize, ty as usize, strength),
+                    None if allow_off_board => 0.0,
+                    None => continue
+                };
+                let drift = cfg.drift.as_ref().map(|drift| drift.offset_exponent(x, y, (ox, oy))).unwrap_or(0.0);
 
-    fn split_energy_equally(specimens: &Vec<Specimen>,
-                            available_energy: f32) -> Vec<Specimen> {
-        let part = available_energy / specimens.len() as f32;
+                offsets.push((ox, oy));
+                exponents.push(score + drift);
+            }
+        }
 
-        specimens.iter()
-                 .map(|s| Specimen {
-                         energy: s.energy + part,
-                         ..*s
-                     })
-                 .collect()
+        let (ox, oy) = pick_weighted(&offsets, &exp_weights(&exponents), rng);
+        (x as i64 + ox, y as i64 + oy)
     }
 
-    fn split_energy_equally_with_children(specimens: &Vec<Specimen>,
-                                          available_energy: f32) -> Vec<Specimen> {
-        let mut new = GoodEvil::split_energy_equally(specimens, available_energy);
-        let mut result = Vec::new();
+    /// True if all neighboring cells are equally likely targets, in which
+    /// case the cheaper get_new_coords can be used.
+    fn moves_uniformly(&self) -> bool {
+        self.cfg.movement == MovementPolicy::Random && self.cfg.drift.is_none()
+    }
 
-        for s in new.iter_mut() {
-            if s.energy > 1.5 {
-                result.push(Specimen { energy: s.energy / 2.0 });
-                s.energy /= 2.0;
+    /// Like get_new_coords, but allows stepping one cell off the board.
+    fn get_new_coords_unbounded(x: usize,
+                                y: usize,
+                                board: &Board<Field>,
+                                rng: &mut StdRng) -> (i64, i64) {
+        loop {
+            let (tx, ty) = (x as i64 + rng.gen_range(-1, 2),
+                            y as i64 + rng.gen_range(-1, 2));
+            if board.get(tx, ty) != Some(&Field::Wall) {
+                return (tx, ty);
             }
         }
-
-        result.extend(new);
-        result
     }
 
-    fn split_energy_poor_half(specimens: &Vec<Specimen>,
-                              mut available_energy: f32) -> Vec<Specimen> {
-        let part = available_energy * 2.0f32 / specimens.len() as f32;
+    /// Places new specimens on random empty cells along the edges.
+    fn immigrate(&mut self) {
+        let (width, height) = (self.board.width, self.board.height);
+        self.immigration_accumulator += self.cfg.immigration_rate;
+        // taken at once, as f32 can't subtract one from large values
+        let rounds = self.immigration_accumulator.floor();
+        self.immigration_accumulator -= rounds;
+
+        for _ in 0..rounds as usize {
+            for &edge in Edge::all() {
+                let idx = self.rng.gen_range(0, edge.len(width, height));
+                let (x, y) = edge.cell(idx, width, height);
+
+                if *self.board.at(x, y) == Field::Empty {
+                    let species = match self.cfg.species {
+                        Some(ref species) => self.rng.gen_range(0, species.count()) as u8,
+                        None => 0
+                    };
+                    *self.board.at_mut(x, y) = Field::Occupied(Specimen {
+                        species: species,
+                        ..Specimen::new(self.cfg.immigrant_energy)
+                    });
+                    self.flux_in[edge.index()] += 1;
+                }
+            }
+        }
+    }
 
-        let mut sorted = specimens.clone();
-        sorted.sort_by(|a, b| a.energy.partial_cmp(&b.energy).unwrap_or(Ordering::Equal));
+    /// Proposes moving the specimen from `origin` to (dst_x, dst_y), or to
+    /// a random neighboring cell if cfg.max_collision_size specimens
+    /// already try to move there. If all of them are full, the limit is
+    /// exceeded. Returns the cell proposed.
+    fn propose_limited(&mut self,
+                       specimen: Specimen,
+                       origin: (usize, usize),
+                       dst_x: usize,
+                       dst_y: usize,
+                       intents: &mut Intents) -> (usize, usize) {
+        let target = self.deflect(dst_x, dst_y, intents);
+        intents.push(Intent {
+            specimen: specimen,
+            origin: origin,
+            target: target
+        });
+        target
+    }
 
-        // TODO: find a way to make it work with map() call instead of this abomination
-        let mut result = Vec::new();
-        for &mut s in sorted.iter_mut() {
-            let gain = if part < available_energy { part } else { available_energy };
-            available_energy -= gain;
-            result.push(Specimen {
-                energy: s.energy + gain,
-                ..s
-            });
+    /// Cell a specimen moving to (dst_x, dst_y) ends up contending for.
+    fn deflect(&mut self,
+               dst_x: usize,
+               dst_y: usize,
+               intents: &Intents) -> (usize, usize) {
+        let limit = match self.cfg.max_collision_size {
+            Some(limit) if intents.contenders(dst_x, dst_y).len() >= limit => limit,
+            _ => return (dst_x, dst_y)
+        };
+
+        let mut candidates = GoodEvil::surrounding_fields(dst_x, dst_y, &self.board);
+        self.rng.shuffle(&mut candidates[..]);
+
+        match candidates.into_iter().find(|&(x, y)| intents.contenders(x, y).len() < limit) {
+            Some((x, y)) => {
+                if (x, y) != (dst_x, dst_y) {
+                    self.deflections += 1;
+                }
+                (x, y)
+            },
+            None => {
+                let detail = format!("all cells around ({}, {}) have {} contenders", dst_x, dst_y, limit);
+                self.warnings.record(WarningKind::CollisionLimitExceeded, self.iteration + 1, detail);
+                (dst_x, dst_y)
+            }
         }
+    }
 
-        result
+    /// True if the specimen at (x, y) is low on energy, and there is no
+    /// food or active specimen next to it to get energy from.
+    fn goes_dormant(&self,
+                    x: usize,
+                    y: usize,
+                    specimen: &Specimen) -> bool {
+        let cfg = match self.cfg.dormancy {
+            Some(cfg) if specimen.energy < cfg.threshold => cfg,
+            _ => return false
+        };
+
+        !GoodEvil::surrounding_fields(x, y, &self.board).into_iter().any(|(nx, ny)| {
+            let food = self.food.as_ref().map(|food| *food.at(nx, ny)).unwrap_or(0.0);
+            let active_neighbor = (nx, ny) != (x, y)
+                                  && self.board.at(nx, ny).specimens().iter().any(|s| !s.dormant);
+            food >= cfg.wake_food || active_neighbor
+        })
     }
 
-    fn split_energy_strong_takes_all(specimens: &Vec<Specimen>,
-                                     mut available_energy: f32) -> Vec<Specimen> {
-        let mut sorted = specimens.clone();
-        sorted.sort_by(|a, b| a.energy.partial_cmp(&b.energy).unwrap_or(Ordering::Equal));
+    /// Picks the cell the specimen at (x, y) moves to. It may be off the
+    /// board only with open boundary.
+    fn pick_target(&mut self,
+                   x: usize,
+                   y: usize,
+                   specimen: &Specimen) -> (i64, i64) {
+        let open = self.cfg.boundary == Boundary::Open;
+
+        let chosen = match self.cfg.behavior {
+            Some(ref behavior) => behavior.choose_move(specimen, x, y, GoodEvil::occupied_neighbors(x, y, &self.board)),
+            None => None
+        };
+        if let Some((dx, dy)) = chosen {
+            return self.move_target(x, y, dx, dy);
+        }
 
-        if let Some(last) = sorted.last_mut() {
-            *last = Specimen { energy: last.energy + available_energy };
+        if !self.moves_uniformly() {
+            GoodEvil::get_new_coords_directed(x, y, specimen, &self.board, self.food.as_ref(), &self.cfg,
+                                              open, &mut self.rng)
+        } else if open {
+            GoodEvil::get_new_coords_unbounded(x, y, &self.board, &mut self.rng)
+        } else {
+            let (target_x, target_y) = GoodEvil::get_new_coords(x, y, &self.board, &mut self.rng);
+            (target_x as i64, target_y as i64)
         }
+    }
 
-        sorted
+    /// Cell the specimen at (x, y) moving by (dx, dy), each clamped to
+    /// [-1, 1], ends up on. Moves into walls, or off the board unless it's
+    /// open, leave it in place.
+    fn move_target(&self,
+                   x: usize,
+                   y: usize,
+                   dx: i64,
+                   dy: i64) -> (i64, i64) {
+        let target = (x as i64 + max(-1, min(1, dx)), y as i64 + max(-1, min(1, dy)));
+        match self.board.get(target.0, target.1) {
+            None if self.cfg.boundary == Boundary::Open => target,
+            None | Some(&Field::Wall) => (x as i64, y as i64),
+            Some(_) => target
+        }
     }
 
-    fn split_energy_weak_takes_all(specimens: &Vec<Specimen>,
-                                   mut available_energy: f32) -> Vec<Specimen> {
+    /// What the specimen at (x, y) perceives, for its policy to decide on.
+    fn senses(&self,
+              x: usize,
+              y: usize,
+              specimen: &Specimen) -> Senses {
+        let mut neighborhood = [[Neighbor::Empty; 3]; 3];
+        for dy in 0..3 {
+            for dx in 0..3 {
+                neighborhood[dy][dx] = match self.board.get(x as i64 + dx as i64 - 1, y as i64 + dy as i64 - 1) {
+                    None => Neighbor::Edge,
+                    Some(&Field::Empty) => Neighbor::Empty,
+                    Some(&Field::Wall) => Neighbor::Wall,
+                    Some(&Field::Occupied(other)) => Neighbor::Specimen {
+                        energy: other.energy,
+                        species: other.species
+                    }
+                };
+            }
+        }
+        neighborhood[1][1] = Neighbor::Specimen {
+            energy: specimen.energy,
+            species: specimen.species
+        };
+
+        Senses {
+            position: (x, y),
+            board_size: (self.board.width, self.board.height),
+            energy: specimen.energy,
+            reputation: specimen.reputation,
+            species: specimen.species,
+            can_reproduce: specimen.cooldown == 0,
+            neighborhood: neighborhood,
+            food: self.food.as_ref().map(|food| *food.at(x, y)).unwrap_or(0.0)
+        }
+    }
+
+    /// Proposes the move of a specimen whose policy chose `action`, keeping
+    /// reproducing and sharing for `act`.
+    fn propose_action(&mut self,
+                      action: Action,
+                      specimen: Specimen,
+                      origin: (usize, usize),
+                      intents: &mut Intents) {
+        let (x, y) = origin;
+        let (target_x, target_y) = match action {
+            Action::Move(dx, dy) => self.move_target(x, y, dx, dy),
+            _ => (x as i64, y as i64)
+        };
+        let specimen = match self.pay_for_move(specimen, origin, (target_x - x as i64, target_y - y as i64)) {
+            Some(specimen) => specimen,
+            None => return
+        };
+
+        match Edge::crossed(target_x, target_y, self.board.width, self.board.height) {
+            Some(edge) => self.flux_out[edge.index()] += 1,
+            None => {
+                let cell = self.propose_limited(specimen, origin, target_x as usize, target_y as usize, intents);
+                match action {
+                    Action::Reproduce | Action::Share { .. } => intents.actions.push((cell, action)),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn die(&mut self,
+           specimen: &Specimen,
+           position: (usize, usize)) {
+        self.journal.record(Event::Death { position: position, energy: specimen.energy });
+        if self.cfg.food.is_none() {
+            self.collision_energy += specimen.energy;
+        }
+    }
+
+    /// Charges the specimen at `origin` the action cost of moving by
+    /// `offset`. Returns None if that killed it.
+    fn pay_for_move(&mut self,
+                    specimen: Specimen,
+                    origin: (usize, usize),
+                    offset: (i64, i64)) -> Option<Specimen> {
+        let cost = match self.cfg.action_costs {
+            Some(ref costs) => costs.cost(offset),
+            None => return Some(specimen)
+        };
+
+        self.action_energy += cost;
+        if self.cfg.food.is_none() {
+            self.collision_energy += cost;
+        }
+
+        let specimen = Specimen { energy: specimen.energy - cost, ..specimen };
+        if specimen.energy < self.cfg.deadly_energy_margin {
+            self.die(&specimen, origin);
+            None
+        } else {
+            Some(specimen)
+        }
+    }
+
+    /// Applies the per-step energy loss to the specimen at (x, y) and
+    /// proposes where it moves, unless it dies or leaves the board.
+    fn propose_move(&mut self,
+                    x: usize,
+                    y: usize,
+                    intents: &mut Intents) {
+        match self.board.at(x, y) {
+            &Field::Empty | &Field::Wall => (),
+            &Field::Occupied(specimen) => {
+                let dormant = self.goes_dormant(x, y, &specimen);
+                let mut energy_loss = self.energy_loss_per_step() * specimen.genome.metabolism;
+                if dormant {
+                    energy_loss *= self.cfg.dormancy.map(|cfg| cfg.loss_factor).unwrap_or(1.0);
+                }
+                if let Some(cfg) = self.cfg.crowding {
+                    let penalty = cfg.penalty(GoodEvil::occupied_neighbors(x, y, &self.board));
+                    self.crowding_energy += penalty;
+                    energy_loss += penalty;
+                }
+                if self.cfg.food.is_none() {
+                    self.collision_energy += energy_loss;
+                }
+                let new_specimen = Specimen {
+                    energy: specimen.energy - energy_loss,
+                    reputation: match self.cfg.reputation {
+                        Some(ref reputation) => reputation.decayed(specimen.reputation),
+                        None => specimen.reputation
+                    },
+                    cooldown: specimen.cooldown.saturating_sub(1),
+                    dormant: dormant,
+                    age: specimen.age + 1,
+                    ..specimen
+                };
+
+                if new_specimen.energy < self.cfg.deadly_energy_margin {
+                    self.die(&new_specimen, (x, y));
+                    return;
+                }
+                if dormant {
+                    self.propose_limited(new_specimen, (x, y), x, y, intents);
+                    return;
+                }
+                if let Some(policy) = self.cfg.policies.get(specimen.species).cloned() {
+                    let senses = self.senses(x, y, &new_specimen);
+                    let action = policy.decide(&senses, &mut self.rng);
+                    return self.propose_action(action, new_specimen, (x, y), intents);
+                }
+
+                let (target_x, target_y) = self.pick_target(x, y, &specimen);
+                let new_specimen = match self.pay_for_move(new_specimen, (x, y), (target_x - x as i64, target_y - y as i64)) {
+                    Some(specimen) => specimen,
+                    None => return
+                };
+
+                match Edge::crossed(target_x, target_y, self.board.width, self.board.height) {
+                    // the specimen takes its energy away with it
+                    Some(edge) => self.flux_out[edge.index()] += 1,
+                    None => {
+                        self.propose_limited(new_specimen, (x, y), target_x as usize, target_y as usize, intents);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Number of specimens on cells next to (x, y).
+    fn occupied_neighbors(x: usize,
+                          y: usize,
+                          board: &Board<Field>) -> usize {
+        GoodEvil::surrounding_fields(x, y, board).into_iter()
+                                                 .filter(|&(nx, ny)| (nx, ny) != (x, y))
+                                                 .filter(|&(nx, ny)| !board.at(nx, ny).specimens().is_empty())
+                                                 .count()
+    }
+
+    /// Cells next to (x, y), including itself, that are not walls.
+    fn surrounding_fields(x: usize,
+                          y: usize,
+                          board: &Board<Field>) -> Vec<(usize, usize)> {
+        let mut fields = vec!();
+
+        for dx in -1..2 {
+            for dy in -1..2 {
+                match board.offset(x, y, dx, dy, false) {
+                    Some((x, y)) if *board.at(x, y) != Field::Wall => fields.push((x, y)),
+                    _ => {}
+                }
+            }
+        }
+
+        fields
+    }
+
+    /// Cell `delta` away from (x, y) along both axes, each coordinate
+    /// staying where it is if that'd leave the board.
+    fn clamped_offset(x: usize,
+                      y: usize,
+                      delta: isize,
+                      board: &Board<Field>) -> (usize, usize) {
+        (board.offset(x, y, delta, 0, false).map_or(x, |(x, _)| x),
+         board.offset(x, y, 0, delta, false).map_or(y, |(_, y)| y))
+    }
+
+    fn split_energy_equally(specimens: &Vec<Specimen>,
+                            available_energy: f32) -> Vec<Specimen> {
+        let part = available_energy / specimens.len() as f32;
+
+        specimens.iter()
+                 .map(|s| Specimen {
+                         energy: s.energy + part,
+                         ..*s
+                     })
+                 .collect()
+    }
+
+    fn split_energy_poor_half(specimens: &Vec<Specimen>,
+                              mut available_energy: f32) -> Vec<Specimen> {
+        let part = available_energy * 2.0f32 / specimens.len() as f32;
+
+        let mut sorted = specimens.clone();
+        sorted.sort_by(|a, b| a.energy.partial_cmp(&b.energy).unwrap_or(Ordering::Equal));
+
+        // TODO: find a way to make it work with map() call instead of this abomination
+        let mut result = Vec::new();
+        for &mut s in sorted.iter_mut() {
+            let gain = if part < available_energy { part } else { available_energy };
+            available_energy -= gain;
+            result.push(Specimen {
+                energy: s.energy + gain,
+                ..s
+            });
+        }
+
+        result
+    }
+
+    /// Gives all the energy to the strongest specimen, the last one of equally
+    /// strong ones. Specimens keep their order.
+    fn split_energy_strong_takes_all(specimens: &Vec<Specimen>,
+                                     available_energy: f32) -> Vec<Specimen> {
+        let mut result = specimens.clone();
+        let mut strongest = 0;
+        for (idx, s) in specimens.iter().enumerate() {
+            if s.strength() >= specimens[strongest].strength() {
+                strongest = idx;
+            }
+        }
+
+        if let Some(s) = result.get_mut(strongest) {
+            s.energy += available_energy;
+        }
+
+        result
+    }
+
+    fn split_energy_weak_takes_all(specimens: &Vec<Specimen>,
+                                   mut available_energy: f32) -> Vec<Specimen> {
         let mut sorted = specimens.clone();
         sorted.sort_by(|a, b| a.energy.partial_cmp(&b.energy).unwrap_or(Ordering::Equal));
 
         if let Some(first) = sorted.first_mut() {
-            *first = Specimen { energy: first.energy + available_energy };
+            first.energy += available_energy;
         }
 
         sorted
@@ -398,54 +1571,274 @@ impl GoodEvil {
         splitter(specimens, available_energy)
     }
 
-    fn resolve_collisions(energy_accumulator: f32,
-                          rng: &mut StdRng,
-                          old: &Board<Field>) -> Board<Field> {
-        let mut new = Board::new(old.width, old.height, Field::Empty);
-        let collisions_count = GoodEvil::count_collisions(&old);
-        let energy_gain = energy_accumulator / collisions_count as f32;
+    /// The strongest specimen shares the energy equally if the others have
+    /// good reputation on average, and takes it all otherwise or when it
+    /// randomly decides to defect. Everyone's reputation is updated.
+    /// Specimens keep their order.
+    fn split_energy_reciprocal(specimens: &Vec<Specimen>,
+                               available_energy: f32,
+                               cfg: &ReputationConfig,
+                               log: &mut CollisionLog,
+                               rng: &mut StdRng) -> Vec<Specimen> {
+        let mut strongest = 0;
+        for (idx, s) in specimens.iter().enumerate() {
+            if s.energy >= specimens[strongest].energy {
+                strongest = idx;
+            }
+        }
 
-        for (x, y) in old.indices() {
-            match old.at(x, y) {
-                &Field::Empty => (),
-                &Field::Occupied(ref specimen) => {
-                    GoodEvil::move_specimen(specimen.clone(), x, y, &mut new);
-                },
-                &Field::Collision(ref specimens) => {
-                    let new_specs = GoodEvil::split_energy(specimens,
-                                                           specimens.len() as f32 * energy_gain);
-                    let positions = GoodEvil::assign_neighbors(x, y, new_specs.len(), &new, rng);
+        let partners_reputation = specimens.iter()
+                                           .enumerate()
+                                           .filter(|&(idx, _)| idx != strongest)
+                                           .fold(0.0, |sum, (_, s)| sum + s.reputation)
+                                  / (specimens.len() - 1) as f32;
+        let cooperates = partners_reputation >= 0.0 && rng.gen::<f32>() >= cfg.defection_probability;
 
-                    for ((new_x, new_y), specimen) in positions.into_iter().zip(new_specs) {
-                        GoodEvil::move_specimen(specimen, new_x, new_y, &mut new);
-                    }
+        let mut result = if cooperates {
+            log.cooperations += 1;
+            GoodEvil::split_energy_equally(specimens, available_energy)
+        } else {
+            log.defections += 1;
+            GoodEvil::split_energy_strong_takes_all(specimens, available_energy)
+        };
+
+        result[strongest].reputation = cfg.after_collision(specimens[strongest].reputation, cooperates,
+                                                           partners_reputation);
+        result
+    }
+
+    /// Resolves a collision of specimens trying to move to the same cell,
+    /// given as indices into `specimens`, and returns the one that gets the
+    /// cell, if any survived. After the specimens interact, the conflict
+    /// resolver picks the winner among the survivors.
+    fn resolve_conflict(&mut self,
+                        contenders: &[usize],
+                        origins: &[(usize, usize)],
+                        available_energy: f32,
+                        specimens: &mut [Option<Specimen>]) -> Option<usize> {
+        let original: Vec<Specimen> = contenders.iter().map(|&idx| specimens[idx].unwrap()).collect();
+        self.interact(contenders, available_energy, specimens);
+        let fighters: Vec<Option<Specimen>> = contenders.iter().map(|&idx| specimens[idx]).collect();
+
+        let (idx, alive) = GoodEvil::alive(&fighters);
+        if alive.is_empty() {
+            return None;
+        }
+        let resolver = self.cfg.conflicts;
+        let winner = idx[resolver.winner(&alive, &mut self.rng)];
+        if let Some(ref mut audit) = self.audit {
+            audit.record(&original, origins, winner);
+        }
+        Some(contenders[winner])
+    }
+
+    /// Lets specimens given as indices into `specimens` interact. With
+    /// predation enabled, specimens may get eaten first, then species
+    /// interactions decide who takes part in splitting `available_energy`.
+    /// Specimens killed in either way are replaced with None and counted in
+    /// `kills`.
+    fn interact(&mut self,
+                contenders: &[usize],
+                available_energy: f32,
+                specimens: &mut [Option<Specimen>]) {
+        let original: Vec<Specimen> = contenders.iter().map(|&idx| specimens[idx].unwrap()).collect();
+        let mut fighters: Vec<Option<Specimen>> = original.iter().map(|&s| Some(s)).collect();
+        let mut sharing = vec![true; fighters.len()];
+
+        if let Some(cfg) = self.cfg.predation {
+            let (survivors, eaten) = cfg.hunt(&original);
+            self.kills += eaten;
+            fighters = survivors;
+        }
+        if let Some(ref cfg) = self.cfg.species {
+            let (idx, alive) = GoodEvil::alive(&fighters);
+            if alive.len() > 1 {
+                let encounter = cfg.encounter(&alive);
+                self.kills += encounter.killed;
+                for (k, &i) in idx.iter().enumerate() {
+                    fighters[i] = encounter.specimens[k];
+                    sharing[i] = encounter.sharing[k];
                 }
             }
         }
 
-        new
+        let idx: Vec<usize> = (0..fighters.len()).filter(|&i| sharing[i] && fighters[i].is_some()).collect();
+        let partners: Vec<Specimen> = idx.iter().map(|&i| fighters[i].unwrap()).collect();
+        let split = match (partners.len(), self.cfg.reputation) {
+            (0, _) => Vec::new(),
+            (1, _) => vec![Specimen { energy: partners[0].energy + available_energy, ..partners[0] }],
+            (_, Some(ref cfg)) => GoodEvil::split_energy_reciprocal(&partners, available_energy, cfg,
+                                                                    &mut self.collisions, &mut self.rng),
+            (_, None) => GoodEvil::split_energy(&partners, available_energy)
+        };
+        for (&i, specimen) in idx.iter().zip(split) {
+            fighters[i] = Some(specimen);
+        }
+
+        for (&idx, &fighter) in contenders.iter().zip(fighters.iter()) {
+            specimens[idx] = fighter;
+        }
     }
 
-    fn has_collisions(board: &Board<Field>) -> bool {
-        board.iter().any(|f| match f {
-                             &Field::Collision(_) => true,
-                             _ => false
-                         })
+    /// Makes specimens within `radius` of each other interact as if they
+    /// collided, without moving them. Specimens are visited row by row and
+    /// each one joins the group of the first one found in range, so it
+    /// interacts at most once per step. Energy recycled through collisions
+    /// is split among all grouped specimens.
+    fn interact_in_range(&mut self,
+                         radius: usize) {
+        let positions: Vec<(usize, usize)> = self.board.enumerate()
+                                                 .filter(|&(_, field)| !field.specimens().is_empty())
+                                                 .map(|(xy, _)| xy)
+                                                 .collect();
+        let original: Vec<Specimen> = positions.iter().map(|&(x, y)| self.board.at(x, y).specimens()[0]).collect();
+        let mut specimens: Vec<Option<Specimen>> = original.iter().map(|&s| Some(s)).collect();
+        let index = SpatialIndex::new(self.board.width, self.board.height, radius, positions);
+
+        let mut grouped = vec![false; specimens.len()];
+        let mut groups = Vec::new();
+        for (idx, &(x, y)) in index.points().iter().enumerate() {
+            if grouped[idx] {
+                continue;
+            }
+            let group: Vec<usize> = index.within(x, y, radius).into_iter().filter(|&i| !grouped[i]).collect();
+            if group.len() > 1 {
+                for &i in group.iter() {
+                    grouped[i] = true;
+                }
+                groups.push(group);
+            }
+        }
+
+        let grouped_count = groups.iter().fold(0, |sum, group| sum + group.len());
+        let energy_gain = if grouped_count > 0 {
+            let gain = self.collision_energy / grouped_count as f32;
+            self.collision_energy = 0.0;
+            gain
+        } else {
+            0.0
+        };
+
+        for group in groups.iter() {
+            let position = index.points()[group[0]];
+            self.journal.record(Event::Collision { position: position, contenders: group.len() });
+            self.interact(group, group.len() as f32 * energy_gain, &mut specimens);
+        }
+        self.range_groups = groups.len();
+
+        for (idx, &(x, y)) in index.points().iter().enumerate() {
+            *self.board.at_mut(x, y) = match specimens[idx] {
+                Some(specimen) => Field::Occupied(specimen),
+                None => {
+                    self.journal.record(Event::Death { position: (x, y), energy: original[idx].energy });
+                    Field::Empty
+                }
+            };
+        }
     }
 
-    fn count_collisions(board: &Board<Field>) -> usize {
-        board.iter().fold(0,
-            |sum, f| match f {
-                &Field::Collision(ref specimens) => sum + specimens.len(),
-                _ => sum
-            })
+    /// Indices and values of specimens that are not None.
+    fn alive(specimens: &[Option<Specimen>]) -> (Vec<usize>, Vec<Specimen>) {
+        specimens.iter()
+                 .enumerate()
+                 .filter_map(|(idx, s)| s.map(|s| (idx, s)))
+                 .unzip()
+    }
+
+    /// Moves specimens according to their intents. Every cell wanted by
+    /// more than one specimen is resolved once, and the losers stay where
+    /// they were. A specimen staying put keeps its cell, so whoever won it
+    /// goes back to its own one, which may in turn send back another
+    /// specimen, and so on. No specimen is sent back twice, so the whole
+    /// step takes time proportional to the board size.
+    ///
+    /// Cells are resolved row by row, so conflicts always consume random
+    /// numbers in the same order, keeping runs with equal seeds identical.
+    /// With cfg.interaction_radius set, recycled energy is left to be split
+    /// by interact_in_range instead.
+    fn resolve_intents(&mut self,
+                       intents: Intents) -> Board<Field> {
+        let (width, height) = (self.board.width, self.board.height);
+        let contested = intents.by_target.iter()
+                                         .filter(|contenders| contenders.len() > 1)
+                                         .fold(0, |sum, contenders| sum + contenders.len());
+        let energy_gain = if contested > 0 && self.cfg.interaction_radius.is_none() {
+            let gain = self.collision_energy / contested as f32;
+            self.collision_energy = 0.0;
+            gain
+        } else {
+            0.0
+        };
+
+        let mut specimens: Vec<Option<Specimen>> = intents.intents.iter().map(|i| Some(i.specimen)).collect();
+        let mut occupants: Board<Option<usize>> = Board::new(width, height, None);
+        let mut losers = Vec::new();
+
+        for (x, y) in self.board.indices() {
+            let contenders = intents.contenders(x, y);
+            let winner = match contenders.len() {
+                0 => continue,
+                1 => contenders[0],
+                n => {
+                    self.journal.record(Event::Collision { position: (x, y), contenders: n });
+                    let origins: Vec<(usize, usize)> = contenders.iter().map(|&i| intents.intents[i].origin).collect();
+                    let winner = self.resolve_conflict(contenders, &origins, n as f32 * energy_gain, &mut specimens);
+                    for &idx in contenders.iter().filter(|&&idx| specimens[idx].is_none()) {
+                        let energy = intents.intents[idx].specimen.energy;
+                        self.journal.record(Event::Death { position: (x, y), energy: energy });
+                    }
+                    match winner {
+                        Some(winner) => winner,
+                        None => continue
+                    }
+                }
+            };
+
+            losers.extend(contenders.iter().cloned().filter(|&i| i != winner && specimens[i].is_some()));
+            *occupants.at_mut(x, y) = Some(winner);
+        }
+
+        while let Some(idx) = losers.pop() {
+            let (x, y) = intents.intents[idx].origin;
+            if let Some(displaced) = *occupants.at(x, y) {
+                if displaced != idx {
+                    losers.push(displaced);
+                }
+            }
+            *occupants.at_mut(x, y) = Some(idx);
+        }
+
+        let mut new = GoodEvil::walls_of(&self.board);
+        for ((x, y), &occupant) in occupants.enumerate() {
+            if let Some(idx) = occupant {
+                *new.at_mut(x, y) = Field::Occupied(specimens[idx].unwrap());
+                let origin = intents.intents[idx].origin;
+                if origin != (x, y) {
+                    self.journal.record(Event::Move { from: origin, to: (x, y) });
+                }
+            }
+        }
+        if self.cfg.checks >= CheckLevel::Cheap {
+            assert_eq!(specimens.iter().filter(|s| s.is_some()).count(), GoodEvil::count_specimens(&new));
+        }
+        new
+    }
+
+    /// Board with the same walls as `board`, and no specimens.
+    fn walls_of(board: &Board<Field>) -> Board<Field> {
+        let mut walls = Board::new(board.width, board.height, Field::Empty);
+        for ((x, y), field) in board.enumerate() {
+            if *field == Field::Wall {
+                *walls.at_mut(x, y) = Field::Wall;
+            }
+        }
+        walls
     }
 
     fn count_specimens(board: &Board<Field>) -> usize {
         board.iter().fold(0, |sum, f| match f {
-            &Field::Empty => sum,
-            &Field::Occupied(_) => sum + 1,
-            &Field::Collision(ref specimens) => sum + specimens.len()
+            &Field::Empty | &Field::Wall => sum,
+            &Field::Occupied(_) => sum + 1
         })
     }
 
@@ -453,9 +1846,8 @@ impl GoodEvil {
         for y in 0..board.height {
             for x in 0..board.width {
                 let c = match board.at(x, y) {
-                    &Field::Empty => 0,
-                    &Field::Occupied(_) => 1,
-                    &Field::Collision(ref specs) => specs.len()
+                    &Field::Empty | &Field::Wall => 0,
+                    &Field::Occupied(_) => 1
                 };
                 print!("{} ", c);
             }
@@ -463,28 +1855,48 @@ impl GoodEvil {
         }
     }
 
+    /// Moving and colliding may only lose energy: whatever specimens and
+    /// the collision pool hold after resolving must not exceed
+    /// `energy_before`.
+    fn audit_energy(&self,
+                    energy_before: f32) {
+        for s in self.specimens() {
+            assert!(s.energy.is_finite(), "specimen energy is {}", s.energy);
+        }
+        let energy_after = GoodEvil::total_energy(&self.board) + self.collision_energy;
+        let tolerance = 1e-3 * energy_before.abs().max(1.0);
+        assert!(energy_after <= energy_before + tolerance,
+                "step {} created energy: {} -> {}", self.iteration + 1, energy_before, energy_after);
+    }
+
     fn total_energy(board: &Board<Field>) -> f32 {
         board.iter().fold(0.0f32, |sum, f| match f {
-            &Field::Empty => sum,
-            &Field::Occupied(ref s) => sum + s.energy,
-            &Field::Collision(ref ss) => sum + ss.iter().fold(0.0f32, |sum, s| sum + s.energy)
+            &Field::Empty | &Field::Wall => sum,
+            &Field::Occupied(ref s) => sum + s.energy
         })
     }
 
-    fn print_stats(iteration: usize,
-                   board: &Board<Field>) {
+    fn collect_stats(iteration: usize,
+                     board: &Board<Field>) -> Stats {
         let mut specimens = Vec::new();
 
         for field in board.iter() {
             match field {
-                &Field::Empty => (),
-                &Field::Collision(_) => panic!("should never happen"),
+                &Field::Empty | &Field::Wall => (),
                 &Field::Occupied(specimen) => {
                     specimens.push(specimen)
                 }
             }
         }
 
+        if specimens.is_empty() {
+            return Stats::new(iteration).count("specimens", 0)
+                                        .real("min", 0.0)
+                                        .real("avg", 0.0)
+                                        .real("max", 0.0)
+                                        .real("stdev", 0.0);
+        }
+
         let mut min_energy = specimens[0].energy;
         let mut max_energy = specimens[0].energy;
         let mut sum_energy = 0.0f32;
@@ -507,47 +1919,1009 @@ impl GoodEvil {
                                                     }) / avg_energy;
         let stdev_energy = variance_energy.sqrt();
 
-        println!("iter {} specimens {} min {} avg {} max {} stdev {}",
-                 iteration, specimens.len(),
-                 min_energy, avg_energy, max_energy, stdev_energy);
+        Stats::new(iteration).count("specimens", specimens.len())
+                             .real("min", min_energy)
+                             .real("avg", avg_energy)
+                             .real("max", max_energy)
+                             .real("stdev", stdev_energy)
     }
 }
 
 impl Simulation<Field> for GoodEvil {
     fn advance(&mut self) {
-        let mut new = Board::new(self.board.width, self.board.height, Field::Empty);
+        let mut replay = if self.cfg.checks >= CheckLevel::Paranoid {
+            let mut replay = self.clone();
+            replay.cfg.checks = CheckLevel::Expensive;
+            Some(replay)
+        } else {
+            None
+        };
+
+        self.journal.start(self.iteration + 1);
+        self.trigger_events();
+        self.flux_in = [0; 4];
+        self.flux_out = [0; 4];
+        self.collisions = CollisionLog::default();
+        self.deflections = 0;
+        self.kills = 0;
+        self.births = 0;
+        self.shared = 0.0;
+        self.range_groups = 0;
+        self.action_energy = 0.0;
+        self.crowding_energy = 0.0;
+
+        let energy_before = if self.cfg.checks >= CheckLevel::Expensive {
+            GoodEvil::total_energy(&self.board) + self.collision_energy
+        } else {
+            0.0
+        };
 
-        for (x, y) in self.board.indices() {
-            self.update_specimen(x, y, &mut new);
+        let started = Instant::now();
+        let mut intents = Intents::new(self.board.width, self.board.height);
+
+        let mut cells: Vec<(usize, usize)> = self.board.indices().collect();
+        if self.cfg.update_order == UpdateOrder::Shuffled {
+            self.rng.shuffle(&mut cells[..]);
+        }
+        for (x, y) in cells {
+            self.propose_move(x, y, &mut intents);
         }
+        self.timings.record("propose", started.elapsed());
 
-        self.board = new;
+        let started = Instant::now();
+        let actions = mem::replace(&mut intents.actions, Vec::new());
+        self.board = self.resolve_intents(intents);
+        if let Some(radius) = self.cfg.interaction_radius {
+            self.interact_in_range(radius);
+        }
+        self.timings.record("resolve", started.elapsed());
 
-        //let mut coll_iters = 0;
-        let specimens = GoodEvil::count_specimens(&self.board);
+        if self.cfg.checks >= CheckLevel::Expensive {
+            self.audit_energy(energy_before);
+        }
+
+        let started = Instant::now();
+        self.act(actions);
+        self.reproduce();
+        if self.cfg.boundary == Boundary::Open {
+            self.immigrate();
+        }
+        self.feed();
+        self.timings.record("populate", started.elapsed());
+        if self.famine_steps_left > 0 {
+            self.famine_steps_left -= 1;
+        }
+        self.iteration += 1;
+        //GoodEvil::debug_collisions(&self.board, &self.collisions);
 
-        while GoodEvil::has_collisions(&self.board) {
-            //coll_iters += 1;
-            //println!("resolve_collisions, iteration {}, {} specimens",
-                     //coll_iters, GoodEvil::count_specimens(&self.board));
+        if let Some(ref mut replay) = replay {
+            replay.advance();
+            assert!(checks::digest(&replay.board) == checks::digest(&self.board),
+                    "step {} is not deterministic", self.iteration);
+        }
+    }
 
-            assert!(GoodEvil::count_specimens(&self.board) >= specimens);
+    fn board(&self) -> &Board<Field> {
+        &self.board
+    }
 
-            self.board = GoodEvil::resolve_collisions(self.collision_energy, &mut self.rng, &self.board);
-            self.collision_energy = 0.0f32;
+    fn stats(&self) -> Stats {
+        const IN: &'static [&'static str] = &["in_left", "in_right", "in_top", "in_bottom"];
+        const OUT: &'static [&'static str] = &["out_left", "out_right", "out_top", "out_bottom"];
 
-            let energy = GoodEvil::total_energy(&self.board);
+        let mut stats = GoodEvil::collect_stats(self.iteration, &self.board);
+        if self.cfg.boundary == Boundary::Open {
+            for &edge in Edge::all() {
+                stats = stats.count(IN[edge.index()], self.flux_in[edge.index()])
+                             .count(OUT[edge.index()], self.flux_out[edge.index()]);
+            }
+        }
+        if self.cfg.max_collision_size.is_some() {
+            stats = stats.count("deflected", self.deflections);
+        }
+        if self.cfg.interaction_radius.is_some() {
+            stats = stats.count("range_groups", self.range_groups);
+        }
+        if self.cfg.reproduction.is_some() || !self.cfg.policies.is_empty() {
+            stats = stats.count("births", self.births);
+        }
+        if !self.cfg.policies.is_empty() {
+            stats = stats.real("shared", self.shared);
+        }
+        if self.cfg.dormancy.is_some() {
+            let specimens = self.specimens();
+            let dormant = specimens.iter().filter(|s| s.dormant).count();
+            let fraction = if specimens.is_empty() { 0.0 } else { dormant as f32 / specimens.len() as f32 };
+            stats = stats.real("dormant", fraction);
+        }
+        if self.cfg.predation.is_some() || self.cfg.species.is_some() {
+            stats = stats.count("kills", self.kills);
+        }
+        if let Some(ref cfg) = self.cfg.species {
+            let specimens = self.specimens();
+            for (idx, &name) in species::stat_names().iter().take(cfg.count()).enumerate() {
+                stats = stats.count(name, specimens.iter().filter(|s| s.species as usize == idx).count());
+            }
+        }
+        if self.cfg.action_costs.is_some() {
+            stats = stats.real("action_energy", self.action_energy);
+        }
+        if self.cfg.crowding.is_some() {
+            stats = stats.real("crowding_loss", self.crowding_energy);
+        }
+        if let Some(ref food) = self.food {
+            stats = stats.real("food", food.iter().fold(0.0, |sum, amount| sum + amount))
+                         .real("eaten", self.eaten);
+        }
+        if self.cfg.genetics.is_some() {
+            let genomes: Vec<Genome> = self.specimens().iter().map(|s| s.genome).collect();
+            for (idx, &(avg_name, stdev_name)) in Genome::stat_names().iter().enumerate() {
+                let values: Vec<f32> = genomes.iter().map(|genome| genome.traits()[idx]).collect();
+                let (avg, stdev) = mean_and_stdev(&values);
+                stats = stats.real(avg_name, avg)
+                             .real(stdev_name, stdev);
+            }
+        }
+        if self.cfg.reputation.is_some() {
+            let specimens = self.specimens();
+            let total = specimens.iter().fold(0.0, |sum, s| sum + s.reputation);
+            let avg = if specimens.is_empty() { 0.0 } else { total / specimens.len() as f32 };
+
+            stats = stats.count("cooperations", self.collisions.cooperations)
+                         .count("defections", self.collisions.defections)
+                         .real("avg_reputation", avg);
         }
 
-        if specimens == 0 {
-            panic!("all specimens died");
+        self.last_events.iter().fold(stats, |stats, event| stats.event(event.to_string()))
+    }
+
+    fn is_extinct(&self) -> bool {
+        GoodEvil::count_specimens(&self.board) == 0
+    }
+
+    fn drift(&self) -> Option<&DriftField> {
+        self.cfg.drift.as_ref()
+    }
+
+    fn food(&self) -> Option<&Board<f32>> {
+        self.food.as_ref()
+    }
+
+    fn density(&self) -> Option<Board<f32>> {
+        let mut density = Board::new(self.board.width, self.board.height, 0.0);
+        for ((x, y), cell) in density.enumerate_mut() {
+            *cell = GoodEvil::occupied_neighbors(x, y, &self.board) as f32 / 8.0;
         }
+        Some(density)
+    }
 
-        let energy = GoodEvil::total_energy(&self.board);
+    fn specimens(&self) -> Vec<Specimen> {
+        self.board.iter()
+                  .filter_map(|field| match *field {
+                      Field::Occupied(specimen) => Some(specimen),
+                      _ => None
+                  })
+                  .collect()
+    }
 
-        //println!("total energy = {} (+{} = {})", energy, self.collision_energy, self.collision_energy + energy);
-        self.iteration += 1;
-        GoodEvil::print_stats(self.iteration, &self.board);
-        //GoodEvil::debug_collisions(&self.board, &self.collisions);
+    fn specimen_at(&self,
+                   x: usize,
+                   y: usize) -> Option<Specimen> {
+        match *self.board.at(x, y) {
+            Field::Occupied(specimen) => Some(specimen),
+            _ => None
+        }
+    }
+
+    fn describe_cell(&self,
+                     x: usize,
+                     y: usize,
+                     lang: Language) -> Vec<String> {
+        let mut details = match *self.board.at(x, y) {
+            Field::Empty => vec![lang.tr(Message::CellEmpty).to_string()],
+            Field::Wall => vec![lang.tr(Message::CellWall).to_string()],
+            Field::Occupied(specimen) => vec![
+                lang.tr(if specimen.dormant { Message::CellDormantSpecimen } else { Message::CellSpecimen }).to_string(),
+                lang.format(Message::CellEnergy, &[&format!("{:.2}", specimen.energy)]),
+                lang.format(Message::CellAge, &[&specimen.age.to_string()]),
+                lang.format(Message::CellSpecies, &[&specimen.species.to_string()]),
+                lang.format(Message::CellGeneration, &[&specimen.generation.to_string()])
+            ]
+        };
+        if let Some(ref food) = self.food {
+            details.push(lang.format(Message::CellFood, &[&format!("{:.2}", food.at(x, y))]));
+        }
+        details
+    }
+
+    fn fairness(&self) -> Option<&FairnessAudit> {
+        self.audit.as_ref()
+    }
+
+    fn journal(&self) -> Option<&Journal> {
+        Some(&self.journal)
+    }
+
+    fn warnings(&self) -> Option<&WarningLog> {
+        Some(&self.warnings)
+    }
+
+    fn perf_report(&self) -> Option<PerfReport> {
+        Some(self.timings.report(self.iteration))
+    }
+
+    /// Energies of specimens, and births, deaths and collisions from the
+    /// journal.
+    fn record_metrics(&self,
+                      metrics: &mut Metrics) {
+        const ENERGY_BOUNDS: &'static [f64] = &[0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+
+        let specimens = self.specimens();
+        metrics.set("population", specimens.len() as f64);
+        for specimen in specimens {
+            metrics.observe("energy", ENERGY_BOUNDS, specimen.energy as f64);
+        }
+        let (mut births, mut deaths, mut collisions) = (0, 0, 0);
+        for entry in self.journal.entries() {
+            match entry.event {
+                Event::Birth { .. } => births += 1,
+                Event::Death { .. } => deaths += 1,
+                Event::Collision { .. } => collisions += 1,
+                Event::Move { .. } => ()
+            }
+        }
+        metrics.increment("births", births);
+        metrics.increment("deaths", deaths);
+        metrics.increment("collisions", collisions);
+    }
+
+    /// Merges `region` into the board at `offset` as `policy` says, see
+    /// `merge::merge_into`, returning the number of cells changed.
+    fn merge(&mut self,
+             region: &Region,
+             offset: (usize, usize),
+             policy: MergePolicy) -> usize {
+        merge::merge_into(&mut self.board, region, offset, policy)
     }
+
+    fn copy(&self,
+            area: symmetry::Region) -> Option<Region> {
+        Some(Region::copy_from(&self.board, area))
+    }
+
+    /// Brush 0 erases walls, 1 builds them, 2 places specimens with the
+    /// initial energy and 3 fills the cell with food, if there is any.
+    fn paint(&mut self,
+             x: usize,
+             y: usize,
+             brush: usize) {
+        if brush == 3 {
+            if let Some(ref mut food) = self.food {
+                *food.at_mut(x, y) = 1.0;
+            }
+            return;
+        }
+
+        let field = self.board.at_mut(x, y);
+        match (brush, field.clone()) {
+            (0, Field::Wall) => *field = Field::Empty,
+            (0, _) => (),
+            (2, Field::Empty) => *field = Field::Occupied(Specimen::new(self.cfg.initial_specimen_energy)),
+            (_, Field::Empty) => *field = Field::Wall,
+            _ => ()
+        }
+    }
+}
+
+#[cfg(test)]
+fn test_good_evil(events: Vec<WorldEvent>) -> GoodEvil {
+    use rand::SeedableRng;
+
+    let cfg = GoodEvilConfig {
+        num_specimens: 1,
+        initial_specimen_energy: 1.0,
+        initial_energy: None,
+        initial_state: None,
+        energy_loss_per_step: 0.1,
+        deadly_energy_margin: 0.0,
+        events: events,
+        random_event_probability: 0.0,
+        boundary: Boundary::Closed,
+        immigration_rate: 0.0,
+        immigrant_energy: 1.0,
+        drift: None,
+        movement: MovementPolicy::Random,
+        update_order: UpdateOrder::RowMajor,
+        conflicts: &STRONGEST_WINS,
+        fairness_audit: None,
+        action_costs: None,
+        reputation: None,
+        predation: None,
+        species: None,
+        max_collision_size: None,
+        interaction_radius: None,
+        genetics: None,
+        dormancy: None,
+        crowding: None,
+        behavior: None,
+        policies: Policies::new(),
+        reproduction: None,
+        food: None,
+        checks: CheckLevel::Paranoid
+    };
+    GoodEvil::new(4, 4, cfg, Box::new(StdRng::from_seed(&[0usize][..])))
+}
+
+#[test]
+fn test_good_evil_collisions_are_deterministic() {
+    let run = || {
+        let mut sim = test_good_evil(Vec::new());
+        sim.cfg.energy_loss_per_step = 0.01;
+        sim.cfg.reputation = Some(ReputationConfig::default());
+        sim.cfg.genetics = Some(GeneticsConfig::default());
+        for (x, y) in [(0, 0), (1, 0), (2, 1), (3, 3), (1, 2), (2, 2)].iter().cloned() {
+            *sim.board.at_mut(x, y) = Field::Occupied(Specimen::new(1.0 + x as f32 / 10.0));
+        }
+
+        let mut collisions = 0;
+        for _ in 0..30 {
+            sim.advance();
+            collisions += sim.collisions.cooperations + sim.collisions.defections;
+        }
+        (sim, collisions)
+    };
+
+    let (first, collisions) = run();
+    let (second, _) = run();
+
+    assert!(collisions > 0);
+    assert!(first.board.indices().all(|(x, y)| first.board.at(x, y) == second.board.at(x, y)));
+    assert_eq!(first.stats().to_string(), second.stats().to_string());
+}
+
+#[test]
+fn test_good_evil_meteor() {
+    let mut sim = test_good_evil(vec![WorldEvent {
+        iteration: 2,
+        kind: EventKind::Meteor { radius: 10 }
+    }]);
+
+    sim.advance();
+    assert!(!sim.is_extinct());
+    assert!(sim.stats().events.is_empty());
+
+    sim.advance();
+    assert!(sim.is_extinct());
+    assert_eq!(vec!["meteor:10".to_string()], sim.stats().events);
+
+    let mut sim = test_good_evil(vec![WorldEvent {
+        iteration: 1,
+        kind: EventKind::Meteor { radius: usize::max_value() }
+    }]);
+    sim.advance();
+    assert!(sim.is_extinct());
+}
+
+#[test]
+fn test_good_evil_famine() {
+    let mut sim = test_good_evil(vec![WorldEvent {
+        iteration: 1,
+        kind: EventKind::Famine { duration: 1 }
+    }]);
+
+    sim.advance();
+    assert!((sim.specimens()[0].energy - 0.8).abs() < 1e-5);
+    sim.advance();
+    assert!((sim.specimens()[0].energy - 0.7).abs() < 1e-5);
+}
+
+#[test]
+fn test_good_evil_open_boundary_outflow() {
+    let mut sim = test_good_evil(Vec::new());
+    sim.cfg.boundary = Boundary::Open;
+
+    let mut out = 0;
+    for _ in 0..100 {
+        sim.advance();
+        out += sim.flux_out.iter().fold(0, |sum, n| sum + n);
+    }
+
+    assert!(sim.is_extinct());
+    assert_eq!(1, out);
+}
+
+#[test]
+fn test_good_evil_open_boundary_inflow() {
+    let mut sim = test_good_evil(Vec::new());
+    sim.cfg.boundary = Boundary::Open;
+    sim.cfg.immigration_rate = 0.5;
+    sim.cfg.immigrant_energy = 2.0;
+
+    sim.advance();
+    assert_eq!(Some(Value::Count(0)), sim.stats().get("in_top"));
+    sim.advance();
+
+    let stats = sim.stats();
+    let inflow = ["in_left", "in_right", "in_top", "in_bottom"].iter()
+                     .fold(0.0, |sum, name| sum + stats.get(name).unwrap().as_f32());
+    assert!(inflow >= 1.0);
+    assert!(sim.specimens().iter().any(|s| s.energy == 2.0));
+}
+
+#[test]
+fn test_good_evil_drift() {
+    let mut sim = test_good_evil(Vec::new());
+    sim.cfg.drift = Some(DriftField::uniform(4, 4, (10.0, 0.0)));
+
+    for _ in 0..5 {
+        sim.advance();
+    }
+
+    let column = sim.board.indices().find(|&(x, y)| *sim.board.at(x, y) != Field::Empty).unwrap().0;
+    assert_eq!(3, column);
+}
+
+#[test]
+fn test_good_evil_gradient_movement() {
+    use rand::SeedableRng;
+
+    let mut board = Board::new(3, 3, Field::Empty);
+    *board.at_mut(0, 1) = Field::Occupied(Specimen::new(5.0));
+    let mut food = Board::new(3, 3, 0.0);
+    *food.at_mut(2, 1) = 1.0;
+
+    let mut sim = test_good_evil(Vec::new());
+    sim.cfg.movement = MovementPolicy::Gradient;
+    let mut rng = StdRng::from_seed(&[0usize][..]);
+    let specimen = Specimen::new(1.0);
+
+    let mut columns = [0; 3];
+    for _ in 0..1000 {
+        let (x, _) = GoodEvil::get_new_coords_directed(1, 1, &specimen, &board, Some(&food), &sim.cfg,
+                                                       false, &mut rng);
+        columns[x as usize] += 1;
+    }
+    assert!(columns[2] > 9 * (columns[0] + columns[1]));
+}
+
+#[test]
+fn test_split_energy_reciprocal() {
+    use rand::SeedableRng;
+
+    let cfg = ReputationConfig { defection_probability: 0.0, learning_rate: 0.5, .. ReputationConfig::default() };
+    let mut rng = StdRng::from_seed(&[0usize][..]);
+    let mut log = CollisionLog::default();
+
+    let good = vec![Specimen::new(1.0), Specimen { energy: 2.0, reputation: 0.5, ..Specimen::new(0.0) }];
+    let shared = GoodEvil::split_energy_reciprocal(&good, 2.0, &cfg, &mut log, &mut rng);
+    assert_eq!(vec![Specimen::new(2.0), Specimen { energy: 3.0, reputation: 0.75, ..Specimen::new(0.0) }], shared);
+
+    let bad = vec![Specimen { energy: 1.0, reputation: -0.5, ..Specimen::new(0.0) }, Specimen::new(2.0)];
+    let taken = GoodEvil::split_energy_reciprocal(&bad, 2.0, &cfg, &mut log, &mut rng);
+    assert_eq!(vec![Specimen { energy: 1.0, reputation: -0.5, ..Specimen::new(0.0) }, Specimen::new(4.0)], taken);
+
+    assert_eq!(CollisionLog { cooperations: 1, defections: 1 }, log);
+}
+
+#[test]
+fn test_good_evil_collision_size_limit() {
+    let mut sim = test_good_evil(Vec::new());
+    sim.cfg.max_collision_size = Some(2);
+
+    let mut intents = Intents::new(4, 4);
+    for _ in 0..3 {
+        sim.propose_limited(Specimen::new(1.0), (0, 0), 0, 0, &mut intents);
+    }
+
+    assert_eq!(2, intents.contenders(0, 0).len());
+    assert_eq!(3, intents.intents.len());
+    assert_eq!(1, sim.deflections);
+}
+
+#[test]
+fn test_good_evil_collision_limit_warning() {
+    let mut sim = test_good_evil(Vec::new());
+    sim.cfg.max_collision_size = Some(1);
+    assert!(sim.warnings().unwrap().is_empty());
+
+    // only 4 cells are around the corner
+    let mut intents = Intents::new(4, 4);
+    for _ in 0..5 {
+        sim.propose_limited(Specimen::new(1.0), (0, 0), 0, 0, &mut intents);
+    }
+
+    assert_eq!(2, intents.contenders(0, 0).len());
+    let warnings = sim.warnings().unwrap().warnings();
+    assert_eq!(1, warnings.len());
+    assert_eq!((WarningKind::CollisionLimitExceeded, 1), (warnings[0].kind, warnings[0].count));
+}
+
+#[cfg(test)]
+fn test_intents(moves: &[(f32, (usize, usize), (usize, usize))]) -> Intents {
+    let mut intents = Intents::new(3, 3);
+    for &(energy, origin, target) in moves {
+        intents.push(Intent {
+            specimen: Specimen::new(energy),
+            origin: origin,
+            target: target
+        });
+    }
+    intents
+}
+
+#[test]
+fn test_good_evil_losers_stay_put() {
+    let mut sim = test_good_evil(Vec::new());
+    sim.board = Board::new(3, 3, Field::Empty);
+    sim.collision_energy = 0.0;
+
+    // the stronger specimen wins the cell of one staying put, but has to go
+    // back, sending back the one that took its own cell in turn
+    let intents = test_intents(&[(2.0, (0, 0), (1, 0)),
+                                 (1.0, (1, 0), (1, 0)),
+                                 (1.0, (2, 0), (0, 0))]);
+    let new = sim.resolve_intents(intents);
+
+    assert_eq!(&Field::Occupied(Specimen::new(2.0)), new.at(0, 0));
+    assert_eq!(&Field::Occupied(Specimen::new(1.0)), new.at(1, 0));
+    assert_eq!(&Field::Occupied(Specimen::new(1.0)), new.at(2, 0));
+    assert_eq!(3, GoodEvil::count_specimens(&new));
+}
+
+#[test]
+fn test_good_evil_action_costs() {
+    let mut sim = test_good_evil(Vec::new());
+    sim.cfg.energy_loss_per_step = 0.0;
+    sim.cfg.action_costs = Some(ActionCosts { stay: 0.0, orthogonal: 0.25, diagonal: 0.5 });
+
+    for _ in 0..5 {
+        sim.board = Board::new(4, 4, Field::Empty);
+        *sim.board.at_mut(1, 1) = Field::Occupied(Specimen::new(1.0));
+        sim.advance();
+
+        let (x, y) = sim.board.indices().find(|&(x, y)| *sim.board.at(x, y) != Field::Empty).unwrap();
+        let expected = match (x != 1, y != 1) {
+            (false, false) => 1.0,
+            (true, true) => 0.5,
+            _ => 0.75
+        };
+        assert_eq!(expected, sim.specimens()[0].energy);
+        assert_eq!(Some(Value::Real(1.0 - expected)), sim.stats().get("action_energy"));
+    }
+}
+
+#[test]
+fn test_good_evil_journal() {
+    let mut sim = test_good_evil(Vec::new());
+    sim.board = Board::new(4, 4, Field::Empty);
+    *sim.board.at_mut(0, 0) = Field::Occupied(Specimen::new(0.05));
+    *sim.board.at_mut(2, 2) = Field::Occupied(Specimen::new(1.0));
+
+    for iteration in 1..6 {
+        let before = sim.board.indices()
+                              .find(|&(x, y)| (x, y) != (0, 0) && *sim.board.at(x, y) != Field::Empty)
+                              .unwrap();
+        sim.advance();
+        let after = sim.board.indices().find(|&(x, y)| *sim.board.at(x, y) != Field::Empty).unwrap();
+
+        let journal = sim.journal().unwrap();
+        assert!(journal.entries().iter().all(|entry| entry.iteration == iteration));
+        let mut events: Vec<Event> = journal.entries().iter().map(|entry| entry.event).collect();
+        if iteration == 1 {
+            assert_eq!(Event::Death { position: (0, 0), energy: 0.05 - 0.1 }, events.remove(0));
+        }
+        let expected = if before == after { Vec::new() } else { vec![Event::Move { from: before, to: after }] };
+        assert_eq!(expected, events);
+    }
+}
+
+#[test]
+fn test_good_evil_crowding() {
+    let mut sim = test_good_evil(Vec::new());
+    sim.cfg.energy_loss_per_step = 0.0;
+    sim.cfg.crowding = Some(CrowdingConfig { coefficient: 0.25, capacity: 1 });
+    sim.board = Board::new(4, 4, Field::Empty);
+    for &(x, y) in [(0, 0), (1, 0), (0, 1), (3, 3)].iter() {
+        *sim.board.at_mut(x, y) = Field::Occupied(Specimen::new(1.0));
+    }
+
+    let density = sim.density().unwrap();
+    assert_eq!(2.0 / 8.0, *density.at(0, 0));
+    assert_eq!(3.0 / 8.0, *density.at(1, 1));
+    assert_eq!(0.0, *density.at(3, 3));
+
+    // each of the three clustered specimens has one neighbor too many
+    sim.advance();
+    assert_eq!(Some(Value::Real(0.75)), sim.stats().get("crowding_loss"));
+}
+
+#[test]
+fn test_good_evil_interaction_radius() {
+    let mut sim = test_good_evil(Vec::new());
+    sim.cfg.interaction_radius = Some(2);
+    sim.board = Board::new(6, 6, Field::Empty);
+    for &(x, y) in [(0, 0), (2, 0), (0, 2), (5, 5)].iter() {
+        *sim.board.at_mut(x, y) = Field::Occupied(Specimen::new(1.0));
+    }
+    sim.collision_energy = 0.3;
+
+    // the three specimens in range share the energy, the far one gets none
+    sim.interact_in_range(2);
+    for &(x, y) in [(0, 0), (2, 0), (0, 2)].iter() {
+        assert!((sim.board.at(x, y).specimens()[0].energy - 1.1).abs() < 1e-5);
+    }
+    assert_eq!(1.0, sim.board.at(5, 5).specimens()[0].energy);
+    assert_eq!(0.0, sim.collision_energy);
+    assert_eq!(1, sim.range_groups);
+}
+
+#[test]
+fn test_good_evil_perf_report() {
+    let mut sim = test_good_evil(Vec::new());
+    sim.advance();
+    sim.advance();
+
+    let report = sim.perf_report().unwrap();
+    assert_eq!(2, report.ticks);
+    let phases: Vec<(&str, usize)> = report.phases.iter().map(|phase| (phase.name, phase.calls)).collect();
+    assert_eq!(vec![("propose", 2), ("resolve", 2), ("populate", 2)], phases);
+}
+
+#[cfg(test)]
+struct Seeds;
+
+#[cfg(test)]
+impl CellRule for Seeds {
+    fn next(&self,
+            alive: bool,
+            alive_neighbors: usize) -> bool {
+        !alive && alive_neighbors == 2
+    }
+}
+
+#[test]
+fn test_game_of_life_rule() {
+    let mut board = Board::new(6, 6, false);
+    *board.at_mut(2, 2) = true;
+    *board.at_mut(3, 2) = true;
+    let mut sim = GameOfLife::new(board).with_rule(Arc::new(Seeds));
+
+    sim.advance();
+    let alive: Vec<(usize, usize)> = sim.board().indices().filter(|&(x, y)| *sim.board().at(x, y)).collect();
+    assert_eq!(vec![(2, 1), (3, 1), (2, 3), (3, 3)], alive);
+}
+
+#[test]
+fn test_game_of_life_sparse() {
+    use demo::board_from_pattern;
+
+    // a glider crossing the board edges
+    let board = board_from_pattern((8, 7), &[".O", "..O", "OOO"], (4, 3), true, false);
+    let mut dense = GameOfLife::new(board.clone()).with_storage(Storage::Dense);
+    let mut sparse = GameOfLife::new(board).with_storage(Storage::Sparse);
+    assert!(sparse.is_sparse() && !dense.is_sparse());
+
+    for _ in 0..20 {
+        dense.advance();
+        sparse.advance();
+        assert!(dense.board().iter().eq(sparse.board().iter()));
+        assert_eq!(5, sparse.stats().get("alive").unwrap().as_f32() as usize);
+    }
+
+    // Seeds brings no cells to life from nothing either, so it can be sparse
+    let seeds = GameOfLife::new(Board::new(4, 4, false)).with_rule(Arc::new(Seeds));
+    assert!(seeds.with_storage(Storage::Sparse).is_sparse());
+}
+
+#[test]
+fn test_game_of_life_paint() {
+    let mut sim = GameOfLife::new(Board::new(4, 4, false)).with_storage(Storage::Sparse);
+    sim.paint(1, 1, 1);
+    sim.paint(2, 1, 1);
+    sim.paint(2, 1, 0);
+
+    assert!(*sim.board().at(1, 1) && !*sim.board().at(2, 1));
+    assert_eq!(1, sim.stats().get("alive").unwrap().as_f32() as usize);
+}
+
+#[cfg(test)]
+struct MoveRight;
+
+#[cfg(test)]
+impl SpecimenBehavior for MoveRight {
+    fn choose_move(&self,
+                   specimen: &Specimen,
+                   _x: usize,
+                   _y: usize,
+                   _neighbors: usize) -> Option<(i64, i64)> {
+        if specimen.energy > 0.5 { Some((5, 0)) } else { None }
+    }
+}
+
+#[test]
+fn test_good_evil_behavior() {
+    let mut sim = test_good_evil(Vec::new());
+    sim.cfg.behavior = Some(Arc::new(MoveRight));
+    sim.board = Board::new(3, 1, Field::Empty);
+    *sim.board.at_mut(0, 0) = Field::Occupied(Specimen::new(1.0));
+
+    sim.advance();
+    assert_eq!(1, sim.board.at(1, 0).specimens().len());
+    sim.advance();
+    sim.advance();
+    // the closed edge stops it
+    assert_eq!(1, sim.board.at(2, 0).specimens().len());
+}
+
+/// Shares with the specimen to the right if there is one, and reproduces
+/// otherwise.
+#[cfg(test)]
+struct ShareRight;
+
+#[cfg(test)]
+impl ::policy::Policy for ShareRight {
+    fn decide(&self,
+              senses: &Senses,
+              _rng: &mut StdRng) -> Action {
+        match senses.neighbor(1, 0) {
+            Neighbor::Specimen { .. } => Action::Share { dx: 1, dy: 0, amount: 0.25 },
+            _ => Action::Reproduce
+        }
+    }
+}
+
+#[cfg(test)]
+struct Stay;
+
+#[cfg(test)]
+impl ::policy::Policy for Stay {
+    fn decide(&self,
+              _senses: &Senses,
+              _rng: &mut StdRng) -> Action {
+        Action::Stay
+    }
+}
+
+#[test]
+fn test_good_evil_policies() {
+    let mut sim = test_good_evil(Vec::new());
+    sim.cfg.policies.register(0, Arc::new(Stay));
+    sim.cfg.policies.register(1, Arc::new(ShareRight));
+    sim.board = Board::new(3, 1, Field::Empty);
+    *sim.board.at_mut(0, 0) = Field::Occupied(Specimen { species: 1, ..Specimen::new(1.0) });
+    *sim.board.at_mut(1, 0) = Field::Occupied(Specimen::new(1.0));
+
+    sim.advance();
+    let (giver, receiver) = (sim.board.at(0, 0).specimens()[0], sim.board.at(1, 0).specimens()[0]);
+    assert!((receiver.energy - giver.energy - 0.5).abs() < 1e-5);
+    assert_eq!(Some(Value::Real(0.25)), sim.stats().get("shared"));
+
+    // alone, it splits off a child onto the only empty cell
+    sim.cfg.reproduction = Some(ReproductionConfig { threshold: 0.0, child_share: 0.5, cooldown: 1 });
+    *sim.board.at_mut(1, 0) = Field::Empty;
+    *sim.board.at_mut(2, 0) = Field::Wall;
+    sim.advance();
+    assert_eq!(Some(Value::Count(1)), sim.stats().get("births"));
+    assert_eq!(1, sim.board.at(1, 0).specimens().len());
+    assert_eq!(1, sim.board.at(1, 0).specimens()[0].species);
+}
+
+#[test]
+fn test_good_evil_policy_reproduction_rules() {
+    let mut sim = test_good_evil(Vec::new());
+    sim.cfg.energy_loss_per_step = 0.0;
+    sim.cfg.policies.register(0, Arc::new(ShareRight));
+    sim.board = Board::new(2, 1, Field::Empty);
+    *sim.board.at_mut(0, 0) = Field::Occupied(Specimen::new(1.0));
+
+    // reproduction is disabled
+    sim.advance();
+    assert_eq!(Some(Value::Count(0)), sim.stats().get("births"));
+
+    // below the threshold
+    sim.cfg.reproduction = Some(ReproductionConfig { threshold: 1.0, child_share: 0.5, cooldown: 0 });
+    sim.advance();
+    assert_eq!(Some(Value::Count(0)), sim.stats().get("births"));
+
+    // below the threshold in the genome, which takes precedence
+    sim.cfg.reproduction = Some(ReproductionConfig { threshold: 0.5, child_share: 0.5, cooldown: 0 });
+    sim.cfg.genetics = Some(GeneticsConfig { mutation_rate: 0.0, mutation_size: 0.1 });
+    sim.advance();
+    assert_eq!(Some(Value::Count(0)), sim.stats().get("births"));
+    assert_eq!(1, sim.specimens().len());
+}
+
+#[test]
+fn test_good_evil_dormancy() {
+    let mut sim = test_good_evil(Vec::new());
+    sim.cfg.dormancy = Some(DormancyConfig { threshold: 0.5, loss_factor: 0.1, wake_food: 0.5 });
+    sim.board = Board::new(4, 4, Field::Empty);
+    *sim.board.at_mut(0, 0) = Field::Occupied(Specimen::new(0.4));
+
+    sim.advance();
+    let specimen = sim.board.at(0, 0).specimens()[0];
+    assert!(specimen.dormant);
+    // lost a tenth of the usual 0.1
+    assert!((specimen.energy - 0.39).abs() < 1e-5);
+    assert_eq!(Some(Value::Real(1.0)), sim.stats().get("dormant"));
+
+    // an active neighbor wakes it up
+    *sim.board.at_mut(1, 1) = Field::Occupied(Specimen::new(1.0));
+    sim.advance();
+    assert!(sim.specimens().iter().all(|s| !s.dormant));
+}
+
+#[test]
+fn test_good_evil_predation() {
+    let mut sim = test_good_evil(Vec::new());
+    sim.board = Board::new(3, 3, Field::Empty);
+    sim.collision_energy = 0.0;
+    sim.cfg.predation = Some(PredationConfig { margin: 1.0, efficiency: 0.5 });
+
+    let new = sim.resolve_intents(test_intents(&[(0.5, (0, 1), (1, 1)), (2.0, (2, 1), (1, 1))]));
+
+    assert_eq!(1, sim.kills);
+    assert_eq!(&Field::Occupied(Specimen::new(2.25)), new.at(1, 1));
+    assert_eq!(1, GoodEvil::count_specimens(&new));
+}
+
+#[test]
+fn test_good_evil_species_collisions() {
+    use species::Interaction;
+
+    let mut sim = test_good_evil(Vec::new());
+    sim.board = Board::new(3, 3, Field::Empty);
+    sim.collision_energy = 0.0;
+    sim.cfg.species = Some(SpeciesConfig {
+        interactions: vec![vec![Interaction::Share, Interaction::Fight],
+                           vec![Interaction::Fight, Interaction::Share]]
+    });
+
+    let mut intents = test_intents(&[(0.5, (0, 1), (1, 1)), (1.0, (2, 1), (1, 1))]);
+    intents.intents[0].specimen.species = 1;
+    let new = sim.resolve_intents(intents);
+
+    assert_eq!(1, sim.kills);
+    assert_eq!(&Field::Occupied(Specimen::new(1.5)), new.at(1, 1));
+    assert_eq!(1, GoodEvil::count_specimens(&new));
+}
+
+#[test]
+fn test_good_evil_describe_cell() {
+    let mut sim = test_good_evil(Vec::new());
+    sim.board = Board::new(2, 1, Field::Empty);
+    *sim.board.at_mut(0, 0) = Field::Occupied(Specimen { age: 7, species: 1, ..Specimen::new(1.5) });
+
+    assert_eq!(vec!["specimen", "energy 1.50", "age 7", "species 1", "generation 0"],
+               sim.describe_cell(0, 0, Language::English));
+    assert_eq!(vec!["pusta"], sim.describe_cell(1, 0, Language::Polish));
+}
+
+#[test]
+fn test_good_evil_walls() {
+    use rand::SeedableRng;
+
+    let mut walls = Board::new(4, 4, true);
+    *walls.at_mut(1, 1) = false;
+    *walls.at_mut(2, 1) = false;
+    *walls.at_mut(2, 2) = false;
+    let mut sim = test_good_evil(Vec::new());
+    sim.cfg.energy_loss_per_step = 0.0;
+    let mut sim = GoodEvil::with_walls(&walls, sim.cfg, vec![Specimen::new(1.0); 3],
+                                       Box::new(StdRng::from_seed(&[0usize][..])));
+
+    for _ in 0..20 {
+        sim.advance();
+        assert_eq!(3, sim.specimens().len());
+        for (x, y) in sim.board.indices() {
+            assert_eq!(*walls.at(x, y), *sim.board.at(x, y) == Field::Wall);
+        }
+    }
+
+    sim.paint(1, 1, 0);
+    sim.paint(0, 0, 0);
+    assert_eq!(&Field::Empty, sim.board.at(0, 0));
+
+    sim.paint(0, 0, 2);
+    assert_eq!(&Field::Occupied(Specimen::new(sim.cfg.initial_specimen_energy)), sim.board.at(0, 0));
+    sim.paint(0, 0, 1);
+    assert_eq!(&Field::Occupied(Specimen::new(sim.cfg.initial_specimen_energy)), sim.board.at(0, 0));
+}
+
+#[test]
+fn test_good_evil_fairness_audit() {
+    let mut sim = test_good_evil(Vec::new());
+    sim.cfg.update_order = UpdateOrder::Shuffled;
+    sim.audit = Some(FairnessAudit::new((4, 4), 2));
+    sim.board = Board::new(4, 4, Field::Empty);
+    for &(x, y) in &[(0, 0), (1, 0), (0, 1), (1, 1), (2, 2), (3, 3)] {
+        *sim.board.at_mut(x, y) = Field::Occupied(Specimen::new(1.0));
+    }
+
+    for _ in 0..50 {
+        sim.advance();
+    }
+
+    let audit = sim.fairness().unwrap();
+    let contests = audit.rank_win_rates().into_iter().filter(|rate| rate.is_some()).count();
+    assert!(contests >= 2);
+}
+
+#[test]
+fn test_good_evil_reproduction() {
+    let mut sim = test_good_evil(Vec::new());
+    sim.cfg.energy_loss_per_step = 0.0;
+    sim.cfg.reproduction = Some(ReproductionConfig { threshold: 1.0, child_share: 0.25, cooldown: 2 });
+    sim.board = Board::new(4, 4, Field::Empty);
+    *sim.board.at_mut(1, 1) = Field::Occupied(Specimen::new(2.0));
+
+    sim.advance();
+    assert_eq!(Some(Value::Count(1)), sim.stats().get("births"));
+    let mut energies: Vec<f32> = sim.specimens().iter().map(|s| s.energy).collect();
+    energies.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(vec![0.5, 1.5], energies);
+    let mut lineage: Vec<(usize, usize)> = sim.specimens().iter().map(|s| (s.age, s.generation)).collect();
+    lineage.sort();
+    assert_eq!(vec![(0, 1), (1, 0)], lineage);
+
+    // both parent and child are cooling down
+    sim.advance();
+    assert_eq!(Some(Value::Count(0)), sim.stats().get("births"));
+    let mut ages: Vec<usize> = sim.specimens().iter().map(|s| s.age).collect();
+    ages.sort();
+    assert_eq!(vec![1, 2], ages);
+}
+
+#[test]
+fn test_good_evil_reproduction_without_cooldown() {
+    let mut sim = test_good_evil(Vec::new());
+    sim.cfg.energy_loss_per_step = 0.0;
+    sim.cfg.reproduction = Some(ReproductionConfig { threshold: 0.0, child_share: 0.5, cooldown: 0 });
+    sim.board = Board::new(4, 4, Field::Empty);
+    *sim.board.at_mut(0, 0) = Field::Occupied(Specimen::new(8.0));
+
+    // children landing after their parents in raster order don't split
+    // until the next step
+    sim.advance();
+    assert_eq!(Some(Value::Count(1)), sim.stats().get("births"));
+    assert_eq!(2, sim.specimens().len());
+
+    sim.advance();
+    assert_eq!(Some(Value::Count(2)), sim.stats().get("births"));
+    assert_eq!(4, sim.specimens().len());
+}
+
+#[test]
+fn test_good_evil_children_inherit_genome() {
+    let genome = Genome { aggression: 0.9, metabolism: 0.5, reproduction_threshold: 1.0 };
+
+    let mut sim = test_good_evil(Vec::new());
+    sim.cfg.energy_loss_per_step = 0.0;
+    sim.cfg.genetics = Some(GeneticsConfig { mutation_rate: 0.0, mutation_size: 0.1 });
+    sim.cfg.reproduction = Some(ReproductionConfig::default());
+    sim.board = Board::new(4, 4, Field::Empty);
+    // only the first one passes its own reproduction threshold
+    *sim.board.at_mut(0, 0) = Field::Occupied(Specimen { genome: genome, ..Specimen::new(1.2) });
+    *sim.board.at_mut(3, 3) = Field::Occupied(Specimen::new(1.2));
+
+    sim.advance();
+    let specimens = sim.specimens();
+    assert_eq!(3, specimens.len());
+    assert_eq!(2, specimens.iter().filter(|s| s.genome == genome).count());
+}
+
+#[test]
+fn test_good_evil_food() {
+    let mut sim = test_good_evil(Vec::new());
+    sim.cfg.food = Some(FoodConfig { initial_density: 1.0, regrowth_rate: 0.5, energy: 0.5 });
+    sim.food = Some(Board::new(4, 4, 1.0));
+
+    sim.advance();
+    // lost 0.1, ate a full cell
+    assert!((sim.specimens()[0].energy - 1.4).abs() < 1e-5);
+    assert_eq!(Some(Value::Real(15.0)), sim.stats().get("food"));
+
+    // lost energy is not recycled
+    sim.advance();
+    assert_eq!(0.0, sim.collision_energy);
 }
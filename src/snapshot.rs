@@ -0,0 +1,113 @@
+//! PNG images of the board taken on request or every few steps, drawn
+//! straight from the board rather than read back from the window.
+
+use offscreen::Image;
+use palette::CellColor;
+use simulation::Simulation;
+use symmetry::Region;
+use theme::Theme;
+
+/// Placeholder in file name templates replaced with the iteration number.
+pub const ITERATION_PLACEHOLDER: &'static str = "{iteration}";
+
+pub struct Snapshots {
+    /// Path of the images, with `ITERATION_PLACEHOLDER` in it.
+    template: String,
+    /// Steps between automatic snapshots, none if not set.
+    every: Option<usize>,
+    /// Pixels per cell side.
+    cell_size: usize,
+    theme: Theme
+}
+
+impl Snapshots {
+    /// Fails if `template` doesn't contain `ITERATION_PLACEHOLDER`, as all
+    /// snapshots would then overwrite each other.
+    pub fn new(template: &str,
+               cell_size: usize) -> Result<Snapshots, String> {
+        assert!(cell_size >= 1);
+        if !template.contains(ITERATION_PLACEHOLDER) {
+            return Err(format!("expected {} in the file name", ITERATION_PLACEHOLDER));
+        }
+
+        Ok(Snapshots {
+            template: template.to_string(),
+            every: None,
+            cell_size: cell_size,
+            theme: Theme::default()
+        })
+    }
+
+    pub fn with_interval(self,
+                         every: usize) -> Snapshots {
+        assert!(every >= 1);
+        Snapshots {
+            every: Some(every),
+            ..self
+        }
+    }
+
+    pub fn with_theme(self,
+                      theme: Theme) -> Snapshots {
+        Snapshots {
+            theme: theme,
+            ..self
+        }
+    }
+
+    /// File name of the snapshot taken after `iteration` steps. The number
+    /// is padded with zeros, so that the files sort by iteration.
+    pub fn path(&self,
+                iteration: usize) -> String {
+        self.template.replace(ITERATION_PLACEHOLDER, &format!("{:06}", iteration))
+    }
+
+    pub fn take<T, S>(&self,
+                      simulation: &S,
+                      iteration: usize) -> Result<String, String> where S: Simulation<T>, T: CellColor {
+        let path = self.path(iteration);
+        let image = Image::render_themed(simulation, Region::whole(simulation.board()), self.cell_size, &self.theme);
+        try!(image.save_png(&path));
+        Ok(path)
+    }
+
+    /// Takes a snapshot if one is due after `iteration` steps. Returns the
+    /// path of the image, if taken.
+    pub fn after_step<T, S>(&self,
+                            simulation: &S,
+                            iteration: usize) -> Result<Option<String>, String> where S: Simulation<T>,
+                                                                                     T: CellColor {
+        match self.every {
+            Some(every) if iteration % every == 0 => self.take(simulation, iteration).map(Some),
+            _ => Ok(None)
+        }
+    }
+}
+
+#[test]
+fn test_snapshot_path() {
+    let snapshots = Snapshots::new("shots/life-{iteration}.png", 1).unwrap();
+    assert_eq!("shots/life-000042.png", snapshots.path(42));
+    assert!(Snapshots::new("life.png", 1).is_err());
+}
+
+#[test]
+fn test_snapshot_every() {
+    use std::env;
+    use std::fs;
+    use board::Board;
+    use simulation::GameOfLife;
+
+    let dir = env::temp_dir().join("cell-test-snapshot");
+    fs::create_dir_all(&dir).unwrap();
+    let template = dir.join("{iteration}.png");
+    let snapshots = Snapshots::new(template.to_str().unwrap(), 2).unwrap().with_interval(5);
+    let simulation = GameOfLife::new(Board::new(3, 3, false));
+
+    assert_eq!(None, snapshots.after_step(&simulation, 4).unwrap());
+    let path = snapshots.after_step(&simulation, 10).unwrap().unwrap();
+    assert_eq!(dir.join("000010.png").to_str().unwrap(), path);
+    assert!(fs::metadata(&path).unwrap().len() > 0);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
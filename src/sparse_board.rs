@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::collections::hash_map;
+
+use board::{self, Board, Indices2D};
+
+/// How a simulation stores its board.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Storage {
+    /// Every cell, see `Board`.
+    Dense,
+    /// Only the cells that differ from the default, see `SparseBoard`.
+    Sparse,
+    /// Sparse for big boards with few occupied cells, dense otherwise.
+    Auto
+}
+
+/// Boards with fewer cells are stored densely by `Storage::Auto`.
+const AUTO_MIN_CELLS: usize = 1 << 16;
+/// Boards with a larger fraction of occupied cells are stored densely too.
+const AUTO_MAX_DENSITY: f64 = 0.05;
+
+impl Storage {
+    pub fn from_name(name: &str) -> Option<Storage> {
+        match name {
+            "dense" => Some(Storage::Dense),
+            "sparse" => Some(Storage::Sparse),
+            "auto" => Some(Storage::Auto),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Storage::Dense => "dense",
+            Storage::Sparse => "sparse",
+            Storage::Auto => "auto"
+        }
+    }
+
+    /// Whether a board of `cells` cells, `occupied` of them set, is stored
+    /// sparsely.
+    pub fn is_sparse(&self,
+                     cells: usize,
+                     occupied: usize) -> bool {
+        match *self {
+            Storage::Dense => false,
+            Storage::Sparse => true,
+            Storage::Auto => cells >= AUTO_MIN_CELLS
+                             && (occupied as f64) < cells as f64 * AUTO_MAX_DENSITY
+        }
+    }
+}
+
+/// Board keeping only the cells that differ from a default value, so that
+/// going through a mostly empty board costs as much as its occupied cells.
+#[derive(Clone)]
+pub struct SparseBoard<T> {
+    cells: HashMap<(usize, usize), T>,
+    default: T,
+    pub width: usize,
+    pub height: usize
+}
+
+impl<T: Clone + PartialEq> SparseBoard<T> {
+    pub fn new(width: usize,
+               height: usize,
+               default: T) -> SparseBoard<T> {
+        SparseBoard {
+            cells: HashMap::new(),
+            default: default,
+            width: width,
+            height: height
+        }
+    }
+
+    pub fn from_board(board: &Board<T>,
+                      default: T) -> SparseBoard<T> {
+        let mut sparse = SparseBoard::new(board.width, board.height, default);
+        for (x, y) in board.indices() {
+            sparse.set(x, y, board.at(x, y).clone());
+        }
+        sparse
+    }
+
+    pub fn at(&self,
+              x: usize,
+              y: usize) -> &T {
+        assert!(x < self.width && y < self.height);
+        self.cells.get(&(x, y)).unwrap_or(&self.default)
+    }
+
+    /// Sets a cell. There is no `at_mut`, as cells set back to the default
+    /// have to be dropped.
+    pub fn set(&mut self,
+               x: usize,
+               y: usize,
+               value: T) {
+        assert!(x < self.width && y < self.height);
+        if value == self.default {
+            self.cells.remove(&(x, y));
+        } else {
+            self.cells.insert((x, y), value);
+        }
+    }
+
+    pub fn indices(&self) -> Indices2D {
+        board::indices_2d(self.width, self.height)
+    }
+
+    /// Cells that differ from the default, in no particular order.
+    pub fn occupied(&self) -> hash_map::Iter<(usize, usize), T> {
+        self.cells.iter()
+    }
+
+    /// Number of cells that differ from the default.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn to_board(&self) -> Board<T> {
+        let mut board = Board::new(self.width, self.height, self.default.clone());
+        for (&(x, y), value) in &self.cells {
+            *board.at_mut(x, y) = value.clone();
+        }
+        board
+    }
+}
+
+#[test]
+fn test_sparse_board_set() {
+    let mut board = SparseBoard::new(4, 3, 0);
+    board.set(1, 2, 5);
+    board.set(3, 0, 7);
+    board.set(3, 0, 0);
+
+    assert_eq!(5, *board.at(1, 2));
+    assert_eq!(0, *board.at(3, 0));
+    assert_eq!(1, board.len());
+    assert_eq!(vec![((1, 2), 5)], board.occupied().map(|(&pos, &value)| (pos, value)).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_sparse_board_round_trip() {
+    let mut dense = Board::new(3, 2, false);
+    *dense.at_mut(2, 1) = true;
+
+    let sparse = SparseBoard::from_board(&dense, false);
+    assert_eq!(1, sparse.len());
+    assert!(sparse.to_board().iter().eq(dense.iter()));
+}
+
+#[test]
+fn test_storage_auto() {
+    assert!(!Storage::Auto.is_sparse(100, 0));
+    assert!(Storage::Auto.is_sparse(1000 * 1000, 100));
+    assert!(!Storage::Auto.is_sparse(1000 * 1000, 300 * 1000));
+    assert!(Storage::Sparse.is_sparse(100, 50));
+}
@@ -0,0 +1,74 @@
+use board::Board;
+
+/// Points on a board sorted into square buckets, so that points near a
+/// given one can be found without scanning the whole board.
+pub struct SpatialIndex {
+    bucket_size: usize,
+    buckets: Board<Vec<usize>>,
+    points: Vec<(usize, usize)>
+}
+
+impl SpatialIndex {
+    pub fn new(width: usize,
+               height: usize,
+               bucket_size: usize,
+               points: Vec<(usize, usize)>) -> SpatialIndex {
+        let bucket_size = if bucket_size == 0 { 1 } else { bucket_size };
+        let mut buckets = Board::new((width + bucket_size - 1) / bucket_size,
+                                     (height + bucket_size - 1) / bucket_size,
+                                     Vec::new());
+        for (idx, &(x, y)) in points.iter().enumerate() {
+            buckets.at_mut(x / bucket_size, y / bucket_size).push(idx);
+        }
+
+        SpatialIndex {
+            bucket_size: bucket_size,
+            buckets: buckets,
+            points: points
+        }
+    }
+
+    pub fn points(&self) -> &[(usize, usize)] {
+        &self.points
+    }
+
+    /// Indices of points not farther than `radius` from (x, y), including
+    /// any lying on (x, y) itself, in ascending order.
+    pub fn within(&self,
+                  x: usize,
+                  y: usize,
+                  radius: usize) -> Vec<usize> {
+        let first = |v: usize| v.saturating_sub(radius) / self.bucket_size;
+        let last = |v: usize, buckets: usize| ::std::cmp::min(v.saturating_add(radius) / self.bucket_size, buckets - 1);
+        let max_squared = (radius as u64).saturating_mul(radius as u64);
+
+        let mut found = Vec::new();
+        for by in first(y)..last(y, self.buckets.height) + 1 {
+            for bx in first(x)..last(x, self.buckets.width) + 1 {
+                for &idx in self.buckets.at(bx, by) {
+                    let (px, py) = self.points[idx];
+                    let dx = px as i64 - x as i64;
+                    let dy = py as i64 - y as i64;
+                    if ((dx * dx + dy * dy) as u64) <= max_squared {
+                        found.push(idx);
+                    }
+                }
+            }
+        }
+        found.sort();
+        found
+    }
+}
+
+#[test]
+fn test_spatial_index_within() {
+    let index = SpatialIndex::new(10, 10, 3, vec![(0, 0), (2, 2), (3, 0), (9, 9), (5, 5)]);
+
+    assert_eq!(vec![0, 1, 2], index.within(0, 0, 3));
+    assert_eq!(vec![0], index.within(0, 0, 2));
+    assert_eq!(vec![0, 1], index.within(1, 1, 2));
+    assert_eq!(vec![3], index.within(9, 9, 1));
+    assert_eq!(vec![4], index.within(5, 5, 0));
+    assert_eq!(vec![1, 4], index.within(4, 4, 3));
+    assert_eq!(vec![0, 1, 2, 3, 4], index.within(9, 9, ::std::usize::MAX));
+}
@@ -0,0 +1,154 @@
+use simulation::Specimen;
+
+/// Most species a simulation can have.
+pub const MAX_SPECIES: usize = 8;
+
+/// Names of stats counting specimens of each species.
+pub fn stat_names() -> &'static [&'static str] {
+    const NAMES: &'static [&'static str] = &["species_0", "species_1", "species_2", "species_3",
+                                             "species_4", "species_5", "species_6", "species_7"];
+    NAMES
+}
+
+/// What happens between two specimens of given species in a collision.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Interaction {
+    /// Take part in splitting the collision energy as usual.
+    Share,
+    /// The stronger one takes all energy of the other, who dies.
+    Fight,
+    /// Leave the collision unaffected.
+    Ignore,
+    /// The weaker one joins the stronger one's species, then they share.
+    Convert
+}
+
+impl Interaction {
+    pub fn from_name(name: &str) -> Option<Interaction> {
+        match name {
+            "share" => Some(Interaction::Share),
+            "fight" => Some(Interaction::Fight),
+            "ignore" => Some(Interaction::Ignore),
+            "convert" => Some(Interaction::Convert),
+            _ => None
+        }
+    }
+}
+
+/// Species of GoodEvil specimens and how they treat each other.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpeciesConfig {
+    /// `interactions[a][b]` applies when the strongest specimen of a
+    /// collision is of species `a` and the other one of species `b`.
+    pub interactions: Vec<Vec<Interaction>>
+}
+
+/// Collision after species interactions were applied.
+#[derive(Debug, PartialEq)]
+pub struct Encounter {
+    /// Specimens of the collision in their original order, with None in
+    /// place of the killed ones.
+    pub specimens: Vec<Option<Specimen>>,
+    /// Whether each specimen takes part in splitting the collision energy.
+    pub sharing: Vec<bool>,
+    pub killed: usize
+}
+
+impl SpeciesConfig {
+    /// Parses a square matrix of interaction names with rows separated by
+    /// ';' and entries by ',', e.g. "share,fight;fight,share".
+    pub fn parse(s: &str) -> Result<SpeciesConfig, String> {
+        let mut interactions = Vec::new();
+        for row in s.split(';') {
+            let mut parsed = Vec::new();
+            for name in row.split(',') {
+                match Interaction::from_name(name.trim()) {
+                    Some(interaction) => parsed.push(interaction),
+                    None => return Err(format!("unknown interaction: {}, expected share, fight, ignore or convert",
+                                               name.trim()))
+                }
+            }
+            interactions.push(parsed);
+        }
+
+        if interactions.len() > MAX_SPECIES {
+            return Err(format!("at most {} species are supported", MAX_SPECIES));
+        }
+        if interactions.iter().any(|row| row.len() != interactions.len()) {
+            return Err("interaction matrix must be square".to_string());
+        }
+
+        Ok(SpeciesConfig {
+            interactions: interactions
+        })
+    }
+
+    pub fn count(&self) -> usize {
+        self.interactions.len()
+    }
+
+    /// Applies interactions between the strongest specimen of a collision
+    /// and each of the others.
+    pub fn encounter(&self,
+                     specimens: &[Specimen]) -> Encounter {
+        let mut strongest = 0;
+        for (idx, s) in specimens.iter().enumerate() {
+            if s.strength() > specimens[strongest].strength() {
+                strongest = idx;
+            }
+        }
+
+        let mut encounter = Encounter {
+            specimens: specimens.iter().map(|&s| Some(s)).collect(),
+            sharing: vec![true; specimens.len()],
+            killed: 0
+        };
+        let mut leader = specimens[strongest];
+
+        for (idx, s) in specimens.iter().enumerate() {
+            if idx == strongest {
+                continue;
+            }
+
+            match self.interactions[leader.species as usize][s.species as usize] {
+                Interaction::Share => (),
+                Interaction::Fight => {
+                    leader.energy += s.energy;
+                    encounter.specimens[idx] = None;
+                    encounter.sharing[idx] = false;
+                    encounter.killed += 1;
+                },
+                Interaction::Ignore => encounter.sharing[idx] = false,
+                Interaction::Convert => encounter.specimens[idx] = Some(Specimen { species: leader.species, ..*s })
+            }
+        }
+        encounter.specimens[strongest] = Some(leader);
+
+        encounter
+    }
+}
+
+#[test]
+fn test_species_parse() {
+    let cfg = SpeciesConfig::parse("share, fight;convert,ignore").unwrap();
+    assert_eq!(2, cfg.count());
+    assert_eq!(Interaction::Convert, cfg.interactions[1][0]);
+
+    assert!(SpeciesConfig::parse("share,fight").is_err());
+    assert!(SpeciesConfig::parse("share,eat;share,share").is_err());
+}
+
+#[test]
+fn test_species_encounter() {
+    let cfg = SpeciesConfig::parse("share,fight,ignore;share,share,share;convert,share,share").unwrap();
+    let of = |species, energy| Specimen { species: species, ..Specimen::new(energy) };
+
+    let encounter = cfg.encounter(&[of(1, 0.5), of(0, 2.0), of(0, 1.0), of(2, 0.5)]);
+    assert_eq!(vec![None, Some(of(0, 2.5)), Some(of(0, 1.0)), Some(of(2, 0.5))], encounter.specimens);
+    assert_eq!(vec![false, true, true, false], encounter.sharing);
+    assert_eq!(1, encounter.killed);
+
+    let encounter = cfg.encounter(&[of(0, 1.0), of(2, 3.0)]);
+    assert_eq!(vec![Some(of(2, 1.0)), Some(of(2, 3.0))], encounter.specimens);
+    assert_eq!(vec![true, true], encounter.sharing);
+}
@@ -0,0 +1,513 @@
+//! Protocol for watching a simulation remotely.
+//!
+//! A spectator opens with a `Hello` listing the protocol versions and
+//! compressions it understands, and the server answers with a `Welcome`
+//! picking one of each, or closes the connection if there is no match.
+//! Frames follow: the first one holds the whole board, every next one only
+//! the cells changed since the previous frame. Cells are sent as RGB
+//! colors, run-length encoded, so large boards that change little take
+//! a few bytes per frame.
+//!
+//! Every message is prefixed with its length as a 32-bit big-endian
+//! number, so the protocol works over any byte stream.
+
+use std::io::{Read, Write};
+
+#[cfg(test)]
+use board::Board;
+use board_view::BoardView;
+use offscreen::to_rgb;
+use palette::CellColor;
+
+/// Protocol versions this build speaks, oldest first.
+pub const VERSIONS: &'static [u8] = &[1];
+
+const MAGIC: &'static [u8] = b"CELL";
+
+/// Largest message accepted, to avoid allocating whatever a broken peer
+/// claims to send.
+const MAX_MESSAGE_LEN: usize = 1 << 28;
+/// Largest board accepted in a `Welcome`, in cells, for the same reason.
+const MAX_BOARD_CELLS: usize = 1 << 26;
+
+const KEY_FRAME: u8 = 0;
+const DELTA_FRAME: u8 = 1;
+
+pub type Rgb = [u8; 3];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Compression {
+    None,
+    /// Available only when built with the `zstd` feature.
+    Zstd
+}
+
+impl Compression {
+    pub fn from_byte(byte: u8) -> Option<Compression> {
+        match byte {
+            0 => Some(Compression::None),
+            1 => Some(Compression::Zstd),
+            _ => None
+        }
+    }
+
+    pub fn byte(&self) -> u8 {
+        match *self {
+            Compression::None => 0,
+            Compression::Zstd => 1
+        }
+    }
+
+    /// Compressions this build can use, most preferred first.
+    pub fn supported() -> Vec<Compression> {
+        if cfg!(feature = "zstd") {
+            vec![Compression::Zstd, Compression::None]
+        } else {
+            vec![Compression::None]
+        }
+    }
+
+    fn compress(&self,
+                data: Vec<u8>) -> Result<Vec<u8>, String> {
+        match *self {
+            Compression::None => Ok(data),
+            Compression::Zstd => zstd_compress(&data)
+        }
+    }
+
+    fn decompress(&self,
+                  data: &[u8]) -> Result<Vec<u8>, String> {
+        match *self {
+            Compression::None => Ok(data.to_vec()),
+            Compression::Zstd => zstd_decompress(data)
+        }
+    }
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_compress(data: &[u8]) -> Result<Vec<u8>, String> {
+    ::zstd::stream::encode_all(data, 0).map_err(|e| format!("cannot compress frame: {}", e))
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    let decoder = try!(::zstd::stream::Decoder::new(data).map_err(|e| format!("cannot decompress frame: {}", e)));
+    let mut out = Vec::new();
+    try!(decoder.take(MAX_MESSAGE_LEN as u64 + 1)
+                .read_to_end(&mut out)
+                .map_err(|e| format!("cannot decompress frame: {}", e)));
+    if out.len() > MAX_MESSAGE_LEN {
+        return Err("decompressed frame is too large".to_string());
+    }
+    Ok(out)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_compress(_data: &[u8]) -> Result<Vec<u8>, String> {
+    Err("built without zstd support".to_string())
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_decompress(_data: &[u8]) -> Result<Vec<u8>, String> {
+    Err("built without zstd support".to_string())
+}
+
+fn push_varint(out: &mut Vec<u8>,
+               mut value: usize) {
+    while value >= 0x80 {
+        out.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Reads bytes of a message one by one, failing on truncated input.
+struct Cursor<'a> {
+    data: &'a [u8],
+    pos: usize
+}
+
+impl<'a> Cursor<'a> {
+    fn new(data: &'a [u8]) -> Cursor<'a> {
+        Cursor {
+            data: data,
+            pos: 0
+        }
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        match self.data.get(self.pos) {
+            Some(&byte) => {
+                self.pos += 1;
+                Ok(byte)
+            },
+            None => Err("message truncated".to_string())
+        }
+    }
+
+    fn bytes(&mut self,
+             len: usize) -> Result<&'a [u8], String> {
+        if self.data.len() - self.pos < len {
+            return Err("message truncated".to_string());
+        }
+        self.pos += len;
+        Ok(&self.data[self.pos - len..self.pos])
+    }
+
+    fn varint(&mut self) -> Result<usize, String> {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = try!(self.byte());
+            if shift >= 64 {
+                return Err("number too large".to_string());
+            }
+            value |= ((byte & 0x7f) as usize) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+            shift += 7;
+        }
+    }
+
+    fn rgb(&mut self) -> Result<Rgb, String> {
+        let bytes = try!(self.bytes(3));
+        Ok([bytes[0], bytes[1], bytes[2]])
+    }
+
+    fn magic(&mut self) -> Result<(), String> {
+        if try!(self.bytes(MAGIC.len())) == MAGIC {
+            Ok(())
+        } else {
+            Err("not a spectator protocol message".to_string())
+        }
+    }
+}
+
+/// Sent by the spectator when connecting.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hello {
+    pub versions: Vec<u8>,
+    /// Most preferred first.
+    pub compressions: Vec<Compression>
+}
+
+impl Hello {
+    /// Hello listing everything this build supports.
+    pub fn supported() -> Hello {
+        Hello {
+            versions: VERSIONS.to_vec(),
+            compressions: Compression::supported()
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        out.push(self.versions.len() as u8);
+        out.extend(self.versions.iter().cloned());
+        out.push(self.compressions.len() as u8);
+        out.extend(self.compressions.iter().map(|c| c.byte()));
+        out
+    }
+
+    /// Compressions unknown to this build are skipped, so newer spectators
+    /// can still connect.
+    pub fn from_bytes(data: &[u8]) -> Result<Hello, String> {
+        let mut cursor = Cursor::new(data);
+        try!(cursor.magic());
+        let count = try!(cursor.byte()) as usize;
+        let versions = try!(cursor.bytes(count)).to_vec();
+        let count = try!(cursor.byte()) as usize;
+        let compressions = try!(cursor.bytes(count)).iter()
+                                                     .filter_map(|&byte| Compression::from_byte(byte))
+                                                     .collect();
+        Ok(Hello {
+            versions: versions,
+            compressions: compressions
+        })
+    }
+}
+
+/// Server's answer to a `Hello`, fixing the parameters of the session.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Welcome {
+    pub version: u8,
+    pub compression: Compression,
+    pub width: usize,
+    pub height: usize
+}
+
+impl Welcome {
+    /// Picks the newest version both sides speak and the compression the
+    /// spectator prefers most among ones this build supports.
+    pub fn negotiate(hello: &Hello,
+                     width: usize,
+                     height: usize) -> Result<Welcome, String> {
+        let version = match VERSIONS.iter().rev().find(|v| hello.versions.contains(v)) {
+            Some(&version) => version,
+            None => return Err(format!("no common protocol version, spectator speaks {:?}, server {:?}",
+                                       hello.versions, VERSIONS))
+        };
+        let supported = Compression::supported();
+        let compression = match hello.compressions.iter().find(|c| supported.contains(c)) {
+            Some(&compression) => compression,
+            None => return Err("no common compression".to_string())
+        };
+
+        Ok(Welcome {
+            version: version,
+            compression: compression,
+            width: width,
+            height: height
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        out.push(self.version);
+        out.push(self.compression.byte());
+        push_varint(&mut out, self.width);
+        push_varint(&mut out, self.height);
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Welcome, String> {
+        let mut cursor = Cursor::new(data);
+        try!(cursor.magic());
+        let version = try!(cursor.byte());
+        if !VERSIONS.contains(&version) {
+            return Err(format!("unsupported protocol version {}", version));
+        }
+        let compression = match Compression::from_byte(try!(cursor.byte())) {
+            Some(compression) => compression,
+            None => return Err("unknown compression".to_string())
+        };
+        let width = try!(cursor.varint());
+        let height = try!(cursor.varint());
+        match width.checked_mul(height) {
+            Some(cells) if cells <= MAX_BOARD_CELLS => {},
+            _ => return Err(format!("board of {}x{} cells is too large", width, height))
+        }
+        Ok(Welcome {
+            version: version,
+            compression: compression,
+            width: width,
+            height: height
+        })
+    }
+}
+
+pub fn write_message<W: Write>(writer: &mut W,
+                               message: &[u8]) -> Result<(), String> {
+    let len = message.len() as u32;
+    let prefix = [(len >> 24) as u8, (len >> 16) as u8, (len >> 8) as u8, len as u8];
+    writer.write_all(&prefix)
+          .and_then(|_| writer.write_all(message))
+          .and_then(|_| writer.flush())
+          .map_err(|e| format!("cannot send message: {}", e))
+}
+
+pub fn read_message<R: Read>(reader: &mut R) -> Result<Vec<u8>, String> {
+    let mut prefix = [0u8; 4];
+    try!(reader.read_exact(&mut prefix).map_err(|e| format!("cannot receive message: {}", e)));
+    let len = prefix.iter().fold(0, |len, &byte| (len << 8) | byte as usize);
+    if len > MAX_MESSAGE_LEN {
+        return Err(format!("message of {} bytes is too large", len));
+    }
+
+    let mut message = vec![0; len];
+    try!(reader.read_exact(&mut message).map_err(|e| format!("cannot receive message: {}", e)));
+    Ok(message)
+}
+
+/// Turns consecutive boards into frames, each one a delta against the
+/// previous frame.
+pub struct FrameEncoder {
+    compression: Compression,
+    previous: Option<Vec<Rgb>>
+}
+
+impl FrameEncoder {
+    pub fn new(welcome: &Welcome) -> FrameEncoder {
+        FrameEncoder {
+            compression: welcome.compression,
+            previous: None
+        }
+    }
+
+    /// Makes the next frame hold the whole board, e.g. for a spectator
+    /// that joined late.
+    pub fn reset(&mut self) {
+        self.previous = None;
+    }
+
+    /// Encodes the board as a list of (unchanged, changed, color) runs:
+    /// `unchanged` cells are the same as in the previous frame, then
+    /// `changed` cells all get `color`. A key frame never skips cells.
+    ///
+    /// Takes a snapshot rather than the simulation's board, so that frames
+    /// can be encoded on another thread while the simulation goes on.
+    pub fn encode<T: CellColor>(&mut self,
+                                view: &BoardView<T>) -> Result<Vec<u8>, String> {
+        let cells: Vec<Rgb> = view.iter().map(|cell| to_rgb(cell.color())).collect();
+        let mut out = Vec::new();
+        out.push(if self.previous.is_some() { DELTA_FRAME } else { KEY_FRAME });
+        push_varint(&mut out, view.iteration());
+
+        {
+            let unchanged = |idx: usize| match self.previous {
+                Some(ref previous) => previous[idx] == cells[idx],
+                None => false
+            };
+            let mut idx = 0;
+            while idx < cells.len() {
+                let skip_start = idx;
+                while idx < cells.len() && unchanged(idx) {
+                    idx += 1;
+                }
+                push_varint(&mut out, idx - skip_start);
+                if idx == cells.len() {
+                    break;
+                }
+
+                let run_start = idx;
+                while idx < cells.len() && !unchanged(idx) && cells[idx] == cells[run_start] {
+                    idx += 1;
+                }
+                push_varint(&mut out, idx - run_start);
+                out.extend(cells[run_start].iter().cloned());
+            }
+        }
+
+        self.previous = Some(cells);
+        self.compression.compress(out)
+    }
+}
+
+/// Rebuilds the board colors from frames made by a `FrameEncoder`.
+pub struct FrameDecoder {
+    compression: Compression,
+    pub width: usize,
+    pub height: usize,
+    cells: Vec<Rgb>,
+    /// Whether a key frame was decoded yet.
+    synced: bool
+}
+
+impl FrameDecoder {
+    pub fn new(welcome: &Welcome) -> FrameDecoder {
+        FrameDecoder {
+            compression: welcome.compression,
+            width: welcome.width,
+            height: welcome.height,
+            cells: vec![[0, 0, 0]; welcome.width * welcome.height],
+            synced: false
+        }
+    }
+
+    /// Applies a frame and returns its iteration.
+    pub fn decode(&mut self,
+                  frame: &[u8]) -> Result<usize, String> {
+        let data = try!(self.compression.decompress(frame));
+        let mut cursor = Cursor::new(&data);
+        match try!(cursor.byte()) {
+            KEY_FRAME => self.synced = true,
+            DELTA_FRAME if self.synced => {},
+            DELTA_FRAME => return Err("delta frame received before a key frame".to_string()),
+            kind => return Err(format!("unknown frame kind {}", kind))
+        }
+        let iteration = try!(cursor.varint());
+
+        let mut idx = 0;
+        while idx < self.cells.len() {
+            let skip = try!(cursor.varint());
+            idx = match idx.checked_add(skip) {
+                Some(idx) => idx,
+                None => return Err("invalid skip length".to_string())
+            };
+            if idx >= self.cells.len() {
+                break;
+            }
+            let len = try!(cursor.varint());
+            let color = try!(cursor.rgb());
+            if len == 0 || len > self.cells.len() - idx {
+                return Err("invalid run length".to_string());
+            }
+            for cell in self.cells[idx..idx + len].iter_mut() {
+                *cell = color;
+            }
+            idx += len;
+        }
+
+        Ok(iteration)
+    }
+
+    pub fn at(&self,
+              x: usize,
+              y: usize) -> Rgb {
+        self.cells[y * self.width + x]
+    }
+}
+
+#[test]
+fn test_negotiate() {
+    let hello = Hello::from_bytes(&Hello {
+        versions: vec![1, 7],
+        compressions: vec![Compression::Zstd, Compression::None]
+    }.to_bytes()).unwrap();
+    let welcome = Welcome::negotiate(&hello, 4096, 4096).unwrap();
+
+    assert_eq!(1, welcome.version);
+    assert_eq!(Compression::supported()[0], welcome.compression);
+    assert_eq!(Ok(welcome), Welcome::from_bytes(&welcome.to_bytes()));
+
+    let hello = Hello { versions: vec![7], compressions: vec![Compression::None] };
+    assert!(Welcome::negotiate(&hello, 1, 1).is_err());
+}
+
+#[test]
+fn test_frames_round_trip() {
+    let mut board = Board::new(64, 64, false);
+    let welcome = Welcome::negotiate(&Hello::supported(), board.width, board.height).unwrap();
+    let mut encoder = FrameEncoder::new(&welcome);
+    let mut decoder = FrameDecoder::new(&welcome);
+
+    let key = encoder.encode(&BoardView::new(board.clone(), 0)).unwrap();
+    assert_eq!(Ok(0), decoder.decode(&key));
+    *board.at_mut(5, 7) = true;
+    *board.at_mut(6, 7) = true;
+    let delta = encoder.encode(&BoardView::new(board, 1)).unwrap();
+    assert_eq!(Ok(1), decoder.decode(&delta));
+
+    assert_eq!([255, 255, 255], decoder.at(6, 7));
+    assert_eq!([0, 0, 0], decoder.at(7, 7));
+    // a run of unchanged cells, a run of two white ones and the rest
+    assert!(delta.len() < 16);
+}
+
+#[test]
+fn test_messages() {
+    let mut stream = Vec::new();
+    write_message(&mut stream, &Hello::supported().to_bytes()).unwrap();
+    write_message(&mut stream, b"frame").unwrap();
+
+    let mut reader = &stream[..];
+    assert_eq!(Ok(Hello::supported()), Hello::from_bytes(&read_message(&mut reader).unwrap()));
+    assert_eq!(Ok(b"frame".to_vec()), read_message(&mut reader));
+    assert!(read_message(&mut reader).is_err());
+
+    let welcome = Welcome::negotiate(&Hello::supported(), 2, 2).unwrap();
+    assert!(FrameDecoder::new(&welcome).decode(&[DELTA_FRAME, 0, 4]).is_err());
+}
+
+#[test]
+fn test_hostile_input() {
+    let huge = Welcome { version: 1, compression: Compression::None, width: 1 << 40, height: 1 << 40 };
+    assert!(Welcome::from_bytes(&huge.to_bytes()).is_err());
+
+    let welcome = Welcome::negotiate(&Hello { versions: vec![1], compressions: vec![Compression::None] }, 2, 2).unwrap();
+    let mut decoder = FrameDecoder::new(&welcome);
+    let mut frame = vec![KEY_FRAME, 0, 1, 1, 0, 0, 0];
+    push_varint(&mut frame, usize::max_value());
+    assert!(decoder.decode(&frame).is_err());
+}
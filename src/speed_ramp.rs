@@ -0,0 +1,72 @@
+//! Scripted playback speed, e.g. slowing down around an interesting event
+//! and fast-forwarding through quiet phases of a presentation.
+
+/// Playback speed as a factor of the normal one, changing over the course
+/// of a run: 0.25 is slow motion, 4 fast-forward. Between keyframes the
+/// speed changes linearly, before the first and after the last one it
+/// stays constant.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpeedRamp {
+    /// (iteration, speed), sorted by iteration.
+    keyframes: Vec<(usize, f64)>
+}
+
+impl SpeedRamp {
+    /// Parses "ITERATION:SPEED,...", e.g. "0:1,90:0.25,110:0.25,120:4".
+    /// Iterations must increase and speeds must be positive.
+    pub fn parse(s: &str) -> Option<SpeedRamp> {
+        let mut keyframes: Vec<(usize, f64)> = Vec::new();
+
+        for keyframe in s.split(',') {
+            let mut parts = keyframe.splitn(2, ':');
+            let iteration = match parts.next().and_then(|p| p.trim().parse().ok()) {
+                Some(iteration) => iteration,
+                None => return None
+            };
+            let speed = match parts.next().and_then(|p| p.trim().parse().ok()) {
+                Some(speed) if speed > 0.0 => speed,
+                _ => return None
+            };
+            if keyframes.last().map(|&(last, _)| last >= iteration).unwrap_or(false) {
+                return None;
+            }
+            keyframes.push((iteration, speed));
+        }
+
+        Some(SpeedRamp { keyframes: keyframes })
+    }
+
+    pub fn speed_at(&self,
+                    iteration: usize) -> f64 {
+        let next = match self.keyframes.iter().position(|&(at, _)| at > iteration) {
+            Some(0) => return self.keyframes[0].1,
+            Some(next) => next,
+            None => return self.keyframes[self.keyframes.len() - 1].1
+        };
+
+        let (from, from_speed) = self.keyframes[next - 1];
+        let (to, to_speed) = self.keyframes[next];
+        let t = (iteration - from) as f64 / (to - from) as f64;
+        from_speed + (to_speed - from_speed) * t
+    }
+}
+
+#[test]
+fn test_speed_ramp_parse() {
+    assert_eq!(Some(SpeedRamp { keyframes: vec![(0, 1.0), (10, 0.5)] }), SpeedRamp::parse("0:1, 10:0.5"));
+    assert_eq!(None, SpeedRamp::parse("10:1,5:2"));
+    assert_eq!(None, SpeedRamp::parse("0:0"));
+    assert_eq!(None, SpeedRamp::parse("0"));
+    assert_eq!(None, SpeedRamp::parse(""));
+}
+
+#[test]
+fn test_speed_ramp_interpolates() {
+    let ramp = SpeedRamp::parse("10:1,20:0.5,30:4").unwrap();
+
+    assert_eq!(1.0, ramp.speed_at(0));
+    assert_eq!(1.0, ramp.speed_at(10));
+    assert_eq!(0.75, ramp.speed_at(15));
+    assert_eq!(0.5, ramp.speed_at(20));
+    assert_eq!(4.0, ramp.speed_at(100));
+}
@@ -0,0 +1,179 @@
+use std::fmt;
+
+use energy_histogram::EnergyHistogram;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Value {
+    Count(usize),
+    Real(f32)
+}
+
+impl Value {
+    pub fn as_f32(&self) -> f32 {
+        match *self {
+            Value::Count(n) => n as f32,
+            Value::Real(x) => x
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Count(n) => write!(f, "{}", n),
+            Value::Real(x) => write!(f, "{}", x)
+        }
+    }
+}
+
+/// Named values describing the state of a simulation after an iteration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats {
+    pub iteration: usize,
+    pub values: Vec<(&'static str, Value)>,
+    /// Notable things that happened during the iteration.
+    pub events: Vec<String>,
+    /// Energies of specimens, if asked for.
+    pub energy_histogram: Option<EnergyHistogram>
+}
+
+impl Stats {
+    pub fn new(iteration: usize) -> Stats {
+        Stats {
+            iteration: iteration,
+            values: Vec::new(),
+            events: Vec::new(),
+            energy_histogram: None
+        }
+    }
+
+    pub fn count(mut self,
+                 name: &'static str,
+                 value: usize) -> Stats {
+        self.values.push((name, Value::Count(value)));
+        self
+    }
+
+    pub fn real(mut self,
+                name: &'static str,
+                value: f32) -> Stats {
+        self.values.push((name, Value::Real(value)));
+        self
+    }
+
+    pub fn event(mut self,
+                 description: String) -> Stats {
+        self.events.push(description);
+        self
+    }
+
+    pub fn with_energy_histogram(self,
+                                 histogram: EnergyHistogram) -> Stats {
+        Stats {
+            energy_histogram: Some(histogram),
+            ..self
+        }
+    }
+
+    pub fn get(&self,
+               name: &str) -> Option<Value> {
+        self.values.iter()
+                   .find(|&&(n, _)| n == name)
+                   .map(|&(_, value)| value)
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f, "iter {}", self.iteration));
+        for &(name, value) in self.values.iter() {
+            try!(write!(f, " {} {}", name, value));
+        }
+        for event in self.events.iter() {
+            try!(write!(f, " event {}", event));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison {
+    Less,
+    Greater
+}
+
+/// Condition on a single stats value, e.g. `infected<1`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StopCriterion {
+    pub name: String,
+    pub comparison: Comparison,
+    pub threshold: f32
+}
+
+impl StopCriterion {
+    pub fn parse(string: &str) -> Option<StopCriterion> {
+        let (pos, comparison) = match (string.find('<'), string.find('>')) {
+            (Some(pos), None) => (pos, Comparison::Less),
+            (None, Some(pos)) => (pos, Comparison::Greater),
+            _ => return None
+        };
+
+        let name = string[..pos].trim();
+        match string[pos + 1..].trim().parse::<f32>() {
+            Ok(threshold) if !name.is_empty() => Some(StopCriterion {
+                name: name.to_string(),
+                comparison: comparison,
+                threshold: threshold
+            }),
+            _ => None
+        }
+    }
+
+    pub fn is_met(&self,
+                  stats: &Stats) -> bool {
+        match stats.get(&self.name) {
+            Some(value) => match self.comparison {
+                Comparison::Less => value.as_f32() < self.threshold,
+                Comparison::Greater => value.as_f32() > self.threshold
+            },
+            None => false
+        }
+    }
+}
+
+impl fmt::Display for StopCriterion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let op = match self.comparison {
+            Comparison::Less => "<",
+            Comparison::Greater => ">"
+        };
+        write!(f, "{}{}{}", self.name, op, self.threshold)
+    }
+}
+
+#[test]
+fn test_stats_display() {
+    let stats = Stats::new(3).count("specimens", 10).real("avg", 0.5);
+
+    assert_eq!("iter 3 specimens 10 avg 0.5", stats.to_string());
+    assert_eq!(Some(Value::Count(10)), stats.get("specimens"));
+    assert_eq!(None, stats.get("max"));
+
+    let stats = stats.event("meteor:5".to_string());
+    assert_eq!("iter 3 specimens 10 avg 0.5 event meteor:5", stats.to_string());
+}
+
+#[test]
+fn test_stop_criterion() {
+    let criterion = StopCriterion::parse("infected<1").unwrap();
+    assert_eq!("infected", criterion.name);
+    assert_eq!(Comparison::Less, criterion.comparison);
+
+    assert!(criterion.is_met(&Stats::new(1).count("infected", 0)));
+    assert!(!criterion.is_met(&Stats::new(1).count("infected", 3)));
+    assert!(!criterion.is_met(&Stats::new(1).count("recovered", 0)));
+
+    assert!(StopCriterion::parse("avg > 2.5").unwrap().is_met(&Stats::new(1).real("avg", 3.0)));
+    assert!(StopCriterion::parse("avg=2").is_none());
+    assert!(StopCriterion::parse("<2").is_none());
+}
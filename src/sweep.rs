@@ -0,0 +1,273 @@
+//! Parameter sweeps: one run per combination of parameter values, done by
+//! a pool of workers. The job list is stored in the sweep directory, and
+//! jobs whose manifest is already there are skipped, so that an interrupted
+//! sweep can be resumed.
+
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// File in the sweep directory listing the jobs, one per line: the job
+/// name followed by its arguments, separated by tabs.
+pub const JOBS_FILE: &'static str = "jobs.txt";
+
+/// Option swept over, given as `NAME=VALUE,VALUE,...`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Param {
+    pub name: String,
+    pub values: Vec<String>
+}
+
+impl Param {
+    pub fn parse(text: &str) -> Result<Param, String> {
+        let mut parts = text.splitn(2, '=');
+        let name = parts.next().unwrap().trim().trim_left_matches('-');
+        let values: Vec<String> = match parts.next() {
+            Some(values) => values.split(',')
+                                  .map(|value| value.trim().to_string())
+                                  .filter(|value| !value.is_empty())
+                                  .collect(),
+            None => Vec::new()
+        };
+        if name.is_empty() || values.is_empty() {
+            return Err("expected NAME=VALUE,VALUE,...".to_string());
+        }
+
+        Ok(Param {
+            name: name.to_string(),
+            values: values
+        })
+    }
+}
+
+/// Single run of a sweep.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Job {
+    /// `job-0000`, `job-0001`..., also the name of its manifest and log.
+    pub name: String,
+    pub args: Vec<String>
+}
+
+/// Jobs for every combination of `params`, with the last parameter
+/// changing fastest. Each job gets `base_args` followed by its values.
+pub fn jobs(base_args: &[String],
+            params: &[Param]) -> Vec<Job> {
+    let mut combinations: Vec<Vec<String>> = vec![Vec::new()];
+    for param in params {
+        combinations = combinations.iter()
+                                   .flat_map(|args| param.values.iter().map(move |value| {
+                                       let mut args = args.clone();
+                                       args.push(format!("--{}", param.name));
+                                       args.push(value.clone());
+                                       args
+                                   }))
+                                   .collect();
+    }
+
+    combinations.into_iter()
+                .enumerate()
+                .map(|(idx, args)| Job {
+                    name: format!("job-{:04}", idx),
+                    args: base_args.iter().cloned().chain(args.into_iter()).collect()
+                })
+                .collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SweepReport {
+    /// Jobs finished before, by an interrupted run of the sweep.
+    pub skipped: usize,
+    pub completed: usize,
+    /// Names of failed jobs with the reason, in order of jobs.
+    pub failed: Vec<(String, String)>
+}
+
+pub struct Sweep {
+    dir: PathBuf,
+    jobs: Vec<Job>
+}
+
+impl Sweep {
+    /// Starts a new sweep in `dir`, which must not hold one already.
+    pub fn create(dir: &str,
+                  jobs: Vec<Job>) -> Result<Sweep, String> {
+        let sweep = Sweep {
+            dir: PathBuf::from(dir),
+            jobs: jobs
+        };
+        let path = sweep.dir.join(JOBS_FILE);
+        if path.exists() {
+            return Err(format!("{} already exists", path.display()));
+        }
+
+        let mut contents = String::new();
+        for job in sweep.jobs.iter() {
+            contents.push_str(&job.name);
+            for arg in job.args.iter() {
+                contents.push('\t');
+                contents.push_str(arg);
+            }
+            contents.push('\n');
+        }
+        try!(fs::create_dir_all(&sweep.dir).map_err(|e| format!("{}: {}", sweep.dir.display(), e)));
+        try!(File::create(&path).and_then(|mut f| f.write_all(contents.as_bytes()))
+                                .map_err(|e| format!("{}: {}", path.display(), e)));
+        Ok(sweep)
+    }
+
+    /// Opens a sweep started before in `dir`.
+    pub fn open(dir: &str) -> Result<Sweep, String> {
+        let path = Path::new(dir).join(JOBS_FILE);
+        let mut contents = String::new();
+        try!(File::open(&path).and_then(|mut f| f.read_to_string(&mut contents))
+                              .map_err(|e| format!("cannot read {}: {}", path.display(), e)));
+
+        let jobs = contents.lines()
+                           .filter(|line| !line.is_empty())
+                           .map(|line| {
+                               let mut fields = line.split('\t').map(|field| field.to_string());
+                               Job {
+                                   name: fields.next().unwrap(),
+                                   args: fields.collect()
+                               }
+                           })
+                           .collect();
+        Ok(Sweep {
+            dir: PathBuf::from(dir),
+            jobs: jobs
+        })
+    }
+
+    pub fn jobs(&self) -> &[Job] {
+        &self.jobs
+    }
+
+    pub fn manifest_path(&self,
+                         job: &Job) -> PathBuf {
+        self.dir.join(format!("{}.json", job.name))
+    }
+
+    pub fn log_path(&self,
+                    job: &Job) -> PathBuf {
+        self.dir.join(format!("{}.log", job.name))
+    }
+
+    pub fn is_done(&self,
+                   job: &Job) -> bool {
+        self.manifest_path(job).exists()
+    }
+
+    /// Runs jobs without a manifest on `threads` workers. `run_job` gets
+    /// a job, the path its manifest is to be written to and the path of its
+    /// log. The manifest is only moved to its final place once the job
+    /// finished, so that jobs interrupted while writing it are run again.
+    pub fn run<F>(&self,
+                  threads: usize,
+                  run_job: F) -> SweepReport where F: Fn(&Job, &Path, &Path) -> Result<(), String> + Send + Sync + 'static {
+        let pending: Vec<(Job, PathBuf, PathBuf)> = self.jobs.iter()
+                                                       .filter(|job| !self.is_done(job))
+                                                       .map(|job| (job.clone(), self.manifest_path(job), self.log_path(job)))
+                                                       .collect();
+        let skipped = self.jobs.len() - pending.len();
+        let pending = Arc::new(pending);
+        let run_job = Arc::new(run_job);
+        let next_job = Arc::new(AtomicUsize::new(0));
+        let (tx, rx) = mpsc::channel();
+
+        let workers: Vec<thread::JoinHandle<()>> = (0..threads.max(1).min(pending.len())).map(|_| {
+            let (pending, run_job, next_job, tx) = (pending.clone(), run_job.clone(), next_job.clone(), tx.clone());
+
+            thread::spawn(move || {
+                loop {
+                    let idx = next_job.fetch_add(1, Ordering::SeqCst);
+                    let (job, manifest, log) = match pending.get(idx) {
+                        Some(&(ref job, ref manifest, ref log)) => (job, manifest, log),
+                        None => break
+                    };
+
+                    let partial = manifest.with_extension("json.part");
+                    let result = run_job(job, &partial, log).and_then(|_| {
+                        fs::rename(&partial, manifest)
+                           .map_err(|e| format!("no manifest at {}: {}", partial.display(), e))
+                    });
+                    let _ = tx.send((idx, result));
+                }
+            })
+        }).collect();
+        drop(tx);
+
+        let mut report = SweepReport {
+            skipped: skipped,
+            completed: 0,
+            failed: Vec::new()
+        };
+        let mut failed = Vec::new();
+        for (idx, result) in rx.iter() {
+            match result {
+                Ok(()) => report.completed += 1,
+                Err(e) => failed.push((idx, pending[idx].0.name.clone(), e))
+            }
+        }
+        for worker in workers {
+            let _ = worker.join();
+        }
+
+        failed.sort();
+        report.failed = failed.into_iter().map(|(_, name, e)| (name, e)).collect();
+        report
+    }
+}
+
+#[test]
+fn test_sweep_jobs() {
+    let params = vec![Param::parse("energy-loss=0.1,0.2").unwrap(),
+                      Param::parse("--specimens=5, 10,20").unwrap()];
+    let jobs = jobs(&["--headless".to_string()], &params);
+
+    assert_eq!(6, jobs.len());
+    assert_eq!("job-0004", jobs[4].name);
+    assert_eq!(vec!["--headless", "--energy-loss", "0.2", "--specimens", "10"], jobs[4].args);
+    assert!(Param::parse("energy-loss").is_err());
+    assert!(Param::parse("=1,2").is_err());
+}
+
+#[test]
+fn test_sweep_resume() {
+    use std::env;
+    use std::sync::Mutex;
+
+    let dir = env::temp_dir().join("cell-test-sweep");
+    let _ = fs::remove_dir_all(&dir);
+    let dir = dir.to_str().unwrap();
+    let params = vec![Param::parse("seed=1,2,3,4").unwrap()];
+    let sweep = Sweep::create(dir, jobs(&[], &params)).unwrap();
+    assert!(Sweep::create(dir, Vec::new()).is_err());
+
+    // the first attempt is interrupted after two jobs
+    let report = sweep.run(2, |job, manifest, _| {
+        if job.args[1] == "3" || job.args[1] == "4" {
+            return Err("interrupted".to_string());
+        }
+        File::create(manifest).map(|_| ()).map_err(|e| e.to_string())
+    });
+    assert_eq!(2, report.completed);
+    assert_eq!(vec!["job-0002", "job-0003"], report.failed.iter().map(|f| &f.0[..]).collect::<Vec<_>>());
+
+    let ran = Arc::new(Mutex::new(Vec::new()));
+    let ran_by_workers = ran.clone();
+    let report = Sweep::open(dir).unwrap().run(3, move |job, manifest, _| {
+        ran_by_workers.lock().unwrap().push(job.name.clone());
+        File::create(manifest).map(|_| ()).map_err(|e| e.to_string())
+    });
+    assert_eq!(SweepReport { skipped: 2, completed: 2, failed: Vec::new() }, report);
+    let mut ran = ran.lock().unwrap().clone();
+    ran.sort();
+    assert_eq!(vec!["job-0002", "job-0003"], ran);
+    assert!(Sweep::open(dir).unwrap().jobs().iter().all(|job| sweep.is_done(job)));
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,265 @@
+use std::cmp::{max, min};
+use std::fmt;
+
+use board::Board;
+
+/// Rectangular part of the board.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Region {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize
+}
+
+impl Region {
+    /// Parses "X,Y,WIDTH,HEIGHT".
+    pub fn parse(s: &str) -> Option<Region> {
+        let numbers: Vec<usize> = match s.split(',').map(|n| n.trim().parse()).collect() {
+            Ok(numbers) => numbers,
+            Err(_) => return None
+        };
+
+        if numbers.len() != 4 || numbers[2] == 0 || numbers[3] == 0 {
+            return None;
+        }
+
+        Some(Region {
+            x: numbers[0],
+            y: numbers[1],
+            width: numbers[2],
+            height: numbers[3]
+        })
+    }
+
+    /// Smallest region containing both cells, e.g. the corners of a
+    /// selection.
+    pub fn spanning(a: (usize, usize),
+                    b: (usize, usize)) -> Region {
+        Region {
+            x: min(a.0, b.0),
+            y: min(a.1, b.1),
+            width: max(a.0, b.0) - min(a.0, b.0) + 1,
+            height: max(a.1, b.1) - min(a.1, b.1) + 1
+        }
+    }
+
+    pub fn whole<T>(board: &Board<T>) -> Region {
+        Region {
+            x: 0,
+            y: 0,
+            width: board.width,
+            height: board.height
+        }
+    }
+
+    /// Smallest region containing all cells that differ from the top-left
+    /// one, which is taken as the background.
+    pub fn bounding_box<T: PartialEq>(board: &Board<T>) -> Option<Region> {
+        let background = board.at(0, 0);
+        let cells: Vec<(usize, usize)> = board.indices()
+                                              .filter(|&(x, y)| board.at(x, y) != background)
+                                              .collect();
+        if cells.is_empty() {
+            return None;
+        }
+
+        let min_x = cells.iter().map(|&(x, _)| x).min().unwrap();
+        let min_y = cells.iter().map(|&(_, y)| y).min().unwrap();
+        let max_x = cells.iter().map(|&(x, _)| x).max().unwrap();
+        let max_y = cells.iter().map(|&(_, y)| y).max().unwrap();
+
+        Some(Region {
+            x: min_x,
+            y: min_y,
+            width: max_x - min_x + 1,
+            height: max_y - min_y + 1
+        })
+    }
+
+    pub fn fits<T>(&self,
+                   board: &Board<T>) -> bool {
+        self.x + self.width <= board.width && self.y + self.height <= board.height
+    }
+}
+
+/// Rotations and reflections of a rectangle. The ones that swap axes only
+/// apply to squares.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Symmetry {
+    Identity,
+    /// Clockwise rotations.
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    /// Reflection about the vertical axis.
+    FlipHorizontal,
+    /// Reflection about the horizontal axis.
+    FlipVertical,
+    /// Reflection about the top-left to bottom-right diagonal.
+    FlipDiagonal,
+    FlipAntiDiagonal
+}
+
+impl Symmetry {
+    pub fn all() -> &'static [Symmetry] {
+        const ALL: &'static [Symmetry] = &[
+            Symmetry::Identity,
+            Symmetry::Rotate90,
+            Symmetry::Rotate180,
+            Symmetry::Rotate270,
+            Symmetry::FlipHorizontal,
+            Symmetry::FlipVertical,
+            Symmetry::FlipDiagonal,
+            Symmetry::FlipAntiDiagonal
+        ];
+        ALL
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Symmetry::Identity => "identity",
+            Symmetry::Rotate90 => "rotate-90",
+            Symmetry::Rotate180 => "rotate-180",
+            Symmetry::Rotate270 => "rotate-270",
+            Symmetry::FlipHorizontal => "flip-horizontal",
+            Symmetry::FlipVertical => "flip-vertical",
+            Symmetry::FlipDiagonal => "flip-diagonal",
+            Symmetry::FlipAntiDiagonal => "flip-anti-diagonal"
+        }
+    }
+
+    pub fn is_reflection(&self) -> bool {
+        match *self {
+            Symmetry::FlipHorizontal | Symmetry::FlipVertical
+                | Symmetry::FlipDiagonal | Symmetry::FlipAntiDiagonal => true,
+            _ => false
+        }
+    }
+
+    fn needs_square(&self) -> bool {
+        match *self {
+            Symmetry::Rotate90 | Symmetry::Rotate270
+                | Symmetry::FlipDiagonal | Symmetry::FlipAntiDiagonal => true,
+            _ => false
+        }
+    }
+
+    /// Image of (x, y) in a rectangle of given size.
+    fn apply(&self,
+             x: usize,
+             y: usize,
+             width: usize,
+             height: usize) -> (usize, usize) {
+        match *self {
+            Symmetry::Identity => (x, y),
+            Symmetry::Rotate90 => (height - 1 - y, x),
+            Symmetry::Rotate180 => (width - 1 - x, height - 1 - y),
+            Symmetry::Rotate270 => (y, width - 1 - x),
+            Symmetry::FlipHorizontal => (width - 1 - x, y),
+            Symmetry::FlipVertical => (x, height - 1 - y),
+            Symmetry::FlipDiagonal => (y, x),
+            Symmetry::FlipAntiDiagonal => (height - 1 - y, width - 1 - x)
+        }
+    }
+}
+
+/// Symmetries that map a region of the board onto itself.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SymmetryReport {
+    pub region: Region,
+    pub symmetries: Vec<Symmetry>
+}
+
+impl SymmetryReport {
+    pub fn analyze<T: PartialEq>(board: &Board<T>,
+                                 region: Region) -> SymmetryReport {
+        assert!(region.fits(board));
+
+        let (w, h) = (region.width, region.height);
+        let at = |x: usize, y: usize| board.at(region.x + x, region.y + y);
+
+        let symmetries = Symmetry::all().iter()
+                                        .filter(|sym| w == h || !sym.needs_square())
+                                        .filter(|sym| (0..h).all(|y| (0..w).all(|x| {
+                                            let (ix, iy) = sym.apply(x, y, w, h);
+                                            at(x, y) == at(ix, iy)
+                                        })))
+                                        .cloned()
+                                        .collect();
+
+        SymmetryReport {
+            region: region,
+            symmetries: symmetries
+        }
+    }
+
+    /// Analyzes `region`, or the bounding box of the pattern if not given.
+    pub fn of_board<T: PartialEq>(board: &Board<T>,
+                                  region: Option<Region>) -> SymmetryReport {
+        let region = region.or_else(|| Region::bounding_box(board))
+                           .unwrap_or_else(|| Region::whole(board));
+        SymmetryReport::analyze(board, region)
+    }
+
+    /// Name of the symmetry group: C1, C2 and C4 are rotations only, D1,
+    /// D2 and D4 also have 1, 2 or 4 reflections.
+    pub fn group(&self) -> &'static str {
+        let has = |sym| self.symmetries.contains(&sym);
+        let reflections = self.symmetries.iter().filter(|sym| sym.is_reflection()).count();
+
+        match (has(Symmetry::Rotate90), has(Symmetry::Rotate180), reflections) {
+            (true, _, 0) => "C4",
+            (true, _, _) => "D4",
+            (false, true, 0) => "C2",
+            (false, true, _) => "D2",
+            (false, false, 0) => "C1",
+            (false, false, _) => "D1"
+        }
+    }
+}
+
+impl fmt::Display for SymmetryReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<&str> = self.symmetries.iter().map(|sym| sym.name()).collect();
+        write!(f, "symmetry of {}x{} at {},{}: {} ({})",
+               self.region.width, self.region.height, self.region.x, self.region.y,
+               self.group(), names.join(", "))
+    }
+}
+
+#[cfg(test)]
+fn pattern_board(rows: &[&str]) -> Board<bool> {
+    use demo::board_from_pattern;
+    board_from_pattern((rows[0].len() + 2, rows.len() + 2), rows, (1, 1), true, false)
+}
+
+#[test]
+fn test_symmetry_region_parse() {
+    assert_eq!(Some(Region { x: 1, y: 2, width: 3, height: 4 }), Region::parse("1,2,3,4"));
+    assert_eq!(None, Region::parse("1,2,0,4"));
+    assert_eq!(None, Region::parse("1,2,3"));
+    assert_eq!(Region { x: 1, y: 2, width: 3, height: 1 }, Region::spanning((3, 2), (1, 2)));
+}
+
+#[test]
+fn test_symmetry_groups() {
+    let cases: &[(&[&str], &str)] = &[
+        (&[".O.", "..O", "OOO"], "C1"),
+        (&["OO.", ".OO"], "C2"),
+        (&["OO", "OO"], "D4"),
+        (&["O.O", "OOO"], "D1"),
+        (&["OOO", "O.O", "OOO"], "D4"),
+        (&["OOO.", "...O"], "C1"),
+        (&["OO.", "...", ".OO"], "C2"),
+        (&["O..O", "O..O"], "D2"),
+        (&[".O..", "OOO.", ".OOO", "..O."], "D2"),
+        (&["OO.O", "...O", "O...", "O.OO"], "C4")
+    ];
+
+    for &(rows, group) in cases {
+        let board = pattern_board(rows);
+        let report = SymmetryReport::analyze(&board, Region::bounding_box(&board).unwrap());
+        assert_eq!(group, report.group(), "{:?}", rows);
+    }
+}
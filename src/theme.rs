@@ -0,0 +1,173 @@
+//! Colors of everything drawn around the cells, and replacements for the
+//! colors of the cells themselves, loaded with `--theme`. Theme files use
+//! the same format as `--config` files:
+//!
+//! ```text
+//! background = "#202020"
+//! grid = "#303030"
+//!
+//! [palette]
+//! "#000000" = "#ffffff"
+//! ```
+//!
+//! Colors are written as `#RRGGBB` or `#RRGGBBAA`. Palette entries replace
+//! cell colors of every simulation, matched after rounding to 8 bits per
+//! channel, so they apply to fixed colors like empty cells or walls rather
+//! than gradients.
+
+use board::Board;
+use color_map::ColorMap;
+use config_file::{ConfigFile, Format, Value};
+use offscreen::to_rgb;
+use palette::{self, CellColor};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    /// Drawn around the board.
+    pub background: [f32; 4],
+    /// Lines between cells, not drawn if not set.
+    pub grid: Option<[f32; 4]>,
+    /// Density overlay, with opacity scaled by the density.
+    pub density: [f32; 4],
+    /// Wind streamlines.
+    pub streamline: [f32; 4],
+    /// Trails of recently left cells, with opacity fading out.
+    pub trail: [f32; 4],
+    /// Cell colors and their replacements.
+    pub palette: Vec<([f32; 4], [f32; 4])>,
+    /// Color map of cells holding a scalar, drawn with their default colors
+    /// if not set.
+    pub heatmap: Option<ColorMap>
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            background: [0.0, 0.0, 0.2, 1.0],
+            grid: None,
+            density: [1.0, 0.0, 0.0, 0.6],
+            streamline: [1.0, 1.0, 1.0, 0.5],
+            trail: [1.0, 0.6, 0.1, 0.7],
+            palette: Vec::new(),
+            heatmap: None
+        }
+    }
+}
+
+/// Parses `#RRGGBB` or `#RRGGBBAA`.
+pub fn parse_color(text: &str) -> Option<[f32; 4]> {
+    if !text.starts_with('#') || (text.len() != 7 && text.len() != 9) {
+        return None;
+    }
+
+    let mut color = [1.0; 4];
+    for idx in 0..(text.len() - 1) / 2 {
+        let channel = match u8::from_str_radix(&text[1 + idx * 2..3 + idx * 2], 16) {
+            Ok(channel) => channel,
+            Err(_) => return None
+        };
+        color[idx] = channel as f32 / 255.0;
+    }
+    Some(color)
+}
+
+impl Theme {
+    pub fn parse(text: &str) -> Result<Theme, String> {
+        let config = try!(ConfigFile::parse(text, Format::Toml));
+        Theme::from_config(&config)
+    }
+
+    pub fn load(path: &str) -> Result<Theme, String> {
+        let config = try!(ConfigFile::load(path));
+        Theme::from_config(&config).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    fn from_config(config: &ConfigFile) -> Result<Theme, String> {
+        let mut theme = Theme::default();
+
+        for &(ref key, ref value) in &config.entries {
+            let color = match *value {
+                Value::Text(ref text) => try!(parse_color(text).ok_or(format!("invalid color of {}: {}", key, text))),
+                _ => return Err(format!("invalid color of {}: expected #RRGGBB or #RRGGBBAA", key))
+            };
+
+            match &key[..] {
+                "background" => theme.background = color,
+                "grid" => theme.grid = Some(color),
+                "density" => theme.density = color,
+                "streamline" => theme.streamline = color,
+                "trail" => theme.trail = color,
+                _ => match parse_color(key) {
+                    Some(from) => theme.palette.push((from, color)),
+                    None => return Err(format!("unknown theme entry: {}", key))
+                }
+            }
+        }
+
+        Ok(theme)
+    }
+
+    /// `color` with palette replacements applied.
+    pub fn apply(&self,
+                 color: [f32; 4]) -> [f32; 4] {
+        let rgb = to_rgb(color);
+        self.palette.iter()
+                    .find(|&&(from, _)| to_rgb(from) == rgb)
+                    .map(|&(_, to)| to)
+                    .unwrap_or(color)
+    }
+
+    /// Like `palette::cell_color`, with the heatmap and palette
+    /// replacements applied.
+    pub fn cell_color<T: CellColor>(&self,
+                                    board: &Board<T>,
+                                    food: Option<&Board<f32>>,
+                                    x: usize,
+                                    y: usize) -> [f32; 4] {
+        match (self.heatmap, board.at(x, y).heat()) {
+            (Some(map), Some(heat)) => self.apply(map.color(heat)),
+            _ => self.apply(palette::cell_color(board, food, x, y))
+        }
+    }
+}
+
+#[test]
+fn test_heatmap() {
+    let mut board = Board::new(2, 1, 0.0f32);
+    *board.at_mut(1, 0) = 0.5;
+    let theme = Theme {
+        heatmap: Some(ColorMap::Jet),
+        ..Theme::default()
+    };
+
+    assert_eq!(ColorMap::Jet.color(0.5), theme.cell_color(&board, None, 1, 0));
+    assert_eq!(0.5f32.color(), Theme::default().cell_color(&board, None, 1, 0));
+}
+
+#[test]
+fn test_parse_color() {
+    assert_eq!(Some([1.0, 0.0, 0.0, 1.0]), parse_color("#ff0000"));
+    assert_eq!(Some([0.0, 0.0, 1.0, 0.0]), parse_color("#0000ff00"));
+    assert_eq!(None, parse_color("ff0000"));
+    assert_eq!(None, parse_color("#ff00"));
+    assert_eq!(None, parse_color("#gg0000"));
+}
+
+#[test]
+fn test_parse_theme() {
+    let theme = Theme::parse("background = \"#ffffff\"\n\
+                              grid = \"#000000\"\n\
+                              [palette]\n\
+                              \"#000000\" = \"#ffffff\"\n").unwrap();
+
+    assert_eq!([1.0; 4], theme.background);
+    assert_eq!(Some([0.0, 0.0, 0.0, 1.0]), theme.grid);
+    assert_eq!(Theme::default().density, theme.density);
+    assert_eq!([1.0; 4], theme.apply(palette::BLACK));
+    assert_eq!([0.5, 0.5, 0.5, 1.0], theme.apply([0.5, 0.5, 0.5, 1.0]));
+
+    assert_eq!(None, theme.heatmap);
+    assert!(Theme::parse("foreground = \"#ffffff\"").is_err());
+    assert!(Theme::parse("background = \"white\"").is_err());
+    assert!(Theme::parse("background = true").is_err());
+}
@@ -1,44 +1,253 @@
+use std::fmt;
+
 use time;
 
+use cell::diagnostics::PerfReport;
+
+/// Number of ticks a `TickMeter` measures over unless set otherwise.
+pub const DEFAULT_WINDOW: usize = 100;
+
+/// Durations of the ticks in the window of a `TickMeter`, in seconds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TickStats {
+    /// Ticks per second, from the mean duration.
+    pub rate: f64,
+    pub mean_s: f64,
+    pub min_s: f64,
+    pub max_s: f64,
+    pub p50_s: f64,
+    pub p95_s: f64,
+    pub p99_s: f64,
+    /// Standard deviation, i.e. jitter.
+    pub std_dev_s: f64
+}
+
+impl TickStats {
+    /// Stats of `durations`, all NaN if there are none.
+    pub fn of(durations: &[f64]) -> TickStats {
+        let count = durations.len() as f64;
+        let mean_s = durations.iter().fold(0.0, |sum, d| sum + d) / count;
+        let variance = durations.iter().fold(0.0, |sum, d| sum + (d - mean_s) * (d - mean_s)) / count;
+
+        let mut sorted = durations.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        // nearest rank
+        let percentile = |p: f64| {
+            if sorted.is_empty() {
+                return ::std::f64::NAN;
+            }
+            let rank = (p / 100.0 * count).ceil().max(1.0) as usize;
+            sorted[rank - 1]
+        };
+
+        TickStats {
+            rate: 1.0 / mean_s,
+            mean_s: mean_s,
+            min_s: sorted.first().cloned().unwrap_or(::std::f64::NAN),
+            max_s: sorted.last().cloned().unwrap_or(::std::f64::NAN),
+            p50_s: percentile(50.0),
+            p95_s: percentile(95.0),
+            p99_s: percentile(99.0),
+            std_dev_s: variance.sqrt()
+        }
+    }
+}
+
+impl fmt::Display for TickStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.1} (min {:.2} ms, p50 {:.2} ms, p95 {:.2} ms, p99 {:.2} ms, max {:.2} ms, sd {:.2} ms)",
+               self.rate, self.min_s * 1000.0, self.p50_s * 1000.0, self.p95_s * 1000.0,
+               self.p99_s * 1000.0, self.max_s * 1000.0, self.std_dev_s * 1000.0)
+    }
+}
+
+/// Measures durations of the last ticks, e.g. frames.
 pub struct TickMeter {
-    tick_times: [f64; 100],
+    /// Durations of up to `window` last ticks, the oldest one at `idx`
+    /// once full.
+    tick_times: Vec<f64>,
+    window: usize,
     idx: usize,
-    tick_start_s: f64,
-    display_prefix: Option<String>
+    tick_start_s: f64
 }
 
 impl TickMeter {
     pub fn new() -> Self {
-        TickMeter {
-            tick_times: [0.0; 100],
-            idx: 0,
-            tick_start_s: time::precise_time_s(),
-            display_prefix: None
-        }
+        TickMeter::with_window(DEFAULT_WINDOW)
     }
 
-    pub fn with_auto_display(self, display_prefix: &str) -> Self {
+    /// Meter measuring over the last `window` ticks, at least one.
+    pub fn with_window(window: usize) -> Self {
+        let window = ::std::cmp::max(window, 1);
         TickMeter {
-            display_prefix: Some(display_prefix.to_string()),
-            .. self
+            tick_times: Vec::with_capacity(window),
+            window: window,
+            idx: 0,
+            tick_start_s: time::precise_time_s()
         }
     }
 
     pub fn tick(&mut self) {
         let end_s = time::precise_time_s();
-
-        self.tick_times[self.idx] = end_s - self.tick_start_s;
+        self.record(end_s - self.tick_start_s);
         self.tick_start_s = end_s;
-        self.idx = (self.idx + 1) % self.tick_times.len();
+    }
+
+    fn record(&mut self,
+              duration_s: f64) {
+        if self.tick_times.len() < self.window {
+            self.tick_times.push(duration_s);
+        } else {
+            self.tick_times[self.idx] = duration_s;
+        }
+        self.idx = (self.idx + 1) % self.window;
+    }
+
+    pub fn stats(&self) -> TickStats {
+        TickStats::of(&self.tick_times)
+    }
+}
+
+/// Length of the window the profiler sums phases over.
+const PROFILE_WINDOW_S: f64 = 1.0;
+
+/// Time spent in named phases of the main loop, e.g. rendering, summed
+/// over every second along with the phases the simulation times itself.
+pub struct Profiler {
+    phases: Vec<(&'static str, f64)>,
+    /// Totals of the simulation's phases as of the last report, in ns.
+    simulation_ns: Vec<(&'static str, u64)>,
+    window_start_s: f64
+}
+
+/// Adds the time until it's dropped to a phase of a `Profiler`.
+pub struct ScopedTimer<'a> {
+    profiler: &'a mut Profiler,
+    phase: &'static str,
+    start_s: f64
+}
+
+impl<'a> Drop for ScopedTimer<'a> {
+    fn drop(&mut self) {
+        let elapsed_s = time::precise_time_s() - self.start_s;
+        self.profiler.add(self.phase, elapsed_s);
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Profiler {
+            phases: Vec::new(),
+            simulation_ns: Vec::new(),
+            window_start_s: time::precise_time_s()
+        }
+    }
+
+    pub fn scope(&mut self,
+                 phase: &'static str) -> ScopedTimer {
+        ScopedTimer {
+            profiler: self,
+            phase: phase,
+            start_s: time::precise_time_s()
+        }
+    }
+
+    fn add(&mut self,
+           phase: &'static str,
+           seconds: f64) {
+        match self.phases.iter_mut().find(|&&mut (name, _)| name == phase) {
+            Some(&mut (_, ref mut total)) => *total += seconds,
+            None => self.phases.push((phase, seconds))
+        }
+    }
+
+    /// Report of the last second once it's over, with the phases of
+    /// `simulation`, profiler counters summed since the start of the run.
+    pub fn report(&mut self,
+                  simulation: Option<PerfReport>) -> Option<String> {
+        self.report_at(time::precise_time_s(), simulation)
+    }
+
+    fn report_at(&mut self,
+                 now_s: f64,
+                 simulation: Option<PerfReport>) -> Option<String> {
+        let elapsed_s = now_s - self.window_start_s;
+        if elapsed_s < PROFILE_WINDOW_S {
+            return None;
+        }
+
+        let share = |seconds: f64| format!("{:.1} ms ({:.1}%)", seconds * 1000.0, seconds / elapsed_s * 100.0);
+        let mut parts: Vec<String> = self.phases.iter()
+                                                .map(|&(name, seconds)| format!("{} {}", name, share(seconds)))
+                                                .collect();
+        let mut simulation_parts = Vec::new();
+        for phase in simulation.map(|report| report.phases).unwrap_or(Vec::new()) {
+            let last_ns = match self.simulation_ns.iter_mut().find(|&&mut (name, _)| name == phase.name) {
+                Some(&mut (_, ref mut total_ns)) => ::std::mem::replace(total_ns, phase.total_ns),
+                None => {
+                    self.simulation_ns.push((phase.name, phase.total_ns));
+                    0
+                }
+            };
+            simulation_parts.push(format!("{} {}", phase.name, share((phase.total_ns - last_ns) as f64 / 1e9)));
+        }
 
-        match (&self.display_prefix, self.idx) {
-            (&Some(ref prefix), 0) => println!("{}{}", prefix, self.measure()),
-            _ => (),
+        self.phases.clear();
+        self.window_start_s = now_s;
+        if !simulation_parts.is_empty() {
+            parts.push(format!("simulation [{}]", simulation_parts.join(", ")));
         }
+        Some(format!("profile over {:.2} s: {}", elapsed_s, parts.join(", ")))
     }
+}
+
+#[test]
+fn test_tick_stats() {
+    let durations: Vec<f64> = (1..101).map(|ms| ms as f64 / 1000.0).collect();
+    let stats = TickStats::of(&durations);
+
+    assert_eq!(0.001, stats.min_s);
+    assert_eq!(0.1, stats.max_s);
+    assert_eq!(0.05, stats.p50_s);
+    assert_eq!(0.095, stats.p95_s);
+    assert_eq!(0.099, stats.p99_s);
+    assert!((stats.mean_s - 0.0505).abs() < 1e-9);
+    assert!((stats.rate - 1.0 / 0.0505).abs() < 1e-6);
+    assert!((stats.std_dev_s - 0.028866).abs() < 1e-6);
 
-    pub fn measure(&self) -> f64 {
-        let total_time_s = self.tick_times.iter().fold(0.0, |sum, x| sum + x);
-        self.tick_times.len() as f64 / total_time_s
+    assert!(TickStats::of(&[]).rate.is_nan());
+}
+
+#[test]
+fn test_tick_meter_window() {
+    let mut meter = TickMeter::with_window(3);
+    for &duration in &[1.0, 2.0, 3.0, 4.0] {
+        meter.record(duration);
     }
+
+    // the first tick fell out of the window
+    assert_eq!(2.0, meter.stats().min_s);
+    assert_eq!(1.0 / 3.0, meter.stats().rate);
+}
+
+#[test]
+fn test_profiler_report() {
+    use cell::diagnostics::PhaseReport;
+
+    let mut profiler = Profiler::new();
+    let start_s = profiler.window_start_s;
+    profiler.add("update", 0.25);
+    profiler.add("render", 0.1);
+    profiler.add("update", 0.25);
+    let simulation = |total_ns| Some(PerfReport {
+        ticks: 10,
+        phases: vec![PhaseReport { name: "resolve", calls: 10, total_ns: total_ns, max_ns: 0 }],
+        allocations: None
+    });
+
+    assert_eq!(None, profiler.report_at(start_s + 0.5, simulation(1000000)));
+    assert_eq!(Some("profile over 1.00 s: update 500.0 ms (50.0%), render 100.0 ms (10.0%), simulation [resolve 200.0 ms (20.0%)]".to_string()),
+               profiler.report_at(start_s + 1.0, simulation(200000000)));
+    assert_eq!(Some("profile over 2.00 s: simulation [resolve 100.0 ms (5.0%)]".to_string()),
+               profiler.report_at(start_s + 3.0, simulation(300000000)));
 }
@@ -0,0 +1,151 @@
+use std::marker::PhantomData;
+use std::sync::mpsc::{self, Receiver};
+use std::thread::{self, JoinHandle};
+
+use board_view::{BoardView, ViewPublisher};
+use simulation::Simulation;
+use stats::Stats;
+
+/// State of a simulation after a single tick.
+#[derive(Clone)]
+pub struct TickReport<T> {
+    pub stats: Stats,
+    /// Snapshot of the board, if requested with `Ticks::with_board` or
+    /// `Ticks::publish_to`.
+    pub board: Option<BoardView<T>>,
+    pub extinct: bool
+}
+
+/// Iterator advancing a simulation by one tick on every call to `next`.
+/// Stops after the given number of ticks or once the simulation goes
+/// extinct.
+pub struct Ticks<'a, S: 'a, T> {
+    simulation: &'a mut S,
+    remaining: usize,
+    views: Option<ViewPublisher<T>>,
+    finished: bool,
+    cell: PhantomData<T>
+}
+
+impl<'a, S, T> Ticks<'a, S, T> where S: Simulation<T> {
+    pub fn new(simulation: &'a mut S,
+               ticks: usize) -> Ticks<'a, S, T> {
+        Ticks {
+            simulation: simulation,
+            remaining: ticks,
+            views: None,
+            finished: false,
+            cell: PhantomData
+        }
+    }
+}
+
+impl<'a, S, T> Ticks<'a, S, T> where S: Simulation<T> {
+    /// The simulation being advanced, in its state after the last tick.
+    pub fn simulation(&self) -> &S {
+        self.simulation
+    }
+
+    /// The simulation being advanced, e.g. to change it between ticks.
+    pub fn simulation_mut(&mut self) -> &mut S {
+        self.simulation
+    }
+}
+
+impl<'a, S, T> Ticks<'a, S, T> where S: Simulation<T>, T: Clone {
+    /// Includes a snapshot of the board in every report.
+    pub fn with_board(self) -> Ticks<'a, S, T> {
+        let publisher = ViewPublisher::new(self.simulation.board(), 0);
+        self.publish_to(publisher)
+    }
+
+    /// Publishes a snapshot of the board after every tick, so that
+    /// observers of `publisher` can read it from other threads.
+    pub fn publish_to(mut self,
+                      publisher: ViewPublisher<T>) -> Ticks<'a, S, T> {
+        self.views = Some(publisher);
+        self
+    }
+}
+
+impl<'a, S, T> Iterator for Ticks<'a, S, T> where S: Simulation<T>, T: Clone {
+    type Item = TickReport<T>;
+
+    fn next(&mut self) -> Option<TickReport<T>> {
+        if self.remaining == 0 || self.finished {
+            return None;
+        }
+
+        self.simulation.advance();
+        self.remaining -= 1;
+        self.finished = self.simulation.is_extinct();
+
+        let stats = self.simulation.stats();
+        let board = self.views.as_ref().map(|views| views.publish(self.simulation.board(), stats.iteration));
+
+        Some(TickReport {
+            stats: stats,
+            board: board,
+            extinct: self.finished
+        })
+    }
+}
+
+/// Runs up to `ticks` steps on a separate thread, sending a report after
+/// each one. Joining the returned handle gives the simulation back.
+pub fn spawn<S, T>(mut simulation: S,
+                   ticks: usize,
+                   with_board: bool) -> (Receiver<TickReport<T>>, JoinHandle<S>)
+        where S: Simulation<T> + Send + 'static, T: Clone + Send + Sync + 'static {
+    let (tx, rx) = mpsc::channel();
+
+    let handle = thread::spawn(move || {
+        {
+            let ticks = Ticks::new(&mut simulation, ticks);
+            let ticks = if with_board { ticks.with_board() } else { ticks };
+
+            for report in ticks {
+                if tx.send(report).is_err() {
+                    break;
+                }
+            }
+        }
+        simulation
+    });
+
+    (rx, handle)
+}
+
+#[cfg(test)]
+fn glider() -> ::simulation::GameOfLife {
+    let board = ::demo::board_from_pattern((6, 6), &[".O", "..O", "OOO"], (0, 0), true, false);
+    ::simulation::GameOfLife::new(board)
+}
+
+#[test]
+fn test_ticks() {
+    let mut sim = glider();
+    let reports: Vec<TickReport<bool>> = sim.run(4).with_board().collect();
+
+    assert_eq!(4, reports.len());
+    assert_eq!(4, reports[3].stats.iteration);
+    assert!(reports.iter().all(|report| report.board.is_some() && !report.extinct));
+    assert!(*reports[3].board.as_ref().unwrap().at(2, 1));
+}
+
+#[test]
+fn test_ticks_stop_on_extinction() {
+    let mut sim = ::simulation::GameOfLife::new(::board::Board::new(4, 4, false));
+    let reports: Vec<TickReport<bool>> = sim.run(10).collect();
+
+    assert_eq!(1, reports.len());
+    assert!(reports[0].extinct && reports[0].board.is_none());
+}
+
+#[test]
+fn test_spawn() {
+    let (reports, handle) = spawn(glider(), 8, false);
+
+    assert_eq!(8, reports.iter().count());
+    assert_eq!(8, handle.join().ok().unwrap().stats().iteration);
+}
@@ -1,19 +1,58 @@
 pub struct TimeAccumulator {
     _accumulator: f64,
-    _step: f64
+    _step: f64,
+    /// Steps emitted by a single update at most, unbounded if not set.
+    _max_steps: Option<usize>,
+    /// Whether time left over after `_max_steps` is dropped instead of
+    /// being caught up with in later updates.
+    _drop_excess: bool,
+    _steps: usize,
+    _dropped: f64
 }
 
 impl TimeAccumulator {
     pub fn new(step: f64) -> TimeAccumulator {
         TimeAccumulator {
             _accumulator: 0.0f64,
-            _step: step
+            _step: step,
+            _max_steps: None,
+            _drop_excess: false,
+            _steps: 0,
+            _dropped: 0.0f64
         }
     }
 
+    /// Limits steps per update, so that a long stall, e.g. while the window
+    /// is dragged, doesn't freeze the application while it catches up.
+    pub fn with_max_steps(self,
+                          max_steps: usize,
+                          drop_excess: bool) -> TimeAccumulator {
+        TimeAccumulator {
+            _max_steps: Some(max_steps),
+            _drop_excess: drop_excess,
+            ..self
+        }
+    }
+
+    pub fn set_step(&mut self,
+                    step: f64) {
+        self._step = step;
+    }
+
+    /// Drops the time accumulated so far.
+    pub fn reset(&mut self) {
+        self._accumulator = 0.0f64;
+    }
+
+    /// Total time dropped because of the step limit, in seconds.
+    pub fn dropped(&self) -> f64 {
+        self._dropped
+    }
+
     pub fn update(&mut self,
                   delta: f64) -> &mut TimeAccumulator {
         self._accumulator += delta;
+        self._steps = 0;
         self
     }
 }
@@ -22,11 +61,40 @@ impl Iterator for TimeAccumulator {
     type Item = f64;
 
     fn next(&mut self) -> Option<f64> {
-        if self._accumulator >= self._step {
-            self._accumulator -= self._step;
-            Some(self._step)
-        } else {
-            None
+        if self._accumulator < self._step {
+            return None;
         }
+
+        if self._max_steps.map_or(false, |max_steps| self._steps >= max_steps) {
+            if self._drop_excess {
+                let excess = self._accumulator - self._accumulator % self._step;
+                self._accumulator -= excess;
+                self._dropped += excess;
+            }
+            return None;
+        }
+
+        self._accumulator -= self._step;
+        self._steps += 1;
+        Some(self._step)
     }
 }
+
+#[test]
+fn test_time_accumulator_max_steps() {
+    let mut accumulator = TimeAccumulator::new(0.5).with_max_steps(2, false);
+
+    assert_eq!(2, accumulator.update(2.25).count());
+    assert_eq!(2, accumulator.update(0.0).count());
+    assert_eq!(0, accumulator.update(0.0).count());
+    assert_eq!(0.0, accumulator.dropped());
+}
+
+#[test]
+fn test_time_accumulator_drop_excess() {
+    let mut accumulator = TimeAccumulator::new(0.5).with_max_steps(2, true);
+
+    assert_eq!(2, accumulator.update(2.25).count());
+    assert_eq!(1.0, accumulator.dropped());
+    assert_eq!(1, accumulator.update(0.25).count());
+}
@@ -0,0 +1,93 @@
+//! How long cells have been occupied, and fading trails of the ones left
+//! recently, drawn by the trail overlay.
+
+use board::Board;
+use palette::CellColor;
+use quadtree::is_occupied;
+
+/// Steps a trail takes to fade out.
+pub const TRAIL_STEPS: u32 = 32;
+
+#[derive(Clone)]
+pub struct Trails {
+    /// Steps each cell has been occupied for, 0 for empty cells.
+    ages: Board<u32>,
+    /// Steps left until the trail of each cell fades out.
+    fading: Board<u32>
+}
+
+impl Trails {
+    /// Trails of `board`, with its occupied cells occupied for a single step.
+    pub fn of_board<T: CellColor>(board: &Board<T>) -> Trails {
+        let mut ages = Board::new(board.width, board.height, 0);
+        for ((x, y), cell) in board.enumerate() {
+            if is_occupied(cell) {
+                *ages.at_mut(x, y) = 1;
+            }
+        }
+
+        Trails {
+            ages: ages,
+            fading: Board::new(board.width, board.height, 0)
+        }
+    }
+
+    /// Ages cells still occupied on `board` and starts trails of the ones
+    /// left since the last update.
+    pub fn update<T: CellColor>(&mut self,
+                                board: &Board<T>) {
+        assert!(board.width == self.ages.width && board.height == self.ages.height);
+        for ((x, y), cell) in board.enumerate() {
+            let age = self.ages.at_mut(x, y);
+            let fading = self.fading.at_mut(x, y);
+            if is_occupied(cell) {
+                *age = age.saturating_add(1);
+                *fading = 0;
+            } else {
+                *fading = if *age > 0 { TRAIL_STEPS } else { fading.saturating_sub(1) };
+                *age = 0;
+            }
+        }
+    }
+
+    pub fn age(&self,
+               x: usize,
+               y: usize) -> u32 {
+        *self.ages.at(x, y)
+    }
+
+    /// Strength of the trail of a cell, from 1 right after it was left down
+    /// to 0 once faded out.
+    pub fn trail(&self,
+                 x: usize,
+                 y: usize) -> f32 {
+        *self.fading.at(x, y) as f32 / TRAIL_STEPS as f32
+    }
+}
+
+#[test]
+fn test_trails() {
+    let mut board = Board::new(3, 1, false);
+    *board.at_mut(0, 0) = true;
+    let mut trails = Trails::of_board(&board);
+
+    trails.update(&board);
+    assert_eq!(2, trails.age(0, 0));
+    assert_eq!(0.0, trails.trail(0, 0));
+
+    // a glider of a single cell, moving right
+    *board.at_mut(0, 0) = false;
+    *board.at_mut(1, 0) = true;
+    trails.update(&board);
+    assert_eq!(0, trails.age(0, 0));
+    assert_eq!(1, trails.age(1, 0));
+    assert_eq!(1.0, trails.trail(0, 0));
+    assert_eq!(0.0, trails.trail(2, 0));
+
+    trails.update(&board);
+    assert_eq!(1.0 - 1.0 / TRAIL_STEPS as f32, trails.trail(0, 0));
+    for _ in 0..TRAIL_STEPS {
+        trails.update(&board);
+    }
+    assert_eq!(0.0, trails.trail(0, 0));
+}
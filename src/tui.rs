@@ -0,0 +1,239 @@
+//! Drawing simulations as colored characters in a terminal, for running
+//! them e.g. over SSH, where there's no OpenGL.
+//!
+//! Every character cell shows two board cells stacked on top of each
+//! other: the upper one as the foreground color of `▀`, the lower one as
+//! its background. Colors are 24-bit ANSI escape codes, and the terminal is
+//! switched into non-canonical mode with `stty`.
+
+use std::cmp::{max, min};
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use offscreen::to_rgb;
+use renderer::{Renderer, Scene, Viewport};
+
+/// Where the simulation is drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backend {
+    /// OpenGL window.
+    Window,
+    /// Colored characters in the terminal.
+    Tui
+}
+
+impl Backend {
+    pub fn from_name(name: &str) -> Option<Backend> {
+        match name {
+            "window" => Some(Backend::Window),
+            "tui" => Some(Backend::Tui),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Backend::Window => "window",
+            Backend::Tui => "tui"
+        }
+    }
+}
+
+/// Key read from the terminal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TermKey {
+    Char(char),
+    Escape,
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    F5,
+    F6,
+    F7,
+    F8,
+    F12
+}
+
+/// Splits bytes read from the terminal into keys. Escape sequences of keys
+/// not listed in `TermKey` are dropped.
+pub fn parse_keys(bytes: &[u8]) -> Vec<TermKey> {
+    let mut keys = Vec::new();
+    let text = String::from_utf8_lossy(bytes);
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            keys.push(TermKey::Char(c));
+            continue;
+        }
+        if chars.peek() != Some(&'[') {
+            keys.push(TermKey::Escape);
+            continue;
+        }
+        chars.next();
+
+        let mut sequence = String::new();
+        while let Some(c) = chars.next() {
+            sequence.push(c);
+            if c.is_alphabetic() || c == '~' {
+                break;
+            }
+        }
+        match &sequence[..] {
+            "A" => keys.push(TermKey::Up),
+            "B" => keys.push(TermKey::Down),
+            "C" => keys.push(TermKey::Right),
+            "D" => keys.push(TermKey::Left),
+            "5~" => keys.push(TermKey::PageUp),
+            "6~" => keys.push(TermKey::PageDown),
+            "15~" => keys.push(TermKey::F5),
+            "17~" => keys.push(TermKey::F6),
+            "18~" => keys.push(TermKey::F7),
+            "19~" => keys.push(TermKey::F8),
+            "24~" => keys.push(TermKey::F12),
+            _ => {}
+        }
+    }
+
+    keys
+}
+
+fn push_color(out: &mut String,
+              layer: u8,
+              color: [u8; 3]) {
+    out.push_str(&format!("\x1b[{};2;{};{};{}m", layer, color[0], color[1], color[2]));
+}
+
+/// Draws the whole board of `scene`, scaled down to fit `size` (columns,
+/// rows) of the terminal, with its status on the last row. The camera is
+/// ignored.
+pub fn frame(scene: &Scene,
+             size: (usize, usize)) -> String {
+    let (width, height) = scene.board_size;
+    let (columns, rows) = (max(size.0, 1), max(size.1.saturating_sub(1), 1));
+    let scale = |cells: usize, available: usize| (cells + available - 1) / available;
+    let step = max(max(scale(width, columns), scale(height, rows * 2)), 1);
+    let background = to_rgb(scene.theme.background);
+    let color_at = |x: usize, y: usize| {
+        if x < width && y < height {
+            to_rgb(scene.cell(x, y).color)
+        } else {
+            background
+        }
+    };
+
+    let mut out = "\x1b[H".to_string();
+    for row in 0..min(scale(height, step * 2), rows) {
+        for column in 0..min(scale(width, step), columns) {
+            let (x, y) = (column * step, row * 2 * step);
+            push_color(&mut out, 38, color_at(x, y));
+            push_color(&mut out, 48, color_at(x, y + step));
+            out.push('▀');
+        }
+        out.push_str("\x1b[0m\x1b[K\n");
+    }
+    out.push_str(&scene.status.chars().take(columns).collect::<String>());
+    out.push_str("\x1b[0m\x1b[J");
+    out
+}
+
+/// Draws scenes to stdout, with the viewport size in characters.
+pub struct TuiRenderer;
+
+impl Renderer for TuiRenderer {
+    fn render(&mut self,
+              scene: &Scene,
+              viewport: Viewport) {
+        let size = (viewport.window_size[0] as usize, viewport.window_size[1] as usize);
+        print!("{}", frame(scene, size));
+        let _ = io::stdout().flush();
+    }
+}
+
+fn stty(args: &[&str]) -> Result<String, String> {
+    let output = try!(Command::new("stty").args(args)
+                                          .stdin(Stdio::inherit())
+                                          .output()
+                                          .map_err(|e| format!("cannot run stty: {}", e)));
+    if !output.status.success() {
+        return Err(format!("stty failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Size of the terminal as (columns, rows), if stdin is one.
+pub fn terminal_size() -> Option<(usize, usize)> {
+    let size = match stty(&["size"]) {
+        Ok(size) => size,
+        Err(_) => return None
+    };
+    let numbers: Vec<usize> = size.split_whitespace().filter_map(|n| n.parse().ok()).collect();
+    if numbers.len() == 2 {
+        Some((numbers[1], numbers[0]))
+    } else {
+        None
+    }
+}
+
+/// Terminal switched to drawing the simulation: keys are read without
+/// waiting for Enter or echoing them, and the output goes to the alternate
+/// screen. The previous state is restored when dropped.
+pub struct Terminal {
+    saved: String
+}
+
+impl Terminal {
+    pub fn enter() -> Result<Terminal, String> {
+        let saved = try!(stty(&["-g"]));
+        try!(stty(&["-icanon", "-echo", "min", "1"]));
+
+        print!("\x1b[?1049h\x1b[?25l\x1b[2J");
+        let _ = io::stdout().flush();
+        Ok(Terminal { saved: saved })
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        print!("\x1b[0m\x1b[?25h\x1b[?1049l");
+        let _ = io::stdout().flush();
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+#[test]
+fn test_parse_keys() {
+    assert_eq!(vec![TermKey::Char('q'), TermKey::Char(' ')], parse_keys(b"q "));
+    assert_eq!(vec![TermKey::Escape], parse_keys(b"\x1b"));
+    assert_eq!(vec![TermKey::Right, TermKey::PageDown, TermKey::Char(']')],
+               parse_keys(b"\x1b[C\x1b[6~]"));
+    assert_eq!(vec![TermKey::Char('m')], parse_keys(b"\x1b[20~m"));
+    assert_eq!(vec![TermKey::F12], parse_keys(b"\x1b[24~"));
+    assert_eq!(vec![TermKey::F5, TermKey::F8], parse_keys(b"\x1b[15~\x1b[19~"));
+}
+
+#[test]
+fn test_frame() {
+    use board::Board;
+    use camera::Camera;
+    use simulation::GameOfLife;
+    use theme::Theme;
+
+    let mut board = Board::new(4, 4, false);
+    *board.at_mut(0, 0) = true;
+    let simulation = GameOfLife::new(board);
+    let theme = Theme::default();
+    let scene = Scene::of(&simulation, &theme, &Camera::new(), Viewport::new([80, 24]), false, false);
+
+    let full = frame(&scene, (80, 24));
+    assert_eq!(8, full.matches('▀').count());
+    assert_eq!(2, full.matches('\n').count());
+    assert!(full.contains("iter 0"));
+
+    // two columns fit only every other cell
+    let scaled = frame(&scene, (2, 24));
+    assert_eq!(2, scaled.matches('▀').count());
+}
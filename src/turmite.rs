@@ -0,0 +1,282 @@
+use std::fs::File;
+use std::io::Read;
+
+use board::Board;
+use rand::{Rng, StdRng};
+use simulation::Simulation;
+use stats::Stats;
+
+/// Langton's ant: turn right on color 0, left on color 1, flipping it.
+const LANGTONS_ANT: &'static str = "0 0 1 R 0\n0 1 0 L 0\n";
+
+/// Unit steps for headings 0-3: north, east, south, west.
+const HEADINGS: [(i64, i64); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Turn {
+    None,
+    Right,
+    UTurn,
+    Left
+}
+
+impl Turn {
+    pub fn from_name(name: &str) -> Option<Turn> {
+        match name {
+            "N" => Some(Turn::None),
+            "R" => Some(Turn::Right),
+            "U" => Some(Turn::UTurn),
+            "L" => Some(Turn::Left),
+            _ => None
+        }
+    }
+
+    /// Number of clockwise quarter turns.
+    fn quarters(&self) -> usize {
+        match *self {
+            Turn::None => 0,
+            Turn::Right => 1,
+            Turn::UTurn => 2,
+            Turn::Left => 3
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rule {
+    pub write: u8,
+    pub turn: Turn,
+    pub next_state: usize
+}
+
+/// What a turmite does for each combination of its state and the color
+/// under it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransitionTable {
+    pub num_states: usize,
+    pub num_colors: u8,
+    rules: Vec<Rule>
+}
+
+impl TransitionTable {
+    /// Parses a table with one `STATE COLOR WRITE TURN NEXT_STATE` rule
+    /// per line, where TURN is one of N (none), R, U (u-turn) or L. Lines
+    /// starting with '#' are comments. Every state/color combination must
+    /// be given.
+    pub fn parse(text: &str) -> Result<TransitionTable, String> {
+        let mut entries = Vec::new();
+
+        for line in text.lines().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+            if line.starts_with('#') {
+                continue;
+            }
+
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words.len() != 5 {
+                return Err(format!("invalid rule: {}, expected STATE COLOR WRITE TURN NEXT_STATE", line));
+            }
+
+            let number = |word: &str| word.parse::<usize>().map_err(|_| format!("invalid rule: {}", line));
+            let turn = try!(Turn::from_name(words[3]).ok_or(format!("invalid turn: {}", words[3])));
+            entries.push((try!(number(words[0])), try!(number(words[1])), Rule {
+                write: try!(number(words[2])) as u8,
+                turn: turn,
+                next_state: try!(number(words[4]))
+            }));
+        }
+
+        let num_states = entries.iter().map(|&(state, _, _)| state + 1).max().unwrap_or(0);
+        let num_colors = entries.iter().map(|&(_, color, _)| color + 1).max().unwrap_or(0);
+        if num_states == 0 || num_colors > 255 {
+            return Err("transition table must have at least one rule and at most 255 colors".to_string());
+        }
+
+        let mut rules = vec![None; num_states * num_colors];
+        for (state, color, rule) in entries {
+            if rule.next_state >= num_states || rule.write as usize >= num_colors {
+                return Err(format!("rule for state {} color {} refers to an unknown state or color",
+                                   state, color));
+            }
+            rules[state * num_colors + color] = Some(rule);
+        }
+
+        if let Some(idx) = rules.iter().position(|rule| rule.is_none()) {
+            return Err(format!("missing rule for state {} color {}", idx / num_colors, idx % num_colors));
+        }
+
+        Ok(TransitionTable {
+            num_states: num_states,
+            num_colors: num_colors as u8,
+            rules: rules.into_iter().map(|rule| rule.unwrap()).collect()
+        })
+    }
+
+    pub fn load(path: &str) -> Result<TransitionTable, String> {
+        let mut contents = String::new();
+        try!(File::open(path).and_then(|mut f| f.read_to_string(&mut contents))
+                             .map_err(|e| format!("cannot read {}: {}", path, e)));
+
+        TransitionTable::parse(&contents).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn langtons_ant() -> TransitionTable {
+        TransitionTable::parse(LANGTONS_ANT).unwrap()
+    }
+
+    pub fn rule(&self,
+                state: usize,
+                color: u8) -> Rule {
+        self.rules[state * self.num_colors as usize + color as usize]
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TurmiteConfig {
+    pub num_turmites: usize,
+    pub table: TransitionTable
+}
+
+impl Default for TurmiteConfig {
+    fn default() -> TurmiteConfig {
+        TurmiteConfig {
+            num_turmites: 1,
+            table: TransitionTable::langtons_ant()
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Turmite {
+    pub x: usize,
+    pub y: usize,
+    /// Index into HEADINGS.
+    pub heading: usize,
+    pub state: usize
+}
+
+/// Color of a cell along with the turmite that last wrote it, so that
+/// every turmite's trail can be drawn differently.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TurmiteCell {
+    pub color: u8,
+    pub num_colors: u8,
+    /// Index of the turmite that last wrote the cell and the total number
+    /// of turmites.
+    pub painter: Option<(usize, usize)>
+}
+
+/// Generalized Langton's ants (2D Turing machines) on a torus, all
+/// following the same transition table.
+#[derive(Clone)]
+pub struct Turmites {
+    pub cfg: TurmiteConfig,
+    board: Board<TurmiteCell>,
+    turmites: Vec<Turmite>,
+    iteration: usize
+}
+
+impl Turmites {
+    /// Places turmites at random cells, facing random directions.
+    pub fn new(width: usize,
+               height: usize,
+               cfg: TurmiteConfig,
+               mut rng: Box<StdRng>) -> Turmites {
+        let turmites = (0..cfg.num_turmites).map(|_| Turmite {
+                                                x: rng.gen_range(0, width),
+                                                y: rng.gen_range(0, height),
+                                                heading: rng.gen_range(0, 4),
+                                                state: 0
+                                            })
+                                            .collect();
+
+        Turmites::with_turmites(width, height, cfg, turmites)
+    }
+
+    pub fn with_turmites(width: usize,
+                         height: usize,
+                         cfg: TurmiteConfig,
+                         turmites: Vec<Turmite>) -> Turmites {
+        let board = Board::new(width, height, TurmiteCell {
+            color: 0,
+            num_colors: cfg.table.num_colors,
+            painter: None
+        });
+
+        Turmites {
+            cfg: cfg,
+            board: board,
+            turmites: turmites,
+            iteration: 0
+        }
+    }
+
+    pub fn turmites(&self) -> &[Turmite] {
+        &self.turmites
+    }
+}
+
+impl Simulation<TurmiteCell> for Turmites {
+    fn advance(&mut self) {
+        let (w, h) = (self.board.width as i64, self.board.height as i64);
+        let count = self.turmites.len();
+
+        for (idx, turmite) in self.turmites.iter_mut().enumerate() {
+            let cell = self.board.at_mut(turmite.x, turmite.y);
+            let rule = self.cfg.table.rule(turmite.state, cell.color);
+
+            cell.color = rule.write;
+            cell.painter = Some((idx, count));
+
+            turmite.heading = (turmite.heading + rule.turn.quarters()) % 4;
+            turmite.state = rule.next_state;
+
+            let (dx, dy) = HEADINGS[turmite.heading];
+            turmite.x = ((turmite.x as i64 + dx + w) % w) as usize;
+            turmite.y = ((turmite.y as i64 + dy + h) % h) as usize;
+        }
+
+        self.iteration += 1;
+    }
+
+    fn board(&self) -> &Board<TurmiteCell> {
+        &self.board
+    }
+
+    fn stats(&self) -> Stats {
+        Stats::new(self.iteration).count("turmites", self.turmites.len())
+                                  .count("painted", self.board.iter().filter(|cell| cell.color != 0).count())
+    }
+}
+
+#[test]
+fn test_turmite_table_parse() {
+    let table = TransitionTable::parse("# comment\n0 0 1 R 1\n0 1 0 L 0\n1 0 1 U 0\n1 1 1 N 1\n").unwrap();
+
+    assert_eq!((2, 2), (table.num_states, table.num_colors));
+    assert_eq!(Rule { write: 1, turn: Turn::UTurn, next_state: 0 }, table.rule(1, 0));
+}
+
+#[test]
+fn test_turmite_table_rejects_incomplete() {
+    assert!(TransitionTable::parse("0 0 1 R 0\n").is_err());
+    assert!(TransitionTable::parse("0 0 1 X 0\n0 1 0 L 0\n").is_err());
+    assert!(TransitionTable::parse("0 0 1 R 1\n0 1 0 L 0\n").is_err());
+}
+
+#[test]
+fn test_langtons_ant() {
+    let ant = Turmite { x: 2, y: 2, heading: 0, state: 0 };
+    let mut sim = Turmites::with_turmites(5, 5, TurmiteConfig::default(), vec![ant]);
+
+    sim.advance();
+    assert_eq!(1, sim.board().at(2, 2).color);
+    assert_eq!(Some((0, 1)), sim.board().at(2, 2).painter);
+    assert_eq!(Turmite { x: 3, y: 2, heading: 1, state: 0 }, sim.turmites()[0]);
+
+    for _ in 0..3 {
+        sim.advance();
+    }
+    // after four right turns the ant is back where it started
+    assert_eq!((2, 2), (sim.turmites()[0].x, sim.turmites()[0].y));
+    assert_eq!(4, sim.stats().get("painted").unwrap().as_f32() as usize);
+}
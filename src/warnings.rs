@@ -0,0 +1,196 @@
+//! Things that went wrong during a run without stopping it. A run with
+//! warnings may still have useful results, but e.g. parameter sweeps should
+//! be able to tell it apart from a clean one.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use rustc_serialize::json::{Json, ToJson};
+
+use manifest::{as_str, as_usize, field};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WarningKind {
+    /// Random search for an empty cell failed, the board was searched
+    /// exhaustively.
+    EmptyFieldFallback,
+    /// All cells around a target were contended by cfg.max_collision_size
+    /// specimens, so the limit was exceeded.
+    CollisionLimitExceeded,
+    /// Writing recorded frames failed, recording stopped.
+    RecordingFailed,
+    /// Writing the event log failed, logging stopped.
+    EventLogFailed,
+    /// Writing the stats log failed, logging stopped.
+    StatsLogFailed,
+    /// Writing a long-exposure image failed, exposing stopped.
+    ExposureFailed,
+    /// Writing a lab notebook entry failed, snapshots stopped.
+    NotebookFailed,
+    /// Writing a PNG snapshot failed, snapshots stopped.
+    SnapshotFailed,
+    /// A metrics sink failed, the sink was dropped.
+    MetricsSinkFailed
+}
+
+impl WarningKind {
+    pub fn all() -> &'static [WarningKind] {
+        const ALL: &'static [WarningKind] = &[WarningKind::EmptyFieldFallback,
+                                              WarningKind::CollisionLimitExceeded,
+                                              WarningKind::RecordingFailed,
+                                              WarningKind::EventLogFailed,
+                                              WarningKind::StatsLogFailed,
+                                              WarningKind::ExposureFailed,
+                                              WarningKind::NotebookFailed,
+                                              WarningKind::SnapshotFailed,
+                                              WarningKind::MetricsSinkFailed];
+        ALL
+    }
+
+    pub fn from_name(name: &str) -> Option<WarningKind> {
+        WarningKind::all().iter().cloned().find(|kind| kind.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            WarningKind::EmptyFieldFallback => "empty_field_fallback",
+            WarningKind::CollisionLimitExceeded => "collision_limit_exceeded",
+            WarningKind::RecordingFailed => "recording_failed",
+            WarningKind::EventLogFailed => "event_log_failed",
+            WarningKind::StatsLogFailed => "stats_log_failed",
+            WarningKind::ExposureFailed => "exposure_failed",
+            WarningKind::NotebookFailed => "notebook_failed",
+            WarningKind::SnapshotFailed => "snapshot_failed",
+            WarningKind::MetricsSinkFailed => "metrics_sink_failed"
+        }
+    }
+}
+
+/// Occurrences of one kind of warning. Only the first one is described in
+/// detail.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub count: usize,
+    pub first_iteration: usize,
+    pub detail: String
+}
+
+/// Warnings of a run, one entry per kind in order of first occurrence.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WarningLog {
+    warnings: Vec<Warning>
+}
+
+impl WarningLog {
+    pub fn new() -> WarningLog {
+        WarningLog::default()
+    }
+
+    pub fn record(&mut self,
+                  kind: WarningKind,
+                  iteration: usize,
+                  detail: String) {
+        if let Some(warning) = self.warnings.iter_mut().find(|warning| warning.kind == kind) {
+            warning.count += 1;
+            return;
+        }
+
+        self.warnings.push(Warning {
+            kind: kind,
+            count: 1,
+            first_iteration: iteration,
+            detail: detail
+        });
+    }
+
+    pub fn merge(&mut self,
+                 other: &WarningLog) {
+        for warning in other.warnings.iter() {
+            if let Some(existing) = self.warnings.iter_mut().find(|w| w.kind == warning.kind) {
+                if warning.first_iteration < existing.first_iteration {
+                    existing.first_iteration = warning.first_iteration;
+                    existing.detail = warning.detail.clone();
+                }
+                existing.count += warning.count;
+                continue;
+            }
+            self.warnings.push(warning.clone());
+        }
+    }
+
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    /// Total number of occurrences of all kinds.
+    pub fn count(&self) -> usize {
+        self.warnings.iter().fold(0, |sum, warning| sum + warning.count)
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::Array(self.warnings.iter().map(|warning| {
+            let mut obj = BTreeMap::new();
+            obj.insert("kind".to_string(), warning.kind.name().to_json());
+            obj.insert("count".to_string(), (warning.count as u64).to_json());
+            obj.insert("first_iteration".to_string(), (warning.first_iteration as u64).to_json());
+            obj.insert("detail".to_string(), warning.detail.to_json());
+            Json::Object(obj)
+        }).collect())
+    }
+
+    pub fn from_json(json: &Json) -> Result<WarningLog, String> {
+        let array = try!(json.as_array().ok_or("warnings are not an array".to_string()));
+        let mut log = WarningLog::new();
+
+        for warning in array.iter() {
+            let obj = try!(warning.as_object().ok_or("warning is not a JSON object".to_string()));
+            let name = try!(as_str(try!(field(obj, "kind")), "kind"));
+            log.warnings.push(Warning {
+                kind: try!(WarningKind::from_name(name).ok_or(format!("unknown warning: {}", name))),
+                count: try!(as_usize(try!(field(obj, "count")), "count")),
+                first_iteration: try!(as_usize(try!(field(obj, "first_iteration")), "first_iteration")),
+                detail: try!(as_str(try!(field(obj, "detail")), "detail")).to_string()
+            });
+        }
+
+        Ok(log)
+    }
+}
+
+impl fmt::Display for WarningLog {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for warning in self.warnings.iter() {
+            try!(writeln!(f, "warning: {} x{} (first at iteration {}: {})",
+                          warning.kind.name(), warning.count, warning.first_iteration, warning.detail));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_warning_log_counts_per_kind() {
+    let mut log = WarningLog::new();
+    log.record(WarningKind::CollisionLimitExceeded, 3, "first".to_string());
+    log.record(WarningKind::RecordingFailed, 4, "disk full".to_string());
+    log.record(WarningKind::CollisionLimitExceeded, 5, "second".to_string());
+
+    let mut other = WarningLog::new();
+    other.record(WarningKind::CollisionLimitExceeded, 1, "earlier".to_string());
+    other.record(WarningKind::EventLogFailed, 2, "no space".to_string());
+    log.merge(&other);
+
+    assert_eq!(vec![Warning { kind: WarningKind::CollisionLimitExceeded, count: 3, first_iteration: 1,
+                              detail: "earlier".to_string() },
+                    Warning { kind: WarningKind::RecordingFailed, count: 1, first_iteration: 4,
+                              detail: "disk full".to_string() },
+                    Warning { kind: WarningKind::EventLogFailed, count: 1, first_iteration: 2,
+                              detail: "no space".to_string() }],
+               log.warnings().to_vec());
+    assert_eq!(5, log.count());
+    assert_eq!(Ok(log.clone()), WarningLog::from_json(&log.to_json()));
+}
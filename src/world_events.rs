@@ -0,0 +1,106 @@
+use std::fmt;
+
+use rand::{Rng, StdRng};
+
+/// Global event affecting the whole GoodEvil board.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EventKind {
+    /// Kills every specimen within given radius of a random point.
+    Meteor { radius: usize },
+    /// Doubles the energy loss per step for given number of steps.
+    Famine { duration: usize },
+    /// Adds given amount of energy to the pool shared by colliding
+    /// specimens.
+    Bloom { energy: f32 }
+}
+
+impl EventKind {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            EventKind::Meteor { .. } => "meteor",
+            EventKind::Famine { .. } => "famine",
+            EventKind::Bloom { .. } => "bloom"
+        }
+    }
+
+    /// Creates an event with default strength, optionally overridden by
+    /// `param`.
+    fn from_name(name: &str,
+                 param: Option<&str>) -> Option<EventKind> {
+        match name {
+            "meteor" => match param {
+                None => Some(EventKind::Meteor { radius: 5 }),
+                Some(p) => p.parse().ok().map(|radius| EventKind::Meteor { radius: radius })
+            },
+            "famine" => match param {
+                None => Some(EventKind::Famine { duration: 50 }),
+                Some(p) => p.parse().ok().map(|duration| EventKind::Famine { duration: duration })
+            },
+            "bloom" => match param {
+                None => Some(EventKind::Bloom { energy: 10.0 }),
+                Some(p) => p.parse().ok()
+                            .and_then(|energy: f32| if energy >= 0.0 { Some(energy) } else { None })
+                            .map(|energy| EventKind::Bloom { energy: energy })
+            },
+            _ => None
+        }
+    }
+
+    pub fn random(rng: &mut StdRng) -> EventKind {
+        const NAMES: &'static [&'static str] = &["meteor", "famine", "bloom"];
+        EventKind::from_name(rng.choose(NAMES).unwrap(), None).unwrap()
+    }
+}
+
+impl fmt::Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EventKind::Meteor { radius } => write!(f, "{}:{}", self.name(), radius),
+            EventKind::Famine { duration } => write!(f, "{}:{}", self.name(), duration),
+            EventKind::Bloom { energy } => write!(f, "{}:{}", self.name(), energy)
+        }
+    }
+}
+
+/// Event scheduled to happen at a given iteration.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WorldEvent {
+    pub iteration: usize,
+    pub kind: EventKind
+}
+
+impl WorldEvent {
+    /// Parses `NAME@ITERATION[:PARAM]`, e.g. `meteor@100:10`.
+    pub fn parse(string: &str) -> Option<WorldEvent> {
+        let at = match string.find('@') {
+            Some(at) => at,
+            None => return None
+        };
+
+        let name = &string[..at];
+        let mut rest = string[at + 1..].splitn(2, ':');
+        let iteration = match rest.next().and_then(|s| s.parse().ok()) {
+            Some(iteration) => iteration,
+            None => return None
+        };
+
+        EventKind::from_name(name, rest.next()).map(|kind| WorldEvent {
+            iteration: iteration,
+            kind: kind
+        })
+    }
+}
+
+#[test]
+fn test_parse_world_event() {
+    assert_eq!(Some(WorldEvent { iteration: 100, kind: EventKind::Meteor { radius: 10 } }),
+               WorldEvent::parse("meteor@100:10"));
+    assert_eq!(Some(WorldEvent { iteration: 5, kind: EventKind::Famine { duration: 50 } }),
+               WorldEvent::parse("famine@5"));
+    assert_eq!("bloom:2.5", WorldEvent::parse("bloom@1:2.5").unwrap().kind.to_string());
+
+    assert!(WorldEvent::parse("meteor").is_none());
+    assert!(WorldEvent::parse("flood@10").is_none());
+    assert!(WorldEvent::parse("bloom@10:-1").is_none());
+    assert!(WorldEvent::parse("famine@x:3").is_none());
+}